@@ -0,0 +1,49 @@
+use crate::detector::{MisbehaviorAlert, Severity};
+use chrono::{DateTime, Utc};
+
+/// Count of alerts raised since the Alerts tab was last looked at, shown next to its title
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AlertBadge {
+    pub unacknowledged: usize,
+    pub critical: usize,
+    /// Most severe unacknowledged alert, which picks the badge color
+    pub highest: Option<Severity>,
+}
+
+impl AlertBadge {
+    /// `[3!]` while critical alerts are unacknowledged, `[5]` with only lesser ones, and
+    /// `None` once everything has been seen
+    pub fn label(&self) -> Option<String> {
+        match (self.critical, self.unacknowledged) {
+            (0, 0) => None,
+            (0, count) => Some(format!("[{}]", count)),
+            (critical, _) => Some(format!("[{}!]", critical)),
+        }
+    }
+}
+
+/// Alerts count as acknowledged once the Alerts tab has been shown after they arrived
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AlertAcknowledgement {
+    until: Option<DateTime<Utc>>,
+}
+
+impl AlertAcknowledgement {
+    /// Mark everything in `alerts` as seen; called while the Alerts tab is shown
+    pub fn acknowledge(&mut self, alerts: &[MisbehaviorAlert]) {
+        let latest = alerts.iter().map(|alert| alert.timestamp).max();
+        self.until = self.until.max(latest);
+    }
+
+    pub fn badge(&self, alerts: &[MisbehaviorAlert]) -> AlertBadge {
+        let mut badge = AlertBadge::default();
+        for alert in alerts.iter().filter(|alert| self.until.is_none_or(|until| alert.timestamp > until)) {
+            badge.unacknowledged += 1;
+            if alert.severity == Severity::Critical {
+                badge.critical += 1;
+            }
+            badge.highest = badge.highest.max(Some(alert.severity));
+        }
+        badge
+    }
+}
@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+use std::fs;
+
+/// Smallest network bar scale, so an idle interface's background chatter doesn't fill its bars
+pub const MIN_SCALE_BYTES_PER_SEC: u64 = 1024 * 1024;
+
+/// Smallest disk bar scale until a device shows it can do more; roughly a slow SATA disk
+pub const MIN_DISK_SCALE_BYTES_PER_SEC: u64 = 100 * 1024 * 1024;
+
+/// Link speed in bytes per second from the contents of `/sys/class/net/<if>/speed`, which is
+/// in Mbit/s and `-1` when unknown
+pub fn parse_link_speed(text: &str) -> Option<u64> {
+    let mbit: i64 = text.trim().parse().ok()?;
+    (mbit > 0).then(|| mbit as u64 * 1_000_000 / 8)
+}
+
+/// Link speed of `interface`; `None` for loopback, virtual and disconnected interfaces
+pub fn read_link_speed(interface: &str) -> Option<u64> {
+    parse_link_speed(&fs::read_to_string(format!("/sys/class/net/{}/speed", interface)).ok()?)
+}
+
+/// Full-bar rate: the device's known capacity (a network link speed), else the highest rate
+/// seen on it, but at least `floor`
+pub fn scale_denominator(capacity: Option<u64>, peak: u64, floor: u64) -> u64 {
+    capacity.unwrap_or(peak).max(floor)
+}
+
+/// How full a bar for `bytes_per_sec` is against `denominator`, `0.0..=1.0`
+pub fn bar_fraction(bytes_per_sec: u64, denominator: u64) -> f32 {
+    if denominator == 0 {
+        return 0.0;
+    }
+    (bytes_per_sec as f64 / denominator as f64).min(1.0) as f32
+}
+
+/// Per-device scale for rate bars (network interfaces, disks)
+#[derive(Debug, Clone)]
+pub struct RateScale {
+    floor: u64,
+    peaks: HashMap<String, u64>,
+    // Read once per interface; the file is missing or unreadable for virtual ones
+    link_speeds: HashMap<String, Option<u64>>,
+}
+
+impl RateScale {
+    pub fn new(floor: u64) -> Self {
+        Self { floor, peaks: HashMap::new(), link_speeds: HashMap::new() }
+    }
+
+    /// Scale for network interfaces, using link speeds where the kernel knows them
+    pub fn network() -> Self {
+        Self::new(MIN_SCALE_BYTES_PER_SEC)
+    }
+
+    /// Scale for block devices, which only have their observed peaks to go on
+    pub fn disks() -> Self {
+        Self::new(MIN_DISK_SCALE_BYTES_PER_SEC)
+    }
+
+    /// Record the current rates (e.g. read and write) of `device`
+    pub fn observe(&mut self, device: &str, rates: &[u64]) {
+        let peak = self.peaks.entry(device.to_string()).or_default();
+        *peak = rates.iter().copied().fold(*peak, u64::max);
+    }
+
+    /// Record the current receive and transmit rates of network `interface`
+    pub fn observe_interface(&mut self, interface: &str, rx_bytes_per_sec: u64, tx_bytes_per_sec: u64) {
+        self.observe(interface, &[rx_bytes_per_sec, tx_bytes_per_sec]);
+        self.link_speeds
+            .entry(interface.to_string())
+            .or_insert_with(|| read_link_speed(interface));
+    }
+
+    pub fn denominator(&self, device: &str) -> u64 {
+        let capacity = self.link_speeds.get(device).copied().flatten();
+        scale_denominator(capacity, self.peaks.get(device).copied().unwrap_or(0), self.floor)
+    }
+
+    /// How full the bar for `bytes_per_sec` on `device` is, `0.0..=1.0`
+    pub fn fraction(&self, device: &str, bytes_per_sec: u64) -> f32 {
+        bar_fraction(bytes_per_sec, self.denominator(device))
+    }
+}
@@ -0,0 +1,349 @@
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Local, NaiveDate, Timelike};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Environment variable holding how many standard deviations count as unusual
+pub const BASELINE_SIGMA_ENV: &str = "PROCMON_BASELINE_SIGMA";
+
+pub const DEFAULT_BASELINE_SIGMA: f64 = 3.0;
+
+/// Days of history an hour needs before its baseline is used
+pub const MIN_BASELINE_DAYS: u32 = 3;
+
+/// Days after which older history fades out, so the baseline follows a changing workload
+pub const BASELINE_WINDOW_DAYS: u32 = 30;
+
+/// Lower bound for the standard deviation, in percentage points. Without it an hour that was
+/// identical on every day would flag the smallest change.
+pub const MIN_STD_DEV: f64 = 1.0;
+
+/// An hour only goes into the baseline if procmon saw at least this much of it
+const MIN_HOUR_COVERAGE: chrono::Duration = chrono::Duration::minutes(30);
+
+/// The current hour is compared once procmon has seen this much of it
+const MIN_CURRENT_COVERAGE: chrono::Duration = chrono::Duration::minutes(5);
+
+/// Pick the anomaly threshold from `--baseline-sigma <k>` / `--baseline-sigma=<k>` in `args`,
+/// falling back to the `preference` (normally `PROCMON_BASELINE_SIGMA`) and then
+/// [`DEFAULT_BASELINE_SIGMA`].
+pub fn baseline_sigma<I>(args: I, preference: Option<&str>) -> Result<f64>
+where
+    I: IntoIterator<Item = String>,
+{
+    let Some(value) = crate::util::flag_value(args, "--baseline-sigma")?.or(preference.map(str::to_string)) else {
+        return Ok(DEFAULT_BASELINE_SIGMA);
+    };
+    match value.trim().parse::<f64>() {
+        Ok(sigma) if sigma.is_finite() && sigma > 0.0 => Ok(sigma),
+        _ => bail!("Invalid baseline sigma '{}' (expected a positive number)", value),
+    }
+}
+
+/// `$XDG_STATE_HOME/procmon/baseline.toml`, falling back to `~/.local/state`
+pub fn default_baseline_file() -> Option<PathBuf> {
+    crate::util::state_file("baseline.toml")
+}
+
+/// Mean and variance of a series, updated one value at a time.
+///
+/// Values are weighted equally until [`BASELINE_WINDOW_DAYS`] of them have been seen; after
+/// that each new one gets that fixed share, so older days decay exponentially.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RunningStats {
+    pub count: u32,
+    pub mean: f64,
+    pub variance: f64,
+}
+
+impl RunningStats {
+    pub fn push(&mut self, value: f64) {
+        self.count = (self.count + 1).min(BASELINE_WINDOW_DAYS);
+        let weight = 1.0 / self.count as f64;
+        let delta = value - self.mean;
+        self.mean += weight * delta;
+        self.variance = (1.0 - weight) * (self.variance + weight * delta * delta);
+    }
+
+    pub fn std_dev(&self) -> f64 {
+        self.variance.sqrt()
+    }
+
+    /// How many standard deviations `value` is from the mean, or `None` with too little history
+    pub fn z_score(&self, value: f64) -> Option<f64> {
+        if self.count < MIN_BASELINE_DAYS {
+            return None;
+        }
+        Some((value - self.mean) / self.std_dev().max(MIN_STD_DEV))
+    }
+}
+
+/// Whether a z-score is far enough out to mention
+pub fn is_unusual(z_score: Option<f64>, sigma: f64) -> bool {
+    z_score.is_some_and(|z| z.abs() > sigma)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BaselineMetric {
+    Cpu,
+    Memory,
+}
+
+impl BaselineMetric {
+    pub const ALL: [BaselineMetric; 2] = [BaselineMetric::Cpu, BaselineMetric::Memory];
+
+    /// Name of the metric's tables in the baseline file
+    pub fn name(&self) -> &'static str {
+        match self {
+            BaselineMetric::Cpu => "cpu",
+            BaselineMetric::Memory => "memory",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            BaselineMetric::Cpu => "CPU",
+            BaselineMetric::Memory => "Memory",
+        }
+    }
+}
+
+/// Statistics of the hourly average CPU and memory usage (in percent) for each hour of the day
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HourlyBaseline {
+    cpu: [RunningStats; 24],
+    memory: [RunningStats; 24],
+}
+
+impl HourlyBaseline {
+    pub fn stats(&self, metric: BaselineMetric, hour: u32) -> &RunningStats {
+        match metric {
+            BaselineMetric::Cpu => &self.cpu[hour as usize % 24],
+            BaselineMetric::Memory => &self.memory[hour as usize % 24],
+        }
+    }
+
+    fn stats_mut(&mut self, metric: BaselineMetric, hour: u32) -> &mut RunningStats {
+        match metric {
+            BaselineMetric::Cpu => &mut self.cpu[hour as usize % 24],
+            BaselineMetric::Memory => &mut self.memory[hour as usize % 24],
+        }
+    }
+
+    /// Add one day's average for `hour`
+    pub fn record(&mut self, hour: u32, cpu_percent: f64, memory_percent: f64) {
+        self.stats_mut(BaselineMetric::Cpu, hour).push(cpu_percent);
+        self.stats_mut(BaselineMetric::Memory, hour).push(memory_percent);
+    }
+
+    /// Read the TOML written by [`HourlyBaseline::to_toml`]
+    pub fn parse(text: &str) -> Result<Self> {
+        let file: BaselineFile = toml::from_str(text)?;
+        let mut baseline = Self::default();
+        for (metric, hours) in [(BaselineMetric::Cpu, file.cpu), (BaselineMetric::Memory, file.memory)] {
+            for HourStats { hour, count, mean, variance } in hours {
+                if hour > 23 {
+                    bail!("Invalid hour {} in [[{}]]", hour, metric.name());
+                }
+                *baseline.stats_mut(metric, hour) = RunningStats { count, mean, variance };
+            }
+        }
+        Ok(baseline)
+    }
+
+    /// One `[[cpu]]` and `[[memory]]` table per hour with history
+    pub fn to_toml(&self) -> Result<String> {
+        let hours = |metric| {
+            (0..24)
+                .filter_map(|hour| {
+                    let stats = self.stats(metric, hour);
+                    (stats.count > 0).then_some(HourStats {
+                        hour,
+                        count: stats.count,
+                        mean: stats.mean,
+                        variance: stats.variance,
+                    })
+                })
+                .collect()
+        };
+        let file = BaselineFile { cpu: hours(BaselineMetric::Cpu), memory: hours(BaselineMetric::Memory) };
+        Ok(toml::to_string(&file)?)
+    }
+}
+
+/// Layout of the baseline file
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct BaselineFile {
+    cpu: Vec<HourStats>,
+    memory: Vec<HourStats>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct HourStats {
+    hour: u32,
+    /// Days of history, see [`RunningStats`]
+    count: u32,
+    mean: f64,
+    variance: f64,
+}
+
+/// Current usage far from what is normal for the hour
+#[derive(Debug, Clone, PartialEq)]
+pub struct Anomaly {
+    pub metric: BaselineMetric,
+    pub hour: u32,
+    pub current: f64,
+    pub mean: f64,
+    pub std_dev: f64,
+    pub z_score: f64,
+}
+
+impl Anomaly {
+    /// e.g. "CPU 78% is unusually high for 14:00 (usually 22% ± 6%)"
+    pub fn describe(&self) -> String {
+        format!(
+            "{} {:.0}% is unusually {} for {:02}:00 (usually {:.0}% ± {:.0}%)",
+            self.metric.label(),
+            self.current,
+            if self.z_score > 0.0 { "high" } else { "low" },
+            self.hour,
+            self.mean,
+            self.std_dev
+        )
+    }
+}
+
+/// Running sums for the hour in progress
+#[derive(Debug, Clone)]
+struct HourInProgress {
+    date: NaiveDate,
+    hour: u32,
+    first_sample: DateTime<Local>,
+    last_sample: DateTime<Local>,
+    cpu_sum: f64,
+    memory_sum: f64,
+    samples: u32,
+}
+
+impl HourInProgress {
+    fn coverage(&self) -> chrono::Duration {
+        self.last_sample - self.first_sample
+    }
+
+    fn averages(&self) -> (f64, f64) {
+        let samples = self.samples.max(1) as f64;
+        (self.cpu_sum / samples, self.memory_sum / samples)
+    }
+}
+
+/// Averages usage over each hour, adds finished hours to the [`HourlyBaseline`] and compares
+/// the hour in progress against it.
+#[derive(Debug, Clone)]
+pub struct BaselineTracker {
+    baseline: HourlyBaseline,
+    /// Where the baseline is saved after each finished hour; `None` keeps it in memory
+    path: Option<PathBuf>,
+    sigma: f64,
+    current: Option<HourInProgress>,
+}
+
+impl BaselineTracker {
+    pub fn new(baseline: HourlyBaseline, path: Option<PathBuf>, sigma: f64) -> Self {
+        Self { baseline, path, sigma, current: None }
+    }
+
+    /// Tracker saving to `path`, starting from the baseline already there if any
+    pub fn load(path: Option<PathBuf>, sigma: f64) -> Result<Self> {
+        let baseline = match &path {
+            Some(path) => read_baseline(path)?,
+            None => HourlyBaseline::default(),
+        };
+        Ok(Self::new(baseline, path, sigma))
+    }
+
+    /// [`BaselineTracker::load`] from [`default_baseline_file`]; an unreadable file is logged
+    /// and replaced rather than keeping the monitor from starting
+    pub fn load_default(sigma: f64) -> Self {
+        let path = default_baseline_file();
+        Self::load(path.clone(), sigma).unwrap_or_else(|e| {
+            tracing::warn!("Starting a new usage baseline: {:#}", e);
+            Self::new(HourlyBaseline::default(), path, sigma)
+        })
+    }
+
+    pub fn baseline(&self) -> &HourlyBaseline {
+        &self.baseline
+    }
+
+    /// Add a sample taken at `now`. Finishing an hour records it and saves the baseline.
+    pub fn observe(&mut self, now: DateTime<Local>, cpu_percent: f64, memory_percent: f64) -> Result<()> {
+        let (date, hour) = (now.date_naive(), now.hour());
+        let mut saved = Ok(());
+        if let Some(finished) = self.current.take_if(|current| (current.date, current.hour) != (date, hour)) {
+            if finished.coverage() >= MIN_HOUR_COVERAGE {
+                let (cpu, memory) = finished.averages();
+                self.baseline.record(finished.hour, cpu, memory);
+                saved = self.save();
+            }
+        }
+
+        let current = self.current.get_or_insert(HourInProgress {
+            date,
+            hour,
+            first_sample: now,
+            last_sample: now,
+            cpu_sum: 0.0,
+            memory_sum: 0.0,
+            samples: 0,
+        });
+        current.last_sample = now;
+        current.cpu_sum += cpu_percent;
+        current.memory_sum += memory_percent;
+        current.samples += 1;
+        saved
+    }
+
+    /// Metrics whose average so far this hour is more than `sigma` standard deviations from
+    /// the same hour's baseline
+    pub fn anomalies(&self) -> Vec<Anomaly> {
+        let Some(current) = self.current.as_ref().filter(|current| current.coverage() >= MIN_CURRENT_COVERAGE) else {
+            return Vec::new();
+        };
+        let (cpu, memory) = current.averages();
+        [(BaselineMetric::Cpu, cpu), (BaselineMetric::Memory, memory)]
+            .into_iter()
+            .filter_map(|(metric, value)| {
+                let stats = self.baseline.stats(metric, current.hour);
+                let z_score = stats.z_score(value);
+                is_unusual(z_score, self.sigma).then(|| Anomaly {
+                    metric,
+                    hour: current.hour,
+                    current: value,
+                    mean: stats.mean,
+                    std_dev: stats.std_dev(),
+                    z_score: z_score.unwrap_or_default(),
+                })
+            })
+            .collect()
+    }
+
+    fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        std::fs::write(path, self.baseline.to_toml()?).with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+fn read_baseline(path: &Path) -> Result<HourlyBaseline> {
+    match std::fs::read_to_string(path) {
+        Ok(text) => HourlyBaseline::parse(&text).with_context(|| format!("Invalid {}", path.display())),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(HourlyBaseline::default()),
+        Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    }
+}
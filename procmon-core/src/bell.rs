@@ -0,0 +1,129 @@
+use crate::detector::{MisbehaviorAlert, Severity};
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// Environment variable holding the preferred alert sound: `off`, `bell` or `command:<cmd>`
+pub const ALERT_SOUND_ENV: &str = "PROCMON_ALERT_SOUND";
+
+/// What to do when a new critical alert appears
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum AlertSound {
+    #[default]
+    Off,
+    /// The terminal bell (`\x07`)
+    Bell,
+    /// A shell command, e.g. `paplay /usr/share/sounds/freedesktop/stereo/bell.oga`
+    Command(String),
+}
+
+impl AlertSound {
+    /// Parse `off`, `bell` or `command:<shell command>`
+    pub fn from_name(name: &str) -> Result<AlertSound> {
+        let name = name.trim();
+        if let Some(command) = name.strip_prefix("command:") {
+            if command.trim().is_empty() {
+                bail!("Alert sound 'command:' needs a command to run");
+            }
+            return Ok(AlertSound::Command(command.trim().to_string()));
+        }
+        match name.to_lowercase().as_str() {
+            "off" | "none" => Ok(AlertSound::Off),
+            "bell" => Ok(AlertSound::Bell),
+            _ => bail!("Unknown alert sound '{}' (expected off, bell or command:<cmd>)", name),
+        }
+    }
+}
+
+/// Pick the alert sound from `--alert-sound <value>` / `--alert-sound=<value>` in `args`,
+/// falling back to the `preference` (normally `PROCMON_ALERT_SOUND`) and then off.
+pub fn alert_sound<I>(args: I, preference: Option<&str>) -> Result<AlertSound>
+where
+    I: IntoIterator<Item = String>,
+{
+    match crate::util::flag_value(args, "--alert-sound")?.as_deref().or(preference) {
+        Some(name) => AlertSound::from_name(name),
+        None => Ok(AlertSound::Off),
+    }
+}
+
+/// Rings the alert sound when a critical alert shows up that was not there before.
+///
+/// The alert list is checked on every frame, so alerts are told apart by timestamp: only
+/// ones newer than everything seen on the previous check count. Rings are at least
+/// `min_gap` apart, since an ongoing violation is raised again on every refresh.
+#[derive(Debug)]
+pub struct AlertBell {
+    sound: AlertSound,
+    min_gap: Duration,
+    last_seen: Option<DateTime<Utc>>,
+    last_rung: Option<Instant>,
+}
+
+impl AlertBell {
+    pub const DEFAULT_MIN_GAP: Duration = Duration::from_secs(30);
+
+    pub fn new(sound: AlertSound) -> Self {
+        Self {
+            sound,
+            min_gap: Self::DEFAULT_MIN_GAP,
+            last_seen: None,
+            last_rung: None,
+        }
+    }
+
+    pub fn set_min_gap(&mut self, min_gap: Duration) {
+        self.min_gap = min_gap;
+    }
+
+    /// Whether `alerts` holds a critical alert newer than any alert seen by the previous call
+    pub fn has_new_critical(&mut self, alerts: &[MisbehaviorAlert]) -> bool {
+        let last_seen = self.last_seen;
+        let is_new = |alert: &&MisbehaviorAlert| last_seen.is_none_or(|seen| alert.timestamp > seen);
+
+        let new_critical = alerts.iter().filter(is_new).any(|alert| alert.severity == Severity::Critical);
+        if let Some(newest) = alerts.iter().map(|alert| alert.timestamp).max() {
+            self.last_seen = Some(last_seen.map_or(newest, |seen| seen.max(newest)));
+        }
+        new_critical
+    }
+
+    /// Whether to ring now for `alerts`: a new critical alert, sound enabled, and the
+    /// previous ring at least `min_gap` ago.
+    pub fn should_ring(&mut self, alerts: &[MisbehaviorAlert], now: Instant) -> bool {
+        if !self.has_new_critical(alerts) || self.sound == AlertSound::Off {
+            return false;
+        }
+        if self.last_rung.is_some_and(|rung| now.duration_since(rung) < self.min_gap) {
+            return false;
+        }
+        self.last_rung = Some(now);
+        true
+    }
+
+    /// Play the configured sound; commands run in the background
+    pub fn ring(&self) -> Result<()> {
+        match &self.sound {
+            AlertSound::Off => {}
+            AlertSound::Bell => {
+                let mut stdout = std::io::stdout();
+                stdout.write_all(b"\x07")?;
+                stdout.flush()?;
+            }
+            AlertSound::Command(command) => {
+                let mut child = Command::new("sh")
+                    .args(["-c", command])
+                    .stdin(Stdio::null())
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::null())
+                    .spawn()
+                    .with_context(|| format!("Failed to run alert sound command '{}'", command))?;
+                // Reap it without holding up the UI, or every ring leaves a zombie behind
+                std::thread::spawn(move || child.wait());
+            }
+        }
+        Ok(())
+    }
+}
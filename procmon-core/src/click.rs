@@ -0,0 +1,82 @@
+use anyhow::{bail, Context, Result};
+use std::time::{Duration, Instant};
+
+/// Environment variable holding the double-click window in milliseconds, or `off`
+pub const DOUBLE_CLICK_ENV: &str = "PROCMON_DOUBLE_CLICK";
+
+pub const DEFAULT_DOUBLE_CLICK: Duration = Duration::from_millis(500);
+
+/// Shorter windows can't be hit reliably, and longer ones turn two separate clicks into one
+pub const DOUBLE_CLICK_RANGE: (Duration, Duration) = (Duration::from_millis(100), Duration::from_millis(2000));
+
+/// Parse a double-click window in milliseconds, e.g. `400`, or `off` to disable double-clicks
+pub fn parse_double_click(value: &str) -> Result<Option<Duration>> {
+    let value = value.trim();
+    if value.eq_ignore_ascii_case("off") {
+        return Ok(None);
+    }
+    let millis: u64 = value
+        .strip_suffix("ms")
+        .unwrap_or(value)
+        .trim()
+        .parse()
+        .with_context(|| format!("Invalid double-click time '{}' (expected milliseconds or off)", value))?;
+    let window = Duration::from_millis(millis);
+    let (min, max) = DOUBLE_CLICK_RANGE;
+    if window < min || window > max {
+        bail!("Double-click time {}ms is outside {}-{}ms", millis, min.as_millis(), max.as_millis());
+    }
+    Ok(Some(window))
+}
+
+/// Pick the double-click window from `--double-click <ms|off>` / `--double-click=<ms|off>` in
+/// `args`, falling back to the `preference` (normally `PROCMON_DOUBLE_CLICK`) and then 500ms.
+/// `None` means double-clicks are off.
+pub fn double_click<I>(args: I, preference: Option<&str>) -> Result<Option<Duration>>
+where
+    I: IntoIterator<Item = String>,
+{
+    match crate::util::flag_value(args, "--double-click")?.as_deref().or(preference) {
+        Some(value) => parse_double_click(value),
+        None => Ok(Some(DEFAULT_DOUBLE_CLICK)),
+    }
+}
+
+/// Tells double-clicks from single clicks: a second click on the same row strictly within the
+/// window. The click completing a double-click doesn't start another one.
+#[derive(Debug, Clone)]
+pub struct DoubleClick {
+    window: Option<Duration>,
+    last: Option<(Instant, usize)>,
+}
+
+impl Default for DoubleClick {
+    fn default() -> Self {
+        Self::new(Some(DEFAULT_DOUBLE_CLICK))
+    }
+}
+
+impl DoubleClick {
+    /// `None` turns double-click detection off; every click is then a single click
+    pub fn new(window: Option<Duration>) -> Self {
+        Self { window, last: None }
+    }
+
+    pub fn window(&self) -> Option<Duration> {
+        self.window
+    }
+
+    /// Register a click on `row` at `now`; true if it completes a double-click
+    pub fn click(&mut self, row: usize, now: Instant) -> bool {
+        let Some(window) = self.window else {
+            return false;
+        };
+        match self.last.take() {
+            Some((at, last_row)) if last_row == row && now.saturating_duration_since(at) < window => true,
+            _ => {
+                self.last = Some((now, row));
+                false
+            }
+        }
+    }
+}
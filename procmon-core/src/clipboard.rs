@@ -0,0 +1,62 @@
+use crate::partition::Partition;
+use crate::process::ProcessSnapshot;
+use crate::service::SystemService;
+
+/// One-line, human readable description of a table row for pasting into tickets.
+pub trait ClipboardText {
+    fn clipboard_text(&self) -> String;
+}
+
+impl ClipboardText for ProcessSnapshot {
+    fn clipboard_text(&self) -> String {
+        let mut text = format!(
+            "PID {} {} (user {}) CPU {:.1}% MEM {:.1} MB status {:?}",
+            self.info.pid,
+            self.info.name,
+            self.info.user,
+            self.stats.cpu_usage,
+            self.stats.memory_usage as f64 / (1024.0 * 1024.0),
+            self.info.status
+        );
+        if !self.info.command_line.is_empty() {
+            text.push_str(&format!(" cmd: {}", self.info.command_line.join(" ")));
+        }
+        text
+    }
+}
+
+impl ClipboardText for SystemService {
+    fn clipboard_text(&self) -> String {
+        let mut text = format!(
+            "{}.service {}/{} enabled={}",
+            self.name,
+            self.active_state,
+            self.sub_state,
+            if self.enabled { "yes" } else { "no" }
+        );
+        if let Some(pid) = self.main_pid {
+            text.push_str(&format!(" pid={}", pid));
+        }
+        if let Some(memory) = self.memory_usage {
+            text.push_str(&format!(" mem={:.1} MB", memory as f64 / (1024.0 * 1024.0)));
+        }
+        if !self.description.is_empty() {
+            text.push_str(&format!(" - {}", self.description));
+        }
+        text
+    }
+}
+
+impl ClipboardText for Partition {
+    fn clipboard_text(&self) -> String {
+        format!(
+            "{} fs={} label={} size={:.2} GB used={:.2} GB mount={}",
+            self.device,
+            self.filesystem.as_deref().unwrap_or("unknown"),
+            self.label.as_deref().unwrap_or("-"),
+            self.size_bytes as f64 / (1024.0 * 1024.0 * 1024.0),
+            self.used_bytes as f64 / (1024.0 * 1024.0 * 1024.0),
+            self.mount_point.as_deref().unwrap_or("-")
+        )
+    }
+}
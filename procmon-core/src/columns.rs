@@ -0,0 +1,309 @@
+use crate::process::ProcessSnapshot;
+use crate::container::short_id;
+use crate::threads::ThreadInfo;
+use crate::util::format_duration;
+use anyhow::{bail, Result};
+
+/// Environment variable holding the preferred process table columns, e.g. `pid,name,cpu`
+pub const COLUMNS_ENV: &str = "PROCMON_COLUMNS";
+
+/// A column of the process table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessColumn {
+    Pid,
+    Name,
+    User,
+    Cpu,
+    CpuAvg,
+    Mem,
+    /// Growth of resident memory since the last refresh
+    MemDelta,
+    VirtMem,
+    Swap,
+    Disk,
+    Threads,
+    Status,
+    Nice,
+    StartTime,
+    Age,
+    /// Short id of the container the process runs in
+    Container,
+    /// Minor page faults since the process started
+    MinFlt,
+    /// Major page faults since the process started, with the increase since the last refresh
+    MajFlt,
+}
+
+/// The columns shown when no preference is set
+pub const DEFAULT_COLUMNS: &[ProcessColumn] = &[
+    ProcessColumn::Pid,
+    ProcessColumn::Name,
+    ProcessColumn::User,
+    ProcessColumn::Cpu,
+    ProcessColumn::Mem,
+    ProcessColumn::Disk,
+    ProcessColumn::Age,
+    ProcessColumn::Status,
+];
+
+const BYTES_PER_MB: f64 = 1024.0 * 1024.0;
+
+impl ProcessColumn {
+    pub const ALL: [ProcessColumn; 18] = [
+        ProcessColumn::Pid,
+        ProcessColumn::Name,
+        ProcessColumn::User,
+        ProcessColumn::Cpu,
+        ProcessColumn::CpuAvg,
+        ProcessColumn::Mem,
+        ProcessColumn::MemDelta,
+        ProcessColumn::VirtMem,
+        ProcessColumn::Swap,
+        ProcessColumn::Disk,
+        ProcessColumn::Threads,
+        ProcessColumn::Status,
+        ProcessColumn::Nice,
+        ProcessColumn::StartTime,
+        ProcessColumn::Age,
+        ProcessColumn::Container,
+        ProcessColumn::MinFlt,
+        ProcessColumn::MajFlt,
+    ];
+
+    /// Name used in the column preference
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProcessColumn::Pid => "pid",
+            ProcessColumn::Name => "name",
+            ProcessColumn::User => "user",
+            ProcessColumn::Cpu => "cpu",
+            ProcessColumn::CpuAvg => "cpuavg",
+            ProcessColumn::Mem => "mem",
+            ProcessColumn::MemDelta => "memdelta",
+            ProcessColumn::VirtMem => "virtmem",
+            ProcessColumn::Swap => "swap",
+            ProcessColumn::Disk => "disk",
+            ProcessColumn::Threads => "threads",
+            ProcessColumn::Status => "status",
+            ProcessColumn::Nice => "nice",
+            ProcessColumn::StartTime => "starttime",
+            ProcessColumn::Age => "age",
+            ProcessColumn::Container => "container",
+            ProcessColumn::MinFlt => "minflt",
+            ProcessColumn::MajFlt => "majflt",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Result<ProcessColumn> {
+        let lower = name.trim().to_lowercase();
+        match Self::ALL.iter().find(|column| column.name() == lower) {
+            Some(column) => Ok(*column),
+            None => {
+                let names: Vec<&str> = Self::ALL.iter().map(|column| column.name()).collect();
+                bail!("Unknown column '{}' (expected one of: {})", name, names.join(", "))
+            }
+        }
+    }
+
+    pub fn header(&self, show_command_line: bool) -> &'static str {
+        match self {
+            ProcessColumn::Pid => "PID",
+            ProcessColumn::Name if show_command_line => "Command",
+            ProcessColumn::Name => "Name",
+            ProcessColumn::User => "User",
+            ProcessColumn::Cpu => "CPU %",
+            ProcessColumn::CpuAvg => "CPU Avg",
+            ProcessColumn::Mem => "Mem (MB)",
+            ProcessColumn::MemDelta => "ΔMem (MB)",
+            ProcessColumn::VirtMem => "Virt (MB)",
+            ProcessColumn::Swap => "Swap (MB)",
+            ProcessColumn::Disk => "Disk (MB)",
+            ProcessColumn::Threads => "Threads",
+            ProcessColumn::Status => "Status",
+            ProcessColumn::Nice => "Nice",
+            ProcessColumn::StartTime => "Started",
+            ProcessColumn::Age => "Age",
+            ProcessColumn::Container => "Container",
+            ProcessColumn::MinFlt => "MinFlt",
+            ProcessColumn::MajFlt => "MajFlt",
+        }
+    }
+
+    /// Width in characters; the name column is the flexible one and this is its minimum
+    pub fn width(&self, show_command_line: bool) -> usize {
+        match self {
+            ProcessColumn::Pid => 8,
+            ProcessColumn::Name if show_command_line => 40,
+            ProcessColumn::Name => 20,
+            ProcessColumn::User => 12,
+            ProcessColumn::Cpu | ProcessColumn::CpuAvg => 8,
+            ProcessColumn::Mem
+            | ProcessColumn::MemDelta
+            | ProcessColumn::VirtMem
+            | ProcessColumn::Swap
+            | ProcessColumn::Disk => 11,
+            ProcessColumn::Threads => 8,
+            ProcessColumn::Status => 10,
+            ProcessColumn::Nice => 5,
+            ProcessColumn::StartTime => 19,
+            ProcessColumn::Age => 9,
+            ProcessColumn::Container => crate::container::SHORT_ID_LEN,
+            ProcessColumn::MinFlt => 10,
+            ProcessColumn::MajFlt => 16,
+        }
+    }
+
+    /// Numbers are right aligned, text left aligned
+    pub fn right_aligned(&self) -> bool {
+        matches!(
+            self,
+            ProcessColumn::Cpu
+                | ProcessColumn::CpuAvg
+                | ProcessColumn::Mem
+                | ProcessColumn::MemDelta
+                | ProcessColumn::VirtMem
+                | ProcessColumn::Swap
+                | ProcessColumn::Disk
+                | ProcessColumn::Threads
+                | ProcessColumn::Nice
+                | ProcessColumn::MinFlt
+                | ProcessColumn::MajFlt
+        )
+    }
+
+    /// Columns the process search looks at, whose matches are highlighted
+    pub fn searchable(&self) -> bool {
+        matches!(self, ProcessColumn::Pid | ProcessColumn::Name | ProcessColumn::User)
+    }
+
+    /// Text of this column for one process
+    pub fn cell(&self, process: &ProcessSnapshot, show_command_line: bool) -> String {
+        let stats = &process.stats;
+        match self {
+            ProcessColumn::Pid => process.info.pid.to_string(),
+            ProcessColumn::Name => process.info.display_name(show_command_line),
+            ProcessColumn::User => process.info.user.clone(),
+            ProcessColumn::Cpu => format!("{:.1}%", stats.cpu_usage),
+            ProcessColumn::CpuAvg => format!("{:.1}%", stats.cpu_usage_avg),
+            ProcessColumn::Mem => format!("{:.1}", stats.memory_usage as f64 / BYTES_PER_MB),
+            ProcessColumn::MemDelta => format!("{:+.1}", stats.memory_delta as f64 / BYTES_PER_MB),
+            ProcessColumn::VirtMem => format!("{:.1}", stats.virtual_memory as f64 / BYTES_PER_MB),
+            ProcessColumn::Swap => format!("{:.1}", stats.swap_usage as f64 / BYTES_PER_MB),
+            ProcessColumn::Disk => {
+                format!("{:.1}", (stats.disk_read_bytes + stats.disk_write_bytes) as f64 / BYTES_PER_MB)
+            }
+            ProcessColumn::Threads => stats.num_threads.to_string(),
+            ProcessColumn::Status => format!("{:?}", process.info.status),
+            ProcessColumn::Nice => stats.nice.to_string(),
+            ProcessColumn::StartTime => stats
+                .start_time
+                .with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string(),
+            ProcessColumn::Age => format_duration(stats.run_time),
+            ProcessColumn::Container => {
+                process.info.container.as_deref().map_or_else(|| "-".to_string(), |id| short_id(id).to_string())
+            }
+            ProcessColumn::MinFlt => stats.minor_faults.to_string(),
+            ProcessColumn::MajFlt if stats.major_faults_delta > 0 => {
+                format!("{} (+{})", stats.major_faults, stats.major_faults_delta)
+            }
+            ProcessColumn::MajFlt => stats.major_faults.to_string(),
+        }
+    }
+
+    /// Text of this column for a thread row under its process; columns without a per-thread
+    /// value stay empty
+    pub fn thread_cell(&self, thread: &ThreadInfo) -> String {
+        match self {
+            ProcessColumn::Pid => format!("└ {}", thread.tid),
+            ProcessColumn::Name => thread.name.clone(),
+            ProcessColumn::Cpu => format!("{:.1}%", thread.cpu_usage),
+            ProcessColumn::Status => format!("{:?}", thread.status),
+            _ => String::new(),
+        }
+    }
+}
+
+/// Parse a comma separated column list such as `pid,name,cpu,mem`.
+pub fn parse_columns(list: &str) -> Result<Vec<ProcessColumn>> {
+    let mut columns = Vec::new();
+    for name in list.split(',').filter(|name| !name.trim().is_empty()) {
+        let column = ProcessColumn::from_name(name)?;
+        if columns.contains(&column) {
+            bail!("Column '{}' listed more than once", column.name());
+        }
+        columns.push(column);
+    }
+
+    if columns.is_empty() {
+        bail!("No columns given");
+    }
+    Ok(columns)
+}
+
+/// Pick the process table columns from `--columns <list>` / `--columns=<list>` in `args`,
+/// falling back to the `preference` (normally `PROCMON_COLUMNS`) and then the defaults.
+pub fn process_columns<I>(args: I, preference: Option<&str>) -> Result<Vec<ProcessColumn>>
+where
+    I: IntoIterator<Item = String>,
+{
+    match crate::util::flag_value(args, "--columns")?.as_deref().or(preference) {
+        Some(list) => parse_columns(list),
+        None => Ok(DEFAULT_COLUMNS.to_vec()),
+    }
+}
+
+/// Header labels for `columns`, in order
+pub fn header_labels(columns: &[ProcessColumn], show_command_line: bool) -> Vec<&'static str> {
+    columns.iter().map(|column| column.header(show_command_line)).collect()
+}
+
+/// Pad or truncate `text` to the column width, marking cuts with "..."
+fn fit(column: ProcessColumn, text: &str, show_command_line: bool) -> String {
+    let width = column.width(show_command_line);
+    let text = if text.chars().count() > width {
+        let kept: String = text.chars().take(width.saturating_sub(3)).collect();
+        format!("{}...", kept)
+    } else {
+        text.to_string()
+    };
+
+    if column.right_aligned() {
+        format!("{:>width$}", text)
+    } else {
+        format!("{:<width$}", text)
+    }
+}
+
+/// Fixed-width header line for monospace rendering
+pub fn format_header_line(columns: &[ProcessColumn], show_command_line: bool) -> String {
+    columns
+        .iter()
+        .map(|column| fit(*column, column.header(show_command_line), show_command_line))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Fixed-width cells of one row, in column order; joined with single spaces they line up
+/// with [`format_header_line`]
+pub fn format_row_cells(columns: &[ProcessColumn], process: &ProcessSnapshot, show_command_line: bool) -> Vec<String> {
+    columns
+        .iter()
+        .map(|column| fit(*column, &column.cell(process, show_command_line), show_command_line))
+        .collect()
+}
+
+/// Fixed-width line of a thread row, aligned with [`format_header_line`]
+pub fn format_thread_line(columns: &[ProcessColumn], thread: &ThreadInfo, show_command_line: bool) -> String {
+    columns
+        .iter()
+        .map(|column| fit(*column, &column.thread_cell(thread), show_command_line))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Fixed-width row line for monospace rendering, aligned with [`format_header_line`]
+pub fn format_row_line(columns: &[ProcessColumn], process: &ProcessSnapshot, show_command_line: bool) -> String {
+    format_row_cells(columns, process, show_command_line).join(" ")
+}
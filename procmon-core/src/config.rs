@@ -0,0 +1,379 @@
+use crate::detail::DEFAULT_DETAIL_ENTRIES;
+use crate::detector::{AlertRateLimiter, MisbehaviorRule};
+use crate::metrics::GaugeSmoothing;
+use crate::util::RefreshSchedule;
+use crate::columns::parse_columns;
+use crate::density::Density;
+use crate::tab::Tab;
+use crate::temperature::TemperatureUnit;
+use anyhow::Result;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use toml::Spanned;
+
+/// Written to [`config_path`] on first run; every setting at its default, optional ones
+/// commented out
+pub const DEFAULT_CONFIG: &str = r#"# procmon configuration, shared by procmon-tui and procmon-gui.
+# Command line flags and PROCMON_* environment variables take precedence over this file.
+
+[general]
+# Milliseconds between refreshes
+refresh_interval_ms = 1000
+# Tab shown at startup: dashboard, processes, services, storage, network, partitions, alerts, logs
+# startup_tab = "processes"
+
+[display]
+# Color scheme of the GUI: "dark" or "light"
+theme = "dark"
+# Temperature unit: "c" or "f"
+# temperature_unit = "c"
+# Row spacing: "compact", "normal" or "comfortable"
+# density = "normal"
+# Process table columns, see --columns
+# columns = "pid,name,user,cpu,mem,disk,age,status"
+# Weight of the newest sample in the smoothed dashboard gauges, from 0.05 (smoothest) to 1 (raw)
+smoothing_alpha = 0.3
+# Alerts kept in the Alerts tab; older ones are dropped
+max_alerts = 100
+# New alerts accepted per second before the rest are collapsed into one summary; 0 disables
+# the cap
+max_alerts_per_sec = 20
+# Open files and threads listed per process in the detail view and the thread list; the rest
+# are only counted
+detail_entries = 500
+
+[lists]
+# Process name globs left out of the process table and the alerts entirely
+ignore = []
+# Process name globs that never raise alerts but are still listed
+allow = []
+# Process names that can't be killed or restarted, on top of protected.toml
+protect = []
+
+[rules]
+# JSON file with the alert rules, replacing the built-in ones; relative to this directory
+# path = "rules.json"
+"#;
+
+/// Everything in `~/.config/procmon/config.toml`. Missing sections and keys keep their defaults.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub general: GeneralConfig,
+    pub display: DisplayConfig,
+    pub lists: ListConfig,
+    pub rules: RulesConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GeneralConfig {
+    pub refresh_interval_ms: u64,
+    /// Fallback for `--tab` and `PROCMON_TAB`
+    pub startup_tab: Option<String>,
+}
+
+impl Default for GeneralConfig {
+    fn default() -> Self {
+        Self { refresh_interval_ms: 1000, startup_tab: None }
+    }
+}
+
+impl GeneralConfig {
+    /// The refresh interval, no shorter than CPU usage sampling allows
+    pub fn refresh_interval(&self) -> Duration {
+        Duration::from_millis(self.refresh_interval_ms).max(RefreshSchedule::MIN_SPACING)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    #[default]
+    Dark,
+    Light,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DisplayConfig {
+    pub theme: Theme,
+    /// Fallback for `--temp-unit` and `PROCMON_TEMP_UNIT`
+    pub temperature_unit: Option<String>,
+    /// Fallback for `--density` and `PROCMON_DENSITY`
+    pub density: Option<String>,
+    /// Fallback for `--columns` and `PROCMON_COLUMNS`
+    pub columns: Option<String>,
+    /// See [`DisplayConfig::gauge_alpha`]
+    pub smoothing_alpha: f64,
+    pub max_alerts: usize,
+    /// See [`DisplayConfig::alert_rate_cap`]
+    pub max_alerts_per_sec: usize,
+    pub detail_entries: usize,
+}
+
+impl DisplayConfig {
+    /// `smoothing_alpha` limited to what the GUI's slider offers; 0 would freeze the gauges
+    pub fn gauge_alpha(&self) -> f64 {
+        self.smoothing_alpha.clamp(0.05, 1.0)
+    }
+
+    /// The cap for [`MisbehaviorDetector::set_max_alerts_per_sec`](crate::MisbehaviorDetector::set_max_alerts_per_sec),
+    /// `None` when `max_alerts_per_sec` is 0
+    pub fn alert_rate_cap(&self) -> Option<usize> {
+        (self.max_alerts_per_sec > 0).then_some(self.max_alerts_per_sec)
+    }
+}
+
+impl Default for DisplayConfig {
+    fn default() -> Self {
+        Self {
+            theme: Theme::Dark,
+            temperature_unit: None,
+            density: None,
+            columns: None,
+            smoothing_alpha: GaugeSmoothing::DEFAULT_ALPHA,
+            max_alerts: 100,
+            max_alerts_per_sec: AlertRateLimiter::DEFAULT_MAX_PER_SEC,
+            detail_entries: DEFAULT_DETAIL_ENTRIES,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ListConfig {
+    pub ignore: Vec<String>,
+    pub allow: Vec<String>,
+    pub protect: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RulesConfig {
+    pub path: Option<PathBuf>,
+}
+
+/// A problem in the config or rules file; the part it is in fell back to its defaults
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigWarning {
+    /// `[section]`, `section.key` or `rule N` the problem is in
+    pub location: String,
+    /// 1-based line in the file, when known
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {}, {}: {}", line, self.location, self.message),
+            None => write!(f, "{}: {}", self.location, self.message),
+        }
+    }
+}
+
+impl Config {
+    /// Load [`config_path`], writing [`DEFAULT_CONFIG`] there first if there is no file yet.
+    /// Without a home directory the defaults are used. Problems never stop procmon; see
+    /// [`Config::parse_lenient`].
+    pub fn load() -> (Self, Vec<ConfigWarning>) {
+        match config_path() {
+            Some(path) => Self::load_or_create(&path),
+            None => (Self::default(), Vec::new()),
+        }
+    }
+
+    pub fn load_or_create(path: &Path) -> (Self, Vec<ConfigWarning>) {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Self::parse_lenient(&text),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                // Not being able to write the default is no reason to stop
+                if let Err(e) = write_default_config(path) {
+                    tracing::warn!("Could not write default config to {}: {:#}", path.display(), e);
+                }
+                (Self::default(), Vec::new())
+            }
+            Err(e) => {
+                let warning = ConfigWarning {
+                    location: path.display().to_string(),
+                    line: None,
+                    message: format!("can't be read, using defaults: {}", e),
+                };
+                (Self::default(), vec![warning])
+            }
+        }
+    }
+
+    /// Parse the whole file, failing on the first problem
+    pub fn parse(text: &str) -> Result<Self> {
+        Ok(toml::from_str(text)?)
+    }
+
+    /// Parse the file once and take each `[section]` on its own, so a typo only resets the
+    /// section it is in to its defaults. Unknown sections are ignored, and a file that isn't
+    /// valid TOML keeps all defaults. Values the frontends can't use (an unknown tab, column
+    /// or unit) only reset that key. Every problem is returned with its line where known.
+    pub fn parse_lenient(text: &str) -> (Self, Vec<ConfigWarning>) {
+        let mut config = Self::default();
+        let mut warnings = Vec::new();
+        let line_at = |offset: usize| text[..offset.min(text.len())].matches('\n').count() + 1;
+
+        let sections: BTreeMap<String, Spanned<toml::Value>> = match toml::from_str(text) {
+            Ok(sections) => sections,
+            Err(e) => {
+                let warning = ConfigWarning {
+                    location: "config file".to_string(),
+                    line: e.span().map(|span| line_at(span.start)),
+                    message: format!("{}, using defaults", e.message()),
+                };
+                return (config, vec![warning]);
+            }
+        };
+        for (name, value) in sections {
+            let line = Some(line_at(value.span().start));
+            let value = value.into_inner();
+            let result = match name.as_str() {
+                "general" => parse_section(value).map(|section| config.general = section),
+                "display" => parse_section(value).map(|section| config.display = section),
+                "lists" => parse_section(value).map(|section| config.lists = section),
+                "rules" => parse_section(value).map(|section| config.rules = section),
+                _ => Err("unknown section, ignored".to_string()),
+            };
+            if let Err(message) = result {
+                warnings.push(ConfigWarning { location: format!("[{}]", name), line, message });
+            }
+        }
+
+        warnings.extend(config.reset_unusable_values());
+        (config, warnings)
+    }
+
+    /// Clear optional values the frontends would refuse at startup, with a warning for each
+    fn reset_unusable_values(&mut self) -> Vec<ConfigWarning> {
+        let mut warnings = Vec::new();
+        let mut check = |location: &str, value: &mut Option<String>, valid: &dyn Fn(&str) -> Result<()>| {
+            if let Some(Err(e)) = value.as_deref().map(valid) {
+                warnings.push(ConfigWarning {
+                    location: location.to_string(),
+                    line: None,
+                    message: format!("{}, using the default", e),
+                });
+                *value = None;
+            }
+        };
+        check("general.startup_tab", &mut self.general.startup_tab, &|name| Tab::from_name(name).map(|_| ()));
+        check("display.temperature_unit", &mut self.display.temperature_unit, &|name| {
+            TemperatureUnit::from_name(name).map(|_| ())
+        });
+        check("display.density", &mut self.display.density, &|name| Density::from_name(name).map(|_| ()));
+        check("display.columns", &mut self.display.columns, &|list| parse_columns(list).map(|_| ()));
+        warnings
+    }
+
+    /// The configured rules file, relative paths taken from the config directory
+    pub fn rules_path(&self) -> Option<PathBuf> {
+        let path = self.rules.path.as_ref()?;
+        if path.is_absolute() {
+            return Some(path.clone());
+        }
+        Some(procmon_config_dir().unwrap_or_default().join(path))
+    }
+
+    /// Rules from the configured rules file, or `None` to keep the built-in ones; see
+    /// [`parse_rules`]
+    pub fn load_rules(&self) -> (Option<Vec<MisbehaviorRule>>, Vec<ConfigWarning>) {
+        let Some(path) = self.rules_path() else {
+            return (None, Vec::new());
+        };
+        match std::fs::read_to_string(&path) {
+            Ok(text) => parse_rules(&text),
+            Err(e) => {
+                let warning = ConfigWarning {
+                    location: path.display().to_string(),
+                    line: None,
+                    message: format!("can't be read, using the built-in rules: {}", e),
+                };
+                (None, vec![warning])
+            }
+        }
+    }
+}
+
+/// Alert rules from a JSON array, skipping rules that don't parse. A file that isn't a JSON
+/// array at all keeps the built-in rules (`None`).
+pub fn parse_rules(text: &str) -> (Option<Vec<MisbehaviorRule>>, Vec<ConfigWarning>) {
+    let values: Vec<serde_json::Value> = match serde_json::from_str(text) {
+        Ok(values) => values,
+        Err(e) => {
+            let warning = ConfigWarning {
+                location: "rules file".to_string(),
+                line: Some(e.line()),
+                message: format!("{}, using the built-in rules", e),
+            };
+            return (None, vec![warning]);
+        }
+    };
+
+    let mut rules = Vec::new();
+    let mut warnings = Vec::new();
+    for (index, value) in values.into_iter().enumerate() {
+        let name = value.get("name").and_then(|name| name.as_str()).map(str::to_string);
+        match serde_json::from_value::<MisbehaviorRule>(value) {
+            Ok(rule) => rules.push(rule),
+            Err(e) => warnings.push(ConfigWarning {
+                location: match name {
+                    Some(name) => format!("rule {} ('{}')", index + 1, name),
+                    None => format!("rule {}", index + 1),
+                },
+                line: None,
+                message: format!("{}, skipped", e),
+            }),
+        }
+    }
+    (Some(rules), warnings)
+}
+
+/// One section of the config file, or what is wrong with it, naming the key at fault
+fn parse_section<T: DeserializeOwned>(value: toml::Value) -> std::result::Result<T, String> {
+    let e = match value.clone().try_into() {
+        Ok(section) => return Ok(section),
+        Err(e) => e,
+    };
+    // The error doesn't say which key it is about; the first key that fails on its own is
+    let culprit = value.as_table().and_then(|table| {
+        table.iter().map(|(key, value)| (key, toml::Table::from_iter([(key.clone(), value.clone())]))).find_map(
+            |(key, single)| toml::Value::Table(single).try_into::<T>().is_err().then_some(key),
+        )
+    });
+    match culprit {
+        Some(key) => Err(format!("{}: {}, using defaults", key, e.message())),
+        None => Err(format!("{}, using defaults", e.message())),
+    }
+}
+
+fn write_default_config(path: &Path) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, DEFAULT_CONFIG)?;
+    Ok(())
+}
+
+/// `$XDG_CONFIG_HOME/procmon`, falling back to `~/.config/procmon`
+pub fn procmon_config_dir() -> Option<PathBuf> {
+    let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config_dir.join("procmon"))
+}
+
+/// `config.toml` in [`procmon_config_dir`]
+pub fn config_path() -> Option<PathBuf> {
+    Some(procmon_config_dir()?.join("config.toml"))
+}
@@ -0,0 +1,60 @@
+use std::time::{Duration, Instant};
+
+/// How long a destructive button has to be held down before it acts
+pub const HOLD_TO_CONFIRM: Duration = Duration::from_secs(1);
+
+/// Press-and-hold state of a destructive button: it fires once the button has been held for
+/// the whole duration, and letting go early starts over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HoldToConfirm {
+    duration: Duration,
+    pressed_at: Option<Instant>,
+    /// Already fired for the current press; the button has to be released before it fires again
+    fired: bool,
+}
+
+impl Default for HoldToConfirm {
+    fn default() -> Self {
+        Self::new(HOLD_TO_CONFIRM)
+    }
+}
+
+impl HoldToConfirm {
+    pub fn new(duration: Duration) -> Self {
+        Self { duration, pressed_at: None, fired: false }
+    }
+
+    /// Feed whether the button is held down this frame; true exactly once per completed hold
+    pub fn update(&mut self, held: bool, now: Instant) -> bool {
+        if !held {
+            self.reset();
+            return false;
+        }
+        let pressed_at = *self.pressed_at.get_or_insert(now);
+        if !self.fired && now.saturating_duration_since(pressed_at) >= self.duration {
+            self.fired = true;
+            return true;
+        }
+        false
+    }
+
+    /// How far the current hold is, from 0.0 (not held) to 1.0 (confirmed), for the fill animation
+    pub fn progress(&self, now: Instant) -> f32 {
+        match self.pressed_at {
+            Some(pressed_at) if !self.duration.is_zero() => {
+                (now.saturating_duration_since(pressed_at).as_secs_f32() / self.duration.as_secs_f32()).min(1.0)
+            }
+            Some(_) => 1.0,
+            None => 0.0,
+        }
+    }
+
+    pub fn is_holding(&self) -> bool {
+        self.pressed_at.is_some()
+    }
+
+    pub fn reset(&mut self) {
+        self.pressed_at = None;
+        self.fired = false;
+    }
+}
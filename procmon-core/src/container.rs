@@ -0,0 +1,101 @@
+use crate::process::ProcessSnapshot;
+use std::fs;
+use std::path::Path;
+
+/// Characters of a container id shown in tables, as `docker ps` does
+pub const SHORT_ID_LEN: usize = 12;
+
+/// Prefixes container runtimes put in front of the id in cgroup v2 scope names
+const SCOPE_PREFIXES: &[&str] = &["docker-", "libpod-", "cri-containerd-", "crio-"];
+
+/// Files container runtimes put in the root of a container (Docker, Podman)
+const CONTAINER_MARKERS: &[&str] = &["/.dockerenv", "/run/.containerenv"];
+
+/// Cgroup path segments of runtimes whose ids [`parse_container_id`] doesn't recognize
+const CONTAINER_CGROUP_HINTS: &[&str] = &["docker", "kubepods", "libpod", "lxc", "containerd"];
+
+/// Shown in the UI when procmon runs inside a container
+pub const CONTAINER_NOTE: &str =
+    "Running in a container: host processes, sensors and disks may be unavailable, and users may show as uid:N";
+
+/// Whether procmon runs inside a container, given whether a runtime marker file exists and the
+/// contents of its own `/proc/self/cgroup`. With a cgroup namespace (the default on cgroup v2)
+/// the cgroup path is just `/`, so then only the marker file tells.
+pub fn is_containerized(has_marker: bool, own_cgroup: &str) -> bool {
+    has_marker
+        || parse_container_id(own_cgroup).is_some()
+        || own_cgroup
+            .lines()
+            .filter_map(|line| line.splitn(3, ':').nth(2))
+            .any(|path| path.split('/').any(|segment| CONTAINER_CGROUP_HINTS.iter().any(|hint| segment.starts_with(hint))))
+}
+
+pub fn detect_container() -> bool {
+    let has_marker = CONTAINER_MARKERS.iter().any(|marker| Path::new(marker).exists());
+    is_containerized(has_marker, &fs::read_to_string("/proc/self/cgroup").unwrap_or_default())
+}
+
+/// Container id of a process from the contents of `/proc/{pid}/cgroup`, or `None` on the host.
+///
+/// Recognizes Docker and Podman scopes (`docker-<id>.scope`, `libpod-<id>.scope`), cgroup v1
+/// paths (`/docker/<id>`) and Kubernetes pods (`/kubepods/.../<id>`,
+/// `cri-containerd-<id>.scope`, `crio-<id>.scope`).
+pub fn parse_container_id(cgroup: &str) -> Option<String> {
+    cgroup
+        .lines()
+        // hierarchy-ID:controller-list:cgroup-path; the path itself may contain ':'
+        .filter_map(|line| line.splitn(3, ':').nth(2))
+        .find_map(|path| path.split('/').rev().find_map(container_id_in_segment))
+}
+
+fn container_id_in_segment(segment: &str) -> Option<String> {
+    let name = segment.strip_suffix(".scope").unwrap_or(segment);
+    let id = SCOPE_PREFIXES
+        .iter()
+        .find_map(|prefix| name.strip_prefix(prefix))
+        .unwrap_or(name);
+    // Runtimes use 64 hex digit ids; this also skips helpers such as libpod-conmon-<id>
+    (id.len() == 64 && id.chars().all(|c| c.is_ascii_hexdigit())).then(|| id.to_string())
+}
+
+/// First [`SHORT_ID_LEN`] characters of a container id
+pub fn short_id(id: &str) -> &str {
+    id.get(..SHORT_ID_LEN).unwrap_or(id)
+}
+
+/// Resource totals of the processes in one container, or on the host when `container` is `None`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContainerGroup {
+    pub container: Option<String>,
+    pub processes: usize,
+    pub cpu_usage: f32,
+    pub memory_usage: u64,
+}
+
+/// Sum processes per container, busiest container first; host processes come last.
+pub fn group_by_container(processes: &[ProcessSnapshot]) -> Vec<ContainerGroup> {
+    let mut groups: Vec<ContainerGroup> = Vec::new();
+    for process in processes {
+        let container = &process.info.container;
+        let index = match groups.iter().position(|group| group.container == *container) {
+            Some(index) => index,
+            None => {
+                groups.push(ContainerGroup { container: container.clone(), processes: 0, cpu_usage: 0.0, memory_usage: 0 });
+                groups.len() - 1
+            }
+        };
+        let group = &mut groups[index];
+        group.processes += 1;
+        group.cpu_usage += process.stats.cpu_usage;
+        group.memory_usage += process.stats.memory_usage;
+    }
+
+    groups.sort_by(|a, b| {
+        a.container
+            .is_none()
+            .cmp(&b.container.is_none())
+            .then_with(|| b.cpu_usage.total_cmp(&a.cpu_usage))
+            .then_with(|| a.container.cmp(&b.container))
+    });
+    groups
+}
@@ -0,0 +1,57 @@
+use crate::process::ProcessSnapshot;
+use crate::util::stat_field;
+
+/// Field 39 (`processor`) of `/proc/{pid}/stat`: the CPU the process last ran on.
+pub fn parse_last_cpu(stat: &str) -> Option<u32> {
+    stat_field(stat, 39)?.parse().ok()
+}
+
+/// Group processes by the core they last ran on, busiest first within each core.
+///
+/// Only processes using at least `min_cpu` percent are included; processes reporting a
+/// core outside `0..core_count` (e.g. after CPU hot-unplug) are dropped.
+pub fn group_by_core(processes: &[ProcessSnapshot], core_count: usize, min_cpu: f32) -> Vec<Vec<&ProcessSnapshot>> {
+    let mut cores: Vec<Vec<&ProcessSnapshot>> = vec![Vec::new(); core_count];
+
+    for process in processes.iter().filter(|p| p.stats.cpu_usage >= min_cpu) {
+        if let Some(core) = cores.get_mut(process.stats.last_cpu as usize) {
+            core.push(process);
+        }
+    }
+
+    for core in &mut cores {
+        core.sort_by(|a, b| {
+            b.stats.cpu_usage
+                .total_cmp(&a.stats.cpu_usage)
+                .then_with(|| a.info.pid.cmp(&b.info.pid))
+        });
+    }
+
+    cores
+}
+
+/// Bar labels for a per-core chart: the core numbers as owned strings.
+pub fn core_labels(core_count: usize) -> Vec<String> {
+    (0..core_count).map(|core| core.to_string()).collect()
+}
+
+/// Per-core chart labels that survive between frames.
+///
+/// The labels are only rebuilt when the number of cores changes, so drawing the chart
+/// every frame borrows the same strings instead of allocating new ones.
+#[derive(Debug, Default)]
+pub struct CoreLabels {
+    labels: Vec<String>,
+}
+
+impl CoreLabels {
+    pub fn update(&mut self, core_count: usize) {
+        if self.labels.len() != core_count {
+            self.labels = core_labels(core_count);
+        }
+    }
+
+    pub fn labels(&self) -> &[String] {
+        &self.labels
+    }
+}
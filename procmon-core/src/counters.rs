@@ -0,0 +1,102 @@
+use crate::process::ProcessSnapshot;
+use std::fs;
+
+/// Percentage of a kernel limit above which a counter is shown as a warning
+pub const WARNING_PERCENT: f32 = 80.0;
+/// Percentage of a kernel limit above which a counter is shown as critical
+pub const CRITICAL_PERCENT: f32 = 95.0;
+
+/// How close a counter is to its kernel limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimitLevel {
+    Normal,
+    Warning,
+    Critical,
+}
+
+/// System-wide totals and the kernel limits they run into
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SystemCounters {
+    pub processes: u64,
+    /// Sum of the per-process thread counts
+    pub threads: u64,
+    /// Tasks in the kernel from `/proc/loadavg`; every thread takes a PID, so this is what
+    /// runs into `pid_max`
+    pub pids_in_use: u64,
+    /// Allocated file handles from `/proc/sys/fs/file-nr`
+    pub open_files: u64,
+    /// `/proc/sys/kernel/pid_max`
+    pub pid_max: Option<u64>,
+    /// `/proc/sys/kernel/threads-max`
+    pub threads_max: Option<u64>,
+    /// Maximum from `/proc/sys/fs/file-nr`
+    pub file_max: Option<u64>,
+}
+
+impl SystemCounters {
+    /// Count processes and threads in `processes` and read the rest from `/proc`.
+    pub fn collect(processes: &[ProcessSnapshot]) -> Self {
+        let file_nr = fs::read_to_string("/proc/sys/fs/file-nr").ok().and_then(|text| parse_file_nr(&text));
+        let threads = processes.iter().map(|p| p.stats.num_threads as u64).sum();
+        // The listed threads leave out ignored processes, so they only stand in when
+        // /proc/loadavg can't be read
+        let tasks = fs::read_to_string("/proc/loadavg").ok().and_then(|text| parse_loadavg_tasks(&text));
+
+        Self {
+            processes: processes.len() as u64,
+            threads,
+            pids_in_use: tasks.unwrap_or(threads),
+            open_files: file_nr.map_or(0, |(allocated, _)| allocated),
+            pid_max: read_sys_value("/proc/sys/kernel/pid_max"),
+            threads_max: read_sys_value("/proc/sys/kernel/threads-max"),
+            file_max: file_nr.map(|(_, max)| max),
+        }
+    }
+
+    /// PIDs taken by all threads against `pid_max`
+    pub fn pids_percent(&self) -> Option<f32> {
+        percent_of_limit(self.pids_in_use, self.pid_max)
+    }
+
+    pub fn threads_percent(&self) -> Option<f32> {
+        percent_of_limit(self.threads, self.threads_max)
+    }
+
+    pub fn open_files_percent(&self) -> Option<f32> {
+        percent_of_limit(self.open_files, self.file_max)
+    }
+}
+
+fn read_sys_value(path: &str) -> Option<u64> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+/// Parse `/proc/sys/fs/file-nr` ("allocated unused max") into (allocated, max).
+///
+/// Kernels since 2.6 always report 0 unused, so allocated is the number of open handles.
+pub fn parse_file_nr(text: &str) -> Option<(u64, u64)> {
+    let fields: Vec<u64> = text.split_whitespace().map(|field| field.parse().ok()).collect::<Option<_>>()?;
+    match fields.as_slice() {
+        [allocated, _unused, max] => Some((*allocated, *max)),
+        _ => None,
+    }
+}
+
+/// Total number of tasks, the denominator of the fourth field of `/proc/loadavg`
+/// ("0.52 0.58 0.59 2/1234 5678")
+pub fn parse_loadavg_tasks(text: &str) -> Option<u64> {
+    text.split_whitespace().nth(3)?.split_once('/')?.1.parse().ok()
+}
+
+/// `value` as a percentage of `limit`, or `None` if the limit is unknown or zero
+pub fn percent_of_limit(value: u64, limit: Option<u64>) -> Option<f32> {
+    limit.filter(|limit| *limit > 0).map(|limit| (value as f64 / limit as f64 * 100.0) as f32)
+}
+
+pub fn limit_level(percent: Option<f32>) -> LimitLevel {
+    match percent {
+        Some(percent) if percent >= CRITICAL_PERCENT => LimitLevel::Critical,
+        Some(percent) if percent >= WARNING_PERCENT => LimitLevel::Warning,
+        _ => LimitLevel::Normal,
+    }
+}
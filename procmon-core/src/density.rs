@@ -0,0 +1,88 @@
+use anyhow::{bail, Result};
+
+/// Environment variable holding the preferred process list density
+pub const DENSITY_ENV: &str = "PROCMON_DENSITY";
+
+/// Lines a TUI process table spends on its block borders (2) and its totals footer with the
+/// blank line above it (2)
+const TABLE_CHROME_LINES: u16 = 4;
+
+/// How tightly process list rows are packed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Density {
+    /// No spacing anywhere, as many rows as fit
+    Compact,
+    #[default]
+    Normal,
+    /// A blank line between rows
+    Comfortable,
+}
+
+impl Density {
+    pub const ALL: [Density; 3] = [Density::Compact, Density::Normal, Density::Comfortable];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Density::Compact => "compact",
+            Density::Normal => "normal",
+            Density::Comfortable => "comfortable",
+        }
+    }
+
+    /// Look up a density by name, ignoring case
+    pub fn from_name(name: &str) -> Result<Density> {
+        let lower = name.trim().to_lowercase();
+        match Self::ALL.iter().find(|density| density.name() == lower) {
+            Some(density) => Ok(*density),
+            None => bail!("Unknown density '{}' (expected compact, normal or comfortable)", name),
+        }
+    }
+
+    /// Blank lines under the TUI table header
+    pub fn header_margin(&self) -> u16 {
+        match self {
+            Density::Compact => 0,
+            Density::Normal | Density::Comfortable => 1,
+        }
+    }
+
+    /// Blank lines under each TUI table row
+    pub fn row_margin(&self) -> u16 {
+        match self {
+            Density::Compact | Density::Normal => 0,
+            Density::Comfortable => 1,
+        }
+    }
+
+    /// Vertical gap between GUI rows in points; `Normal` is egui's default item spacing
+    pub fn row_spacing(&self) -> f32 {
+        match self {
+            Density::Compact => 0.0,
+            Density::Normal => 3.0,
+            Density::Comfortable => 8.0,
+        }
+    }
+
+    /// Lines from the top of a TUI process table to its first row: border, header, margin
+    pub fn first_row_offset(&self) -> u16 {
+        2 + self.header_margin()
+    }
+
+    /// Process rows that fit in a TUI process table `height` lines tall
+    pub fn visible_rows(&self, height: u16) -> usize {
+        let available = height.saturating_sub(TABLE_CHROME_LINES + 1 + self.header_margin());
+        (available / (1 + self.row_margin())) as usize
+    }
+}
+
+/// Pick the process list density from `--density <name>` / `--density=<name>` in `args`,
+/// falling back to the `preference` (normally `PROCMON_DENSITY`) and then normal.
+pub fn density<I>(args: I, preference: Option<&str>) -> Result<Density>
+where
+    I: IntoIterator<Item = String>,
+{
+    match crate::util::flag_value(args, "--density")?.as_deref().or(preference) {
+        Some(name) => Density::from_name(name),
+        None => Ok(Density::Normal),
+    }
+}
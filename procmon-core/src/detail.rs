@@ -0,0 +1,193 @@
+use crate::cores::parse_last_cpu;
+use crate::faults::parse_page_faults;
+use crate::fds::list_open_fds;
+use crate::limits::{limit_rows, read_process_limits, LimitRow};
+use crate::monitor::{parse_status_id, parse_vm_swap};
+use crate::process::{ProcessSnapshot, ProcessStatus};
+use crate::search::{can_read_environ, read_environ};
+use crate::util::{stat_field, Capped};
+
+/// Command line flag that adds the raw `/proc/{pid}/stat` and `status` fields to the detail view
+pub const DEBUG_FLAG: &str = "--debug";
+
+/// Open files and threads listed per process unless the config says otherwise; a busy server
+/// can hold 100k sockets
+pub const DEFAULT_DETAIL_ENTRIES: usize = 500;
+
+/// `/proc/{pid}/stat` fields procmon reads, by 1-based field number
+const STAT_FIELDS: [(usize, &str); 7] = [
+    (3, "state"),
+    (4, "ppid"),
+    (10, "minflt"),
+    (12, "majflt"),
+    (19, "nice"),
+    (20, "num_threads"),
+    (39, "processor"),
+];
+
+/// `/proc/{pid}/status` lines procmon reads
+const STATUS_KEYS: [&str; 3] = ["Uid:", "Gid:", "VmSwap:"];
+
+/// Command line and environment of one process for the detail view, read when it opens
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcessDetail {
+    pub pid: u32,
+    pub name: String,
+    pub command_line: String,
+    /// `None` when `/proc/{pid}/environ` belongs to another user or can't be read
+    pub environment: Option<Vec<String>>,
+    pub minor_faults: u64,
+    pub major_faults: u64,
+    /// Major faults since the previous refresh
+    pub major_faults_delta: u64,
+    /// Resource limits with current usage, for those procmon can measure
+    pub limits: Vec<LimitRow>,
+    /// Open descriptors as `(fd, target)`, the first of them if there are many
+    pub open_files: Capped<(u32, String)>,
+    /// Raw `/proc` fields next to what procmon made of them; only filled in with `--debug`
+    pub raw_fields: Vec<RawField>,
+}
+
+/// One field as read from `/proc/{pid}/stat` or `status`, and its parsed value ("?" when the
+/// parser found nothing)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawField {
+    /// `stat` or `status`
+    pub source: &'static str,
+    pub name: String,
+    pub raw: String,
+    pub parsed: String,
+}
+
+/// Raw values of the `stat` fields procmon reads, as `(field number, name, value)`
+pub fn raw_stat_fields(stat: &str) -> Vec<(usize, &'static str, &str)> {
+    STAT_FIELDS
+        .iter()
+        .filter_map(|(field, name)| Some((*field, *name, stat_field(stat, *field)?)))
+        .collect()
+}
+
+/// Raw values of the `status` lines procmon reads, as `(key, value)` with the value trimmed
+pub fn raw_status_fields(status: &str) -> Vec<(&'static str, &str)> {
+    STATUS_KEYS
+        .iter()
+        .filter_map(|key| {
+            let line = status.lines().find(|line| line.starts_with(key))?;
+            Some((*key, line[key.len()..].trim()))
+        })
+        .collect()
+}
+
+/// Pair the raw `stat` and `status` contents with what the monitor's parsers make of the same
+/// text, so a mismatch points at a parsing bug rather than at the process having changed
+/// since the last refresh
+pub fn raw_proc_fields(stat: &str, status: &str) -> Vec<RawField> {
+    let faults = parse_page_faults(stat);
+    let mut fields: Vec<RawField> = raw_stat_fields(stat)
+        .into_iter()
+        .map(|(field, name, raw)| {
+            let number = raw.parse::<i64>().ok();
+            let parsed = match name {
+                "state" => raw.chars().next().map(|state| format!("{:?}", ProcessStatus::from_stat_char(state))),
+                "minflt" => faults.map(|(minor, _)| minor.to_string()),
+                "majflt" => faults.map(|(_, major)| major.to_string()),
+                "processor" => parse_last_cpu(stat).map(|cpu| cpu.to_string()),
+                _ => number.map(|number| number.to_string()),
+            };
+            let parsed = parsed.unwrap_or_else(|| "?".to_string());
+            RawField { source: "stat", name: format!("{} ({})", name, field), raw: raw.to_string(), parsed }
+        })
+        .collect();
+
+    fields.extend(raw_status_fields(status).into_iter().map(|(key, raw)| {
+        let parsed = match key {
+            "VmSwap:" => parse_vm_swap(status).map(|swap| format!("{} bytes", swap)),
+            _ => parse_status_id(status, key).map(|id| id.to_string()),
+        };
+        let parsed = parsed.unwrap_or_else(|| "?".to_string());
+        RawField { source: "status", name: key.trim_end_matches(':').to_string(), raw: raw.to_string(), parsed }
+    }));
+    fields
+}
+
+impl ProcessDetail {
+    /// Read the detail of `snapshot`'s process, listing at most `max_entries` open files.
+    /// `user_threads` is the owner's thread count from [`crate::limits::user_thread_count`].
+    /// Slow for processes with many descriptors, so the frontends call it off the UI thread.
+    pub fn load(snapshot: &ProcessSnapshot, user_threads: u64, max_entries: usize) -> Self {
+        let info = &snapshot.info;
+        let euid = unsafe { libc::geteuid() };
+        let environment = if can_read_environ(info, euid) { read_environ(info.pid) } else { None };
+        let open_files = list_open_fds(info.pid, max_entries);
+        // Another user's descriptors can't be listed, which doesn't mean there are none
+        let open_fds = (open_files.total > 0).then_some(open_files.total);
+        Self {
+            pid: info.pid,
+            name: info.name.clone(),
+            command_line: info.display_name(true),
+            environment,
+            minor_faults: snapshot.stats.minor_faults,
+            major_faults: snapshot.stats.major_faults,
+            major_faults_delta: snapshot.stats.major_faults_delta,
+            limits: limit_rows(read_process_limits(info.pid), snapshot, open_fds, user_threads),
+            open_files,
+            raw_fields: Vec::new(),
+        }
+    }
+
+    /// Read `/proc/{pid}/stat` and `status` for the raw field table
+    pub fn load_raw_fields(&mut self) {
+        let read = |file: &str| std::fs::read_to_string(format!("/proc/{}/{}", self.pid, file)).unwrap_or_default();
+        self.raw_fields = raw_proc_fields(&read("stat"), &read("status"));
+    }
+
+    /// Command line and environment as copied by "copy full", one environment entry per line
+    pub fn full_text(&self) -> String {
+        let mut text = self.command_line.clone();
+        for entry in self.environment.iter().flatten() {
+            text.push('\n');
+            text.push_str(entry);
+        }
+        text
+    }
+}
+
+/// Wrap `text` into lines of at most `width` characters, breaking at spaces where possible.
+/// Words longer than a line (a classpath, a base64 blob) are split between characters, never
+/// inside a multi-byte one.
+pub fn wrap_text(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    let mut line_len = 0;
+
+    for word in text.split(' ') {
+        let word_len = word.chars().count();
+        // A leading space is needed unless the line is empty
+        if line_len > 0 && line_len + 1 + word_len <= width {
+            line.push(' ');
+            line.push_str(word);
+            line_len += 1 + word_len;
+            continue;
+        }
+        if line_len > 0 {
+            lines.push(std::mem::take(&mut line));
+            line_len = 0;
+        }
+
+        let mut chars = word.chars().peekable();
+        while chars.peek().is_some() {
+            let chunk: String = chars.by_ref().take(width).collect();
+            line_len = chunk.chars().count();
+            line = chunk;
+            if chars.peek().is_some() {
+                lines.push(std::mem::take(&mut line));
+                line_len = 0;
+            }
+        }
+    }
+    if line_len > 0 || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
@@ -1,6 +1,15 @@
-use crate::process::ProcessSnapshot;
+use crate::exehash::{hash_listed, ExeHashCache};
+use crate::fds::{count_open_fds, read_soft_open_files_limit, too_many_open_files};
+use crate::metrics::SystemMetrics;
+use crate::process::{ProcessKey, ProcessSnapshot, ProcessStatus};
+use crate::respawn::RespawnTracker;
+use crate::script::ScriptRunner;
+use crate::zombie::zombie_counts_by_parent;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::Path;
+use std::time::{Duration, Instant};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MisbehaviorRule {
@@ -20,6 +29,55 @@ pub enum MisbehaviorCondition {
     TooManyThreads { threshold: u32 },
     ZombieProcess,
     HighDiskWrites { threshold_bytes_per_sec: u64, duration_secs: u64 },
+    /// Read rate between the last two refreshes above the threshold, e.g. a process scanning the disk
+    HighDiskReads { threshold_bytes_per_sec: u64, duration_secs: u64 },
+    /// Evaluated by `check_system`: a parent creating direct children faster than the threshold
+    ChildSpawnRate { children_per_sec: f32, window_secs: u64 },
+    /// Evaluated by `check_system`: a parent whose zombie children grew to at least `min_zombies`
+    ZombiesAccumulating { min_zombies: usize },
+    /// Evaluated by `check_system`: the same program (name and executable) started again by the
+    /// same parent after dying young more than `max_respawns` times within `window_secs`, i.e. a
+    /// crash loop (see [`RespawnTracker`])
+    CrashLoop { max_respawns: usize, window_secs: u64 },
+    /// Evaluated by `check_system` against the whole system
+    System(SystemCondition),
+    /// Executable lives in a world-writable scratch location or has been deleted
+    SuspiciousExePath,
+    /// Process name does not match the basename of its executable
+    NameExeMismatch,
+    /// Open descriptors in `/proc/{pid}/fd` above the threshold or close to the process's soft limit
+    TooManyOpenFiles { threshold: usize },
+    /// Continuously in uninterruptible (D) sleep for at least `duration_secs`, often a hung mount
+    StuckInDiskSleep { duration_secs: u64 },
+    /// A process matching `name_pattern` has been running for more than `seconds`, e.g. a hung
+    /// cron job or deploy script. See [`process_matches_pattern`] for the pattern syntax.
+    RunTimeExceeds { seconds: u64, name_pattern: String },
+    /// External script given the snapshot as JSON on stdin; a nonzero exit or any output is a
+    /// violation. Runs in the background with a timeout, see [`crate::script`].
+    Script { path: String },
+    /// SHA-256 of `/proc/{pid}/exe` is one of `hashes` (hex, any case), e.g. a list of known
+    /// malware. Hashes are cached per executable path and computed in the background, so a new
+    /// executable is matched from a later refresh on, see [`crate::exehash`].
+    ExeHashMatches { hashes: Vec<String> },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum SystemCondition {
+    TotalProcessesAbove { threshold: usize },
+    TotalMemoryPressure { threshold_percent: f32 },
+    PerUserCpuAbove { threshold: f32 },
+}
+
+impl fmt::Display for SystemCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SystemCondition::TotalProcessesAbove { threshold } => write!(f, "More than {} processes", threshold),
+            SystemCondition::TotalMemoryPressure { threshold_percent } => {
+                write!(f, "System memory above {:.1}% used", threshold_percent)
+            }
+            SystemCondition::PerUserCpuAbove { threshold } => write!(f, "A user's processes above {:.1}% CPU", threshold),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
@@ -42,7 +100,100 @@ pub struct MisbehaviorAlert {
 
 pub struct MisbehaviorDetector {
     rules: Vec<MisbehaviorRule>,
-    violation_history: HashMap<u32, Vec<ViolationRecord>>,
+    violation_history: HashMap<ProcessKey, Vec<ViolationRecord>>,
+    // Process last seen under each PID, to drop per-PID state when the PID is reused
+    process_keys: HashMap<u32, ProcessKey>,
+    spawn_history: HashMap<u32, SpawnRecord>,
+    fd_samples: HashMap<u32, FdSample>,
+    // Zombie children per parent PID at the previous `check_system`
+    zombie_counts: HashMap<u32, usize>,
+    scripts: ScriptRunner,
+    // When each process was first seen in its current run of D-state samples
+    disk_sleep_since: HashMap<u32, Instant>,
+    rate_limiter: AlertRateLimiter,
+    /// Alert on procmon's own processes too (`ProcessInfo::is_own`); off by default
+    own_alerts: bool,
+    exe_hashes: ExeHashCache,
+    respawns: RespawnTracker,
+    /// Name globs of processes that never raise alerts
+    allowed: Vec<String>,
+}
+
+/// Rule name of the summary entry that stands in for alerts over the rate cap
+pub const SUPPRESSED_RULE_NAME: &str = "Alerts Suppressed";
+
+/// Hard ceiling on alerts per second, so a system-wide problem tripping a rule for hundreds
+/// of processes at once cannot flood the alert list.
+#[derive(Debug, Clone)]
+pub struct AlertRateLimiter {
+    max_per_sec: Option<usize>,
+    window_start: Option<Instant>,
+    emitted: usize,
+}
+
+impl AlertRateLimiter {
+    pub const DEFAULT_MAX_PER_SEC: usize = 20;
+
+    /// `None` disables the cap
+    pub fn new(max_per_sec: Option<usize>) -> Self {
+        Self { max_per_sec, window_start: None, emitted: 0 }
+    }
+
+    pub fn set_max_per_sec(&mut self, max_per_sec: Option<usize>) {
+        self.max_per_sec = max_per_sec;
+    }
+
+    /// Pass through what fits in the current one-second window, most severe first, and replace
+    /// the rest with a single summary alert.
+    pub fn limit(&mut self, mut alerts: Vec<MisbehaviorAlert>, now: Instant) -> Vec<MisbehaviorAlert> {
+        let Some(max) = self.max_per_sec else {
+            return alerts;
+        };
+
+        if self.window_start.is_none_or(|start| now.duration_since(start) >= Duration::from_secs(1)) {
+            self.window_start = Some(now);
+            self.emitted = 0;
+        }
+
+        let budget = max.saturating_sub(self.emitted);
+        if alerts.len() <= budget {
+            self.emitted += alerts.len();
+            return alerts;
+        }
+
+        // Stable, so alerts of equal severity keep their order
+        alerts.sort_by_key(|alert| std::cmp::Reverse(alert.severity));
+        let suppressed = alerts.split_off(budget);
+        self.emitted += budget;
+
+        alerts.push(MisbehaviorAlert {
+            pid: 0,
+            process_name: "system".to_string(),
+            rule_name: SUPPRESSED_RULE_NAME.to_string(),
+            description: format!("More than {} alerts per second", max),
+            severity: suppressed.iter().map(|alert| alert.severity).max().unwrap_or(Severity::Info),
+            timestamp: chrono::Utc::now(),
+            details: format!("{} additional alerts suppressed", suppressed.len()),
+        });
+        alerts
+    }
+}
+
+impl Default for AlertRateLimiter {
+    fn default() -> Self {
+        Self::new(Some(Self::DEFAULT_MAX_PER_SEC))
+    }
+}
+
+/// Counting `/proc/{pid}/fd` for every process on every refresh is expensive, so
+/// descriptor counts are re-sampled at most this often per process
+const FD_SAMPLE_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone)]
+struct FdSample {
+    open: usize,
+    soft_limit: Option<u64>,
+    sampled_at: Instant,
 }
 
 #[derive(Debug, Clone)]
@@ -51,11 +202,28 @@ struct ViolationRecord {
     timestamp: chrono::DateTime<chrono::Utc>,
 }
 
+#[derive(Debug, Clone, Default)]
+struct SpawnRecord {
+    children: HashSet<u32>,
+    spawn_times: Vec<chrono::DateTime<chrono::Utc>>,
+}
+
 impl MisbehaviorDetector {
     pub fn new() -> Self {
         Self {
             rules: Self::default_rules(),
             violation_history: HashMap::new(),
+            process_keys: HashMap::new(),
+            spawn_history: HashMap::new(),
+            fd_samples: HashMap::new(),
+            zombie_counts: HashMap::new(),
+            scripts: ScriptRunner::default(),
+            disk_sleep_since: HashMap::new(),
+            rate_limiter: AlertRateLimiter::default(),
+            own_alerts: false,
+            exe_hashes: ExeHashCache::default(),
+            respawns: RespawnTracker::default(),
+            allowed: Vec::new(),
         }
     }
 
@@ -63,6 +231,17 @@ impl MisbehaviorDetector {
         Self {
             rules,
             violation_history: HashMap::new(),
+            process_keys: HashMap::new(),
+            spawn_history: HashMap::new(),
+            fd_samples: HashMap::new(),
+            zombie_counts: HashMap::new(),
+            scripts: ScriptRunner::default(),
+            disk_sleep_since: HashMap::new(),
+            rate_limiter: AlertRateLimiter::default(),
+            own_alerts: false,
+            exe_hashes: ExeHashCache::default(),
+            respawns: RespawnTracker::default(),
+            allowed: Vec::new(),
         }
     }
 
@@ -110,6 +289,18 @@ impl MisbehaviorDetector {
                 condition: MisbehaviorCondition::ZombieProcess,
                 severity: Severity::Warning,
             },
+            MisbehaviorRule {
+                name: "Zombies Accumulating".to_string(),
+                description: "Parent process is not reaping its exited children".to_string(),
+                condition: MisbehaviorCondition::ZombiesAccumulating { min_zombies: 3 },
+                severity: Severity::Warning,
+            },
+            MisbehaviorRule {
+                name: "Stuck in Disk Sleep".to_string(),
+                description: "Process blocked in uninterruptible sleep, often on a hung mount".to_string(),
+                condition: MisbehaviorCondition::StuckInDiskSleep { duration_secs: 30 },
+                severity: Severity::Warning,
+            },
             MisbehaviorRule {
                 name: "High Disk I/O".to_string(),
                 description: "Process performing excessive disk operations".to_string(),
@@ -119,15 +310,98 @@ impl MisbehaviorDetector {
                 },
                 severity: Severity::Warning,
             },
+            MisbehaviorRule {
+                name: "High Disk Reads".to_string(),
+                description: "Process reading from disk at a thrashing rate".to_string(),
+                condition: MisbehaviorCondition::HighDiskReads {
+                    threshold_bytes_per_sec: 200 * 1024 * 1024, // 200 MB/s
+                    duration_secs: 30,
+                },
+                severity: Severity::Warning,
+            },
+            MisbehaviorRule {
+                name: "Rapid Process Spawning".to_string(),
+                description: "Process creating child processes at a fork-bomb rate".to_string(),
+                condition: MisbehaviorCondition::ChildSpawnRate {
+                    children_per_sec: 20.0,
+                    window_secs: 10,
+                },
+                severity: Severity::Critical,
+            },
+            MisbehaviorRule {
+                name: "Crash Loop".to_string(),
+                description: "Program keeps exiting and being started again".to_string(),
+                condition: MisbehaviorCondition::CrashLoop { max_respawns: 5, window_secs: 60 },
+                severity: Severity::Warning,
+            },
+            MisbehaviorRule {
+                name: "Suspicious Executable".to_string(),
+                description: "Process running from /tmp, /dev/shm or a deleted binary".to_string(),
+                condition: MisbehaviorCondition::SuspiciousExePath,
+                severity: Severity::Warning,
+            },
+            MisbehaviorRule {
+                name: "Name/Executable Mismatch".to_string(),
+                description: "Process name differs from its executable, possible masquerading".to_string(),
+                condition: MisbehaviorCondition::NameExeMismatch,
+                severity: Severity::Info,
+            },
+            MisbehaviorRule {
+                name: "Too Many Open Files".to_string(),
+                description: "Process holding an unusually large number of file descriptors".to_string(),
+                condition: MisbehaviorCondition::TooManyOpenFiles { threshold: 4096 },
+                severity: Severity::Warning,
+            },
+            MisbehaviorRule {
+                name: "Memory Pressure".to_string(),
+                description: "System memory almost exhausted".to_string(),
+                condition: MisbehaviorCondition::System(SystemCondition::TotalMemoryPressure {
+                    threshold_percent: 95.0,
+                }),
+                severity: Severity::Critical,
+            },
         ]
     }
 
+    /// Whether procmon's own processes and the commands it runs can raise alerts
+    pub fn set_own_alerts(&mut self, enabled: bool) {
+        self.own_alerts = enabled;
+    }
+
+    /// Change the alert rate cap; `None` disables it
+    pub fn set_max_alerts_per_sec(&mut self, max_per_sec: Option<usize>) {
+        self.rate_limiter.set_max_per_sec(max_per_sec);
+    }
+
+    /// Apply the alert rate cap to the alerts of one refresh, see [`AlertRateLimiter`]
+    pub fn limit_rate(&mut self, alerts: Vec<MisbehaviorAlert>) -> Vec<MisbehaviorAlert> {
+        self.rate_limiter.limit(alerts, Instant::now())
+    }
+
+    /// Replace all rules, e.g. with the ones from the configured rules file
+    pub fn set_rules(&mut self, rules: Vec<MisbehaviorRule>) {
+        self.rules = rules;
+    }
+
+    /// Never alert on processes whose name matches one of the `patterns` (globs)
+    pub fn set_allowed(&mut self, patterns: Vec<String>) {
+        self.allowed = patterns;
+    }
+
+    fn is_allowed(&self, name: &str) -> bool {
+        self.allowed.iter().any(|pattern| glob_matches(pattern, name))
+    }
+
     pub fn add_rule(&mut self, rule: MisbehaviorRule) {
         self.rules.push(rule);
     }
 
     pub fn check_process(&mut self, snapshot: &ProcessSnapshot) -> Vec<MisbehaviorAlert> {
+        self.track_identity(snapshot.key());
         let mut alerts = Vec::new();
+        if (snapshot.info.is_own && !self.own_alerts) || self.is_allowed(&snapshot.info.name) {
+            return alerts;
+        }
         let rules = self.rules.clone();
 
         for rule in &rules {
@@ -149,88 +423,381 @@ impl MisbehaviorDetector {
         alerts
     }
 
-    fn check_rule(&mut self, snapshot: &ProcessSnapshot, rule: &MisbehaviorRule) -> bool {
-        match &rule.condition {
-            MisbehaviorCondition::CpuUsageAbove { threshold, duration_secs } => {
-                if snapshot.stats.cpu_usage > *threshold {
-                    self.record_violation(snapshot.info.pid, &rule.name, *duration_secs)
-                } else {
-                    false
+    /// Check rules that need the whole process table rather than a single process.
+    ///
+    /// Call once per refresh with the complete snapshot list.
+    pub fn check_system(&mut self, snapshots: &[ProcessSnapshot], metrics: &SystemMetrics) -> Vec<MisbehaviorAlert> {
+        let now = chrono::Utc::now();
+        self.update_spawn_history(snapshots, now);
+        self.update_respawns(snapshots, now);
+        let zombie_counts = zombie_counts_by_parent(snapshots);
+
+        let mut alerts = Vec::new();
+        let rules = self.rules.clone();
+
+        for rule in &rules {
+            match &rule.condition {
+                MisbehaviorCondition::ChildSpawnRate { children_per_sec, window_secs } => {
+                    let cutoff = now - chrono::Duration::seconds(*window_secs as i64);
+
+                    for snapshot in snapshots {
+                        let Some(record) = self.spawn_history.get(&snapshot.info.pid) else {
+                            continue;
+                        };
+
+                        let spawned = record.spawn_times.iter().filter(|t| **t > cutoff).count();
+                        let rate = spawned as f32 / (*window_secs).max(1) as f32;
+
+                        if rate > *children_per_sec {
+                            alerts.push(Self::system_alert(
+                                rule,
+                                snapshot.info.pid,
+                                &snapshot.info.name,
+                                format!(
+                                    "Spawned {} children in {}s: {:.1}/s (threshold: {:.1}/s)",
+                                    spawned, window_secs, rate, children_per_sec
+                                ),
+                            ));
+                        }
+                    }
+                }
+                MisbehaviorCondition::ZombiesAccumulating { min_zombies } => {
+                    let mut grown: Vec<(u32, usize, usize)> = zombie_counts
+                        .iter()
+                        .map(|(parent, count)| (*parent, *count, self.zombie_counts.get(parent).copied().unwrap_or(0)))
+                        .filter(|(_, count, previous)| count >= min_zombies && count > previous)
+                        .collect();
+                    grown.sort();
+
+                    for (parent, count, previous) in grown {
+                        let name = snapshots
+                            .iter()
+                            .find(|s| s.info.pid == parent)
+                            .map_or_else(|| format!("PID {}", parent), |s| s.info.name.clone());
+                        alerts.push(Self::system_alert(
+                            rule,
+                            parent,
+                            &name,
+                            format!(
+                                "{} zombie children (was {}); signal PID {} with SIGCHLD to prompt reaping",
+                                count, previous, parent
+                            ),
+                        ));
+                    }
+                }
+                MisbehaviorCondition::CrashLoop { max_respawns, window_secs } => {
+                    let cutoff = now - chrono::Duration::seconds(*window_secs as i64);
+                    for ((name, exe), count) in self.respawns.crash_loops(*max_respawns, cutoff, now) {
+                        // The newest process of the program is the one to look at
+                        let pid = snapshots
+                            .iter()
+                            .filter(|s| s.info.name == *name && s.info.exe_path == *exe)
+                            .map(|s| s.info.pid)
+                            .max()
+                            .unwrap_or(0);
+                        let exe = exe.as_ref().map_or_else(|| name.clone(), |exe| exe.display().to_string());
+                        alerts.push(Self::system_alert(
+                            rule,
+                            pid,
+                            name,
+                            format!(
+                                "{} restarted {} times in {}s (threshold: {}); likely a crash loop",
+                                exe, count, window_secs, max_respawns
+                            ),
+                        ));
+                    }
                 }
+                MisbehaviorCondition::System(condition) => {
+                    alerts.extend(Self::check_system_condition(rule, condition, snapshots, metrics));
+                }
+                _ => {}
             }
-            MisbehaviorCondition::MemoryUsageAbove { threshold_bytes, duration_secs } => {
-                if snapshot.stats.memory_usage > *threshold_bytes {
-                    self.record_violation(snapshot.info.pid, &rule.name, *duration_secs)
+        }
+
+        self.zombie_counts = zombie_counts;
+        if !self.own_alerts {
+            let own: HashSet<u32> = snapshots.iter().filter(|s| s.info.is_own).map(|s| s.info.pid).collect();
+            alerts.retain(|alert| !own.contains(&alert.pid));
+        }
+        alerts.retain(|alert| !self.is_allowed(&alert.process_name));
+        alerts
+    }
+
+    fn check_system_condition(
+        rule: &MisbehaviorRule,
+        condition: &SystemCondition,
+        snapshots: &[ProcessSnapshot],
+        metrics: &SystemMetrics,
+    ) -> Vec<MisbehaviorAlert> {
+        match condition {
+            SystemCondition::TotalProcessesAbove { threshold } => {
+                if snapshots.len() > *threshold {
+                    vec![Self::system_alert(
+                        rule,
+                        0,
+                        "system",
+                        format!("Processes: {} (threshold: {})", snapshots.len(), threshold),
+                    )]
                 } else {
-                    false
+                    Vec::new()
                 }
             }
-            MisbehaviorCondition::MemoryPercentAbove { threshold_percent, duration_secs } => {
-                if snapshot.stats.memory_percent > *threshold_percent {
-                    self.record_violation(snapshot.info.pid, &rule.name, *duration_secs)
+            SystemCondition::TotalMemoryPressure { threshold_percent } => {
+                if metrics.memory.total == 0 {
+                    return Vec::new();
+                }
+                let used_percent = metrics.memory.used as f32 / metrics.memory.total as f32 * 100.0;
+                if used_percent > *threshold_percent {
+                    vec![Self::system_alert(
+                        rule,
+                        0,
+                        "system",
+                        format!("Memory used: {:.1}% (threshold: {:.1}%)", used_percent, threshold_percent),
+                    )]
                 } else {
-                    false
+                    Vec::new()
                 }
             }
+            SystemCondition::PerUserCpuAbove { threshold } => {
+                let mut per_user: HashMap<&str, f32> = HashMap::new();
+                for snapshot in snapshots {
+                    *per_user.entry(snapshot.info.user.as_str()).or_default() += snapshot.stats.cpu_usage;
+                }
+
+                let mut users: Vec<(&str, f32)> = per_user
+                    .into_iter()
+                    .filter(|(_, cpu)| cpu > threshold)
+                    .collect();
+                users.sort_by(|a, b| a.0.cmp(b.0));
+
+                users
+                    .into_iter()
+                    .map(|(user, cpu)| {
+                        Self::system_alert(
+                            rule,
+                            0,
+                            user,
+                            format!("User {} CPU usage: {:.1}% (threshold: {:.1}%)", user, cpu, threshold),
+                        )
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    fn system_alert(rule: &MisbehaviorRule, pid: u32, name: &str, details: String) -> MisbehaviorAlert {
+        MisbehaviorAlert {
+            pid,
+            process_name: name.to_string(),
+            rule_name: rule.name.clone(),
+            description: rule.description.clone(),
+            severity: rule.severity,
+            timestamp: chrono::Utc::now(),
+            details,
+        }
+    }
+
+    fn update_respawns(&mut self, snapshots: &[ProcessSnapshot], now: chrono::DateTime<chrono::Utc>) {
+        let longest_window = self.rules.iter()
+            .filter_map(|r| match r.condition {
+                MisbehaviorCondition::CrashLoop { window_secs, .. } => Some(window_secs),
+                _ => None,
+            })
+            .max()
+            .unwrap_or(0);
+        self.respawns.update(snapshots, now, chrono::Duration::seconds(longest_window as i64));
+    }
+
+    fn update_spawn_history(&mut self, snapshots: &[ProcessSnapshot], now: chrono::DateTime<chrono::Utc>) {
+        let mut children: HashMap<u32, HashSet<u32>> = snapshots
+            .iter()
+            .map(|s| (s.info.pid, HashSet::new()))
+            .collect();
+        for snapshot in snapshots {
+            if let Some(parent) = snapshot.info.parent_pid.and_then(|ppid| children.get_mut(&ppid)) {
+                parent.insert(snapshot.info.pid);
+            }
+        }
+
+        let longest_window = self.rules.iter()
+            .filter_map(|r| match r.condition {
+                MisbehaviorCondition::ChildSpawnRate { window_secs, .. } => Some(window_secs),
+                _ => None,
+            })
+            .max()
+            .unwrap_or(0);
+        let cutoff = now - chrono::Duration::seconds(longest_window as i64);
+
+        for (pid, current) in &children {
+            match self.spawn_history.get_mut(pid) {
+                Some(record) => {
+                    let spawned = current.difference(&record.children).count();
+                    record.spawn_times.extend(std::iter::repeat_n(now, spawned));
+                    record.spawn_times.retain(|t| *t > cutoff);
+                    record.children = current.clone();
+                }
+                None => {
+                    // Children that already exist when a parent is first seen are not spawns
+                    self.spawn_history.insert(*pid, SpawnRecord {
+                        children: current.clone(),
+                        spawn_times: Vec::new(),
+                    });
+                }
+            }
+        }
+
+        self.spawn_history.retain(|pid, _| children.contains_key(pid));
+    }
+
+    fn check_rule(&mut self, snapshot: &ProcessSnapshot, rule: &MisbehaviorRule) -> bool {
+        match &rule.condition {
+            MisbehaviorCondition::CpuUsageAbove { threshold, duration_secs } => {
+                let violating = snapshot.stats.cpu_usage > *threshold;
+                self.record_violation(snapshot.key(), &rule.name, violating, *duration_secs)
+            }
+            MisbehaviorCondition::MemoryUsageAbove { threshold_bytes, duration_secs } => {
+                let violating = snapshot.stats.memory_usage > *threshold_bytes;
+                self.record_violation(snapshot.key(), &rule.name, violating, *duration_secs)
+            }
+            MisbehaviorCondition::MemoryPercentAbove { threshold_percent, duration_secs } => {
+                let violating = snapshot.stats.memory_percent > *threshold_percent;
+                self.record_violation(snapshot.key(), &rule.name, violating, *duration_secs)
+            }
             MisbehaviorCondition::DiskIoAbove { threshold_bytes_per_sec, duration_secs } => {
                 let total_io = snapshot.stats.disk_read_bytes + snapshot.stats.disk_write_bytes;
                 let io_per_sec = total_io / snapshot.stats.run_time.as_secs().max(1);
 
-                if io_per_sec > *threshold_bytes_per_sec {
-                    self.record_violation(snapshot.info.pid, &rule.name, *duration_secs)
-                } else {
-                    false
-                }
+                let violating = io_per_sec > *threshold_bytes_per_sec;
+                self.record_violation(snapshot.key(), &rule.name, violating, *duration_secs)
             }
             MisbehaviorCondition::NetworkIoAbove { threshold_bytes_per_sec, duration_secs } => {
                 let total_net = snapshot.stats.network_rx_bytes + snapshot.stats.network_tx_bytes;
                 let net_per_sec = total_net / snapshot.stats.run_time.as_secs().max(1);
 
-                if net_per_sec > *threshold_bytes_per_sec {
-                    self.record_violation(snapshot.info.pid, &rule.name, *duration_secs)
-                } else {
-                    false
-                }
+                let violating = net_per_sec > *threshold_bytes_per_sec;
+                self.record_violation(snapshot.key(), &rule.name, violating, *duration_secs)
             }
             MisbehaviorCondition::TooManyThreads { threshold } => {
                 snapshot.stats.num_threads > *threshold
             }
             MisbehaviorCondition::ZombieProcess => {
-                matches!(snapshot.info.status, crate::process::ProcessStatus::Zombie)
+                matches!(snapshot.info.status, ProcessStatus::Zombie)
             }
             MisbehaviorCondition::HighDiskWrites { threshold_bytes_per_sec, duration_secs } => {
                 let write_per_sec = snapshot.stats.disk_write_bytes / snapshot.stats.run_time.as_secs().max(1);
 
-                if write_per_sec > *threshold_bytes_per_sec {
-                    self.record_violation(snapshot.info.pid, &rule.name, *duration_secs)
-                } else {
-                    false
+                let violating = write_per_sec > *threshold_bytes_per_sec;
+                self.record_violation(snapshot.key(), &rule.name, violating, *duration_secs)
+            }
+            MisbehaviorCondition::HighDiskReads { threshold_bytes_per_sec, duration_secs } => {
+                let violating = snapshot.stats.disk_read_bytes_per_sec > *threshold_bytes_per_sec;
+                self.record_violation(snapshot.key(), &rule.name, violating, *duration_secs)
+            }
+            MisbehaviorCondition::SuspiciousExePath => suspicious_exe_reason(&snapshot.info).is_some(),
+            MisbehaviorCondition::NameExeMismatch => name_exe_mismatch(&snapshot.info).is_some(),
+            MisbehaviorCondition::TooManyOpenFiles { threshold } => {
+                match self.fd_sample(snapshot.info.pid) {
+                    Some(sample) => too_many_open_files(sample.open, *threshold, sample.soft_limit),
+                    None => false,
                 }
             }
+            MisbehaviorCondition::Script { path } => self.scripts.poll(path, snapshot).is_some(),
+            MisbehaviorCondition::ExeHashMatches { hashes } => self
+                .exe_hash(snapshot)
+                .is_some_and(|hash| hash_listed(&hash, hashes)),
+            MisbehaviorCondition::StuckInDiskSleep { duration_secs } => self
+                .disk_sleep_duration(snapshot)
+                .is_some_and(|stuck| stuck >= Duration::from_secs(*duration_secs)),
+            MisbehaviorCondition::RunTimeExceeds { seconds, name_pattern } => {
+                snapshot.stats.run_time > Duration::from_secs(*seconds)
+                    && process_matches_pattern(&snapshot.info, name_pattern)
+            }
+            // Need the full process table, see check_system
+            MisbehaviorCondition::ChildSpawnRate { .. }
+            | MisbehaviorCondition::ZombiesAccumulating { .. }
+            | MisbehaviorCondition::CrashLoop { .. }
+            | MisbehaviorCondition::System(_) => false,
         }
     }
 
-    fn record_violation(&mut self, pid: u32, rule_name: &str, duration_secs: u64) -> bool {
-        let now = chrono::Utc::now();
-        let history = self.violation_history.entry(pid).or_insert_with(Vec::new);
+    /// SHA-256 of the process's executable, read through `/proc/{pid}/exe` so deleted and
+    /// replaced binaries still hash what is actually running
+    fn exe_hash(&mut self, snapshot: &ProcessSnapshot) -> Option<String> {
+        let key = snapshot.info.exe_path.as_ref()?;
+        let exe = format!("/proc/{}/exe", snapshot.info.pid);
+        self.exe_hashes.hash(key, Path::new(&exe))
+    }
 
-        // Add new violation
-        history.push(ViolationRecord {
-            rule_name: rule_name.to_string(),
-            timestamp: now,
-        });
+    /// Descriptor count for `pid`, re-read only when the cached sample is older than
+    /// `FD_SAMPLE_INTERVAL`
+    fn fd_sample(&mut self, pid: u32) -> Option<&FdSample> {
+        let now = Instant::now();
+        let fresh = self.fd_samples
+            .get(&pid)
+            .is_some_and(|sample| now.duration_since(sample.sampled_at) < FD_SAMPLE_INTERVAL);
 
-        // Clean up old violations
-        let cutoff = now - chrono::Duration::seconds(duration_secs as i64);
-        history.retain(|v| v.timestamp > cutoff && v.rule_name == rule_name);
+        if !fresh {
+            match count_open_fds(pid) {
+                Some(open) => {
+                    self.fd_samples.insert(pid, FdSample {
+                        open,
+                        soft_limit: read_soft_open_files_limit(pid),
+                        sampled_at: now,
+                    });
+                }
+                None => {
+                    self.fd_samples.remove(&pid);
+                }
+            }
+        }
 
-        // Check if violation has persisted for the required duration
-        if let Some(first) = history.first() {
-            let violation_duration = (now - first.timestamp).num_seconds() as u64;
-            violation_duration >= duration_secs
-        } else {
-            false
+        self.fd_samples.get(&pid)
+    }
+
+    /// How long `snapshot` has been in D state without interruption, or `None` if it isn't
+    fn disk_sleep_duration(&mut self, snapshot: &ProcessSnapshot) -> Option<Duration> {
+        if snapshot.info.status != ProcessStatus::DiskSleep {
+            self.disk_sleep_since.remove(&snapshot.info.pid);
+            return None;
+        }
+        let since = self.disk_sleep_since.entry(snapshot.info.pid).or_insert_with(Instant::now);
+        Some(since.elapsed())
+    }
+
+    /// Forget what was tracked for an earlier process that had `key`'s PID, so a reused PID
+    /// doesn't inherit its violations or spawn counts
+    fn track_identity(&mut self, key: ProcessKey) {
+        let Some(previous) = self.process_keys.insert(key.pid, key) else {
+            return;
+        };
+        if previous != key {
+            self.violation_history.remove(&previous);
+            self.spawn_history.remove(&key.pid);
+            self.fd_samples.remove(&key.pid);
+            self.disk_sleep_since.remove(&key.pid);
+        }
+    }
+
+    /// Track a condition that has to hold for `duration_secs` before it counts as a violation.
+    ///
+    /// The first record for a rule marks when the current run of violations started; it is
+    /// dropped as soon as the condition stops holding.
+    fn record_violation(&mut self, key: ProcessKey, rule_name: &str, violating: bool, duration_secs: u64) -> bool {
+        let now = chrono::Utc::now();
+        let history = self.violation_history.entry(key).or_default();
+
+        if !violating {
+            history.retain(|v| v.rule_name != rule_name);
+            return false;
         }
+        let since = match history.iter().find(|v| v.rule_name == rule_name) {
+            Some(record) => record.timestamp,
+            None => {
+                history.push(ViolationRecord { rule_name: rule_name.to_string(), timestamp: now });
+                now
+            }
+        };
+        (now - since).num_seconds() >= duration_secs as i64
     }
 
     fn get_violation_details(&self, snapshot: &ProcessSnapshot, condition: &MisbehaviorCondition) -> String {
@@ -280,11 +847,76 @@ impl MisbehaviorDetector {
                     *threshold_bytes_per_sec as f64 / (1024.0 * 1024.0)
                 )
             }
+            MisbehaviorCondition::HighDiskReads { threshold_bytes_per_sec, .. } => {
+                format!(
+                    "Disk reads: {:.2} MB/s (threshold: {:.2} MB/s)",
+                    snapshot.stats.disk_read_bytes_per_sec as f64 / (1024.0 * 1024.0),
+                    *threshold_bytes_per_sec as f64 / (1024.0 * 1024.0)
+                )
+            }
+            MisbehaviorCondition::ChildSpawnRate { children_per_sec, window_secs } => {
+                format!("Child spawn rate above {:.1}/s over {}s", children_per_sec, window_secs)
+            }
+            MisbehaviorCondition::ZombiesAccumulating { min_zombies } => {
+                format!("At least {} zombie children", min_zombies)
+            }
+            MisbehaviorCondition::CrashLoop { max_respawns, window_secs } => {
+                format!("Restarted more than {} times in {}s", max_respawns, window_secs)
+            }
+            MisbehaviorCondition::Script { path } => {
+                self.scripts.violation(snapshot.info.pid, path).unwrap_or_default().to_string()
+            }
+            MisbehaviorCondition::ExeHashMatches { .. } => {
+                let exe = snapshot.info.exe_path.as_deref();
+                format!(
+                    "Executable {} matches known hash {}",
+                    exe.map_or("?".into(), |exe| exe.display().to_string()),
+                    exe.and_then(|exe| self.exe_hashes.cached(exe)).unwrap_or("?")
+                )
+            }
+            MisbehaviorCondition::StuckInDiskSleep { duration_secs } => {
+                let stuck = self.disk_sleep_since.get(&snapshot.info.pid).map_or(0, |since| since.elapsed().as_secs());
+                format!(
+                    "In uninterruptible sleep (D) for {}s (threshold: {}s); check for a hung mount or failing disk",
+                    stuck, duration_secs
+                )
+            }
+            MisbehaviorCondition::RunTimeExceeds { seconds, name_pattern } => {
+                format!(
+                    "Running for {} (limit: {}, pattern: {})",
+                    crate::util::format_duration(snapshot.stats.run_time),
+                    crate::util::format_duration(Duration::from_secs(*seconds)),
+                    name_pattern
+                )
+            }
+            MisbehaviorCondition::System(condition) => {
+                format!("System condition: {}", condition)
+            }
+            MisbehaviorCondition::SuspiciousExePath => {
+                suspicious_exe_reason(&snapshot.info).unwrap_or_default()
+            }
+            MisbehaviorCondition::NameExeMismatch => {
+                name_exe_mismatch(&snapshot.info).unwrap_or_default()
+            }
+            MisbehaviorCondition::TooManyOpenFiles { threshold } => {
+                match self.fd_samples.get(&snapshot.info.pid) {
+                    Some(FdSample { open, soft_limit: Some(limit), .. }) => {
+                        format!("Open files: {} (threshold: {}, soft limit: {})", open, threshold, limit)
+                    }
+                    Some(sample) => format!("Open files: {} (threshold: {})", sample.open, threshold),
+                    None => format!("Open files above threshold {}", threshold),
+                }
+            }
         }
     }
 
     pub fn cleanup_dead_processes(&mut self, active_pids: &[u32]) {
-        self.violation_history.retain(|pid, _| active_pids.contains(pid));
+        self.violation_history.retain(|key, _| active_pids.contains(&key.pid));
+        self.process_keys.retain(|pid, _| active_pids.contains(pid));
+        self.spawn_history.retain(|pid, _| active_pids.contains(pid));
+        self.fd_samples.retain(|pid, _| active_pids.contains(pid));
+        self.scripts.retain_pids(active_pids);
+        self.disk_sleep_since.retain(|pid, _| active_pids.contains(pid));
     }
 
     pub fn get_rules(&self) -> &[MisbehaviorRule] {
@@ -292,6 +924,105 @@ impl MisbehaviorDetector {
     }
 }
 
+/// Why a process's executable looks suspicious, if it does
+fn suspicious_exe_reason(info: &crate::process::ProcessInfo) -> Option<String> {
+    let exe = info.exe_path.as_ref()?;
+
+    if info.exe_deleted {
+        return Some(format!("Executable {} has been deleted", exe.display()));
+    }
+
+    ["/tmp", "/dev/shm"]
+        .iter()
+        .find(|dir| exe.starts_with(dir))
+        .map(|dir| format!("Executable {} is under {}", exe.display(), dir))
+}
+
+/// Whether `pattern`, a glob with `*` and `?`, matches the process name or the file name of
+/// its first two command line arguments, so `deploy*` also finds `bash deploy.sh`.
+pub fn process_matches_pattern(info: &crate::process::ProcessInfo, pattern: &str) -> bool {
+    let arg_names = info
+        .command_line
+        .iter()
+        .take(2)
+        .filter_map(|arg| std::path::Path::new(arg).file_name())
+        .map(|name| name.to_string_lossy());
+    glob_matches(pattern, &info.name) || arg_names.into_iter().any(|name| glob_matches(pattern, &name))
+}
+
+/// Match `text` against a glob where `*` is any run of characters and `?` any single one
+pub fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Position of the last `*` and the text position it is currently matched up to
+    let mut star: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = star {
+            // Let the last `*` swallow one more character
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, t));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Interpreters and launchers whose processes legitimately take the name of the
+/// script or program they run
+const NAME_CHANGING_EXECUTABLES: &[&str] = &[
+    "python", "perl", "ruby", "node", "java", "php", "lua", "bash", "sh", "dash", "zsh",
+    "busybox", "wine", "wine64", "electron", "flatpak-bwrap", "bwrap",
+];
+
+/// Longest process name the kernel keeps (`TASK_COMM_LEN` less the terminating NUL)
+const COMM_MAX_LEN: usize = 15;
+
+/// Details for a process whose name does not match its executable, if it doesn't
+fn name_exe_mismatch(info: &crate::process::ProcessInfo) -> Option<String> {
+    // Kernel threads (children of kthreadd) have no executable
+    if info.parent_pid == Some(2) {
+        return None;
+    }
+
+    let exe = info.exe_path.as_ref()?;
+    let exe_name = exe.file_name()?.to_string_lossy().to_lowercase();
+    let name = info.name.to_lowercase();
+
+    // "(sd-pam)" and similar names are set on purpose by the process itself
+    if name.is_empty() || name.starts_with('(') {
+        return None;
+    }
+
+    let is_interpreter = NAME_CHANGING_EXECUTABLES.iter().any(|known| {
+        exe_name == *known
+            || exe_name
+                .strip_prefix(known)
+                .is_some_and(|version| version.chars().all(|c| c.is_ascii_digit() || c == '.'))
+    });
+    if is_interpreter {
+        return None;
+    }
+
+    // The kernel truncates names to 15 characters, so only a full-length name may be a prefix.
+    // argv[0] is not consulted: the process sets it just as freely as its name.
+    let truncated = name.chars().count() == COMM_MAX_LEN && exe_name.starts_with(&name);
+    if exe_name == name || truncated {
+        return None;
+    }
+
+    Some(format!("Process name \"{}\" does not match executable {}", info.name, exe.display()))
+}
+
 impl Default for MisbehaviorDetector {
     fn default() -> Self {
         Self::new()
@@ -0,0 +1,94 @@
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::thread::{self, JoinHandle};
+use std::time::SystemTime;
+
+/// Executables hashed at once in the background; others wait for a later refresh, so a burst
+/// of new processes queues up instead of piling up threads
+pub const MAX_RUNNING_HASHES: usize = 2;
+
+/// Larger executables are never hashed
+pub const MAX_HASHED_SIZE: u64 = 256 * 1024 * 1024;
+
+/// Cached hashes kept before the cache starts over
+const MAX_CACHED: usize = 4096;
+
+/// Lowercase hex SHA-256 of the file at `path`
+pub fn sha256_file(path: &Path) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+/// Whether `hash` is one of `hashes`, ignoring case and surrounding whitespace
+pub fn hash_listed(hash: &str, hashes: &[String]) -> bool {
+    hashes.iter().any(|listed| listed.trim().eq_ignore_ascii_case(hash))
+}
+
+/// Size and modification time; a change means the executable was replaced and is hashed again
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileStamp {
+    len: u64,
+    modified: Option<SystemTime>,
+}
+
+/// SHA-256 of executables, keyed by executable path so every process running the same binary
+/// shares one hash. Files are hashed on background threads, at most [`MAX_RUNNING_HASHES`] at
+/// a time, so a check never waits for one; the hash shows up on a later refresh.
+#[derive(Debug, Default)]
+pub struct ExeHashCache {
+    /// `None` for files that couldn't be read, so they aren't retried until they change
+    hashes: HashMap<PathBuf, (FileStamp, Option<String>)>,
+    running: HashMap<PathBuf, (FileStamp, JoinHandle<Option<String>>)>,
+}
+
+impl ExeHashCache {
+    /// Hash of `file` (normally `/proc/{pid}/exe`), cached under `key` (the executable's path)
+    /// until its size or modification time changes. `None` while the hash is being computed,
+    /// or if the file can't be read or is larger than [`MAX_HASHED_SIZE`].
+    pub fn hash(&mut self, key: &Path, file: &Path) -> Option<String> {
+        let metadata = fs::metadata(file).ok()?;
+        let stamp = FileStamp { len: metadata.len(), modified: metadata.modified().ok() };
+        if let Some((cached_stamp, hash)) = self.hashes.get(key) {
+            if *cached_stamp == stamp {
+                return hash.clone();
+            }
+        }
+
+        if self.running.get(key).is_some_and(|(_, handle)| handle.is_finished()) {
+            let (hashed_stamp, handle) = self.running.remove(key).expect("checked above");
+            let hash = handle.join().ok().flatten();
+            if self.hashes.len() >= MAX_CACHED {
+                self.hashes.clear();
+            }
+            self.hashes.insert(key.to_path_buf(), (hashed_stamp, hash.clone()));
+            // Replaced while it was being hashed: start over below
+            if hashed_stamp == stamp {
+                return hash;
+            }
+        }
+
+        if stamp.len <= MAX_HASHED_SIZE && !self.running.contains_key(key) && self.running.len() < MAX_RUNNING_HASHES {
+            let file = file.to_path_buf();
+            let handle = thread::spawn(move || sha256_file(&file).ok());
+            self.running.insert(key.to_path_buf(), (stamp, handle));
+        }
+        None
+    }
+
+    /// Last hash computed for `key`, without checking the file again
+    pub fn cached(&self, key: &Path) -> Option<&str> {
+        self.hashes.get(key)?.1.as_deref()
+    }
+}
@@ -0,0 +1,76 @@
+use crate::detector::MisbehaviorAlert;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Json,
+    Csv,
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Json => "json",
+            ExportFormat::Csv => "csv",
+        }
+    }
+}
+
+const CSV_HEADER: &str = "timestamp,severity,pid,process_name,rule_name,description,details";
+
+/// Write `alerts` to `alerts-<timestamp>.<ext>` inside `dir` and return the path written.
+pub fn export_alerts(
+    alerts: &[MisbehaviorAlert],
+    dir: &Path,
+    format: ExportFormat,
+    now: DateTime<Utc>,
+) -> Result<PathBuf> {
+    let path = dir.join(format!(
+        "alerts-{}.{}",
+        now.format("%Y%m%d-%H%M%S"),
+        format.extension()
+    ));
+
+    let contents = match format {
+        ExportFormat::Json => serde_json::to_string_pretty(alerts)?,
+        ExportFormat::Csv => alerts_to_csv(alerts),
+    };
+    fs::write(&path, contents)?;
+
+    Ok(path)
+}
+
+/// Render alerts as CSV with a header row, one alert per line.
+pub fn alerts_to_csv(alerts: &[MisbehaviorAlert]) -> String {
+    let mut csv = String::from(CSV_HEADER);
+    csv.push('\n');
+
+    for alert in alerts {
+        let fields = [
+            alert.timestamp.to_rfc3339(),
+            format!("{:?}", alert.severity),
+            alert.pid.to_string(),
+            alert.process_name.clone(),
+            alert.rule_name.clone(),
+            alert.description.clone(),
+            alert.details.clone(),
+        ];
+        let row: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+
+    csv
+}
+
+// Quote fields containing separators, quotes or line breaks, doubling embedded quotes
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
@@ -0,0 +1,40 @@
+use crate::process::ProcessStats;
+use crate::util::stat_field;
+use std::collections::HashMap;
+use std::time::Instant;
+
+/// Major faults between two refreshes at which a process is highlighted; that many reads from
+/// disk for paged-out memory usually means it is thrashing swap
+pub const MAJOR_FAULT_SPIKE: u64 = 100;
+
+/// `(minflt, majflt)` from the contents of `/proc/{pid}/stat` (fields 10 and 12)
+pub fn parse_page_faults(stat: &str) -> Option<(u64, u64)> {
+    let minor = stat_field(stat, 10)?.parse().ok()?;
+    let major = stat_field(stat, 12)?.parse().ok()?;
+    Some((minor, major))
+}
+
+pub fn is_major_fault_spike(stats: &ProcessStats) -> bool {
+    stats.major_faults_delta >= MAJOR_FAULT_SPIKE
+}
+
+/// Major fault counts of the previous refresh, to turn the lifetime counters into deltas
+#[derive(Debug, Default)]
+pub struct FaultHistory {
+    refreshed: Option<Instant>,
+    previous: HashMap<u32, u64>,
+    current: HashMap<u32, u64>,
+}
+
+impl FaultHistory {
+    /// Major faults of `pid` since the refresh before `refreshed`. Processes not seen then
+    /// report 0, since their whole count may have built up over a long lifetime.
+    pub fn delta(&mut self, refreshed: Instant, pid: u32, major_faults: u64) -> u64 {
+        if self.refreshed != Some(refreshed) {
+            self.previous = std::mem::take(&mut self.current);
+            self.refreshed = Some(refreshed);
+        }
+        self.current.insert(pid, major_faults);
+        self.previous.get(&pid).map_or(0, |previous| major_faults.saturating_sub(*previous))
+    }
+}
@@ -0,0 +1,128 @@
+use crate::util::Capped;
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Suffix the kernel appends to `/proc/{pid}/fd` link targets whose file has been removed
+const DELETED_SUFFIX: &str = " (deleted)";
+
+/// How often the storage views rescan `/proc/*/fd`; walking every descriptor is too slow for each refresh
+pub const DELETED_FILES_SCAN_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Number of entries in `/proc/{pid}/fd`, or `None` if the directory can't be read
+/// (the process exited or belongs to another user).
+pub fn count_open_fds(pid: u32) -> Option<usize> {
+    fs::read_dir(format!("/proc/{}/fd", pid)).ok().map(|entries| entries.count())
+}
+
+/// Open descriptors of `pid` as `(fd, target)` in descriptor order, resolving only the first
+/// `cap` link targets; empty when `/proc/{pid}/fd` can't be read
+pub fn list_open_fds(pid: u32, cap: usize) -> Capped<(u32, String)> {
+    let fd_dir = PathBuf::from(format!("/proc/{}/fd", pid));
+    let Ok(entries) = fs::read_dir(&fd_dir) else {
+        return Capped::default();
+    };
+    let mut fds: Vec<u32> = entries.flatten().filter_map(|entry| entry.file_name().to_str()?.parse().ok()).collect();
+    fds.sort_unstable();
+    Capped::collect(fds, cap, |fd| {
+        let target = fs::read_link(fd_dir.join(fd.to_string())).ok()?;
+        Some((fd, target.to_string_lossy().to_string()))
+    })
+}
+
+/// Soft limit from the "Max open files" line of `/proc/{pid}/limits`.
+///
+/// Returns `None` when the line is missing or the limit is "unlimited".
+pub fn parse_soft_open_files_limit(limits: &str) -> Option<u64> {
+    let line = limits.lines().find(|line| line.starts_with("Max open files"))?;
+    line["Max open files".len()..].split_whitespace().next()?.parse().ok()
+}
+
+pub fn read_soft_open_files_limit(pid: u32) -> Option<u64> {
+    parse_soft_open_files_limit(&fs::read_to_string(format!("/proc/{}/limits", pid)).ok()?)
+}
+
+/// Fraction of its own soft limit a process may use before it counts as too many
+pub const SOFT_LIMIT_FRACTION: f64 = 0.9;
+
+/// Whether `open` descriptors exceed `threshold`, or come within
+/// [`SOFT_LIMIT_FRACTION`] of the process's soft limit, whichever is lower.
+pub fn too_many_open_files(open: usize, threshold: usize, soft_limit: Option<u64>) -> bool {
+    let near_soft_limit = soft_limit.is_some_and(|limit| limit > 0 && open as f64 >= limit as f64 * SOFT_LIMIT_FRACTION);
+    open > threshold || near_soft_limit
+}
+
+/// A removed file that a process still holds open, so its space is not freed yet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeletedOpenFile {
+    pub pid: u32,
+    pub fd: u32,
+    /// Path the file had before it was deleted
+    pub path: PathBuf,
+    pub size: u64,
+    /// Device and inode of the file, the same for every descriptor that reaches it
+    pub dev: u64,
+    pub ino: u64,
+}
+
+/// Distinct files among `files` and the space they hold. A file open in several processes
+/// (a log shared by a server's workers) or on several descriptors only counts once.
+pub fn space_held(files: &[DeletedOpenFile]) -> (usize, u64) {
+    let mut seen = std::collections::HashSet::new();
+    files
+        .iter()
+        .filter(|file| seen.insert((file.dev, file.ino)))
+        .fold((0, 0), |(count, bytes), file| (count + 1, bytes + file.size))
+}
+
+/// Deleted regular files held open by the processes under `proc_root` (normally `/proc`),
+/// largest first.
+///
+/// The size comes from stat-ing the descriptor link itself, which still reaches the file.
+/// Anonymous memory files (`/memfd:...`) hold memory rather than disk space and are skipped,
+/// as are processes whose descriptors can't be read. Every descriptor is listed; see
+/// [`space_held`] for the total. Reading every descriptor of every process takes a while on
+/// a busy server, so the frontends run this on a background thread.
+pub fn scan_deleted_open_files(proc_root: &Path) -> Vec<DeletedOpenFile> {
+    let mut files = Vec::new();
+    let Ok(entries) = fs::read_dir(proc_root) else {
+        return files;
+    };
+    for entry in entries.flatten() {
+        let Some(pid) = entry.file_name().to_str().and_then(|name| name.parse::<u32>().ok()) else {
+            continue;
+        };
+        let Ok(fds) = fs::read_dir(entry.path().join("fd")) else {
+            continue;
+        };
+        for fd_entry in fds.flatten() {
+            let Some(fd) = fd_entry.file_name().to_str().and_then(|name| name.parse::<u32>().ok()) else {
+                continue;
+            };
+            let Ok(target) = fs::read_link(fd_entry.path()) else {
+                continue;
+            };
+            let target = target.to_string_lossy();
+            let Some(path) = target.strip_suffix(DELETED_SUFFIX) else {
+                continue;
+            };
+            if path.starts_with("/memfd:") {
+                continue;
+            }
+            match fs::metadata(fd_entry.path()) {
+                Ok(metadata) if metadata.is_file() => files.push(DeletedOpenFile {
+                    pid,
+                    fd,
+                    path: PathBuf::from(path),
+                    size: metadata.len(),
+                    dev: metadata.dev(),
+                    ino: metadata.ino(),
+                }),
+                _ => {}
+            }
+        }
+    }
+    files.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.pid.cmp(&b.pid)).then_with(|| a.fd.cmp(&b.fd)));
+    files
+}
@@ -0,0 +1,253 @@
+use crate::process::ProcessSnapshot;
+use anyhow::Result;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// How often per-process GPU usage is re-read; walking every descriptor is too slow for each refresh
+pub const GPU_PROCESS_SCAN_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Device nodes of DRM drivers; descriptors pointing elsewhere can't be GPU clients
+const DRM_DEVICE_PREFIX: &str = "/dev/dri/";
+
+/// One process's use of one GPU, as reported by a [`GpuProcessSource`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GpuClient {
+    pub pid: u32,
+    /// PCI address of the GPU, e.g. `0000:03:00.0`
+    pub device: String,
+    /// DRM client id; a process holding several descriptors to one client reports it on each
+    pub client_id: Option<u64>,
+    /// Video memory held by this client
+    pub memory_bytes: u64,
+}
+
+/// Somewhere per-process GPU usage can be read from
+pub trait GpuProcessSource: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn clients(&self) -> Result<Vec<GpuClient>>;
+}
+
+/// GPU clients from `/proc/{pid}/fdinfo`, which DRM drivers (amdgpu, i915, xe, nouveau, ...)
+/// fill with `drm-client-id`, `drm-pdev` and memory keys.
+pub struct DrmFdinfoSource {
+    proc_root: PathBuf,
+}
+
+impl DrmFdinfoSource {
+    pub fn new(proc_root: impl Into<PathBuf>) -> Self {
+        Self { proc_root: proc_root.into() }
+    }
+}
+
+impl GpuProcessSource for DrmFdinfoSource {
+    fn name(&self) -> &'static str {
+        "DRM fdinfo"
+    }
+
+    fn clients(&self) -> Result<Vec<GpuClient>> {
+        Ok(scan_drm_clients(&self.proc_root))
+    }
+}
+
+/// Fields of one DRM client's fdinfo
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DrmFdinfo {
+    pub device: String,
+    pub client_id: u64,
+    pub vram_bytes: u64,
+}
+
+/// Parse `/proc/{pid}/fdinfo/{fd}` of a DRM descriptor, or `None` for other descriptors.
+///
+/// Video memory is `drm-memory-vram` (amdgpu) or the `drm-total-` keys for device-local
+/// regions (`vram*` on xe, `local*` on i915); integrated GPUs have none and report 0.
+pub fn parse_drm_fdinfo(text: &str) -> Option<DrmFdinfo> {
+    let mut device = None;
+    let mut client_id = None;
+    let mut vram_bytes = 0;
+    for line in text.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        match key.trim() {
+            "drm-pdev" => device = Some(value.to_lowercase()),
+            "drm-client-id" => client_id = value.parse().ok(),
+            "drm-memory-vram" => vram_bytes += parse_fdinfo_size(value).unwrap_or(0),
+            key if key.starts_with("drm-total-vram") || key.starts_with("drm-total-local") => {
+                vram_bytes += parse_fdinfo_size(value).unwrap_or(0)
+            }
+            _ => {}
+        }
+    }
+    Some(DrmFdinfo { device: device.unwrap_or_default(), client_id: client_id?, vram_bytes })
+}
+
+/// `1024 KiB`, `12 MiB` or a plain byte count
+fn parse_fdinfo_size(value: &str) -> Option<u64> {
+    let mut parts = value.split_whitespace();
+    let number: u64 = parts.next()?.parse().ok()?;
+    let multiplier = match parts.next() {
+        None => 1,
+        Some("KiB") => 1024,
+        Some("MiB") => 1024 * 1024,
+        Some("GiB") => 1024 * 1024 * 1024,
+        Some(_) => return None,
+    };
+    Some(number * multiplier)
+}
+
+/// DRM clients of every process under `proc_root` (normally `/proc`) whose descriptors can
+/// be read. Only descriptors pointing into `/dev/dri/` have their fdinfo parsed.
+pub fn scan_drm_clients(proc_root: &Path) -> Vec<GpuClient> {
+    let mut clients = Vec::new();
+    let Ok(entries) = fs::read_dir(proc_root) else {
+        return clients;
+    };
+    for entry in entries.flatten() {
+        let Some(pid) = entry.file_name().to_str().and_then(|name| name.parse::<u32>().ok()) else {
+            continue;
+        };
+        let Ok(fds) = fs::read_dir(entry.path().join("fd")) else {
+            continue;
+        };
+        for fd_entry in fds.flatten() {
+            let is_drm = fs::read_link(fd_entry.path())
+                .is_ok_and(|target| target.to_string_lossy().starts_with(DRM_DEVICE_PREFIX));
+            if !is_drm {
+                continue;
+            }
+            let fdinfo = entry.path().join("fdinfo").join(fd_entry.file_name());
+            if let Some(info) = fs::read_to_string(fdinfo).ok().as_deref().and_then(parse_drm_fdinfo) {
+                clients.push(GpuClient {
+                    pid,
+                    device: info.device,
+                    client_id: Some(info.client_id),
+                    memory_bytes: info.vram_bytes,
+                });
+            }
+        }
+    }
+    clients
+}
+
+/// Per-process memory on NVIDIA GPUs through NVML, with the `nvidia` feature
+#[cfg(feature = "nvidia")]
+pub struct NvmlSource {
+    nvml: nvml_wrapper::Nvml,
+}
+
+#[cfg(feature = "nvidia")]
+impl NvmlSource {
+    /// Load NVML; fails without the NVIDIA driver
+    pub fn new() -> Result<Self> {
+        Ok(Self { nvml: nvml_wrapper::Nvml::init()? })
+    }
+}
+
+#[cfg(feature = "nvidia")]
+impl GpuProcessSource for NvmlSource {
+    fn name(&self) -> &'static str {
+        "NVML"
+    }
+
+    fn clients(&self) -> Result<Vec<GpuClient>> {
+        use nvml_wrapper::enums::device::UsedGpuMemory;
+
+        let mut clients = Vec::new();
+        for index in 0..self.nvml.device_count()? {
+            let device = self.nvml.device_by_index(index)?;
+            let address = normalize_pci_address(&device.pci_info()?.bus_id);
+            let mut processes = device.running_compute_processes()?;
+            processes.extend(device.running_graphics_processes()?);
+            for process in processes {
+                let memory_bytes = match process.used_gpu_memory {
+                    UsedGpuMemory::Used(bytes) => bytes,
+                    UsedGpuMemory::Unavailable => 0,
+                };
+                clients.push(GpuClient { pid: process.pid, device: address.clone(), client_id: None, memory_bytes });
+            }
+        }
+        Ok(clients)
+    }
+}
+
+/// PCI address in the `drm-pdev` form: NVML writes `00000000:01:00.0`, DRM `0000:01:00.0`
+pub fn normalize_pci_address(address: &str) -> String {
+    let address = address.trim().to_lowercase();
+    match address.split_once(':') {
+        Some((domain, rest)) => match u32::from_str_radix(domain, 16) {
+            Ok(domain) => format!("{:04x}:{}", domain, rest),
+            Err(_) => address,
+        },
+        None => address,
+    }
+}
+
+/// The sources available on this machine: DRM fdinfo, plus NVML when built with the `nvidia`
+/// feature and an NVIDIA driver is loaded
+pub fn default_sources() -> Vec<Box<dyn GpuProcessSource>> {
+    #[allow(unused_mut)]
+    let mut sources: Vec<Box<dyn GpuProcessSource>> = vec![Box::new(DrmFdinfoSource::new("/proc"))];
+    #[cfg(feature = "nvidia")]
+    match NvmlSource::new() {
+        Ok(source) => sources.push(Box::new(source)),
+        Err(e) => tracing::debug!("NVML unavailable, no per-process NVIDIA usage: {}", e),
+    }
+    sources
+}
+
+/// Clients from every source; a failing source is logged and skipped
+pub fn collect_gpu_clients(sources: &[Box<dyn GpuProcessSource>]) -> Vec<GpuClient> {
+    let mut clients = Vec::new();
+    for source in sources {
+        match source.clients() {
+            Ok(found) => clients.extend(found),
+            Err(e) => tracing::debug!("{} per-process GPU usage failed: {}", source.name(), e),
+        }
+    }
+    clients
+}
+
+/// GPU memory per PID, summed over all GPUs the process uses.
+///
+/// A DRM client reached through several descriptors counts once. When both DRM and NVML
+/// report a process on the same GPU, the larger figure is used rather than their sum.
+pub fn gpu_memory_by_pid(clients: &[GpuClient]) -> HashMap<u32, u64> {
+    let mut seen = HashSet::new();
+    let mut drm: HashMap<(u32, &str), u64> = HashMap::new();
+    let mut other: HashMap<(u32, &str), u64> = HashMap::new();
+    for client in clients {
+        let key = (client.pid, client.device.as_str());
+        match client.client_id {
+            Some(id) => {
+                if seen.insert((client.pid, client.device.as_str(), id)) {
+                    *drm.entry(key).or_default() += client.memory_bytes;
+                }
+            }
+            None => *other.entry(key).or_default() += client.memory_bytes,
+        }
+    }
+
+    let mut totals: HashMap<u32, u64> = HashMap::new();
+    for key in drm.keys().chain(other.keys()).collect::<HashSet<_>>() {
+        let bytes = drm.get(key).copied().unwrap_or(0).max(other.get(key).copied().unwrap_or(0));
+        *totals.entry(key.0).or_default() += bytes;
+    }
+    totals
+}
+
+/// Processes using a GPU, most GPU memory first
+pub fn gpu_processes(processes: &[ProcessSnapshot]) -> Vec<&ProcessSnapshot> {
+    let mut using_gpu: Vec<&ProcessSnapshot> =
+        processes.iter().filter(|process| process.stats.gpu_memory_bytes.is_some()).collect();
+    using_gpu.sort_by(|a, b| {
+        b.stats
+            .gpu_memory_bytes
+            .cmp(&a.stats.gpu_memory_bytes)
+            .then_with(|| a.info.pid.cmp(&b.info.pid))
+    });
+    using_gpu
+}
@@ -0,0 +1,49 @@
+use crate::process::ProcessSnapshot;
+use std::collections::HashMap;
+use std::time::Instant;
+
+/// Change in resident memory from `previous` to `current`; a process without a previous
+/// size is new, and all of its memory counts as growth
+fn growth(previous: Option<u64>, current: u64) -> i64 {
+    current as i64 - previous.unwrap_or(0) as i64
+}
+
+/// Processes whose memory grew since the last refresh, most growth first; ties are broken by
+/// ascending PID
+pub fn top_memory_growth(processes: &[ProcessSnapshot]) -> Vec<&ProcessSnapshot> {
+    let mut growing: Vec<&ProcessSnapshot> = processes.iter().filter(|p| p.stats.memory_delta > 0).collect();
+    growing.sort_by(|a, b| {
+        b.stats
+            .memory_delta
+            .cmp(&a.stats.memory_delta)
+            .then_with(|| a.info.pid.cmp(&b.info.pid))
+    });
+    growing
+}
+
+/// Resident memory of every process at the previous refresh, to compute growth per refresh
+#[derive(Debug, Default)]
+pub struct MemoryGrowth {
+    refreshed: Option<Instant>,
+    /// `None` until a second refresh, so processes already running at startup don't all
+    /// show up as new
+    previous: Option<HashMap<u32, u64>>,
+    current: HashMap<u32, u64>,
+}
+
+impl MemoryGrowth {
+    /// Growth of `pid` since the refresh before `refreshed`
+    pub fn delta(&mut self, refreshed: Instant, pid: u32, memory_usage: u64) -> i64 {
+        if self.refreshed != Some(refreshed) {
+            if self.refreshed.is_some() {
+                self.previous = Some(std::mem::take(&mut self.current));
+            }
+            self.refreshed = Some(refreshed);
+        }
+        self.current.insert(pid, memory_usage);
+        match &self.previous {
+            Some(previous) => growth(previous.get(&pid).copied(), memory_usage),
+            None => 0,
+        }
+    }
+}
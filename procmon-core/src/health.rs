@@ -0,0 +1,109 @@
+use crate::detector::{MisbehaviorAlert, MisbehaviorDetector, Severity};
+use crate::metrics::SystemMetrics;
+use crate::monitor::SystemMonitor;
+use anyhow::Result;
+
+/// Command line flag that runs one health check and exits instead of starting the UI
+pub const CHECK_FLAG: &str = "--check";
+
+/// Result of a health check, exiting with the Nagios plugin status codes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum HealthStatus {
+    Ok,
+    Warning,
+    Critical,
+    /// The check itself failed
+    Unknown,
+}
+
+impl HealthStatus {
+    /// Status for the most severe of `alerts`; info alerts and no alerts are OK
+    pub fn from_alerts(alerts: &[MisbehaviorAlert]) -> Self {
+        match alerts.iter().map(|alert| alert.severity).max() {
+            Some(Severity::Critical) => HealthStatus::Critical,
+            Some(Severity::Warning) => HealthStatus::Warning,
+            Some(Severity::Info) | None => HealthStatus::Ok,
+        }
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            HealthStatus::Ok => 0,
+            HealthStatus::Warning => 1,
+            HealthStatus::Critical => 2,
+            HealthStatus::Unknown => 3,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            HealthStatus::Ok => "OK",
+            HealthStatus::Warning => "WARNING",
+            HealthStatus::Critical => "CRITICAL",
+            HealthStatus::Unknown => "UNKNOWN",
+        }
+    }
+}
+
+/// Status plus the one-line summary printed by `--check`
+#[derive(Debug, Clone, PartialEq)]
+pub struct HealthReport {
+    pub status: HealthStatus,
+    pub summary: String,
+}
+
+impl HealthReport {
+    /// Summarize `alerts`, naming the most severe one, with CPU and memory as performance data, e.g.
+    /// `PROCMON CRITICAL - 1 critical, 0 warning: Memory Pressure: Memory used: 97.0% (threshold: 95.0%) | cpu=12.5% memory=97.0%`
+    pub fn from_alerts(alerts: &[MisbehaviorAlert], metrics: &SystemMetrics) -> Self {
+        let status = HealthStatus::from_alerts(alerts);
+        let count = |severity: Severity| alerts.iter().filter(|alert| alert.severity == severity).count();
+
+        // First of the most severe alerts, in detector order
+        let worst = alerts.iter().rev().max_by_key(|alert| alert.severity);
+        let message = match worst {
+            Some(alert) if status != HealthStatus::Ok => format!(
+                "{} critical, {} warning: {}: {}",
+                count(Severity::Critical),
+                count(Severity::Warning),
+                alert.rule_name,
+                alert.details
+            ),
+            _ => "no active alerts".to_string(),
+        };
+
+        let memory_percent = if metrics.memory.total > 0 {
+            metrics.memory.used as f64 / metrics.memory.total as f64 * 100.0
+        } else {
+            0.0
+        };
+
+        Self {
+            status,
+            summary: format!(
+                "PROCMON {} - {} | cpu={:.1}% memory={:.1}%",
+                status.label(),
+                message,
+                metrics.cpu.total_usage,
+                memory_percent
+            ),
+        }
+    }
+
+    /// Report for a check that could not run
+    pub fn unknown(error: &anyhow::Error) -> Self {
+        Self {
+            status: HealthStatus::Unknown,
+            summary: format!("PROCMON UNKNOWN - {}", error),
+        }
+    }
+}
+
+/// Sample the system once and evaluate the detector's system-wide rules against it
+pub fn run_check(detector: &mut MisbehaviorDetector) -> Result<HealthReport> {
+    let monitor = SystemMonitor::new_warmed();
+    let metrics = monitor.get_system_metrics()?;
+    let processes = monitor.get_all_processes()?;
+    let alerts = detector.check_system(&processes, &metrics);
+    Ok(HealthReport::from_alerts(&alerts, &metrics))
+}
@@ -0,0 +1,146 @@
+use crate::process::{ProcessInfo, ProcessSnapshot, ProcessStatus};
+use crate::protect::ProtectedSet;
+use crate::util::stat_field;
+use anyhow::{bail, Result};
+use std::collections::HashSet;
+use std::process::Command;
+
+/// Signal sent by the kill-all-of-a-user action
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Signal {
+    /// SIGTERM, lets processes clean up
+    #[default]
+    Term,
+    /// SIGKILL, for processes that ignore SIGTERM
+    Kill,
+}
+
+impl Signal {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Signal::Term => "TERM",
+            Signal::Kill => "KILL",
+        }
+    }
+}
+
+pub(crate) fn kill_command(pid: u32) -> Command {
+    let mut cmd = Command::new("kill");
+    cmd.arg(pid.to_string());
+    cmd
+}
+
+/// Send SIGTERM to `info` unless it is protected
+pub fn kill_process(info: &ProcessInfo, protected: &ProtectedSet) -> Result<()> {
+    protected.check(info)?;
+    run_kill(kill_command(info.pid), info.pid)
+}
+
+/// `info` and its descendants in `processes`, parents before children; an error naming the
+/// rule if any of them is protected
+pub fn tree_kill_targets(info: &ProcessInfo, processes: &[ProcessSnapshot], protected: &ProtectedSet) -> Result<Vec<u32>> {
+    protected.check(info)?;
+    let mut targets = vec![info.pid];
+    let mut seen = HashSet::from([info.pid]);
+    let mut next = 0;
+    while let Some(&parent) = targets.get(next) {
+        next += 1;
+        for child in processes.iter().filter(|p| p.info.parent_pid == Some(parent)) {
+            if seen.insert(child.info.pid) {
+                protected.check(&child.info)?;
+                targets.push(child.info.pid);
+            }
+        }
+    }
+    Ok(targets)
+}
+
+/// Send SIGTERM to `info` and each of its descendants in `processes`, refusing if any of them
+/// is protected.
+///
+/// Exactly the PIDs that were checked are signalled, not the process group, which can hold
+/// processes outside the tree and miss children that started their own group.
+pub fn kill_process_tree(info: &ProcessInfo, processes: &[ProcessSnapshot], protected: &ProtectedSet) -> Result<()> {
+    let targets = tree_kill_targets(info, processes, protected)?;
+    let results = signal_processes(&targets, Signal::Term);
+    let failures: Vec<String> = results
+        .iter()
+        .filter_map(|(pid, result)| result.as_ref().err().map(|e| format!("PID {}: {}", pid, e)))
+        .collect();
+    if let Some(first) = failures.first() {
+        bail!("{} of {} processes not signalled; {}", failures.len(), targets.len(), first);
+    }
+    Ok(())
+}
+
+pub(crate) fn signal_command(pid: u32, signal: Signal) -> Command {
+    let mut cmd = Command::new("kill");
+    cmd.args([format!("-{}", signal.name()), pid.to_string()]);
+    cmd
+}
+
+/// Session id of `pid` (field 6 of `/proc/{pid}/stat`)
+pub fn process_session(pid: u32) -> Option<u32> {
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    stat_field(&stat, 6)?.parse().ok()
+}
+
+/// PIDs to signal when terminating every process of `uid`.
+///
+/// Skips protected processes, zombies (already exited) and anything in `own_session`, the
+/// session procmon runs in, so the action can't take down the terminal it was started from.
+/// Root's processes are never selected.
+pub fn user_kill_targets(
+    processes: &[ProcessSnapshot],
+    uid: u32,
+    protected: &ProtectedSet,
+    own_session: Option<u32>,
+    session_of: impl Fn(u32) -> Option<u32>,
+) -> Vec<u32> {
+    if uid == 0 {
+        return Vec::new();
+    }
+    processes
+        .iter()
+        .map(|process| &process.info)
+        .filter(|info| info.uid == uid && info.status != ProcessStatus::Zombie)
+        .filter(|info| protected.protection_reason(info).is_none())
+        .filter(|info| own_session.is_none() || session_of(info.pid) != own_session)
+        .map(|info| info.pid)
+        .filter(|pid| *pid != std::process::id())
+        .collect()
+}
+
+/// Send `signal` to each of `pids`, one result per PID
+pub fn signal_processes(pids: &[u32], signal: Signal) -> Vec<(u32, Result<()>)> {
+    pids.iter().map(|&pid| (pid, run_kill(signal_command(pid, signal), pid))).collect()
+}
+
+/// One-line outcome of signalling every process of `user`, e.g.
+/// "Sent SIGTERM to 4 of 5 processes of alice; PID 812: Operation not permitted"
+pub fn summarize_signal_results(user: &str, signal: Signal, results: &[(u32, Result<()>)]) -> String {
+    if results.is_empty() {
+        return format!("No processes of {} to signal", user);
+    }
+    let failures: Vec<String> = results
+        .iter()
+        .filter_map(|(pid, result)| result.as_ref().err().map(|e| format!("PID {}: {}", pid, e)))
+        .collect();
+    let sent = results.len() - failures.len();
+    let mut summary = format!("Sent SIG{} to {} of {} processes of {}", signal.name(), sent, results.len(), user);
+    if let Some(first) = failures.first() {
+        summary.push_str(&format!("; {}", first));
+        if failures.len() > 1 {
+            summary.push_str(&format!(" (+{} more failures)", failures.len() - 1));
+        }
+    }
+    summary
+}
+
+fn run_kill(mut cmd: Command, pid: u32) -> Result<()> {
+    let output = cmd.output()?;
+    if !output.status.success() {
+        bail!("Failed to kill PID {}: {}", pid, String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(())
+}
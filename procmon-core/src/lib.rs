@@ -1,16 +1,62 @@
+pub mod badge;
+pub mod bandwidth;
+pub mod baseline;
+pub mod bell;
 pub mod monitor;
+pub mod netio;
+pub mod once;
+pub mod own;
 pub mod process;
 pub mod metrics;
 pub mod detector;
 pub mod partition;
+pub mod privilege;
+pub mod protect;
+pub mod respawn;
+pub mod restart;
+pub mod script;
+pub mod search;
+pub mod selection;
 pub mod service;
+pub mod sort;
+pub mod tracking;
+pub mod users;
+pub mod util;
+pub mod click;
+pub mod clipboard;
+pub mod columns;
+pub mod config;
+pub mod confirm;
+pub mod container;
+pub mod cores;
+pub mod density;
+pub mod detail;
+pub mod exehash;
+pub mod faults;
+pub mod counters;
+pub mod fds;
+pub mod gpu;
+pub mod growth;
+pub mod health;
+pub mod kill;
+pub mod limits;
+pub mod logging;
+pub mod sink;
+pub mod snapshot;
+pub mod export;
+pub mod tab;
+pub mod temperature;
+pub mod threads;
+pub mod throttle;
+pub mod zombie;
 
 #[cfg(test)]
 mod tests;
 
-pub use monitor::SystemMonitor;
+pub use monitor::{MonitorConfig, SystemMonitor};
 pub use process::{ProcessInfo, ProcessStats};
 pub use metrics::*;
-pub use detector::{MisbehaviorDetector, MisbehaviorRule, MisbehaviorAlert};
+pub use detector::{MisbehaviorDetector, MisbehaviorRule, MisbehaviorAlert, SystemCondition};
 pub use partition::{PartitionManager, Disk, Partition};
-pub use service::{ServiceManager, SystemService, ServiceState};
+pub use service::{ServiceManager, SystemService, ServiceState, ServiceDetails, StopMode, StopPlan};
+pub use tracking::NewProcessTracker;
@@ -0,0 +1,108 @@
+use crate::fds::SOFT_LIMIT_FRACTION;
+use crate::process::ProcessSnapshot;
+use std::fmt;
+use std::fs;
+
+/// One side of a resource limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimitValue {
+    Unlimited,
+    Value(u64),
+}
+
+impl LimitValue {
+    fn parse(text: &str) -> Option<Self> {
+        match text {
+            "unlimited" => Some(LimitValue::Unlimited),
+            _ => text.parse().ok().map(LimitValue::Value),
+        }
+    }
+}
+
+impl fmt::Display for LimitValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LimitValue::Unlimited => f.write_str("unlimited"),
+            LimitValue::Value(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+/// `(name, soft, hard)` for every line of the contents of `/proc/{pid}/limits`, e.g.
+/// `("Max open files", Value(1024), Value(524288))`. The header and lines that don't parse
+/// are skipped.
+pub fn parse_limits(limits: &str) -> Vec<(String, LimitValue, LimitValue)> {
+    limits
+        .lines()
+        .filter_map(|line| {
+            // Names contain spaces but values don't, so the name ends at the first value
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            let first_value = tokens.iter().position(|token| LimitValue::parse(token).is_some())?;
+            if first_value == 0 {
+                return None;
+            }
+            let soft = LimitValue::parse(tokens[first_value])?;
+            let hard = LimitValue::parse(tokens.get(first_value + 1)?)?;
+            Some((tokens[..first_value].join(" "), soft, hard))
+        })
+        .collect()
+}
+
+pub fn read_process_limits(pid: u32) -> Vec<(String, LimitValue, LimitValue)> {
+    fs::read_to_string(format!("/proc/{}/limits", pid)).map(|limits| parse_limits(&limits)).unwrap_or_default()
+}
+
+/// A limit in the detail view, with what the process currently uses of it where procmon knows
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LimitRow {
+    pub name: String,
+    pub soft: LimitValue,
+    pub hard: LimitValue,
+    pub used: Option<u64>,
+}
+
+impl LimitRow {
+    /// Whether usage is within [`SOFT_LIMIT_FRACTION`] of the soft limit, where errors such as
+    /// "too many open files" start
+    pub fn near_limit(&self) -> bool {
+        match (self.used, self.soft) {
+            (Some(used), LimitValue::Value(soft)) => soft > 0 && used as f64 >= soft as f64 * SOFT_LIMIT_FRACTION,
+            _ => false,
+        }
+    }
+}
+
+/// Threads of all of `uid`'s processes in `processes`, which is what "Max processes" counts
+/// against the user (processes left out of the table, like ignored ones, are not counted)
+pub fn user_thread_count(processes: &[ProcessSnapshot], uid: u32) -> u64 {
+    processes.iter().filter(|p| p.info.uid == uid).map(|p| p.stats.num_threads as u64).sum()
+}
+
+/// Current usage of the limit called `name`: open descriptors for "Max open files", the
+/// user's threads (see [`user_thread_count`]) for "Max processes", and memory for the address
+/// space and resident set limits
+pub fn limit_usage(name: &str, snapshot: &ProcessSnapshot, open_fds: Option<usize>, user_threads: u64) -> Option<u64> {
+    match name {
+        "Max open files" => open_fds.map(|open| open as u64),
+        "Max processes" => Some(user_threads),
+        "Max address space" => Some(snapshot.stats.virtual_memory),
+        "Max resident set" => Some(snapshot.stats.memory_usage),
+        _ => None,
+    }
+}
+
+/// Limits of `snapshot`'s process with their usage filled in
+pub fn limit_rows(
+    limits: Vec<(String, LimitValue, LimitValue)>,
+    snapshot: &ProcessSnapshot,
+    open_fds: Option<usize>,
+    user_threads: u64,
+) -> Vec<LimitRow> {
+    limits
+        .into_iter()
+        .map(|(name, soft, hard)| {
+            let used = limit_usage(&name, snapshot, open_fds, user_threads);
+            LimitRow { name, soft, hard, used }
+        })
+        .collect()
+}
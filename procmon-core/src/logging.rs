@@ -0,0 +1,311 @@
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Local};
+use std::collections::VecDeque;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::layer::{Context as LayerContext, Layer, SubscriberExt};
+
+/// Filter used when neither `--log-level` nor `RUST_LOG` is given
+pub const DEFAULT_LOG_FILTER: &str = "info";
+
+/// Where the app's own log goes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LogTarget {
+    Stderr,
+    /// Appended to; the TUI's default, since stderr would draw over the alternate screen
+    File(PathBuf),
+    Off,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogConfig {
+    /// `RUST_LOG`-style directives, e.g. `warn` or `info,procmon_core=debug`
+    pub filter: String,
+    pub target: LogTarget,
+}
+
+/// Logging from `--log-level <filter>` and `--log-file <path>` in `args`, falling back to
+/// `rust_log` (normally `RUST_LOG`) and [`DEFAULT_LOG_FILTER`] for the level and to
+/// `default_target` for the destination. `--log-file -` logs to stderr.
+pub fn log_config<I>(args: I, rust_log: Option<&str>, default_target: LogTarget) -> Result<LogConfig>
+where
+    I: IntoIterator<Item = String>,
+{
+    let args: Vec<String> = args.into_iter().collect();
+    let level = crate::util::flag_value(args.clone(), "--log-level")?;
+    let filter = level
+        .as_deref()
+        .or(rust_log.filter(|filter| !filter.trim().is_empty()))
+        .unwrap_or(DEFAULT_LOG_FILTER)
+        .to_string();
+    let target = match crate::util::flag_value(args, "--log-file")?.as_deref() {
+        Some("-") => LogTarget::Stderr,
+        Some(path) => LogTarget::File(PathBuf::from(path)),
+        None => default_target,
+    };
+    Ok(LogConfig { filter, target })
+}
+
+/// `$XDG_STATE_HOME/procmon/<file_name>`, falling back to `~/.local/state`
+pub fn default_log_file(file_name: &str) -> Option<PathBuf> {
+    crate::util::state_file(file_name)
+}
+
+/// Stderr for log output, held back while a full-screen UI owns the terminal.
+///
+/// While held, output is buffered (up to [`StderrGate::MAX_HELD_BYTES`]) and written out by
+/// [`StderrGate::release`], e.g. once the TUI has left the alternate screen.
+#[derive(Clone)]
+pub struct StderrGate {
+    state: Arc<parking_lot::Mutex<GateState>>,
+}
+
+struct GateState {
+    held: bool,
+    buffer: Vec<u8>,
+    dropped: usize,
+    sink: Box<dyn Write + Send>,
+}
+
+impl Default for StderrGate {
+    fn default() -> Self {
+        Self::with_sink(Box::new(io::stderr()))
+    }
+}
+
+impl StderrGate {
+    pub const MAX_HELD_BYTES: usize = 1024 * 1024;
+
+    /// A gate in front of `sink` instead of stderr
+    pub fn with_sink(sink: Box<dyn Write + Send>) -> Self {
+        let state = GateState { held: false, buffer: Vec::new(), dropped: 0, sink };
+        Self { state: Arc::new(parking_lot::Mutex::new(state)) }
+    }
+
+    /// Buffer output from now on instead of writing it
+    pub fn hold(&self) {
+        self.state.lock().held = true;
+    }
+
+    /// Write out everything held back and pass output straight through again
+    pub fn release(&self) -> io::Result<()> {
+        let mut state = self.state.lock();
+        state.held = false;
+        let buffer = std::mem::take(&mut state.buffer);
+        let dropped = std::mem::take(&mut state.dropped);
+        state.sink.write_all(&buffer)?;
+        if dropped > 0 {
+            writeln!(state.sink, "({} bytes of log output dropped while the display was active)", dropped)?;
+        }
+        state.sink.flush()
+    }
+}
+
+impl Write for StderrGate {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut state = self.state.lock();
+        if !state.held {
+            return state.sink.write(buf);
+        }
+        if state.buffer.len() + buf.len() <= Self::MAX_HELD_BYTES {
+            state.buffer.extend_from_slice(buf);
+        } else {
+            state.dropped += buf.len();
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let mut state = self.state.lock();
+        if state.held {
+            return Ok(());
+        }
+        state.sink.flush()
+    }
+}
+
+impl<'a> MakeWriter<'a> for StderrGate {
+    type Writer = StderrGate;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.clone()
+    }
+}
+
+/// One event kept by [`LogBuffer`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogEntry {
+    pub timestamp: DateTime<Local>,
+    pub level: Level,
+    pub target: String,
+    /// The message followed by any other fields as `key=value`
+    pub message: String,
+}
+
+/// The most recent log events in memory, for an in-app log view.
+///
+/// Installed as a layer next to the configured [`LogTarget`], so it sees the same events
+/// whether the log goes to a file, stderr or nowhere. Once full, the oldest event is dropped.
+#[derive(Clone)]
+pub struct LogBuffer {
+    capacity: usize,
+    entries: Arc<parking_lot::Mutex<VecDeque<LogEntry>>>,
+}
+
+impl Default for LogBuffer {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_CAPACITY)
+    }
+}
+
+impl LogBuffer {
+    pub const DEFAULT_CAPACITY: usize = 1000;
+
+    pub fn new(capacity: usize) -> Self {
+        Self { capacity: capacity.max(1), entries: Arc::new(parking_lot::Mutex::new(VecDeque::new())) }
+    }
+
+    pub fn push(&self, entry: LogEntry) {
+        let mut entries = self.entries.lock();
+        if entries.len() == self.capacity {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+
+    /// Copy of the kept events, oldest first
+    pub fn entries(&self) -> Vec<LogEntry> {
+        self.entries.lock().iter().cloned().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.lock().is_empty()
+    }
+}
+
+impl<S: Subscriber> Layer<S> for LogBuffer {
+    fn on_event(&self, event: &Event<'_>, _ctx: LayerContext<'_, S>) {
+        let mut message = MessageVisitor::default();
+        event.record(&mut message);
+        self.push(LogEntry {
+            timestamp: Local::now(),
+            level: *event.metadata().level(),
+            target: event.metadata().target().to_string(),
+            message: message.0,
+        });
+    }
+}
+
+#[derive(Default)]
+struct MessageVisitor(String);
+
+impl MessageVisitor {
+    fn append(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.0.insert_str(0, value);
+        } else {
+            self.0.push_str(&format!(" {}={}", field.name(), value));
+        }
+    }
+}
+
+impl Visit for MessageVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.append(field, value);
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.append(field, &format!("{:?}", value));
+    }
+}
+
+/// Where the log goes besides the configured [`LogTarget`]
+#[derive(Clone, Default)]
+pub struct LogSinks {
+    /// Stands in for stderr when the target is [`LogTarget::Stderr`]
+    pub stderr: StderrGate,
+    /// Recent events, whatever the target
+    pub recent: LogBuffer,
+}
+
+/// `target=level` directives such as `warn,procmon_core=debug`. `RUST_LOG`'s span and field
+/// filters (`procmon_core[refresh{pid=1}]=debug`) are rejected rather than read as a target
+/// that never matches.
+fn parse_log_filter(filter: &str) -> Result<Targets> {
+    if filter.contains('[') {
+        bail!("Invalid log level '{}': span and field filters are not supported", filter);
+    }
+    Targets::from_str(filter).with_context(|| format!("Invalid log level '{}'", filter))
+}
+
+/// Build the subscriber for `config` without installing it
+pub fn log_subscriber(config: &LogConfig, sinks: &LogSinks) -> Result<Box<dyn Subscriber + Send + Sync>> {
+    let targets = parse_log_filter(&config.filter)?;
+    let registry = tracing_subscriber::registry().with(targets).with(sinks.recent.clone());
+
+    Ok(match &config.target {
+        LogTarget::Stderr => Box::new(registry.with(tracing_subscriber::fmt::layer().with_writer(sinks.stderr.clone()))),
+        LogTarget::File(path) => {
+            let file = open_log_file(path)?;
+            Box::new(registry.with(tracing_subscriber::fmt::layer().with_ansi(false).with_writer(Mutex::new(file))))
+        }
+        LogTarget::Off => Box::new(registry),
+    })
+}
+
+/// Like [`log_subscriber`], but a problem with `config` doesn't stop the app from starting:
+/// a filter that can't be parsed is replaced by [`DEFAULT_LOG_FILTER`], and a log file that
+/// can't be opened by [`LogTarget::Off`]. Returns what was replaced, to be logged once the
+/// subscriber is in place.
+pub fn log_subscriber_with_fallback(config: &LogConfig, sinks: &LogSinks) -> (Box<dyn Subscriber + Send + Sync>, Vec<String>) {
+    let mut config = config.clone();
+    let mut warnings = Vec::new();
+    if let Err(e) = parse_log_filter(&config.filter) {
+        warnings.push(format!("{:#}; using '{}'", e, DEFAULT_LOG_FILTER));
+        config.filter = DEFAULT_LOG_FILTER.to_string();
+    }
+    match log_subscriber(&config, sinks) {
+        Ok(subscriber) => (subscriber, warnings),
+        Err(e) => {
+            // With a valid filter only opening the log file can fail
+            warnings.push(format!("{:#}; file logging is disabled", e));
+            config.target = LogTarget::Off;
+            (log_subscriber(&config, sinks).expect("logging nowhere can't fail"), warnings)
+        }
+    }
+}
+
+/// Install the subscriber for `config` as the global default, falling back as
+/// [`log_subscriber_with_fallback`] does and logging a warning for each fallback. A
+/// full-screen UI holds the returned stderr gate while it owns the terminal.
+pub fn init_logging(config: &LogConfig) -> Result<LogSinks> {
+    let sinks = LogSinks::default();
+    let (subscriber, warnings) = log_subscriber_with_fallback(config, &sinks);
+    tracing::subscriber::set_global_default(subscriber).context("Logging is already set up")?;
+    for warning in warnings {
+        tracing::warn!("{}", warning);
+    }
+    Ok(sinks)
+}
+
+fn open_log_file(path: &Path) -> Result<fs::File> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open log file {}", path.display()))
+}
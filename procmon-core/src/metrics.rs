@@ -1,5 +1,7 @@
+use crate::util::Ema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::net::IpAddr;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CpuMetrics {
@@ -12,9 +14,10 @@ pub struct CpuMetrics {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GpuMetrics {
     pub name: String,
-    pub usage: f32,
-    pub memory_used: u64,
-    pub memory_total: u64,
+    /// `None` when the driver doesn't report it or sysfs can't be read, e.g. in a container
+    pub usage: Option<f32>,
+    pub memory_used: Option<u64>,
+    pub memory_total: Option<u64>,
     pub temperature: Option<f32>,
 }
 
@@ -23,10 +26,50 @@ pub struct NetworkMetrics {
     pub interface_name: String,
     pub bytes_sent: u64,
     pub bytes_received: u64,
+    pub tx_bytes_per_sec: u64,
+    pub rx_bytes_per_sec: u64,
     pub packets_sent: u64,
     pub packets_received: u64,
     pub errors_in: u64,
     pub errors_out: u64,
+    /// IPv4 addresses first, then IPv6
+    #[serde(default)]
+    pub addresses: Vec<IpAddr>,
+}
+
+/// Where an interface address is reachable from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddressScope {
+    Loopback,
+    /// 169.254.0.0/16 or fe80::/10, only valid on the attached link
+    LinkLocal,
+    /// Anything else, private ranges included
+    Global,
+}
+
+impl AddressScope {
+    pub fn of(address: &IpAddr) -> Self {
+        match address {
+            _ if address.is_loopback() => AddressScope::Loopback,
+            IpAddr::V4(v4) if v4.is_link_local() => AddressScope::LinkLocal,
+            IpAddr::V6(v6) if v6.segments()[0] & 0xffc0 == 0xfe80 => AddressScope::LinkLocal,
+            _ => AddressScope::Global,
+        }
+    }
+}
+
+/// An address as listed under its interface, e.g. `fe80::1 (link-local)`
+pub fn format_address(address: &IpAddr) -> String {
+    match AddressScope::of(address) {
+        AddressScope::Loopback => format!("{} (loopback)", address),
+        AddressScope::LinkLocal => format!("{} (link-local)", address),
+        AddressScope::Global => address.to_string(),
+    }
+}
+
+/// IPv4 before IPv6, each in address order
+pub fn sort_addresses(addresses: &mut [IpAddr]) {
+    addresses.sort_by_key(|address| (address.is_ipv6(), *address));
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -36,6 +79,8 @@ pub struct DiskIoMetrics {
     pub write_bytes: u64,
     pub read_ops: u64,
     pub write_ops: u64,
+    pub read_bytes_per_sec: u64,
+    pub write_bytes_per_sec: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -47,13 +92,50 @@ pub struct UsbIoMetrics {
     pub bytes_transferred: u64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Static host identity, collected once at startup
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SystemInfo {
+    pub hostname: String,
+    pub kernel_version: String,
+    pub os_name: String,
+    pub os_version: String,
+    pub architecture: String,
+    pub cpu_model: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct MemoryMetrics {
     pub total: u64,
     pub used: u64,
     pub available: u64,
     pub swap_total: u64,
     pub swap_used: u64,
+    /// One entry per NUMA node; a single entry on non-NUMA machines
+    #[serde(default)]
+    pub numa_nodes: Vec<NumaNode>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NumaNode {
+    pub id: u32,
+    pub total: u64,
+    pub free: u64,
+}
+
+impl MemoryMetrics {
+    /// Used memory as a percentage of the total, 0 when the total is unknown
+    pub fn used_percent(&self) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        self.used as f64 / self.total as f64 * 100.0
+    }
+}
+
+impl NumaNode {
+    pub fn used(&self) -> u64 {
+        self.total.saturating_sub(self.free)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -78,18 +160,6 @@ impl Default for CpuMetrics {
     }
 }
 
-impl Default for MemoryMetrics {
-    fn default() -> Self {
-        Self {
-            total: 0,
-            used: 0,
-            available: 0,
-            swap_total: 0,
-            swap_used: 0,
-        }
-    }
-}
-
 impl Default for SystemMetrics {
     fn default() -> Self {
         Self {
@@ -103,3 +173,64 @@ impl Default for SystemMetrics {
         }
     }
 }
+
+/// Values shown on the dashboard gauges
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct GaugeValues {
+    pub cpu_usage: f32,
+    pub memory_used: u64,
+    pub memory_total: u64,
+    pub temperature: Option<f32>,
+}
+
+impl From<&SystemMetrics> for GaugeValues {
+    fn from(metrics: &SystemMetrics) -> Self {
+        Self {
+            cpu_usage: metrics.cpu.total_usage,
+            memory_used: metrics.memory.used,
+            memory_total: metrics.memory.total,
+            temperature: metrics.cpu.temperature,
+        }
+    }
+}
+
+/// Exponential smoothing for the dashboard gauges.
+///
+/// Only the displayed values are smoothed; `SystemMetrics` itself is left raw so the
+/// detector still sees every spike.
+#[derive(Debug, Clone)]
+pub struct GaugeSmoothing {
+    cpu: Ema,
+    memory: Ema,
+    temperature: Ema,
+}
+
+impl GaugeSmoothing {
+    pub const DEFAULT_ALPHA: f64 = 0.3;
+
+    pub fn new(alpha: f64) -> Self {
+        Self {
+            cpu: Ema::new(alpha),
+            memory: Ema::new(alpha),
+            temperature: Ema::new(alpha),
+        }
+    }
+
+    /// Feed one metrics sample and return the smoothed gauge values
+    pub fn update(&mut self, metrics: &SystemMetrics) -> GaugeValues {
+        let temperature = match metrics.cpu.temperature {
+            Some(temp) => Some(self.temperature.update(temp as f64) as f32),
+            None => {
+                self.temperature.reset();
+                None
+            }
+        };
+
+        GaugeValues {
+            cpu_usage: self.cpu.update(metrics.cpu.total_usage as f64) as f32,
+            memory_used: self.memory.update(metrics.memory.used as f64) as u64,
+            memory_total: metrics.memory.total,
+            temperature,
+        }
+    }
+}
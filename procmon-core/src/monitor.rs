@@ -1,53 +1,213 @@
 use crate::metrics::*;
-use crate::process::{ProcessInfo, ProcessStats, ProcessSnapshot, ProcessStatus};
+use crate::container::{detect_container, parse_container_id};
+use crate::detector::glob_matches;
+use crate::faults::{parse_page_faults, FaultHistory};
+use crate::growth::MemoryGrowth;
+use crate::own::{mark_own_processes, SelfProcesses};
+use crate::netio::{read_tcp_sockets, NetworkIoTracker};
+use crate::gpu::{collect_gpu_clients, gpu_memory_by_pid, GpuProcessSource, GPU_PROCESS_SCAN_INTERVAL};
+use crate::process::{resolve_process_name, ProcessInfo, ProcessStats, ProcessSnapshot, ProcessStatus};
+use crate::throttle::{ThrottleGroups, CGROUP_ROOT};
+use crate::util::{counter_delta, rate_per_sec, stat_field, RollingAverages, SourceBackoff};
 use anyhow::Result;
 use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use sysinfo::{System, Process, Pid, Networks, Disks};
+use std::time::{Duration, Instant};
+use sysinfo::{System, Process, Pid, Networks, Disks, Users};
+
+const DELETED_SUFFIX: &str = " (deleted)";
+
+/// Refreshes averaged into `ProcessStats::cpu_usage_avg`
+pub const CPU_AVERAGE_WINDOW: usize = 5;
+
+/// Which collectors run on every refresh; turning some off reduces overhead on small systems.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MonitorConfig {
+    /// GPU usage and VRAM from `/sys/class/drm`, and per-process GPU memory
+    pub collect_gpu: bool,
+    /// USB devices from `/sys/bus/usb/devices`
+    pub collect_usb: bool,
+    /// Per-device `/proc/diskstats` rates and per-process disk I/O
+    pub collect_disk_io: bool,
+    /// Per-interface network counters, and per-process TCP traffic from `ss`
+    pub collect_network: bool,
+    /// CPU temperature from thermal zones and hwmon
+    pub collect_temperature: bool,
+}
+
+impl Default for MonitorConfig {
+    fn default() -> Self {
+        Self {
+            collect_gpu: true,
+            collect_usb: true,
+            collect_disk_io: true,
+            collect_network: true,
+            collect_temperature: true,
+        }
+    }
+}
+
+/// When per-process GPU memory was read, and the bytes per PID
+type GpuMemorySample = (Instant, HashMap<u32, u64>);
 
 pub struct SystemMonitor {
+    config: MonitorConfig,
     system: Arc<RwLock<System>>,
     networks: Arc<RwLock<Networks>>,
     disks: Arc<RwLock<Disks>>,
     previous_disk_stats: Arc<RwLock<HashMap<String, (u64, u64)>>>,
     previous_net_stats: Arc<RwLock<HashMap<String, (u64, u64)>>>,
+    last_rate_sample: Arc<RwLock<Option<Instant>>>,
+    last_process_refresh: Arc<RwLock<Option<Instant>>>,
+    /// Time between the last two process refreshes, which sysinfo's per-process disk counters span
+    process_refresh_gap: Arc<RwLock<Option<Duration>>>,
+    cpu_averages: Arc<RwLock<RollingAverages>>,
+    fault_history: Arc<RwLock<FaultHistory>>,
+    memory_growth: Arc<RwLock<MemoryGrowth>>,
+    gpu_sources: Arc<Vec<Box<dyn GpuProcessSource>>>,
+    /// Per-PID GPU memory and when it was read, rescanned every [`GPU_PROCESS_SCAN_INTERVAL`]
+    gpu_memory: Arc<RwLock<Option<GpuMemorySample>>>,
+    /// Per-process TCP traffic, sampled from `ss` on each refresh
+    network_io: Arc<RwLock<NetworkIoTracker>>,
+    ss_backoff: Arc<RwLock<SourceBackoff>>,
+    /// Cgroups of processes throttled with [`Self::throttle_io`]
+    throttle_groups: Arc<RwLock<ThrottleGroups>>,
+    /// Whether procmon's own processes are tagged or left out of the process list
+    self_processes: SelfProcesses,
+    /// Name globs of processes left out of the process table
+    ignored: Vec<String>,
+    /// Whether procmon runs in a container, where host-level data may be missing
+    in_container: bool,
+    users: Users,
 }
 
 impl SystemMonitor {
     pub fn new() -> Self {
+        Self::with_config(MonitorConfig::default())
+    }
+
+    pub fn with_config(config: MonitorConfig) -> Self {
         // Start with empty system, we'll populate it on first refresh
         let system = System::new();
+        let networks = if config.collect_network { Networks::new_with_refreshed_list() } else { Networks::new() };
+        let in_container = detect_container();
+        if in_container {
+            tracing::info!("Running in a container; host-level data may be unavailable");
+        }
 
         Self {
+            config,
             system: Arc::new(RwLock::new(system)),
-            networks: Arc::new(RwLock::new(Networks::new_with_refreshed_list())),
+            networks: Arc::new(RwLock::new(networks)),
             disks: Arc::new(RwLock::new(Disks::new_with_refreshed_list())),
             previous_disk_stats: Arc::new(RwLock::new(HashMap::new())),
             previous_net_stats: Arc::new(RwLock::new(HashMap::new())),
+            last_rate_sample: Arc::new(RwLock::new(None)),
+            last_process_refresh: Arc::new(RwLock::new(None)),
+            process_refresh_gap: Arc::new(RwLock::new(None)),
+            cpu_averages: Arc::new(RwLock::new(RollingAverages::new(CPU_AVERAGE_WINDOW))),
+            fault_history: Arc::new(RwLock::new(FaultHistory::default())),
+            memory_growth: Arc::new(RwLock::new(MemoryGrowth::default())),
+            gpu_sources: Arc::new(if config.collect_gpu { crate::gpu::default_sources() } else { Vec::new() }),
+            gpu_memory: Arc::new(RwLock::new(None)),
+            network_io: Arc::new(RwLock::new(NetworkIoTracker::default())),
+            ss_backoff: Arc::new(RwLock::new(SourceBackoff::new("ss", Duration::from_millis(500)))),
+            throttle_groups: Arc::new(RwLock::new(ThrottleGroups::default())),
+            self_processes: SelfProcesses::default(),
+            ignored: Vec::new(),
+            in_container,
+            users: Users::new_with_refreshed_list(),
         }
     }
 
+    pub fn config(&self) -> MonitorConfig {
+        self.config
+    }
+
+    /// Whether procmon runs inside a container, so `/proc` and sysfs may show only part of the
+    /// host and the container's users may not match the host's uids
+    pub fn in_container(&self) -> bool {
+        self.in_container
+    }
+
+    /// How procmon itself and the commands it runs appear in [`Self::get_all_processes`]
+    pub fn set_self_processes(&mut self, mode: SelfProcesses) {
+        self.self_processes = mode;
+    }
+
+    /// Leave processes whose name matches one of the `patterns` (globs) out of
+    /// [`get_all_processes`](Self::get_all_processes)
+    pub fn set_ignored(&mut self, patterns: Vec<String>) {
+        self.ignored = patterns;
+    }
+
+    /// Create a monitor whose first snapshot already has valid CPU numbers.
+    ///
+    /// CPU usage is computed from the difference between two samples, so a plain
+    /// `new()` followed by one `refresh()` reports 0% everywhere. This refreshes,
+    /// waits sysinfo's minimum update interval and refreshes again.
+    pub fn new_warmed() -> Self {
+        let monitor = Self::new();
+        monitor.warm_up();
+        monitor
+    }
+
+    /// Take two samples spaced by the minimum CPU update interval.
+    pub fn warm_up(&self) {
+        self.refresh();
+        std::thread::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL);
+        self.refresh();
+    }
+
     pub fn refresh(&self) {
         let mut system = self.system.write();
-        // IMPORTANT: We need to completely rebuild the process list to avoid stale PIDs
-        // sysinfo has a known issue where it doesn't properly remove terminated processes
-        // So we clear the process list and rebuild it from scratch
-        use sysinfo::{ProcessRefreshKind, RefreshKind, MemoryRefreshKind, CpuRefreshKind};
-
-        // Create a completely fresh system to avoid accumulated stale processes
-        *system = System::new_with_specifics(RefreshKind::new()
-            .with_processes(ProcessRefreshKind::everything())
-            .with_memory(MemoryRefreshKind::everything())
-            .with_cpu(CpuRefreshKind::everything()));
+        use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, MemoryRefreshKind, CpuRefreshKind};
+
+        // Refresh the existing system in place so CPU usage can be computed against the
+        // previous sample. sysinfo does not drop terminated processes on its own, so we
+        // ask it to remove every process that was not seen during this refresh.
+        system.refresh_memory_specifics(MemoryRefreshKind::everything());
+        system.refresh_cpu_specifics(CpuRefreshKind::everything());
+        let process_refresh = if self.config.collect_disk_io {
+            ProcessRefreshKind::everything()
+        } else {
+            ProcessRefreshKind::everything().without_disk_usage()
+        };
+        system.refresh_processes_specifics(ProcessesToUpdate::All, true, process_refresh);
+        let now = Instant::now();
+        *self.process_refresh_gap.write() = self.last_process_refresh.write().replace(now).map(|prev| now - prev);
 
-        let mut networks = self.networks.write();
-        networks.refresh();
+        // One CPU sample per refresh; processes sysinfo dropped above lose their history
+        let mut cpu_averages = self.cpu_averages.write();
+        for (pid, process) in system.processes() {
+            cpu_averages.record(pid.as_u32(), process.cpu_usage());
+        }
+        cpu_averages.retain(|pid| system.process(Pid::from_u32(pid)).is_some());
+        drop(cpu_averages);
+
+        if self.config.collect_network {
+            let mut networks = self.networks.write();
+            networks.refresh();
+
+            let mut backoff = self.ss_backoff.write();
+            if backoff.is_due(now) {
+                let mut network_io = self.network_io.write();
+                if let Some(sockets) = backoff.record(read_tcp_sockets(), now) {
+                    network_io.update(&sockets, now);
+                }
+                network_io.retain(|pid| system.process(Pid::from_u32(pid)).is_some());
+            }
+        }
 
         let mut disks = self.disks.write();
         disks.refresh();
+
+        // Throttle groups of processes that have exited
+        self.throttle_groups.write().cleanup();
     }
 
     pub fn get_system_metrics(&self) -> Result<SystemMetrics> {
@@ -56,10 +216,23 @@ impl SystemMonitor {
 
         let cpu = self.get_cpu_metrics(&system)?;
         let memory = self.get_memory_metrics(&system)?;
-        let gpus = self.get_gpu_metrics()?;
-        let network = self.get_network_metrics(&networks)?;
-        let disk_io = self.get_disk_io_metrics()?;
-        let usb_io = self.get_usb_io_metrics()?;
+        let gpus = if self.config.collect_gpu { self.get_gpu_metrics()? } else { Vec::new() };
+
+        // Rates are computed against the counters seen by the previous call
+        let now = Instant::now();
+        let elapsed = self.last_rate_sample.write().replace(now).map(|prev| now - prev);
+
+        let network = if self.config.collect_network {
+            self.get_network_metrics(&networks, elapsed)?
+        } else {
+            HashMap::new()
+        };
+        let disk_io = if self.config.collect_disk_io {
+            self.get_disk_io_metrics(elapsed)?
+        } else {
+            HashMap::new()
+        };
+        let usb_io = if self.config.collect_usb { self.get_usb_io_metrics()? } else { Vec::new() };
 
         Ok(SystemMetrics {
             timestamp: chrono::Utc::now(),
@@ -77,7 +250,7 @@ impl SystemMonitor {
         let total_usage = system.global_cpu_usage();
         let per_core_usage: Vec<f32> = cpus.iter().map(|cpu| cpu.cpu_usage()).collect();
 
-        let temperature = self.read_cpu_temperature();
+        let temperature = if self.config.collect_temperature { self.read_cpu_temperature() } else { None };
         let frequency = cpus.first().map(|cpu| cpu.frequency());
 
         Ok(CpuMetrics {
@@ -88,6 +261,13 @@ impl SystemMonitor {
         })
     }
 
+    /// Re-read memory and swap totals without a full refresh, e.g. after `swapon`
+    pub fn refresh_memory_metrics(&self) -> Result<MemoryMetrics> {
+        let mut system = self.system.write();
+        system.refresh_memory_specifics(sysinfo::MemoryRefreshKind::everything());
+        self.get_memory_metrics(&system)
+    }
+
     fn get_memory_metrics(&self, system: &System) -> Result<MemoryMetrics> {
         Ok(MemoryMetrics {
             total: system.total_memory(),
@@ -95,6 +275,7 @@ impl SystemMonitor {
             available: system.available_memory(),
             swap_total: system.total_swap(),
             swap_used: system.used_swap(),
+            numa_nodes: read_numa_nodes(),
         })
     }
 
@@ -111,9 +292,7 @@ impl SystemMonitor {
                 let name_str = name.to_string_lossy();
 
                 if name_str.starts_with("card") && !name_str.contains('-') {
-                    if let Some(gpu) = self.read_amd_gpu_info(&path) {
-                        gpus.push(gpu);
-                    }
+                    gpus.push(read_drm_gpu(&path.join("device")));
                 }
             }
         }
@@ -121,60 +300,34 @@ impl SystemMonitor {
         Ok(gpus)
     }
 
-    fn read_amd_gpu_info(&self, card_path: &Path) -> Option<GpuMetrics> {
-        let device_path = card_path.join("device");
-
-        let name = fs::read_to_string(device_path.join("product_name"))
-            .or_else(|_| fs::read_to_string(device_path.join("model")))
-            .unwrap_or_else(|_| "Unknown GPU".to_string())
-            .trim()
-            .to_string();
-
-        // Try to read GPU usage
-        let usage = fs::read_to_string(device_path.join("gpu_busy_percent"))
-            .ok()
-            .and_then(|s| s.trim().parse::<f32>().ok())
-            .unwrap_or(0.0);
-
-        // Try to read VRAM usage
-        let memory_used = fs::read_to_string(device_path.join("mem_info_vram_used"))
-            .ok()
-            .and_then(|s| s.trim().parse::<u64>().ok())
-            .unwrap_or(0);
-
-        let memory_total = fs::read_to_string(device_path.join("mem_info_vram_total"))
-            .ok()
-            .and_then(|s| s.trim().parse::<u64>().ok())
-            .unwrap_or(0);
+    fn get_network_metrics(&self, networks: &Networks, elapsed: Option<Duration>) -> Result<HashMap<String, NetworkMetrics>> {
+        let mut result = HashMap::new();
+        let mut previous = self.previous_net_stats.write();
 
-        // Try to read temperature
-        let temperature = fs::read_to_string(device_path.join("hwmon/hwmon0/temp1_input"))
-            .or_else(|_| fs::read_to_string(device_path.join("hwmon/hwmon1/temp1_input")))
-            .ok()
-            .and_then(|s| s.trim().parse::<f32>().ok())
-            .map(|t| t / 1000.0); // Convert from millidegrees
+        for (interface_name, data) in networks.iter() {
+            let bytes_received = data.total_received();
+            let bytes_sent = data.total_transmitted();
 
-        Some(GpuMetrics {
-            name,
-            usage,
-            memory_used,
-            memory_total,
-            temperature,
-        })
-    }
+            let (rx_bytes_per_sec, tx_bytes_per_sec) = match (previous.get(interface_name.as_str()), elapsed) {
+                (Some(&prev), Some(elapsed)) => interface_rates(prev, (bytes_received, bytes_sent), elapsed),
+                _ => (0, 0),
+            };
+            previous.insert(interface_name.to_string(), (bytes_received, bytes_sent));
 
-    fn get_network_metrics(&self, networks: &Networks) -> Result<HashMap<String, NetworkMetrics>> {
-        let mut result = HashMap::new();
+            let mut addresses: Vec<_> = data.ip_networks().iter().map(|network| network.addr).collect();
+            sort_addresses(&mut addresses);
 
-        for (interface_name, data) in networks.iter() {
             let metrics = NetworkMetrics {
                 interface_name: interface_name.to_string(),
-                bytes_sent: data.total_transmitted(),
-                bytes_received: data.total_received(),
+                bytes_sent,
+                bytes_received,
+                rx_bytes_per_sec,
+                tx_bytes_per_sec,
                 packets_sent: data.total_packets_transmitted(),
                 packets_received: data.total_packets_received(),
                 errors_in: data.total_errors_on_received(),
                 errors_out: data.total_errors_on_transmitted(),
+                addresses,
             };
             result.insert(interface_name.to_string(), metrics);
         }
@@ -182,8 +335,9 @@ impl SystemMonitor {
         Ok(result)
     }
 
-    fn get_disk_io_metrics(&self) -> Result<HashMap<String, DiskIoMetrics>> {
+    fn get_disk_io_metrics(&self, elapsed: Option<Duration>) -> Result<HashMap<String, DiskIoMetrics>> {
         let mut result = HashMap::new();
+        let mut previous = self.previous_disk_stats.write();
 
         // Read disk I/O stats from /proc/diskstats on Linux
         if let Ok(content) = fs::read_to_string("/proc/diskstats") {
@@ -202,12 +356,20 @@ impl SystemMonitor {
                     let write_ops = parts[7].parse::<u64>().unwrap_or(0);
                     let write_sectors = parts[9].parse::<u64>().unwrap_or(0);
 
+                    let (read_bytes_per_sec, write_bytes_per_sec) = match (previous.get(&device_name), elapsed) {
+                        (Some(&prev), Some(elapsed)) => sector_rates(prev, (read_sectors, write_sectors), elapsed),
+                        _ => (0, 0),
+                    };
+                    previous.insert(device_name.clone(), (read_sectors, write_sectors));
+
                     let metrics = DiskIoMetrics {
                         device_name: device_name.clone(),
-                        read_bytes: read_sectors * 512, // sectors are 512 bytes
-                        write_bytes: write_sectors * 512,
+                        read_bytes: read_sectors.saturating_mul(512), // sectors are 512 bytes
+                        write_bytes: write_sectors.saturating_mul(512),
                         read_ops,
                         write_ops,
+                        read_bytes_per_sec,
+                        write_bytes_per_sec,
                     };
 
                     result.insert(device_name, metrics);
@@ -261,6 +423,62 @@ impl SystemMonitor {
         Ok(usb_devices)
     }
 
+    /// Cap the disk bandwidth of `pid` (bytes per second, `None` for unlimited) by moving
+    /// it into a cgroup v2 group below its current one. Requires root. Returns the cgroup path.
+    pub fn throttle_io(&self, pid: u32, read_bps: Option<u64>, write_bps: Option<u64>) -> Result<PathBuf> {
+        self.throttle_groups.write().apply(Path::new(CGROUP_ROOT), pid, read_bps, write_bps)
+    }
+
+    /// Lift a throttle set with [`Self::throttle_io`]
+    pub fn remove_io_throttle(&self, pid: u32) -> Result<()> {
+        self.throttle_groups.write().remove(Path::new(CGROUP_ROOT), pid)
+    }
+
+    /// Resource limits of `pid` from `/proc/{pid}/limits` as `(name, soft, hard)`; empty when
+    /// the process is gone
+    pub fn process_limits(&self, pid: u32) -> Vec<(String, crate::limits::LimitValue, crate::limits::LimitValue)> {
+        crate::limits::read_process_limits(pid)
+    }
+
+    /// Send `signal` to every process of `uid`, e.g. to clean up after a logged-out user.
+    ///
+    /// Protected processes, zombies and processes in procmon's own session are left alone,
+    /// see [`crate::kill::user_kill_targets`]. Returns one result per PID signalled.
+    pub fn kill_user_processes(
+        &self,
+        uid: u32,
+        signal: crate::kill::Signal,
+        protected: &crate::protect::ProtectedSet,
+    ) -> Result<Vec<(u32, Result<()>)>> {
+        use crate::kill::{process_session, signal_processes, user_kill_targets};
+
+        let processes = self.get_all_processes()?;
+        let own_session = process_session(std::process::id());
+        let targets = user_kill_targets(&processes, uid, protected, own_session, process_session);
+        Ok(signal_processes(&targets, signal))
+    }
+
+    /// Host name, kernel, distribution, architecture and CPU model
+    pub fn get_system_info(&self) -> SystemInfo {
+        let cpu_model = fs::read_to_string("/proc/cpuinfo")
+            .ok()
+            .and_then(|cpuinfo| parse_cpu_model(&cpuinfo))
+            .or_else(|| {
+                let system = self.system.read();
+                system.cpus().first().map(|cpu| cpu.brand().to_string()).filter(|b| !b.is_empty())
+            })
+            .unwrap_or_else(|| "Unknown".to_string());
+
+        SystemInfo {
+            hostname: System::host_name().unwrap_or_default(),
+            kernel_version: System::kernel_version().unwrap_or_default(),
+            os_name: System::name().unwrap_or_default(),
+            os_version: System::os_version().unwrap_or_default(),
+            architecture: System::cpu_arch().unwrap_or_else(|| std::env::consts::ARCH.to_string()),
+            cpu_model,
+        }
+    }
+
     fn read_cpu_temperature(&self) -> Option<f32> {
         // Try to read from common thermal zones
         for i in 0..10 {
@@ -322,13 +540,50 @@ impl SystemMonitor {
             }
         }
 
-        #[cfg(test)]
-        eprintln!("get_all_processes: sysinfo reported {}, skipped {}, returning {}",
+        tracing::debug!("get_all_processes: sysinfo reported {}, skipped {}, returning {}",
                   total_from_sysinfo, skipped_count, processes.len());
 
+        if !self.gpu_sources.is_empty() {
+            let gpu_memory = self.gpu_memory_by_pid();
+            for process in &mut processes {
+                process.stats.gpu_memory_bytes = gpu_memory.get(&process.info.pid).copied();
+            }
+        }
+        mark_own_processes(&mut processes, std::process::id(), self.self_processes);
+        if !self.ignored.is_empty() {
+            processes.retain(|p| !self.ignored.iter().any(|pattern| glob_matches(pattern, &p.info.name)));
+        }
+
         Ok(processes)
     }
 
+    /// CPU usage and name of each thread of `pid` as of the last refresh, for
+    /// [`crate::threads::read_threads`]. sysinfo lists every thread next to the processes, so
+    /// its per-thread CPU usage is measured on each refresh; this reads nothing from `/proc`.
+    pub fn thread_usage(&self, pid: u32) -> HashMap<u32, (f32, String)> {
+        let system = self.system.read();
+        let Some(tasks) = system.process(Pid::from_u32(pid)).and_then(|process| process.tasks()) else {
+            return HashMap::new();
+        };
+        tasks
+            .iter()
+            .filter_map(|tid| system.process(*tid).map(|task| (tid.as_u32(), (task.cpu_usage(), task.name().to_string_lossy().to_string()))))
+            .collect()
+    }
+
+    /// GPU memory per PID from the GPU sources, re-read at most every [`GPU_PROCESS_SCAN_INTERVAL`]
+    fn gpu_memory_by_pid(&self) -> HashMap<u32, u64> {
+        let mut cached = self.gpu_memory.write();
+        match cached.as_ref() {
+            Some((scanned, memory)) if scanned.elapsed() < GPU_PROCESS_SCAN_INTERVAL => memory.clone(),
+            _ => {
+                let memory = gpu_memory_by_pid(&collect_gpu_clients(&self.gpu_sources));
+                *cached = Some((Instant::now(), memory.clone()));
+                memory
+            }
+        }
+    }
+
     pub fn get_process(&self, pid: u32) -> Result<Option<ProcessSnapshot>> {
         let system = self.system.read();
         let pid = Pid::from_u32(pid);
@@ -337,31 +592,85 @@ impl SystemMonitor {
     }
 
     fn process_to_snapshot(&self, pid: Pid, process: &Process) -> Option<ProcessSnapshot> {
-        let user = self.get_process_user(pid.as_u32());
+        let status = fs::read_to_string(format!("/proc/{}/status", pid.as_u32())).unwrap_or_default();
+        let user = self.get_process_user(&status);
+
+        // The kernel appends " (deleted)" to the exe link target once the binary is removed
+        let exe_link = process.exe().map(|p| p.to_string_lossy().to_string());
+        let exe_deleted = exe_link.as_deref().is_some_and(|link| link.ends_with(DELETED_SUFFIX));
+        let exe_path = exe_link.map(|link| PathBuf::from(link.trim_end_matches(DELETED_SUFFIX)));
+
+        let stat = fs::read_to_string(format!("/proc/{}/stat", pid.as_u32())).unwrap_or_default();
+        let stat_number = |field| stat_field(&stat, field).and_then(|v| v.parse::<i64>().ok());
+        // The stat state tells uninterruptible (D) sleep apart from ordinary sleep
+        let state = stat_field(&stat, 3)
+            .and_then(|state| state.chars().next())
+            .map_or_else(|| self.convert_process_status(process.status()), ProcessStatus::from_stat_char);
+
+        let (minor_faults, major_faults) = parse_page_faults(&stat).unwrap_or((0, 0));
+        let refreshed = *self.last_process_refresh.read();
+        let major_faults_delta =
+            refreshed.map_or(0, |refreshed| self.fault_history.write().delta(refreshed, pid.as_u32(), major_faults));
+        let memory_delta =
+            refreshed.map_or(0, |refreshed| self.memory_growth.write().delta(refreshed, pid.as_u32(), process.memory()));
+
+        let network = self.network_io.read().get(pid.as_u32());
+
+        let command_line: Vec<String> = process.cmd().iter().map(|s| s.to_string_lossy().to_string()).collect();
+        let name = resolve_process_name(
+            pid.as_u32(),
+            &process.name().to_string_lossy(),
+            || fs::read_to_string(format!("/proc/{}/comm", pid.as_u32())).ok(),
+            &command_line,
+        );
 
         let info = ProcessInfo {
             pid: pid.as_u32(),
-            name: process.name().to_string_lossy().to_string(),
+            name,
             user: user.0,
             uid: user.1,
-            exe_path: process.exe().map(|p| p.to_path_buf()),
-            command_line: process.cmd().iter().map(|s| s.to_string_lossy().to_string()).collect(),
-            status: self.convert_process_status(process.status()),
+            gid: parse_status_id(&status, "Gid:").unwrap_or(0),
+            exe_path,
+            exe_deleted,
+            command_line,
+            status: state,
             parent_pid: process.parent().map(|p| p.as_u32()),
+            container: fs::read_to_string(format!("/proc/{}/cgroup", pid.as_u32()))
+                .ok()
+                .and_then(|cgroup| parse_container_id(&cgroup)),
+            // Filled in for all processes at once by get_all_processes
+            is_own: false,
         };
 
         let stats = ProcessStats {
             pid: pid.as_u32(),
             cpu_usage: process.cpu_usage(),
+            cpu_usage_avg: self.cpu_averages.read().average(pid.as_u32()).unwrap_or(process.cpu_usage()),
             memory_usage: process.memory(),
+            memory_delta,
             memory_percent: 0.0, // Calculate if needed
             virtual_memory: process.virtual_memory(),
             disk_read_bytes: process.disk_usage().read_bytes,
             disk_write_bytes: process.disk_usage().written_bytes,
-            network_rx_bytes: 0, // Would need per-process network tracking
-            network_tx_bytes: 0,
-            num_threads: 0, // Not available in sysinfo
-            start_time: chrono::Utc::now(), // Would need to calculate from process start time
+            // sysinfo's read_bytes covers only the span since the previous refresh
+            disk_read_bytes_per_sec: self
+                .process_refresh_gap
+                .read()
+                .map_or(0, |gap| rate_per_sec(process.disk_usage().read_bytes, gap)),
+            network_rx_bytes: network.rx_bytes,
+            network_tx_bytes: network.tx_bytes,
+            network_bytes_per_sec: network.bytes_per_sec,
+            num_threads: stat_number(20).unwrap_or(0) as u32,
+            last_cpu: crate::cores::parse_last_cpu(&stat).unwrap_or(0),
+            nice: stat_number(19).unwrap_or(0) as i32,
+            swap_usage: parse_vm_swap(&status).unwrap_or(0),
+            minor_faults,
+            major_faults,
+            major_faults_delta,
+            // Filled in for all processes at once by get_all_processes
+            gpu_memory_bytes: None,
+            start_time: chrono::DateTime::from_timestamp(process.start_time() as i64, 0)
+                .unwrap_or_else(chrono::Utc::now),
             run_time: std::time::Duration::from_secs(process.run_time()),
         };
 
@@ -372,27 +681,20 @@ impl SystemMonitor {
         })
     }
 
-    fn get_process_user(&self, pid: u32) -> (String, u32) {
-        // Try to read user from /proc
-        let status_path = format!("/proc/{}/status", pid);
-        if let Ok(content) = fs::read_to_string(&status_path) {
-            for line in content.lines() {
-                if line.starts_with("Uid:") {
-                    if let Some(uid_str) = line.split_whitespace().nth(1) {
-                        if let Ok(uid) = uid_str.parse::<u32>() {
-                            let username = self.uid_to_username(uid);
-                            return (username, uid);
-                        }
-                    }
-                }
-            }
+    /// User name and uid from the contents of `/proc/{pid}/status`
+    fn get_process_user(&self, status: &str) -> (String, u32) {
+        match parse_status_id(status, "Uid:") {
+            Some(uid) => (self.uid_to_username(uid), uid),
+            None => ("unknown".to_string(), 0),
         }
-
-        ("unknown".to_string(), 0)
     }
 
     fn uid_to_username(&self, uid: u32) -> String {
-        // Try to read from /etc/passwd
+        if let Some(user) = self.users.iter().find(|user| **user.id() == uid) {
+            return user.name().to_string();
+        }
+
+        // Users added since startup are not in sysinfo's list yet
         if let Ok(content) = fs::read_to_string("/etc/passwd") {
             for line in content.lines() {
                 let parts: Vec<&str> = line.split(':').collect();
@@ -412,7 +714,8 @@ impl SystemMonitor {
     fn convert_process_status(&self, status: sysinfo::ProcessStatus) -> ProcessStatus {
         match status {
             sysinfo::ProcessStatus::Run => ProcessStatus::Running,
-            sysinfo::ProcessStatus::Sleep => ProcessStatus::Sleeping,
+            sysinfo::ProcessStatus::Sleep | sysinfo::ProcessStatus::Idle => ProcessStatus::Sleeping,
+            sysinfo::ProcessStatus::UninterruptibleDiskSleep => ProcessStatus::DiskSleep,
             sysinfo::ProcessStatus::Stop => ProcessStatus::Stopped,
             sysinfo::ProcessStatus::Zombie => ProcessStatus::Zombie,
             sysinfo::ProcessStatus::Dead => ProcessStatus::Dead,
@@ -426,3 +729,111 @@ impl Default for SystemMonitor {
         Self::new()
     }
 }
+
+/// Receive and transmit bytes per second of an interface from two readings of its
+/// `(received, sent)` counters, allowing for a wrap of 32-bit driver counters
+pub fn interface_rates(prev: (u64, u64), cur: (u64, u64), elapsed: Duration) -> (u64, u64) {
+    (
+        rate_per_sec(counter_delta(prev.0, cur.0), elapsed),
+        rate_per_sec(counter_delta(prev.1, cur.1), elapsed),
+    )
+}
+
+/// Read and write bytes per second of a disk from two readings of its `/proc/diskstats`
+/// `(sectors read, sectors written)`, allowing for a wrap of the counters; sectors are 512 bytes
+pub fn sector_rates(prev: (u64, u64), cur: (u64, u64), elapsed: Duration) -> (u64, u64) {
+    (
+        rate_per_sec(counter_delta(prev.0, cur.0).saturating_mul(512), elapsed),
+        rate_per_sec(counter_delta(prev.1, cur.1).saturating_mul(512), elapsed),
+    )
+}
+
+/// CPU model from `/proc/cpuinfo`; ARM kernels report "Model" or "Hardware" instead of "model name"
+pub fn parse_cpu_model(cpuinfo: &str) -> Option<String> {
+    ["model name", "Model", "Hardware"].iter().find_map(|key| {
+        cpuinfo.lines().find_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            (name.trim() == *key && !value.trim().is_empty()).then(|| value.trim().to_string())
+        })
+    })
+}
+
+/// Real id from the `Uid:` or `Gid:` line of `/proc/{pid}/status` (the first of the four ids)
+pub fn parse_status_id(status: &str, key: &str) -> Option<u32> {
+    let line = status.lines().find(|line| line.starts_with(key))?;
+    line[key.len()..].split_whitespace().next()?.parse().ok()
+}
+
+/// Swapped-out memory in bytes from the `VmSwap:` line of `/proc/{pid}/status`.
+///
+/// Kernel threads have no `VmSwap` line at all.
+pub fn parse_vm_swap(status: &str) -> Option<u64> {
+    let line = status.lines().find(|line| line.starts_with("VmSwap:"))?;
+    let mut parts = line["VmSwap:".len()..].split_whitespace();
+    let kb: u64 = parts.next()?.parse().ok()?;
+    match parts.next() {
+        Some("kB") | None => Some(kb * 1024),
+        Some(_) => None,
+    }
+}
+
+/// GPU of a DRM card from its sysfs `device` directory (amdgpu exposes usage and VRAM there).
+/// Values the driver doesn't provide or that can't be read are left `None` rather than shown
+/// as 0.
+pub fn read_drm_gpu(device_path: &Path) -> GpuMetrics {
+    fn read_value<T: std::str::FromStr>(path: PathBuf) -> Option<T> {
+        fs::read_to_string(path).ok()?.trim().parse().ok()
+    }
+
+    let name = fs::read_to_string(device_path.join("product_name"))
+        .or_else(|_| fs::read_to_string(device_path.join("model")))
+        .unwrap_or_else(|_| "Unknown GPU".to_string())
+        .trim()
+        .to_string();
+
+    GpuMetrics {
+        name,
+        usage: read_value(device_path.join("gpu_busy_percent")),
+        memory_used: read_value(device_path.join("mem_info_vram_used")),
+        memory_total: read_value(device_path.join("mem_info_vram_total")),
+        temperature: read_value::<f32>(device_path.join("hwmon/hwmon0/temp1_input"))
+            .or_else(|| read_value(device_path.join("hwmon/hwmon1/temp1_input")))
+            .map(|t| t / 1000.0), // Convert from millidegrees
+    }
+}
+
+/// Memory of every node under `/sys/devices/system/node`, ordered by node id
+fn read_numa_nodes() -> Vec<NumaNode> {
+    let Ok(entries) = fs::read_dir("/sys/devices/system/node") else {
+        return Vec::new();
+    };
+
+    let mut nodes: Vec<NumaNode> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let id = entry.file_name().to_str()?.strip_prefix("node")?.parse().ok()?;
+            let meminfo = fs::read_to_string(entry.path().join("meminfo")).ok()?;
+            parse_node_meminfo(id, &meminfo)
+        })
+        .collect();
+    nodes.sort_by_key(|node| node.id);
+    nodes
+}
+
+/// Parse a node's `meminfo`, whose lines look like `Node 0 MemTotal:  32768000 kB`.
+pub fn parse_node_meminfo(id: u32, meminfo: &str) -> Option<NumaNode> {
+    let prefix = format!("Node {} ", id);
+    let field = |name: &str| -> Option<u64> {
+        let line = meminfo
+            .lines()
+            .find_map(|line| line.strip_prefix(&prefix)?.strip_prefix(name))?;
+        let kb: u64 = line.split_whitespace().next()?.parse().ok()?;
+        Some(kb * 1024)
+    };
+
+    Some(NumaNode {
+        id,
+        total: field("MemTotal:")?,
+        free: field("MemFree:")?,
+    })
+}
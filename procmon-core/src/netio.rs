@@ -0,0 +1,132 @@
+use crate::util::rate_per_sec;
+use anyhow::{bail, Result};
+use std::collections::HashMap;
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+/// Byte counters of one TCP socket as reported by `ss -tinpe`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SocketBytes {
+    pub inode: u64,
+    /// First process holding the socket; a socket shared by several is counted once
+    pub pid: u32,
+    pub received: u64,
+    /// Bytes acknowledged by the peer, so retransmissions are not counted twice
+    pub sent: u64,
+}
+
+/// Network traffic of one process
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProcessNetworkIo {
+    pub rx_bytes: u64,
+    pub tx_bytes: u64,
+    /// Bytes received plus sent per second between the last two samples
+    pub bytes_per_sec: u64,
+}
+
+/// `ss` listing TCP sockets with their owning process, inode and byte counters, no header
+pub(crate) fn ss_command() -> Command {
+    let mut cmd = Command::new("ss");
+    cmd.args(["-t", "-i", "-n", "-p", "-e", "-H"]);
+    cmd
+}
+
+/// Run [`ss_command`] and parse its sockets
+pub fn read_tcp_sockets() -> Result<Vec<SocketBytes>> {
+    let output = ss_command().output()?;
+    if !output.status.success() {
+        bail!("ss failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(parse_ss_sockets(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Sockets from `ss -tinpeH` output, where each socket is a line with its `users:` and `ino:`
+/// followed by an indented line of TCP info holding `bytes_received` and `bytes_acked`.
+/// Sockets without a visible owner (other users' sockets without root) are left out.
+pub fn parse_ss_sockets(output: &str) -> Vec<SocketBytes> {
+    let mut records: Vec<String> = Vec::new();
+    for line in output.lines() {
+        match records.last_mut() {
+            Some(record) if line.starts_with(char::is_whitespace) => {
+                record.push(' ');
+                record.push_str(line.trim());
+            }
+            _ => records.push(line.to_string()),
+        }
+    }
+    records.iter().filter_map(|record| parse_ss_record(record)).collect()
+}
+
+fn parse_ss_record(record: &str) -> Option<SocketBytes> {
+    let pid_start = record.find("pid=")? + "pid=".len();
+    let pid = record[pid_start..].split(|c: char| !c.is_ascii_digit()).next()?.parse().ok()?;
+    let field = |name: &str| {
+        record
+            .split_whitespace()
+            .find_map(|token| token.strip_prefix(name)?.strip_prefix(':')?.parse::<u64>().ok())
+    };
+    Some(SocketBytes {
+        inode: field("ino")?,
+        pid,
+        received: field("bytes_received").unwrap_or(0),
+        sent: field("bytes_acked").or_else(|| field("bytes_sent")).unwrap_or(0),
+    })
+}
+
+/// Per-process TCP traffic built from successive socket samples.
+///
+/// Totals are what procmon has seen a process transfer: a socket's whole counters when it first
+/// shows up, then what it adds between samples, so closed connections still count. Rates cover
+/// only the interval between the last two samples.
+#[derive(Debug, Default)]
+pub struct NetworkIoTracker {
+    sockets: HashMap<u64, SocketBytes>,
+    totals: HashMap<u32, (u64, u64)>,
+    rates: HashMap<u32, u64>,
+    last_sample: Option<Instant>,
+}
+
+impl NetworkIoTracker {
+    pub fn update(&mut self, sockets: &[SocketBytes], now: Instant) {
+        let elapsed: Option<Duration> = self.last_sample.replace(now).map(|prev| now - prev);
+        let mut transferred: HashMap<u32, u64> = HashMap::new();
+        let mut seen = HashMap::with_capacity(sockets.len());
+
+        for socket in sockets {
+            let (received, sent) = match self.sockets.get(&socket.inode) {
+                // Socket counters are 64-bit and never wrap in practice; a decrease means the
+                // inode now names a different socket
+                Some(prev) if prev.pid == socket.pid && socket.received >= prev.received && socket.sent >= prev.sent => {
+                    (socket.received - prev.received, socket.sent - prev.sent)
+                }
+                // New since the previous sample, so everything it carried is recent
+                _ => (socket.received, socket.sent),
+            };
+            let total = self.totals.entry(socket.pid).or_default();
+            total.0 += received;
+            total.1 += sent;
+            // A socket already open at the first sample carried its bytes at some unknown time
+            if elapsed.is_some() {
+                *transferred.entry(socket.pid).or_default() += received + sent;
+            }
+            seen.insert(socket.inode, *socket);
+        }
+
+        self.sockets = seen;
+        self.rates = match elapsed {
+            Some(elapsed) => transferred.into_iter().map(|(pid, bytes)| (pid, rate_per_sec(bytes, elapsed))).collect(),
+            None => HashMap::new(),
+        };
+    }
+
+    pub fn get(&self, pid: u32) -> ProcessNetworkIo {
+        let (rx_bytes, tx_bytes) = self.totals.get(&pid).copied().unwrap_or_default();
+        ProcessNetworkIo { rx_bytes, tx_bytes, bytes_per_sec: self.rates.get(&pid).copied().unwrap_or(0) }
+    }
+
+    /// Forget processes that have exited, so a reused PID starts from zero
+    pub fn retain(&mut self, keep: impl Fn(u32) -> bool) {
+        self.totals.retain(|pid, _| keep(*pid));
+        self.rates.retain(|pid, _| keep(*pid));
+    }
+}
@@ -0,0 +1,79 @@
+use crate::columns::{format_header_line, format_row_line, ProcessColumn};
+use crate::monitor::SystemMonitor;
+use crate::process::ProcessSnapshot;
+use crate::search::matches_search;
+use crate::sort::{sort_processes, SortColumn};
+use anyhow::{Context, Result};
+
+/// Command line flag that prints the process list once and exits instead of starting the UI
+pub const ONCE_FLAG: &str = "--once";
+
+/// Like [`ONCE_FLAG`], printing the processes as a JSON array
+pub const JSON_FLAG: &str = "--json";
+
+/// What `--once` / `--json` print
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OnceOptions {
+    pub json: bool,
+    pub sort: SortColumn,
+    /// Only the first this many processes after sorting
+    pub top: Option<usize>,
+    /// Same matching as the interactive search: name, command line, PID or user
+    pub filter: Option<String>,
+}
+
+/// Options from `--once`, `--json`, `--sort <cpu|mem|disk|pid|name>`, `--top <n>` and
+/// `--filter <text>` in `args`, or `None` when neither `--once` nor `--json` is given.
+pub fn once_options<I>(args: I) -> Result<Option<OnceOptions>>
+where
+    I: IntoIterator<Item = String>,
+{
+    let args: Vec<String> = args.into_iter().collect();
+    let json = args.iter().any(|arg| arg == JSON_FLAG);
+    if !json && !args.iter().any(|arg| arg == ONCE_FLAG) {
+        return Ok(None);
+    }
+
+    let sort = match crate::util::flag_value(args.clone(), "--sort")? {
+        Some(name) => SortColumn::from_name(&name)?,
+        None => SortColumn::Cpu,
+    };
+    let top = match crate::util::flag_value(args.clone(), "--top")? {
+        Some(value) => Some(value.trim().parse().with_context(|| format!("Invalid --top '{}'", value))?),
+        None => None,
+    };
+    let filter = crate::util::flag_value(args, "--filter")?;
+    Ok(Some(OnceOptions { json, sort, top, filter }))
+}
+
+/// Filter, sort and cut `processes` down as `options` ask, in that order
+pub fn select_processes(mut processes: Vec<ProcessSnapshot>, options: &OnceOptions) -> Vec<ProcessSnapshot> {
+    if let Some(filter) = &options.filter {
+        processes.retain(|process| matches_search(&process.info, filter));
+    }
+    sort_processes(&mut processes, options.sort, options.sort.default_ascending());
+    if let Some(top) = options.top {
+        processes.truncate(top);
+    }
+    processes
+}
+
+/// The output for `processes`: a JSON array, or a table with `columns`
+pub fn render_processes(processes: &[ProcessSnapshot], options: &OnceOptions, columns: &[ProcessColumn]) -> Result<String> {
+    if options.json {
+        return Ok(serde_json::to_string_pretty(processes)?);
+    }
+    let mut text = format_header_line(columns, false);
+    for process in processes {
+        text.push('\n');
+        text.push_str(&format_row_line(columns, process, false));
+    }
+    Ok(text)
+}
+
+/// Sample the processes once and render them as `options` ask
+pub fn run_once(options: &OnceOptions, columns: &[ProcessColumn]) -> Result<String> {
+    let monitor = SystemMonitor::new_warmed();
+    let processes = select_processes(monitor.get_all_processes()?, options);
+    render_processes(&processes, options, columns)
+}
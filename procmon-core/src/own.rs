@@ -0,0 +1,93 @@
+use crate::process::ProcessSnapshot;
+use anyhow::{bail, Result};
+use std::collections::{HashMap, HashSet};
+
+/// Environment variable holding how procmon's own processes are treated: `include`, `tag` or `hide`
+pub const SELF_PROCESSES_ENV: &str = "PROCMON_SELF_PROCESSES";
+
+/// How procmon itself and the commands it runs (`lsblk`, `systemctl`, `df`, ...) show up
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SelfProcesses {
+    /// Listed and checked like any other process
+    Include,
+    /// Listed but marked, and never alerted on
+    #[default]
+    Tag,
+    /// Left out of the process list and never alerted on
+    Hide,
+}
+
+impl SelfProcesses {
+    pub const ALL: [SelfProcesses; 3] = [SelfProcesses::Include, SelfProcesses::Tag, SelfProcesses::Hide];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SelfProcesses::Include => "include",
+            SelfProcesses::Tag => "tag",
+            SelfProcesses::Hide => "hide",
+        }
+    }
+
+    /// Look up a mode by name, ignoring case
+    pub fn from_name(name: &str) -> Result<SelfProcesses> {
+        let lower = name.trim().to_lowercase();
+        match Self::ALL.iter().find(|mode| mode.name() == lower) {
+            Some(mode) => Ok(*mode),
+            None => bail!("Unknown self-processes mode '{}' (expected include, tag or hide)", name),
+        }
+    }
+
+    /// Whether alerts about procmon's own processes are raised
+    pub fn alerts(&self) -> bool {
+        *self == SelfProcesses::Include
+    }
+}
+
+/// Pick the mode from `--self-processes <mode>` / `--self-processes=<mode>` in `args`, falling
+/// back to the `preference` (normally `PROCMON_SELF_PROCESSES`) and then tagging.
+pub fn self_processes<I>(args: I, preference: Option<&str>) -> Result<SelfProcesses>
+where
+    I: IntoIterator<Item = String>,
+{
+    match crate::util::flag_value(args, "--self-processes")?.as_deref().or(preference) {
+        Some(name) => SelfProcesses::from_name(name),
+        None => Ok(SelfProcesses::default()),
+    }
+}
+
+/// `own_pid` and every listed process descending from it
+pub fn own_process_tree(processes: &[ProcessSnapshot], own_pid: u32) -> HashSet<u32> {
+    let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+    for process in processes {
+        if let Some(parent) = process.info.parent_pid {
+            children.entry(parent).or_default().push(process.info.pid);
+        }
+    }
+
+    let mut own = HashSet::from([own_pid]);
+    let mut pending = vec![own_pid];
+    while let Some(pid) = pending.pop() {
+        for child in children.get(&pid).into_iter().flatten() {
+            if own.insert(*child) {
+                pending.push(*child);
+            }
+        }
+    }
+    own
+}
+
+/// Apply `mode` to `own_pid` and its descendants: set `is_own` on them when tagging, drop them
+/// from `processes` when hiding, and leave them like any other process when including
+pub fn mark_own_processes(processes: &mut Vec<ProcessSnapshot>, own_pid: u32, mode: SelfProcesses) {
+    if mode == SelfProcesses::Include {
+        return;
+    }
+    let own = own_process_tree(processes, own_pid);
+    if mode == SelfProcesses::Hide {
+        processes.retain(|process| !own.contains(&process.info.pid));
+        return;
+    }
+    for process in processes.iter_mut() {
+        process.info.is_own = own.contains(&process.info.pid);
+    }
+}
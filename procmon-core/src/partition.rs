@@ -1,7 +1,27 @@
-use anyhow::Result;
+use crate::privilege::{escalated_command, is_root, privileged_actions_enabled};
+use crate::util::output_with_timeout;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::process::Command;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+const FSTAB_PATH: &str = "/etc/fstab";
+
+const SYS_BLOCK: &str = "/sys/block";
+
+/// Freshly formatted partitions are mounted in a directory below this one
+pub const TEMP_MOUNT_ROOT: &str = "/mnt/procmon";
+
+/// Columns requested from lsblk
+const LSBLK_COLUMNS: &str = "NAME,TYPE,SIZE,FSTYPE,LABEL,MOUNTPOINT,MODEL";
+/// Columns every lsblk with JSON output knows, used when the full list is rejected
+const LSBLK_BASIC_COLUMNS: &str = "NAME,TYPE,SIZE,MOUNTPOINT";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Partition {
@@ -24,47 +44,82 @@ pub struct Disk {
     pub logical_sector_size: u32,
     pub physical_sector_size: u32,
     pub partitions: Vec<Partition>,
+    /// Removable media or attached over USB, so it can be ejected
+    #[serde(default)]
+    pub removable: bool,
+}
+
+/// Result of a sequential read benchmark
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BenchmarkResult {
+    pub mb_per_sec: f64,
 }
 
+#[derive(Debug, Clone)]
 pub struct PartitionManager {
+    /// Command prefix (e.g. `sudo -n`) for partition changes when not running as root
+    escalation: Option<Vec<String>>,
 }
 
 impl PartitionManager {
     pub fn new() -> Self {
-        Self {}
+        Self { escalation: None }
+    }
+
+    pub fn set_escalation(&mut self, escalation: Option<Vec<String>>) {
+        self.escalation = escalation;
+    }
+
+    /// Whether Format/Delete/Check/Create can run: as root, or through the escalation command
+    pub fn can_modify(&self) -> bool {
+        privileged_actions_enabled(is_root(), self.escalation.as_deref())
+    }
+
+    /// `cmd` prefixed with the escalation command unless procmon is root
+    fn privileged(&self, cmd: &Command) -> Command {
+        escalated_command(cmd, self.escalation.as_deref(), is_root())
     }
 
     /// List all block devices and their partitions
+    ///
+    /// Older lsblk versions reject columns they do not know (e.g. MODEL), failing the whole
+    /// call; those get a reduced column list, and the missing fields their defaults.
     pub fn list_disks(&self) -> Result<Vec<Disk>> {
-        let mut disks = Vec::new();
-
         // Use lsblk to get block device information
-        let output = Command::new("lsblk")
-            .args(&["-J", "-b", "-o", "NAME,TYPE,SIZE,FSTYPE,LABEL,MOUNTPOINT,MODEL"])
-            .output()?;
+        let mut output = lsblk_command(LSBLK_COLUMNS).output()?;
+        if !output.status.success() {
+            tracing::warn!(
+                "lsblk rejected the full column list ({}); retrying with {}",
+                String::from_utf8_lossy(&output.stderr).trim(),
+                LSBLK_BASIC_COLUMNS
+            );
+            output = lsblk_command(LSBLK_BASIC_COLUMNS).output()?;
+        }
+        if !output.status.success() {
+            anyhow::bail!("lsblk failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+        }
 
-        if output.status.success() {
-            let json_str = String::from_utf8_lossy(&output.stdout);
-            if let Ok(lsblk_data) = serde_json::from_str::<serde_json::Value>(&json_str) {
-                if let Some(blockdevices) = lsblk_data["blockdevices"].as_array() {
-                    for device in blockdevices {
-                        if device["type"].as_str() == Some("disk") {
-                            disks.push(self.parse_disk(device)?);
-                        }
-                    }
+        self.parse_lsblk_disks(&String::from_utf8_lossy(&output.stdout))
+    }
+
+    /// Disks in `lsblk -J` output; columns missing from it fall back to defaults
+    pub fn parse_lsblk_disks(&self, json: &str) -> Result<Vec<Disk>> {
+        let lsblk_data: serde_json::Value = serde_json::from_str(json).context("Invalid lsblk JSON")?;
+        let mut disks = Vec::new();
+        if let Some(blockdevices) = lsblk_data["blockdevices"].as_array() {
+            for device in blockdevices {
+                if device["type"].as_str() == Some("disk") {
+                    disks.push(self.parse_disk(device)?);
                 }
             }
         }
-
         Ok(disks)
     }
 
     fn parse_disk(&self, device: &serde_json::Value) -> Result<Disk> {
         let device_name = device["name"].as_str().unwrap_or("unknown").to_string();
         let model = device["model"].as_str().unwrap_or("Unknown").trim().to_string();
-        let size_bytes = device["size"].as_str()
-            .and_then(|s| s.parse::<u64>().ok())
-            .unwrap_or(0);
+        let size_bytes = lsblk_size(&device["size"]);
 
         // Get sector sizes from sysfs
         let (logical_sector_size, physical_sector_size) = self.get_sector_sizes(&device_name);
@@ -86,19 +141,15 @@ impl PartitionManager {
             logical_sector_size,
             physical_sector_size,
             partitions,
+            removable: is_removable(Path::new(SYS_BLOCK), &device_name),
         })
     }
 
     fn parse_partition(&self, part: &serde_json::Value, parent_device: &str) -> Option<Partition> {
         let name = part["name"].as_str()?;
-        let size_bytes = part["size"].as_str()
-            .and_then(|s| s.parse::<u64>().ok())
-            .unwrap_or(0);
+        let size_bytes = lsblk_size(&part["size"]);
 
-        // Extract partition number
-        let partition_number = name.trim_start_matches(parent_device)
-            .trim_start_matches('p')
-            .parse::<u32>().ok();
+        let partition_number = partition_number(name, parent_device);
 
         // Get filesystem info
         let filesystem = part["fstype"].as_str().map(|s| s.to_string());
@@ -145,7 +196,7 @@ impl PartitionManager {
     fn get_partition_info(&self, device: &str) -> (Option<String>, Vec<String>) {
         // Use parted to get partition type and flags
         let output = Command::new("parted")
-            .args(&[device, "print"])
+            .args([device, "print"])
             .output();
 
         if let Ok(output) = output {
@@ -169,7 +220,7 @@ impl PartitionManager {
 
     fn get_used_space(&self, mount_point: &str) -> Option<u64> {
         let output = Command::new("df")
-            .args(&["-B1", mount_point])
+            .args(["-B1", mount_point])
             .output()
             .ok()?;
 
@@ -187,12 +238,52 @@ impl PartitionManager {
         None
     }
 
+    /// Network filesystems from `/proc/mounts`, without their usage: running `df` on them is
+    /// left to [`Self::check_network_mount`] so a dead server can't stall the disk list
+    pub fn list_network_mounts(&self) -> Result<Vec<NetworkMount>> {
+        let mounts = fs::read_to_string("/proc/mounts").context("Failed to read /proc/mounts")?;
+        Ok(parse_network_mounts(&mounts))
+    }
+
+    /// `(used, size)` of a network mount from `df`, giving up after [`NETWORK_DF_TIMEOUT`]
+    pub fn check_network_mount(&self, mount_point: &str) -> Result<(u64, u64)> {
+        let output = output_with_timeout(Command::new("df").args(["-P", "-B1", mount_point]), NETWORK_DF_TIMEOUT)?;
+        if !output.status.success() {
+            anyhow::bail!("df {} failed: {}", mount_point, String::from_utf8_lossy(&output.stderr).trim());
+        }
+        parse_df_usage(&String::from_utf8_lossy(&output.stdout))
+            .with_context(|| format!("Unexpected df output for {}", mount_point))
+    }
+
+    /// Start `du -x` on `path` down to `depth` levels, so other filesystems mounted below it
+    /// aren't counted; see [`DirectoryScan`] for collecting the result.
+    pub fn scan_directories(&self, path: &Path, depth: u32) -> Result<DirectoryScan> {
+        let mut child = Command::new("du")
+            .arg("-x")
+            .arg("-B1")
+            .arg(format!("--max-depth={}", depth))
+            .arg(path)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .context("Failed to run du")?;
+        let read_all = |mut pipe: Box<dyn Read + Send>| {
+            std::thread::spawn(move || {
+                let mut buffer = Vec::new();
+                let _ = pipe.read_to_end(&mut buffer);
+                buffer
+            })
+        };
+        let stdout = read_all(Box::new(child.stdout.take().expect("stdout is piped")));
+        let stderr = read_all(Box::new(child.stderr.take().expect("stderr is piped")));
+        Ok(DirectoryScan { child, stdout: Some(stdout), stderr: Some(stderr), path: path.to_path_buf(), started: Instant::now() })
+    }
+
     /// Create a new partition table (WARNING: destroys all data)
     pub fn create_partition_table(&self, device: &str, table_type: &str) -> Result<()> {
         // table_type can be: gpt, msdos, etc.
-        let output = Command::new("parted")
-            .args(&["-s", device, "mklabel", table_type])
-            .output()?;
+        let output = self.privileged(Command::new("parted").args(["-s", device, "mklabel", table_type])).output()?;
 
         if !output.status.success() {
             anyhow::bail!("Failed to create partition table: {}", String::from_utf8_lossy(&output.stderr));
@@ -209,8 +300,8 @@ impl PartitionManager {
         end: &str,
         fs_type: &str,
     ) -> Result<()> {
-        let output = Command::new("parted")
-            .args(&["-s", device, "mkpart", "primary", fs_type, start, end])
+        let output = self
+            .privileged(Command::new("parted").args(["-s", device, "mkpart", "primary", fs_type, start, end]))
             .output()?;
 
         if !output.status.success() {
@@ -222,8 +313,8 @@ impl PartitionManager {
 
     /// Delete a partition
     pub fn delete_partition(&self, device: &str, partition_number: u32) -> Result<()> {
-        let output = Command::new("parted")
-            .args(&["-s", device, "rm", &partition_number.to_string()])
+        let output = self
+            .privileged(Command::new("parted").args(["-s", device, "rm", &partition_number.to_string()]))
             .output()?;
 
         if !output.status.success() {
@@ -241,7 +332,7 @@ impl PartitionManager {
         end: &str,
     ) -> Result<()> {
         let output = Command::new("parted")
-            .args(&["-s", device, "resizepart", &partition_number.to_string(), end])
+            .args(["-s", device, "resizepart", &partition_number.to_string(), end])
             .output()?;
 
         if !output.status.success() {
@@ -253,40 +344,38 @@ impl PartitionManager {
 
     /// Format a partition with specified filesystem
     pub fn format_partition(&self, device: &str, filesystem: &str, label: Option<&str>) -> Result<()> {
-        let mut args = vec![device];
-
         match filesystem {
             "ext2" | "ext3" | "ext4" => {
-                let mut cmd = Command::new(&format!("mkfs.{}", filesystem));
+                let mut cmd = Command::new(format!("mkfs.{}", filesystem));
                 if let Some(lbl) = label {
-                    cmd.args(&["-L", lbl]);
+                    cmd.args(["-L", lbl]);
                 }
                 cmd.arg(device);
-                let output = cmd.output()?;
+                let output = self.privileged(&cmd).output()?;
                 if !output.status.success() {
                     anyhow::bail!("Failed to format: {}", String::from_utf8_lossy(&output.stderr));
                 }
             }
             "xfs" => {
                 let mut cmd = Command::new("mkfs.xfs");
-                cmd.args(&["-f"]);
+                cmd.args(["-f"]);
                 if let Some(lbl) = label {
-                    cmd.args(&["-L", lbl]);
+                    cmd.args(["-L", lbl]);
                 }
                 cmd.arg(device);
-                let output = cmd.output()?;
+                let output = self.privileged(&cmd).output()?;
                 if !output.status.success() {
                     anyhow::bail!("Failed to format: {}", String::from_utf8_lossy(&output.stderr));
                 }
             }
             "btrfs" => {
                 let mut cmd = Command::new("mkfs.btrfs");
-                cmd.args(&["-f"]);
+                cmd.args(["-f"]);
                 if let Some(lbl) = label {
-                    cmd.args(&["-L", lbl]);
+                    cmd.args(["-L", lbl]);
                 }
                 cmd.arg(device);
-                let output = cmd.output()?;
+                let output = self.privileged(&cmd).output()?;
                 if !output.status.success() {
                     anyhow::bail!("Failed to format: {}", String::from_utf8_lossy(&output.stderr));
                 }
@@ -294,34 +383,35 @@ impl PartitionManager {
             "f2fs" => {
                 let mut cmd = Command::new("mkfs.f2fs");
                 if let Some(lbl) = label {
-                    cmd.args(&["-l", lbl]);
+                    cmd.args(["-l", lbl]);
                 }
                 cmd.arg(device);
-                let output = cmd.output()?;
+                let output = self.privileged(&cmd).output()?;
                 if !output.status.success() {
                     anyhow::bail!("Failed to format: {}", String::from_utf8_lossy(&output.stderr));
                 }
             }
             "ntfs" => {
                 let mut cmd = Command::new("mkfs.ntfs");
-                cmd.args(&["-f"]);
+                cmd.args(["-f"]);
                 if let Some(lbl) = label {
-                    cmd.args(&["-L", lbl]);
+                    cmd.args(["-L", lbl]);
                 }
                 cmd.arg(device);
-                let output = cmd.output()?;
+                let output = self.privileged(&cmd).output()?;
                 if !output.status.success() {
                     anyhow::bail!("Failed to format: {}", String::from_utf8_lossy(&output.stderr));
                 }
             }
+            "swap" => self.make_swap(device, label)?,
             "fat32" | "vfat" => {
                 let mut cmd = Command::new("mkfs.vfat");
-                cmd.args(&["-F", "32"]);
+                cmd.args(["-F", "32"]);
                 if let Some(lbl) = label {
-                    cmd.args(&["-n", lbl]);
+                    cmd.args(["-n", lbl]);
                 }
                 cmd.arg(device);
-                let output = cmd.output()?;
+                let output = self.privileged(&cmd).output()?;
                 if !output.status.success() {
                     anyhow::bail!("Failed to format: {}", String::from_utf8_lossy(&output.stderr));
                 }
@@ -332,6 +422,36 @@ impl PartitionManager {
         Ok(())
     }
 
+    /// Initialize a partition as swap space
+    pub fn make_swap(&self, device: &str, label: Option<&str>) -> Result<()> {
+        let output = self.privileged(&mkswap_command(device, label)).output()?;
+        if !output.status.success() {
+            anyhow::bail!("Failed to create swap: {}", String::from_utf8_lossy(&output.stderr));
+        }
+
+        Ok(())
+    }
+
+    /// Start paging to a swap partition
+    pub fn swapon(&self, device: &str) -> Result<()> {
+        let output = swapon_command(device).output()?;
+        if !output.status.success() {
+            anyhow::bail!("Failed to enable swap: {}", String::from_utf8_lossy(&output.stderr));
+        }
+
+        Ok(())
+    }
+
+    /// Stop paging to a swap partition
+    pub fn swapoff(&self, device: &str) -> Result<()> {
+        let output = swapoff_command(device).output()?;
+        if !output.status.success() {
+            anyhow::bail!("Failed to disable swap: {}", String::from_utf8_lossy(&output.stderr));
+        }
+
+        Ok(())
+    }
+
     /// Resize filesystem (must be done after partition resize)
     pub fn resize_filesystem(&self, device: &str, filesystem: &str) -> Result<()> {
         match filesystem {
@@ -349,7 +469,7 @@ impl PartitionManager {
             }
             "btrfs" => {
                 let output = Command::new("btrfs")
-                    .args(&["filesystem", "resize", "max", device])
+                    .args(["filesystem", "resize", "max", device])
                     .output()?;
                 if !output.status.success() {
                     anyhow::bail!("Failed to resize filesystem: {}", String::from_utf8_lossy(&output.stderr));
@@ -365,7 +485,7 @@ impl PartitionManager {
     pub fn set_partition_flag(&self, device: &str, partition_number: u32, flag: &str, state: bool) -> Result<()> {
         let state_str = if state { "on" } else { "off" };
         let output = Command::new("parted")
-            .args(&["-s", device, "set", &partition_number.to_string(), flag, state_str])
+            .args(["-s", device, "set", &partition_number.to_string(), flag, state_str])
             .output()?;
 
         if !output.status.success() {
@@ -375,26 +495,61 @@ impl PartitionManager {
         Ok(())
     }
 
+    /// Set the type of partition `partition_number` on `device` to `type_code`, a GPT type
+    /// code such as `EF00` or a full type GUID.
+    ///
+    /// GPT disks are changed with `sgdisk -t`. MBR disks have no type GUIDs, so the parted
+    /// flag with the same meaning is set instead; codes without one are refused there.
+    pub fn set_partition_type(&self, device: &str, partition_number: u32, type_code: &str) -> Result<()> {
+        let code = validate_type_code(type_code)?;
+
+        match self.partition_table_type(device)?.as_str() {
+            "gpt" => {
+                let output = sgdisk_type_command(device, partition_number, &code).output()?;
+                if !output.status.success() {
+                    anyhow::bail!("Failed to set partition type: {}", String::from_utf8_lossy(&output.stderr));
+                }
+            }
+            "dos" => {
+                let Some(flag) = mbr_flag_for_type(&code) else {
+                    anyhow::bail!("Type {} has no MBR equivalent on {}", code, device);
+                };
+                self.set_partition_flag(device, partition_number, flag, true)?;
+            }
+            other => anyhow::bail!("Unsupported partition table '{}' on {}", other, device),
+        }
+
+        Ok(())
+    }
+
+    /// Partition table of a disk as reported by blkid: `gpt` or `dos`
+    pub fn partition_table_type(&self, device: &str) -> Result<String> {
+        let output = blkid_pttype_command(device).output()?;
+        let table = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if !output.status.success() || table.is_empty() {
+            anyhow::bail!("{} has no partition table", device);
+        }
+        Ok(table)
+    }
+
     /// Check filesystem for errors
     pub fn check_filesystem(&self, device: &str, filesystem: &str, repair: bool) -> Result<String> {
         let output = match filesystem {
             "ext2" | "ext3" | "ext4" => {
                 let mut cmd = Command::new("e2fsck");
                 if repair {
-                    cmd.args(&["-p"]); // Automatic repair
+                    cmd.args(["-p"]); // Automatic repair
                 } else {
-                    cmd.args(&["-n"]); // No changes, just check
+                    cmd.args(["-n"]); // No changes, just check
                 }
-                cmd.arg(device).output()?
+                self.privileged(cmd.arg(device)).output()?
             }
             "xfs" => {
-                Command::new("xfs_repair")
-                    .args(&[if repair { "-n" } else { "-n" }, device])
-                    .output()?
+                // xfs_repair is always run in no-modify mode here
+                self.privileged(Command::new("xfs_repair").args(["-n", device])).output()?
             }
             "btrfs" => {
-                Command::new("btrfs")
-                    .args(&["check", if repair { "--repair" } else { "" }, device])
+                self.privileged(Command::new("btrfs").args(["check", if repair { "--repair" } else { "" }, device]))
                     .output()?
             }
             _ => anyhow::bail!("Filesystem check not supported for: {}", filesystem),
@@ -403,9 +558,264 @@ impl PartitionManager {
         Ok(String::from_utf8_lossy(&output.stdout).to_string())
     }
 
+    /// Look up the filesystem UUID of a device
+    pub fn get_partition_uuid(&self, device: &str) -> Option<String> {
+        blkid_value(device, "UUID")
+    }
+
+    /// Filesystem label of `device`, read fresh with blkid so a just-formatted partition has it
+    pub fn get_partition_label(&self, device: &str) -> Option<String> {
+        blkid_value(device, "LABEL")
+    }
+
+    /// Mount `device` at a new directory below [`TEMP_MOUNT_ROOT`] named after its label or
+    /// UUID, with a numeric suffix if that name is taken; returns the mount point. The
+    /// directory is removed again if the mount fails.
+    pub fn mount_temp(&self, device: &str, label: Option<&str>) -> Result<PathBuf> {
+        let uuid = self.get_partition_uuid(device);
+        let base = temp_mount_point(device, label, uuid.as_deref());
+        fs::create_dir_all(TEMP_MOUNT_ROOT).with_context(|| format!("Failed to create {}", TEMP_MOUNT_ROOT))?;
+        let mount_point = create_unique_dir(&base).with_context(|| format!("Failed to create {}", base.display()))?;
+
+        let output = temp_mount_command(device, &mount_point).output()?;
+        if !output.status.success() {
+            let _ = fs::remove_dir(&mount_point);
+            anyhow::bail!("Failed to mount {}: {}", device, String::from_utf8_lossy(&output.stderr));
+        }
+
+        Ok(mount_point)
+    }
+
+    /// Unmount every partition of the removable disk `device` and power it off so it can be
+    /// unplugged. Powering off goes through `udisksctl`; without it the kernel is asked to
+    /// drop the device through its sysfs `delete` node instead.
+    pub fn eject(&self, device: &str) -> Result<()> {
+        let name = device.trim_start_matches("/dev/");
+        if !is_removable(Path::new(SYS_BLOCK), name) {
+            anyhow::bail!("{} is not a removable disk", device);
+        }
+
+        let mounts = fs::read_to_string("/proc/mounts").context("Failed to read /proc/mounts")?;
+        let mut plan = eject_commands(device, &disk_mount_points(&mounts, device));
+        let mut power_off = plan.pop().expect("power-off is the last step");
+        for mut cmd in plan {
+            let output = cmd.output()?;
+            if !output.status.success() {
+                anyhow::bail!("Failed to unmount before ejecting {}: {}", device, String::from_utf8_lossy(&output.stderr).trim());
+            }
+        }
+
+        match power_off.output() {
+            Ok(output) if output.status.success() => Ok(()),
+            Ok(output) => anyhow::bail!("Failed to power off {}: {}", device, String::from_utf8_lossy(&output.stderr).trim()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                tracing::debug!("udisksctl not found, removing {} through sysfs", device);
+                fs::write(Path::new(SYS_BLOCK).join(name).join("device/delete"), "1")
+                    .with_context(|| format!("Failed to remove {} (udisksctl is not installed)", device))
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Unmount a mount made by [`PartitionManager::mount_temp`] and remove its directory
+    pub fn unmount_temp(&self, mount_point: &Path) -> Result<()> {
+        // Resolve symlinks and `..` so the check is about the directory actually unmounted
+        let resolved = fs::canonicalize(mount_point).with_context(|| format!("Failed to resolve {}", mount_point.display()))?;
+        let root = fs::canonicalize(TEMP_MOUNT_ROOT).unwrap_or_else(|_| PathBuf::from(TEMP_MOUNT_ROOT));
+        if !is_temp_mount_point(mount_point) || resolved.parent() != Some(root.as_path()) {
+            anyhow::bail!("{} was not mounted by procmon (only {} is)", mount_point.display(), TEMP_MOUNT_ROOT);
+        }
+
+        for mut cmd in temp_unmount_commands(&resolved) {
+            let output = cmd.output()?;
+            if !output.status.success() {
+                anyhow::bail!("Failed to unmount {}: {}", mount_point.display(), String::from_utf8_lossy(&output.stderr));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Build an `/etc/fstab` line that mounts the filesystem with `device_uuid`
+    pub fn generate_fstab_entry(&self, device_uuid: &str, mount_point: &str, fs: &str, options: Option<&str>) -> String {
+        let options = options.filter(|o| !o.is_empty()).unwrap_or("defaults");
+
+        // Root is checked first, other filesystems afterwards; swap and non-Linux filesystems are never checked
+        let pass = match (fs, mount_point) {
+            ("swap" | "ntfs" | "vfat" | "fat32" | "exfat", _) => 0,
+            (_, "/") => 1,
+            _ => 2,
+        };
+        let mount_point = if fs == "swap" { "none" } else { mount_point };
+        let fs = if fs == "fat32" { "vfat" } else { fs };
+
+        format!("UUID={} {} {} {} 0 {}", device_uuid, mount_point, fs, options, pass)
+    }
+
+    /// Append a mount for `device_uuid` to `/etc/fstab`.
+    ///
+    /// The UUID must belong to an existing device and must not already appear in the
+    /// file. The current file is copied to `/etc/fstab.bak` before it is modified.
+    pub fn append_to_fstab(&self, device_uuid: &str, mount_point: &str, fs: &str, options: Option<&str>) -> Result<()> {
+        validate_fstab_fields(mount_point, fs, options)?;
+        if !Path::new("/dev/disk/by-uuid").join(device_uuid).exists() {
+            anyhow::bail!("No device with UUID {}", device_uuid);
+        }
+
+        let current = fs::read_to_string(FSTAB_PATH)?;
+        if fstab_contains_uuid(&current, device_uuid) {
+            anyhow::bail!("{} already has an entry for UUID {}", FSTAB_PATH, device_uuid);
+        }
+
+        fs::copy(FSTAB_PATH, format!("{}.bak", FSTAB_PATH))?;
+
+        let entry = self.generate_fstab_entry(device_uuid, mount_point, fs, options);
+        let mut file = fs::OpenOptions::new().append(true).open(FSTAB_PATH)?;
+        if !current.is_empty() && !current.ends_with('\n') {
+            writeln!(file)?;
+        }
+        writeln!(file, "{}", entry)?;
+
+        Ok(())
+    }
+
+    /// Check a filesystem while reporting progress through `on_progress`.
+    ///
+    /// e2fsck is run with `-C 0` so it draws a completion bar that can be parsed as it
+    /// runs. Filesystems without progress output are checked with `check_filesystem`
+    /// and report a single 100% update when done.
+    pub fn check_filesystem_with_progress<F>(
+        &self,
+        device: &str,
+        filesystem: &str,
+        repair: bool,
+        mut on_progress: F,
+    ) -> Result<String>
+    where
+        F: FnMut(FsckProgress),
+    {
+        if !matches!(filesystem, "ext2" | "ext3" | "ext4") {
+            let output = self.check_filesystem(device, filesystem, repair)?;
+            on_progress(FsckProgress { pass: None, percent: 100.0 });
+            return Ok(output);
+        }
+
+        let mut child = self
+            .privileged(Command::new("e2fsck").args(["-C", "0", if repair { "-p" } else { "-n" }, device]))
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        // Drained alongside stdout so a chatty e2fsck can't block on a full pipe
+        let stderr = child.stderr.take();
+        let stderr_reader = std::thread::spawn(move || {
+            let mut text = String::new();
+            if let Some(mut stderr) = stderr {
+                let _ = stderr.read_to_string(&mut text);
+            }
+            text
+        });
+
+        let mut parser = FsckProgressParser::default();
+        let mut output = String::new();
+        if let Some(stdout) = child.stdout.take() {
+            for_each_progress_line(stdout, |line| {
+                if let Some(progress) = parser.feed(line) {
+                    on_progress(progress);
+                }
+                // Keep the report text, not every redraw of the completion bar
+                if !line.contains('|') {
+                    output.push_str(line);
+                    output.push('\n');
+                }
+            })?;
+        }
+
+        let status = child.wait()?;
+        let stderr = stderr_reader.join().unwrap_or_default();
+        if let Some(problem) = e2fsck_failure(status.code()) {
+            let detail = if stderr.trim().is_empty() { output.trim() } else { stderr.trim() };
+            anyhow::bail!("{}: {}", problem, detail);
+        }
+        Ok(output)
+    }
+
+    /// Copy the block device `device` to a new image file at `out_path` with `dd`, streaming
+    /// progress on the returned channel until a final [`ImageProgress::Done`] or
+    /// [`ImageProgress::Failed`].
+    ///
+    /// dd only opens the source for reading, and `conv=excl` makes it fail rather than
+    /// overwrite an existing file or device. A source mounted read-write can change under
+    /// the copy, so it is refused unless `force` is set.
+    pub fn image_partition(&self, device: &str, out_path: &Path, force: bool) -> Result<Receiver<ImageProgress>> {
+        if !force {
+            let mounts = fs::read_to_string("/proc/mounts").context("Failed to read /proc/mounts")?;
+            let resolved = fs::canonicalize(device).map(|path| path.to_string_lossy().into_owned());
+            if is_mounted_read_write(&mounts, device) || resolved.is_ok_and(|dev| is_mounted_read_write(&mounts, &dev)) {
+                anyhow::bail!("{} is mounted read-write; unmount it or remount it read-only first", device);
+            }
+        }
+
+        let mut child = self
+            .privileged(&dd_image_command(device, out_path))
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .context("Failed to run dd")?;
+
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let mut copied = 0;
+            let mut errors = String::new();
+            if let Some(stderr) = child.stderr.take() {
+                let _ = for_each_progress_line(stderr, |line| match parse_dd_progress(line) {
+                    Some(bytes) => {
+                        copied = bytes;
+                        let _ = sender.send(ImageProgress::Copied(bytes));
+                    }
+                    None if !line.contains("records in") && !line.contains("records out") => {
+                        errors.push_str(line.trim());
+                        errors.push('\n');
+                    }
+                    None => {}
+                });
+            }
+
+            let done = match child.wait() {
+                Ok(status) if status.success() => ImageProgress::Done(copied),
+                Ok(_) => ImageProgress::Failed(errors.trim().to_string()),
+                Err(e) => ImageProgress::Failed(e.to_string()),
+            };
+            let _ = sender.send(done);
+        });
+
+        Ok(receiver)
+    }
+
+    /// Measure sequential read throughput of a disk with `hdparm -t`.
+    ///
+    /// hdparm only reads, and stops after roughly three seconds of buffered reads, so
+    /// this is safe to run on a disk that is in use.
+    pub fn benchmark_read(&self, device: &str) -> Result<BenchmarkResult> {
+        let output = Command::new("hdparm")
+            .args(["-t", device])
+            .output()?;
+
+        if !output.status.success() {
+            anyhow::bail!("Benchmark failed: {}", String::from_utf8_lossy(&output.stderr));
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        match parse_hdparm_output(&stdout) {
+            Some(mb_per_sec) => Ok(BenchmarkResult { mb_per_sec }),
+            None => anyhow::bail!("Could not parse hdparm output: {}", stdout.trim()),
+        }
+    }
+
     /// Get supported filesystems on this system
     pub fn get_supported_filesystems(&self) -> Vec<String> {
-        let mut filesystems = vec![
+        let filesystems = [
             "ext2", "ext3", "ext4", "xfs", "btrfs", "f2fs",
             "ntfs", "vfat", "fat32", "exfat", "swap"
         ];
@@ -415,6 +825,7 @@ impl PartitionManager {
         for fs in filesystems {
             let binary = match fs {
                 "fat32" | "vfat" => "mkfs.vfat",
+                "swap" => "mkswap",
                 _ => &format!("mkfs.{}", fs),
             };
 
@@ -430,6 +841,610 @@ impl PartitionManager {
     }
 }
 
+/// Extract the read rate in MB/s from `hdparm -t` output, e.g.
+/// ` Timing buffered disk reads: 1540 MB in  3.00 seconds = 513.19 MB/sec`
+pub fn parse_hdparm_output(output: &str) -> Option<f64> {
+    let line = output.lines().find(|line| line.contains("Timing buffered disk reads"))?;
+    let rate = line.rsplit('=').next()?.trim();
+    let mut parts = rate.split_whitespace();
+    let value = parts.next()?.parse::<f64>().ok()?;
+
+    match parts.next()? {
+        "kB/sec" => Some(value / 1024.0),
+        "MB/sec" => Some(value),
+        "GB/sec" => Some(value * 1024.0),
+        _ => None,
+    }
+}
+
+/// Progress of a running filesystem check
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FsckProgress {
+    pub pass: Option<u8>,
+    pub percent: f32,
+}
+
+/// What went wrong in an e2fsck run that exited with `code`, or `None` if the filesystem is
+/// clean or was repaired (bits 1 and 2). `None` for `code` means it was killed by a signal.
+pub fn e2fsck_failure(code: Option<i32>) -> Option<&'static str> {
+    let Some(code) = code else {
+        return Some("e2fsck was killed");
+    };
+    if code & 32 != 0 {
+        Some("e2fsck was canceled")
+    } else if code & 16 != 0 {
+        Some("e2fsck usage or syntax error")
+    } else if code & 8 != 0 || code & 128 != 0 {
+        Some("e2fsck failed to run the check")
+    } else if code & 4 != 0 {
+        Some("Filesystem errors left uncorrected")
+    } else {
+        None
+    }
+}
+
+/// Turns e2fsck `-C 0` output into progress updates.
+///
+/// e2fsck prints a "Pass N: ..." line when each pass starts and redraws a completion
+/// bar ending in the overall percentage, e.g. `/dev/sdb1: |=====     / 12.5%`.
+#[derive(Debug, Default)]
+pub struct FsckProgressParser {
+    pass: Option<u8>,
+    percent: f32,
+}
+
+impl FsckProgressParser {
+    pub fn feed(&mut self, line: &str) -> Option<FsckProgress> {
+        let line = line.trim();
+
+        if let Some(rest) = line.strip_prefix("Pass ") {
+            let pass = rest.split(':').next()?.trim().parse::<u8>().ok()?;
+            self.pass = Some(pass);
+        } else {
+            let percent = line
+                .split_whitespace()
+                .rev()
+                .find_map(|token| token.strip_suffix('%'))?
+                .parse::<f32>()
+                .ok()?;
+            self.percent = percent.clamp(0.0, 100.0);
+        }
+
+        Some(FsckProgress {
+            pass: self.pass,
+            percent: self.percent,
+        })
+    }
+}
+
+/// Split a progress stream into lines on both `\n` and the `\r` used to redraw bars
+pub fn for_each_progress_line<R: Read>(mut reader: R, mut f: impl FnMut(&str)) -> Result<()> {
+    let mut buf = [0u8; 4096];
+    let mut pending = Vec::new();
+
+    loop {
+        let n = reader.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+
+        for &byte in &buf[..n] {
+            if byte == b'\n' || byte == b'\r' {
+                if !pending.is_empty() {
+                    f(&String::from_utf8_lossy(&pending));
+                    pending.clear();
+                }
+            } else {
+                pending.push(byte);
+            }
+        }
+    }
+
+    if !pending.is_empty() {
+        f(&String::from_utf8_lossy(&pending));
+    }
+
+    Ok(())
+}
+
+/// Progress of a partition image started with [`PartitionManager::image_partition`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImageProgress {
+    /// Bytes copied so far
+    Copied(u64),
+    /// dd finished; total bytes copied
+    Done(u64),
+    /// dd failed, with its error output
+    Failed(String),
+}
+
+/// Bytes copied from a `dd status=progress` line, e.g.
+/// `1048576000 bytes (1.0 GB, 1000 MiB) copied, 5 s, 210 MB/s`
+pub fn parse_dd_progress(line: &str) -> Option<u64> {
+    if !line.contains(" copied") {
+        return None;
+    }
+    let mut parts = line.split_whitespace();
+    let bytes = parts.next()?.parse().ok()?;
+    (parts.next()? == "bytes").then_some(bytes)
+}
+
+/// Partition number of the kernel device `name` on the disk `parent`: `sda3` on `sda` is 3,
+/// `nvme0n1p3` on `nvme0n1` and `mmcblk0p2` on `mmcblk0` are 3 and 2.
+///
+/// Disks whose name ends in a digit separate the number with a `p`; stripping any `p` would
+/// also eat into names like `sdp1`. Children that aren't named after the disk (device-mapper,
+/// LVM) have no number.
+pub fn partition_number(name: &str, parent: &str) -> Option<u32> {
+    let suffix = name.strip_prefix(parent)?;
+    let digits = if parent.ends_with(|c: char| c.is_ascii_digit()) {
+        suffix.strip_prefix('p')?
+    } else {
+        suffix
+    };
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok()
+}
+
+/// Whether `/proc/mounts` lists `device` mounted with the `rw` option
+pub fn is_mounted_read_write(mounts: &str, device: &str) -> bool {
+    mounts
+        .lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>())
+        .filter(|fields| fields.first() == Some(&device))
+        .any(|fields| fields.get(3).is_some_and(|options| options.split(',').any(|option| option == "rw")))
+}
+
+/// Default image file name for `device`, e.g. `sdb1-20240501-142233.img`
+pub fn default_image_file_name(device: &str, now: DateTime<Local>) -> String {
+    let name = device.trim_start_matches("/dev/").replace('/', "_");
+    format!("{}-{}.img", name, now.format("%Y%m%d-%H%M%S"))
+}
+
+pub(crate) fn dd_image_command(device: &str, out_path: &Path) -> Command {
+    let mut cmd = Command::new("dd");
+    cmd.arg(format!("if={}", device))
+        .arg(format!("of={}", out_path.display()))
+        .args(["bs=4M", "status=progress", "conv=excl,fsync"]);
+    cmd
+}
+
+/// A GPT partition type offered in the UI
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PartitionType {
+    /// sgdisk type code
+    pub code: &'static str,
+    pub name: &'static str,
+    /// parted flag that marks the same use on an MBR disk
+    pub mbr_flag: Option<&'static str>,
+}
+
+/// Partition types commonly needed when setting up a disk
+pub const COMMON_PARTITION_TYPES: &[PartitionType] = &[
+    PartitionType { code: "8300", name: "Linux filesystem", mbr_flag: None },
+    PartitionType { code: "EF00", name: "EFI system partition", mbr_flag: Some("esp") },
+    PartitionType { code: "EF02", name: "BIOS boot partition", mbr_flag: Some("bios_grub") },
+    PartitionType { code: "8200", name: "Linux swap", mbr_flag: Some("swap") },
+    PartitionType { code: "8E00", name: "Linux LVM", mbr_flag: Some("lvm") },
+    PartitionType { code: "FD00", name: "Linux RAID", mbr_flag: Some("raid") },
+    PartitionType { code: "0700", name: "Microsoft basic data", mbr_flag: None },
+];
+
+/// Check that `code` is a 4 hex digit sgdisk type code (`EF00`) or a type GUID and return it
+/// upper-cased.
+pub fn validate_type_code(code: &str) -> Result<String> {
+    let code = code.trim().to_ascii_uppercase();
+    let is_hex = |part: &str| part.chars().all(|c| c.is_ascii_hexdigit());
+
+    let is_short_code = code.len() == 4 && is_hex(&code);
+    let groups: Vec<&str> = code.split('-').collect();
+    let is_guid = groups.iter().map(|group| group.len()).eq([8, 4, 4, 4, 12]) && groups.iter().all(|group| is_hex(group));
+
+    if !is_short_code && !is_guid {
+        anyhow::bail!("Invalid partition type '{}' (expected a code like EF00 or a type GUID)", code);
+    }
+    Ok(code)
+}
+
+/// parted flag to use on an MBR disk for the GPT type `code`, if it has one
+pub fn mbr_flag_for_type(code: &str) -> Option<&'static str> {
+    COMMON_PARTITION_TYPES
+        .iter()
+        .find(|partition_type| partition_type.code.eq_ignore_ascii_case(code))
+        .and_then(|partition_type| partition_type.mbr_flag)
+}
+
+pub(crate) fn sgdisk_type_command(device: &str, partition_number: u32, code: &str) -> Command {
+    let mut cmd = Command::new("sgdisk");
+    cmd.arg("-t").arg(format!("{}:{}", partition_number, code)).arg(device);
+    cmd
+}
+
+pub(crate) fn blkid_pttype_command(device: &str) -> Command {
+    let mut cmd = Command::new("blkid");
+    cmd.args(["-p", "-o", "value", "-s", "PTTYPE", device]);
+    cmd
+}
+
+pub(crate) fn mkswap_command(device: &str, label: Option<&str>) -> Command {
+    let mut cmd = Command::new("mkswap");
+    if let Some(lbl) = label {
+        cmd.args(["-L", lbl]);
+    }
+    cmd.arg(device);
+    cmd
+}
+
+pub(crate) fn swapon_command(device: &str) -> Command {
+    let mut cmd = Command::new("swapon");
+    cmd.arg(device);
+    cmd
+}
+
+pub(crate) fn swapoff_command(device: &str) -> Command {
+    let mut cmd = Command::new("swapoff");
+    cmd.arg(device);
+    cmd
+}
+
+/// Space used on one mounted filesystem
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilesystemUsage {
+    pub device: String,
+    pub mount_point: String,
+    pub filesystem: Option<String>,
+    pub used_bytes: u64,
+    pub size_bytes: u64,
+}
+
+impl FilesystemUsage {
+    pub fn percent(&self) -> f32 {
+        if self.size_bytes == 0 {
+            return 0.0;
+        }
+        (self.used_bytes as f64 / self.size_bytes as f64 * 100.0) as f32
+    }
+}
+
+/// Mounted filesystems on `disks`, fullest first; swap and unmounted partitions are left out
+pub fn filesystem_usage(disks: &[Disk]) -> Vec<FilesystemUsage> {
+    let mut usage: Vec<FilesystemUsage> = disks
+        .iter()
+        .flat_map(|disk| &disk.partitions)
+        .filter(|partition| partition.size_bytes > 0 && !partition.is_active_swap())
+        .filter_map(|partition| {
+            Some(FilesystemUsage {
+                device: partition.device.clone(),
+                mount_point: partition.mount_point.clone()?,
+                filesystem: partition.filesystem.clone(),
+                used_bytes: partition.used_bytes,
+                size_bytes: partition.size_bytes,
+            })
+        })
+        .collect();
+    usage.sort_by(|a, b| b.percent().total_cmp(&a.percent()).then_with(|| a.mount_point.cmp(&b.mount_point)));
+    usage
+}
+
+impl Partition {
+    /// Whether this partition is an active swap area (lsblk reports its mount point as `[SWAP]`)
+    pub fn is_active_swap(&self) -> bool {
+        self.mount_point.as_deref() == Some("[SWAP]")
+    }
+}
+
+/// Value of the blkid `tag` (e.g. `UUID`, `LABEL`) of `device`, if it has one
+fn blkid_value(device: &str, tag: &str) -> Option<String> {
+    let output = Command::new("blkid")
+        .args(["-s", tag, "-o", "value", device])
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if value.is_empty() { None } else { Some(value) }
+}
+
+/// Directory below [`TEMP_MOUNT_ROOT`] for `device`: its label if it has one, else its UUID,
+/// else the device name. A label containing `/` or starting with `.` (so also `.` and `..`) is
+/// not used; in the others, characters other than letters, digits, `-`, `_` and `.` become `_`.
+pub fn temp_mount_point(device: &str, label: Option<&str>, uuid: Option<&str>) -> PathBuf {
+    let name = [label, uuid]
+        .into_iter()
+        .flatten()
+        .map(str::trim)
+        .find(|name| !name.is_empty() && !name.contains('/') && !name.starts_with('.'))
+        .unwrap_or_else(|| device.trim_start_matches("/dev/"));
+    let name: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') { c } else { '_' })
+        .collect();
+    Path::new(TEMP_MOUNT_ROOT).join(name)
+}
+
+/// Create `base`, or `base-2`, `base-3`, ... if it already exists, and return the directory
+/// created. Never reuses an existing directory, so a second mount can't stack on a first one.
+pub fn create_unique_dir(base: &Path) -> std::io::Result<PathBuf> {
+    let name = base.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    for attempt in 1..=100 {
+        let dir = if attempt == 1 { base.to_path_buf() } else { base.with_file_name(format!("{}-{}", name, attempt)) };
+        match fs::create_dir(&dir) {
+            Ok(()) => return Ok(dir),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Err(std::io::Error::new(std::io::ErrorKind::AlreadyExists, "no free directory name"))
+}
+
+/// Whether `mount_point` names a directory [`PartitionManager::mount_temp`] creates: a plain
+/// entry directly below [`TEMP_MOUNT_ROOT`], not `.` or `..`
+pub fn is_temp_mount_point(mount_point: &Path) -> bool {
+    let mut components = mount_point.components();
+    matches!(components.next_back(), Some(std::path::Component::Normal(_)))
+        && components.as_path() == Path::new(TEMP_MOUNT_ROOT)
+}
+
+/// Whether the disk `name` under `sys_block` (normally `/sys/block`) can be ejected: it has
+/// removable media, or sits on a USB bus (USB hard drives often don't set `removable`)
+pub fn is_removable(sys_block: &Path, name: &str) -> bool {
+    let device = sys_block.join(name);
+    let removable = fs::read_to_string(device.join("removable")).is_ok_and(|flag| flag.trim() == "1");
+    removable
+        || fs::canonicalize(&device).is_ok_and(|path| {
+            path.components().any(|component| component.as_os_str().to_string_lossy().starts_with("usb"))
+        })
+}
+
+/// Where partitions of `device` (or the whole disk) are mounted according to `/proc/mounts`,
+/// deepest first so nested mounts are unmounted before their parents
+pub fn disk_mount_points(mounts: &str, device: &str) -> Vec<String> {
+    let disk = device.trim_start_matches("/dev/");
+    let mut mount_points: Vec<String> = mounts
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let source = fields.next()?.strip_prefix("/dev/")?;
+            let mount_point = fields.next()?;
+            (source == disk || partition_number(source, disk).is_some()).then(|| unescape_mount_field(mount_point))
+        })
+        .collect();
+    mount_points.sort_by_key(|mount_point| std::cmp::Reverse(mount_point.matches('/').count()));
+    mount_points
+}
+
+/// Commands that unmount `mount_points` and then power off `device`, in order
+pub(crate) fn eject_commands(device: &str, mount_points: &[String]) -> Vec<Command> {
+    let mut commands: Vec<Command> = mount_points
+        .iter()
+        .map(|mount_point| {
+            let mut umount = Command::new("umount");
+            umount.arg(mount_point);
+            umount
+        })
+        .collect();
+    let mut power_off = Command::new("udisksctl");
+    power_off.args(["power-off", "-b", device]);
+    commands.push(power_off);
+    commands
+}
+
+/// Command that mounts `device` on the already created `mount_point`
+pub(crate) fn temp_mount_command(device: &str, mount_point: &Path) -> Command {
+    let mut mount = Command::new("mount");
+    mount.arg(device).arg(mount_point);
+    mount
+}
+
+/// Commands that unmount `mount_point` and remove the then empty directory, in order
+pub(crate) fn temp_unmount_commands(mount_point: &Path) -> Vec<Command> {
+    let mut umount = Command::new("umount");
+    umount.arg(mount_point);
+    let mut rmdir = Command::new("rmdir");
+    rmdir.arg(mount_point);
+    vec![umount, rmdir]
+}
+
+pub(crate) fn lsblk_command(columns: &str) -> Command {
+    let mut cmd = Command::new("lsblk");
+    cmd.args(["-J", "-b", "-o", columns]);
+    cmd
+}
+
+/// Size in bytes from `lsblk -b -J`, which older versions print as a string and newer ones as a number
+fn lsblk_size(value: &serde_json::Value) -> u64 {
+    value
+        .as_u64()
+        .or_else(|| value.as_str().and_then(|s| s.parse().ok()))
+        .unwrap_or(0)
+}
+
+/// Filesystem types served over the network; `df` on one whose server is gone can hang
+const NETWORK_FILESYSTEMS: &[&str] = &[
+    "nfs", "nfs4", "cifs", "smb3", "smbfs", "ncpfs", "9p", "afs", "ceph", "glusterfs", "lustre", "davfs",
+    "fuse.sshfs", "fuse.glusterfs", "fuse.ceph", "fuse.rclone", "fuse.s3fs",
+];
+
+/// How long `df` gets for one network mount before it is given up on
+pub const NETWORK_DF_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Whether a `/proc/mounts` fstype is a network filesystem (NFS, CIFS, SSHFS, ...)
+pub fn is_network_filesystem(fstype: &str) -> bool {
+    NETWORK_FILESYSTEMS.contains(&fstype)
+}
+
+/// A network filesystem mounted on this machine
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetworkMount {
+    /// Remote source, e.g. `server:/export` or `//server/share`
+    pub source: String,
+    pub mount_point: String,
+    pub filesystem: String,
+    /// `(used, size)` in bytes; only filled in by an explicit [`PartitionManager::check_network_mount`]
+    pub usage: Option<(u64, u64)>,
+}
+
+/// Network mounts listed in `/proc/mounts` text, in mount order
+pub fn parse_network_mounts(mounts: &str) -> Vec<NetworkMount> {
+    mounts
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let (source, mount_point, fstype) = (fields.first()?, fields.get(1)?, fields.get(2)?);
+            is_network_filesystem(fstype).then(|| NetworkMount {
+                source: unescape_mount_field(source),
+                mount_point: unescape_mount_field(mount_point),
+                filesystem: fstype.to_string(),
+                usage: None,
+            })
+        })
+        .collect()
+}
+
+/// Keep the usage already checked for mounts of `previous` that are still mounted
+pub fn carry_over_usage(mounts: &mut [NetworkMount], previous: &[NetworkMount]) {
+    for mount in mounts {
+        mount.usage = previous
+            .iter()
+            .find(|old| old.mount_point == mount.mount_point && old.source == mount.source)
+            .and_then(|old| old.usage);
+    }
+}
+
+/// Undo the octal escapes `/proc/mounts` uses for spaces, tabs, newlines and backslashes
+fn unescape_mount_field(field: &str) -> String {
+    let mut result = String::with_capacity(field.len());
+    let mut rest = field;
+    while let Some(index) = rest.find('\\') {
+        result.push_str(&rest[..index]);
+        let escape = rest.get(index + 1..index + 4);
+        match escape.and_then(|digits| u8::from_str_radix(digits, 8).ok()) {
+            Some(byte) => {
+                result.push(byte as char);
+                rest = &rest[index + 4..];
+            }
+            None => {
+                result.push('\\');
+                rest = &rest[index + 1..];
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// `(used, size)` in bytes from `df -P -B1` output for a single filesystem
+pub fn parse_df_usage(output: &str) -> Option<(u64, u64)> {
+    let fields: Vec<&str> = output.lines().nth(1)?.split_whitespace().collect();
+    let size = fields.get(1)?.parse().ok()?;
+    let used = fields.get(2)?.parse().ok()?;
+    Some((used, size))
+}
+
+/// How long a disk usage scan may run; a large filesystem on a slow disk takes a while
+pub const DU_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Directory levels the disk usage drill-down scans at a time; opening an entry scans the next
+pub const DIRECTORY_USAGE_DEPTH: u32 = 1;
+
+/// A running `du` from [`PartitionManager::scan_directories`]. Dropping it kills and reaps
+/// `du`, so closing the drill-down doesn't leave a scan running.
+pub struct DirectoryScan {
+    child: Child,
+    stdout: Option<JoinHandle<Vec<u8>>>,
+    stderr: Option<JoinHandle<Vec<u8>>>,
+    path: PathBuf,
+    started: Instant,
+}
+
+impl DirectoryScan {
+    /// `None` while `du` runs; then the largest directories below the scanned path, biggest
+    /// first. Directories `du` can't read are left out rather than failing the whole scan.
+    pub fn poll(&mut self) -> Option<Result<Vec<(PathBuf, u64)>>> {
+        match self.child.try_wait() {
+            Ok(Some(_)) => {}
+            Ok(None) if self.started.elapsed() < DU_TIMEOUT => return None,
+            Ok(None) => {
+                self.stop();
+                return Some(Err(anyhow::anyhow!("du {} timed out after {:?}", self.path.display(), DU_TIMEOUT)));
+            }
+            Err(e) => return Some(Err(e.into())),
+        }
+        let stdout = self.stdout.take().and_then(|reader| reader.join().ok()).unwrap_or_default();
+        let stderr = self.stderr.take().and_then(|reader| reader.join().ok()).unwrap_or_default();
+        // du exits with 1 after permission errors but still reports everything else
+        if stdout.is_empty() {
+            return Some(Err(anyhow::anyhow!(
+                "du {} failed: {}",
+                self.path.display(),
+                String::from_utf8_lossy(&stderr).trim()
+            )));
+        }
+        Some(Ok(parse_du_output(&String::from_utf8_lossy(&stdout), &self.path)))
+    }
+
+    /// Kill `du` if it still runs and reap it
+    fn stop(&mut self) {
+        if let Ok(None) = self.child.try_wait() {
+            let _ = self.child.kill();
+        }
+        let _ = self.child.wait();
+    }
+}
+
+impl Drop for DirectoryScan {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// `(path, bytes)` from `du -B1` output, largest first, without the line for `root` itself
+pub fn parse_du_output(output: &str, root: &Path) -> Vec<(PathBuf, u64)> {
+    let mut entries: Vec<(PathBuf, u64)> = output
+        .lines()
+        .filter_map(|line| {
+            let (size, path) = line.split_once('\t')?;
+            Some((PathBuf::from(path), size.trim().parse().ok()?))
+        })
+        .filter(|(path, _)| path != root)
+        .collect();
+    entries.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    entries
+}
+
+/// Reject a mount point or options that would write a broken `/etc/fstab` line: fields are
+/// separated by whitespace, so neither may contain any, and the mount point must be a non-empty
+/// absolute path (swap has none). Empty options are written as "defaults".
+pub fn validate_fstab_fields(mount_point: &str, fs: &str, options: Option<&str>) -> Result<()> {
+    let blank_or_spaced = |field: &str| field.is_empty() || field.chars().any(char::is_whitespace);
+    if fs != "swap" && (blank_or_spaced(mount_point) || !mount_point.starts_with('/')) {
+        anyhow::bail!("Mount point must be an absolute path without spaces, got {:?}", mount_point);
+    }
+    // Empty options are written as "defaults", like `None`
+    if let Some(options) = options.filter(|o| !o.is_empty()) {
+        if options.chars().any(char::is_whitespace) {
+            anyhow::bail!("Mount options must be a comma-separated list without spaces, got {:?}", options);
+        }
+    }
+    Ok(())
+}
+
+/// Whether any active line of an fstab file mounts the filesystem with `uuid`
+pub fn fstab_contains_uuid(fstab: &str, uuid: &str) -> bool {
+    fstab
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_whitespace().next())
+        .filter_map(|spec| spec.strip_prefix("UUID="))
+        .any(|spec| spec.trim_matches('"').eq_ignore_ascii_case(uuid))
+}
+
 impl Default for PartitionManager {
     fn default() -> Self {
         Self::new()
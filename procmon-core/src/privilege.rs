@@ -0,0 +1,56 @@
+use anyhow::{bail, Result};
+use std::process::Command;
+
+/// Environment variable holding the command partition changes run through when procmon is not
+/// root, e.g. `sudo -n` or `pkexec`
+pub const ESCALATION_ENV: &str = "PROCMON_ESCALATE";
+
+/// Shown on partition actions that are disabled because procmon can't run them
+pub const REQUIRES_ROOT: &str = "requires root";
+
+pub fn is_root() -> bool {
+    unsafe { libc::geteuid() == 0 }
+}
+
+/// Split an escalation command such as `sudo -n` into the program and its arguments
+pub fn parse_escalation(value: &str) -> Result<Vec<String>> {
+    let prefix: Vec<String> = value.split_whitespace().map(str::to_string).collect();
+    if prefix.is_empty() {
+        bail!("Escalation command is empty");
+    }
+    Ok(prefix)
+}
+
+/// Pick the escalation command from `--escalate <command>` / `--escalate=<command>` in `args`,
+/// falling back to the `preference` (normally `PROCMON_ESCALATE`). `None` means partition
+/// changes run as procmon's own user.
+pub fn escalation<I>(args: I, preference: Option<&str>) -> Result<Option<Vec<String>>>
+where
+    I: IntoIterator<Item = String>,
+{
+    crate::util::flag_value(args, "--escalate")?
+        .as_deref()
+        .or(preference)
+        .map(parse_escalation)
+        .transpose()
+}
+
+/// Whether Format/Delete/Check/Create can be offered: as root, or through an escalation command
+pub fn privileged_actions_enabled(root: bool, escalation: Option<&[String]>) -> bool {
+    root || escalation.is_some_and(|prefix| !prefix.is_empty())
+}
+
+/// `cmd` run through the `escalation` prefix, or unchanged when already `root` or no prefix is
+/// configured. Only the program and arguments are carried over.
+pub fn escalated_command(cmd: &Command, escalation: Option<&[String]>, root: bool) -> Command {
+    let mut prefix = match escalation {
+        Some([program, args @ ..]) if !root => {
+            let mut prefixed = Command::new(program);
+            prefixed.args(args).arg(cmd.get_program());
+            prefixed
+        }
+        _ => Command::new(cmd.get_program()),
+    };
+    prefix.args(cmd.get_args());
+    prefix
+}
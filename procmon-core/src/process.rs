@@ -7,16 +7,30 @@ pub struct ProcessInfo {
     pub name: String,
     pub user: String,
     pub uid: u32,
+    /// Real group id, used to restart the process under its original credentials
+    #[serde(default)]
+    pub gid: u32,
     pub exe_path: Option<PathBuf>,
+    /// The binary behind `/proc/{pid}/exe` has been deleted since the process started
+    #[serde(default)]
+    pub exe_deleted: bool,
     pub command_line: Vec<String>,
     pub status: ProcessStatus,
     pub parent_pid: Option<u32>,
+    /// Docker/Podman/Kubernetes container id from `/proc/{pid}/cgroup`; `None` on the host
+    #[serde(default)]
+    pub container: Option<String>,
+    /// procmon itself or a command it started, see [`crate::own`]
+    #[serde(default)]
+    pub is_own: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 pub enum ProcessStatus {
     Running,
     Sleeping,
+    /// Uninterruptible sleep ("D"), usually waiting on disk or a hung network mount
+    DiskSleep,
     Stopped,
     Zombie,
     Dead,
@@ -27,14 +41,48 @@ pub enum ProcessStatus {
 pub struct ProcessStats {
     pub pid: u32,
     pub cpu_usage: f32,
+    /// Mean `cpu_usage` over the last few refreshes, steadier than the instantaneous value
+    #[serde(default)]
+    pub cpu_usage_avg: f32,
     pub memory_usage: u64,
+    /// Change in `memory_usage` since the previous refresh; a new process's whole size
+    #[serde(default)]
+    pub memory_delta: i64,
     pub memory_percent: f32,
     pub virtual_memory: u64,
     pub disk_read_bytes: u64,
     pub disk_write_bytes: u64,
+    /// Bytes read per second between the last two refreshes
+    #[serde(default)]
+    pub disk_read_bytes_per_sec: u64,
     pub network_rx_bytes: u64,
     pub network_tx_bytes: u64,
+    /// Bytes received plus sent per second between the last two refreshes
+    #[serde(default)]
+    pub network_bytes_per_sec: u64,
     pub num_threads: u32,
+    /// CPU core the process last ran on
+    #[serde(default)]
+    pub last_cpu: u32,
+    /// Scheduling niceness, -20 (highest priority) to 19
+    #[serde(default)]
+    pub nice: i32,
+    /// Bytes swapped out (`VmSwap`), which `memory_usage` (RSS) does not include
+    #[serde(default)]
+    pub swap_usage: u64,
+    /// Page faults served without disk I/O (`minflt`)
+    #[serde(default)]
+    pub minor_faults: u64,
+    /// Page faults that had to read from disk (`majflt`)
+    #[serde(default)]
+    pub major_faults: u64,
+    /// Major faults since the previous refresh
+    #[serde(default)]
+    pub major_faults_delta: u64,
+    /// Video memory held on all GPUs; `None` if the process uses no GPU or the driver doesn't
+    /// report per-process usage
+    #[serde(default)]
+    pub gpu_memory_bytes: Option<u64>,
     pub start_time: chrono::DateTime<chrono::Utc>,
     pub run_time: std::time::Duration,
 }
@@ -51,12 +99,50 @@ impl ProcessInfo {
             name,
             user,
             uid,
+            gid: 0,
             exe_path: None,
+            exe_deleted: false,
             command_line: Vec::new(),
             status: ProcessStatus::Unknown,
             parent_pid: None,
+            container: None,
+            is_own: false,
         }
     }
+
+    /// Label for process tables: the full command line when requested, falling back
+    /// to the process name when there is no command line (e.g. kernel threads).
+    pub fn display_name(&self, show_command_line: bool) -> String {
+        if show_command_line && !self.command_line.is_empty() {
+            self.command_line.join(" ")
+        } else {
+            self.name.clone()
+        }
+    }
+}
+
+/// Name for a process whose reported `name` may be empty (transient or permission-limited
+/// `/proc` entries): `name`, else the kernel's `comm` (only read when needed), else the file
+/// name of the first command line argument, else `[pid N]`.
+pub fn resolve_process_name(
+    pid: u32,
+    name: &str,
+    comm: impl FnOnce() -> Option<String>,
+    command_line: &[String],
+) -> String {
+    let usable = |candidate: &str| !candidate.trim().is_empty();
+    if usable(name) {
+        return name.to_string();
+    }
+    if let Some(comm) = comm().map(|comm| comm.trim().to_string()).filter(|comm| usable(comm)) {
+        return comm;
+    }
+    command_line
+        .first()
+        .and_then(|arg| std::path::Path::new(arg.trim()).file_name())
+        .map(|arg| arg.to_string_lossy().into_owned())
+        .filter(|arg| usable(arg))
+        .unwrap_or_else(|| format!("[pid {}]", pid))
 }
 
 impl Default for ProcessStats {
@@ -64,14 +150,25 @@ impl Default for ProcessStats {
         Self {
             pid: 0,
             cpu_usage: 0.0,
+            cpu_usage_avg: 0.0,
             memory_usage: 0,
+            memory_delta: 0,
             memory_percent: 0.0,
             virtual_memory: 0,
             disk_read_bytes: 0,
             disk_write_bytes: 0,
+            disk_read_bytes_per_sec: 0,
             network_rx_bytes: 0,
             network_tx_bytes: 0,
+            network_bytes_per_sec: 0,
             num_threads: 0,
+            last_cpu: 0,
+            nice: 0,
+            swap_usage: 0,
+            minor_faults: 0,
+            major_faults: 0,
+            major_faults_delta: 0,
+            gpu_memory_bytes: None,
             start_time: chrono::Utc::now(),
             run_time: std::time::Duration::from_secs(0),
         }
@@ -84,3 +181,102 @@ pub struct ProcessSnapshot {
     pub stats: ProcessStats,
     pub timestamp: chrono::DateTime<chrono::Utc>,
 }
+
+/// The same process across refreshes. PIDs get reused, so state kept per PID alone can end up
+/// attached to an unrelated process started later under the same number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ProcessKey {
+    pub pid: u32,
+    pub start_time: chrono::DateTime<chrono::Utc>,
+}
+
+impl ProcessSnapshot {
+    pub fn key(&self) -> ProcessKey {
+        ProcessKey { pid: self.info.pid, start_time: self.stats.start_time }
+    }
+}
+
+/// Position of `child`'s parent within `processes`, if the parent is listed.
+pub fn find_parent_index(processes: &[ProcessSnapshot], child: &ProcessSnapshot) -> Option<usize> {
+    let parent_pid = child.info.parent_pid?;
+    processes.iter().position(|p| p.info.pid == parent_pid)
+}
+
+impl ProcessStatus {
+    /// Status from the state character (field 3) of `/proc/{pid}/stat`
+    pub fn from_stat_char(state: char) -> ProcessStatus {
+        match state {
+            'R' => ProcessStatus::Running,
+            // 'I' is an idle kernel thread, which sleeps without counting towards load
+            'S' | 'I' => ProcessStatus::Sleeping,
+            'D' => ProcessStatus::DiskSleep,
+            'T' | 't' => ProcessStatus::Stopped,
+            'Z' => ProcessStatus::Zombie,
+            'X' | 'x' => ProcessStatus::Dead,
+            _ => ProcessStatus::Unknown,
+        }
+    }
+}
+
+/// Combined footprint of a set of processes, e.g. all `chrome` processes matching a search
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ProcessTotals {
+    pub count: usize,
+    /// Sum of the per-process CPU percentages, so it can exceed 100 on multi-core machines
+    pub cpu_usage: f32,
+    pub memory_usage: u64,
+}
+
+impl ProcessTotals {
+    pub fn of(processes: &[ProcessSnapshot]) -> Self {
+        processes.iter().fold(Self::default(), |totals, process| Self {
+            count: totals.count + 1,
+            cpu_usage: totals.cpu_usage + process.stats.cpu_usage,
+            memory_usage: totals.memory_usage + process.stats.memory_usage,
+        })
+    }
+}
+
+/// Process states the process list is restricted to; an empty filter shows everything
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StateFilter {
+    states: Vec<ProcessStatus>,
+}
+
+impl StateFilter {
+    /// Filter showing only processes in `status`
+    pub fn only(status: ProcessStatus) -> Self {
+        Self { states: vec![status] }
+    }
+
+    /// Add `status` to the filter, or remove it if already present
+    pub fn toggle(&mut self, status: ProcessStatus) {
+        match self.states.iter().position(|s| *s == status) {
+            Some(index) => {
+                self.states.remove(index);
+            }
+            None => self.states.push(status),
+        }
+    }
+
+    pub fn contains(&self, status: ProcessStatus) -> bool {
+        self.states.contains(&status)
+    }
+
+    pub fn is_active(&self) -> bool {
+        !self.states.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.states.clear();
+    }
+
+    pub fn matches(&self, info: &ProcessInfo) -> bool {
+        self.states.is_empty() || self.states.contains(&info.status)
+    }
+
+    /// Short description such as "Running+Zombie" for titles
+    pub fn label(&self) -> String {
+        self.states.iter().map(|s| format!("{:?}", s)).collect::<Vec<_>>().join("+")
+    }
+}
@@ -0,0 +1,133 @@
+use crate::config::procmon_config_dir;
+use crate::process::ProcessInfo;
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+
+/// Process names that are protected even without a config file
+pub const DEFAULT_PROTECTED_NAMES: &[&str] = &["systemd", "init"];
+
+/// Processes that must never be killed or restarted from the UI.
+///
+/// Loaded from `~/.config/procmon/protected.toml`, e.g.
+///
+/// ```toml
+/// names = ["sshd", "postgres"]
+/// pids = [812]
+/// uids = ["0-99", 472]
+/// ```
+///
+/// Entries from the file are added to the defaults: PID 1 and [`DEFAULT_PROTECTED_NAMES`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProtectedSet {
+    names: Vec<String>,
+    pids: Vec<u32>,
+    uid_ranges: Vec<RangeInclusive<u32>>,
+}
+
+impl Default for ProtectedSet {
+    fn default() -> Self {
+        Self {
+            names: DEFAULT_PROTECTED_NAMES.iter().map(|name| name.to_string()).collect(),
+            pids: vec![1],
+            uid_ranges: Vec::new(),
+        }
+    }
+}
+
+impl ProtectedSet {
+    /// Load the protection list from [`protected_config_path`], or the defaults if there is no file.
+    pub fn load() -> Result<Self> {
+        let Some(path) = protected_config_path() else {
+            return Ok(Self::default());
+        };
+        match std::fs::read_to_string(&path) {
+            Ok(text) => Self::parse(&text).with_context(|| format!("Invalid {}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        }
+    }
+
+    /// Parse the `names`, `pids` and `uids` arrays of a protection file on top of the defaults.
+    ///
+    /// A uid entry is either a single uid or an inclusive `"first-last"` range.
+    pub fn parse(text: &str) -> Result<Self> {
+        let file: ProtectedFile = toml::from_str(text)?;
+        let mut set = Self::default();
+        set.names.extend(file.names);
+        set.pids.extend(file.pids);
+        for entry in file.uids {
+            set.uid_ranges.push(match entry {
+                UidEntry::Uid(uid) => uid..=uid,
+                UidEntry::Range(range) => parse_uid_range(&range)?,
+            });
+        }
+        Ok(set)
+    }
+
+    /// Protect processes named `names` too, e.g. the `protect` list of `config.toml`
+    pub fn add_names(&mut self, names: impl IntoIterator<Item = String>) {
+        self.names.extend(names);
+    }
+
+    /// Why `info` is protected, or `None` if it may be killed
+    pub fn protection_reason(&self, info: &ProcessInfo) -> Option<String> {
+        if self.pids.contains(&info.pid) {
+            Some(format!("PID {} is protected", info.pid))
+        } else if self.names.contains(&info.name) {
+            Some(format!("'{}' is a protected process", info.name))
+        } else if self.uid_ranges.iter().any(|range| range.contains(&info.uid)) {
+            Some(format!("processes of uid {} are protected", info.uid))
+        } else {
+            None
+        }
+    }
+
+    /// Refuse with an error naming the rule if `info` is protected
+    pub fn check(&self, info: &ProcessInfo) -> Result<()> {
+        match self.protection_reason(info) {
+            Some(reason) => bail!("Refusing to signal PID {} ({}): {}", info.pid, info.name, reason),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Contents of `protected.toml`
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ProtectedFile {
+    names: Vec<String>,
+    pids: Vec<u32>,
+    uids: Vec<UidEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum UidEntry {
+    Uid(u32),
+    /// A single uid or `"first-last"` written as a string
+    Range(String),
+}
+
+/// `$XDG_CONFIG_HOME/procmon/protected.toml`, falling back to `~/.config`
+pub fn protected_config_path() -> Option<PathBuf> {
+    Some(procmon_config_dir()?.join("protected.toml"))
+}
+
+fn parse_uid_range(value: &str) -> Result<RangeInclusive<u32>> {
+    let parse = |uid: &str| uid.trim().parse::<u32>().with_context(|| format!("Invalid uid '{}'", value));
+    match value.split_once('-') {
+        Some((first, last)) => {
+            let (first, last) = (parse(first)?, parse(last)?);
+            if first > last {
+                bail!("Empty uid range '{}'", value);
+            }
+            Ok(first..=last)
+        }
+        None => {
+            let uid = parse(value)?;
+            Ok(uid..=uid)
+        }
+    }
+}
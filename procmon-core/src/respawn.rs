@@ -0,0 +1,106 @@
+use crate::process::ProcessSnapshot;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// A program as far as respawns are concerned: process name and executable path
+pub type ProgramKey = (String, Option<PathBuf>);
+
+/// Processes that ran at least this long exited normally as far as respawns are concerned
+pub const SHORT_LIVED_SECS: i64 = 30;
+
+fn program_key(snapshot: &ProcessSnapshot) -> ProgramKey {
+    (snapshot.info.name.clone(), snapshot.info.exe_path.clone())
+}
+
+#[derive(Debug, Clone)]
+struct LiveProcess {
+    key: ProgramKey,
+    parent: Option<u32>,
+    started: DateTime<Utc>,
+}
+
+/// The last exit of a short-lived process of a program
+#[derive(Debug, Clone, Copy)]
+struct Exit {
+    at: DateTime<Utc>,
+    parent: u32,
+}
+
+/// Counts respawns: a program starting a new process under the same parent shortly after one
+/// of its processes died young (within [`SHORT_LIVED_SECS`] of starting). Many of them in a
+/// short time usually mean a crash loop under a supervisor. Jobs started periodically by cron
+/// and the like get a new parent each run, a daemon's forks of itself aren't restarts, and
+/// long-running processes being replaced don't count either.
+#[derive(Debug, Default)]
+pub struct RespawnTracker {
+    live: HashMap<u32, LiveProcess>,
+    /// When a short-lived process of each program last exited, and its parent
+    exited: HashMap<ProgramKey, Exit>,
+    respawns: HashMap<ProgramKey, Vec<DateTime<Utc>>>,
+    initialized: bool,
+}
+
+impl RespawnTracker {
+    /// Record the process table of the latest refresh; history older than `window` is dropped.
+    ///
+    /// Processes in the very first table were already running and are not respawns.
+    pub fn update(&mut self, snapshots: &[ProcessSnapshot], now: DateTime<Utc>, window: chrono::Duration) {
+        let current: HashMap<u32, LiveProcess> = snapshots
+            .iter()
+            .map(|s| {
+                let process = LiveProcess { key: program_key(s), parent: s.info.parent_pid, started: s.stats.start_time };
+                (s.info.pid, process)
+            })
+            .collect();
+        let cutoff = now - window;
+
+        for (pid, process) in &self.live {
+            let short_lived = now - process.started < chrono::Duration::seconds(SHORT_LIVED_SECS);
+            if let (false, true, Some(parent)) = (current.contains_key(pid), short_lived, process.parent) {
+                self.exited.insert(process.key.clone(), Exit { at: now, parent });
+            }
+        }
+        if self.initialized {
+            for (pid, process) in &current {
+                // A copy a daemon forks of itself, like cron's per-job child, wasn't restarted
+                let forked = process.parent.and_then(|parent| current.get(&parent)).is_some_and(|parent| parent.key == process.key);
+                let respawned = !forked
+                    && self
+                    .exited
+                    .get(&process.key)
+                    .is_some_and(|exit| exit.at > cutoff && process.parent == Some(exit.parent));
+                if !self.live.contains_key(pid) && respawned {
+                    self.respawns.entry(process.key.clone()).or_default().push(now);
+                }
+            }
+        }
+
+        self.exited.retain(|_, exit| exit.at > cutoff);
+        self.respawns.retain(|_, times| {
+            times.retain(|t| *t > cutoff);
+            !times.is_empty()
+        });
+        self.live = current;
+        self.initialized = true;
+    }
+
+    /// Respawns of `key` after `cutoff`
+    pub fn respawns_since(&self, key: &ProgramKey, cutoff: DateTime<Utc>) -> usize {
+        self.respawns.get(key).map_or(0, |times| times.iter().filter(|t| **t > cutoff).count())
+    }
+
+    /// Programs that respawned more than `max_respawns` times after `cutoff` and did so again at
+    /// `now`, with their respawn count, so a crash loop is reported once per new respawn
+    pub fn crash_loops(&self, max_respawns: usize, cutoff: DateTime<Utc>, now: DateTime<Utc>) -> Vec<(&ProgramKey, usize)> {
+        let mut loops: Vec<(&ProgramKey, usize)> = self
+            .respawns
+            .iter()
+            .filter(|(_, times)| times.last() == Some(&now))
+            .map(|(key, _)| (key, self.respawns_since(key, cutoff)))
+            .filter(|(_, count)| *count > max_respawns)
+            .collect();
+        loops.sort();
+        loops
+    }
+}
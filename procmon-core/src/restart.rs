@@ -0,0 +1,86 @@
+use crate::kill::kill_command;
+use crate::process::ProcessInfo;
+use crate::protect::ProtectedSet;
+use anyhow::{bail, Context, Result};
+use std::os::unix::process::CommandExt;
+use std::path::PathBuf;
+use std::process::Command;
+use std::time::Duration;
+
+/// How long to wait between killing a process and starting it again
+const RESTART_DELAY: Duration = Duration::from_millis(100);
+
+/// What to run to start a copy of a process, and as whom
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RestartCommand {
+    pub program: PathBuf,
+    pub args: Vec<String>,
+    /// (uid, gid) to drop to before exec; `None` keeps ours
+    pub credentials: Option<(u32, u32)>,
+}
+
+impl RestartCommand {
+    /// Plan the restart of `info` by a monitor running with effective uid `euid`.
+    ///
+    /// Root restarts other users' processes as their owner so the copy never gains
+    /// privileges; anyone else may only restart their own processes.
+    pub fn for_process(info: &ProcessInfo, euid: u32) -> Result<Self> {
+        let Some(program) = info.exe_path.clone() else {
+            bail!("PID {} has no known executable to restart", info.pid);
+        };
+
+        let credentials = if euid == 0 {
+            (info.uid != 0).then_some((info.uid, info.gid))
+        } else if info.uid == euid {
+            None
+        } else {
+            bail!(
+                "PID {} belongs to {} (uid {}); only root can restart another user's process",
+                info.pid, info.user, info.uid
+            );
+        };
+
+        Ok(Self {
+            program,
+            // The first argument is the executable itself
+            args: info.command_line.iter().skip(1).cloned().collect(),
+            credentials,
+        })
+    }
+
+    pub fn to_command(&self) -> Command {
+        let mut command = Command::new(&self.program);
+        command.args(&self.args);
+        if let Some((uid, gid)) = self.credentials {
+            // std drops supplementary groups when a root parent sets the uid
+            command.uid(uid).gid(gid);
+        }
+        command
+    }
+}
+
+/// Kill `info` and start it again with the same command line, as its original user.
+///
+/// The restart is planned before anything is killed, so a refused restart leaves the process running.
+/// Protected processes are never restarted.
+pub fn restart_process(info: &ProcessInfo, protected: &ProtectedSet) -> Result<()> {
+    protected.check(info)?;
+    let restart = RestartCommand::for_process(info, unsafe { libc::geteuid() })?;
+
+    let output = kill_command(info.pid).output()?;
+    if !output.status.success() {
+        bail!("Failed to kill PID {}: {}", info.pid, String::from_utf8_lossy(&output.stderr).trim());
+    }
+
+    std::thread::sleep(RESTART_DELAY);
+
+    // Detached from procmon's terminal, which a full-screen UI may be drawing on
+    restart
+        .to_command()
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .with_context(|| format!("Failed to start {}", restart.program.display()))?;
+    Ok(())
+}
@@ -0,0 +1,146 @@
+use crate::process::ProcessSnapshot;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// A rule script still running after this long is killed and counts as no verdict
+pub const SCRIPT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How often a script is re-run for the same process
+pub const SCRIPT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Scripts running at once; processes beyond this wait for a later refresh
+const MAX_RUNNING_SCRIPTS: usize = 8;
+
+/// Outcome of one rule script run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScriptVerdict {
+    Pass,
+    /// Nonzero exit or printed output; holds the message shown in the alert
+    Violation(String),
+    /// The script could not be started or timed out; not treated as a violation
+    Failed(String),
+}
+
+/// Run the rule script at `path` with `input` on stdin, killing it after `timeout`.
+///
+/// Exit 0 with no output passes; a nonzero exit or any output on stdout is a violation.
+pub fn run_script(path: &Path, input: &[u8], timeout: Duration) -> ScriptVerdict {
+    let mut child = match Command::new(path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) => return ScriptVerdict::Failed(format!("Failed to run {}: {}", path.display(), e)),
+    };
+
+    // Feed stdin and drain stdout on their own threads so a script that ignores its input
+    // or prints a lot cannot block us on a full pipe
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let input = input.to_vec();
+    thread::spawn(move || {
+        let _ = stdin.write_all(&input);
+    });
+    let mut stdout = child.stdout.take().expect("stdout is piped");
+    let reader = thread::spawn(move || {
+        let mut output = String::new();
+        let _ = stdout.read_to_string(&mut output);
+        output
+    });
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return ScriptVerdict::Failed(format!("{} timed out after {:?}", path.display(), timeout));
+            }
+            Ok(None) => thread::sleep(Duration::from_millis(10)),
+            Err(e) => return ScriptVerdict::Failed(format!("Failed to wait for {}: {}", path.display(), e)),
+        }
+    };
+
+    let output = reader.join().unwrap_or_default().trim().to_string();
+    if !status.success() {
+        let message = if output.is_empty() { format!("Script exited with {}", status) } else { output };
+        ScriptVerdict::Violation(message)
+    } else if !output.is_empty() {
+        ScriptVerdict::Violation(output)
+    } else {
+        ScriptVerdict::Pass
+    }
+}
+
+#[derive(Debug, Default)]
+struct ScriptRun {
+    verdict: Option<ScriptVerdict>,
+    finished_at: Option<Instant>,
+    /// Filled in by the background thread when the running script finishes
+    pending: Option<Arc<Mutex<Option<ScriptVerdict>>>>,
+}
+
+/// Runs `MisbehaviorCondition::Script` rules in the background so a slow script never
+/// stalls a refresh; checks report the latest finished verdict for each process.
+#[derive(Debug, Default)]
+pub struct ScriptRunner {
+    runs: HashMap<(u32, String), ScriptRun>,
+}
+
+impl ScriptRunner {
+    /// Violation message of the last finished run of `path` for `snapshot`, starting a new
+    /// run when the previous result is older than [`SCRIPT_INTERVAL`].
+    pub fn poll(&mut self, path: &str, snapshot: &ProcessSnapshot) -> Option<String> {
+        let running = self.runs.values().filter(|run| run.pending.is_some()).count();
+        let run = self.runs.entry((snapshot.info.pid, path.to_string())).or_default();
+
+        let finished = run.pending.as_ref().and_then(|slot| slot.lock().take());
+        if let Some(verdict) = finished {
+            if let ScriptVerdict::Failed(ref reason) = verdict {
+                tracing::warn!("Rule script for PID {}: {}", snapshot.info.pid, reason);
+            }
+            run.verdict = Some(verdict);
+            run.finished_at = Some(Instant::now());
+            run.pending = None;
+        }
+
+        let due = run.finished_at.is_none_or(|finished| finished.elapsed() >= SCRIPT_INTERVAL);
+        if run.pending.is_none() && due && running < MAX_RUNNING_SCRIPTS {
+            if let Ok(input) = serde_json::to_vec(snapshot) {
+                let slot = Arc::new(Mutex::new(None));
+                let result = slot.clone();
+                let script = path.to_string();
+                thread::spawn(move || {
+                    let verdict = run_script(Path::new(&script), &input, SCRIPT_TIMEOUT);
+                    *result.lock() = Some(verdict);
+                });
+                run.pending = Some(slot);
+            }
+        }
+
+        match &run.verdict {
+            Some(ScriptVerdict::Violation(message)) => Some(message.clone()),
+            _ => None,
+        }
+    }
+
+    /// Message of the last violation recorded for `pid` and `path`, without starting a run
+    pub fn violation(&self, pid: u32, path: &str) -> Option<&str> {
+        match self.runs.get(&(pid, path.to_string()))?.verdict.as_ref()? {
+            ScriptVerdict::Violation(message) => Some(message),
+            _ => None,
+        }
+    }
+
+    pub fn retain_pids(&mut self, active_pids: &[u32]) {
+        self.runs.retain(|(pid, _), _| active_pids.contains(pid));
+    }
+}
@@ -0,0 +1,108 @@
+use crate::process::ProcessInfo;
+use std::ops::Range;
+
+/// Whether `info` matches the process search `query` by name, command line, PID or user.
+///
+/// Matching is case-insensitive; an empty query matches everything.
+pub fn matches_search(info: &ProcessInfo, query: &str) -> bool {
+    if query.is_empty() {
+        return true;
+    }
+    let query = query.to_lowercase();
+    info.name.to_lowercase().contains(&query)
+        || info.command_line.join(" ").to_lowercase().contains(&query)
+        || info.pid.to_string().contains(&query)
+        || info.user.to_lowercase().contains(&query)
+}
+
+/// Whether any environment variable of `info` contains `query`, ignoring case.
+///
+/// Reading `/proc/{pid}/environ` is expensive and can expose secrets, so callers only use
+/// this when the user opts in. Processes of other users are skipped unless we are root.
+pub fn environment_matches(info: &ProcessInfo, query: &str) -> bool {
+    if query.is_empty() || !can_read_environ(info, unsafe { libc::geteuid() }) {
+        return false;
+    }
+    let query = query.to_lowercase();
+    read_environ(info.pid).is_some_and(|vars| vars.iter().any(|var| var.to_lowercase().contains(&query)))
+}
+
+/// The kernel only lets the owner of a process (or root) read its environment
+pub fn can_read_environ(info: &ProcessInfo, euid: u32) -> bool {
+    euid == 0 || info.uid == euid
+}
+
+/// `NAME=value` entries of `/proc/{pid}/environ`, or `None` if it cannot be read
+pub fn read_environ(pid: u32) -> Option<Vec<String>> {
+    std::fs::read(format!("/proc/{}/environ", pid)).ok().map(|bytes| parse_environ(&bytes))
+}
+
+/// Split the NUL separated contents of an `environ` file into entries
+pub fn parse_environ(bytes: &[u8]) -> Vec<String> {
+    bytes
+        .split(|byte| *byte == 0)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| String::from_utf8_lossy(entry).into_owned())
+        .collect()
+}
+
+/// Byte ranges of every non-overlapping case-insensitive occurrence of `query` in `text`.
+///
+/// Ranges always fall on character boundaries of `text`, even where lowercasing changes
+/// the length of a character.
+pub fn highlight_ranges(text: &str, query: &str) -> Vec<Range<usize>> {
+    let query: Vec<char> = query.chars().flat_map(char::to_lowercase).collect();
+    if query.is_empty() {
+        return Vec::new();
+    }
+
+    let mut ranges = Vec::new();
+    let mut search_from = 0;
+    for (start, _) in text.char_indices() {
+        if start < search_from {
+            continue;
+        }
+        if let Some(end) = match_at(text, start, &query) {
+            ranges.push(start..end);
+            search_from = end;
+        }
+    }
+    ranges
+}
+
+/// End of the match of `query` (already lowercased) starting at byte `start` of `text`
+fn match_at(text: &str, start: usize, query: &[char]) -> Option<usize> {
+    let mut matched = 0;
+    for (offset, c) in text[start..].char_indices() {
+        for lower in c.to_lowercase() {
+            if query.get(matched) != Some(&lower) {
+                return None;
+            }
+            matched += 1;
+        }
+        if matched == query.len() {
+            return Some(start + offset + c.len_utf8());
+        }
+    }
+    None
+}
+
+/// Split `text` into consecutive (segment, highlighted) pieces around `ranges`, which must be
+/// sorted, non-overlapping and on character boundaries, as returned by [`highlight_ranges`].
+pub fn segments<'a>(text: &'a str, ranges: &[Range<usize>]) -> Vec<(&'a str, bool)> {
+    let mut pieces = Vec::new();
+    let mut position = 0;
+    for range in ranges {
+        if range.start > position {
+            pieces.push((&text[position..range.start], false));
+        }
+        if range.end > range.start {
+            pieces.push((&text[range.clone()], true));
+        }
+        position = range.end;
+    }
+    if position < text.len() {
+        pieces.push((&text[position..], false));
+    }
+    pieces
+}
@@ -0,0 +1,98 @@
+use crate::process::{ProcessKey, ProcessSnapshot};
+
+/// Where the selection and scroll offset of a process list land after the list is replaced.
+///
+/// The previously selected process stays selected if it is still listed, and keeps the same
+/// row on screen relative to the scroll offset. If it exited (even if a new process took its
+/// PID) the selection stays at the same index, clamped to the new length. Returns
+/// `(selected_index, scroll_offset)`.
+pub fn reconcile_selection(
+    processes: &[ProcessSnapshot],
+    selected: Option<ProcessKey>,
+    old_index: usize,
+    old_scroll: usize,
+) -> (usize, usize) {
+    if processes.is_empty() {
+        return (0, 0);
+    }
+
+    let row_on_screen = old_index.saturating_sub(old_scroll);
+    match selected.and_then(|key| processes.iter().position(|p| p.key() == key)) {
+        Some(index) => (index, index.saturating_sub(row_on_screen)),
+        None => {
+            let index = old_index.min(processes.len() - 1);
+            (index, old_scroll.min(index))
+        }
+    }
+}
+
+/// Keyboard position in the partition manager, moving like the TUI: Left/Right pick a disk,
+/// Up/Down a partition of the expanded disk, both wrapping around. Enter expands or collapses
+/// the selected disk's partition list.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DiskCursor {
+    pub disk: Option<usize>,
+    pub partition: Option<usize>,
+    /// Whether the selected disk shows its partitions
+    pub expanded: bool,
+}
+
+impl DiskCursor {
+    /// Select `disk` (e.g. by mouse) and show its partitions
+    pub fn select_disk(&mut self, disk: usize) {
+        self.disk = Some(disk);
+        self.partition = None;
+        self.expanded = true;
+    }
+
+    pub fn select_partition(&mut self, disk: usize, partition: usize) {
+        self.disk = Some(disk);
+        self.partition = Some(partition);
+        self.expanded = true;
+    }
+
+    pub fn next_disk(&mut self, disk_count: usize) {
+        if disk_count > 0 {
+            self.disk = Some(self.disk.map_or(0, |disk| (disk + 1) % disk_count));
+            self.partition = None;
+        }
+    }
+
+    pub fn previous_disk(&mut self, disk_count: usize) {
+        if disk_count > 0 {
+            self.disk = Some(self.disk.map_or(disk_count - 1, |disk| (disk + disk_count - 1) % disk_count));
+            self.partition = None;
+        }
+    }
+
+    /// Move down the expanded disk's `partition_count` partitions; nothing happens while collapsed
+    pub fn next_partition(&mut self, partition_count: usize) {
+        if self.disk.is_some() && self.expanded && partition_count > 0 {
+            self.partition = Some(self.partition.map_or(0, |partition| (partition + 1) % partition_count));
+        }
+    }
+
+    pub fn previous_partition(&mut self, partition_count: usize) {
+        if self.disk.is_some() && self.expanded && partition_count > 0 {
+            self.partition = Some(
+                self.partition
+                    .map_or(partition_count - 1, |partition| (partition + partition_count - 1) % partition_count),
+            );
+        }
+    }
+
+    /// Expand or collapse the selected disk; collapsing drops the partition selection
+    pub fn toggle_expanded(&mut self) {
+        if self.disk.is_some() {
+            self.expanded = !self.expanded;
+            if !self.expanded {
+                self.partition = None;
+            }
+        }
+    }
+
+    /// Whether `disk` is selected and showing its partitions
+    pub fn is_expanded(&self, disk: usize) -> bool {
+        self.expanded && self.disk == Some(disk)
+    }
+}
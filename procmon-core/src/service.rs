@@ -1,6 +1,9 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::process::Command;
+use parking_lot::Mutex;
+use std::time::{Duration, Instant};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SystemService {
@@ -15,7 +18,8 @@ pub struct SystemService {
     pub main_pid: Option<u32>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+/// Ordered as listed, so sorting by state puts running services first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum ServiceState {
     Running,
     Stopped,
@@ -34,19 +38,48 @@ impl From<&str> for ServiceState {
     }
 }
 
+/// Per-unit fields fetched with `systemctl show`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ServiceDetails {
+    pub main_pid: Option<u32>,
+    pub memory_usage: Option<u64>,
+    pub enabled: bool,
+}
+
+/// Cached `systemctl show` fields for one unit.
+///
+/// Enabled-state rarely changes and is kept for `static_ttl`; PID and memory are
+/// refreshed once they are older than `volatile_ttl`.
+#[derive(Debug, Clone)]
+struct CachedDetails {
+    details: ServiceDetails,
+    static_fetched: Instant,
+    volatile_fetched: Instant,
+}
+
 pub struct ServiceManager {
-    // No state needed, operates on systemctl
+    cache: Mutex<HashMap<String, CachedDetails>>,
+    static_ttl: Duration,
+    volatile_ttl: Duration,
 }
 
 impl ServiceManager {
     pub fn new() -> Self {
-        Self {}
+        Self::with_cache_ttls(Duration::from_secs(60), Duration::from_secs(5))
+    }
+
+    pub fn with_cache_ttls(static_ttl: Duration, volatile_ttl: Duration) -> Self {
+        Self {
+            cache: Mutex::new(HashMap::new()),
+            static_ttl,
+            volatile_ttl,
+        }
     }
 
     /// List all systemd services
     pub fn list_services(&self) -> Result<Vec<SystemService>> {
         let output = Command::new("systemctl")
-            .args(&["list-units", "--type=service", "--all", "--no-pager", "--plain"])
+            .args(["list-units", "--type=service", "--all", "--no-pager", "--plain"])
             .output()?;
 
         if !output.status.success() {
@@ -79,147 +112,320 @@ impl ServiceManager {
 
             let state = ServiceState::from(active_state.as_str());
 
-            // Check if service is enabled
-            let enabled = self.is_service_enabled(&name).unwrap_or(false);
-
-            // Get detailed info including PID and resource usage
-            let (main_pid, memory_usage, cpu_usage) = self.get_service_details(&name).unwrap_or((None, None, None));
-
             services.push(SystemService {
                 name,
                 description,
                 state,
-                enabled,
+                enabled: false,
                 active_state,
                 sub_state,
-                memory_usage,
-                cpu_usage,
-                main_pid,
+                memory_usage: None,
+                cpu_usage: None, // CPU usage would require tracking over time
+                main_pid: None,
             });
         }
 
-        Ok(services)
-    }
-
-    /// Get detailed information about a service
-    fn get_service_details(&self, service_name: &str) -> Result<(Option<u32>, Option<u64>, Option<f32>)> {
-        let output = Command::new("systemctl")
-            .args(&["show", &format!("{}.service", service_name), "--no-pager"])
-            .output()?;
+        let names: Vec<&str> = services.iter().map(|s| s.name.as_str()).collect();
+        self.refresh_cache(&names);
 
-        if !output.status.success() {
-            return Ok((None, None, None));
+        let cache = self.cache.lock();
+        for service in &mut services {
+            if let Some(cached) = cache.get(&service.name) {
+                service.main_pid = cached.details.main_pid;
+                service.memory_usage = cached.details.memory_usage;
+                service.enabled = cached.details.enabled;
+            }
         }
 
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let mut main_pid = None;
-        let mut memory_usage = None;
-
-        for line in stdout.lines() {
-            if let Some(value) = line.strip_prefix("MainPID=") {
-                if let Ok(pid) = value.parse::<u32>() {
-                    if pid > 0 {
-                        main_pid = Some(pid);
-                    }
-                }
-            } else if let Some(value) = line.strip_prefix("MemoryCurrent=") {
-                if let Ok(mem) = value.parse::<u64>() {
-                    if mem > 0 {
-                        memory_usage = Some(mem);
+        Ok(services)
+    }
+
+    /// Re-query only the cached fields that have expired for `service_names`.
+    ///
+    /// Units whose enabled-state is stale get every property; units with only stale
+    /// PID/memory get a cheaper query that leaves the enabled-state untouched.
+    fn refresh_cache(&self, service_names: &[&str]) {
+        let now = Instant::now();
+        let mut full = Vec::new();
+        let mut volatile = Vec::new();
+
+        {
+            let cache = self.cache.lock();
+            for name in service_names {
+                match cache.get(*name) {
+                    Some(cached) if now.duration_since(cached.static_fetched) < self.static_ttl => {
+                        if now.duration_since(cached.volatile_fetched) >= self.volatile_ttl {
+                            volatile.push(*name);
+                        }
                     }
+                    _ => full.push(*name),
                 }
             }
         }
 
-        // CPU usage would require tracking over time, skip for now
-        Ok((main_pid, memory_usage, None))
+        let full_details = self
+            .get_service_details(&full, "Id,MainPID,MemoryCurrent,UnitFileState")
+            .unwrap_or_default();
+        let volatile_details = self
+            .get_service_details(&volatile, "Id,MainPID,MemoryCurrent")
+            .unwrap_or_default();
+
+        let mut cache = self.cache.lock();
+        for (name, details) in full_details {
+            cache.insert(name, CachedDetails {
+                details,
+                static_fetched: now,
+                volatile_fetched: now,
+            });
+        }
+        for (name, details) in volatile_details {
+            if let Some(cached) = cache.get_mut(&name) {
+                cached.details.main_pid = details.main_pid;
+                cached.details.memory_usage = details.memory_usage;
+                cached.volatile_fetched = now;
+            }
+        }
+
+        // Forget units that no longer exist
+        cache.retain(|name, _| service_names.contains(&name.as_str()));
     }
 
-    /// Check if a service is enabled
-    fn is_service_enabled(&self, service_name: &str) -> Result<bool> {
+    /// Get detailed information about several services with one `systemctl show`
+    fn get_service_details(&self, service_names: &[&str], properties: &str) -> Result<HashMap<String, ServiceDetails>> {
+        if service_names.is_empty() {
+            return Ok(HashMap::new());
+        }
+
         let output = Command::new("systemctl")
-            .args(&["is-enabled", &format!("{}.service", service_name)])
+            .args(["show", "--no-pager", &format!("--property={}", properties)])
+            .args(service_names.iter().map(|name| format!("{}.service", name)))
             .output()?;
 
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        Ok(stdout.trim() == "enabled")
+        if !output.status.success() {
+            return Ok(HashMap::new());
+        }
+
+        Ok(parse_show_output(&String::from_utf8_lossy(&output.stdout)))
     }
 
-    /// Start a service
-    pub fn start_service(&self, service_name: &str) -> Result<()> {
-        let output = Command::new("systemctl")
-            .args(&["start", &format!("{}.service", service_name)])
-            .output()?;
+    /// Cached details for a unit, if any
+    pub fn cached_details(&self, service_name: &str) -> Option<ServiceDetails> {
+        self.cache.lock().get(service_name).map(|c| c.details.clone())
+    }
+
+    #[cfg(test)]
+    pub(crate) fn insert_cached(&self, service_name: &str, details: ServiceDetails) {
+        let now = Instant::now();
+        self.cache.lock().insert(service_name.to_string(), CachedDetails {
+            details,
+            static_fetched: now,
+            volatile_fetched: now,
+        });
+    }
+
+    /// Drop cached details so the next listing re-queries the unit
+    pub fn invalidate(&self, service_name: &str) {
+        self.cache.lock().remove(service_name);
+    }
 
+    /// Run a systemctl action on a unit, then drop its cached details. Invalidating only once
+    /// the command finished keeps a refresh running meanwhile from caching the old state again.
+    pub(crate) fn run_action(&self, service_name: &str, mut command: Command, action: &str) -> Result<()> {
+        let output = command.output();
+        self.invalidate(service_name);
+
+        let output = output?;
         if !output.status.success() {
-            anyhow::bail!("Failed to start service: {}", String::from_utf8_lossy(&output.stderr));
+            anyhow::bail!("Failed to {} service: {}", action, String::from_utf8_lossy(&output.stderr));
         }
 
         Ok(())
     }
 
+    /// Start a service
+    pub fn start_service(&self, service_name: &str) -> Result<()> {
+        self.run_action(service_name, action_command("start", service_name), "start")
+    }
+
     /// Stop a service
     pub fn stop_service(&self, service_name: &str) -> Result<()> {
-        let output = Command::new("systemctl")
-            .args(&["stop", &format!("{}.service", service_name)])
-            .output()?;
-
-        if !output.status.success() {
-            anyhow::bail!("Failed to stop service: {}", String::from_utf8_lossy(&output.stderr));
-        }
+        self.stop_service_with_mode(service_name, StopMode::Default)
+    }
 
-        Ok(())
+    /// Stop a service using the given systemd job mode
+    pub fn stop_service_with_mode(&self, service_name: &str, mode: StopMode) -> Result<()> {
+        self.run_action(service_name, stop_command(service_name, mode), "stop")
     }
 
     /// Restart a service
     pub fn restart_service(&self, service_name: &str) -> Result<()> {
-        let output = Command::new("systemctl")
-            .args(&["restart", &format!("{}.service", service_name)])
-            .output()?;
-
-        if !output.status.success() {
-            anyhow::bail!("Failed to restart service: {}", String::from_utf8_lossy(&output.stderr));
-        }
-
-        Ok(())
+        self.run_action(service_name, action_command("restart", service_name), "restart")
     }
 
     /// Enable a service
     pub fn enable_service(&self, service_name: &str) -> Result<()> {
-        let output = Command::new("systemctl")
-            .args(&["enable", &format!("{}.service", service_name)])
-            .output()?;
-
-        if !output.status.success() {
-            anyhow::bail!("Failed to enable service: {}", String::from_utf8_lossy(&output.stderr));
-        }
-
-        Ok(())
+        self.run_action(service_name, action_command("enable", service_name), "enable")
     }
 
     /// Disable a service
     pub fn disable_service(&self, service_name: &str) -> Result<()> {
-        let output = Command::new("systemctl")
-            .args(&["disable", &format!("{}.service", service_name)])
-            .output()?;
+        self.run_action(service_name, action_command("disable", service_name), "disable")
+    }
 
+    /// Units the service depends on (`systemctl list-dependencies`)
+    pub fn dependencies(&self, service_name: &str) -> Result<Vec<String>> {
+        self.list_dependencies(service_name, false)
+    }
+
+    /// Units that depend on the service and are affected by stopping it
+    /// (`systemctl list-dependencies --reverse`)
+    pub fn dependents(&self, service_name: &str) -> Result<Vec<String>> {
+        self.list_dependencies(service_name, true)
+    }
+
+    fn list_dependencies(&self, service_name: &str, reverse: bool) -> Result<Vec<String>> {
+        let unit = format!("{}.service", service_name);
+        let mut args = vec!["list-dependencies", "--no-pager"];
+        if reverse {
+            args.push("--reverse");
+        }
+        args.push(&unit);
+
+        let output = Command::new("systemctl").args(&args).output()?;
         if !output.status.success() {
-            anyhow::bail!("Failed to disable service: {}", String::from_utf8_lossy(&output.stderr));
+            anyhow::bail!("Failed to list dependencies: {}", String::from_utf8_lossy(&output.stderr));
         }
 
-        Ok(())
+        Ok(parse_list_dependencies(&String::from_utf8_lossy(&output.stdout)))
     }
 
     /// Get service status details
     pub fn get_service_status(&self, service_name: &str) -> Result<String> {
         let output = Command::new("systemctl")
-            .args(&["status", &format!("{}.service", service_name), "--no-pager"])
+            .args(["status", &format!("{}.service", service_name), "--no-pager"])
             .output()?;
 
         Ok(String::from_utf8_lossy(&output.stdout).to_string())
     }
 }
 
+/// Parse multi-unit `systemctl show` output into details keyed by service name.
+///
+/// Units are printed as blocks of `Key=Value` lines separated by blank lines.
+pub fn parse_show_output(output: &str) -> HashMap<String, ServiceDetails> {
+    let mut result = HashMap::new();
+
+    for block in output.split("\n\n") {
+        let mut name = None;
+        let mut details = ServiceDetails::default();
+
+        for line in block.lines() {
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+
+            match key {
+                "Id" => name = Some(value.trim_end_matches(".service").to_string()),
+                "MainPID" => {
+                    details.main_pid = value.parse::<u32>().ok().filter(|pid| *pid > 0);
+                }
+                "MemoryCurrent" => {
+                    // Unset values show as "[not set]" or u64::MAX
+                    details.memory_usage = value.parse::<u64>().ok().filter(|mem| *mem > 0 && *mem != u64::MAX);
+                }
+                "UnitFileState" => details.enabled = value == "enabled",
+                _ => {}
+            }
+        }
+
+        if let Some(name) = name {
+            result.insert(name, details);
+        }
+    }
+
+    result
+}
+
+/// Job mode used when stopping a unit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StopMode {
+    /// systemd's default `replace` mode; a conflicting queued job may still undo the stop
+    #[default]
+    Default,
+    /// `--job-mode=replace-irreversibly`: the stop can't be cancelled by later jobs
+    ReplaceIrreversibly,
+}
+
+/// What the UI has to do before stopping a unit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StopPlan {
+    /// Nothing depends on the unit, stop right away
+    Immediate,
+    /// Other units depend on it; show them and ask first
+    NeedsConfirmation { dependents: Vec<String> },
+}
+
+/// Decide whether stopping a unit with these dependents needs the user's confirmation
+pub fn plan_stop(dependents: Result<Vec<String>>) -> Result<StopPlan> {
+    // Without the list the unit may well have dependents, so nothing is stopped
+    let dependents = dependents.context("Not stopping: couldn't list the units that depend on it")?;
+    if dependents.is_empty() {
+        Ok(StopPlan::Immediate)
+    } else {
+        Ok(StopPlan::NeedsConfirmation { dependents })
+    }
+}
+
+/// `systemctl <action> <name>.service`
+fn action_command(action: &str, service_name: &str) -> Command {
+    let mut cmd = Command::new("systemctl");
+    cmd.args([action, &format!("{}.service", service_name)]);
+    cmd
+}
+
+pub(crate) fn stop_command(service_name: &str, mode: StopMode) -> Command {
+    let mut cmd = Command::new("systemctl");
+    cmd.arg("stop");
+    if mode == StopMode::ReplaceIrreversibly {
+        cmd.arg("--job-mode=replace-irreversibly");
+    }
+    cmd.arg(format!("{}.service", service_name));
+    cmd
+}
+
+/// Flatten the tree printed by `systemctl list-dependencies` into unit names.
+///
+/// The first line is the queried unit itself and is skipped; units reached through
+/// several branches are listed once, in order of first appearance.
+pub fn parse_list_dependencies(output: &str) -> Vec<String> {
+    let mut units: Vec<String> = Vec::new();
+
+    for line in output.lines().skip(1) {
+        let unit = strip_tree_prefix(line).trim_end();
+        if !unit.is_empty() && !units.iter().any(|u| u == unit) {
+            units.push(unit.to_string());
+        }
+    }
+
+    units
+}
+
+// Remove the state bullet and tree drawing in front of a unit name. Stops right after the
+// branch connector so names such as "-.mount" keep their leading dash.
+fn strip_tree_prefix(line: &str) -> &str {
+    let mut rest = line;
+    loop {
+        rest = rest.trim_start();
+        if let Some(unit) = ["├─", "└─", "|-", "`-"].iter().find_map(|c| rest.strip_prefix(c)) {
+            return unit;
+        }
+        match rest.chars().next() {
+            Some(c) if "●○*│|".contains(c) => rest = &rest[c.len_utf8()..],
+            _ => return rest,
+        }
+    }
+}
+
 impl Default for ServiceManager {
     fn default() -> Self {
         Self::new()
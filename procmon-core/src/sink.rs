@@ -0,0 +1,92 @@
+use crate::detector::{MisbehaviorAlert, Severity};
+use anyhow::Result;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Somewhere alerts are delivered besides the in-app alert list.
+pub trait AlertSink {
+    fn send(&mut self, alert: &MisbehaviorAlert) -> Result<()>;
+}
+
+type Notifier = Box<dyn FnMut(&MisbehaviorAlert) -> Result<()> + Send>;
+
+/// Pops a desktop notification for alerts at or above `min_severity`.
+///
+/// Repeats of the same rule for the same process are suppressed for `debounce`, since
+/// the detector re-raises an ongoing violation on every refresh.
+pub struct DesktopNotificationSink {
+    min_severity: Severity,
+    debounce: Duration,
+    last_sent: HashMap<(u32, String), Instant>,
+    notifier: Notifier,
+}
+
+impl DesktopNotificationSink {
+    pub const DEFAULT_DEBOUNCE: Duration = Duration::from_secs(60);
+
+    /// Sink that shows notifications through the desktop notification service
+    #[cfg(feature = "desktop-notifications")]
+    pub fn new(min_severity: Severity) -> Self {
+        Self::with_notifier(min_severity, Box::new(show_desktop_notification))
+    }
+
+    /// Sink that hands accepted alerts to `notifier` instead of the desktop
+    pub fn with_notifier(min_severity: Severity, notifier: Notifier) -> Self {
+        Self {
+            min_severity,
+            debounce: Self::DEFAULT_DEBOUNCE,
+            last_sent: HashMap::new(),
+            notifier,
+        }
+    }
+
+    pub fn set_min_severity(&mut self, min_severity: Severity) {
+        self.min_severity = min_severity;
+    }
+
+    pub fn set_debounce(&mut self, debounce: Duration) {
+        self.debounce = debounce;
+    }
+}
+
+impl AlertSink for DesktopNotificationSink {
+    fn send(&mut self, alert: &MisbehaviorAlert) -> Result<()> {
+        if alert.severity < self.min_severity {
+            return Ok(());
+        }
+
+        let now = Instant::now();
+        let key = (alert.pid, alert.rule_name.clone());
+        if let Some(last) = self.last_sent.get(&key) {
+            if now.duration_since(*last) < self.debounce {
+                return Ok(());
+            }
+        }
+
+        // Forget entries that can no longer suppress anything
+        let debounce = self.debounce;
+        self.last_sent.retain(|_, sent| now.duration_since(*sent) < debounce);
+        self.last_sent.insert(key, now);
+
+        (self.notifier)(alert)
+    }
+}
+
+#[cfg(feature = "desktop-notifications")]
+fn show_desktop_notification(alert: &MisbehaviorAlert) -> Result<()> {
+    let mut notification = notify_rust::Notification::new();
+    notification
+        .appname("Process Monitor")
+        .summary(&format!("{:?}: {}", alert.severity, alert.rule_name))
+        .body(&format!("{} (PID {})\n{}", alert.process_name, alert.pid, alert.details));
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    notification.urgency(match alert.severity {
+        Severity::Critical => notify_rust::Urgency::Critical,
+        Severity::Warning => notify_rust::Urgency::Normal,
+        Severity::Info => notify_rust::Urgency::Low,
+    });
+
+    notification.show()?;
+    Ok(())
+}
@@ -0,0 +1,146 @@
+use crate::detector::MisbehaviorAlert;
+use crate::metrics::SystemMetrics;
+use crate::process::ProcessSnapshot;
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Environment variable naming the directory periodic snapshots are written to
+pub const SNAPSHOT_DIR_ENV: &str = "PROCMON_SNAPSHOT_DIR";
+
+/// Seconds between snapshots when `--snapshot-interval` is not given
+pub const DEFAULT_SNAPSHOT_INTERVAL_SECS: u64 = 60;
+
+/// Snapshot files kept when `--snapshot-keep` is not given
+pub const DEFAULT_SNAPSHOT_KEEP: usize = 60;
+
+const FILE_PREFIX: &str = "snapshot-";
+const FILE_SUFFIX: &str = ".json";
+
+/// Where, how often and how many snapshots to write
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotConfig {
+    pub dir: PathBuf,
+    pub interval: Duration,
+    /// Older files beyond this many are deleted after each write
+    pub keep: usize,
+}
+
+/// Snapshot settings from `--snapshot-dir <dir>`, `--snapshot-interval <secs>` and
+/// `--snapshot-keep <n>` in `args`. The directory falls back to the `preference` (normally
+/// `PROCMON_SNAPSHOT_DIR`); without one no snapshots are written.
+pub fn snapshot_config<I>(args: I, preference: Option<&str>) -> Result<Option<SnapshotConfig>>
+where
+    I: IntoIterator<Item = String>,
+{
+    let args: Vec<String> = args.into_iter().collect();
+    let dir = crate::util::flag_value(args.clone(), "--snapshot-dir")?;
+    let Some(dir) = dir.as_deref().or(preference).filter(|dir| !dir.trim().is_empty()) else {
+        return Ok(None);
+    };
+
+    let interval = match crate::util::flag_value(args.clone(), "--snapshot-interval")? {
+        Some(value) => value.trim().parse().with_context(|| format!("Invalid snapshot interval '{}'", value))?,
+        None => DEFAULT_SNAPSHOT_INTERVAL_SECS,
+    };
+    if interval == 0 {
+        bail!("Snapshot interval must be at least 1 second");
+    }
+    let keep = match crate::util::flag_value(args, "--snapshot-keep")? {
+        Some(value) => value.trim().parse().with_context(|| format!("Invalid snapshot count '{}'", value))?,
+        None => DEFAULT_SNAPSHOT_KEEP,
+    };
+    if keep == 0 {
+        bail!("--snapshot-keep must be at least 1");
+    }
+
+    Ok(Some(SnapshotConfig { dir: PathBuf::from(dir), interval: Duration::from_secs(interval), keep }))
+}
+
+/// Everything on screen at one refresh, as written to a snapshot file
+#[derive(Debug, Serialize)]
+pub struct Snapshot<'a> {
+    pub timestamp: DateTime<Utc>,
+    pub metrics: &'a SystemMetrics,
+    pub processes: &'a [ProcessSnapshot],
+    pub alerts: &'a [MisbehaviorAlert],
+}
+
+/// Writes a [`Snapshot`] every `interval` and keeps only the newest `keep` files.
+///
+/// Files are named `snapshot-<UTC timestamp>.json` so they sort by age; each is written to a
+/// temporary name first and renamed, so a crash never leaves a half-written snapshot.
+#[derive(Debug)]
+pub struct SnapshotWriter {
+    config: SnapshotConfig,
+    last_written: Option<Instant>,
+}
+
+impl SnapshotWriter {
+    pub fn new(config: SnapshotConfig) -> Self {
+        Self { config, last_written: None }
+    }
+
+    pub fn config(&self) -> &SnapshotConfig {
+        &self.config
+    }
+
+    pub fn is_due(&self, now: Instant) -> bool {
+        self.last_written.is_none_or(|last| now.saturating_duration_since(last) >= self.config.interval)
+    }
+
+    /// Write `snapshot` if the interval has passed, returning the file written
+    pub fn write_if_due(&mut self, snapshot: &Snapshot, now: Instant) -> Result<Option<PathBuf>> {
+        if !self.is_due(now) {
+            return Ok(None);
+        }
+        // Count a failed attempt too, so a full disk is not retried on every refresh
+        self.last_written = Some(now);
+        self.write(snapshot).map(Some)
+    }
+
+    /// Write `snapshot` now and delete the oldest files beyond `keep`
+    pub fn write(&self, snapshot: &Snapshot) -> Result<PathBuf> {
+        let dir = &self.config.dir;
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+
+        let name = format!("{}{}{}", FILE_PREFIX, snapshot.timestamp.format("%Y%m%d-%H%M%S-%3f"), FILE_SUFFIX);
+        let path = dir.join(name);
+        let partial = path.with_extension("json.tmp");
+        fs::write(&partial, serde_json::to_vec(snapshot)?)
+            .with_context(|| format!("Failed to write {}", partial.display()))?;
+        fs::rename(&partial, &path).with_context(|| format!("Failed to write {}", path.display()))?;
+
+        rotate_snapshots(dir, self.config.keep)?;
+        Ok(path)
+    }
+}
+
+/// Snapshot files in `dir`, oldest first
+pub fn list_snapshots(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .with_context(|| format!("Failed to read {}", dir.display()))?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            name.starts_with(FILE_PREFIX) && name.ends_with(FILE_SUFFIX)
+        })
+        .map(|entry| entry.path())
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+/// Delete all but the newest `keep` snapshot files in `dir`
+pub fn rotate_snapshots(dir: &Path, keep: usize) -> Result<()> {
+    let files = list_snapshots(dir)?;
+    let excess = files.len().saturating_sub(keep);
+    for old in &files[..excess] {
+        fs::remove_file(old).with_context(|| format!("Failed to remove {}", old.display()))?;
+    }
+    Ok(())
+}
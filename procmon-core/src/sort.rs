@@ -0,0 +1,165 @@
+use crate::process::ProcessSnapshot;
+use crate::service::SystemService;
+use anyhow::{bail, Result};
+use std::cmp::Ordering;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortColumn {
+    Name,
+    Cpu,
+    Memory,
+    /// Growth of resident memory since the last refresh
+    MemoryGrowth,
+    DiskIo,
+    User,
+    /// Time since the process started
+    Age,
+    Swap,
+    /// Rolling average CPU usage
+    CpuAvg,
+    Pid,
+}
+
+impl SortColumn {
+    /// Look up a column by the name used on the command line: `cpu`, `mem`, `disk`, `pid` or `name`
+    pub fn from_name(name: &str) -> Result<SortColumn> {
+        match name.trim().to_lowercase().as_str() {
+            "cpu" => Ok(SortColumn::Cpu),
+            "mem" | "memory" => Ok(SortColumn::Memory),
+            "disk" => Ok(SortColumn::DiskIo),
+            "pid" => Ok(SortColumn::Pid),
+            "name" => Ok(SortColumn::Name),
+            _ => bail!("Unknown sort column '{}' (expected cpu, mem, disk, pid or name)", name),
+        }
+    }
+
+    /// Whether the column reads naturally smallest first; usage columns list the biggest first
+    pub fn default_ascending(&self) -> bool {
+        matches!(self, SortColumn::Name | SortColumn::User | SortColumn::Pid)
+    }
+}
+
+/// Compare two processes by `column`.
+///
+/// Ties are broken by ascending PID regardless of direction, so rows with equal keys
+/// keep the same relative order from one refresh to the next.
+pub fn compare_processes(a: &ProcessSnapshot, b: &ProcessSnapshot, column: SortColumn, ascending: bool) -> Ordering {
+    let primary = match column {
+        SortColumn::Name => a.info.name.cmp(&b.info.name),
+        SortColumn::Cpu => a.stats.cpu_usage.total_cmp(&b.stats.cpu_usage),
+        SortColumn::Memory => a.stats.memory_usage.cmp(&b.stats.memory_usage),
+        SortColumn::MemoryGrowth => a.stats.memory_delta.cmp(&b.stats.memory_delta),
+        SortColumn::DiskIo => {
+            let a_io = a.stats.disk_read_bytes + a.stats.disk_write_bytes;
+            let b_io = b.stats.disk_read_bytes + b.stats.disk_write_bytes;
+            a_io.cmp(&b_io)
+        }
+        SortColumn::User => a.info.user.cmp(&b.info.user),
+        SortColumn::Age => a.stats.run_time.cmp(&b.stats.run_time),
+        SortColumn::Swap => a.stats.swap_usage.cmp(&b.stats.swap_usage),
+        SortColumn::CpuAvg => a.stats.cpu_usage_avg.total_cmp(&b.stats.cpu_usage_avg),
+        SortColumn::Pid => a.info.pid.cmp(&b.info.pid),
+    };
+
+    let primary = if ascending { primary } else { primary.reverse() };
+    primary.then_with(|| a.info.pid.cmp(&b.info.pid))
+}
+
+pub fn sort_processes(processes: &mut [ProcessSnapshot], column: SortColumn, ascending: bool) {
+    processes.sort_by(|a, b| compare_processes(a, b, column, ascending));
+}
+
+/// What the network tab's process table is ordered by, biggest first
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NetworkSort {
+    /// Current rate, so a process that is busy right now is on top
+    #[default]
+    Rate,
+    /// Bytes received and sent over the process's lifetime
+    Total,
+}
+
+impl NetworkSort {
+    pub fn label(&self) -> &'static str {
+        match self {
+            NetworkSort::Rate => "Rate",
+            NetworkSort::Total => "Total",
+        }
+    }
+
+    pub fn toggle(&self) -> NetworkSort {
+        match self {
+            NetworkSort::Rate => NetworkSort::Total,
+            NetworkSort::Total => NetworkSort::Rate,
+        }
+    }
+}
+
+/// Compare two processes by network usage, biggest first; ties are broken by ascending PID.
+pub fn compare_network(a: &ProcessSnapshot, b: &ProcessSnapshot, sort: NetworkSort) -> Ordering {
+    let key = |process: &ProcessSnapshot| match sort {
+        NetworkSort::Rate => process.stats.network_bytes_per_sec,
+        NetworkSort::Total => process.stats.network_rx_bytes + process.stats.network_tx_bytes,
+    };
+    key(b).cmp(&key(a)).then_with(|| a.info.pid.cmp(&b.info.pid))
+}
+
+pub fn sort_by_network(processes: &mut [ProcessSnapshot], sort: NetworkSort) {
+    processes.sort_by(|a, b| compare_network(a, b, sort));
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServiceSortColumn {
+    Name,
+    State,
+    Memory,
+    /// Main PID
+    Pid,
+}
+
+impl ServiceSortColumn {
+    pub const ALL: [ServiceSortColumn; 4] =
+        [ServiceSortColumn::Name, ServiceSortColumn::State, ServiceSortColumn::Memory, ServiceSortColumn::Pid];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ServiceSortColumn::Name => "Name",
+            ServiceSortColumn::State => "State",
+            ServiceSortColumn::Memory => "Memory",
+            ServiceSortColumn::Pid => "PID",
+        }
+    }
+
+    pub fn next(&self) -> ServiceSortColumn {
+        let index = Self::ALL.iter().position(|column| column == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+/// Compare optional values with `None` last in either direction, e.g. services without a
+/// memory reading always end up below the ones that have one.
+pub fn compare_optional<T: Ord>(a: Option<T>, b: Option<T>, ascending: bool) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) if ascending => a.cmp(&b),
+        (Some(a), Some(b)) => b.cmp(&a),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+/// Compare two services by `column`; ties are broken by ascending name.
+pub fn compare_services(a: &SystemService, b: &SystemService, column: ServiceSortColumn, ascending: bool) -> Ordering {
+    let directed = |ordering: Ordering| if ascending { ordering } else { ordering.reverse() };
+    let primary = match column {
+        ServiceSortColumn::Name => directed(a.name.cmp(&b.name)),
+        ServiceSortColumn::State => directed(a.state.cmp(&b.state)),
+        ServiceSortColumn::Memory => compare_optional(a.memory_usage, b.memory_usage, ascending),
+        ServiceSortColumn::Pid => compare_optional(a.main_pid, b.main_pid, ascending),
+    };
+    primary.then_with(|| a.name.cmp(&b.name))
+}
+
+pub fn sort_services(services: &mut [SystemService], column: ServiceSortColumn, ascending: bool) {
+    services.sort_by(|a, b| compare_services(a, b, column, ascending));
+}
@@ -0,0 +1,73 @@
+use anyhow::{bail, Result};
+
+/// Environment variable holding the preferred startup tab
+pub const STARTUP_TAB_ENV: &str = "PROCMON_TAB";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tab {
+    Dashboard,
+    Processes,
+    Services,
+    Storage,
+    Network,
+    Partitions,
+    Alerts,
+    /// procmon's own recent log events
+    Logs,
+}
+
+impl Tab {
+    /// All tabs in display order
+    pub const ALL: [Tab; 8] = [
+        Tab::Dashboard,
+        Tab::Processes,
+        Tab::Services,
+        Tab::Storage,
+        Tab::Network,
+        Tab::Partitions,
+        Tab::Alerts,
+        Tab::Logs,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Tab::Dashboard => "dashboard",
+            Tab::Processes => "processes",
+            Tab::Services => "services",
+            Tab::Storage => "storage",
+            Tab::Network => "network",
+            Tab::Partitions => "partitions",
+            Tab::Alerts => "alerts",
+            Tab::Logs => "logs",
+        }
+    }
+
+    /// Position of the tab in the tab bar
+    pub fn index(&self) -> usize {
+        Self::ALL.iter().position(|tab| tab == self).unwrap_or(0)
+    }
+
+    /// Look up a tab by name, ignoring case
+    pub fn from_name(name: &str) -> Result<Tab> {
+        let lower = name.trim().to_lowercase();
+        match Self::ALL.iter().find(|tab| tab.name() == lower) {
+            Some(tab) => Ok(*tab),
+            None => {
+                let names: Vec<&str> = Self::ALL.iter().map(|tab| tab.name()).collect();
+                bail!("Unknown tab '{}' (expected one of: {})", name, names.join(", "))
+            }
+        }
+    }
+}
+
+/// Pick the startup tab from `--tab <name>` / `--tab=<name>` in `args`, falling back to
+/// the `preference` (normally the `PROCMON_TAB` environment variable) and then the Dashboard.
+pub fn startup_tab<I>(args: I, preference: Option<&str>) -> Result<Tab>
+where
+    I: IntoIterator<Item = String>,
+{
+    match crate::util::flag_value(args, "--tab")?.as_deref().or(preference) {
+        Some(name) => Tab::from_name(name),
+        None => Ok(Tab::Dashboard),
+    }
+}
@@ -0,0 +1,55 @@
+use anyhow::{bail, Result};
+
+/// Environment variable holding the preferred temperature unit, `c` or `f`
+pub const TEMP_UNIT_ENV: &str = "PROCMON_TEMP_UNIT";
+
+/// Unit temperatures are displayed in; readings and alert thresholds stay in Celsius
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TemperatureUnit {
+    #[default]
+    Celsius,
+    Fahrenheit,
+}
+
+impl TemperatureUnit {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            TemperatureUnit::Celsius => "°C",
+            TemperatureUnit::Fahrenheit => "°F",
+        }
+    }
+
+    /// Look up a unit by name (`c`, `celsius`, `f`, `fahrenheit`), ignoring case
+    pub fn from_name(name: &str) -> Result<TemperatureUnit> {
+        match name.trim().to_lowercase().as_str() {
+            "c" | "celsius" => Ok(TemperatureUnit::Celsius),
+            "f" | "fahrenheit" => Ok(TemperatureUnit::Fahrenheit),
+            _ => bail!("Unknown temperature unit '{}' (expected c or f)", name),
+        }
+    }
+
+    /// Convert a Celsius reading into this unit
+    pub fn convert(&self, celsius: f32) -> f32 {
+        match self {
+            TemperatureUnit::Celsius => celsius,
+            TemperatureUnit::Fahrenheit => celsius * 9.0 / 5.0 + 32.0,
+        }
+    }
+}
+
+/// Format a Celsius reading for display, e.g. "212.0°F"
+pub fn format_temp(celsius: f32, unit: TemperatureUnit) -> String {
+    format!("{:.1}{}", unit.convert(celsius), unit.symbol())
+}
+
+/// Pick the temperature unit from `--temp-unit <c|f>` / `--temp-unit=<c|f>` in `args`,
+/// falling back to the `preference` (normally `PROCMON_TEMP_UNIT`) and then Celsius.
+pub fn temperature_unit<I>(args: I, preference: Option<&str>) -> Result<TemperatureUnit>
+where
+    I: IntoIterator<Item = String>,
+{
+    match crate::util::flag_value(args, "--temp-unit")?.as_deref().or(preference) {
+        Some(name) => TemperatureUnit::from_name(name),
+        None => Ok(TemperatureUnit::Celsius),
+    }
+}
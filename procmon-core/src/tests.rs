@@ -1,6 +1,6 @@
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod tests {
-    use super::*;
     use std::fs;
     use std::collections::HashSet;
 
@@ -92,4 +92,4036 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_warmed_monitor_reports_cpu_usage() {
+        // Generate some load so there is something to measure
+        let stop = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let worker_stop = stop.clone();
+        let worker = std::thread::spawn(move || {
+            let mut x: u64 = 0;
+            while !worker_stop.load(std::sync::atomic::Ordering::Relaxed) {
+                x = x.wrapping_mul(31).wrapping_add(7);
+                std::hint::black_box(x);
+            }
+        });
+
+        let monitor = crate::monitor::SystemMonitor::new_warmed();
+        stop.store(true, std::sync::atomic::Ordering::Relaxed);
+        worker.join().unwrap();
+
+        let metrics = monitor.get_system_metrics().unwrap();
+        assert!(
+            metrics.cpu.per_core_usage.iter().any(|u| *u > 0.0),
+            "Per-core CPU usage is uniformly zero after warm-up: {:?}",
+            metrics.cpu.per_core_usage
+        );
+
+        let processes = monitor.get_all_processes().unwrap();
+        assert!(
+            processes.iter().any(|p| p.stats.cpu_usage > 0.0),
+            "No process reported CPU usage after warm-up"
+        );
+    }
+
+    #[test]
+    fn test_delta_with_wrap_no_wrap() {
+        use crate::util::delta_with_wrap;
+        assert_eq!(delta_with_wrap(100, 150, u32::MAX as u64), 50);
+        assert_eq!(delta_with_wrap(100, 100, u32::MAX as u64), 0);
+    }
+
+    #[test]
+    fn test_delta_with_wrap_exact_wrap() {
+        use crate::util::delta_with_wrap;
+        // Counter at its maximum steps over to zero: one unit elapsed
+        assert_eq!(delta_with_wrap(u32::MAX as u64, 0, u32::MAX as u64), 1);
+        // Counter wraps and continues counting
+        assert_eq!(delta_with_wrap(u32::MAX as u64 - 9, 10, u32::MAX as u64), 20);
+    }
+
+    #[test]
+    fn test_delta_with_wrap_max_range() {
+        use crate::util::delta_with_wrap;
+        // Almost a full revolution of the counter
+        assert_eq!(delta_with_wrap(1, 0, u32::MAX as u64), u32::MAX as u64);
+        assert_eq!(delta_with_wrap(1, 0, u64::MAX), u64::MAX);
+        assert_eq!(delta_with_wrap(u64::MAX, 0, u64::MAX), 1);
+        // A previous value beyond the range means the counter was reset
+        assert_eq!(delta_with_wrap(u32::MAX as u64 + 5, 3, u32::MAX as u64), 3);
+    }
+
+    #[test]
+    fn test_infer_counter_max() {
+        use crate::util::infer_counter_max;
+        assert_eq!(infer_counter_max(0), u32::MAX as u64);
+        assert_eq!(infer_counter_max(u32::MAX as u64), u32::MAX as u64);
+        assert_eq!(infer_counter_max(u32::MAX as u64 + 1), u64::MAX);
+    }
+
+    #[test]
+    fn test_interface_and_disk_rates_survive_counter_wrap() {
+        use crate::monitor::{interface_rates, sector_rates};
+        use std::time::Duration;
+
+        let second = Duration::from_secs(1);
+        assert_eq!(interface_rates((1000, 2000), (1500, 2600), second), (500, 600));
+        // A 32-bit driver counter wraps: 100 bytes up to the wrap, 100 after it
+        let near_wrap = u32::MAX as u64 - 99;
+        assert_eq!(interface_rates((near_wrap, 0), (100, 0), Duration::from_secs(2)), (100, 0));
+        // A 64-bit counter wraps at u64::MAX
+        assert_eq!(interface_rates((u64::MAX - 9, 0), (10, 0), second), (20, 0));
+
+        assert_eq!(sector_rates((100, 200), (104, 200), second), (2048, 0));
+        assert_eq!(sector_rates((u32::MAX as u64, 0), (1, 0), second), (1024, 0));
+        // The byte conversion of an enormous delta saturates instead of overflowing
+        assert_eq!(sector_rates((u32::MAX as u64 + 1, 0), (0, 0), second).0, u64::MAX);
+    }
+
+    #[test]
+    fn test_display_name_respects_command_line_toggle() {
+        use crate::process::ProcessInfo;
+
+        let mut info = ProcessInfo::new(4242, "python3".to_string(), "alice".to_string(), 1000);
+        info.command_line = vec!["/usr/bin/python3".to_string(), "server.py".to_string(), "--port=8080".to_string()];
+
+        assert_eq!(info.display_name(false), "python3");
+        assert_eq!(info.display_name(true), "/usr/bin/python3 server.py --port=8080");
+
+        // Kernel threads have no command line and keep showing their name
+        let kthread = ProcessInfo::new(2, "kthreadd".to_string(), "root".to_string(), 0);
+        assert_eq!(kthread.display_name(true), "kthreadd");
+    }
+
+    #[test]
+    fn test_new_process_tracker_flags_and_expires() {
+        use crate::tracking::NewProcessTracker;
+        use std::time::{Duration, Instant};
+
+        let mut tracker = NewProcessTracker::new(Duration::from_secs(5));
+        let start = Instant::now();
+
+        // Processes in the first snapshot were already running
+        tracker.update(&[1, 100, 200], start);
+        assert!(!tracker.is_new(100, start));
+
+        // PID 300 shows up in the second snapshot
+        let second = start + Duration::from_secs(1);
+        tracker.update(&[1, 100, 200, 300], second);
+        assert!(tracker.is_new(300, second));
+        assert!(!tracker.is_new(200, second));
+
+        // Still flagged inside the window, cleared once it has passed
+        assert!(tracker.is_new(300, second + Duration::from_secs(4)));
+        assert!(!tracker.is_new(300, second + Duration::from_secs(5)));
+
+        // A PID that disappears and comes back is new again
+        tracker.update(&[1, 100, 200], second + Duration::from_secs(6));
+        let later = second + Duration::from_secs(7);
+        tracker.update(&[1, 100, 200, 300], later);
+        assert!(tracker.is_new(300, later));
+    }
+
+    fn synthetic_process(pid: u32, name: &str, cpu_usage: f32, memory_usage: u64) -> crate::process::ProcessSnapshot {
+        use crate::process::{ProcessInfo, ProcessSnapshot, ProcessStats};
+
+        ProcessSnapshot {
+            info: ProcessInfo::new(pid, name.to_string(), "user".to_string(), 1000),
+            stats: ProcessStats {
+                pid,
+                cpu_usage,
+                memory_usage,
+                ..Default::default()
+            },
+            timestamp: chrono::Utc::now(),
+        }
+    }
+
+    /// Call `poll` until it returns something, for results produced on a background thread
+    fn wait_for<T>(mut poll: impl FnMut() -> Option<T>) -> Option<T> {
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        loop {
+            let result = poll();
+            if result.is_some() || std::time::Instant::now() > deadline {
+                return result;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn test_sort_ties_broken_by_ascending_pid() {
+        use crate::sort::{sort_processes, SortColumn};
+
+        for ascending in [false, true] {
+            let mut processes = vec![
+                synthetic_process(300, "c", 12.5, 10),
+                synthetic_process(42, "a", 12.5, 10),
+                synthetic_process(7, "b", 50.0, 10),
+                synthetic_process(150, "d", 12.5, 10),
+            ];
+            sort_processes(&mut processes, SortColumn::Cpu, ascending);
+
+            let tied: Vec<u32> = processes
+                .iter()
+                .filter(|p| p.stats.cpu_usage == 12.5)
+                .map(|p| p.info.pid)
+                .collect();
+            assert_eq!(tied, vec![42, 150, 300]);
+        }
+
+        // Reversing the input order does not change the result
+        let mut forward = vec![synthetic_process(2, "x", 1.0, 5), synthetic_process(1, "y", 1.0, 5)];
+        let mut backward: Vec<_> = forward.iter().rev().cloned().collect();
+        sort_processes(&mut forward, SortColumn::Memory, false);
+        sort_processes(&mut backward, SortColumn::Memory, false);
+        let forward_pids: Vec<u32> = forward.iter().map(|p| p.info.pid).collect();
+        let backward_pids: Vec<u32> = backward.iter().map(|p| p.info.pid).collect();
+        assert_eq!(forward_pids, vec![1, 2]);
+        assert_eq!(forward_pids, backward_pids);
+    }
+
+    #[test]
+    fn test_child_spawn_rate_detects_fork_bomb() {
+        use crate::detector::{MisbehaviorCondition, MisbehaviorDetector, MisbehaviorRule, Severity};
+        use crate::metrics::SystemMetrics;
+
+        let mut detector = MisbehaviorDetector::with_rules(vec![MisbehaviorRule {
+            name: "Fork Bomb".to_string(),
+            description: "Too many children".to_string(),
+            condition: MisbehaviorCondition::ChildSpawnRate { children_per_sec: 2.0, window_secs: 10 },
+            severity: Severity::Critical,
+        }]);
+
+        let mut parent = synthetic_process(500, "bash", 0.0, 0);
+        parent.info.parent_pid = Some(1);
+        let init = synthetic_process(1, "init", 0.0, 0);
+
+        // First refresh: the parent has no children yet
+        let before = vec![init.clone(), parent.clone()];
+        assert!(detector.check_system(&before, &SystemMetrics::default()).is_empty());
+
+        // Second refresh: 50 new children appeared
+        let mut after = before.clone();
+        for pid in 1000..1050 {
+            let mut child = synthetic_process(pid, "bash", 0.0, 0);
+            child.info.parent_pid = Some(500);
+            after.push(child);
+        }
+        let alerts = detector.check_system(&after, &SystemMetrics::default());
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].pid, 500);
+        assert_eq!(alerts[0].rule_name, "Fork Bomb");
+
+        // A parent seen for the first time with existing children is not flagged
+        let mut fresh = MisbehaviorDetector::with_rules(detector.get_rules().to_vec());
+        assert!(fresh.check_system(&after, &SystemMetrics::default()).is_empty());
+    }
+
+    fn system_rule(condition: crate::detector::SystemCondition) -> crate::detector::MisbehaviorRule {
+        use crate::detector::{MisbehaviorCondition, MisbehaviorRule, Severity};
+
+        MisbehaviorRule {
+            name: "System Rule".to_string(),
+            description: "Test system rule".to_string(),
+            condition: MisbehaviorCondition::System(condition),
+            severity: Severity::Warning,
+        }
+    }
+
+    #[test]
+    fn test_system_condition_total_processes_above() {
+        use crate::detector::{MisbehaviorDetector, SystemCondition};
+        use crate::metrics::SystemMetrics;
+
+        let mut detector = MisbehaviorDetector::with_rules(vec![system_rule(
+            SystemCondition::TotalProcessesAbove { threshold: 3 },
+        )]);
+        let metrics = SystemMetrics::default();
+
+        let few: Vec<_> = (1..=3).map(|pid| synthetic_process(pid, "p", 0.0, 0)).collect();
+        assert!(detector.check_system(&few, &metrics).is_empty());
+
+        let many: Vec<_> = (1..=4).map(|pid| synthetic_process(pid, "p", 0.0, 0)).collect();
+        let alerts = detector.check_system(&many, &metrics);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].pid, 0);
+
+        // Shown to users as a sentence, not as the enum's Debug output
+        assert_eq!(SystemCondition::TotalProcessesAbove { threshold: 3 }.to_string(), "More than 3 processes");
+        assert_eq!(
+            SystemCondition::TotalMemoryPressure { threshold_percent: 90.0 }.to_string(),
+            "System memory above 90.0% used"
+        );
+    }
+
+    #[test]
+    fn test_system_condition_total_memory_pressure() {
+        use crate::detector::{MisbehaviorDetector, SystemCondition};
+        use crate::metrics::SystemMetrics;
+
+        let mut detector = MisbehaviorDetector::with_rules(vec![system_rule(
+            SystemCondition::TotalMemoryPressure { threshold_percent: 90.0 },
+        )]);
+
+        let mut metrics = SystemMetrics::default();
+        metrics.memory.total = 1000;
+        metrics.memory.used = 850;
+        assert!(detector.check_system(&[], &metrics).is_empty());
+
+        metrics.memory.used = 950;
+        let alerts = detector.check_system(&[], &metrics);
+        assert_eq!(alerts.len(), 1);
+        assert!(alerts[0].details.contains("95.0%"));
+
+        // Unknown totals never alert
+        assert!(detector.check_system(&[], &SystemMetrics::default()).is_empty());
+    }
+
+    #[test]
+    fn test_system_condition_per_user_cpu_above() {
+        use crate::detector::{MisbehaviorDetector, SystemCondition};
+        use crate::metrics::SystemMetrics;
+
+        let mut detector = MisbehaviorDetector::with_rules(vec![system_rule(
+            SystemCondition::PerUserCpuAbove { threshold: 100.0 },
+        )]);
+
+        let mut processes = Vec::new();
+        for (pid, user, cpu) in [(1, "alice", 60.0), (2, "alice", 50.0), (3, "bob", 90.0)] {
+            let mut p = synthetic_process(pid, "worker", cpu, 0);
+            p.info.user = user.to_string();
+            processes.push(p);
+        }
+
+        let alerts = detector.check_system(&processes, &SystemMetrics::default());
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].process_name, "alice");
+    }
+
+    #[test]
+    fn test_parse_multi_unit_systemctl_show() {
+        use crate::service::parse_show_output;
+
+        let output = "\
+    MainPID=812
+MemoryCurrent=15728640
+UnitFileState=enabled
+Id=sshd.service
+
+MainPID=0
+MemoryCurrent=[not set]
+UnitFileState=disabled
+Id=bluetooth.service
+
+Id=cron.service
+MainPID=901
+MemoryCurrent=18446744073709551615
+UnitFileState=static
+";
+
+        let details = parse_show_output(output);
+        assert_eq!(details.len(), 3);
+
+        let sshd = &details["sshd"];
+        assert_eq!(sshd.main_pid, Some(812));
+        assert_eq!(sshd.memory_usage, Some(15728640));
+        assert!(sshd.enabled);
+
+        let bluetooth = &details["bluetooth"];
+        assert_eq!(bluetooth.main_pid, None);
+        assert_eq!(bluetooth.memory_usage, None);
+        assert!(!bluetooth.enabled);
+
+        let cron = &details["cron"];
+        assert_eq!(cron.main_pid, Some(901));
+        assert_eq!(cron.memory_usage, None);
+        assert!(!cron.enabled);
+    }
+
+    #[test]
+    fn test_service_start_invalidates_cache() {
+        use crate::service::{ServiceDetails, ServiceManager};
+        use std::process::Command;
+
+        let manager = ServiceManager::new();
+        let details = ServiceDetails {
+            main_pid: Some(812),
+            memory_usage: Some(1024),
+            enabled: true,
+        };
+        manager.insert_cached("sshd", details.clone());
+        manager.insert_cached("cron", details.clone());
+        assert_eq!(manager.cached_details("sshd"), Some(details.clone()));
+
+        // Stand-ins for systemctl, so the test doesn't touch the host's units
+        manager.run_action("sshd", Command::new("true"), "start").unwrap();
+        assert_eq!(manager.cached_details("sshd"), None);
+        assert_eq!(manager.cached_details("cron"), Some(details.clone()));
+
+        // A failed action may still have changed the unit, so its cache goes too
+        manager.insert_cached("sshd", details.clone());
+        assert!(manager.run_action("sshd", Command::new("false"), "start").is_err());
+        assert_eq!(manager.cached_details("sshd"), None);
+    }
+
+    #[test]
+    fn test_generate_fstab_entry() {
+        let pm = crate::PartitionManager::new();
+
+        assert_eq!(
+            pm.generate_fstab_entry("1234-abcd", "/data", "ext4", None),
+            "UUID=1234-abcd /data ext4 defaults 0 2"
+        );
+        assert_eq!(
+            pm.generate_fstab_entry("1234-abcd", "/", "xfs", Some("noatime")),
+            "UUID=1234-abcd / xfs noatime 0 1"
+        );
+        assert_eq!(
+            pm.generate_fstab_entry("5678-ef01", "/boot/efi", "fat32", Some("umask=0077")),
+            "UUID=5678-ef01 /boot/efi vfat umask=0077 0 0"
+        );
+        assert_eq!(
+            pm.generate_fstab_entry("9abc", "", "swap", Some("sw")),
+            "UUID=9abc none swap sw 0 0"
+        );
+    }
+
+    #[test]
+    fn test_fstab_fields_validated_before_writing() {
+        use crate::partition::validate_fstab_fields;
+
+        assert!(validate_fstab_fields("/data", "ext4", Some("noatime,nofail")).is_ok());
+        assert!(validate_fstab_fields("/data", "ext4", None).is_ok());
+        // Empty options are written as "defaults"
+        assert!(validate_fstab_fields("/data", "ext4", Some("")).is_ok());
+        // Swap is written with "none" whatever the field holds
+        assert!(validate_fstab_fields("", "swap", None).is_ok());
+
+        assert!(validate_fstab_fields("", "ext4", None).is_err());
+        assert!(validate_fstab_fields("data", "ext4", None).is_err());
+        assert!(validate_fstab_fields("/my data", "ext4", None).is_err());
+        assert!(validate_fstab_fields("/data\n/dev/sda1 / ext4", "ext4", None).is_err());
+        assert!(validate_fstab_fields("/data", "ext4", Some("noatime, nofail")).is_err());
+    }
+
+    #[test]
+    fn test_fstab_duplicate_detection() {
+        use crate::partition::fstab_contains_uuid;
+
+        let fstab = "\
+    # /etc/fstab: static file system information.
+UUID=0a1b2c3d-1111-2222-3333-444455556666 /     ext4 errors=remount-ro 0 1
+UUID=\"7E2A-1F3C\"                        /boot/efi vfat umask=0077 0 1
+#UUID=deadbeef-0000-0000-0000-000000000000 /old ext4 defaults 0 2
+/dev/sdb1 /mnt/usb auto noauto 0 0
+";
+
+        assert!(fstab_contains_uuid(fstab, "0a1b2c3d-1111-2222-3333-444455556666"));
+        assert!(fstab_contains_uuid(fstab, "7e2a-1f3c"));
+        // Commented-out entries do not count
+        assert!(!fstab_contains_uuid(fstab, "deadbeef-0000-0000-0000-000000000000"));
+        assert!(!fstab_contains_uuid(fstab, "0a1b2c3d"));
+    }
+
+    fn command_line(cmd: &std::process::Command) -> Vec<String> {
+        std::iter::once(cmd.get_program())
+            .chain(cmd.get_args())
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn test_swap_command_construction() {
+        use crate::partition::{mkswap_command, swapoff_command, swapon_command};
+
+        assert_eq!(command_line(&mkswap_command("/dev/sdb2", None)), ["mkswap", "/dev/sdb2"]);
+        assert_eq!(
+            command_line(&mkswap_command("/dev/sdb2", Some("swap0"))),
+            ["mkswap", "-L", "swap0", "/dev/sdb2"]
+        );
+        assert_eq!(command_line(&swapon_command("/dev/sdb2")), ["swapon", "/dev/sdb2"]);
+        assert_eq!(command_line(&swapoff_command("/dev/sdb2")), ["swapoff", "/dev/sdb2"]);
+    }
+
+    #[test]
+    fn test_fsck_progress_parsing() {
+        use crate::partition::{for_each_progress_line, FsckProgressParser};
+
+        // Captured from `e2fsck -f -n -C 0 /dev/sdb1`; the bar is redrawn with carriage returns
+        let captured = "e2fsck 1.47.0 (5-Feb-2023)\n\
+            Pass 1: Checking inodes, blocks, and sizes\n\
+            /dev/sdb1: |====                                            | 8.1%   \r\
+            /dev/sdb1: |=============================                   / 58.3%   \r\
+            Pass 2: Checking directory structure\n\
+            /dev/sdb1: |==========================================      - 84.0%   \r\
+            Pass 5: Checking group summary information\n\
+            /dev/sdb1: |================================================| 100.0%   \r\
+            /dev/sdb1: 11/65536 files (0.0% non-contiguous), 12955/262144 blocks\n";
+
+        let mut parser = FsckProgressParser::default();
+        let mut updates = Vec::new();
+        for_each_progress_line(captured.as_bytes(), |line| {
+            if let Some(progress) = parser.feed(line) {
+                updates.push(progress);
+            }
+        })
+        .unwrap();
+
+        let percents: Vec<f32> = updates.iter().map(|p| p.percent).collect();
+        assert_eq!(percents, [0.0, 8.1, 58.3, 58.3, 84.0, 84.0, 100.0]);
+
+        let passes: Vec<Option<u8>> = updates.iter().map(|p| p.pass).collect();
+        assert_eq!(passes, [Some(1), Some(1), Some(1), Some(2), Some(2), Some(5), Some(5)]);
+    }
+
+    #[test]
+    fn test_e2fsck_exit_status_mapped_to_failure() {
+        use crate::partition::e2fsck_failure;
+
+        assert_eq!(e2fsck_failure(Some(0)), None);
+        // Errors corrected, with or without a reboot needed
+        assert_eq!(e2fsck_failure(Some(1)), None);
+        assert_eq!(e2fsck_failure(Some(3)), None);
+        // `-n` finding errors
+        assert_eq!(e2fsck_failure(Some(4)), Some("Filesystem errors left uncorrected"));
+        assert_eq!(e2fsck_failure(Some(8)), Some("e2fsck failed to run the check"));
+        assert_eq!(e2fsck_failure(Some(16)), Some("e2fsck usage or syntax error"));
+        assert_eq!(e2fsck_failure(Some(32 | 4)), Some("e2fsck was canceled"));
+        assert_eq!(e2fsck_failure(None), Some("e2fsck was killed"));
+    }
+
+    #[test]
+    fn test_parse_hdparm_output() {
+        use crate::partition::parse_hdparm_output;
+
+        let output = "\n/dev/sda:\n Timing buffered disk reads: 1540 MB in  3.00 seconds = 513.19 MB/sec\n";
+        assert_eq!(parse_hdparm_output(output), Some(513.19));
+
+        let slow = "\n/dev/sdc:\n Timing buffered disk reads:  10 MB in  3.41 seconds =   3.00 kB/sec\n";
+        assert!((parse_hdparm_output(slow).unwrap() - 3.0 / 1024.0).abs() < 1e-9);
+
+        assert_eq!(parse_hdparm_output("/dev/sda:\n HDIO_DRIVE_CMD(identify) failed"), None);
+    }
+
+    #[test]
+    fn test_clipboard_text_for_each_row_type() {
+        use crate::clipboard::ClipboardText;
+
+        let mut process = synthetic_process(4242, "firefox", 12.34, 512 * 1024 * 1024);
+        process.info.status = crate::process::ProcessStatus::Running;
+        process.info.command_line = vec!["/usr/bin/firefox".to_string(), "-P".to_string()];
+        assert_eq!(
+            process.clipboard_text(),
+            "PID 4242 firefox (user user) CPU 12.3% MEM 512.0 MB status Running cmd: /usr/bin/firefox -P"
+        );
+
+        let service = crate::SystemService {
+            name: "sshd".to_string(),
+            description: "OpenSSH Daemon".to_string(),
+            state: crate::ServiceState::Running,
+            enabled: true,
+            active_state: "active".to_string(),
+            sub_state: "running".to_string(),
+            memory_usage: Some(8 * 1024 * 1024),
+            cpu_usage: None,
+            main_pid: Some(812),
+        };
+        assert_eq!(
+            service.clipboard_text(),
+            "sshd.service active/running enabled=yes pid=812 mem=8.0 MB - OpenSSH Daemon"
+        );
+
+        let partition = crate::Partition {
+            device: "/dev/sda1".to_string(),
+            partition_number: Some(1),
+            filesystem: Some("ext4".to_string()),
+            label: None,
+            size_bytes: 100 * 1024 * 1024 * 1024,
+            used_bytes: 25 * 1024 * 1024 * 1024,
+            mount_point: Some("/".to_string()),
+            partition_type: None,
+            flags: Vec::new(),
+        };
+        assert_eq!(
+            partition.clipboard_text(),
+            "/dev/sda1 fs=ext4 label=- size=100.00 GB used=25.00 GB mount=/"
+        );
+    }
+
+    #[test]
+    fn test_staleness_decision() {
+        use crate::util::{staleness, Staleness};
+        use std::time::Duration;
+
+        let interval = Duration::from_secs(1);
+        assert_eq!(staleness(Duration::from_millis(0), interval), Staleness::Live);
+        assert_eq!(staleness(Duration::from_millis(1500), interval), Staleness::Live);
+        assert_eq!(staleness(Duration::from_secs(2), interval), Staleness::Live);
+        assert_eq!(staleness(Duration::from_millis(2001), interval), Staleness::Stale);
+        assert_eq!(staleness(Duration::from_secs(30), interval), Staleness::Stale);
+    }
+
+    #[test]
+    fn test_age_sort_uses_run_time() {
+        use crate::sort::{sort_processes, SortColumn};
+        use std::time::Duration;
+
+        let mut daemon = synthetic_process(1, "init", 0.0, 0);
+        daemon.stats.run_time = Duration::from_secs(86_400);
+        let mut shell = synthetic_process(300, "bash", 0.0, 0);
+        shell.stats.run_time = Duration::from_secs(600);
+        let mut fresh = synthetic_process(900, "ls", 0.0, 0);
+        fresh.stats.run_time = Duration::from_secs(1);
+        let mut twin = synthetic_process(250, "bash", 0.0, 0);
+        twin.stats.run_time = Duration::from_secs(600);
+
+        let mut processes = vec![shell, fresh, daemon, twin];
+
+        // Descending: longest running first, equal ages by PID
+        sort_processes(&mut processes, SortColumn::Age, false);
+        let pids: Vec<u32> = processes.iter().map(|p| p.info.pid).collect();
+        assert_eq!(pids, [1, 250, 300, 900]);
+
+        // Ascending: newest first
+        sort_processes(&mut processes, SortColumn::Age, true);
+        let pids: Vec<u32> = processes.iter().map(|p| p.info.pid).collect();
+        assert_eq!(pids, [900, 250, 300, 1]);
+    }
+
+    #[test]
+    fn test_format_duration() {
+        use crate::util::format_duration;
+        use std::time::Duration;
+
+        assert_eq!(format_duration(Duration::from_secs(42)), "42s");
+        assert_eq!(format_duration(Duration::from_secs(5 * 60 + 7)), "5m 07s");
+        assert_eq!(format_duration(Duration::from_secs(3 * 3600 + 12 * 60)), "3h 12m");
+        assert_eq!(format_duration(Duration::from_secs(2 * 86_400 + 4 * 3600)), "2d 04h");
+    }
+
+    #[test]
+    fn test_system_info_identity_fields() {
+        let monitor = crate::SystemMonitor::new();
+        let info = monitor.get_system_info();
+
+        assert!(!info.hostname.is_empty(), "hostname should be available on Linux");
+        assert!(!info.kernel_version.is_empty(), "kernel version should be available on Linux");
+        assert!(!info.architecture.is_empty());
+    }
+
+    #[test]
+    fn test_parse_cpu_model() {
+        use crate::monitor::parse_cpu_model;
+
+        let x86 = "processor\t: 0\nvendor_id\t: GenuineIntel\nmodel\t\t: 154\nmodel name\t: 12th Gen Intel(R) Core(TM) i7-1260P\n";
+        assert_eq!(parse_cpu_model(x86).as_deref(), Some("12th Gen Intel(R) Core(TM) i7-1260P"));
+
+        let arm = "processor\t: 0\nBogoMIPS\t: 108.00\n\nRevision\t: c03114\nModel\t\t: Raspberry Pi 4 Model B Rev 1.4\n";
+        assert_eq!(parse_cpu_model(arm).as_deref(), Some("Raspberry Pi 4 Model B Rev 1.4"));
+
+        assert_eq!(parse_cpu_model("processor\t: 0\n"), None);
+    }
+
+    #[test]
+    fn test_suspicious_exe_path_rule() {
+        use crate::detector::{MisbehaviorCondition, MisbehaviorDetector, MisbehaviorRule, Severity};
+        use std::path::PathBuf;
+
+        let rule = MisbehaviorRule {
+            name: "Suspicious Executable".to_string(),
+            description: "Suspicious exe".to_string(),
+            condition: MisbehaviorCondition::SuspiciousExePath,
+            severity: Severity::Warning,
+        };
+        let mut detector = MisbehaviorDetector::with_rules(vec![rule]);
+
+        let mut dropped = synthetic_process(100, "miner", 0.0, 0);
+        dropped.info.exe_path = Some(PathBuf::from("/tmp/.x/miner"));
+        let alerts = detector.check_process(&dropped);
+        assert_eq!(alerts.len(), 1);
+        assert!(alerts[0].details.contains("/tmp"), "details: {}", alerts[0].details);
+
+        let mut deleted = synthetic_process(101, "sshd", 0.0, 0);
+        deleted.info.exe_path = Some(PathBuf::from("/usr/sbin/sshd"));
+        deleted.info.exe_deleted = true;
+        let alerts = detector.check_process(&deleted);
+        assert_eq!(alerts.len(), 1);
+        assert!(alerts[0].details.contains("deleted"), "details: {}", alerts[0].details);
+
+        let mut normal = synthetic_process(102, "bash", 0.0, 0);
+        normal.info.exe_path = Some(PathBuf::from("/usr/bin/bash"));
+        assert!(detector.check_process(&normal).is_empty());
+
+        // A directory that merely starts with the same characters is not /tmp
+        let mut lookalike = synthetic_process(103, "tool", 0.0, 0);
+        lookalike.info.exe_path = Some(PathBuf::from("/tmpfs-tools/tool"));
+        assert!(detector.check_process(&lookalike).is_empty());
+    }
+
+    #[test]
+    fn test_name_exe_mismatch_rule() {
+        use crate::detector::{MisbehaviorCondition, MisbehaviorDetector, MisbehaviorRule, Severity};
+        use std::path::PathBuf;
+
+        let rule = MisbehaviorRule {
+            name: "Name/Executable Mismatch".to_string(),
+            description: "Masquerading".to_string(),
+            condition: MisbehaviorCondition::NameExeMismatch,
+            severity: Severity::Info,
+        };
+        let mut detector = MisbehaviorDetector::with_rules(vec![rule]);
+
+        let mut fake = synthetic_process(200, "nginx", 0.0, 0);
+        fake.info.exe_path = Some(PathBuf::from("/tmp/evil"));
+        let alerts = detector.check_process(&fake);
+        assert_eq!(alerts.len(), 1);
+        assert!(alerts[0].details.contains("nginx"), "details: {}", alerts[0].details);
+        assert!(alerts[0].details.contains("/tmp/evil"), "details: {}", alerts[0].details);
+
+        let mut real = synthetic_process(201, "nginx", 0.0, 0);
+        real.info.exe_path = Some(PathBuf::from("/usr/sbin/nginx"));
+        assert!(detector.check_process(&real).is_empty());
+
+        // Names are truncated to 15 characters by the kernel
+        let mut truncated = synthetic_process(202, "gnome-session-b", 0.0, 0);
+        truncated.info.exe_path = Some(PathBuf::from("/usr/libexec/gnome-session-binary"));
+        assert!(detector.check_process(&truncated).is_empty());
+
+        // Scripts run by an interpreter take the script's name
+        let mut script = synthetic_process(203, "backup.py", 0.0, 0);
+        script.info.exe_path = Some(PathBuf::from("/usr/bin/python3.12"));
+        assert!(detector.check_process(&script).is_empty());
+
+        // Kernel threads have no executable
+        let mut kworker = synthetic_process(204, "kworker/0:1", 0.0, 0);
+        kworker.info.parent_pid = Some(2);
+        assert!(detector.check_process(&kworker).is_empty());
+
+        // An interpreter keeps its exemption whatever argv[0] says
+        let mut node = synthetic_process(205, "server.js", 0.0, 0);
+        node.info.exe_path = Some(PathBuf::from("/usr/bin/node"));
+        node.info.command_line = vec!["node".to_string(), "server.js".to_string()];
+        assert!(detector.check_process(&node).is_empty());
+
+        // argv[0] is as easy to fake as the name, so a matching one is no excuse
+        let mut spoofed = synthetic_process(206, "sshd", 0.0, 0);
+        spoofed.info.exe_path = Some(PathBuf::from("/tmp/.cache/kdevtmpfsi"));
+        spoofed.info.command_line = vec!["/usr/sbin/sshd".to_string(), "-D".to_string()];
+        assert_eq!(detector.check_process(&spoofed).len(), 1);
+
+        // A short name is not a prefix of whatever it happens to start
+        let mut short = synthetic_process(207, "sh", 0.0, 0);
+        short.info.exe_path = Some(PathBuf::from("/var/tmp/shadowminer"));
+        short.info.command_line = vec!["sh".to_string()];
+        assert_eq!(detector.check_process(&short).len(), 1);
+    }
+
+    #[test]
+    fn test_parse_last_cpu_from_stat() {
+        use crate::cores::parse_last_cpu;
+
+        // Real /proc/{pid}/stat line; field 39 (processor) is 5
+        let stat = "1234 (Web Content (x)) S 1200 1200 1200 0 -1 4194560 51234 0 12 0 5123 812 0 0 20 0 \
+                    27 0 98765 2800000000 65000 18446744073709551615 1 1 0 0 0 0 0 16781312 1073745144 \
+                    0 0 0 17 5 0 0 0 0 0 0 0 0 0 0 0 0 0";
+        assert_eq!(parse_last_cpu(stat), Some(5));
+
+        assert_eq!(parse_last_cpu("1 (init) S 0 1"), None);
+        assert_eq!(parse_last_cpu("garbage"), None);
+    }
+
+    #[test]
+    fn test_group_processes_by_core() {
+        use crate::cores::group_by_core;
+
+        let mut a = synthetic_process(10, "a", 50.0, 0);
+        a.stats.last_cpu = 0;
+        let mut b = synthetic_process(11, "b", 80.0, 0);
+        b.stats.last_cpu = 0;
+        let mut c = synthetic_process(12, "c", 30.0, 0);
+        c.stats.last_cpu = 2;
+        let mut idle = synthetic_process(13, "idle", 0.1, 0);
+        idle.stats.last_cpu = 1;
+        let mut offline = synthetic_process(14, "offline", 90.0, 0);
+        offline.stats.last_cpu = 7;
+
+        let processes = vec![a, b, c, idle, offline];
+        let cores = group_by_core(&processes, 4, 1.0);
+
+        let pids: Vec<Vec<u32>> = cores.iter().map(|core| core.iter().map(|p| p.info.pid).collect()).collect();
+        assert_eq!(pids, vec![vec![11, 10], vec![], vec![12], vec![]]);
+    }
+
+    #[test]
+    fn test_ema_smoothed_sequence() {
+        use crate::util::Ema;
+
+        let mut ema = Ema::new(0.5);
+        let smoothed: Vec<f64> = [10.0, 20.0, 20.0, 0.0].iter().map(|s| ema.update(*s)).collect();
+        assert_eq!(smoothed, [10.0, 15.0, 17.5, 8.75]);
+
+        // Alpha of 1.0 passes samples through unchanged
+        let mut raw = Ema::new(1.0);
+        let passthrough: Vec<f64> = [3.0, 90.0, 1.0].iter().map(|s| raw.update(*s)).collect();
+        assert_eq!(passthrough, [3.0, 90.0, 1.0]);
+    }
+
+    #[test]
+    fn test_gauge_smoothing_leaves_metrics_raw() {
+        use crate::metrics::GaugeSmoothing;
+
+        let mut smoothing = GaugeSmoothing::new(0.5);
+        let mut metrics = crate::SystemMetrics::default();
+
+        metrics.cpu.total_usage = 0.0;
+        smoothing.update(&metrics);
+        metrics.cpu.total_usage = 100.0;
+        let gauges = smoothing.update(&metrics);
+
+        assert_eq!(gauges.cpu_usage, 50.0);
+        assert_eq!(metrics.cpu.total_usage, 100.0);
+    }
+
+    fn alert_with(pid: u32, rule_name: &str, severity: crate::detector::Severity) -> crate::MisbehaviorAlert {
+        crate::MisbehaviorAlert {
+            pid,
+            process_name: "test".to_string(),
+            rule_name: rule_name.to_string(),
+            description: String::new(),
+            severity,
+            timestamp: chrono::Utc::now(),
+            details: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_notification_sink_filters_by_severity_and_debounces() {
+        use crate::detector::Severity;
+        use crate::sink::{AlertSink, DesktopNotificationSink};
+        use std::sync::{Arc, Mutex};
+
+        let delivered = Arc::new(Mutex::new(Vec::new()));
+        let delivered_clone = delivered.clone();
+        let mut sink = DesktopNotificationSink::with_notifier(
+            Severity::Critical,
+            Box::new(move |alert| {
+                delivered_clone.lock().unwrap().push((alert.pid, alert.rule_name.clone()));
+                Ok(())
+            }),
+        );
+
+        sink.send(&alert_with(1, "High CPU Usage", Severity::Warning)).unwrap();
+        sink.send(&alert_with(1, "High CPU Usage", Severity::Info)).unwrap();
+        assert!(delivered.lock().unwrap().is_empty());
+
+        // A storm of identical critical alerts produces one notification
+        for _ in 0..5 {
+            sink.send(&alert_with(2, "Extreme CPU Usage", Severity::Critical)).unwrap();
+        }
+        sink.send(&alert_with(3, "Extreme CPU Usage", Severity::Critical)).unwrap();
+        assert_eq!(
+            *delivered.lock().unwrap(),
+            [(2, "Extreme CPU Usage".to_string()), (3, "Extreme CPU Usage".to_string())]
+        );
+
+        // Lowering the threshold lets warnings through
+        sink.set_min_severity(Severity::Warning);
+        sink.send(&alert_with(1, "High CPU Usage", Severity::Warning)).unwrap();
+        assert_eq!(delivered.lock().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_find_parent_index() {
+        use crate::process::find_parent_index;
+
+        let init = synthetic_process(1, "init", 0.0, 0);
+        let mut shell = synthetic_process(500, "bash", 0.0, 0);
+        shell.info.parent_pid = Some(1);
+        let mut child = synthetic_process(600, "make", 0.0, 0);
+        child.info.parent_pid = Some(500);
+        let mut orphan = synthetic_process(700, "cc1", 0.0, 0);
+        orphan.info.parent_pid = Some(999);
+
+        let processes = vec![child.clone(), init.clone(), shell.clone(), orphan.clone()];
+        assert_eq!(find_parent_index(&processes, &child), Some(2));
+        assert_eq!(find_parent_index(&processes, &shell), Some(1));
+        assert_eq!(find_parent_index(&processes, &orphan), None);
+        assert_eq!(find_parent_index(&processes, &init), None);
+
+        // A filtered list that hides the parent does not find it
+        let filtered = vec![child.clone(), orphan];
+        assert_eq!(find_parent_index(&filtered, &child), None);
+    }
+
+    #[test]
+    fn test_state_filter_matches() {
+        use crate::process::{ProcessStatus, StateFilter};
+
+        let mut running = synthetic_process(1, "worker", 50.0, 0);
+        running.info.status = ProcessStatus::Running;
+        let mut sleeping = synthetic_process(2, "daemon", 0.0, 0);
+        sleeping.info.status = ProcessStatus::Sleeping;
+        let mut zombie = synthetic_process(3, "defunct", 0.0, 0);
+        zombie.info.status = ProcessStatus::Zombie;
+
+        // An empty filter shows everything
+        let mut filter = StateFilter::default();
+        assert!(!filter.is_active());
+        assert!([&running, &sleeping, &zombie].iter().all(|p| filter.matches(&p.info)));
+
+        filter.toggle(ProcessStatus::Running);
+        filter.toggle(ProcessStatus::Zombie);
+        assert!(filter.matches(&running.info));
+        assert!(!filter.matches(&sleeping.info));
+        assert!(filter.matches(&zombie.info));
+        assert_eq!(filter.label(), "Running+Zombie");
+
+        // Toggling again removes the state
+        filter.toggle(ProcessStatus::Running);
+        assert!(!filter.matches(&running.info));
+        assert_eq!(filter, StateFilter::only(ProcessStatus::Zombie));
+
+        filter.clear();
+        assert!(filter.matches(&sleeping.info));
+    }
+
+    #[test]
+    fn test_export_alerts_round_trip() {
+        use crate::detector::Severity;
+        use crate::export::{export_alerts, ExportFormat};
+        use chrono::TimeZone;
+
+        let mut quoted = alert_with(42, "High CPU", Severity::Warning);
+        quoted.details = "CPU at 97%, says \"busy\"".to_string();
+        let alerts = vec![quoted, alert_with(7, "Zombie Process", Severity::Critical)];
+
+        let dir = std::env::temp_dir().join(format!("procmon-export-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let now = chrono::Utc.with_ymd_and_hms(2024, 5, 1, 13, 45, 10).unwrap();
+
+        let json_path = export_alerts(&alerts, &dir, ExportFormat::Json, now).unwrap();
+        assert_eq!(json_path, dir.join("alerts-20240501-134510.json"));
+        let reread: Vec<crate::MisbehaviorAlert> =
+            serde_json::from_str(&fs::read_to_string(&json_path).unwrap()).unwrap();
+        assert_eq!(reread.len(), 2);
+        assert_eq!(reread[0].pid, 42);
+        assert_eq!(reread[0].details, alerts[0].details);
+        assert_eq!(reread[1].severity, Severity::Critical);
+        assert_eq!(reread[1].timestamp, alerts[1].timestamp);
+
+        let csv_path = export_alerts(&alerts, &dir, ExportFormat::Csv, now).unwrap();
+        assert_eq!(csv_path, dir.join("alerts-20240501-134510.csv"));
+        let csv = fs::read_to_string(&csv_path).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], "timestamp,severity,pid,process_name,rule_name,description,details");
+        assert!(lines[1].ends_with(",Warning,42,test,High CPU,,\"CPU at 97%, says \"\"busy\"\"\""));
+        assert!(lines[2].starts_with(&alerts[1].timestamp.to_rfc3339()));
+        assert!(lines[2].ends_with(",Critical,7,test,Zombie Process,,"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_tab_names_and_startup_tab() {
+        use crate::tab::{startup_tab, Tab};
+
+        for tab in Tab::ALL {
+            assert_eq!(Tab::from_name(tab.name()).unwrap(), tab);
+        }
+        assert_eq!(Tab::from_name("Partitions").unwrap(), Tab::Partitions);
+        assert_eq!(Tab::from_name(" PROCESSES ").unwrap(), Tab::Processes);
+        assert_eq!(Tab::Alerts.index(), 6);
+        assert_eq!(Tab::from_name("logs").unwrap().index(), 7);
+
+        let err = Tab::from_name("proceses").unwrap_err().to_string();
+        assert!(err.contains("Unknown tab 'proceses'"));
+        assert!(err.contains("dashboard"));
+        assert!(Tab::from_name("").is_err());
+
+        let args = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(startup_tab(args(&[]), None).unwrap(), Tab::Dashboard);
+        assert_eq!(startup_tab(args(&["--tab", "services"]), None).unwrap(), Tab::Services);
+        assert_eq!(startup_tab(args(&["--tab=alerts"]), None).unwrap(), Tab::Alerts);
+        // The flag wins over the stored preference
+        assert_eq!(startup_tab(args(&[]), Some("network")).unwrap(), Tab::Network);
+        assert_eq!(startup_tab(args(&["--tab", "storage"]), Some("network")).unwrap(), Tab::Storage);
+        assert!(startup_tab(args(&["--tab", "bogus"]), None).is_err());
+        assert!(startup_tab(args(&["--tab"]), None).is_err());
+        assert!(startup_tab(args(&[]), Some("bogus")).is_err());
+    }
+
+    #[test]
+    fn test_core_labels_are_owned() {
+        use crate::cores::core_labels;
+
+        assert!(core_labels(0).is_empty());
+
+        // Owned strings are freed with the Vec instead of being leaked as &'static str
+        let labels: Vec<String> = core_labels(4);
+        assert_eq!(labels, vec!["0", "1", "2", "3"]);
+    }
+
+    #[test]
+    fn test_core_labels_reused_across_updates() {
+        use crate::cores::CoreLabels;
+
+        let mut labels = CoreLabels::default();
+        labels.update(8);
+        assert_eq!(labels.labels().len(), 8);
+        let storage = labels.labels().as_ptr();
+        let first_label = labels.labels()[0].as_ptr();
+
+        // Same core count on later refreshes: the existing strings are kept
+        for _ in 0..100 {
+            labels.update(8);
+        }
+        assert_eq!(labels.labels().as_ptr(), storage);
+        assert_eq!(labels.labels()[0].as_ptr(), first_label);
+
+        // A change in core count rebuilds them
+        labels.update(4);
+        assert_eq!(labels.labels(), ["0", "1", "2", "3"]);
+    }
+
+    #[test]
+    fn test_too_many_open_files_rule() {
+        use crate::detector::{MisbehaviorCondition, MisbehaviorDetector, MisbehaviorRule, Severity};
+        use crate::fds::{count_open_fds, parse_soft_open_files_limit, too_many_open_files};
+
+        let pid = std::process::id();
+        let before = count_open_fds(pid).expect("own fd directory is readable");
+        assert!(before >= 3, "stdin/stdout/stderr at least, got {}", before);
+
+        let held: Vec<fs::File> = (0..5).map(|_| fs::File::open("/proc/self/stat").unwrap()).collect();
+        assert!(count_open_fds(pid).unwrap() >= before + held.len());
+        drop(held);
+
+        let limits = "Limit                     Soft Limit           Hard Limit           Units     \n\
+                      Max processes             63204                63204                processes \n\
+                      Max open files            1024                 524288               files     \n";
+        assert_eq!(parse_soft_open_files_limit(limits), Some(1024));
+        assert_eq!(parse_soft_open_files_limit("Max open files            unlimited            unlimited            files"), None);
+        assert_eq!(parse_soft_open_files_limit(""), None);
+
+        assert!(too_many_open_files(101, 100, None));
+        assert!(!too_many_open_files(100, 100, None));
+        // Close to its own soft limit even though the absolute threshold is far away
+        assert!(too_many_open_files(950, 4096, Some(1024)));
+        assert!(!too_many_open_files(500, 4096, Some(1024)));
+        assert!(!too_many_open_files(5, 4096, Some(0)));
+
+        let rule = |threshold| MisbehaviorRule {
+            name: "Too Many Open Files".to_string(),
+            description: "fd leak".to_string(),
+            condition: MisbehaviorCondition::TooManyOpenFiles { threshold },
+            severity: Severity::Warning,
+        };
+        let me = synthetic_process(pid, "procmon-tests", 0.0, 0);
+
+        let mut strict = MisbehaviorDetector::with_rules(vec![rule(1)]);
+        let alerts = strict.check_process(&me);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].severity, Severity::Warning);
+        assert!(alerts[0].details.starts_with("Open files: "), "details: {}", alerts[0].details);
+
+        let mut relaxed = MisbehaviorDetector::with_rules(vec![rule(1_000_000)]);
+        assert!(relaxed.check_process(&me).is_empty());
+
+        // Processes whose fd directory can't be read never alert
+        let gone = synthetic_process(u32::MAX - 1, "gone", 0.0, 0);
+        assert!(strict.check_process(&gone).is_empty());
+    }
+
+    #[test]
+    fn test_io_throttle_cgroup_construction() {
+        use crate::throttle::{io_max_line, is_throttle_cgroup, parse_cgroup_v2_path, parse_throttle_input, throttle_cgroup_path};
+        use std::path::Path;
+
+        let root = Path::new("/sys/fs/cgroup");
+        let cgroup = "12:pids:/system.slice/backup.service\n0::/system.slice/backup.service\n";
+        let current = parse_cgroup_v2_path(cgroup).unwrap();
+        assert_eq!(current, "/system.slice/backup.service");
+        assert_eq!(parse_cgroup_v2_path("12:pids:/system.slice\n"), None);
+
+        // Below the process's own cgroup, named by PID and start time
+        let throttled = throttle_cgroup_path(root, current, 4242, 987654);
+        assert_eq!(throttled, Path::new("/sys/fs/cgroup/system.slice/backup.service/procmon-throttle-4242-987654"));
+        assert!(is_throttle_cgroup(&throttled));
+        assert!(!is_throttle_cgroup(Path::new("/sys/fs/cgroup/system.slice/backup.service")));
+        // A reused PID gets a different group
+        assert_ne!(throttle_cgroup_path(root, current, 4242, 990000), throttled);
+        // A process that is already throttled keeps its group
+        let inside = "/system.slice/backup.service/procmon-throttle-4242-987654";
+        assert_eq!(throttle_cgroup_path(root, inside, 4242, 987654), throttled);
+
+        assert_eq!(io_max_line("8:0", Some(1048576), Some(2097152)), "8:0 rbps=1048576 wbps=2097152");
+        assert_eq!(io_max_line("259:0", None, Some(512)), "259:0 rbps=max wbps=512");
+        assert_eq!(io_max_line("8:16", None, None), "8:16 rbps=max wbps=max");
+
+        let mb = 1024 * 1024;
+        assert_eq!(parse_throttle_input("10").unwrap(), (Some(10 * mb), Some(10 * mb)));
+        assert_eq!(parse_throttle_input(" 0 5 ").unwrap(), (None, Some(5 * mb)));
+        assert_eq!(parse_throttle_input("20 0").unwrap(), (Some(20 * mb), None));
+        assert!(parse_throttle_input("").is_err());
+        assert!(parse_throttle_input("fast").is_err());
+        assert!(parse_throttle_input("1 2 3").is_err());
+    }
+
+    #[test]
+    fn test_parse_list_dependencies() {
+        use crate::service::parse_list_dependencies;
+
+        let output = "sshd.service\n\
+                      ● ├─system.slice\n\
+                      ○ ├─sshd-keygen.target\n\
+                      ● │ ├─sshd-keygen@ecdsa.service\n\
+                      ● │ └─sshd-keygen@rsa.service\n\
+                      ● └─sysinit.target\n\
+                      ●   ├─systemd-journald.service\n\
+                      ●   ├─system.slice\n\
+                      ●   └─-.mount\n";
+        assert_eq!(
+            parse_list_dependencies(output),
+            vec![
+                "system.slice",
+                "sshd-keygen.target",
+                "sshd-keygen@ecdsa.service",
+                "sshd-keygen@rsa.service",
+                "sysinit.target",
+                "systemd-journald.service",
+                "-.mount",
+            ]
+        );
+
+        // Reverse output with ASCII tree characters (non-UTF-8 terminals)
+        let reverse = "dbus.service\n\
+                       * |-dbus.socket\n\
+                       * `-multi-user.target\n\
+                       *   `-graphical.target\n";
+        assert_eq!(
+            parse_list_dependencies(reverse),
+            vec!["dbus.socket", "multi-user.target", "graphical.target"]
+        );
+
+        assert!(parse_list_dependencies("lonely.service\n").is_empty());
+        assert!(parse_list_dependencies("").is_empty());
+    }
+
+    #[test]
+    fn test_stop_confirmation_gating() {
+        use crate::service::{plan_stop, stop_command, StopMode, StopPlan};
+
+        assert_eq!(plan_stop(Ok(Vec::new())).unwrap(), StopPlan::Immediate);
+
+        let dependents = vec!["multi-user.target".to_string(), "cups-browsed.service".to_string()];
+        match plan_stop(Ok(dependents.clone())).unwrap() {
+            StopPlan::NeedsConfirmation { dependents: shown } => assert_eq!(shown, dependents),
+            StopPlan::Immediate => panic!("a unit with dependents must ask before stopping"),
+        }
+
+        // Dependents that couldn't be listed must not turn into an unconfirmed stop
+        assert!(plan_stop(Err(anyhow::anyhow!("Failed to list dependencies: bus timeout"))).is_err());
+
+        assert_eq!(
+            command_line(&stop_command("cups", StopMode::Default)),
+            ["systemctl", "stop", "cups.service"]
+        );
+        assert_eq!(
+            command_line(&stop_command("cups", StopMode::ReplaceIrreversibly)),
+            ["systemctl", "stop", "--job-mode=replace-irreversibly", "cups.service"]
+        );
+    }
+
+    #[test]
+    fn test_selection_follows_process_across_refresh() {
+        use crate::selection::reconcile_selection;
+        use crate::sort::{sort_processes, SortColumn};
+
+        let mut processes = vec![
+            synthetic_process(10, "a", 50.0, 0),
+            synthetic_process(20, "b", 40.0, 0),
+            synthetic_process(30, "c", 30.0, 0),
+            synthetic_process(40, "d", 20.0, 0),
+            synthetic_process(50, "e", 10.0, 0),
+        ];
+
+        // PID 40 selected at index 3, shown on the second visible row (scrolled by 2)
+        let selected = Some(processes[3].key());
+
+        // Next refresh: CPU usage changes and the list is re-sorted
+        processes[3].stats.cpu_usage = 99.0;
+        processes[0].stats.cpu_usage = 5.0;
+        sort_processes(&mut processes, SortColumn::Cpu, false);
+        assert_eq!(processes[0].info.pid, 40);
+
+        let (index, scroll) = reconcile_selection(&processes, selected, 3, 2);
+        assert_eq!(processes[index].info.pid, 40);
+        assert_eq!((index, scroll), (0, 0));
+
+        // Moving down the list keeps the row's position on screen
+        let selected = processes.iter().find(|p| p.info.pid == 10).map(|p| p.key());
+        let (index, scroll) = reconcile_selection(&processes, selected, 1, 0);
+        assert_eq!(processes[index].info.pid, 10);
+        assert_eq!((index, scroll), (4, 3));
+
+        // A process that exited leaves the selection at the same index, clamped
+        let exited = Some(synthetic_process(999, "gone", 0.0, 0).key());
+        let (index, scroll) = reconcile_selection(&processes[..2], exited, 3, 2);
+        assert_eq!((index, scroll), (1, 1));
+
+        // So does one whose PID was reused by a process started later
+        let mut reused = processes[0].key();
+        reused.start_time -= chrono::Duration::seconds(60);
+        let (index, scroll) = reconcile_selection(&processes, Some(reused), 3, 2);
+        assert_eq!((index, scroll), (3, 2));
+
+        assert_eq!(reconcile_selection(&[], selected, 3, 2), (0, 0));
+    }
+
+    #[test]
+    fn test_process_columns_header_matches_configuration() {
+        use crate::columns::{
+            format_header_line, format_row_line, header_labels, parse_columns, process_columns, ProcessColumn,
+            DEFAULT_COLUMNS,
+        };
+
+        let columns = parse_columns("pid, Name,cpu,virtmem,threads,nice,starttime").unwrap();
+        assert_eq!(
+            header_labels(&columns, false),
+            ["PID", "Name", "CPU %", "Virt (MB)", "Threads", "Nice", "Started"]
+        );
+        assert_eq!(header_labels(&columns[..2], true), ["PID", "Command"]);
+
+        // Order follows the preference, not the enum
+        let reordered = parse_columns("status,pid").unwrap();
+        assert_eq!(header_labels(&reordered, false), ["Status", "PID"]);
+
+        let err = parse_columns("pid,colour").unwrap_err().to_string();
+        assert!(err.contains("Unknown column 'colour'"), "{}", err);
+        assert!(parse_columns("pid,pid").is_err());
+        assert!(parse_columns(" , ").is_err());
+
+        let args = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(process_columns(args(&[]), None).unwrap(), DEFAULT_COLUMNS);
+        assert_eq!(process_columns(args(&["--columns=pid,user"]), Some("cpu")).unwrap(), [ProcessColumn::Pid, ProcessColumn::User]);
+        assert_eq!(process_columns(args(&[]), Some("cpu")).unwrap(), [ProcessColumn::Cpu]);
+
+        // Monospace header and rows line up column for column
+        let mut process = synthetic_process(4242, "a-rather-long-process-name", 12.5, 3 * 1024 * 1024);
+        process.stats.nice = -5;
+        process.stats.num_threads = 17;
+        let header = format_header_line(&columns, false);
+        let row = format_row_line(&columns, &process, false);
+        assert_eq!(header.len(), row.len());
+        assert!(header.starts_with("PID      Name "));
+        assert!(row.starts_with("4242     a-rather-long-pro... "));
+        assert!(row.contains("  12.5% "));
+        assert!(row.contains("      17 "));
+        assert!(row.contains("   -5 "));
+    }
+
+    #[test]
+    fn test_stat_field() {
+        use crate::util::stat_field;
+
+        let stat = "1234 (my (odd) proc) S 1 1234 1234 0 -1 4194560 100 0 0 0 5 3 0 0 20 -5 7 0 123";
+        assert_eq!(stat_field(stat, 3), Some("S"));
+        assert_eq!(stat_field(stat, 4), Some("1"));
+        assert_eq!(stat_field(stat, 19), Some("-5"));
+        assert_eq!(stat_field(stat, 20), Some("7"));
+        assert_eq!(stat_field(stat, 2), None);
+        assert_eq!(stat_field(stat, 99), None);
+        assert_eq!(stat_field("garbage", 3), None);
+    }
+
+    #[test]
+    fn test_parse_vm_swap() {
+        use crate::monitor::parse_vm_swap;
+        use crate::sort::{sort_processes, SortColumn};
+
+        let status = "Name:\tfirefox\n\
+                      State:\tS (sleeping)\n\
+                      VmRSS:\t  512000 kB\n\
+                      VmSwap:\t  204800 kB\n\
+                      Threads:\t87\n";
+        assert_eq!(parse_vm_swap(status), Some(204800 * 1024));
+        assert_eq!(parse_vm_swap("VmSwap:\t       0 kB\n"), Some(0));
+        // Kernel threads have no VmSwap line
+        assert_eq!(parse_vm_swap("Name:\tkthreadd\nState:\tS (sleeping)\n"), None);
+        assert_eq!(parse_vm_swap("VmSwap:\tlots\n"), None);
+
+        let mut heavy = synthetic_process(1, "heavy", 0.0, 0);
+        heavy.stats.swap_usage = 200 * 1024 * 1024;
+        let mut light = synthetic_process(2, "light", 0.0, 0);
+        light.stats.swap_usage = 1024;
+        let mut processes = vec![light, heavy];
+        sort_processes(&mut processes, SortColumn::Swap, false);
+        assert_eq!(processes[0].info.pid, 1);
+    }
+
+    #[test]
+    fn test_signal_parent_targets_parent_pid() {
+        use crate::process::ProcessStatus;
+        use crate::zombie::{reap_target, signal_parent_command};
+
+        let mut zombie = synthetic_process(4321, "defunct", 0.0, 0);
+        zombie.info.status = ProcessStatus::Zombie;
+        zombie.info.parent_pid = Some(1200);
+
+        assert_eq!(reap_target(&zombie.info).unwrap(), 1200);
+        let cmd = signal_parent_command(&zombie.info).unwrap();
+        assert_eq!(command_line(&cmd), vec!["kill", "-CHLD", "1200"]);
+
+        // Only zombies with a parent can be reaped this way
+        zombie.info.parent_pid = None;
+        assert!(signal_parent_command(&zombie.info).is_err());
+        let mut running = synthetic_process(4322, "worker", 0.0, 0);
+        running.info.parent_pid = Some(1200);
+        assert!(reap_target(&running.info).is_err());
+    }
+
+    #[test]
+    fn test_zombies_accumulating_points_at_parent() {
+        use crate::detector::{MisbehaviorCondition, MisbehaviorDetector, MisbehaviorRule, Severity};
+        use crate::metrics::SystemMetrics;
+        use crate::process::ProcessStatus;
+
+        let mut detector = MisbehaviorDetector::with_rules(vec![MisbehaviorRule {
+            name: "Zombies Accumulating".to_string(),
+            description: "Parent not reaping".to_string(),
+            condition: MisbehaviorCondition::ZombiesAccumulating { min_zombies: 2 },
+            severity: Severity::Warning,
+        }]);
+        let zombie = |pid: u32| {
+            let mut process = synthetic_process(pid, "defunct", 0.0, 0);
+            process.info.status = ProcessStatus::Zombie;
+            process.info.parent_pid = Some(800);
+            process
+        };
+
+        let mut processes = vec![synthetic_process(800, "leaky-server", 0.0, 0), zombie(801)];
+        assert!(detector.check_system(&processes, &SystemMetrics::default()).is_empty());
+
+        processes.push(zombie(802));
+        let alerts = detector.check_system(&processes, &SystemMetrics::default());
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].pid, 800);
+        assert_eq!(alerts[0].process_name, "leaky-server");
+        assert!(alerts[0].details.contains("2 zombie children (was 1)"));
+
+        // A steady count is not reported again
+        assert!(detector.check_system(&processes, &SystemMetrics::default()).is_empty());
+    }
+
+    #[test]
+    fn test_parse_file_nr_and_percent_of_limit() {
+        use crate::counters::{limit_level, parse_file_nr, parse_loadavg_tasks, percent_of_limit, LimitLevel, SystemCounters};
+
+        assert_eq!(parse_file_nr("10656\t0\t9223372036854775807\n"), Some((10656, 9223372036854775807)));
+        assert_eq!(parse_file_nr("8192 0 10240"), Some((8192, 10240)));
+        assert_eq!(parse_file_nr("8192 0"), None);
+        assert_eq!(parse_file_nr("a b c"), None);
+
+        assert_eq!(percent_of_limit(8192, Some(10240)), Some(80.0));
+        assert_eq!(percent_of_limit(5, Some(0)), None);
+        assert_eq!(percent_of_limit(5, None), None);
+
+        assert_eq!(limit_level(Some(50.0)), LimitLevel::Normal);
+        assert_eq!(limit_level(Some(80.0)), LimitLevel::Warning);
+        assert_eq!(limit_level(Some(99.0)), LimitLevel::Critical);
+        assert_eq!(limit_level(None), LimitLevel::Normal);
+
+        let counters = SystemCounters { open_files: 9800, file_max: Some(10000), ..Default::default() };
+        assert_eq!(limit_level(counters.open_files_percent()), LimitLevel::Critical);
+
+        assert_eq!(parse_loadavg_tasks("0.52 0.58 0.59 2/1234 5678\n"), Some(1234));
+        assert_eq!(parse_loadavg_tasks("0.52 0.58 0.59"), None);
+        // A few processes with many threads exhaust PIDs long before the process count shows it
+        let counters = SystemCounters { processes: 100, threads: 32_000, pids_in_use: 32_000, pid_max: Some(32768), ..Default::default() };
+        assert_eq!(limit_level(counters.pids_percent()), LimitLevel::Critical);
+        let collected = SystemCounters::collect(&[]);
+        assert!(collected.pids_in_use > 0, "the kernel's task count covers threads procmon doesn't list");
+    }
+
+    #[test]
+    fn test_disabled_collectors_yield_empty_metrics() {
+        use crate::monitor::{MonitorConfig, SystemMonitor};
+
+        assert!(MonitorConfig::default().collect_gpu);
+
+        let config = MonitorConfig {
+            collect_gpu: false,
+            collect_usb: false,
+            collect_disk_io: false,
+            collect_network: false,
+            collect_temperature: false,
+        };
+        let monitor = SystemMonitor::with_config(config);
+        assert_eq!(monitor.config(), config);
+        monitor.refresh();
+
+        let metrics = monitor.get_system_metrics().unwrap();
+        assert!(metrics.gpus.is_empty());
+        assert!(metrics.usb_io.is_empty());
+        assert!(metrics.disk_io.is_empty());
+        assert!(metrics.network.is_empty());
+        assert_eq!(metrics.cpu.temperature, None);
+    }
+
+    #[test]
+    fn test_parse_node_meminfo() {
+        use crate::monitor::parse_node_meminfo;
+
+        let meminfo = "\
+    Node 1 MemTotal:       65842216 kB
+Node 1 MemFree:        12039480 kB
+Node 1 MemUsed:        53802736 kB
+Node 1 Active:         30122524 kB
+Node 1 HugePages_Total:     0
+";
+        let node = parse_node_meminfo(1, meminfo).unwrap();
+        assert_eq!(node.id, 1);
+        assert_eq!(node.total, 65842216 * 1024);
+        assert_eq!(node.free, 12039480 * 1024);
+        assert_eq!(node.used(), 53802736 * 1024);
+
+        // Lines for another node or missing fields are not picked up
+        assert!(parse_node_meminfo(0, meminfo).is_none());
+        assert!(parse_node_meminfo(1, "Node 1 MemTotal: 1024 kB\n").is_none());
+    }
+
+    #[test]
+    fn test_temperature_unit_conversion() {
+        use crate::temperature::{format_temp, temperature_unit, TemperatureUnit};
+
+        assert_eq!(TemperatureUnit::Fahrenheit.convert(0.0), 32.0);
+        assert_eq!(TemperatureUnit::Fahrenheit.convert(100.0), 212.0);
+        assert_eq!(TemperatureUnit::Celsius.convert(100.0), 100.0);
+        assert_eq!(format_temp(0.0, TemperatureUnit::Fahrenheit), "32.0°F");
+        assert_eq!(format_temp(100.0, TemperatureUnit::Celsius), "100.0°C");
+
+        let args = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(temperature_unit(args(&[]), None).unwrap(), TemperatureUnit::Celsius);
+        assert_eq!(temperature_unit(args(&[]), Some("F")).unwrap(), TemperatureUnit::Fahrenheit);
+        assert_eq!(temperature_unit(args(&["--temp-unit=c"]), Some("f")).unwrap(), TemperatureUnit::Celsius);
+        assert!(temperature_unit(args(&["--temp-unit", "kelvin"]), None).is_err());
+    }
+
+    #[test]
+    fn test_restart_runs_as_original_user() {
+        use crate::process::ProcessInfo;
+        use crate::restart::RestartCommand;
+
+        let mut info = ProcessInfo::new(4242, "editor".to_string(), "alice".to_string(), 1000);
+        info.gid = 1001;
+        info.exe_path = Some("/usr/bin/editor".into());
+        info.command_line = vec!["editor".to_string(), "notes.txt".to_string()];
+
+        // Root drops to the owner's uid and gid
+        let restart = RestartCommand::for_process(&info, 0).unwrap();
+        assert_eq!(restart.credentials, Some((1000, 1001)));
+        assert_eq!(restart.program, std::path::PathBuf::from("/usr/bin/editor"));
+        assert_eq!(restart.args, vec!["notes.txt"]);
+
+        // The owner restarts their own process without changing credentials
+        assert_eq!(RestartCommand::for_process(&info, 1000).unwrap().credentials, None);
+
+        // Another unprivileged user is refused
+        let err = RestartCommand::for_process(&info, 1002).unwrap_err();
+        assert!(err.to_string().contains("only root"));
+
+        // Root's own processes stay root
+        let root_info = ProcessInfo { uid: 0, ..info.clone() };
+        assert_eq!(RestartCommand::for_process(&root_info, 0).unwrap().credentials, None);
+    }
+
+    #[test]
+    fn test_parse_status_id() {
+        use crate::monitor::parse_status_id;
+
+        let status = "Name:\tbash\nUid:\t1000\t1000\t1000\t1000\nGid:\t100\t100\t100\t100\n";
+        assert_eq!(parse_status_id(status, "Uid:"), Some(1000));
+        assert_eq!(parse_status_id(status, "Gid:"), Some(100));
+        assert_eq!(parse_status_id("Name:\tbash\n", "Gid:"), None);
+    }
+
+    #[test]
+    fn test_script_rule_fires() {
+        use crate::detector::{MisbehaviorCondition, MisbehaviorDetector, MisbehaviorRule, Severity};
+        use std::time::{Duration, Instant};
+
+        // `false` is the most trivial script there is: it always exits nonzero
+        let mut detector = MisbehaviorDetector::with_rules(vec![MisbehaviorRule {
+            name: "Custom Script".to_string(),
+            description: "Site-specific check".to_string(),
+            condition: MisbehaviorCondition::Script { path: "/bin/false".to_string() },
+            severity: Severity::Info,
+        }]);
+        let process = synthetic_process(4242, "app", 0.0, 0);
+
+        // Scripts run in the background, so the first check only starts the run
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let alerts = loop {
+            let alerts = detector.check_process(&process);
+            if !alerts.is_empty() || Instant::now() > deadline {
+                break alerts;
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        };
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].pid, 4242);
+        assert!(alerts[0].details.contains("exit status: 1"), "details: {}", alerts[0].details);
+    }
+
+    #[test]
+    fn test_run_script_verdicts() {
+        use crate::script::{run_script, ScriptVerdict};
+        use std::path::Path;
+        use std::time::{Duration, Instant};
+
+        let timeout = Duration::from_secs(5);
+        assert_eq!(run_script(Path::new("/bin/true"), b"{}", timeout), ScriptVerdict::Pass);
+
+        // Output is a violation; `cat` shows the snapshot arrives on stdin
+        let json = serde_json::to_vec(&synthetic_process(4242, "app", 0.0, 0)).unwrap();
+        match run_script(Path::new("/bin/cat"), &json, timeout) {
+            ScriptVerdict::Violation(message) => assert!(message.contains("\"pid\":4242"), "message: {}", message),
+            other => panic!("unexpected verdict {:?}", other),
+        }
+
+        // A script that never exits is killed at the timeout
+        let started = Instant::now();
+        let verdict = run_script(Path::new("/bin/yes"), b"", Duration::from_millis(200));
+        assert!(matches!(verdict, ScriptVerdict::Failed(_)), "verdict: {:?}", verdict);
+        assert!(started.elapsed() < Duration::from_secs(3));
+
+        assert!(matches!(
+            run_script(Path::new("/nonexistent/rule"), b"", timeout),
+            ScriptVerdict::Failed(_)
+        ));
+    }
+
+    #[test]
+    fn test_rolling_cpu_average_smooths_spikes() {
+        use crate::util::RollingAverages;
+
+        let mut averages = RollingAverages::new(5);
+        let spiky = [2.0, 95.0, 3.0, 90.0, 1.0, 99.0, 4.0];
+        for sample in spiky {
+            averages.record(7, sample);
+        }
+
+        let smoothed = averages.average(7).unwrap();
+        assert!(smoothed > 1.0 && smoothed < 99.0, "smoothed: {}", smoothed);
+        // Only the last five samples count
+        assert_eq!(smoothed, (3.0 + 90.0 + 1.0 + 99.0 + 4.0) / 5.0);
+
+        // Dead PIDs are forgotten
+        averages.record(8, 50.0);
+        averages.retain(|pid| pid == 8);
+        assert_eq!(averages.average(7), None);
+        assert_eq!(averages.average(8), Some(50.0));
+    }
+
+    #[test]
+    fn test_disk_sleep_status_mapping() {
+        use crate::process::ProcessStatus;
+        use crate::util::stat_field;
+
+        assert_eq!(ProcessStatus::from_stat_char('D'), ProcessStatus::DiskSleep);
+        assert_eq!(ProcessStatus::from_stat_char('S'), ProcessStatus::Sleeping);
+        assert_eq!(ProcessStatus::from_stat_char('I'), ProcessStatus::Sleeping);
+        assert_eq!(ProcessStatus::from_stat_char('R'), ProcessStatus::Running);
+        assert_eq!(ProcessStatus::from_stat_char('t'), ProcessStatus::Stopped);
+        assert_eq!(ProcessStatus::from_stat_char('Z'), ProcessStatus::Zombie);
+        assert_eq!(ProcessStatus::from_stat_char('?'), ProcessStatus::Unknown);
+
+        let stat = "812 (mount.nfs) D 1 812 812 0 -1 4194560 120 0 0 0 0 3 0 0 20 0 1 0 5000";
+        let state = stat_field(stat, 3).and_then(|s| s.chars().next()).unwrap();
+        assert_eq!(ProcessStatus::from_stat_char(state), ProcessStatus::DiskSleep);
+    }
+
+    #[test]
+    fn test_stuck_in_disk_sleep_needs_uninterrupted_d_state() {
+        use crate::detector::{MisbehaviorCondition, MisbehaviorDetector, MisbehaviorRule, Severity};
+        use crate::process::ProcessStatus;
+
+        let mut detector = MisbehaviorDetector::with_rules(vec![MisbehaviorRule {
+            name: "Stuck".to_string(),
+            description: "D state".to_string(),
+            condition: MisbehaviorCondition::StuckInDiskSleep { duration_secs: 0 },
+            severity: Severity::Warning,
+        }]);
+
+        let mut process = synthetic_process(812, "mount.nfs", 0.0, 0);
+        process.info.status = ProcessStatus::Sleeping;
+        assert!(detector.check_process(&process).is_empty());
+
+        process.info.status = ProcessStatus::DiskSleep;
+        let alerts = detector.check_process(&process);
+        assert_eq!(alerts.len(), 1);
+        assert!(alerts[0].details.contains("uninterruptible"));
+
+        // With a real threshold, a fresh D-state run is not yet reported
+        let mut patient = MisbehaviorDetector::with_rules(vec![MisbehaviorRule {
+            condition: MisbehaviorCondition::StuckInDiskSleep { duration_secs: 30 },
+            ..detector.get_rules()[0].clone()
+        }]);
+        assert!(patient.check_process(&process).is_empty());
+    }
+
+    #[test]
+    fn test_alert_rate_cap_coalesces_overflow() {
+        use crate::detector::{
+            AlertRateLimiter, MisbehaviorCondition, MisbehaviorDetector, MisbehaviorRule, Severity, SUPPRESSED_RULE_NAME,
+        };
+        use std::time::{Duration, Instant};
+
+        // 1000 zombies trip the same rule in one pass
+        let mut detector = MisbehaviorDetector::with_rules(vec![MisbehaviorRule {
+            name: "Zombie".to_string(),
+            description: "Zombie".to_string(),
+            condition: MisbehaviorCondition::ZombieProcess,
+            severity: Severity::Warning,
+        }]);
+        let mut alerts = Vec::new();
+        for pid in 1..=1000 {
+            let mut process = synthetic_process(pid, "defunct", 0.0, 0);
+            process.info.status = crate::process::ProcessStatus::Zombie;
+            alerts.extend(detector.check_process(&process));
+        }
+        assert_eq!(alerts.len(), 1000);
+
+        let limited = detector.limit_rate(alerts);
+        let max = AlertRateLimiter::DEFAULT_MAX_PER_SEC;
+        assert_eq!(limited.len(), max + 1);
+        let summary = limited.last().unwrap();
+        assert_eq!(summary.rule_name, SUPPRESSED_RULE_NAME);
+        assert_eq!(summary.details, format!("{} additional alerts suppressed", 1000 - max));
+        assert_eq!(summary.severity, Severity::Warning);
+
+        // The budget is shared within the second and resets after it; critical alerts go first
+        let mut limiter = AlertRateLimiter::new(Some(2));
+        let start = Instant::now();
+        let batch = || {
+            vec![
+                alert_with(1, "a", Severity::Info),
+                alert_with(2, "b", Severity::Critical),
+                alert_with(3, "c", Severity::Info),
+            ]
+        };
+        let first = limiter.limit(batch(), start);
+        assert_eq!(first.iter().map(|a| a.pid).collect::<Vec<_>>(), vec![2, 1, 0]);
+        let second = limiter.limit(batch(), start + Duration::from_millis(500));
+        assert_eq!(second.len(), 1);
+        assert_eq!(second[0].details, "3 additional alerts suppressed");
+        assert_eq!(limiter.limit(batch(), start + Duration::from_secs(1)).len(), 3);
+
+        let mut unlimited = AlertRateLimiter::new(None);
+        assert_eq!(unlimited.limit(batch(), start).len(), 3);
+    }
+
+    #[test]
+    fn test_search_highlight_segments() {
+        use crate::search::{highlight_ranges, matches_search, segments};
+
+        let ranges = highlight_ranges("FireFox-firefox", "fox");
+        assert_eq!(ranges, vec![4..7, 12..15]);
+        assert_eq!(
+            segments("FireFox-firefox", &ranges),
+            vec![("Fire", false), ("Fox", true), ("-fire", false), ("fox", true)]
+        );
+
+        // Matches never overlap and whole-text matches yield a single highlighted piece
+        assert_eq!(highlight_ranges("aaaa", "aa"), vec![0..2, 2..4]);
+        assert_eq!(segments("bash", &highlight_ranges("bash", "BASH")), vec![("bash", true)]);
+
+        // Ranges stay on character boundaries around multibyte text
+        let text = "café-Ärger";
+        let ranges = highlight_ranges(text, "är");
+        assert_eq!(segments(text, &ranges), vec![("café-", false), ("Är", true), ("ger", false)]);
+
+        // An empty query or no match leaves the text as one plain piece
+        assert!(highlight_ranges("bash", "").is_empty());
+        assert_eq!(segments("bash", &highlight_ranges("bash", "zsh")), vec![("bash", false)]);
+
+        let process = synthetic_process(4242, "Postgres", 0.0, 0);
+        assert!(matches_search(&process.info, ""));
+        assert!(matches_search(&process.info, "postGRES"));
+        assert!(matches_search(&process.info, "424"));
+        assert!(matches_search(&process.info, "USER"));
+        assert!(!matches_search(&process.info, "nginx"));
+    }
+
+    #[test]
+    fn test_manual_refresh_updates_last_update() {
+        use crate::util::RefreshSchedule;
+        use std::time::{Duration, Instant};
+
+        let start = Instant::now();
+        let mut schedule = RefreshSchedule::new(Duration::from_secs(1), start);
+        assert!(!schedule.is_due(start + Duration::from_millis(500)));
+        assert!(schedule.is_due(start + Duration::from_secs(1)));
+
+        // A request right after a refresh waits for the minimum CPU sampling gap
+        schedule.request_now();
+        assert!(!schedule.is_due(start + RefreshSchedule::MIN_SPACING / 2));
+        let now = start + RefreshSchedule::MIN_SPACING;
+        assert!(schedule.is_due(now));
+
+        schedule.mark_refreshed(now);
+        assert_eq!(schedule.last_update(), now);
+        assert!(!schedule.is_requested());
+        assert!(!schedule.is_due(now + RefreshSchedule::MIN_SPACING));
+    }
+
+    #[test]
+    fn test_failing_refresh_keeps_last_data_and_shows_stale() {
+        use crate::metrics::SystemMetrics;
+        use crate::util::{RefreshSchedule, Staleness};
+        use std::time::{Duration, Instant};
+
+        let interval = Duration::from_secs(1);
+        let start = Instant::now();
+        let mut schedule = RefreshSchedule::new(interval, start);
+        // A metrics source that works once and then can't read /proc any more
+        let mut calls = 0;
+        let mut source = || -> anyhow::Result<SystemMetrics> {
+            calls += 1;
+            if calls == 1 {
+                let mut metrics = SystemMetrics::default();
+                metrics.cpu.total_usage = 42.0;
+                Ok(metrics)
+            } else {
+                anyhow::bail!("/proc/stat: permission denied")
+            }
+        };
+
+        let mut metrics = SystemMetrics::default();
+        let first = start + interval;
+        if let Some(collected) = schedule.record(source(), first) {
+            metrics = collected;
+        }
+        assert_eq!(schedule.staleness(first), Staleness::Live);
+
+        let failed = first + interval;
+        assert!(schedule.is_due(failed));
+        if let Some(collected) = schedule.record(source(), failed) {
+            metrics = collected;
+        }
+        // The last good data stays, and the indicator says it's stale right away
+        assert_eq!(metrics.cpu.total_usage, 42.0);
+        assert_eq!(schedule.last_update(), first);
+        assert_eq!(schedule.failing_since(), Some(failed));
+        assert_eq!(schedule.staleness(failed), Staleness::Stale);
+        // Retried at the normal interval rather than on every tick
+        assert!(!schedule.is_due(failed + interval / 2));
+        assert!(schedule.is_due(failed + interval));
+        assert!(schedule.record(source(), failed + interval).is_none());
+        assert_eq!(schedule.failing_since(), Some(failed));
+
+        let recovered = failed + interval * 2;
+        assert!(schedule.record(anyhow::Ok(SystemMetrics::default()), recovered).is_some());
+        assert_eq!(schedule.failing_since(), None);
+        assert_eq!(schedule.staleness(recovered), Staleness::Live);
+    }
+
+    #[test]
+    fn test_service_memory_sort_puts_unknown_last() {
+        use crate::sort::{compare_optional, sort_services, ServiceSortColumn};
+        use std::cmp::Ordering;
+
+        assert_eq!(compare_optional(Some(1), None, true), Ordering::Less);
+        assert_eq!(compare_optional(Some(1), None, false), Ordering::Less);
+        assert_eq!(compare_optional(None::<u64>, None, false), Ordering::Equal);
+        assert_eq!(compare_optional(Some(1), Some(2), false), Ordering::Greater);
+
+        let service = |name: &str, memory_usage: Option<u64>| crate::SystemService {
+            name: name.to_string(),
+            description: String::new(),
+            state: crate::ServiceState::Running,
+            enabled: true,
+            active_state: "active".to_string(),
+            sub_state: "running".to_string(),
+            memory_usage,
+            cpu_usage: None,
+            main_pid: None,
+        };
+        let mut services = vec![
+            service("cron", None),
+            service("sshd", Some(8)),
+            service("avahi", None),
+            service("nginx", Some(64)),
+            service("dbus", Some(2)),
+        ];
+        let names = |services: &[crate::SystemService]| services.iter().map(|s| s.name.clone()).collect::<Vec<_>>();
+
+        sort_services(&mut services, ServiceSortColumn::Memory, false);
+        assert_eq!(names(&services), vec!["nginx", "sshd", "dbus", "avahi", "cron"]);
+        sort_services(&mut services, ServiceSortColumn::Memory, true);
+        assert_eq!(names(&services), vec!["dbus", "sshd", "nginx", "avahi", "cron"]);
+    }
+
+    #[test]
+    fn test_protected_set_refuses_protected_processes() {
+        use crate::protect::ProtectedSet;
+
+        let protected = ProtectedSet::parse(
+            r#"
+        # Never kill the database
+        names = ["postgres", 'sshd']
+        pids = [
+            812,
+        ]
+        uids = ["100-199", 472]
+        "#,
+        )
+        .unwrap();
+
+        let refused = synthetic_process(4242, "postgres", 0.0, 0);
+        let err = protected.check(&refused.info).unwrap_err().to_string();
+        assert!(err.contains("'postgres' is a protected process"), "{}", err);
+        let allowed = synthetic_process(4243, "firefox", 0.0, 0);
+        assert!(protected.check(&allowed.info).is_ok());
+
+        // Defaults stay protected alongside the file entries
+        assert!(protected.check(&synthetic_process(1, "bash", 0.0, 0).info).is_err());
+        assert!(protected.check(&synthetic_process(900, "systemd", 0.0, 0).info).is_err());
+        assert!(protected.check(&synthetic_process(812, "bash", 0.0, 0).info).is_err());
+
+        let mut by_uid = synthetic_process(4244, "bash", 0.0, 0);
+        by_uid.info.uid = 150;
+        assert!(protected.check(&by_uid.info).is_err());
+        by_uid.info.uid = 472;
+        assert!(protected.check(&by_uid.info).is_err());
+        by_uid.info.uid = 200;
+        assert!(protected.check(&by_uid.info).is_ok());
+
+        assert!(ProtectedSet::parse("names = \"sshd\"").is_err());
+        assert!(ProtectedSet::parse("uids = [\"9-3\"]").is_err());
+        assert!(ProtectedSet::parse("colors = [\"red\"]").is_err());
+        assert!(ProtectedSet::parse("pids = [1,").is_err());
+
+        // Quoted names may hold the characters TOML uses for comments and separators
+        let quoted = ProtectedSet::parse(r#"names = ["worker#1", "a,b"] # trailing comment"#).unwrap();
+        assert!(quoted.check(&synthetic_process(4245, "worker#1", 0.0, 0).info).is_err());
+        assert!(quoted.check(&synthetic_process(4246, "a,b", 0.0, 0).info).is_err());
+        assert!(quoted.check(&synthetic_process(4247, "worker", 0.0, 0).info).is_ok());
+    }
+
+    #[test]
+    fn test_kill_commands() {
+        let cmd = crate::kill::kill_command(4242);
+        assert_eq!(command_line(&cmd), vec!["kill", "4242"]);
+    }
+
+    #[test]
+    fn test_tree_kill_targets_are_the_checked_descendants() {
+        use crate::kill::tree_kill_targets;
+        use crate::protect::ProtectedSet;
+
+        let child_of = |pid: u32, name: &str, parent: u32| {
+            let mut process = synthetic_process(pid, name, 0.0, 0);
+            process.info.parent_pid = Some(parent);
+            process
+        };
+        let processes = vec![
+            child_of(10, "make", 1),
+            child_of(11, "cc", 10),
+            child_of(12, "ld", 11),
+            child_of(13, "cc", 10),
+            child_of(20, "unrelated", 1),
+        ];
+        let protected = ProtectedSet::default();
+
+        let root = &processes[0].info;
+        assert_eq!(tree_kill_targets(root, &processes, &protected).unwrap(), vec![10, 11, 13, 12]);
+
+        let protected = ProtectedSet::parse("names = [\"ld\"]").unwrap();
+        let err = tree_kill_targets(root, &processes, &protected).unwrap_err().to_string();
+        assert!(err.contains("PID 12"), "{}", err);
+    }
+
+    #[test]
+    fn test_parse_container_id_from_cgroup() {
+        use crate::container::{group_by_container, parse_container_id, short_id};
+
+        let id = "3f4e2a1b9c8d7e6f5a4b3c2d1e0f9a8b7c6d5e4f3a2b1c0d9e8f7a6b5c4d3e2f";
+        let docker_v2 = format!("0::/system.slice/docker-{}.scope\n", id);
+        assert_eq!(parse_container_id(&docker_v2).as_deref(), Some(id));
+        let docker_v1 = format!("12:pids:/docker/{}\n11:memory:/docker/{}\n", id, id);
+        assert_eq!(parse_container_id(&docker_v1).as_deref(), Some(id));
+        let podman = format!("0::/user.slice/user-1000.slice/user@1000.service/user.slice/libpod-{}.scope/container\n", id);
+        assert_eq!(parse_container_id(&podman).as_deref(), Some(id));
+        let kubepods = format!(
+            "0::/kubepods.slice/kubepods-burstable.slice/kubepods-burstable-pod1a2b.slice/cri-containerd-{}.scope\n",
+            id
+        );
+        assert_eq!(parse_container_id(&kubepods).as_deref(), Some(id));
+        let kubepods_v1 = format!("4:cpu,cpuacct:/kubepods/besteffort/pod8c1d/{}\n", id);
+        assert_eq!(parse_container_id(&kubepods_v1).as_deref(), Some(id));
+        assert_eq!(short_id(id), "3f4e2a1b9c8d");
+
+        // Host processes and container runtime helpers are not in a container
+        assert_eq!(parse_container_id("0::/user.slice/user-1000.slice/session-2.scope\n"), None);
+        assert_eq!(parse_container_id("0::/init.scope\n"), None);
+        assert_eq!(parse_container_id(&format!("0::/machine.slice/libpod-conmon-{}.scope\n", id)), None);
+        assert_eq!(parse_container_id(""), None);
+
+        let mut web = synthetic_process(10, "nginx", 20.0, 100);
+        web.info.container = Some(id.to_string());
+        let mut worker = synthetic_process(11, "nginx", 5.0, 50);
+        worker.info.container = Some(id.to_string());
+        let host = synthetic_process(12, "sshd", 50.0, 10);
+        let groups = group_by_container(&[host, web, worker]);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].container.as_deref(), Some(id));
+        assert_eq!((groups[0].processes, groups[0].memory_usage), (2, 150));
+        assert_eq!(groups[0].cpu_usage, 25.0);
+        assert_eq!(groups[1].container, None);
+    }
+
+    #[test]
+    fn test_totals_of_filtered_processes() {
+        use crate::process::ProcessTotals;
+        use crate::search::matches_search;
+
+        let processes = [
+            synthetic_process(100, "chrome", 12.5, 300 * 1024 * 1024),
+            synthetic_process(101, "chrome", 30.0, 200 * 1024 * 1024),
+            synthetic_process(102, "sshd", 1.0, 8 * 1024 * 1024),
+            synthetic_process(103, "chrome", 80.0, 500 * 1024 * 1024),
+        ];
+        let chrome: Vec<_> = processes.iter().filter(|p| matches_search(&p.info, "chrome")).cloned().collect();
+
+        let totals = ProcessTotals::of(&chrome);
+        assert_eq!(totals.count, 3);
+        assert!((totals.cpu_usage - 122.5).abs() < 1e-4, "{}", totals.cpu_usage);
+        assert_eq!(totals.memory_usage, 1000 * 1024 * 1024);
+
+        assert_eq!(ProcessTotals::of(&[]), ProcessTotals::default());
+    }
+
+    #[test]
+    fn test_reduced_lsblk_output_still_lists_disks() {
+        let manager = crate::PartitionManager::new();
+
+        // NAME,TYPE,SIZE,MOUNTPOINT only, as requested from lsblk versions without MODEL
+        let reduced = r#"{
+        "blockdevices": [
+            {"name": "sdz", "type": "disk", "size": "500107862016", "mountpoint": null},
+            {"name": "sr9", "type": "rom", "size": "1073741312", "mountpoint": null},
+            {"name": "vdz", "type": "disk", "size": 21474836480}
+        ]
+    }"#;
+        let disks = manager.parse_lsblk_disks(reduced).unwrap();
+        assert_eq!(disks.len(), 2);
+        assert_eq!(disks[0].device, "/dev/sdz");
+        assert_eq!(disks[0].model, "Unknown");
+        assert_eq!(disks[0].size_bytes, 500107862016);
+        assert!(disks[0].partitions.is_empty());
+        // Newer lsblk prints sizes as numbers
+        assert_eq!(disks[1].size_bytes, 21474836480);
+
+        assert!(manager.parse_lsblk_disks("lsblk: unknown column: MODEL").is_err());
+        let cmd = crate::partition::lsblk_command("NAME,TYPE");
+        assert_eq!(command_line(&cmd), vec!["lsblk", "-J", "-b", "-o", "NAME,TYPE"]);
+    }
+
+    #[test]
+    fn test_temp_mount_point_and_command_sequence() {
+        use crate::partition::{
+            create_unique_dir, is_temp_mount_point, temp_mount_command, temp_mount_point, temp_unmount_commands,
+        };
+        use std::path::Path;
+
+        let uuid = "5f3c9a2e-1b7d-4e8f-9a6b-2c4d6e8f0a1b";
+        assert_eq!(temp_mount_point("/dev/sdb1", Some("Backup Disk"), Some(uuid)), Path::new("/mnt/procmon/Backup_Disk"));
+        assert_eq!(temp_mount_point("/dev/sdb1", None, Some(uuid)), Path::new("/mnt/procmon").join(uuid));
+        assert_eq!(temp_mount_point("/dev/sdb1", Some("  "), None), Path::new("/mnt/procmon/sdb1"));
+        // A label that could escape or hide in the mount root is not used
+        assert_eq!(temp_mount_point("/dev/sdb1", Some("../etc"), None), Path::new("/mnt/procmon/sdb1"));
+        assert_eq!(temp_mount_point("/dev/sdb1", Some(".."), Some(uuid)), Path::new("/mnt/procmon").join(uuid));
+        assert_eq!(temp_mount_point("/dev/sdb1", Some("."), None), Path::new("/mnt/procmon/sdb1"));
+        assert_eq!(temp_mount_point("/dev/sdb1", Some("a/b"), None), Path::new("/mnt/procmon/sdb1"));
+
+        let mount_point = temp_mount_point("/dev/sdb1", Some("data"), None);
+        assert!(is_temp_mount_point(&mount_point));
+        assert!(!is_temp_mount_point(Path::new("/home")));
+        assert!(!is_temp_mount_point(Path::new("/mnt/procmon")));
+        assert!(!is_temp_mount_point(Path::new("/mnt/procmon/..")));
+        assert!(!is_temp_mount_point(Path::new("/mnt/procmon/data/..")));
+
+        assert_eq!(command_line(&temp_mount_command("/dev/sdb1", &mount_point)), vec!["mount", "/dev/sdb1", "/mnt/procmon/data"]);
+        let unmount: Vec<_> = temp_unmount_commands(&mount_point).iter().map(command_line).collect();
+        assert_eq!(unmount, vec![vec!["umount", "/mnt/procmon/data"], vec!["rmdir", "/mnt/procmon/data"]]);
+
+        // Two partitions with the same label get separate directories
+        let root = std::env::temp_dir().join(format!("procmon-temp-mount-test-{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        let base = root.join("data");
+        assert_eq!(create_unique_dir(&base).unwrap(), base);
+        assert_eq!(create_unique_dir(&base).unwrap(), root.join("data-2"));
+        assert_eq!(create_unique_dir(&base).unwrap(), root.join("data-3"));
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_environment_search_finds_process_by_variable() {
+        use crate::search::{can_read_environ, environment_matches, parse_environ};
+
+        assert_eq!(parse_environ(b"DISPLAY=:1\0HOME=/root\0"), vec!["DISPLAY=:1", "HOME=/root"]);
+        assert!(parse_environ(b"").is_empty());
+
+        let mut child = std::process::Command::new("sleep")
+            .arg("5")
+            .env("PROCMON_TEST_TOKEN", "c0ffee")
+            .spawn()
+            .unwrap();
+        let mut process = synthetic_process(child.id(), "sleep", 0.0, 0);
+        process.info.uid = unsafe { libc::geteuid() };
+
+        // spawn() can return before the child has exec'd and its new environment shows up
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(2);
+        while !environment_matches(&process.info, "procmon_test_token=C0FFEE") {
+            assert!(std::time::Instant::now() < deadline, "child environment never matched");
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert!(!environment_matches(&process.info, "PROCMON_TEST_TOKEN=decaf"));
+        assert!(!environment_matches(&process.info, ""));
+        child.kill().unwrap();
+        child.wait().unwrap();
+
+        // Other users' environments are only readable as root
+        process.info.uid = 1000;
+        assert!(can_read_environ(&process.info, 1000));
+        assert!(can_read_environ(&process.info, 0));
+        assert!(!can_read_environ(&process.info, 1001));
+    }
+
+    #[test]
+    fn test_alert_bell_rings_only_for_new_critical_alerts() {
+        use crate::bell::{alert_sound, AlertBell, AlertSound};
+        use crate::detector::Severity;
+        use std::time::{Duration, Instant};
+
+        let start = chrono::Utc::now();
+        let at = |pid: u32, severity: Severity, secs: i64| {
+            let mut alert = alert_with(pid, "rule", severity);
+            alert.timestamp = start + chrono::Duration::seconds(secs);
+            alert
+        };
+
+        let mut bell = AlertBell::new(AlertSound::Bell);
+        let mut alerts = vec![at(1, Severity::Warning, 0)];
+        assert!(!bell.has_new_critical(&alerts));
+
+        alerts.push(at(2, Severity::Critical, 1));
+        assert!(bell.has_new_critical(&alerts));
+        // Re-rendering the same list is not new
+        assert!(!bell.has_new_critical(&alerts));
+        alerts.push(at(3, Severity::Warning, 2));
+        assert!(!bell.has_new_critical(&alerts));
+        // Older alerts falling off the bounded list do not matter
+        alerts.remove(0);
+        alerts.push(at(2, Severity::Critical, 3));
+        assert!(bell.has_new_critical(&alerts));
+
+        // Rings are debounced, but the alerts seen meanwhile still count as seen
+        let mut bell = AlertBell::new(AlertSound::Bell);
+        bell.set_min_gap(Duration::from_secs(30));
+        let now = Instant::now();
+        let mut alerts = vec![at(1, Severity::Critical, 0)];
+        assert!(bell.should_ring(&alerts, now));
+        alerts.push(at(1, Severity::Critical, 1));
+        assert!(!bell.should_ring(&alerts, now + Duration::from_secs(5)));
+        assert!(!bell.should_ring(&alerts, now + Duration::from_secs(40)));
+        alerts.push(at(1, Severity::Critical, 2));
+        assert!(bell.should_ring(&alerts, now + Duration::from_secs(41)));
+
+        let mut silent = AlertBell::new(AlertSound::Off);
+        assert!(!silent.should_ring(&[at(1, Severity::Critical, 0)], now));
+
+        assert_eq!(AlertSound::from_name("BELL").unwrap(), AlertSound::Bell);
+        assert_eq!(
+            AlertSound::from_name("command:paplay bell.oga").unwrap(),
+            AlertSound::Command("paplay bell.oga".to_string())
+        );
+        assert!(AlertSound::from_name("command:").is_err());
+        assert!(AlertSound::from_name("beep").is_err());
+        let args = vec!["--alert-sound=bell".to_string()];
+        assert_eq!(alert_sound(args, Some("off")).unwrap(), AlertSound::Bell);
+        assert_eq!(alert_sound(Vec::new(), None).unwrap(), AlertSound::Off);
+    }
+
+    #[test]
+    fn test_dd_progress_parsing_and_image_command() {
+        use crate::partition::{dd_image_command, for_each_progress_line, is_mounted_read_write, parse_dd_progress};
+        use std::path::Path;
+
+        // dd redraws its status line with \r and ends with the record counts and a final summary
+        let stderr = "104857600 bytes (105 MB, 100 MiB) copied, 1 s, 105 MB/s\r\
+                      524288000 bytes (524 MB, 500 MiB) copied, 5 s, 105 MB/s\r\
+                      125+0 records in\n125+0 records out\n\
+                      524288000 bytes (524 MB, 500 MiB) copied, 5.01 s, 105 MB/s\n";
+        let mut progress = Vec::new();
+        for_each_progress_line(stderr.as_bytes(), |line| progress.extend(parse_dd_progress(line))).unwrap();
+        assert_eq!(progress, vec![104_857_600, 524_288_000, 524_288_000]);
+
+        // Older coreutils print only the decimal size
+        assert_eq!(parse_dd_progress("2048 bytes (2.0 kB) copied, 0.01 s, 200 kB/s"), Some(2048));
+        assert_eq!(parse_dd_progress("dd: failed to open '/dev/sdz1': No such file or directory"), None);
+
+        let mounts = "/dev/sda2 / ext4 rw,relatime 0 0\n/dev/sdb1 /mnt/backup ext4 ro,relatime 0 0\n";
+        assert!(is_mounted_read_write(mounts, "/dev/sda2"));
+        assert!(!is_mounted_read_write(mounts, "/dev/sdb1"));
+        assert!(!is_mounted_read_write(mounts, "/dev/sdc1"));
+
+        assert_eq!(
+            command_line(&dd_image_command("/dev/sdb1", Path::new("/tmp/sdb1.img"))),
+            vec!["dd", "if=/dev/sdb1", "of=/tmp/sdb1.img", "bs=4M", "status=progress", "conv=excl,fsync"]
+        );
+    }
+
+    #[test]
+    fn test_partition_type_validation_and_sgdisk_command() {
+        use crate::partition::{mbr_flag_for_type, sgdisk_type_command, validate_type_code, COMMON_PARTITION_TYPES};
+
+        assert_eq!(validate_type_code("ef00").unwrap(), "EF00");
+        assert_eq!(
+            validate_type_code(" c12a7328-f81f-11d2-ba4b-00a0c93ec93b ").unwrap(),
+            "C12A7328-F81F-11D2-BA4B-00A0C93EC93B"
+        );
+        for invalid in ["", "EF0", "EF000", "XY00", "8300:1", "c12a7328-f81f-11d2-ba4b-00a0c93ec93", "c12a7328f81f11d2ba4b00a0c93ec93b"] {
+            assert!(validate_type_code(invalid).is_err(), "{:?} should be rejected", invalid);
+        }
+        // Every offered type must itself be valid
+        for partition_type in COMMON_PARTITION_TYPES {
+            assert_eq!(validate_type_code(partition_type.code).unwrap(), partition_type.code);
+        }
+
+        assert_eq!(mbr_flag_for_type("ef00"), Some("esp"));
+        assert_eq!(mbr_flag_for_type("8300"), None);
+
+        assert_eq!(command_line(&sgdisk_type_command("/dev/sdb", 1, "EF00")), vec!["sgdisk", "-t", "1:EF00", "/dev/sdb"]);
+    }
+
+    #[test]
+    fn test_run_time_exceeds_flags_long_running_matching_process() {
+        use crate::detector::{glob_matches, MisbehaviorCondition, MisbehaviorDetector, MisbehaviorRule, Severity};
+        use std::time::Duration;
+
+        let mut detector = MisbehaviorDetector::with_rules(vec![MisbehaviorRule {
+            name: "Stuck Deploy".to_string(),
+            description: "Deploy script running too long".to_string(),
+            condition: MisbehaviorCondition::RunTimeExceeds { seconds: 3600, name_pattern: "deploy*".to_string() },
+            severity: Severity::Warning,
+        }]);
+
+        let mut stuck = synthetic_process(4100, "bash", 0.0, 0);
+        stuck.info.command_line = vec!["/bin/bash".to_string(), "/opt/app/deploy.sh".to_string()];
+        stuck.stats.run_time = Duration::from_secs(3 * 3600);
+        let alerts = detector.check_process(&stuck);
+        assert_eq!(alerts.len(), 1);
+        assert!(alerts[0].details.contains("3h 00m"), "{}", alerts[0].details);
+
+        // Under the limit, or a long-running process that doesn't match the pattern
+        let mut fresh = stuck.clone();
+        fresh.stats.run_time = Duration::from_secs(60);
+        assert!(detector.check_process(&fresh).is_empty());
+        let mut daemon = synthetic_process(812, "sshd", 0.0, 0);
+        daemon.stats.run_time = Duration::from_secs(30 * 86_400);
+        assert!(detector.check_process(&daemon).is_empty());
+
+        assert!(glob_matches("backup-?.sh", "backup-1.sh"));
+        assert!(glob_matches("*cron*", "anacron"));
+        assert!(!glob_matches("backup-?.sh", "backup-12.sh"));
+        assert!(!glob_matches("deploy", "deploy.sh"));
+    }
+
+    #[test]
+    fn test_health_check_reports_critical_system_condition() {
+        use crate::detector::{MisbehaviorDetector, Severity, SystemCondition};
+        use crate::health::{HealthReport, HealthStatus};
+        use crate::metrics::SystemMetrics;
+
+        let mut detector = MisbehaviorDetector::with_rules(vec![crate::detector::MisbehaviorRule {
+            name: "Memory Pressure".to_string(),
+            severity: Severity::Critical,
+            ..system_rule(SystemCondition::TotalMemoryPressure { threshold_percent: 95.0 })
+        }]);
+        let mut metrics = SystemMetrics::default();
+        metrics.cpu.total_usage = 12.5;
+        metrics.memory.total = 1000;
+        metrics.memory.used = 970;
+
+        let alerts = detector.check_system(&[], &metrics);
+        let report = HealthReport::from_alerts(&alerts, &metrics);
+        assert_eq!(report.status, HealthStatus::Critical);
+        assert_eq!(report.status.exit_code(), 2);
+        assert_eq!(
+            report.summary,
+            "PROCMON CRITICAL - 1 critical, 0 warning: Memory Pressure: Memory used: 97.0% (threshold: 95.0%) | cpu=12.5% memory=97.0%"
+        );
+
+        metrics.memory.used = 500;
+        let report = HealthReport::from_alerts(&detector.check_system(&[], &metrics), &metrics);
+        assert_eq!(report.status.exit_code(), 0);
+        assert_eq!(report.summary, "PROCMON OK - no active alerts | cpu=12.5% memory=50.0%");
+
+        let warnings = [alert_with(0, "Zombies", Severity::Warning), alert_with(0, "Note", Severity::Info)];
+        assert_eq!(HealthStatus::from_alerts(&warnings).exit_code(), 1);
+    }
+
+    #[test]
+    fn test_empty_process_name_gets_fallback() {
+        use crate::process::resolve_process_name;
+
+        let cmdline = vec!["/usr/lib/systemd/systemd-userwork".to_string(), "--xfer".to_string()];
+        assert_eq!(resolve_process_name(77, "sshd", || panic!("comm read needlessly"), &cmdline), "sshd");
+        assert_eq!(resolve_process_name(77, "", || Some("kworker/0:1\n".to_string()), &cmdline), "kworker/0:1");
+        assert_eq!(resolve_process_name(77, " ", || Some("\n".to_string()), &cmdline), "systemd-userwork");
+        assert_eq!(resolve_process_name(77, "", || None, &[]), "[pid 77]");
+        assert_eq!(resolve_process_name(77, "", || None, &[String::new()]), "[pid 77]");
+    }
+
+    #[test]
+    fn test_filesystem_usage_lists_fullest_first() {
+        use crate::partition::{filesystem_usage, Disk, Partition};
+
+        let partition = |device: &str, mount_point: Option<&str>, used_bytes: u64, size_bytes: u64| Partition {
+            device: device.to_string(),
+            partition_number: None,
+            filesystem: Some("ext4".to_string()),
+            label: None,
+            size_bytes,
+            used_bytes,
+            mount_point: mount_point.map(str::to_string),
+            partition_type: None,
+            flags: Vec::new(),
+        };
+        let disks = vec![
+            Disk {
+                device: "/dev/sda".to_string(),
+                model: "SSD".to_string(),
+                size_bytes: 4000,
+                logical_sector_size: 512,
+                physical_sector_size: 512,
+                removable: false,
+                partitions: vec![
+                    partition("/dev/sda1", Some("/boot"), 50, 100),
+                    partition("/dev/sda2", Some("/"), 950, 1000),
+                    partition("/dev/sda3", None, 0, 1000),
+                    partition("/dev/sda4", Some("[SWAP]"), 0, 1000),
+                ],
+            },
+            Disk {
+                device: "/dev/sdb".to_string(),
+                model: "HDD".to_string(),
+                size_bytes: 2000,
+                logical_sector_size: 512,
+                physical_sector_size: 512,
+                removable: false,
+                partitions: vec![partition("/dev/sdb1", Some("/home"), 700, 1000)],
+            },
+        ];
+
+        let usage = filesystem_usage(&disks);
+        let mount_points: Vec<&str> = usage.iter().map(|fs| fs.mount_point.as_str()).collect();
+        assert_eq!(mount_points, vec!["/", "/home", "/boot"]);
+        assert_eq!(usage[0].percent(), 95.0);
+    }
+
+    #[test]
+    fn test_disk_cursor_keyboard_transitions() {
+        use crate::selection::DiskCursor;
+
+        let mut cursor = DiskCursor::default();
+        // Nothing to select without disks, and Up/Down need an expanded disk
+        cursor.next_disk(0);
+        assert_eq!(cursor, DiskCursor::default());
+        cursor.next_partition(3);
+        assert_eq!(cursor.partition, None);
+
+        cursor.next_disk(2);
+        assert_eq!((cursor.disk, cursor.expanded), (Some(0), false));
+        cursor.next_partition(3);
+        assert_eq!(cursor.partition, None);
+
+        cursor.toggle_expanded();
+        assert!(cursor.is_expanded(0));
+        cursor.previous_partition(3);
+        assert_eq!(cursor.partition, Some(2));
+        cursor.next_partition(3);
+        assert_eq!(cursor.partition, Some(0));
+
+        // Changing disk drops the partition but keeps the list open; both directions wrap
+        cursor.previous_disk(2);
+        assert_eq!((cursor.disk, cursor.partition), (Some(1), None));
+        assert!(cursor.is_expanded(1));
+        cursor.next_disk(2);
+        assert_eq!(cursor.disk, Some(0));
+
+        cursor.select_partition(1, 1);
+        cursor.toggle_expanded();
+        assert_eq!((cursor.disk, cursor.partition, cursor.expanded), (Some(1), None, false));
+    }
+
+    #[test]
+    fn test_source_backoff_grows_and_resets() {
+        use crate::util::SourceBackoff;
+        use std::time::{Duration, Instant};
+
+        let start = Instant::now();
+        let mut backoff = SourceBackoff::new("lsblk", Duration::from_secs(5));
+        assert!(backoff.is_due(start));
+
+        // Below the threshold failures keep the normal interval
+        let mut now = start;
+        for _ in 0..SourceBackoff::FAILURE_THRESHOLD - 1 {
+            backoff.record_failure(now, &"exit status 32");
+            assert_eq!(backoff.delay(), Duration::from_secs(5));
+            assert!(!backoff.is_disabled());
+            now += backoff.delay();
+        }
+
+        // Then the gap doubles per failure, capped at MAX_DELAY
+        let mut delays = Vec::new();
+        for _ in 0..8 {
+            backoff.record_failure(now, &"exit status 32");
+            delays.push(backoff.delay().as_secs());
+            assert!(!backoff.is_due(now + backoff.delay() - Duration::from_millis(1)));
+            now += backoff.delay();
+            assert!(backoff.is_due(now));
+        }
+        assert_eq!(delays, vec![10, 20, 40, 80, 160, 300, 300, 300]);
+        assert!(backoff.is_disabled());
+        let status = backoff.status(now).unwrap();
+        assert!(status.starts_with("lsblk disabled due to repeated failures (10 in a row)"), "{}", status);
+
+        let value = backoff.record(Ok(42), now);
+        assert_eq!(value, Some(42));
+        assert!(!backoff.is_disabled());
+        assert_eq!(backoff.failures(), 0);
+        assert_eq!(backoff.delay(), Duration::from_secs(5));
+        assert_eq!(backoff.status(now), None);
+        assert!(!backoff.is_due(now) && backoff.is_due(now + Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn test_network_bar_scale_prefers_link_speed_then_peak() {
+        use crate::bandwidth::{parse_link_speed, scale_denominator, RateScale, MIN_SCALE_BYTES_PER_SEC};
+
+        // /sys/class/net/<if>/speed is in Mbit/s; -1 means unknown
+        assert_eq!(parse_link_speed("1000\n"), Some(125_000_000));
+        assert_eq!(parse_link_speed("-1\n"), None);
+        assert_eq!(parse_link_speed(""), None);
+
+        assert_eq!(scale_denominator(Some(125_000_000), 40_000_000, MIN_SCALE_BYTES_PER_SEC), 125_000_000);
+        assert_eq!(scale_denominator(None, 40_000_000, MIN_SCALE_BYTES_PER_SEC), 40_000_000);
+        assert_eq!(scale_denominator(None, 0, MIN_SCALE_BYTES_PER_SEC), MIN_SCALE_BYTES_PER_SEC);
+
+        // An interface with no speed file scales to its own peak and keeps it
+        let mut scale = RateScale::network();
+        scale.observe_interface("procmon-test0", 8 * MIN_SCALE_BYTES_PER_SEC, MIN_SCALE_BYTES_PER_SEC);
+        scale.observe_interface("procmon-test0", 0, 2 * MIN_SCALE_BYTES_PER_SEC);
+        assert_eq!(scale.denominator("procmon-test0"), 8 * MIN_SCALE_BYTES_PER_SEC);
+        assert_eq!(scale.fraction("procmon-test0", 2 * MIN_SCALE_BYTES_PER_SEC), 0.25);
+        assert_eq!(scale.denominator("unseen0"), MIN_SCALE_BYTES_PER_SEC);
+    }
+
+    #[test]
+    fn test_disk_bar_fraction_uses_rate_against_peak() {
+        use crate::bandwidth::{bar_fraction, RateScale, MIN_DISK_SCALE_BYTES_PER_SEC};
+
+        const MB: u64 = 1024 * 1024;
+        assert_eq!(bar_fraction(50 * MB, 200 * MB), 0.25);
+        assert_eq!(bar_fraction(300 * MB, 200 * MB), 1.0);
+        assert_eq!(bar_fraction(5 * MB, 0), 0.0);
+
+        let mut scale = RateScale::disks();
+        // Until the device shows more, bars are against the default maximum
+        scale.observe("sda", &[20 * MB, 5 * MB]);
+        assert_eq!(scale.denominator("sda"), MIN_DISK_SCALE_BYTES_PER_SEC);
+        assert_eq!(scale.fraction("sda", 25 * MB), 0.25);
+
+        // A fast device raises its own scale; the peak is per device
+        scale.observe("nvme0n1", &[400 * MB, 1600 * MB]);
+        assert_eq!(scale.denominator("nvme0n1"), 1600 * MB);
+        assert_eq!(scale.fraction("nvme0n1", 400 * MB), 0.25);
+        assert_eq!(scale.denominator("sda"), MIN_DISK_SCALE_BYTES_PER_SEC);
+    }
+
+    #[test]
+    fn test_high_disk_reads_flags_heavy_reader() {
+        use crate::detector::{MisbehaviorCondition, MisbehaviorDetector, MisbehaviorRule, Severity};
+
+        let rule = MisbehaviorRule {
+            name: "High Disk Reads".to_string(),
+            description: "Reading at a thrashing rate".to_string(),
+            condition: MisbehaviorCondition::HighDiskReads {
+                threshold_bytes_per_sec: 100 * 1024 * 1024,
+                duration_secs: 0,
+            },
+            severity: Severity::Warning,
+        };
+        let mut detector = MisbehaviorDetector::with_rules(vec![rule]);
+
+        let mut writer = synthetic_process(910, "backup-writer", 5.0, 0);
+        writer.stats.disk_write_bytes = 4 * 1024 * 1024 * 1024;
+        assert!(detector.check_process(&writer).is_empty());
+
+        let mut scanner = synthetic_process(911, "updatedb", 20.0, 0);
+        scanner.stats.disk_read_bytes_per_sec = 300 * 1024 * 1024;
+        let alerts = detector.check_process(&scanner);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].rule_name, "High Disk Reads");
+        assert!(alerts[0].details.contains("Disk reads: 300.00 MB/s"), "{}", alerts[0].details);
+
+        // The default rule wants the rate sustained, so one heavy sample is not enough
+        let mut defaults = MisbehaviorDetector::new();
+        assert!(defaults
+            .get_rules()
+            .iter()
+            .any(|rule| matches!(rule.condition, MisbehaviorCondition::HighDiskReads { .. })));
+        assert!(defaults.check_process(&scanner).iter().all(|alert| alert.rule_name != "High Disk Reads"));
+    }
+
+    #[test]
+    fn test_sustained_rule_fires_after_duration_and_resets_on_clean_sample() {
+        use crate::detector::{MisbehaviorCondition, MisbehaviorDetector, MisbehaviorRule, Severity};
+
+        let rule = MisbehaviorRule {
+            name: "High CPU".to_string(),
+            description: "Sustained CPU usage".to_string(),
+            condition: MisbehaviorCondition::CpuUsageAbove { threshold: 90.0, duration_secs: 1 },
+            severity: Severity::Warning,
+        };
+        let mut detector = MisbehaviorDetector::with_rules(vec![rule]);
+
+        let busy = synthetic_process(5150, "spinner", 99.0, 0);
+        assert!(detector.check_process(&busy).is_empty());
+        std::thread::sleep(std::time::Duration::from_millis(1100));
+        assert_eq!(detector.check_process(&busy).len(), 1);
+
+        // One sample under the threshold ends the run, so it has to last the full duration again
+        let mut idle = busy.clone();
+        idle.stats.cpu_usage = 5.0;
+        assert!(detector.check_process(&idle).is_empty());
+        assert!(detector.check_process(&busy).is_empty());
+    }
+
+    #[test]
+    fn test_density_changes_visible_row_count() {
+        use crate::density::{density, Density};
+
+        // 40 lines: borders, footer, header and its margin leave 34 for rows at normal density
+        assert_eq!(Density::Normal.visible_rows(40), 34);
+        assert_eq!(Density::Compact.visible_rows(40), 35);
+        assert_eq!(Density::Comfortable.visible_rows(40), 17);
+        assert_eq!(Density::Compact.visible_rows(3), 0);
+
+        assert_eq!(Density::Compact.first_row_offset(), 2);
+        assert_eq!(Density::Normal.first_row_offset(), 3);
+        assert!(Density::Compact.row_spacing() < Density::Comfortable.row_spacing());
+
+        let args = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(density(args(&[]), None).unwrap(), Density::Normal);
+        assert_eq!(density(args(&[]), Some("Compact")).unwrap(), Density::Compact);
+        assert_eq!(density(args(&["--density=comfortable"]), Some("compact")).unwrap(), Density::Comfortable);
+        assert!(density(args(&["--density", "tight"]), None).is_err());
+    }
+
+    #[test]
+    fn test_user_kill_targets_select_only_that_users_processes() {
+        use crate::kill::{signal_command, summarize_signal_results, user_kill_targets, Signal};
+        use crate::process::ProcessStatus;
+        use crate::protect::ProtectedSet;
+        use crate::users::group_by_user;
+
+        let owned = |pid: u32, name: &str, uid: u32| {
+            let mut process = synthetic_process(pid, name, 1.0, 1024);
+            process.info.uid = uid;
+            process.info.user = format!("user{}", uid);
+            process
+        };
+        let mut zombie = owned(106, "defunct", 1001);
+        zombie.info.status = ProcessStatus::Zombie;
+        let processes = vec![
+            owned(100, "bash", 1000),
+            owned(101, "firefox", 1001),
+            owned(102, "tmux", 1001),
+            owned(103, "sshd", 1001),
+            owned(104, "bash", 1001),
+            owned(105, "cron", 0),
+            zombie,
+        ];
+        let protected = ProtectedSet::parse("names = [\"sshd\"]").unwrap();
+        // PID 104 shares the session procmon runs in
+        let session_of = |pid: u32| Some(if pid == 104 { 7 } else { pid });
+
+        assert_eq!(user_kill_targets(&processes, 1001, &protected, Some(7), session_of), vec![101, 102]);
+        assert_eq!(user_kill_targets(&processes, 1001, &protected, None, session_of), vec![101, 102, 104]);
+        assert_eq!(user_kill_targets(&processes, 1000, &protected, Some(7), session_of), vec![100]);
+        assert!(user_kill_targets(&processes, 0, &protected, Some(7), session_of).is_empty());
+        assert!(user_kill_targets(&processes, 4242, &protected, Some(7), session_of).is_empty());
+
+        assert_eq!(command_line(&signal_command(101, Signal::Kill)), ["kill", "-KILL", "101"]);
+        let results = vec![(101, Ok(())), (102, Err(anyhow::anyhow!("Operation not permitted")))];
+        assert_eq!(
+            summarize_signal_results("user1001", Signal::Term, &results),
+            "Sent SIGTERM to 1 of 2 processes of user1001; PID 102: Operation not permitted"
+        );
+
+        let groups = group_by_user(&processes);
+        assert_eq!(groups.len(), 3);
+        assert_eq!((groups[0].uid, groups[0].processes), (1001, 5));
+        assert_eq!(groups[0].user, "user1001");
+    }
+
+    #[test]
+    fn test_deleted_open_files_found_in_mocked_proc_tree() {
+        use crate::fds::{scan_deleted_open_files, space_held};
+        use std::fs;
+        use std::os::unix::fs::symlink;
+
+        let root = std::env::temp_dir().join(format!("procmon-deleted-fds-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let proc_root = root.join("proc");
+        let data = root.join("data");
+        fs::create_dir_all(proc_root.join("1234/fd")).unwrap();
+        fs::create_dir_all(proc_root.join("77/fd")).unwrap();
+        fs::create_dir_all(proc_root.join("self")).unwrap();
+        fs::create_dir_all(&data).unwrap();
+
+        // Stand-ins for the files the fd links still reach; the kernel names the target "<path> (deleted)"
+        fs::write(data.join("huge.log (deleted)"), vec![0u8; 4096]).unwrap();
+        fs::write(data.join("small.tmp (deleted)"), vec![0u8; 10]).unwrap();
+        fs::write(data.join("live.db"), vec![0u8; 8192]).unwrap();
+        symlink(data.join("huge.log (deleted)"), proc_root.join("1234/fd/4")).unwrap();
+        symlink(data.join("live.db"), proc_root.join("1234/fd/5")).unwrap();
+        symlink("/memfd:wayland-shm (deleted)", proc_root.join("1234/fd/6")).unwrap();
+        symlink(data.join("small.tmp (deleted)"), proc_root.join("77/fd/3")).unwrap();
+
+        let files = scan_deleted_open_files(&proc_root);
+        assert_eq!(files.len(), 2);
+        assert_eq!((files[0].pid, files[0].fd, files[0].size), (1234, 4, 4096));
+        assert_eq!(files[0].path, data.join("huge.log"));
+        assert_eq!((files[1].pid, files[1].fd, files[1].size), (77, 3, 10));
+        assert_eq!(space_held(&files), (2, 4096 + 10));
+
+        // The same log open in a second worker and on a second descriptor is listed each time
+        // but holds its space once
+        fs::create_dir_all(proc_root.join("1235/fd")).unwrap();
+        symlink(data.join("huge.log (deleted)"), proc_root.join("1235/fd/4")).unwrap();
+        symlink(data.join("huge.log (deleted)"), proc_root.join("1234/fd/9")).unwrap();
+        let files = scan_deleted_open_files(&proc_root);
+        assert_eq!(files.len(), 4);
+        assert_eq!(space_held(&files), (2, 4096 + 10));
+
+        assert!(scan_deleted_open_files(&root.join("missing")).is_empty());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_log_file_gets_lines_at_configured_level() {
+        use crate::logging::{log_config, log_subscriber, LogSinks, LogTarget};
+        use std::fs;
+
+        let dir = std::env::temp_dir().join(format!("procmon-log-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let path = dir.join("nested").join("procmon.log");
+
+        let args = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let config = log_config(
+            args(&["--log-level", "debug", "--log-file", path.to_str().unwrap()]),
+            Some("error"),
+            LogTarget::Off,
+        )
+        .unwrap();
+        assert_eq!(config.filter, "debug");
+        assert_eq!(config.target, LogTarget::File(path.clone()));
+
+        tracing::subscriber::with_default(log_subscriber(&config, &LogSinks::default()).unwrap(), || {
+            tracing::debug!("refreshed 42 processes");
+            tracing::trace!("too detailed to keep");
+        });
+        let log = fs::read_to_string(&path).unwrap();
+        assert!(log.contains("DEBUG"), "{}", log);
+        assert!(log.contains("refreshed 42 processes"), "{}", log);
+        assert!(!log.contains("too detailed"), "{}", log);
+        assert!(!log.contains('\u{1b}'), "no colour codes in files: {}", log);
+
+        // RUST_LOG applies without --log-level; --log-file - means stderr
+        let config = log_config(args(&["--log-file=-"]), Some("warn,procmon_core=debug"), LogTarget::Off).unwrap();
+        assert_eq!(config.filter, "warn,procmon_core=debug");
+        assert_eq!(config.target, LogTarget::Stderr);
+        assert_eq!(log_config(args(&[]), None, LogTarget::Off).unwrap().filter, "info");
+        let loud = log_config(args(&["--log-level", "procmon_core=loud"]), None, LogTarget::Off).unwrap();
+        assert!(log_subscriber(&loud, &LogSinks::default()).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_logging_falls_back_instead_of_failing() {
+        use crate::logging::{log_subscriber_with_fallback, LogConfig, LogSinks, LogTarget};
+        use std::fs;
+
+        // A RUST_LOG with span filters, which only the full EnvFilter syntax understands
+        let sinks = LogSinks::default();
+        let config = LogConfig { filter: "warn,procmon_core[refresh{pid=1}]=debug".to_string(), target: LogTarget::Off };
+        let (subscriber, warnings) = log_subscriber_with_fallback(&config, &sinks);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("not supported") && warnings[0].contains("using 'info'"), "{:?}", warnings);
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!("kept at the default level");
+            tracing::debug!("dropped at the default level");
+        });
+        let messages: Vec<String> = sinks.recent.entries().into_iter().map(|entry| entry.message).collect();
+        assert_eq!(messages, vec!["kept at the default level"]);
+
+        // A log directory that can't be created, because a file is in the way
+        let blocker = std::env::temp_dir().join(format!("procmon-log-fallback-test-{}", std::process::id()));
+        fs::write(&blocker, "not a directory").unwrap();
+        let config = LogConfig { filter: "debug".to_string(), target: LogTarget::File(blocker.join("procmon.log")) };
+        let sinks = LogSinks::default();
+        let (subscriber, warnings) = log_subscriber_with_fallback(&config, &sinks);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("file logging is disabled"), "{:?}", warnings);
+        // The level still applies to the in-app log
+        tracing::subscriber::with_default(subscriber, || tracing::debug!("still recorded"));
+        assert_eq!(sinks.recent.len(), 1);
+        fs::remove_file(&blocker).unwrap();
+    }
+
+    #[test]
+    fn test_stderr_gate_holds_log_output_while_screen_is_active() {
+        use crate::logging::{log_config, log_subscriber, LogSinks, LogTarget, StderrGate};
+        use std::io::Write;
+        use std::sync::{Arc, Mutex};
+
+        #[derive(Clone, Default)]
+        struct Captured(Arc<Mutex<Vec<u8>>>);
+        impl Write for Captured {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().extend_from_slice(buf);
+                Ok(buf.len())
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let stderr = Captured::default();
+        let sinks = LogSinks { stderr: StderrGate::with_sink(Box::new(stderr.clone())), ..Default::default() };
+        let gate = &sinks.stderr;
+        let config = log_config(vec!["--log-file=-".to_string()], None, LogTarget::Off).unwrap();
+        let subscriber = log_subscriber(&config, &sinks).unwrap();
+
+        tracing::subscriber::with_default(subscriber, || {
+            gate.hold();
+            tracing::warn!("lsblk failed while drawing");
+            assert!(stderr.0.lock().unwrap().is_empty(), "nothing reaches stderr while the TUI is up");
+
+            gate.release().unwrap();
+            let released = String::from_utf8(stderr.0.lock().unwrap().clone()).unwrap();
+            assert!(released.contains("lsblk failed while drawing"), "{}", released);
+
+            tracing::warn!("after leaving the alternate screen");
+        });
+        let all = String::from_utf8(stderr.0.lock().unwrap().clone()).unwrap();
+        assert!(all.contains("after leaving the alternate screen"), "{}", all);
+
+        // The TUI's default, a file, never involves stderr at all
+        let dir = std::env::temp_dir().join(format!("procmon-gate-test-{}", std::process::id()));
+        let file_config = log_config(Vec::new(), None, LogTarget::File(dir.join("tui.log"))).unwrap();
+        let untouched = Captured::default();
+        let file_sinks = LogSinks { stderr: StderrGate::with_sink(Box::new(untouched.clone())), ..Default::default() };
+        tracing::subscriber::with_default(log_subscriber(&file_config, &file_sinks).unwrap(), || {
+            tracing::error!("goes to the file");
+        });
+        assert!(untouched.0.lock().unwrap().is_empty());
+        assert!(std::fs::read_to_string(dir.join("tui.log")).unwrap().contains("goes to the file"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_log_events_reach_the_in_app_buffer() {
+        use crate::logging::{log_config, log_subscriber, LogBuffer, LogSinks, LogTarget};
+        use tracing::Level;
+
+        let sinks = LogSinks { recent: LogBuffer::new(3), ..Default::default() };
+        let config = log_config(vec!["--log-level=warn".to_string()], None, LogTarget::Off).unwrap();
+        tracing::subscriber::with_default(log_subscriber(&config, &sinks).unwrap(), || {
+            tracing::info!("below the level");
+            tracing::warn!(device = "sda", "smartctl failed");
+            tracing::error!("lsblk exited with status 1");
+        });
+
+        let entries = sinks.recent.entries();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].level, Level::WARN);
+        assert_eq!(entries[0].message, "smartctl failed device=sda");
+        assert_eq!(entries[1].level, Level::ERROR);
+        assert_eq!(entries[1].message, "lsblk exited with status 1");
+        assert_eq!(entries[1].target, module_path!());
+
+        // Oldest events make room for new ones
+        tracing::subscriber::with_default(log_subscriber(&config, &sinks).unwrap(), || {
+            for n in 0..3 {
+                tracing::warn!("retry {}", n);
+            }
+        });
+        let messages: Vec<String> = sinks.recent.entries().into_iter().map(|entry| entry.message).collect();
+        assert_eq!(messages, ["retry 0", "retry 1", "retry 2"]);
+    }
+
+    #[test]
+    fn test_snapshot_rotation_keeps_newest_files() {
+        use crate::metrics::SystemMetrics;
+        use crate::snapshot::{list_snapshots, snapshot_config, Snapshot, SnapshotWriter};
+        use chrono::{Duration as ChronoDuration, TimeZone, Utc};
+        use std::time::{Duration, Instant};
+
+        let args = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(snapshot_config(args(&["--snapshot-keep", "3"]), None).unwrap(), None);
+        assert!(snapshot_config(args(&["--snapshot-dir=/tmp/x", "--snapshot-keep=0"]), None).is_err());
+        assert!(snapshot_config(args(&["--snapshot-interval", "soon"]), Some("/tmp/x")).is_err());
+
+        let dir = std::env::temp_dir().join(format!("procmon-snapshot-test-{}", std::process::id()));
+        let dir_arg = format!("--snapshot-dir={}", dir.display());
+        let config = snapshot_config(args(&[&dir_arg, "--snapshot-interval", "5", "--snapshot-keep", "3"]), None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(config.interval, Duration::from_secs(5));
+        let mut writer = SnapshotWriter::new(config);
+
+        let metrics = SystemMetrics::default();
+        let processes = vec![synthetic_process(42, "leaky", 12.5, 1 << 30)];
+        let start = Utc.with_ymd_and_hms(2026, 10, 16, 9, 0, 0).unwrap();
+        let clock = Instant::now();
+        for n in 0..5 {
+            let snapshot = Snapshot {
+                timestamp: start + ChronoDuration::seconds(5 * n),
+                metrics: &metrics,
+                processes: &processes,
+                alerts: &[],
+            };
+            assert!(writer.write_if_due(&snapshot, clock + Duration::from_secs(5 * n as u64)).unwrap().is_some());
+            // Not due again until the interval has passed
+            assert!(writer.write_if_due(&snapshot, clock + Duration::from_secs(5 * n as u64 + 1)).unwrap().is_none());
+        }
+
+        let files = list_snapshots(&dir).unwrap();
+        let names: Vec<String> = files.iter().map(|f| f.file_name().unwrap().to_string_lossy().into_owned()).collect();
+        assert_eq!(
+            names,
+            ["snapshot-20261016-090010-000.json", "snapshot-20261016-090015-000.json", "snapshot-20261016-090020-000.json"]
+        );
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 3, "no temporary files left behind");
+
+        let newest: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&files[2]).unwrap()).unwrap();
+        assert_eq!(newest["processes"][0]["info"]["pid"], 42);
+        assert!(newest["metrics"]["cpu"].is_object());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_baseline_z_score_and_unusual_flag() {
+        use crate::baseline::{is_unusual, BaselineMetric, BaselineTracker, HourlyBaseline, RunningStats, MIN_STD_DEV};
+        use chrono::{Duration, Local, TimeZone};
+
+        // Too little history gives no opinion
+        let mut stats = RunningStats::default();
+        stats.push(20.0);
+        stats.push(30.0);
+        assert_eq!(stats.z_score(90.0), None);
+        assert!(!is_unusual(stats.z_score(90.0), 3.0));
+
+        // 20, 30, 25, 25: mean 25, population std dev 3.54
+        stats.push(25.0);
+        stats.push(25.0);
+        assert!((stats.mean - 25.0).abs() < 1e-9);
+        assert!((stats.std_dev() - 12.5f64.sqrt()).abs() < 1e-9);
+        let z = stats.z_score(40.0).unwrap();
+        assert!((z - 15.0 / 12.5f64.sqrt()).abs() < 1e-9);
+        assert!(is_unusual(Some(z), 3.0));
+        assert!(!is_unusual(stats.z_score(30.0), 3.0));
+        assert!(is_unusual(stats.z_score(5.0), 3.0), "low usage counts too");
+
+        // Identical days don't make every wiggle unusual
+        let mut flat = RunningStats::default();
+        for _ in 0..5 {
+            flat.push(10.0);
+        }
+        assert_eq!(flat.z_score(11.0), Some(1.0 / MIN_STD_DEV));
+
+        // Five days of ~20% CPU at 14:00, then a day at 80%
+        let day = |d: u32, minute: u32| Local.with_ymd_and_hms(2026, 10, d, 14, minute, 0).unwrap();
+        let mut tracker = BaselineTracker::new(HourlyBaseline::default(), None, 3.0);
+        for (d, cpu) in (1..=5).zip([18.0, 22.0, 20.0, 19.0, 21.0]) {
+            for minute in 0..60 {
+                tracker.observe(day(d, minute), cpu, 40.0).unwrap();
+            }
+            // The next hour finishes this one
+            tracker.observe(day(d, 59) + Duration::minutes(1), cpu, 40.0).unwrap();
+        }
+        let cpu = tracker.baseline().stats(BaselineMetric::Cpu, 14);
+        assert_eq!(cpu.count, 5);
+        assert!((cpu.mean - 20.0).abs() < 1e-9);
+
+        // A partial hour (procmon started at 15:50) is not recorded
+        assert_eq!(tracker.baseline().stats(BaselineMetric::Cpu, 15).count, 0);
+
+        for minute in 0..10 {
+            tracker.observe(day(6, minute), 80.0, 41.0).unwrap();
+        }
+        let anomalies = tracker.anomalies();
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].metric, BaselineMetric::Cpu);
+        assert!(anomalies[0].z_score > 3.0);
+        assert_eq!(anomalies[0].describe(), "CPU 80% is unusually high for 14:00 (usually 20% ± 1%)");
+
+        let text = tracker.baseline().to_toml().unwrap();
+        assert!(text.contains("[[cpu]]\nhour = 14\ncount = 5\n"), "{}", text);
+        let reloaded = HourlyBaseline::parse(&text).unwrap();
+        assert_eq!(&reloaded, tracker.baseline());
+        assert_eq!(HourlyBaseline::parse("").unwrap(), HourlyBaseline::default());
+        assert!(HourlyBaseline::parse("[[cpu]]\nhour = 24\ncount = 1\nmean = 1.0\nvariance = 1.0\n").is_err());
+        assert!(HourlyBaseline::parse("[[cpu]]\nhour = 1\ncount = 1\nmean = 1.0\n").is_err());
+        assert!(HourlyBaseline::parse("cpu 14 1 1 1").is_err());
+    }
+
+    #[test]
+    fn test_once_output_is_sorted_filtered_and_cut() {
+        use crate::columns::ProcessColumn;
+        use crate::once::{once_options, render_processes, select_processes};
+        use crate::sort::SortColumn;
+
+        let args = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(once_options(args(&["--sort", "mem"])).unwrap(), None);
+        assert!(once_options(args(&["--once", "--sort", "size"])).is_err());
+        assert!(once_options(args(&["--json", "--top", "-1"])).is_err());
+
+        let mut processes = vec![
+            synthetic_process(30, "postgres", 5.0, 900),
+            synthetic_process(10, "nginx", 40.0, 100),
+            synthetic_process(20, "postgres", 25.0, 300),
+            synthetic_process(40, "bash", 1.0, 50),
+        ];
+        processes[2].stats.disk_read_bytes = 5000;
+        let pids = |list: &[crate::process::ProcessSnapshot]| list.iter().map(|p| p.info.pid).collect::<Vec<_>>();
+
+        let cpu = once_options(args(&["--json"])).unwrap().unwrap();
+        assert_eq!(cpu.sort, SortColumn::Cpu);
+        assert_eq!(pids(&select_processes(processes.clone(), &cpu)), [10, 20, 30, 40]);
+
+        let by_name = once_options(args(&["--once", "--sort=name"])).unwrap().unwrap();
+        assert_eq!(pids(&select_processes(processes.clone(), &by_name)), [40, 10, 20, 30]);
+        let by_pid = once_options(args(&["--once", "--sort", "pid"])).unwrap().unwrap();
+        assert_eq!(pids(&select_processes(processes.clone(), &by_pid)), [10, 20, 30, 40]);
+        let by_disk = once_options(args(&["--once", "--sort", "disk", "--top", "1"])).unwrap().unwrap();
+        assert_eq!(pids(&select_processes(processes.clone(), &by_disk)), [20]);
+
+        // Filter first, then sort, then --top
+        let options = once_options(args(&["--json", "--filter", "POSTGRES", "--sort", "mem", "--top", "1"])).unwrap().unwrap();
+        let json = render_processes(&select_processes(processes.clone(), &options), &options, &[]).unwrap();
+        let emitted: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let emitted = emitted.as_array().unwrap();
+        assert_eq!(emitted.len(), 1);
+        assert_eq!(emitted[0]["info"]["pid"], 30);
+
+        let table = once_options(args(&["--once", "--filter", "postgres"])).unwrap().unwrap();
+        let text = render_processes(&select_processes(processes, &table), &table, &[ProcessColumn::Pid, ProcessColumn::Name]).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[1].trim_start().starts_with("20"), "{}", text);
+        assert!(lines[2].trim_start().starts_with("30"), "{}", text);
+    }
+
+    #[test]
+    fn test_gpu_memory_aggregated_per_process_from_mocked_sources() {
+        use crate::gpu::{
+            collect_gpu_clients, gpu_memory_by_pid, gpu_processes, normalize_pci_address, parse_drm_fdinfo, scan_drm_clients,
+            GpuClient, GpuProcessSource,
+        };
+
+        struct Mocked(Vec<GpuClient>);
+        impl GpuProcessSource for Mocked {
+            fn name(&self) -> &'static str {
+                "mocked"
+            }
+            fn clients(&self) -> anyhow::Result<Vec<GpuClient>> {
+                Ok(self.0.clone())
+            }
+        }
+        struct Broken;
+        impl GpuProcessSource for Broken {
+            fn name(&self) -> &'static str {
+                "broken"
+            }
+            fn clients(&self) -> anyhow::Result<Vec<GpuClient>> {
+                anyhow::bail!("driver not loaded")
+            }
+        }
+
+        let client = |pid, device: &str, client_id, memory_bytes| GpuClient { pid, device: device.to_string(), client_id, memory_bytes };
+        let drm = Mocked(vec![
+            // Two descriptors to the same client count once
+            client(100, "0000:03:00.0", Some(7), 512 << 20),
+            client(100, "0000:03:00.0", Some(7), 512 << 20),
+            // A second client of the same process adds up
+            client(100, "0000:03:00.0", Some(8), 256 << 20),
+            client(200, "0000:03:00.0", Some(9), 0),
+            client(300, "0000:01:00.0", Some(10), 1 << 30),
+        ]);
+        // NVML sees PID 300 on the same GPU (larger figure wins) and PID 400 on a second one
+        let nvml = Mocked(vec![client(300, "0000:01:00.0", None, 2 << 30), client(400, "0000:02:00.0", None, 64 << 20)]);
+        let sources: Vec<Box<dyn GpuProcessSource>> = vec![Box::new(drm), Box::new(Broken), Box::new(nvml)];
+
+        let memory = gpu_memory_by_pid(&collect_gpu_clients(&sources));
+        assert_eq!(memory.len(), 4);
+        assert_eq!(memory[&100], 768 << 20);
+        assert_eq!(memory[&200], 0);
+        assert_eq!(memory[&300], 2 << 30);
+        assert_eq!(memory[&400], 64 << 20);
+
+        let mut processes: Vec<_> = [100, 200, 300, 400, 500].into_iter().map(|pid| synthetic_process(pid, "p", 0.0, 0)).collect();
+        for process in &mut processes {
+            process.stats.gpu_memory_bytes = memory.get(&process.info.pid).copied();
+        }
+        let listed: Vec<u32> = gpu_processes(&processes).iter().map(|p| p.info.pid).collect();
+        assert_eq!(listed, [300, 100, 400, 200]);
+
+        assert_eq!(normalize_pci_address("00000000:01:00.0"), "0000:01:00.0");
+
+        let fdinfo = "pos:\t0\nflags:\t02100002\ndrm-driver:\tamdgpu\ndrm-pdev:\t0000:03:00.0\ndrm-client-id:\t42\ndrm-memory-vram:\t1024 KiB\ndrm-memory-gtt:\t2048 KiB\n";
+        let parsed = parse_drm_fdinfo(fdinfo).unwrap();
+        assert_eq!((parsed.device.as_str(), parsed.client_id, parsed.vram_bytes), ("0000:03:00.0", 42, 1 << 20));
+        let xe = "drm-driver:\txe\ndrm-client-id:\t3\ndrm-total-vram0:\t4 MiB\ndrm-total-system:\t8 MiB\n";
+        assert_eq!(parse_drm_fdinfo(xe).unwrap().vram_bytes, 4 << 20);
+        assert_eq!(parse_drm_fdinfo("pos:\t0\nflags:\t02\n"), None);
+
+        // Only descriptors into /dev/dri are looked at
+        let root = std::env::temp_dir().join(format!("procmon-gpu-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        for (fd, target, info) in [("3", "/dev/dri/renderD128", fdinfo), ("4", "/tmp/model.bin", fdinfo)] {
+            std::fs::create_dir_all(root.join("77/fd")).unwrap();
+            std::fs::create_dir_all(root.join("77/fdinfo")).unwrap();
+            std::os::unix::fs::symlink(target, root.join("77/fd").join(fd)).unwrap();
+            std::fs::write(root.join("77/fdinfo").join(fd), info).unwrap();
+        }
+        let clients = scan_drm_clients(&root);
+        assert_eq!(clients, [client(77, "0000:03:00.0", Some(42), 1 << 20)]);
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_hold_to_confirm_fires_once_after_full_hold() {
+        use crate::confirm::HoldToConfirm;
+        use std::time::{Duration, Instant};
+
+        let start = Instant::now();
+        let ms = |n| start + Duration::from_millis(n);
+        let mut hold = HoldToConfirm::new(Duration::from_secs(1));
+        assert_eq!(hold.progress(start), 0.0);
+
+        // Letting go early starts over
+        assert!(!hold.update(true, ms(0)));
+        assert!(!hold.update(true, ms(600)));
+        assert!((hold.progress(ms(600)) - 0.6).abs() < 1e-6);
+        assert!(!hold.update(false, ms(700)));
+        assert!(!hold.is_holding());
+        assert_eq!(hold.progress(ms(700)), 0.0);
+
+        assert!(!hold.update(true, ms(800)));
+        assert!(!hold.update(true, ms(1700)));
+        assert!(hold.update(true, ms(1800)));
+        assert_eq!(hold.progress(ms(2500)), 1.0);
+        // Keeping the button down doesn't fire again until it is released and held anew
+        assert!(!hold.update(true, ms(3000)));
+        assert!(!hold.update(false, ms(3100)));
+        assert!(!hold.update(true, ms(3200)));
+        assert!(hold.update(true, ms(4200)));
+    }
+
+    #[test]
+    fn test_mount_fstypes_classified_as_local_or_network() {
+        use crate::partition::{is_network_filesystem, parse_df_usage, parse_network_mounts};
+        use crate::util::output_with_timeout;
+        use std::process::Command;
+        use std::time::Duration;
+
+        for fstype in ["nfs", "nfs4", "cifs", "smb3", "fuse.sshfs", "ceph", "9p"] {
+            assert!(is_network_filesystem(fstype), "{}", fstype);
+        }
+        for fstype in ["ext4", "xfs", "btrfs", "vfat", "tmpfs", "proc", "fuse", "fuse.gvfsd-fuse", "overlay", "nfsd"] {
+            assert!(!is_network_filesystem(fstype), "{}", fstype);
+        }
+
+        let mounts = "\
+    /dev/nvme0n1p2 / ext4 rw,relatime 0 0
+proc /proc proc rw,nosuid,nodev,noexec,relatime 0 0
+nfsd /proc/fs/nfsd nfsd rw,relatime 0 0
+fileserver:/export/home /home/shared nfs4 rw,relatime,vers=4.2 0 0
+//nas/Team\\040Files /mnt/team\\040files cifs rw,relatime,vers=3.1.1 0 0
+user@host:/srv /home/me/remote fuse.sshfs rw,nosuid,nodev,relatime,user_id=1000 0 0
+";
+        let network = parse_network_mounts(mounts);
+        let listed: Vec<(&str, &str, &str)> = network
+            .iter()
+            .map(|mount| (mount.source.as_str(), mount.mount_point.as_str(), mount.filesystem.as_str()))
+            .collect();
+        assert_eq!(
+            listed,
+            [
+                ("fileserver:/export/home", "/home/shared", "nfs4"),
+                ("//nas/Team Files", "/mnt/team files", "cifs"),
+                ("user@host:/srv", "/home/me/remote", "fuse.sshfs"),
+            ]
+        );
+        assert!(network.iter().all(|mount| mount.usage.is_none()));
+
+        let df = "Filesystem 1-blocks Used Available Capacity Mounted on\nfileserver:/export/home 1000000 250000 750000 25% /home/shared\n";
+        assert_eq!(parse_df_usage(df), Some((250000, 1000000)));
+        assert_eq!(parse_df_usage("Filesystem 1-blocks Used\n"), None);
+
+        // A command that outlives its timeout is given up on instead of blocking
+        let output = output_with_timeout(Command::new("echo").arg("ok"), Duration::from_secs(5)).unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "ok");
+        assert!(output_with_timeout(Command::new("sleep").arg("5"), Duration::from_millis(50)).is_err());
+    }
+
+    #[test]
+    fn test_partition_number_for_each_device_naming_scheme() {
+        use crate::partition::partition_number;
+
+        // SCSI/SATA/virtio: number right after the disk name
+        assert_eq!(partition_number("sda3", "sda"), Some(3));
+        assert_eq!(partition_number("sdp1", "sdp"), Some(1));
+        assert_eq!(partition_number("vdb12", "vdb"), Some(12));
+        // NVMe namespaces and SD/eMMC cards end in a digit, so a `p` separates the number
+        assert_eq!(partition_number("nvme0n1p3", "nvme0n1"), Some(3));
+        assert_eq!(partition_number("nvme1n2p10", "nvme1n2"), Some(10));
+        assert_eq!(partition_number("mmcblk0p2", "mmcblk0"), Some(2));
+        assert_eq!(partition_number("loop0p1", "loop0"), Some(1));
+        // Not partitions of that disk, or not numbered
+        assert_eq!(partition_number("nvme0n12", "nvme0n1"), None);
+        assert_eq!(partition_number("mmcblk0boot0", "mmcblk0"), None);
+        assert_eq!(partition_number("vg0-root", "sda"), None);
+        assert_eq!(partition_number("sda", "sda"), None);
+    }
+
+    #[test]
+    fn test_removable_detection_and_eject_command_sequence() {
+        use crate::partition::{disk_mount_points, eject_commands, is_removable};
+
+        // A card reader sets `removable`; a USB hard drive doesn't but sits below a USB port
+        let root = std::env::temp_dir().join(format!("procmon-eject-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let sys_block = root.join("block");
+        let usb_disk = root.join("devices/pci0000:00/usb2/2-1/2-1:1.0/block/sdc");
+        let sata_disk = root.join("devices/pci0000:00/ata1/host0/block/sda");
+        std::fs::create_dir_all(sys_block.join("mmcblk0")).unwrap();
+        std::fs::write(sys_block.join("mmcblk0/removable"), "1\n").unwrap();
+        for disk in [&usb_disk, &sata_disk] {
+            std::fs::create_dir_all(disk).unwrap();
+            std::fs::write(disk.join("removable"), "0\n").unwrap();
+        }
+        std::os::unix::fs::symlink(&usb_disk, sys_block.join("sdc")).unwrap();
+        std::os::unix::fs::symlink(&sata_disk, sys_block.join("sda")).unwrap();
+        assert!(is_removable(&sys_block, "mmcblk0"));
+        assert!(is_removable(&sys_block, "sdc"));
+        assert!(!is_removable(&sys_block, "sda"));
+        assert!(!is_removable(&sys_block, "nvme0n1"));
+        std::fs::remove_dir_all(&root).unwrap();
+
+        let mounts = "\
+    /dev/sda2 / ext4 rw,relatime 0 0
+/dev/sdc1 /media/me/STICK vfat rw,nosuid,nodev 0 0
+/dev/sdc2 /media/me/STICK/inner ext4 rw 0 0
+/dev/sdc10 /media/me/My\\040Data ntfs3 rw 0 0
+/dev/sdcd1 /mnt/other ext4 rw 0 0
+";
+        let mount_points = disk_mount_points(mounts, "/dev/sdc");
+        assert_eq!(mount_points, ["/media/me/STICK/inner", "/media/me/STICK", "/media/me/My Data"]);
+
+        let plan: Vec<_> = eject_commands("/dev/sdc", &mount_points).iter().map(command_line).collect();
+        assert_eq!(
+            plan,
+            vec![
+                vec!["umount", "/media/me/STICK/inner"],
+                vec!["umount", "/media/me/STICK"],
+                vec!["umount", "/media/me/My Data"],
+                vec!["udisksctl", "power-off", "-b", "/dev/sdc"],
+            ]
+        );
+        assert_eq!(command_line(&eject_commands("/dev/sdc", &[])[0]), vec!["udisksctl", "power-off", "-b", "/dev/sdc"]);
+    }
+
+    #[test]
+    fn test_own_process_excluded_when_enabled() {
+        use crate::detector::{MisbehaviorCondition, MisbehaviorDetector, MisbehaviorRule, Severity};
+        use crate::monitor::SystemMonitor;
+        use crate::own::{mark_own_processes, self_processes, SelfProcesses};
+
+        // procmon (1000) ran lsblk (1001), which ran a helper (1002); 2000 is unrelated
+        let processes: Vec<_> = [(1000, 1), (1001, 1000), (1002, 1001), (2000, 1)]
+            .into_iter()
+            .map(|(pid, parent)| {
+                let mut process = synthetic_process(pid, "p", 0.0, 0);
+                process.info.parent_pid = Some(parent);
+                process.stats.num_threads = 50;
+                process
+            })
+            .collect();
+
+        let mut hidden = processes.clone();
+        mark_own_processes(&mut hidden, 1000, SelfProcesses::Hide);
+        assert_eq!(hidden.iter().map(|p| p.info.pid).collect::<Vec<_>>(), [2000]);
+
+        let mut tagged = processes.clone();
+        mark_own_processes(&mut tagged, 1000, SelfProcesses::Tag);
+        let own: Vec<u32> = tagged.iter().filter(|p| p.info.is_own).map(|p| p.info.pid).collect();
+        assert_eq!(own, [1000, 1001, 1002]);
+
+        // Included processes are listed untagged, like any other
+        let mut included = processes.clone();
+        mark_own_processes(&mut included, 1000, SelfProcesses::Include);
+        assert_eq!(included.len(), 4);
+        assert!(included.iter().all(|p| !p.info.is_own));
+
+        // Tagged processes raise no alerts unless asked for
+        let rule = MisbehaviorRule {
+            name: "Threads".to_string(),
+            description: "Too many threads".to_string(),
+            condition: MisbehaviorCondition::TooManyThreads { threshold: 10 },
+            severity: Severity::Warning,
+        };
+        let mut detector = MisbehaviorDetector::with_rules(vec![rule]);
+        let alerted: Vec<u32> = tagged.iter().flat_map(|p| detector.check_process(p)).map(|a| a.pid).collect();
+        assert_eq!(alerted, [2000]);
+        detector.set_own_alerts(true);
+        assert_eq!(tagged.iter().flat_map(|p| detector.check_process(p)).count(), 4);
+
+        assert_eq!(self_processes(Vec::new(), None).unwrap(), SelfProcesses::Tag);
+        assert_eq!(self_processes(vec!["--self-processes=hide".to_string()], Some("include")).unwrap(), SelfProcesses::Hide);
+        assert_eq!(self_processes(Vec::new(), Some("Include")).unwrap(), SelfProcesses::Include);
+        assert!(self_processes(Vec::new(), Some("skip")).is_err());
+
+        // The real monitor finds this test process and leaves it out when hiding
+        let own_pid = std::process::id();
+        let mut monitor = SystemMonitor::new();
+        monitor.refresh();
+        let listed = monitor.get_all_processes().unwrap();
+        assert!(listed.iter().any(|p| p.info.pid == own_pid && p.info.is_own));
+        monitor.set_self_processes(SelfProcesses::Include);
+        assert!(monitor.get_all_processes().unwrap().iter().any(|p| p.info.pid == own_pid && !p.info.is_own));
+        monitor.set_self_processes(SelfProcesses::Hide);
+        assert!(!monitor.get_all_processes().unwrap().iter().any(|p| p.info.pid == own_pid));
+    }
+
+    #[test]
+    fn test_double_click_at_configured_threshold_boundary() {
+        use crate::click::{double_click, parse_double_click, DoubleClick};
+        use std::time::{Duration, Instant};
+
+        let start = Instant::now();
+        let ms = |n| start + Duration::from_millis(n);
+
+        let mut clicks = DoubleClick::new(Some(Duration::from_millis(300)));
+        assert!(!clicks.click(4, ms(0)));
+        assert!(clicks.click(4, ms(299)));
+        // Exactly at the threshold is too slow and starts a new pair
+        assert!(!clicks.click(4, ms(1000)));
+        assert!(!clicks.click(4, ms(1300)));
+        assert!(clicks.click(4, ms(1301)));
+        // A different row, or the click after a completed double-click, is a single click again
+        assert!(!clicks.click(4, ms(2000)));
+        assert!(!clicks.click(5, ms(2100)));
+        assert!(clicks.click(5, ms(2200)));
+        assert!(!clicks.click(5, ms(2250)));
+
+        let mut off = DoubleClick::new(None);
+        assert!(!off.click(1, ms(0)));
+        assert!(!off.click(1, ms(10)));
+
+        assert_eq!(parse_double_click("400").unwrap(), Some(Duration::from_millis(400)));
+        assert_eq!(parse_double_click("250ms").unwrap(), Some(Duration::from_millis(250)));
+        assert_eq!(parse_double_click("OFF").unwrap(), None);
+        assert_eq!(parse_double_click("100").unwrap(), Some(Duration::from_millis(100)));
+        assert_eq!(parse_double_click("2000").unwrap(), Some(Duration::from_millis(2000)));
+        assert!(parse_double_click("99").is_err());
+        assert!(parse_double_click("2001").is_err());
+        assert!(parse_double_click("fast").is_err());
+
+        assert_eq!(double_click(Vec::new(), None).unwrap(), Some(Duration::from_millis(500)));
+        assert_eq!(double_click(vec!["--double-click".to_string(), "off".to_string()], Some("700")).unwrap(), None);
+        assert_eq!(double_click(Vec::new(), Some("700")).unwrap(), Some(Duration::from_millis(700)));
+    }
+
+    #[test]
+    fn test_exe_hash_cache_hashes_lazily_and_rehashes_replaced_files() {
+        use crate::exehash::{hash_listed, sha256_file, ExeHashCache, MAX_RUNNING_HASHES};
+
+        let dir = std::env::temp_dir().join(format!("procmon-exehash-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("payload");
+        fs::write(&file, b"hello\n").unwrap();
+        let hello = "5891b5b522d5df086d0ff0b110fbd9d21bb4fc7163af34d08286a2e846f6be03";
+        assert_eq!(sha256_file(&file).unwrap(), hello);
+        assert!(hash_listed(hello, &[" 5891B5B522D5DF086D0FF0B110FBD9D21BB4FC7163AF34D08286A2E846F6BE03 ".to_string()]));
+        assert!(!hash_listed(hello, &["00".to_string()]));
+
+        // Hashing happens in the background; the result appears on a later call
+        let mut cache = ExeHashCache::default();
+        assert_eq!(cache.hash(&file, &file), None);
+        assert_eq!(wait_for(|| cache.hash(&file, &file)).as_deref(), Some(hello));
+        assert_eq!(cache.cached(&file), Some(hello));
+
+        // Only a few files are hashed at once; the rest wait for a later call
+        let others: Vec<_> = (0..=MAX_RUNNING_HASHES).map(|i| dir.join(format!("other-{}", i))).collect();
+        for other in &others {
+            fs::write(other, b"other").unwrap();
+            assert_eq!(cache.hash(other, other), None);
+        }
+        let last = &others[MAX_RUNNING_HASHES];
+        for other in &others[..MAX_RUNNING_HASHES] {
+            assert!(wait_for(|| cache.hash(other, other)).is_some());
+        }
+        assert!(cache.cached(last).is_none());
+        assert!(wait_for(|| cache.hash(last, last)).is_some());
+
+        // A replaced executable (different size) is hashed again
+        fs::write(&file, b"hello, world\n").unwrap();
+        let rehashed = wait_for(|| cache.hash(&file, &file));
+        assert!(rehashed.is_some_and(|hash| hash != hello));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_exe_hash_rule_matches_running_executable() {
+        use crate::detector::{MisbehaviorCondition, MisbehaviorDetector, MisbehaviorRule, Severity};
+        use crate::exehash::sha256_file;
+
+        // The test binary itself stands in for a known-bad executable
+        let exe = std::env::current_exe().unwrap();
+        let mut process = synthetic_process(std::process::id(), "procmon-core", 0.0, 0);
+        process.info.exe_path = Some(exe.clone());
+
+        let rule = |hashes: Vec<String>| MisbehaviorRule {
+            name: "Known Malware".to_string(),
+            description: "Executable hash on the blocklist".to_string(),
+            condition: MisbehaviorCondition::ExeHashMatches { hashes },
+            severity: Severity::Critical,
+        };
+        let hash = sha256_file(&exe).unwrap();
+        let mut detector = MisbehaviorDetector::with_rules(vec![rule(vec![hash.to_uppercase()])]);
+        // The first check only starts hashing in the background
+        assert!(detector.check_process(&process).is_empty());
+        let alerts = wait_for(|| Some(detector.check_process(&process)).filter(|alerts| !alerts.is_empty())).unwrap();
+        assert_eq!(alerts.len(), 1);
+        assert!(alerts[0].details.contains(&hash));
+
+        let mut detector = MisbehaviorDetector::with_rules(vec![rule(vec!["0".repeat(64)])]);
+        for _ in 0..20 {
+            assert!(detector.check_process(&process).is_empty());
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn test_partition_actions_need_root_or_escalation() {
+        use crate::privilege::{escalated_command, escalation, privileged_actions_enabled};
+        use std::process::Command;
+
+        let sudo = vec!["sudo".to_string(), "-n".to_string()];
+        assert!(privileged_actions_enabled(true, None));
+        assert!(privileged_actions_enabled(true, Some(&sudo)));
+        assert!(privileged_actions_enabled(false, Some(&sudo)));
+        assert!(!privileged_actions_enabled(false, None));
+        assert!(!privileged_actions_enabled(false, Some(&[])));
+
+        assert_eq!(escalation(Vec::new(), None).unwrap(), None);
+        assert_eq!(escalation(Vec::new(), Some("sudo -n")).unwrap(), Some(sudo.clone()));
+        assert_eq!(escalation(vec!["--escalate=pkexec".to_string()], Some("sudo -n")).unwrap(), Some(vec!["pkexec".to_string()]));
+        assert!(escalation(vec!["--escalate".to_string(), "  ".to_string()], None).is_err());
+
+        let mut parted = Command::new("parted");
+        parted.args(["-s", "/dev/sdb", "rm", "2"]);
+        assert_eq!(command_line(&escalated_command(&parted, Some(&sudo), false)), vec!["sudo", "-n", "parted", "-s", "/dev/sdb", "rm", "2"]);
+        assert_eq!(command_line(&escalated_command(&parted, Some(&sudo), true)), vec!["parted", "-s", "/dev/sdb", "rm", "2"]);
+        assert_eq!(command_line(&escalated_command(&parted, None, false)), vec!["parted", "-s", "/dev/sdb", "rm", "2"]);
+    }
+
+    #[test]
+    fn test_network_sort_by_rate_or_total() {
+        use crate::sort::{sort_by_network, NetworkSort};
+
+        let process = |pid: u32, rate: u64, total: u64| {
+            let mut process = synthetic_process(pid, "net", 0.0, 0);
+            process.stats.network_bytes_per_sec = rate;
+            process.stats.network_rx_bytes = total;
+            process
+        };
+        // A long-lived daemon with a large lifetime total but idle now, and two busy ties
+        let mut processes = vec![process(30, 0, 900_000), process(12, 50_000, 1_000), process(7, 50_000, 2_000), process(3, 10, 10)];
+
+        sort_by_network(&mut processes, NetworkSort::Rate);
+        let pids: Vec<u32> = processes.iter().map(|p| p.info.pid).collect();
+        assert_eq!(pids, vec![7, 12, 3, 30]);
+
+        sort_by_network(&mut processes, NetworkSort::Total);
+        let pids: Vec<u32> = processes.iter().map(|p| p.info.pid).collect();
+        assert_eq!(pids, vec![30, 7, 12, 3]);
+
+        assert_eq!(NetworkSort::default(), NetworkSort::Rate);
+        assert_eq!(NetworkSort::Rate.toggle(), NetworkSort::Total);
+        assert_eq!(NetworkSort::Total.toggle().label(), "Rate");
+    }
+
+    #[test]
+    fn test_per_process_tcp_traffic_from_ss() {
+        use crate::netio::{parse_ss_sockets, NetworkIoTracker, SocketBytes};
+        use std::time::{Duration, Instant};
+
+        let output = "ESTAB 0      0      127.0.0.1:55986 127.0.0.1:48271 users:((\"curl\",pid=988,fd=14)) timer:(keepalive,25sec,0) ino:51227 sk:1 cgroup:/ <->\n\
+                      \t ts sack cubic wscale:10,10 rto:204 bytes_sent:6522990 bytes_acked:6522984 bytes_received:864912 segs_out:439\n\
+                      ESTAB 0      0      10.0.0.2:22 10.0.0.9:50122 ino:61234 sk:2 <->\n\
+                      \t ts sack cubic bytes_acked:100 bytes_received:200\n\
+                      LISTEN 0      128    0.0.0.0:80 0.0.0.0:* users:((\"nginx\",pid=700,fd=6),(\"nginx\",pid=701,fd=6)) ino:4411 sk:3 <->\n\
+                      \t cubic rto:1000\n";
+        let sockets = parse_ss_sockets(output);
+        assert_eq!(
+            sockets,
+            vec![
+                SocketBytes { inode: 51227, pid: 988, received: 864912, sent: 6522984 },
+                // A listening socket shared by workers goes to the first of them
+                SocketBytes { inode: 4411, pid: 700, received: 0, sent: 0 },
+            ]
+        );
+
+        let socket = |inode: u64, pid: u32, received: u64, sent: u64| SocketBytes { inode, pid, received, sent };
+        let start = Instant::now();
+        let mut tracker = NetworkIoTracker::default();
+        tracker.update(&[socket(1, 10, 5_000, 1_000)], start);
+        // Bytes of connections open before the first sample count towards the total, not the rate
+        assert_eq!(tracker.get(10).rx_bytes, 5_000);
+        assert_eq!(tracker.get(10).bytes_per_sec, 0);
+
+        let later = start + Duration::from_secs(2);
+        tracker.update(&[socket(1, 10, 7_000, 1_000), socket(2, 10, 0, 4_000)], later);
+        let io = tracker.get(10);
+        assert_eq!((io.rx_bytes, io.tx_bytes), (7_000, 5_000));
+        assert_eq!(io.bytes_per_sec, 3_000);
+
+        // Closed connections keep their bytes in the total; an idle process has no rate
+        tracker.update(&[], later + Duration::from_secs(2));
+        assert_eq!(tracker.get(10).rx_bytes, 7_000);
+        assert_eq!(tracker.get(10).bytes_per_sec, 0);
+
+        tracker.retain(|pid| pid != 10);
+        assert_eq!(tracker.get(10), Default::default());
+    }
+
+    #[test]
+    fn test_alert_badge_counts_unacknowledged_alerts() {
+        use crate::badge::{AlertAcknowledgement, AlertBadge};
+        use crate::detector::Severity;
+
+        let start = chrono::Utc::now();
+        let at = |seconds: i64, severity: Severity| {
+            let mut alert = alert_with(1, "rule", severity);
+            alert.timestamp = start + chrono::Duration::seconds(seconds);
+            alert
+        };
+        let mut alerts = vec![
+            at(0, Severity::Critical),
+            at(1, Severity::Warning),
+            at(2, Severity::Critical),
+            at(3, Severity::Info),
+            at(4, Severity::Critical),
+        ];
+
+        let mut acknowledgement = AlertAcknowledgement::default();
+        let badge = acknowledgement.badge(&alerts);
+        assert_eq!(badge, AlertBadge { unacknowledged: 5, critical: 3, highest: Some(Severity::Critical) });
+        assert_eq!(badge.label().as_deref(), Some("[3!]"));
+
+        // Viewing the Alerts tab clears the badge; only alerts arriving afterwards count
+        acknowledgement.acknowledge(&alerts);
+        assert_eq!(acknowledgement.badge(&alerts).label(), None);
+        alerts.push(at(5, Severity::Warning));
+        alerts.push(at(6, Severity::Info));
+        let badge = acknowledgement.badge(&alerts);
+        assert_eq!(badge, AlertBadge { unacknowledged: 2, critical: 0, highest: Some(Severity::Warning) });
+        assert_eq!(badge.label().as_deref(), Some("[2]"));
+
+        // Old alerts trimmed from the front don't bring the badge back
+        alerts.drain(0..3);
+        assert_eq!(acknowledgement.badge(&alerts).unacknowledged, 2);
+        acknowledgement.acknowledge(&[]);
+        assert_eq!(acknowledgement.badge(&alerts).unacknowledged, 2);
+    }
+
+    #[test]
+    fn test_wrap_text_splits_long_arguments_at_char_boundaries() {
+        use crate::detail::wrap_text;
+
+        assert_eq!(wrap_text("java -Xmx2g -jar app.jar", 11), vec!["java -Xmx2g", "-jar", "app.jar"]);
+        assert_eq!(wrap_text("", 10), vec![""]);
+
+        // A kilobyte-long classpath with multi-byte characters in it
+        let classpath = format!("-cp {}", "/opt/lib/ünïcode.jar:".repeat(60));
+        let lines = wrap_text(&format!("java {} Main", classpath), 40);
+        assert!(lines.len() > 30);
+        assert!(lines.iter().all(|line| line.chars().count() <= 40));
+        assert_eq!(lines[0], "java -cp");
+        // Nothing is lost: the pieces put back together give the original argument, and the
+        // next word follows the tail of the classpath on its last line
+        assert_eq!(lines[1..].concat(), format!("{} Main", "/opt/lib/ünïcode.jar:".repeat(60)));
+
+        assert_eq!(wrap_text("ab", 0), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_crash_loop_detected_from_repeated_short_lived_processes() {
+        use crate::detector::{MisbehaviorCondition, MisbehaviorDetector, MisbehaviorRule, Severity};
+        use crate::metrics::SystemMetrics;
+
+        let mut detector = MisbehaviorDetector::with_rules(vec![MisbehaviorRule {
+            name: "Crash Loop".to_string(),
+            description: "Keeps restarting".to_string(),
+            condition: MisbehaviorCondition::CrashLoop { max_respawns: 3, window_secs: 60 },
+            severity: Severity::Warning,
+        }]);
+        let init = synthetic_process(1, "init", 0.0, 0);
+        let worker = |pid: u32| {
+            let mut process = synthetic_process(pid, "worker", 0.0, 0);
+            process.info.exe_path = Some(std::path::PathBuf::from("/usr/bin/worker"));
+            process.info.parent_pid = Some(1);
+            process
+        };
+        // Another program with the same name but a different executable doesn't add to the count
+        let mut other = synthetic_process(900, "worker", 0.0, 0);
+        other.info.parent_pid = Some(1);
+        other.info.exe_path = Some(std::path::PathBuf::from("/opt/worker"));
+
+        // Each refresh the previous worker has died and a new one was started in its place
+        assert!(detector.check_system(&[init.clone(), worker(100), other.clone()], &SystemMetrics::default()).is_empty());
+        for pid in 101..=103 {
+            let alerts = detector.check_system(&[init.clone(), worker(pid)], &SystemMetrics::default());
+            assert!(alerts.is_empty(), "respawn of PID {} is still under the threshold", pid);
+        }
+        let alerts = detector.check_system(&[init.clone(), worker(104), other.clone()], &SystemMetrics::default());
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].pid, 104);
+        assert_eq!(alerts[0].process_name, "worker");
+        assert!(alerts[0].details.contains("/usr/bin/worker restarted 4 times"));
+
+        // Nothing new respawned: no repeated alert for the same loop
+        assert!(detector.check_system(&[init.clone(), worker(104), other], &SystemMetrics::default()).is_empty());
+
+        // Several instances running side by side are not respawns
+        let mut parallel = MisbehaviorDetector::with_rules(detector.get_rules().to_vec());
+        let mut table = vec![init.clone()];
+        for pid in 200..220 {
+            table.push(worker(pid));
+            assert!(parallel.check_system(&table, &SystemMetrics::default()).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_crash_loop_ignores_periodic_jobs_and_replaced_long_runners() {
+        use crate::detector::MisbehaviorDetector;
+        use crate::metrics::SystemMetrics;
+        use crate::respawn::SHORT_LIVED_SECS;
+
+        let rules: Vec<_> = MisbehaviorDetector::new()
+            .get_rules()
+            .iter()
+            .filter(|rule| rule.name == "Crash Loop")
+            .cloned()
+            .collect();
+        assert_eq!(rules.len(), 1);
+
+        // A short job cron runs over and over: each run is forked off by a new cron child
+        let mut detector = MisbehaviorDetector::with_rules(rules.clone());
+        let cron = synthetic_process(1, "cron", 0.0, 0);
+        for run in 0..20 {
+            let mut fork = synthetic_process(1000 + run, "cron", 0.0, 0);
+            fork.info.parent_pid = Some(1);
+            let mut job = synthetic_process(2000 + run, "logrotate", 0.0, 0);
+            job.info.exe_path = Some(std::path::PathBuf::from("/usr/sbin/logrotate"));
+            job.info.parent_pid = Some(1000 + run);
+            assert!(detector.check_system(&[cron.clone(), fork, job], &SystemMetrics::default()).is_empty());
+            // Between runs nothing of the job is left
+            assert!(detector.check_system(std::slice::from_ref(&cron), &SystemMetrics::default()).is_empty());
+        }
+
+        // Workers a supervisor replaces after they've served for a while aren't crashing
+        let mut detector = MisbehaviorDetector::with_rules(rules);
+        let init = synthetic_process(1, "init", 0.0, 0);
+        for pid in 100..120 {
+            let mut worker = synthetic_process(pid, "worker", 0.0, 0);
+            worker.info.exe_path = Some(std::path::PathBuf::from("/usr/bin/worker"));
+            worker.info.parent_pid = Some(1);
+            worker.stats.start_time = chrono::Utc::now() - chrono::Duration::seconds(SHORT_LIVED_SECS + 60);
+            assert!(detector.check_system(&[init.clone(), worker], &SystemMetrics::default()).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_raw_proc_fields_match_parsed_values() {
+        use crate::detail::{raw_proc_fields, raw_stat_fields, raw_status_fields};
+
+        let stat = "1234 (Web Content (x)) S 1200 1200 1200 0 -1 4194560 51234 0 12 0 5123 812 0 0 20 -5 \
+                    27 0 98765 2800000000 65000 18446744073709551615 1 1 0 0 0 0 0 16781312 1073745144 \
+                    0 0 0 17 5 0 0 0 0 0 0 0 0 0 0 0 0 0";
+        let status = "Name:\tWeb Content\nUid:\t1000\t1000\t1000\t1000\nGid:\t100\t100\t100\t100\nVmSwap:\t     200 kB\n";
+
+        assert_eq!(
+            raw_stat_fields(stat),
+            vec![
+                (3, "state", "S"),
+                (4, "ppid", "1200"),
+                (10, "minflt", "51234"),
+                (12, "majflt", "12"),
+                (19, "nice", "-5"),
+                (20, "num_threads", "27"),
+                (39, "processor", "5"),
+            ]
+        );
+        assert_eq!(raw_status_fields(status), vec![("Uid:", "1000\t1000\t1000\t1000"), ("Gid:", "100\t100\t100\t100"), ("VmSwap:", "200 kB")]);
+        assert!(raw_stat_fields("garbage").is_empty());
+        assert!(raw_status_fields("Name:\tkthreadd\n").is_empty());
+
+        let fields: Vec<(&str, String, String, String)> = raw_proc_fields(stat, status)
+            .into_iter()
+            .map(|field| (field.source, field.name, field.raw, field.parsed))
+            .collect();
+        let row = |source: &'static str, name: &str, raw: &str, parsed: &str| {
+            (source, name.to_string(), raw.to_string(), parsed.to_string())
+        };
+        assert_eq!(
+            fields,
+            vec![
+                row("stat", "state (3)", "S", "Sleeping"),
+                row("stat", "ppid (4)", "1200", "1200"),
+                row("stat", "minflt (10)", "51234", "51234"),
+                row("stat", "majflt (12)", "12", "12"),
+                row("stat", "nice (19)", "-5", "-5"),
+                row("stat", "num_threads (20)", "27", "27"),
+                row("stat", "processor (39)", "5", "5"),
+                row("status", "Uid", "1000\t1000\t1000\t1000", "1000"),
+                row("status", "Gid", "100\t100\t100\t100", "100"),
+                row("status", "VmSwap", "200 kB", "204800 bytes"),
+            ]
+        );
+
+        // A value the parser can't make sense of shows as such instead of a stale snapshot value
+        let fields = raw_proc_fields(stat, "Uid:\tnobody\n");
+        assert_eq!(fields.last().map(|field| (field.raw.as_str(), field.parsed.as_str())), Some(("nobody", "?")));
+    }
+
+    #[test]
+    fn test_page_faults_from_stat() {
+        use crate::columns::ProcessColumn;
+        use crate::faults::{is_major_fault_spike, parse_page_faults, FaultHistory, MAJOR_FAULT_SPIKE};
+
+        // minflt is field 10 and majflt field 12, counted after the parenthesised name
+        let stat = "1234 (Web Content (x)) S 1200 1200 1200 0 -1 4194560 51234 0 12 0 5123 812 0 0 20 0 \
+                    27 0 98765 2800000000 65000 18446744073709551615 1 1 0 0 0 0 0 16781312 1073745144 \
+                    0 0 0 17 5 0 0 0 0 0 0 0 0 0 0 0 0 0";
+        assert_eq!(parse_page_faults(stat), Some((51234, 12)));
+        assert_eq!(parse_page_faults("1 (init) S 0 1"), None);
+        assert_eq!(parse_page_faults("garbage"), None);
+
+        // Deltas are per refresh; a second read within the same refresh sees the same one
+        let mut history = FaultHistory::default();
+        let first = std::time::Instant::now();
+        let second = first + std::time::Duration::from_secs(1);
+        assert_eq!(history.delta(first, 1234, 12), 0);
+        assert_eq!(history.delta(second, 1234, 12 + MAJOR_FAULT_SPIKE), MAJOR_FAULT_SPIKE);
+        assert_eq!(history.delta(second, 1234, 12 + MAJOR_FAULT_SPIKE), MAJOR_FAULT_SPIKE);
+        // A process that appeared since the last refresh has no baseline yet
+        assert_eq!(history.delta(second, 4321, 5000), 0);
+
+        let mut process = synthetic_process(1234, "Web Content", 0.0, 0);
+        process.stats.major_faults = 12 + MAJOR_FAULT_SPIKE;
+        process.stats.major_faults_delta = MAJOR_FAULT_SPIKE;
+        assert!(is_major_fault_spike(&process.stats));
+        assert_eq!(ProcessColumn::MajFlt.cell(&process, false), "112 (+100)");
+        process.stats.major_faults_delta = 0;
+        assert!(!is_major_fault_spike(&process.stats));
+        assert_eq!(ProcessColumn::MajFlt.cell(&process, false), "112");
+    }
+
+    #[test]
+    fn test_memory_growth_between_refreshes() {
+        use crate::growth::{top_memory_growth, MemoryGrowth};
+
+        const MB: u64 = 1024 * 1024;
+        let previous = vec![
+            synthetic_process(10, "leaky", 0.0, 100 * MB),
+            synthetic_process(20, "steady", 0.0, 50 * MB),
+            synthetic_process(30, "shrinking", 0.0, 80 * MB),
+            synthetic_process(40, "exited", 0.0, 10 * MB),
+        ];
+        let current = vec![
+            synthetic_process(10, "leaky", 0.0, 160 * MB),
+            synthetic_process(20, "steady", 0.0, 50 * MB),
+            synthetic_process(30, "shrinking", 0.0, 70 * MB),
+            synthetic_process(50, "new", 0.0, 20 * MB),
+        ];
+
+        let mut growth = MemoryGrowth::default();
+        let first = std::time::Instant::now();
+        let second = first + std::time::Duration::from_secs(1);
+        // Nothing counts as growth on the very first refresh
+        for process in &previous {
+            assert_eq!(growth.delta(first, process.info.pid, process.stats.memory_usage), 0);
+        }
+        let mut tracked = current.clone();
+        for process in &mut tracked {
+            process.stats.memory_delta = growth.delta(second, process.info.pid, process.stats.memory_usage);
+        }
+        let deltas: Vec<(u32, i64)> = tracked.iter().map(|p| (p.info.pid, p.stats.memory_delta)).collect();
+        // A new process's whole size is growth
+        assert_eq!(deltas, vec![(10, 60 * MB as i64), (20, 0), (30, -10 * MB as i64), (50, 20 * MB as i64)]);
+
+        // The same refresh asked about again doesn't move the baseline
+        assert_eq!(growth.delta(second, 10, 160 * MB), 60 * MB as i64);
+        // The exited process is gone from the baseline, so a reused PID starts over
+        let third = second + std::time::Duration::from_secs(1);
+        assert_eq!(growth.delta(third, 10, 150 * MB), -10 * MB as i64);
+        assert_eq!(growth.delta(third, 40, 5 * MB), 5 * MB as i64);
+
+        let top: Vec<u32> = top_memory_growth(&tracked).iter().map(|p| p.info.pid).collect();
+        assert_eq!(top, vec![10, 50]);
+    }
+
+    #[test]
+    fn test_config_round_trip() {
+        use crate::config::{Config, DisplayConfig, GeneralConfig, ListConfig, RulesConfig, Theme, DEFAULT_CONFIG};
+
+        // The file written on first run is exactly the defaults
+        assert_eq!(Config::parse(DEFAULT_CONFIG).unwrap(), Config::default());
+        assert_eq!(Config::parse("").unwrap(), Config::default());
+
+        let config = Config {
+            general: GeneralConfig { refresh_interval_ms: 2500, startup_tab: Some("processes".to_string()) },
+            display: DisplayConfig {
+                theme: Theme::Light,
+                temperature_unit: Some("f".to_string()),
+                density: Some("compact".to_string()),
+                columns: Some("pid,name,cpu".to_string()),
+                smoothing_alpha: 0.5,
+                max_alerts: 500,
+                max_alerts_per_sec: 0,
+                detail_entries: 200,
+            },
+            lists: ListConfig {
+                ignore: vec!["kworker*".to_string()],
+                allow: vec!["ffmpeg".to_string()],
+                protect: vec!["sshd".to_string(), "postgres".to_string()],
+            },
+            rules: RulesConfig { path: Some("/etc/procmon/rules.json".into()) },
+        };
+        let text = toml::to_string(&config).unwrap();
+        assert_eq!(Config::parse(&text).unwrap(), config);
+        assert_eq!(config.rules_path(), Some("/etc/procmon/rules.json".into()));
+        assert_eq!(config.general.refresh_interval(), std::time::Duration::from_millis(2500));
+
+        // Missing keys keep their defaults
+        let partial = Config::parse("[display]\ntheme = \"light\"\n").unwrap();
+        assert_eq!(partial.display.theme, Theme::Light);
+        assert_eq!(partial.display.max_alerts, 100);
+        assert_eq!(partial.display.alert_rate_cap(), Some(20));
+        assert_eq!(config.display.alert_rate_cap(), None);
+        assert_eq!(partial.display.gauge_alpha(), crate::GaugeSmoothing::DEFAULT_ALPHA);
+        let frozen = Config::parse("[display]\nsmoothing_alpha = 0.0\n").unwrap();
+        assert_eq!(frozen.display.gauge_alpha(), 0.05);
+        assert_eq!(partial.general, GeneralConfig::default());
+
+        // The default is written on first run and read back afterwards
+        let dir = std::env::temp_dir().join(format!("procmon-config-test-{}", std::process::id()));
+        let path = dir.join("procmon").join("config.toml");
+        assert_eq!(Config::load_or_create(&path), (Config::default(), Vec::new()));
+        assert_eq!(fs::read_to_string(&path).unwrap(), DEFAULT_CONFIG);
+        fs::write(&path, &text).unwrap();
+        assert_eq!(Config::load_or_create(&path), (config, Vec::new()));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_malformed_config_sections_fall_back_to_defaults() {
+        use crate::config::{parse_rules, Config, DisplayConfig, Theme};
+
+        let text = "\
+    [general]
+refresh_interval_ms = 2000
+
+[display]
+theme = \"light\"
+max_alerts = \"lots\"
+
+[lists]
+ignore = [\"kworker*\",
+          \"migration*\"]
+protect = [\"sshd\"]
+
+[rules]
+path = \"rules.json\"
+";
+        let (config, warnings) = Config::parse_lenient(text);
+        // Valid sections load, including a multi-line array
+        assert_eq!(config.general.refresh_interval_ms, 2000);
+        assert_eq!(config.lists.ignore, vec!["kworker*".to_string(), "migration*".to_string()]);
+        assert_eq!(config.lists.protect, vec!["sshd".to_string()]);
+        assert_eq!(config.rules.path, Some("rules.json".into()));
+        // The bad section is all defaults, its valid theme included
+        assert_eq!(config.display, DisplayConfig::default());
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].location, "[display]");
+        assert_eq!(warnings[0].line, Some(4));
+        assert!(warnings[0].message.starts_with("max_alerts: invalid type"), "{}", warnings[0].message);
+        assert!(warnings[0].to_string().starts_with("line 4, [display]: "));
+
+        // Unknown keys also stay within their section; unknown sections are ignored
+        let (config, warnings) =
+            Config::parse_lenient("[general]\nrefresh_interval_ms = 5\nrefresh = 5\n[display]\ntheme = \"light\"\n[lsts]\nallow = []\n");
+        assert_eq!(config.general.refresh_interval_ms, 1000);
+        assert_eq!(config.display.theme, Theme::Light);
+        let locations: Vec<(&str, Option<usize>)> = warnings.iter().map(|w| (w.location.as_str(), w.line)).collect();
+        assert_eq!(locations, vec![("[general]", Some(1)), ("[lsts]", Some(6))]);
+        assert!(warnings[0].message.starts_with("refresh: unknown field `refresh`"), "{}", warnings[0].message);
+        assert!(warnings[1].message.contains("unknown section"), "{}", warnings[1].message);
+
+        // A file that isn't TOML at all keeps every default
+        let (config, warnings) = Config::parse_lenient("[general]\nrefresh_interval_ms = = 5\n[display]\ntheme = \"light\"\n");
+        assert_eq!(config, Config::default());
+        let locations: Vec<(&str, Option<usize>)> = warnings.iter().map(|w| (w.location.as_str(), w.line)).collect();
+        assert_eq!(locations, vec![("config file", Some(2))]);
+
+        // Values the frontends would refuse only reset that key
+        let (config, warnings) = Config::parse_lenient("[display]\ntheme = \"light\"\ntemperature_unit = \"kelvin\"\n");
+        assert_eq!(config.display.theme, Theme::Light);
+        assert_eq!(config.display.temperature_unit, None);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].location, "display.temperature_unit");
+
+        // A bad rule is skipped, a broken rules file keeps the built-in rules
+        let rules = r#"[
+        {"name": "Busy", "description": "CPU", "condition": {"CpuUsageAbove": {"threshold": 90.0, "duration_secs": 30}}, "severity": "Warning"},
+        {"name": "Typo", "description": "CPU", "condition": {"CpuUsageAbov": {"threshold": 90.0}}, "severity": "Warning"}
+    ]"#;
+        let (loaded, warnings) = parse_rules(rules);
+        assert_eq!(loaded.unwrap().iter().map(|rule| rule.name.as_str()).collect::<Vec<_>>(), vec!["Busy"]);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].location, "rule 2 ('Typo')");
+        let (loaded, warnings) = parse_rules("[\n  {\"name\": \"Busy\",,\n]");
+        assert!(loaded.is_none());
+        assert_eq!(warnings[0].line, Some(2));
+    }
+
+    #[test]
+    fn test_process_limits_parsed_into_soft_and_hard() {
+        use crate::limits::{limit_rows, parse_limits, user_thread_count, LimitValue};
+
+        let limits = "\
+    Limit                     Soft Limit           Hard Limit           Units     
+Max cpu time              unlimited            unlimited            seconds   
+Max processes             63304                63304                processes 
+Max open files            1024                 524288               files     
+Max address space         unlimited            unlimited            bytes     
+Max realtime timeout      unlimited            unlimited            us        
+";
+        let parsed = parse_limits(limits);
+        assert_eq!(
+            parsed,
+            vec![
+                ("Max cpu time".to_string(), LimitValue::Unlimited, LimitValue::Unlimited),
+                ("Max processes".to_string(), LimitValue::Value(63304), LimitValue::Value(63304)),
+                ("Max open files".to_string(), LimitValue::Value(1024), LimitValue::Value(524288)),
+                ("Max address space".to_string(), LimitValue::Unlimited, LimitValue::Unlimited),
+                ("Max realtime timeout".to_string(), LimitValue::Unlimited, LimitValue::Unlimited),
+            ]
+        );
+        assert!(parse_limits("").is_empty());
+
+        // "Max processes" counts the threads of all the user's processes, not just this one's
+        let mut process = synthetic_process(1234, "server", 0.0, 0);
+        process.stats.num_threads = 12;
+        let mut sibling = synthetic_process(1300, "worker", 0.0, 0);
+        sibling.stats.num_threads = 30;
+        let mut other_user = synthetic_process(1400, "postgres", 0.0, 0);
+        other_user.info.uid = process.info.uid + 1;
+        other_user.stats.num_threads = 500;
+        let user_threads = user_thread_count(&[process.clone(), sibling, other_user], process.info.uid);
+        assert_eq!(user_threads, 42);
+
+        // 950 of 1024 open files is past the 90% mark; unlimited and unmeasured limits never are
+        let rows = limit_rows(parsed, &process, Some(950), user_threads);
+        let near: Vec<&str> = rows.iter().filter(|row| row.near_limit()).map(|row| row.name.as_str()).collect();
+        assert_eq!(near, vec!["Max open files"]);
+        assert_eq!(rows[1].used, Some(42));
+        assert_eq!(rows[0].used, None);
+    }
+
+    #[test]
+    fn test_du_output_parsed_into_sizes() {
+        use crate::partition::parse_du_output;
+        use std::path::{Path, PathBuf};
+
+        let output = "4096\t/home/alice/.cache\n\
+                      52428800\t/home/alice/videos\n\
+                      8192\t/home/alice/dir with spaces\n\
+                      1048576\t/home/alice/src\n\
+                      52441088\t/home/alice/other\n\
+                      not-a-size\t/home/alice/broken\n\
+                      158834688\t/home/alice\n";
+        let entries = parse_du_output(output, Path::new("/home/alice"));
+        assert_eq!(
+            entries,
+            vec![
+                (PathBuf::from("/home/alice/other"), 52441088),
+                (PathBuf::from("/home/alice/videos"), 52428800),
+                (PathBuf::from("/home/alice/src"), 1048576),
+                (PathBuf::from("/home/alice/dir with spaces"), 8192),
+                (PathBuf::from("/home/alice/.cache"), 4096),
+            ]
+        );
+        assert!(parse_du_output("", Path::new("/")).is_empty());
+    }
+
+    #[test]
+    fn test_directory_scan_reports_sizes_and_is_killed_when_dropped() {
+        use crate::partition::PartitionManager;
+
+        let own_du = || {
+            let own_pid = std::process::id().to_string();
+            fs::read_dir("/proc")
+                .unwrap()
+                .flatten()
+                .filter_map(|entry| fs::read_to_string(entry.path().join("stat")).ok())
+                .any(|stat| {
+                    let fields: Vec<&str> = stat.rsplit_once(") ").map_or(vec![], |(_, rest)| rest.split(' ').collect());
+                    stat.contains("(du)") && fields.get(1) == Some(&own_pid.as_str())
+                })
+        };
+
+        let dir = std::env::temp_dir().join(format!("procmon-du-test-{}", std::process::id()));
+        fs::create_dir_all(dir.join("big")).unwrap();
+        fs::create_dir_all(dir.join("small")).unwrap();
+        fs::write(dir.join("big/data"), vec![1u8; 256 * 1024]).unwrap();
+
+        let manager = PartitionManager::new();
+        let mut scan = manager.scan_directories(&dir, 1).unwrap();
+        let entries = wait_for(|| scan.poll()).unwrap().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].0, dir.join("big"));
+        assert!(entries[0].1 >= 256 * 1024);
+
+        // Closing the drill-down mid-scan leaves no du behind, running or zombie
+        let scan = manager.scan_directories(std::path::Path::new("/"), 64).unwrap();
+        assert!(own_du());
+        drop(scan);
+        assert!(!own_du());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_container_detection_heuristic() {
+        use crate::container::is_containerized;
+
+        let docker_v1 = "12:memory:/docker/3f2a9c5e8b7d6a1f0e4c3b2a19f8e7d6c5b4a39281706f5e4d3c2b1a09f8e7d6\n\
+                         1:name=systemd:/docker/3f2a9c5e8b7d6a1f0e4c3b2a19f8e7d6c5b4a39281706f5e4d3c2b1a09f8e7d6\n";
+        assert!(is_containerized(false, docker_v1));
+        assert!(is_containerized(false, "0::/kubepods/besteffort/pod1234/abc\n"));
+        assert!(is_containerized(false, "0::/lxc.payload.web\n"));
+
+        // A host process, and a container with a cgroup namespace that only a marker file gives away
+        assert!(!is_containerized(false, "0::/user.slice/user-1000.slice/session-2.scope\n"));
+        assert!(!is_containerized(false, "0::/\n"));
+        assert!(is_containerized(true, "0::/\n"));
+        assert!(!is_containerized(false, ""));
+    }
+
+    #[test]
+    fn test_unreadable_sysfs_gpu_values_are_unavailable_not_zero() {
+        use crate::monitor::read_drm_gpu;
+
+        // A card whose driver exposes VRAM size and temperature but not usage, as a container's
+        // partial sysfs might
+        let device = std::env::temp_dir().join(format!("procmon-drm-test-{}", std::process::id()));
+        fs::create_dir_all(device.join("hwmon/hwmon1")).unwrap();
+        fs::write(device.join("product_name"), "Radeon RX 7600\n").unwrap();
+        fs::write(device.join("mem_info_vram_total"), "8573157376\n").unwrap();
+        fs::write(device.join("mem_info_vram_used"), "garbage\n").unwrap();
+        fs::write(device.join("hwmon/hwmon1/temp1_input"), "54000\n").unwrap();
+
+        let gpu = read_drm_gpu(&device);
+        assert_eq!(gpu.name, "Radeon RX 7600");
+        assert_eq!(gpu.usage, None);
+        assert_eq!(gpu.memory_used, None);
+        assert_eq!(gpu.memory_total, Some(8573157376));
+        assert_eq!(gpu.temperature, Some(54.0));
+
+        fs::write(device.join("gpu_busy_percent"), "0\n").unwrap();
+        assert_eq!(read_drm_gpu(&device).usage, Some(0.0), "a real 0% is still shown");
+
+        fs::remove_dir_all(&device).unwrap();
+        let missing = read_drm_gpu(&device);
+        assert_eq!((missing.usage, missing.memory_total, missing.temperature), (None, None, None));
+    }
+
+    #[test]
+    fn test_threads_enumerated_for_multithreaded_process() {
+        use crate::columns::ProcessColumn;
+        use crate::process::ProcessStatus;
+        use crate::threads::{list_thread_ids, read_threads};
+        use std::collections::HashMap;
+        use std::sync::{mpsc, Arc, Barrier};
+
+        // Park three extra threads so this process has at least four tasks
+        let (release, parked) = mpsc::channel::<()>();
+        let parked = Arc::new(std::sync::Mutex::new(parked));
+        let started = Arc::new(Barrier::new(4));
+        let workers: Vec<_> = (0..3)
+            .map(|_| {
+                let (started, parked) = (started.clone(), parked.clone());
+                std::thread::spawn(move || {
+                    started.wait();
+                    let _ = parked.lock().unwrap().recv();
+                })
+            })
+            .collect();
+        started.wait();
+
+        let pid = std::process::id();
+        let tids = list_thread_ids(std::path::Path::new(&format!("/proc/{}/task", pid)));
+        // The main thread's id is the PID
+        assert!(tids.contains(&pid));
+        assert!(tids.len() >= 4, "only {} threads", tids.len());
+        assert!(tids.windows(2).all(|pair| pair[0] < pair[1]));
+
+        // Ranked by the measured usage, busiest first and then by thread id; only the listed
+        // threads are read
+        let (busy, quiet) = (tids[2], tids[1]);
+        let usage: HashMap<u32, (f32, String)> =
+            HashMap::from([(busy, (95.5, "busy".to_string())), (quiet, (5.0, "quiet".to_string()))]);
+        let threads = read_threads(pid, &usage, 3);
+        assert_eq!(threads.items.iter().map(|t| t.tid).collect::<Vec<_>>(), vec![busy, quiet, pid]);
+        assert_eq!(threads.total, tids.len());
+        assert!(threads.items.iter().all(|t| t.status != ProcessStatus::Unknown));
+        assert_eq!(ProcessColumn::Pid.thread_cell(&threads.items[0]), format!("└ {}", busy));
+        assert_eq!(ProcessColumn::Cpu.thread_cell(&threads.items[0]), "95.5%");
+        assert_eq!(ProcessColumn::Mem.thread_cell(&threads.items[0]), "");
+        let capped = read_threads(pid, &usage, 1);
+        assert_eq!(capped.items.len(), 1);
+        assert!(capped.is_truncated());
+
+        drop(release);
+        for worker in workers {
+            worker.join().unwrap();
+        }
+        assert!(list_thread_ids(std::path::Path::new("/proc/does-not-exist/task")).is_empty());
+        assert_eq!(read_threads(u32::MAX, &usage, 3).total, 0);
+    }
+
+    #[test]
+    fn test_open_fd_listing_resolves_only_the_cap() {
+        use crate::fds::list_open_fds;
+
+        // stdin, stdout and stderr at least
+        let fds = list_open_fds(std::process::id(), 1);
+        assert_eq!(fds.items.len(), 1);
+        assert!(fds.total > 1, "only {} descriptors", fds.total);
+        assert!(fds.is_truncated());
+        assert_eq!(fds.items[0].0, 0);
+    }
+
+    #[test]
+    fn test_interface_addresses_classified() {
+        use crate::metrics::{format_address, sort_addresses, AddressScope};
+        use std::net::IpAddr;
+
+        let mut addresses: Vec<IpAddr> = ["fe80::a00:27ff:fe4e:66a1", "192.168.1.20", "2001:db8::5", "169.254.10.2", "::1", "127.0.0.1"]
+            .iter()
+            .map(|text| text.parse().unwrap())
+            .collect();
+        sort_addresses(&mut addresses);
+        let formatted: Vec<String> = addresses.iter().map(format_address).collect();
+        assert_eq!(
+            formatted,
+            vec![
+                "127.0.0.1 (loopback)",
+                "169.254.10.2 (link-local)",
+                "192.168.1.20",
+                "::1 (loopback)",
+                "2001:db8::5",
+                "fe80::a00:27ff:fe4e:66a1 (link-local)",
+            ]
+        );
+
+        let scope = |text: &str| AddressScope::of(&text.parse().unwrap());
+        assert_eq!(scope("10.0.0.1"), AddressScope::Global);
+        assert_eq!(scope("febf::1"), AddressScope::LinkLocal);
+        // fec0::/10 is the old site-local range, not link-local
+        assert_eq!(scope("fec0::1"), AddressScope::Global);
+        assert_eq!(scope("127.8.8.8"), AddressScope::Loopback);
+    }
+
+    #[test]
+    fn test_capped_list_loads_only_the_first_entries() {
+        use crate::util::Capped;
+
+        let mut loaded = 0;
+        let fds = Capped::collect(0..100_000u32, 500, |fd| {
+            loaded += 1;
+            Some((fd, format!("socket:[{}]", fd)))
+        });
+        assert_eq!(loaded, 500);
+        assert_eq!(fds.items.len(), 500);
+        assert_eq!(fds.total, 100_000);
+        assert!(fds.is_truncated());
+        assert_eq!(fds.summary(), "showing 500 of 100000");
+
+        // Entries that vanish while loading are not counted
+        let threads = Capped::collect(0..4u32, 10, |tid| (tid != 2).then_some(tid));
+        assert_eq!(threads.items, vec![0, 1, 3]);
+        assert_eq!(threads.total, 3);
+        assert!(!threads.is_truncated());
+    }
+
+    #[test]
+    fn test_reused_pid_starts_with_fresh_violation_history() {
+        use crate::detector::{MisbehaviorCondition, MisbehaviorDetector, MisbehaviorRule, Severity};
+
+        let rule = MisbehaviorRule {
+            name: "High CPU".to_string(),
+            description: "Sustained CPU usage".to_string(),
+            condition: MisbehaviorCondition::CpuUsageAbove { threshold: 90.0, duration_secs: 1 },
+            severity: Severity::Warning,
+        };
+        let mut detector = MisbehaviorDetector::with_rules(vec![rule]);
+
+        let mut original = synthetic_process(4242, "encoder", 99.0, 0);
+        original.stats.start_time -= chrono::Duration::minutes(10);
+        assert!(detector.check_process(&original).is_empty());
+        std::thread::sleep(std::time::Duration::from_millis(1100));
+        assert_eq!(detector.check_process(&original).len(), 1);
+
+        // The encoder exited and an unrelated busy process got its PID
+        let reused = synthetic_process(4242, "compiler", 99.0, 0);
+        assert_ne!(reused.key(), original.key());
+        assert!(detector.check_process(&reused).is_empty());
+        assert!(detector.check_process(&reused).is_empty());
+    }
 }
@@ -0,0 +1,54 @@
+use crate::process::ProcessStatus;
+use crate::util::{stat_field, Capped};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// One thread of a process, listed under it when threads are shown
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThreadInfo {
+    pub tid: u32,
+    /// From `/proc/{pid}/task/{tid}/comm`; threads can be named apart from their process
+    pub name: String,
+    /// CPU usage of this thread alone, so at most 100% where its process can exceed that
+    pub cpu_usage: f32,
+    pub status: ProcessStatus,
+}
+
+/// Thread ids in a `/proc/{pid}/task` directory, ascending; empty when it can't be read
+pub fn list_thread_ids(task_dir: &Path) -> Vec<u32> {
+    let Ok(entries) = fs::read_dir(task_dir) else {
+        return Vec::new();
+    };
+    let mut tids: Vec<u32> = entries
+        .flatten()
+        .filter_map(|entry| entry.file_name().to_str()?.parse().ok())
+        .collect();
+    tids.sort_unstable();
+    tids
+}
+
+/// Threads of `pid`, the `max_entries` busiest first. `usage` holds each thread's CPU usage and
+/// name as last measured (threads missing from it count as idle), so every thread is ranked
+/// without touching `/proc`; only the listed threads' `comm` and `stat` are read. This still
+/// lists `/proc/{pid}/task`, so the frontends call it on a background thread.
+pub fn read_threads(pid: u32, usage: &HashMap<u32, (f32, String)>, max_entries: usize) -> Capped<ThreadInfo> {
+    let task_dir = PathBuf::from(format!("/proc/{}/task", pid));
+    let cpu_usage = |tid: &u32| usage.get(tid).map_or(0.0, |(cpu, _)| *cpu);
+    let mut tids = list_thread_ids(&task_dir);
+    // Busiest thread first; ties keep thread id order, so the main thread leads an idle process
+    tids.sort_by(|a, b| cpu_usage(b).total_cmp(&cpu_usage(a)).then_with(|| a.cmp(b)));
+    Capped::collect(tids, max_entries, |tid| {
+        let read = |file: &str| fs::read_to_string(task_dir.join(tid.to_string()).join(file)).ok();
+        // Gone since the listing
+        let stat = read("stat")?;
+        let name = read("comm")
+            .map(|comm| comm.trim_end().to_string())
+            .or_else(|| usage.get(&tid).map(|(_, name)| name.clone()))
+            .unwrap_or_default();
+        let status = stat_field(&stat, 3)
+            .and_then(|state| state.chars().next())
+            .map_or(ProcessStatus::Unknown, ProcessStatus::from_stat_char);
+        Some(ThreadInfo { tid, name, cpu_usage: cpu_usage(&tid), status })
+    })
+}
@@ -0,0 +1,286 @@
+use crate::privilege::is_root;
+use anyhow::{bail, Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Mount point of the unified (v2) cgroup hierarchy
+pub const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+
+const BYTES_PER_MB: u64 = 1024 * 1024;
+
+/// Name prefix of the cgroups throttled processes are moved into
+const THROTTLE_PREFIX: &str = "procmon-throttle-";
+
+/// Path of the v2 cgroup a process is in, relative to the hierarchy root, from the `0::` line
+/// of `/proc/{pid}/cgroup`
+pub fn parse_cgroup_v2_path(cgroup: &str) -> Option<&str> {
+    cgroup.lines().find_map(|line| line.strip_prefix("0::"))
+}
+
+/// Cgroup a process is moved into to throttle it: a child of the cgroup `current` it is in now,
+/// so it stays within its service's or session's accounting. Named by PID and start time (in
+/// clock ticks) so a reused PID never lands in a stale group. A process that is already
+/// throttled keeps its group.
+pub fn throttle_cgroup_path(root: &Path, current: &str, pid: u32, start_ticks: u64) -> PathBuf {
+    let current = root.join(current.trim_start_matches('/'));
+    if is_throttle_cgroup(&current) {
+        return current;
+    }
+    current.join(format!("{}{}-{}", THROTTLE_PREFIX, pid, start_ticks))
+}
+
+/// Whether `cgroup` is one procmon created for a throttled process
+pub fn is_throttle_cgroup(cgroup: &Path) -> bool {
+    cgroup.file_name().is_some_and(|name| name.to_string_lossy().starts_with(THROTTLE_PREFIX))
+}
+
+/// One `io.max` line for the block device `major:minor`; `None` leaves that direction unlimited.
+pub fn io_max_line(device: &str, read_bps: Option<u64>, write_bps: Option<u64>) -> String {
+    let limit = |bps: Option<u64>| bps.map_or_else(|| "max".to_string(), |bps| bps.to_string());
+    format!("{} rbps={} wbps={}", device, limit(read_bps), limit(write_bps))
+}
+
+/// `major:minor` numbers of the whole-disk block devices listed in `/sys/block`.
+///
+/// Loop and RAM devices are skipped since throttling them is never what the user means.
+pub fn block_device_numbers() -> Vec<String> {
+    let Ok(entries) = fs::read_dir("/sys/block") else {
+        return Vec::new();
+    };
+
+    let mut devices: Vec<String> = entries
+        .flatten()
+        .filter(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            !name.starts_with("loop") && !name.starts_with("ram")
+        })
+        .filter_map(|entry| fs::read_to_string(entry.path().join("dev")).ok())
+        .map(|dev| dev.trim().to_string())
+        .filter(|dev| !dev.is_empty())
+        .collect();
+    devices.sort();
+    devices
+}
+
+/// Parse the "<read> [write]" MB/s limits typed in the throttle prompt.
+///
+/// A single value applies to both directions and 0 means unlimited.
+pub fn parse_throttle_input(input: &str) -> Result<(Option<u64>, Option<u64>)> {
+    let values: Vec<&str> = input.split_whitespace().collect();
+    let parse = |text: &str| -> Result<Option<u64>> {
+        let mb: u64 = text.parse().with_context(|| format!("Invalid MB/s value '{}'", text))?;
+        Ok((mb > 0).then(|| mb * BYTES_PER_MB))
+    };
+
+    match values.as_slice() {
+        [both] => {
+            let limit = parse(both)?;
+            Ok((limit, limit))
+        }
+        [read, write] => Ok((parse(read)?, parse(write)?)),
+        _ => bail!("Enter one or two MB/s values"),
+    }
+}
+
+/// A cgroup procmon created to throttle a process
+#[derive(Debug)]
+struct ThrottleGroup {
+    path: PathBuf,
+    /// The io controller was enabled on the parent for this group, and is disabled again
+    /// once the parent has no throttled children left
+    enabled_io: bool,
+}
+
+/// The cgroups procmon created for throttled processes, so they can be taken down again once
+/// their processes exit or the throttle is lifted.
+///
+/// cgroup v2 only lets a cgroup without processes of its own delegate the io controller, so a
+/// process can be throttled when it is the only one in its cgroup (a single-process service or
+/// application scope). The io controller is enabled there while the throttle group exists;
+/// [`ThrottleGroups::cleanup`] disables it once the group is gone, so the unit can start
+/// processes in its own cgroup again.
+#[derive(Debug, Default)]
+pub struct ThrottleGroups {
+    groups: Vec<ThrottleGroup>,
+}
+
+impl ThrottleGroups {
+    /// Cap the disk bandwidth of `pid` on every block device by moving it into a child cgroup
+    /// (see [`throttle_cgroup_path`]) with `io.max` set. Returns the cgroup path.
+    pub fn apply(&mut self, root: &Path, pid: u32, read_bps: Option<u64>, write_bps: Option<u64>) -> Result<PathBuf> {
+        if !is_root() {
+            bail!("I/O throttling requires root privileges");
+        }
+        if read_bps.is_none() && write_bps.is_none() {
+            bail!("No I/O limit given");
+        }
+        let (current, start_ticks) = read_process_cgroup(pid)?;
+        let cgroup = throttle_cgroup_path(root, &current, pid, start_ticks);
+        let devices = block_device_numbers();
+        if devices.is_empty() {
+            bail!("No block devices found to throttle");
+        }
+
+        let created = !cgroup.exists();
+        if created {
+            let parent = cgroup.parent().context("Throttle cgroup has no parent")?.to_path_buf();
+            let enabled_io = enter_throttle_cgroup(&parent, &cgroup, pid)?;
+            self.groups.push(ThrottleGroup { path: cgroup.clone(), enabled_io });
+        }
+
+        if let Err(e) = write_io_max(&cgroup, &devices, read_bps, write_bps) {
+            if created {
+                self.release(&cgroup);
+            }
+            return Err(e);
+        }
+        Ok(cgroup)
+    }
+
+    /// Lift the throttle on `pid`: its limits are reset to unlimited right away, and its group
+    /// is dissolved, moving its processes back to the parent, when no other throttled
+    /// process shares that parent.
+    pub fn remove(&mut self, root: &Path, pid: u32) -> Result<()> {
+        let (current, _) = read_process_cgroup(pid)?;
+        let cgroup = root.join(current.trim_start_matches('/'));
+        if !is_throttle_cgroup(&cgroup) {
+            bail!("PID {} is not throttled", pid);
+        }
+        write_io_max(&cgroup, &block_device_numbers(), None, None)?;
+        self.release(&cgroup);
+        Ok(())
+    }
+
+    /// Remove groups whose processes have all exited, and disable the io controller on
+    /// parents that no longer have a throttled child
+    pub fn cleanup(&mut self) {
+        self.groups.retain(|group| {
+            if !group.path.exists() {
+                return false;
+            }
+            let empty = fs::read_to_string(group.path.join("cgroup.procs")).is_ok_and(|procs| procs.trim().is_empty());
+            !(empty && remove_throttle_group(group))
+        });
+    }
+
+    /// Move the processes of the group at `cgroup` back to its parent and remove it, if no
+    /// sibling throttle group still needs the parent's io controller
+    fn release(&mut self, cgroup: &Path) {
+        let Some(index) = self.groups.iter().position(|group| group.path == cgroup) else {
+            return;
+        };
+        let group = &self.groups[index];
+        let Some(parent) = group.path.parent() else {
+            return;
+        };
+        if group.enabled_io && self.groups.iter().any(|other| other.path != group.path && other.path.parent() == Some(parent)) {
+            tracing::debug!("Keeping {} while sibling throttle groups exist", group.path.display());
+            return;
+        }
+        // The parent can only take processes back once it no longer delegates io
+        if group.enabled_io {
+            if let Err(e) = fs::write(parent.join("cgroup.subtree_control"), "-io") {
+                tracing::debug!("Failed to disable io on {}: {}", parent.display(), e);
+                return;
+            }
+        }
+        let procs = fs::read_to_string(group.path.join("cgroup.procs")).unwrap_or_default();
+        for pid in procs.lines() {
+            if let Err(e) = fs::write(parent.join("cgroup.procs"), pid) {
+                tracing::debug!("Failed to move PID {} out of {}: {}", pid, group.path.display(), e);
+            }
+        }
+        if remove_throttle_group(&ThrottleGroup { path: group.path.clone(), enabled_io: false }) {
+            self.groups.remove(index);
+        }
+    }
+}
+
+/// Current cgroup (relative to the hierarchy root) and start time in clock ticks of `pid`
+fn read_process_cgroup(pid: u32) -> Result<(String, u64)> {
+    let cgroup = fs::read_to_string(format!("/proc/{}/cgroup", pid)).with_context(|| format!("Process {} not found", pid))?;
+    let current = parse_cgroup_v2_path(&cgroup)
+        .with_context(|| format!("Process {} is not in a cgroup v2 hierarchy", pid))?
+        .to_string();
+    let stat = fs::read_to_string(format!("/proc/{}/stat", pid)).with_context(|| format!("Process {} not found", pid))?;
+    let start_ticks = crate::util::stat_field(&stat, 22)
+        .and_then(|ticks| ticks.parse().ok())
+        .with_context(|| format!("Failed to read the start time of PID {}", pid))?;
+    Ok((current, start_ticks))
+}
+
+/// Create `cgroup` under `parent`, move `pid` into it and make sure `parent` delegates the io
+/// controller. Returns whether io had to be enabled on `parent`. Undone if any step fails.
+fn enter_throttle_cgroup(parent: &Path, cgroup: &Path, pid: u32) -> Result<bool> {
+    let available = fs::read_to_string(parent.join("cgroup.controllers"))
+        .with_context(|| format!("{} is not a cgroup v2 group", parent.display()))?;
+    if !available.split_whitespace().any(|controller| controller == "io") {
+        bail!(
+            "The io controller is not available in {}; enable IO accounting for its unit first \
+             (systemctl set-property <unit> IOAccounting=yes)",
+            parent.display()
+        );
+    }
+    let subtree_control = parent.join("cgroup.subtree_control");
+    let enabled = fs::read_to_string(&subtree_control).unwrap_or_default();
+    let needs_io = !enabled.split_whitespace().any(|controller| controller == "io");
+
+    fs::create_dir(cgroup).with_context(|| format!("Failed to create {}", cgroup.display()))?;
+    let undo = |error: anyhow::Error| {
+        let _ = fs::write(parent.join("cgroup.procs"), pid.to_string());
+        let _ = fs::remove_dir(cgroup);
+        error
+    };
+    fs::write(cgroup.join("cgroup.procs"), pid.to_string())
+        .with_context(|| format!("Failed to move PID {} into {}", pid, cgroup.display()))
+        .map_err(undo)?;
+    if needs_io {
+        fs::write(&subtree_control, "+io")
+            .with_context(|| {
+                format!(
+                    "Failed to enable the io controller in {}; other processes share that cgroup, so only the \
+                     whole unit can be limited",
+                    parent.display()
+                )
+            })
+            .map_err(undo)?;
+    }
+    Ok(needs_io)
+}
+
+/// Write one `io.max` line per device. Some devices (optical drives, certain device-mapper
+/// targets) reject io.max; it is enough that the disks which accept it are capped.
+fn write_io_max(cgroup: &Path, devices: &[String], read_bps: Option<u64>, write_bps: Option<u64>) -> Result<()> {
+    let mut last_error = None;
+    let mut applied = 0;
+    for device in devices {
+        match fs::write(cgroup.join("io.max"), io_max_line(device, read_bps, write_bps)) {
+            Ok(()) => applied += 1,
+            Err(e) => last_error = Some(format!("device {}: {}", device, e)),
+        }
+    }
+    if applied == 0 {
+        bail!("Failed to set io.max ({})", last_error.unwrap_or_default());
+    }
+    Ok(())
+}
+
+/// Remove an empty throttle group, then stop delegating io on its parent if procmon enabled
+/// it and no other group below the parent is left. Returns whether the group is gone.
+fn remove_throttle_group(group: &ThrottleGroup) -> bool {
+    if let Err(e) = fs::remove_dir(&group.path) {
+        tracing::debug!("Failed to remove {}: {}", group.path.display(), e);
+        return false;
+    }
+    if let Some(parent) = group.path.parent().filter(|_| group.enabled_io) {
+        let has_children = fs::read_dir(parent)
+            .map(|entries| entries.flatten().any(|entry| entry.path().is_dir()))
+            .unwrap_or(true);
+        if !has_children {
+            if let Err(e) = fs::write(parent.join("cgroup.subtree_control"), "-io") {
+                tracing::debug!("Failed to disable io on {}: {}", parent.display(), e);
+            }
+        }
+    }
+    true
+}
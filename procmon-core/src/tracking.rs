@@ -0,0 +1,49 @@
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+/// Remembers when each PID was first seen so recently started processes can be flagged.
+pub struct NewProcessTracker {
+    // None for processes that were already running when tracking started
+    first_seen: HashMap<u32, Option<Instant>>,
+    window: Duration,
+    initialized: bool,
+}
+
+impl NewProcessTracker {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            first_seen: HashMap::new(),
+            window,
+            initialized: false,
+        }
+    }
+
+    /// Record the PIDs from the latest refresh.
+    ///
+    /// Processes present in the very first snapshot were already running when the
+    /// monitor started, so they are never reported as new.
+    pub fn update(&mut self, pids: &[u32], now: Instant) {
+        let seen = if self.initialized { Some(now) } else { None };
+        for pid in pids {
+            self.first_seen.entry(*pid).or_insert(seen);
+        }
+
+        let current: HashSet<u32> = pids.iter().copied().collect();
+        self.first_seen.retain(|pid, _| current.contains(pid));
+        self.initialized = true;
+    }
+
+    /// Whether `pid` appeared within the tracking window.
+    pub fn is_new(&self, pid: u32, now: Instant) -> bool {
+        match self.first_seen.get(&pid) {
+            Some(Some(seen)) => now.duration_since(*seen) < self.window,
+            _ => false,
+        }
+    }
+}
+
+impl Default for NewProcessTracker {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(5))
+    }
+}
@@ -0,0 +1,38 @@
+use crate::process::ProcessSnapshot;
+
+/// Resource totals of the processes of one user
+#[derive(Debug, Clone, PartialEq)]
+pub struct UserGroup {
+    pub user: String,
+    pub uid: u32,
+    pub processes: usize,
+    pub cpu_usage: f32,
+    pub memory_usage: u64,
+}
+
+/// Sum processes per uid, busiest user first.
+pub fn group_by_user(processes: &[ProcessSnapshot]) -> Vec<UserGroup> {
+    let mut groups: Vec<UserGroup> = Vec::new();
+    for process in processes {
+        let index = match groups.iter().position(|group| group.uid == process.info.uid) {
+            Some(index) => index,
+            None => {
+                groups.push(UserGroup {
+                    user: process.info.user.clone(),
+                    uid: process.info.uid,
+                    processes: 0,
+                    cpu_usage: 0.0,
+                    memory_usage: 0,
+                });
+                groups.len() - 1
+            }
+        };
+        let group = &mut groups[index];
+        group.processes += 1;
+        group.cpu_usage += process.stats.cpu_usage;
+        group.memory_usage += process.stats.memory_usage;
+    }
+
+    groups.sort_by(|a, b| b.cpu_usage.total_cmp(&a.cpu_usage).then_with(|| a.uid.cmp(&b.uid)));
+    groups
+}
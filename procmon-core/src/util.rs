@@ -0,0 +1,459 @@
+/// Difference between two readings of a cumulative counter that wraps after `max`.
+///
+/// The counter is assumed to count through `0..=max` and then start again at 0, so a
+/// current value below the previous one is treated as a single wrap. A previous value
+/// above `max` can only come from a counter reset (e.g. a re-plugged device), in which
+/// case the current value is the best available delta.
+pub fn delta_with_wrap(prev: u64, cur: u64, max: u64) -> u64 {
+    if cur >= prev {
+        cur - prev
+    } else if prev > max {
+        cur
+    } else {
+        // (max - prev) counts up to the wrap point, +1 for the step from max to 0
+        (max - prev).saturating_add(cur).saturating_add(1)
+    }
+}
+
+/// Best guess at the width of a counter whose exact size is not known.
+///
+/// Some kernel interfaces (network drivers in particular) still expose 32-bit counters.
+/// A counter that has never exceeded `u32::MAX` is treated as 32-bit so that its wrap
+/// yields a small delta instead of one close to `u64::MAX`.
+pub fn infer_counter_max(prev: u64) -> u64 {
+    if prev <= u32::MAX as u64 {
+        u32::MAX as u64
+    } else {
+        u64::MAX
+    }
+}
+
+/// Difference between two readings of a cumulative counter of unknown width, such as network
+/// and diskstats counters: [`delta_with_wrap`] at the width [`infer_counter_max`] guesses.
+pub fn counter_delta(prev: u64, cur: u64) -> u64 {
+    delta_with_wrap(prev, cur, infer_counter_max(prev))
+}
+
+/// Convert a counter delta into a per-second rate.
+pub fn rate_per_sec(delta: u64, elapsed: std::time::Duration) -> u64 {
+    let secs = elapsed.as_secs_f64();
+    if secs <= 0.0 {
+        return 0;
+    }
+    (delta as f64 / secs) as u64
+}
+
+/// Whether the data on screen is current.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Staleness {
+    Live,
+    /// No successful refresh for more than twice the interval, e.g. a hung external command
+    Stale,
+}
+
+/// Decide staleness from the time since the last successful refresh.
+pub fn staleness(since_last_refresh: std::time::Duration, interval: std::time::Duration) -> Staleness {
+    if since_last_refresh > interval * 2 {
+        Staleness::Stale
+    } else {
+        Staleness::Live
+    }
+}
+
+/// When the next refresh is due: every `interval`, or sooner when one is requested.
+///
+/// CPU usage is the difference between two samples, so even a requested refresh waits
+/// until [`RefreshSchedule::MIN_SPACING`] has passed since the last one. A failed refresh
+/// is retried after the interval like any other, while the last update stays where it was.
+#[derive(Debug, Clone)]
+pub struct RefreshSchedule {
+    interval: std::time::Duration,
+    last_update: std::time::Instant,
+    last_attempt: std::time::Instant,
+    /// First failure since the last successful refresh
+    failing_since: Option<std::time::Instant>,
+    requested: bool,
+}
+
+impl RefreshSchedule {
+    /// Shortest gap between two refreshes that still gives meaningful CPU numbers
+    pub const MIN_SPACING: std::time::Duration = sysinfo::MINIMUM_CPU_UPDATE_INTERVAL;
+
+    pub fn new(interval: std::time::Duration, last_update: std::time::Instant) -> Self {
+        Self { interval, last_update, last_attempt: last_update, failing_since: None, requested: false }
+    }
+
+    /// Refresh as soon as the minimum spacing allows instead of waiting for the interval
+    pub fn request_now(&mut self) {
+        self.requested = true;
+    }
+
+    pub fn is_requested(&self) -> bool {
+        self.requested
+    }
+
+    pub fn is_due(&self, now: std::time::Instant) -> bool {
+        let since = now.saturating_duration_since(self.last_attempt);
+        since >= self.interval || (self.requested && since >= Self::MIN_SPACING)
+    }
+
+    /// Record a refresh at `now`, clearing any pending request
+    pub fn mark_refreshed(&mut self, now: std::time::Instant) {
+        self.last_update = now;
+        self.last_attempt = now;
+        self.failing_since = None;
+        self.requested = false;
+    }
+
+    /// Record a refresh at `now` that failed; the data from the last update stays on screen
+    pub fn mark_failed(&mut self, now: std::time::Instant) {
+        self.last_attempt = now;
+        self.failing_since.get_or_insert(now);
+        self.requested = false;
+    }
+
+    /// Record the outcome of a refresh made at `now`, passing on the value if it succeeded
+    pub fn record<T>(&mut self, result: anyhow::Result<T>, now: std::time::Instant) -> Option<T> {
+        match result {
+            Ok(value) => {
+                self.mark_refreshed(now);
+                Some(value)
+            }
+            Err(e) => {
+                if self.failing_since.is_none() {
+                    tracing::warn!("Refresh failed, keeping the last data: {:#}", e);
+                }
+                self.mark_failed(now);
+                None
+            }
+        }
+    }
+
+    /// Time of the last successful refresh
+    pub fn last_update(&self) -> std::time::Instant {
+        self.last_update
+    }
+
+    /// When refreshes started failing, if the last one failed
+    pub fn failing_since(&self) -> Option<std::time::Instant> {
+        self.failing_since
+    }
+
+    /// Whether the data on screen is current at `now`: stale as soon as a refresh fails, or
+    /// once refreshes stop happening (see [`staleness`])
+    pub fn staleness(&self, now: std::time::Instant) -> Staleness {
+        if self.failing_since.is_some() {
+            return Staleness::Stale;
+        }
+        staleness(now.saturating_duration_since(self.last_update), self.interval)
+    }
+
+    pub fn interval(&self) -> std::time::Duration {
+        self.interval
+    }
+}
+
+/// Refresh timing for one external command (`systemctl`, `lsblk`, ...) that backs off
+/// while the command keeps failing.
+///
+/// A source is attempted every `interval`. After [`SourceBackoff::FAILURE_THRESHOLD`]
+/// consecutive failures it counts as disabled and the gap doubles with every further
+/// failure, up to [`SourceBackoff::MAX_DELAY`]. One success restores the normal interval.
+#[derive(Debug, Clone)]
+pub struct SourceBackoff {
+    name: &'static str,
+    interval: std::time::Duration,
+    failures: u32,
+    next_attempt: Option<std::time::Instant>,
+}
+
+impl SourceBackoff {
+    pub const FAILURE_THRESHOLD: u32 = 3;
+    pub const MAX_DELAY: std::time::Duration = std::time::Duration::from_secs(300);
+
+    pub fn new(name: &'static str, interval: std::time::Duration) -> Self {
+        Self { name, interval, failures: 0, next_attempt: None }
+    }
+
+    pub fn is_due(&self, now: std::time::Instant) -> bool {
+        self.next_attempt.is_none_or(|next| now >= next)
+    }
+
+    /// Gap until the next attempt after the failures recorded so far
+    pub fn delay(&self) -> std::time::Duration {
+        if self.failures < Self::FAILURE_THRESHOLD {
+            return self.interval;
+        }
+        let doublings = (self.failures - Self::FAILURE_THRESHOLD + 1).min(16);
+        (self.interval * 2u32.pow(doublings)).min(Self::MAX_DELAY)
+    }
+
+    /// Too many consecutive failures; attempts are spaced out until one succeeds
+    pub fn is_disabled(&self) -> bool {
+        self.failures >= Self::FAILURE_THRESHOLD
+    }
+
+    pub fn failures(&self) -> u32 {
+        self.failures
+    }
+
+    pub fn record_success(&mut self, now: std::time::Instant) {
+        if self.is_disabled() {
+            tracing::info!("{} is working again after {} failures", self.name, self.failures);
+        }
+        self.failures = 0;
+        self.next_attempt = Some(now + self.interval);
+    }
+
+    /// Count a failure; only the one that disables the source is logged as a warning
+    pub fn record_failure(&mut self, now: std::time::Instant, error: &dyn std::fmt::Display) {
+        self.failures += 1;
+        if self.failures == Self::FAILURE_THRESHOLD {
+            tracing::warn!("{} failed {} times in a row, backing off: {}", self.name, self.failures, error);
+        } else {
+            tracing::debug!("{} failed: {}", self.name, error);
+        }
+        self.next_attempt = Some(now + self.delay());
+    }
+
+    /// Record the outcome of an attempt made at `now`, passing on the value if it succeeded
+    pub fn record<T>(&mut self, result: anyhow::Result<T>, now: std::time::Instant) -> Option<T> {
+        match result {
+            Ok(value) => {
+                self.record_success(now);
+                Some(value)
+            }
+            Err(e) => {
+                self.record_failure(now, &e);
+                None
+            }
+        }
+    }
+
+    /// "disabled due to repeated failures" notice for the UI, or `None` while the source works
+    pub fn status(&self, now: std::time::Instant) -> Option<String> {
+        if !self.is_disabled() {
+            return None;
+        }
+        let retry = self.next_attempt.map_or(std::time::Duration::ZERO, |next| next.saturating_duration_since(now));
+        Some(format!(
+            "{} disabled due to repeated failures ({} in a row), retrying in {}",
+            self.name,
+            self.failures,
+            format_duration(retry)
+        ))
+    }
+}
+
+/// Run `cmd` and collect its output, giving up after `timeout`.
+///
+/// A command stuck in the kernel, like `df` on a dead network mount, may not even die when
+/// killed, so it is reaped on a background thread instead of waited for here.
+pub fn output_with_timeout(cmd: &mut std::process::Command, timeout: std::time::Duration) -> anyhow::Result<std::process::Output> {
+    use std::io::Read;
+    use std::process::Stdio;
+
+    let mut child = cmd.stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped()).spawn()?;
+    let read_all = |mut pipe: Box<dyn Read + Send>| {
+        std::thread::spawn(move || {
+            let mut buffer = Vec::new();
+            let _ = pipe.read_to_end(&mut buffer);
+            buffer
+        })
+    };
+    let stdout = read_all(Box::new(child.stdout.take().expect("stdout is piped")));
+    let stderr = read_all(Box::new(child.stderr.take().expect("stderr is piped")));
+
+    let deadline = std::time::Instant::now() + timeout;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if std::time::Instant::now() >= deadline {
+            let _ = child.kill();
+            std::thread::spawn(move || child.wait());
+            anyhow::bail!("{:?} timed out after {:?}", cmd.get_program(), timeout);
+        }
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    };
+
+    Ok(std::process::Output {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    })
+}
+
+/// Value of `--flag <value>` or `--flag=<value>` in command line `args`; the last one wins.
+pub fn flag_value<I>(args: I, flag: &str) -> anyhow::Result<Option<String>>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut args = args.into_iter();
+    let prefix = format!("{}=", flag);
+    let mut value = None;
+
+    while let Some(arg) = args.next() {
+        if arg == flag {
+            match args.next() {
+                Some(next) => value = Some(next),
+                None => anyhow::bail!("{} requires a value", flag),
+            }
+        } else if let Some(rest) = arg.strip_prefix(&prefix) {
+            value = Some(rest.to_string());
+        }
+    }
+
+    Ok(value)
+}
+
+/// `$XDG_STATE_HOME/procmon/<file_name>`, falling back to `~/.local/state`
+pub fn state_file(file_name: &str) -> Option<std::path::PathBuf> {
+    let state_dir = std::env::var_os("XDG_STATE_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(std::path::PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| std::path::PathBuf::from(home).join(".local").join("state")))?;
+    Some(state_dir.join("procmon").join(file_name))
+}
+
+/// Field `field` (1-based, as numbered in proc(5)) of a `/proc/{pid}/stat` line.
+///
+/// The command name (field 2) is wrapped in parentheses and may itself contain spaces
+/// or parentheses, so fields after it are counted from the last `)`.
+pub fn stat_field(stat: &str, field: usize) -> Option<&str> {
+    let after_comm = &stat[stat.rfind(')')? + 1..];
+    // The first field after the command name is field 3 (state)
+    after_comm.split_whitespace().nth(field.checked_sub(3)?)
+}
+
+/// Compact human-readable duration, e.g. `42s`, `5m 07s`, `3h 12m`, `2d 04h`.
+pub fn format_duration(duration: std::time::Duration) -> String {
+    let secs = duration.as_secs();
+    let (days, hours, mins, secs) = (secs / 86_400, secs / 3600 % 24, secs / 60 % 60, secs % 60);
+
+    if days > 0 {
+        format!("{}d {:02}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {:02}m", hours, mins)
+    } else if mins > 0 {
+        format!("{}m {:02}s", mins, secs)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+/// Exponential moving average: `value = alpha * sample + (1 - alpha) * value`.
+///
+/// `alpha` is clamped to `0.0..=1.0`; 1.0 disables smoothing. The first sample is taken
+/// as-is so the average does not ramp up from zero.
+#[derive(Debug, Clone, Copy)]
+pub struct Ema {
+    alpha: f64,
+    value: Option<f64>,
+}
+
+impl Ema {
+    pub fn new(alpha: f64) -> Self {
+        Self {
+            alpha: alpha.clamp(0.0, 1.0),
+            value: None,
+        }
+    }
+
+    pub fn update(&mut self, sample: f64) -> f64 {
+        let value = match self.value {
+            Some(prev) => self.alpha * sample + (1.0 - self.alpha) * prev,
+            None => sample,
+        };
+        self.value = Some(value);
+        value
+    }
+
+    pub fn value(&self) -> Option<f64> {
+        self.value
+    }
+
+    pub fn reset(&mut self) {
+        self.value = None;
+    }
+}
+
+/// Mean of the last `window` samples recorded per PID.
+///
+/// Unlike [`Ema`] the old samples drop out entirely, so a process that calms down stops
+/// looking busy after `window` refreshes.
+#[derive(Debug, Clone)]
+pub struct RollingAverages {
+    window: usize,
+    samples: std::collections::HashMap<u32, std::collections::VecDeque<f32>>,
+}
+
+impl RollingAverages {
+    pub fn new(window: usize) -> Self {
+        Self {
+            window: window.max(1),
+            samples: std::collections::HashMap::new(),
+        }
+    }
+
+    pub fn record(&mut self, pid: u32, sample: f32) {
+        let samples = self.samples.entry(pid).or_default();
+        samples.push_back(sample);
+        while samples.len() > self.window {
+            samples.pop_front();
+        }
+    }
+
+    pub fn average(&self, pid: u32) -> Option<f32> {
+        let samples = self.samples.get(&pid).filter(|samples| !samples.is_empty())?;
+        Some(samples.iter().sum::<f32>() / samples.len() as f32)
+    }
+
+    /// Forget PIDs for which `keep` returns false
+    pub fn retain(&mut self, mut keep: impl FnMut(u32) -> bool) {
+        self.samples.retain(|pid, _| keep(*pid));
+    }
+}
+
+/// The first entries of a list that may be too long to load in full, and how long it was
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Capped<T> {
+    pub items: Vec<T>,
+    pub total: usize,
+}
+
+// Derived, this would require `T: Default`
+impl<T> Default for Capped<T> {
+    fn default() -> Self {
+        Self { items: Vec::new(), total: 0 }
+    }
+}
+
+impl<T> Capped<T> {
+    /// Load the first `cap` of `entries` with `load` and only count the rest, so a process with
+    /// 100k open sockets costs a directory listing rather than 100k `readlink`s. Entries `load`
+    /// returns `None` for (gone in the meantime) are not counted.
+    pub fn collect<E>(entries: impl IntoIterator<Item = E>, cap: usize, mut load: impl FnMut(E) -> Option<T>) -> Self {
+        let mut items = Vec::new();
+        let mut total = 0;
+        for entry in entries {
+            if items.len() < cap {
+                match load(entry) {
+                    Some(item) => items.push(item),
+                    None => continue,
+                }
+            }
+            total += 1;
+        }
+        Self { items, total }
+    }
+
+    pub fn is_truncated(&self) -> bool {
+        self.total > self.items.len()
+    }
+
+    /// e.g. "showing 500 of 100000"
+    pub fn summary(&self) -> String {
+        format!("showing {} of {}", self.items.len(), self.total)
+    }
+}
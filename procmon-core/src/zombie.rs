@@ -0,0 +1,52 @@
+use crate::process::{ProcessInfo, ProcessSnapshot, ProcessStatus};
+use anyhow::{bail, Result};
+use std::collections::HashMap;
+use std::process::Command;
+
+/// Shown next to the "Signal parent" action: why killing a zombie does nothing
+pub const REAP_EXPLANATION: &str = "A zombie has already exited and cannot be killed; it stays in the \
+process table until its parent reaps it. SIGCHLD reminds the parent to do so. If the parent ignores it, \
+only stopping the parent gets the zombie adopted and reaped by init.";
+
+/// PID to signal so that `zombie` gets reaped: its parent, never the zombie itself
+pub fn reap_target(zombie: &ProcessInfo) -> Result<u32> {
+    if zombie.status != ProcessStatus::Zombie {
+        bail!("PID {} is not a zombie", zombie.pid);
+    }
+    match zombie.parent_pid {
+        Some(parent) if parent != 0 => Ok(parent),
+        _ => bail!("Zombie PID {} has no parent to signal", zombie.pid),
+    }
+}
+
+pub(crate) fn signal_parent_command(zombie: &ProcessInfo) -> Result<Command> {
+    let parent = reap_target(zombie)?;
+    let mut cmd = Command::new("kill");
+    cmd.args(["-CHLD", &parent.to_string()]);
+    Ok(cmd)
+}
+
+/// Send SIGCHLD to the parent of `zombie` to prompt it to reap; returns the parent PID.
+pub fn signal_parent(zombie: &ProcessInfo) -> Result<u32> {
+    let parent = reap_target(zombie)?;
+    let output = signal_parent_command(zombie)?.output()?;
+
+    if !output.status.success() {
+        bail!("Failed to signal PID {}: {}", parent, String::from_utf8_lossy(&output.stderr).trim());
+    }
+
+    Ok(parent)
+}
+
+/// Number of zombie children of each parent PID
+pub fn zombie_counts_by_parent(snapshots: &[ProcessSnapshot]) -> HashMap<u32, usize> {
+    let mut counts = HashMap::new();
+    for snapshot in snapshots {
+        if snapshot.info.status == ProcessStatus::Zombie {
+            if let Some(parent) = snapshot.info.parent_pid {
+                *counts.entry(parent).or_default() += 1;
+            }
+        }
+    }
+    counts
+}
@@ -1,16 +1,117 @@
 use eframe::egui;
 use procmon_core::{
-    MisbehaviorDetector, SystemMetrics, SystemMonitor, PartitionManager, Disk,
-    ServiceManager, SystemService, ServiceState,
-    process::ProcessSnapshot,
+    GaugeSmoothing, GaugeValues, MisbehaviorDetector, NewProcessTracker, SystemInfo, SystemMetrics, SystemMonitor, PartitionManager, Disk, Partition,
+    ServiceManager, SystemService, ServiceState, StopMode, StopPlan,
+    service::plan_stop,
+    process::{ProcessInfo, ProcessKey, ProcessSnapshot, ProcessStatus, ProcessTotals, StateFilter},
+    metrics::{format_address, AddressScope},
+    restart::restart_process,
+    kill::{kill_process, kill_process_tree, summarize_signal_results, Signal},
+    users::{group_by_user, UserGroup},
+    protect::ProtectedSet,
+    zombie::{signal_parent, REAP_EXPLANATION},
+    search::{environment_matches, highlight_ranges, matches_search, segments},
     detector::Severity,
+    partition::{
+        carry_over_usage, default_image_file_name, filesystem_usage, is_temp_mount_point, BenchmarkResult, FsckProgress, ImageProgress,
+        NetworkMount, COMMON_PARTITION_TYPES, DirectoryScan, DIRECTORY_USAGE_DEPTH, TEMP_MOUNT_ROOT,
+    },
+    badge::AlertAcknowledgement,
+    detail::{ProcessDetail, DEBUG_FLAG, DEFAULT_DETAIL_ENTRIES},
+    limits::user_thread_count,
+    threads::{read_threads, ThreadInfo},
+    clipboard::ClipboardText,
+    confirm::HoldToConfirm,
+    export::{export_alerts, ExportFormat},
+    sink::{AlertSink, DesktopNotificationSink},
+    snapshot::{snapshot_config, Snapshot, SnapshotConfig, SnapshotWriter, SNAPSHOT_DIR_ENV},
+    baseline::{baseline_sigma, Anomaly, BaselineTracker, BASELINE_SIGMA_ENV},
+    own::{self_processes, SelfProcesses, SELF_PROCESSES_ENV},
+    privilege::{escalation, ESCALATION_ENV, REQUIRES_ROOT},
+    cores::group_by_core,
+    counters::{limit_level, LimitLevel, SystemCounters},
+    sort::{sort_by_network, sort_processes, sort_services, NetworkSort, ServiceSortColumn, SortColumn},
+    tab::{startup_tab, Tab, STARTUP_TAB_ENV},
+    temperature::{format_temp, temperature_unit, TemperatureUnit, TEMP_UNIT_ENV},
+    density::{density, Density, DENSITY_ENV},
+    container::{group_by_container, short_id, CONTAINER_NOTE},
+    gpu::gpu_processes,
+    growth::top_memory_growth,
+    columns::{format_header_line, format_row_cells, format_row_line, format_thread_line, process_columns, ProcessColumn, COLUMNS_ENV},
+    util::{staleness, Capped, RefreshSchedule, SourceBackoff, Staleness},
+    selection::DiskCursor,
+    bandwidth::RateScale,
+    logging::{init_logging, log_config, LogBuffer, LogTarget},
+    fds::{scan_deleted_open_files, space_held, DeletedOpenFile, DELETED_FILES_SCAN_INTERVAL},
+    faults::{is_major_fault_spike, MAJOR_FAULT_SPIKE},
+    config::{Config, ConfigWarning, DisplayConfig, Theme},
 };
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::mpsc::{Receiver, TryRecvError};
 use std::sync::Arc;
+use std::thread::JoinHandle;
 use std::time::{Duration, Instant};
 use parking_lot::RwLock;
+use tracing::Level;
+
+/// How often the background thread collects new data
+const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How often the background thread checks whether a refresh is due
+const REFRESH_POLL: Duration = Duration::from_millis(50);
 
 fn main() -> eframe::Result<()> {
-    tracing_subscriber::fmt::init();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let rust_log = std::env::var("RUST_LOG").ok();
+    let log_buffer = match log_config(args.clone(), rust_log.as_deref(), LogTarget::Stderr).and_then(|config| init_logging(&config)) {
+        Ok(sinks) => sinks.recent,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(2);
+        }
+    };
+
+    // config.toml fills in whatever the command line and environment leave open
+    let (config, mut config_warnings) = Config::load();
+    let (rules, rule_warnings) = config.load_rules();
+    config_warnings.extend(rule_warnings);
+    for warning in &config_warnings {
+        tracing::warn!("Config: {}", warning);
+    }
+    let tab_preference = std::env::var(STARTUP_TAB_ENV).ok().or(config.general.startup_tab.clone());
+    let columns_preference = std::env::var(COLUMNS_ENV).ok().or(config.display.columns.clone());
+    let temp_unit_preference = std::env::var(TEMP_UNIT_ENV).ok().or(config.display.temperature_unit.clone());
+    let density_preference = std::env::var(DENSITY_ENV).ok().or(config.display.density.clone());
+    let snapshot_preference = std::env::var(SNAPSHOT_DIR_ENV).ok();
+    let sigma_preference = std::env::var(BASELINE_SIGMA_ENV).ok();
+    let self_processes_preference = std::env::var(SELF_PROCESSES_ENV).ok();
+    let escalation_preference = std::env::var(ESCALATION_ENV).ok();
+    let debug = args.iter().any(|arg| arg == DEBUG_FLAG);
+    let preferences = (|| -> anyhow::Result<_> {
+        Ok((
+            startup_tab(args.clone(), tab_preference.as_deref())?,
+            process_columns(args.clone(), columns_preference.as_deref())?,
+            temperature_unit(args.clone(), temp_unit_preference.as_deref())?,
+            density(args.clone(), density_preference.as_deref())?,
+            ProtectedSet::load().map(|mut protected| {
+                protected.add_names(config.lists.protect.clone());
+                protected
+            })?,
+            snapshot_config(args.clone(), snapshot_preference.as_deref())?,
+            baseline_sigma(args.clone(), sigma_preference.as_deref())?,
+            self_processes(args.clone(), self_processes_preference.as_deref())?,
+            escalation(args, escalation_preference.as_deref())?,
+        ))
+    })();
+    let (startup_tab, process_columns, temperature_unit, density, protected, snapshots, baseline_sigma, self_processes, escalation) =
+        match preferences {
+            Ok(preferences) => preferences,
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(2);
+            }
+        };
 
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
@@ -22,57 +123,231 @@ fn main() -> eframe::Result<()> {
     eframe::run_native(
         "Process Monitor",
         options,
-        Box::new(|_cc| Ok(Box::new(ProcessMonitorApp::new()))),
+        Box::new(move |cc| {
+            cc.egui_ctx.set_visuals(match config.display.theme {
+                Theme::Dark => egui::Visuals::dark(),
+                Theme::Light => egui::Visuals::light(),
+            });
+            let mut app = ProcessMonitorApp::new(startup_tab, process_columns, temperature_unit, density, protected, snapshots, baseline_sigma);
+            app.set_self_processes(self_processes);
+            app.partition_manager.write().set_escalation(escalation);
+            app.debug = debug;
+            app.log_buffer = log_buffer;
+            app.apply_config(&config);
+            app.config_warnings = config_warnings;
+            if let Some(rules) = rules {
+                app.detector.write().set_rules(rules);
+            }
+            Ok(Box::new(app))
+        }),
     )
 }
 
+/// `hdparm -t` running on a background thread; it reads for several seconds
+struct BenchmarkJob {
+    device: String,
+    handle: JoinHandle<anyhow::Result<BenchmarkResult>>,
+}
+
+/// A filesystem check running on a background thread
+struct FsckJob {
+    device: String,
+    progress: Arc<RwLock<FsckProgress>>,
+    handle: JoinHandle<anyhow::Result<String>>,
+}
+
+/// Disk usage drill-down below a mount point, listing the directories in `path`
+struct DirectoryUsage {
+    mount_point: PathBuf,
+    path: PathBuf,
+    entries: Vec<(PathBuf, u64)>,
+    error: Option<String>,
+    /// `du` scan of `path` while it runs
+    job: Option<DirectoryScan>,
+}
+
+/// A partition being copied to an image file by dd
+struct ImageJob {
+    device: String,
+    out_path: PathBuf,
+    total_bytes: u64,
+    copied_bytes: u64,
+    progress: Receiver<ImageProgress>,
+}
+
 struct ProcessMonitorApp {
     monitor: Arc<RwLock<SystemMonitor>>,
     detector: Arc<RwLock<MisbehaviorDetector>>,
     partition_manager: Arc<RwLock<PartitionManager>>,
     service_manager: Arc<RwLock<ServiceManager>>,
     system_metrics: Arc<RwLock<SystemMetrics>>,
+    system_info: SystemInfo,
+    // Dashboard gauge smoothing; system_metrics stays raw for alerting
+    smoothing_enabled: bool,
+    smoothing_alpha: f64,
+    gauge_smoothing: GaugeSmoothing,
+    gauges: GaugeValues,
+    gauges_sampled_at: Option<chrono::DateTime<chrono::Utc>>,
     processes: Arc<RwLock<Vec<ProcessSnapshot>>>,
+    system_counters: Arc<RwLock<SystemCounters>>,
+    new_processes: Arc<RwLock<NewProcessTracker>>,
     disks: Arc<RwLock<Vec<Disk>>>,
     services: Arc<RwLock<Vec<SystemService>>>,
     alerts: Arc<RwLock<Vec<procmon_core::MisbehaviorAlert>>>,
+    /// Alerts up to here have been seen on the Alerts tab
+    alert_acknowledgement: AlertAcknowledgement,
     selected_tab: usize,
-    sort_by_cpu: bool,
-    selected_process: Option<usize>,
-    selected_process_pid: Option<u32>,
-    show_process_context_menu: bool,
-    context_menu_pos: egui::Pos2,
-    selected_disk: Option<usize>,
-    selected_partition: Option<usize>,
+    sort_column: SortColumn,
+    service_sort_column: ServiceSortColumn,
+    service_sort_ascending: bool,
+    show_command_line: bool,
+    /// Process states the table is restricted to
+    state_filter: StateFilter,
+    search_query: String,
+    /// Opt-in: the search also reads process environments, which is slow and sensitive
+    search_environment: bool,
+    group_by_container: bool,
+    group_by_user: bool,
+    /// Only processes holding GPU memory, with how much
+    show_gpu_processes: bool,
+    /// Processes tab lists the biggest memory growth since the last refresh
+    show_memory_growth: bool,
+    /// Threads of the selected process are listed under it
+    show_threads: bool,
+    /// Threads of this PID, and when they were read
+    threads: Option<(ProcessKey, Instant, Capped<ThreadInfo>)>,
+    /// Threads being read on a background thread, with the process they belong to
+    threads_job: Option<(ProcessKey, JoinHandle<Capped<ThreadInfo>>)>,
+    /// User whose processes are about to be killed, the name typed to confirm, and SIGKILL instead of SIGTERM
+    user_kill: Option<(UserGroup, String, bool)>,
+    /// Selection follows the process when the list re-sorts, and not a new process reusing its PID
+    selected_process: Option<ProcessKey>,
+    /// Process table columns in display order
+    process_columns: Vec<ProcessColumn>,
+    /// Unit temperatures are displayed in
+    temperature_unit: TemperatureUnit,
+    /// Spacing of the process list rows
+    density: Density,
+    /// Processes the kill and restart actions refuse to touch
+    protected: ProtectedSet,
+    /// Selected disk and partition, and whether the disk's partitions are shown
+    disk_cursor: DiskCursor,
     status_message: String,
     show_format_dialog: bool,
     format_filesystem: String,
+    /// Mount a freshly formatted partition under `/mnt/procmon`
+    format_auto_mount: bool,
+    /// Format and Delete only act after their button is held down for a second
+    format_hold: HoldToConfirm,
     show_delete_confirm: bool,
+    delete_hold: HoldToConfirm,
+    /// NFS/CIFS/SSHFS mounts; their usage is only read when asked for, since `df` can hang on them
+    network_mounts: Vec<NetworkMount>,
+    fstab_candidate: Option<(String, String)>,
+    fstab_mount_point: String,
+    fstab_options: String,
+    fsck_job: Option<FsckJob>,
+    benchmark_job: Option<BenchmarkJob>,
+    directory_usage: Option<DirectoryUsage>,
+    /// Device and size of the partition the "Create Image" dialog is open for
+    image_source: Option<(String, u64)>,
+    image_out_path: String,
+    /// Image even if the source is mounted read-write
+    image_force: bool,
+    image_job: Option<ImageJob>,
+    /// Disk, partition number and partition device the "Set Type" dialog is open for
+    type_target: Option<(String, u32, String)>,
+    type_code: String,
+    last_refresh: Arc<RwLock<Instant>>,
+    refresh_schedule: Arc<RwLock<RefreshSchedule>>,
+    /// `lsblk` and `systemctl` polling, spaced out while they keep failing
+    disks_backoff: Arc<RwLock<SourceBackoff>>,
+    services_backoff: Arc<RwLock<SourceBackoff>>,
+    benchmark_results: HashMap<String, BenchmarkResult>,
+    /// Full-bar rate per network interface: link speed or observed peak
+    network_scale: RateScale,
+    /// Network tab's process table by current rate or lifetime bytes
+    network_sort: NetworkSort,
+    /// Full-bar rate per block device: observed peak throughput
+    disk_scale: RateScale,
+    /// Deleted files still held open, rescanned while the Storage tab is shown
+    deleted_files: Vec<DeletedOpenFile>,
+    deleted_files_scanned: Option<Instant>,
+    /// Deleted-file scan running on a background thread
+    deleted_files_job: Option<JoinHandle<Vec<DeletedOpenFile>>>,
+    /// Lowest severity shown as a desktop notification; `None` disables them
+    notify_min_severity: Arc<RwLock<Option<Severity>>>,
+    /// Alerts kept in the Alerts tab
+    max_alerts: Arc<RwLock<usize>>,
+    /// Problems in config.toml or the rules file, shown under the tabs
+    config_warnings: Vec<ConfigWarning>,
+    /// CPU or memory usage far from the same hour on previous days
+    anomalies: Arc<RwLock<Vec<Anomaly>>>,
+    /// PID whose I/O throttle dialog is open, with the read/write limits in MB/s (0 = none)
+    throttle_target: Option<u32>,
+    /// Command line and environment window, read when it was opened
+    process_detail: Option<ProcessDetail>,
+    /// Detail being read on a background thread
+    detail_job: Option<JoinHandle<ProcessDetail>>,
+    /// Open files and threads listed per process
+    detail_entries: usize,
+    /// `--debug`: the detail window also lists raw `/proc` fields
+    debug: bool,
+    /// procmon's own recent log events, shown in the Logs tab
+    log_buffer: LogBuffer,
+    throttle_read_mb: u64,
+    throttle_write_mb: u64,
+    /// Service whose dependency window is open, with what it depends on and what depends on it
+    service_dependency_view: Option<(String, Vec<String>, Vec<String>)>,
+    /// Service awaiting stop confirmation, with the units that depend on it
+    pending_stop: Option<(String, Vec<String>)>,
 }
 
 impl ProcessMonitorApp {
-    fn new() -> Self {
-        let monitor = SystemMonitor::new();
-        monitor.refresh();
+    fn new(
+        startup_tab: Tab,
+        process_columns: Vec<ProcessColumn>,
+        temperature_unit: TemperatureUnit,
+        density: Density,
+        protected: ProtectedSet,
+        snapshots: Option<SnapshotConfig>,
+        baseline_sigma: f64,
+    ) -> Self {
+        let monitor = SystemMonitor::new_warmed();
 
         let partition_manager = PartitionManager::new();
         let disks = partition_manager.list_disks().unwrap_or_default();
+        let network_mounts = partition_manager.list_network_mounts().unwrap_or_default();
 
         let service_manager = ServiceManager::new();
         let services = service_manager.list_services().unwrap_or_default();
 
         let system_metrics = monitor.get_system_metrics().unwrap_or_default();
+        let system_info = monitor.get_system_info();
         let processes = monitor.get_all_processes().unwrap_or_default();
 
+        let mut new_processes = NewProcessTracker::default();
+        let pids: Vec<u32> = processes.iter().map(|p| p.info.pid).collect();
+        new_processes.update(&pids, Instant::now());
+
         let monitor = Arc::new(RwLock::new(monitor));
         let detector = Arc::new(RwLock::new(MisbehaviorDetector::new()));
         let partition_manager = Arc::new(RwLock::new(partition_manager));
         let service_manager = Arc::new(RwLock::new(service_manager));
         let system_metrics = Arc::new(RwLock::new(system_metrics));
+        let system_counters = Arc::new(RwLock::new(SystemCounters::collect(&processes)));
         let processes = Arc::new(RwLock::new(processes));
+        let new_processes = Arc::new(RwLock::new(new_processes));
         let disks = Arc::new(RwLock::new(disks));
         let services = Arc::new(RwLock::new(services));
         let alerts = Arc::new(RwLock::new(Vec::new()));
+        let last_refresh = Arc::new(RwLock::new(Instant::now()));
+        let refresh_schedule = Arc::new(RwLock::new(RefreshSchedule::new(REFRESH_INTERVAL, Instant::now())));
+        let disks_backoff = Arc::new(RwLock::new(SourceBackoff::new("lsblk", Duration::from_secs(5))));
+        let services_backoff = Arc::new(RwLock::new(SourceBackoff::new("systemctl", Duration::from_secs(3))));
+        let notify_min_severity = Arc::new(RwLock::new(Some(Severity::Critical)));
+        let max_alerts = Arc::new(RwLock::new(DisplayConfig::default().max_alerts));
+        let anomalies = Arc::new(RwLock::new(Vec::new()));
 
         // Spawn background update task
         let monitor_clone = monitor.clone();
@@ -81,55 +356,102 @@ impl ProcessMonitorApp {
         let service_manager_clone = service_manager.clone();
         let system_metrics_clone = system_metrics.clone();
         let processes_clone = processes.clone();
+        let system_counters_clone = system_counters.clone();
+        let new_processes_clone = new_processes.clone();
         let disks_clone = disks.clone();
         let services_clone = services.clone();
         let alerts_clone = alerts.clone();
+        let last_refresh_clone = last_refresh.clone();
+        let refresh_schedule_clone = refresh_schedule.clone();
+        let disks_backoff_clone = disks_backoff.clone();
+        let services_backoff_clone = services_backoff.clone();
+        let notify_min_severity_clone = notify_min_severity.clone();
+        let max_alerts_clone = max_alerts.clone();
+        let anomalies_clone = anomalies.clone();
 
         std::thread::spawn(move || {
             let rt = tokio::runtime::Runtime::new().unwrap();
             rt.block_on(async {
+                let mut notifications = DesktopNotificationSink::new(Severity::Critical);
+                let mut snapshots = snapshots.map(SnapshotWriter::new);
+                let mut baseline = BaselineTracker::load_default(baseline_sigma);
+
                 loop {
-                    tokio::time::sleep(Duration::from_secs(1)).await;
+                    tokio::time::sleep(REFRESH_POLL).await;
+                    if !refresh_schedule_clone.read().is_due(Instant::now()) {
+                        continue;
+                    }
 
                     let monitor = monitor_clone.read();
                     monitor.refresh();
-
-                    if let Ok(metrics) = monitor.get_system_metrics() {
-                        *system_metrics_clone.write() = metrics;
+                    refresh_schedule_clone.write().mark_refreshed(Instant::now());
+
+                    let metrics = monitor.get_system_metrics().ok();
+                    if let Some(ref metrics) = metrics {
+                        *system_metrics_clone.write() = metrics.clone();
+                        let (cpu, memory) = (metrics.cpu.total_usage as f64, metrics.memory.used_percent());
+                        if let Err(e) = baseline.observe(chrono::Local::now(), cpu, memory) {
+                            tracing::warn!("Failed to save usage baseline: {:#}", e);
+                        }
+                        *anomalies_clone.write() = baseline.anomalies();
                     }
 
                     if let Ok(procs) = monitor.get_all_processes() {
                         *processes_clone.write() = procs.clone();
+                        *system_counters_clone.write() = SystemCounters::collect(&procs);
 
                         let mut detector = detector_clone.write();
+                        let mut new_alerts = Vec::new();
+                        for process in &procs {
+                            new_alerts.extend(detector.check_process(process));
+                        }
+                        if let Some(ref metrics) = metrics {
+                            new_alerts.extend(detector.check_system(&procs, metrics));
+                        }
+                        let new_alerts = detector.limit_rate(new_alerts);
+
+                        if let Some(min_severity) = *notify_min_severity_clone.read() {
+                            notifications.set_min_severity(min_severity);
+                            for alert in &new_alerts {
+                                if let Err(e) = notifications.send(alert) {
+                                    tracing::warn!("Desktop notification failed: {}", e);
+                                }
+                            }
+                        }
+
                         let mut alerts = alerts_clone.write();
+                        alerts.extend(new_alerts);
 
-                        for process in &procs {
-                            let process_alerts = detector.check_process(process);
-                            alerts.extend(process_alerts);
+                        let (alerts_len, max_alerts) = (alerts.len(), *max_alerts_clone.read());
+                        if alerts_len > max_alerts {
+                            alerts.drain(0..alerts_len - max_alerts);
                         }
 
-                        let alerts_len = alerts.len();
-                        if alerts_len > 100 {
-                            alerts.drain(0..alerts_len - 100);
+                        if let (Some(writer), Some(metrics)) = (snapshots.as_mut(), metrics.as_ref()) {
+                            let snapshot = Snapshot { timestamp: metrics.timestamp, metrics, processes: &procs, alerts: &alerts };
+                            if let Err(e) = writer.write_if_due(&snapshot, Instant::now()) {
+                                tracing::warn!("Failed to write snapshot: {:#}", e);
+                            }
                         }
 
                         let active_pids: Vec<u32> = procs.iter().map(|p| p.info.pid).collect();
                         detector.cleanup_dead_processes(&active_pids);
+                        new_processes_clone.write().update(&active_pids, Instant::now());
+                        *last_refresh_clone.write() = Instant::now();
                     }
 
-                    // Refresh disks every 5 seconds
-                    if Instant::now().elapsed().as_secs() % 5 == 0 {
-                        let pm = partition_manager_clone.read();
-                        if let Ok(disk_list) = pm.list_disks() {
+                    // Disks every 5 seconds and services every 3, less often while failing
+                    let now = Instant::now();
+                    if disks_backoff_clone.read().is_due(now) {
+                        let result = partition_manager_clone.read().list_disks();
+                        if let Some(disk_list) = disks_backoff_clone.write().record(result, now) {
                             *disks_clone.write() = disk_list;
                         }
                     }
 
-                    // Refresh services every 3 seconds
-                    if Instant::now().elapsed().as_secs() % 3 == 0 {
-                        let sm = service_manager_clone.read();
-                        if let Ok(service_list) = sm.list_services() {
+                    if services_backoff_clone.read().is_due(now) {
+                        let result = service_manager_clone.read().list_services();
+                        if let Some(service_list) = services_backoff_clone.write().record(result, now) {
                             *services_clone.write() = service_list;
                         }
                     }
@@ -143,113 +465,366 @@ impl ProcessMonitorApp {
             partition_manager,
             service_manager,
             system_metrics,
+            system_info,
+            smoothing_enabled: true,
+            smoothing_alpha: GaugeSmoothing::DEFAULT_ALPHA,
+            gauge_smoothing: GaugeSmoothing::new(GaugeSmoothing::DEFAULT_ALPHA),
+            gauges: GaugeValues::default(),
+            gauges_sampled_at: None,
             processes,
+            system_counters,
+            new_processes,
             disks,
             services,
             alerts,
-            selected_tab: 0,
-            sort_by_cpu: true,
+            alert_acknowledgement: AlertAcknowledgement::default(),
+            selected_tab: startup_tab.index(),
+            sort_column: SortColumn::Cpu,
+            service_sort_column: ServiceSortColumn::Name,
+            service_sort_ascending: true,
+            show_command_line: false,
+            state_filter: StateFilter::default(),
+            search_query: String::new(),
+            search_environment: false,
+            group_by_container: false,
+            group_by_user: false,
+            show_gpu_processes: false,
+            show_memory_growth: false,
+            show_threads: false,
+            threads: None,
+            threads_job: None,
+            user_kill: None,
             selected_process: None,
-            selected_process_pid: None,
-            show_process_context_menu: false,
-            context_menu_pos: egui::Pos2::ZERO,
-            selected_disk: None,
-            selected_partition: None,
+            process_columns,
+            temperature_unit,
+            density,
+            protected,
+            disk_cursor: DiskCursor::default(),
             status_message: String::new(),
             show_format_dialog: false,
             format_filesystem: "ext4".to_string(),
+            format_auto_mount: false,
+            format_hold: HoldToConfirm::default(),
             show_delete_confirm: false,
+            delete_hold: HoldToConfirm::default(),
+            network_mounts,
+            fstab_candidate: None,
+            fstab_mount_point: String::new(),
+            fstab_options: "defaults".to_string(),
+            fsck_job: None,
+            benchmark_job: None,
+            directory_usage: None,
+            image_source: None,
+            image_out_path: String::new(),
+            image_force: false,
+            image_job: None,
+            type_target: None,
+            type_code: String::new(),
+            last_refresh,
+            refresh_schedule,
+            disks_backoff,
+            services_backoff,
+            notify_min_severity,
+            max_alerts,
+            config_warnings: Vec::new(),
+            anomalies,
+            benchmark_results: HashMap::new(),
+            network_scale: RateScale::network(),
+            network_sort: NetworkSort::default(),
+            disk_scale: RateScale::disks(),
+            deleted_files: Vec::new(),
+            deleted_files_scanned: None,
+            deleted_files_job: None,
+            throttle_target: None,
+            process_detail: None,
+            detail_job: None,
+            detail_entries: DEFAULT_DETAIL_ENTRIES,
+            debug: false,
+            log_buffer: LogBuffer::default(),
+            throttle_read_mb: 0,
+            throttle_write_mb: 10,
+            service_dependency_view: None,
+            pending_stop: None,
         }
     }
 
+    /// Tag or hide procmon's own processes, and alert on them only in `Include` mode
+    fn set_self_processes(&mut self, mode: SelfProcesses) {
+        self.monitor.write().set_self_processes(mode);
+        self.detector.write().set_own_alerts(mode.alerts());
+    }
+
+    /// Refresh interval, ignore/allow lists, alert limits and gauge smoothing from `config.toml`
+    fn apply_config(&mut self, config: &Config) {
+        let last_update = self.refresh_schedule.read().last_update();
+        *self.refresh_schedule.write() = RefreshSchedule::new(config.general.refresh_interval(), last_update);
+        self.monitor.write().set_ignored(config.lists.ignore.clone());
+        self.detector.write().set_allowed(config.lists.allow.clone());
+        *self.max_alerts.write() = config.display.max_alerts;
+        self.detector.write().set_max_alerts_per_sec(config.display.alert_rate_cap());
+        self.smoothing_alpha = config.display.gauge_alpha();
+        self.gauge_smoothing = GaugeSmoothing::new(self.smoothing_alpha);
+        self.gauges_sampled_at = None;
+        self.detail_entries = config.display.detail_entries;
+    }
+
     fn draw_dashboard(&mut self, ui: &mut egui::Ui) {
         let metrics = self.system_metrics.read();
 
+        egui::CollapsingHeader::new("System Information")
+            .default_open(true)
+            .show(ui, |ui| {
+                let info = &self.system_info;
+                egui::Grid::new("system_info")
+                    .num_columns(2)
+                    .spacing([40.0, 4.0])
+                    .show(ui, |ui| {
+                        ui.label("Hostname:");
+                        ui.label(&info.hostname);
+                        ui.end_row();
+                        ui.label("OS:");
+                        ui.label(format!("{} {}", info.os_name, info.os_version));
+                        ui.end_row();
+                        ui.label("Kernel:");
+                        ui.label(&info.kernel_version);
+                        ui.end_row();
+                        ui.label("Architecture:");
+                        ui.label(&info.architecture);
+                        ui.end_row();
+                        ui.label("CPU:");
+                        ui.label(&info.cpu_model);
+                        ui.end_row();
+                    });
+            });
+        ui.add_space(10.0);
+
         ui.heading("System Overview");
         ui.add_space(10.0);
 
+        ui.horizontal(|ui| {
+            let mut changed = ui.checkbox(&mut self.smoothing_enabled, "Smooth gauges").changed();
+            if self.smoothing_enabled {
+                changed |= ui
+                    .add(egui::Slider::new(&mut self.smoothing_alpha, 0.05..=1.0).text("alpha"))
+                    .changed();
+            }
+            if changed {
+                self.gauge_smoothing = GaugeSmoothing::new(self.smoothing_alpha);
+                self.gauges_sampled_at = None;
+            }
+        });
+
+        // Feed each metrics sample into the smoother once, not once per frame
+        if self.gauges_sampled_at != Some(metrics.timestamp) {
+            self.gauges = if self.smoothing_enabled {
+                self.gauge_smoothing.update(&metrics)
+            } else {
+                GaugeValues::from(&*metrics)
+            };
+            self.gauges_sampled_at = Some(metrics.timestamp);
+        }
+        let gauges = self.gauges;
+
         egui::Grid::new("system_metrics")
             .num_columns(2)
             .spacing([40.0, 10.0])
             .show(ui, |ui| {
                 ui.label("CPU Usage:");
                 ui.add(
-                    egui::ProgressBar::new(metrics.cpu.total_usage / 100.0)
-                        .text(format!("{:.1}%", metrics.cpu.total_usage)),
+                    egui::ProgressBar::new(gauges.cpu_usage / 100.0)
+                        .text(format!("{:.1}%", gauges.cpu_usage)),
                 );
                 ui.end_row();
 
-                let mem_percent = metrics.memory.used as f64 / metrics.memory.total as f64;
+                let mem_percent = gauges.memory_used as f64 / gauges.memory_total as f64;
                 ui.label("Memory Usage:");
                 ui.add(
                     egui::ProgressBar::new(mem_percent as f32)
                         .text(format!(
                             "{:.1} / {:.1} GB",
-                            metrics.memory.used as f64 / (1024.0 * 1024.0 * 1024.0),
-                            metrics.memory.total as f64 / (1024.0 * 1024.0 * 1024.0)
+                            gauges.memory_used as f64 / (1024.0 * 1024.0 * 1024.0),
+                            gauges.memory_total as f64 / (1024.0 * 1024.0 * 1024.0)
                         )),
                 );
                 ui.end_row();
 
+                // Per-node memory only matters on NUMA machines
+                if metrics.memory.numa_nodes.len() > 1 {
+                    for node in &metrics.memory.numa_nodes {
+                        let fraction = if node.total > 0 { node.used() as f64 / node.total as f64 } else { 0.0 };
+                        ui.label(format!("NUMA Node {}:", node.id));
+                        ui.add(
+                            egui::ProgressBar::new(fraction as f32)
+                                .text(format!(
+                                    "{:.1} / {:.1} GB",
+                                    node.used() as f64 / (1024.0 * 1024.0 * 1024.0),
+                                    node.total as f64 / (1024.0 * 1024.0 * 1024.0)
+                                )),
+                        );
+                        ui.end_row();
+                    }
+                }
+
                 ui.label("CPU Temperature:");
-                if let Some(temp) = metrics.cpu.temperature {
-                    ui.label(format!("{:.1}°C", temp));
+                if let Some(temp) = gauges.temperature {
+                    ui.label(format_temp(temp, self.temperature_unit));
                 } else {
                     ui.label("N/A");
                 }
                 ui.end_row();
             });
 
+        // Hints from the hourly baseline
+        for anomaly in self.anomalies.read().iter() {
+            ui.colored_label(egui::Color32::from_rgb(200, 120, 255), anomaly.describe());
+        }
+
         ui.add_space(20.0);
-        ui.heading("CPU Core Usage");
+        ui.heading("System Counters");
         ui.add_space(10.0);
 
-        let bar_width = 30.0;
-        let bar_spacing = 5.0;
-        let num_cores = metrics.cpu.per_core_usage.len();
-        let chart_height = 150.0;
-
-        let (response, painter) = ui.allocate_painter(
-            egui::Vec2::new(
-                (bar_width + bar_spacing) * num_cores as f32,
-                chart_height,
-            ),
-            egui::Sense::hover(),
-        );
+        let counters = self.system_counters.read().clone();
+        egui::Grid::new("system_counters")
+            .num_columns(2)
+            .spacing([40.0, 4.0])
+            .show(ui, |ui| {
+                let rows = [
+                    ("Processes:", counters.processes, None, None),
+                    ("PIDs:", counters.pids_in_use, counters.pid_max, counters.pids_percent()),
+                    ("Threads:", counters.threads, counters.threads_max, counters.threads_percent()),
+                    ("Open files:", counters.open_files, counters.file_max, counters.open_files_percent()),
+                ];
+                for (label, value, limit, percent) in rows {
+                    let text = match (limit, percent) {
+                        (Some(limit), Some(percent)) => format!("{} / {} ({:.1}%)", value, limit, percent),
+                        _ => value.to_string(),
+                    };
+                    let color = match limit_level(percent) {
+                        LimitLevel::Normal => egui::Color32::GREEN,
+                        LimitLevel::Warning => egui::Color32::YELLOW,
+                        LimitLevel::Critical => egui::Color32::RED,
+                    };
+                    ui.label(label);
+                    ui.colored_label(color, text);
+                    ui.end_row();
+                }
+            });
 
-        let rect = response.rect;
+        let filesystems = filesystem_usage(&self.disks.read());
+        if !filesystems.is_empty() {
+            ui.add_space(20.0);
+            ui.heading("Filesystems");
+            ui.add_space(10.0);
 
-        for (i, usage) in metrics.cpu.per_core_usage.iter().enumerate() {
-            let x = rect.left() + (bar_width + bar_spacing) * i as f32;
-            let bar_height = (chart_height - 20.0) * (usage / 100.0);
-            let y = rect.bottom() - bar_height - 20.0;
+            egui::Grid::new("filesystems")
+                .num_columns(2)
+                .spacing([40.0, 4.0])
+                .show(ui, |ui| {
+                    for fs in &filesystems {
+                        let percent = fs.percent();
+                        let color = match limit_level(Some(percent)) {
+                            LimitLevel::Normal => egui::Color32::GREEN,
+                            LimitLevel::Warning => egui::Color32::YELLOW,
+                            LimitLevel::Critical => egui::Color32::RED,
+                        };
+                        ui.label(&fs.mount_point).on_hover_text(&fs.device);
+                        ui.add(
+                            egui::ProgressBar::new(percent / 100.0)
+                                .fill(color)
+                                .text(format!(
+                                    "{:.1} / {:.1} GB ({:.1}%)",
+                                    fs.used_bytes as f64 / (1024.0 * 1024.0 * 1024.0),
+                                    fs.size_bytes as f64 / (1024.0 * 1024.0 * 1024.0),
+                                    percent
+                                )),
+                        );
+                        ui.end_row();
+                    }
+                });
+        }
 
-            let color = if *usage > 80.0 {
-                egui::Color32::RED
-            } else if *usage > 60.0 {
-                egui::Color32::YELLOW
-            } else {
-                egui::Color32::GREEN
-            };
+        ui.add_space(20.0);
+        ui.heading("CPU Core Usage");
+        ui.add_space(10.0);
 
-            painter.rect_filled(
-                egui::Rect::from_min_size(
-                    egui::Pos2::new(x, y),
-                    egui::Vec2::new(bar_width, bar_height),
+        if metrics.cpu.per_core_usage.is_empty() {
+            ui.label("No per-core CPU data available");
+        } else {
+            let bar_width = 30.0;
+            let bar_spacing = 5.0;
+            let num_cores = metrics.cpu.per_core_usage.len();
+            let chart_height = 150.0;
+
+            let (response, painter) = ui.allocate_painter(
+                egui::Vec2::new(
+                    (bar_width + bar_spacing) * num_cores as f32,
+                    chart_height,
                 ),
-                0.0,
-                color,
+                egui::Sense::hover(),
             );
 
-            painter.text(
-                egui::Pos2::new(x + bar_width / 2.0, rect.bottom() - 10.0),
-                egui::Align2::CENTER_CENTER,
-                i.to_string(),
-                egui::FontId::proportional(12.0),
-                egui::Color32::WHITE,
-            );
+            let rect = response.rect;
+
+            for (i, usage) in metrics.cpu.per_core_usage.iter().enumerate() {
+                let x = rect.left() + (bar_width + bar_spacing) * i as f32;
+                let bar_height = (chart_height - 20.0) * (usage / 100.0);
+                let y = rect.bottom() - bar_height - 20.0;
+
+                let color = if *usage > 80.0 {
+                    egui::Color32::RED
+                } else if *usage > 60.0 {
+                    egui::Color32::YELLOW
+                } else {
+                    egui::Color32::GREEN
+                };
+
+                painter.rect_filled(
+                    egui::Rect::from_min_size(
+                        egui::Pos2::new(x, y),
+                        egui::Vec2::new(bar_width, bar_height),
+                    ),
+                    0.0,
+                    color,
+                );
+
+                painter.text(
+                    egui::Pos2::new(x + bar_width / 2.0, rect.bottom() - 10.0),
+                    egui::Align2::CENTER_CENTER,
+                    i.to_string(),
+                    egui::FontId::proportional(12.0),
+                    egui::Color32::WHITE,
+                );
+            }
         }
 
+        ui.add_space(10.0);
+        egui::CollapsingHeader::new("Core → Process Map")
+            .default_open(false)
+            .show(ui, |ui| {
+                let processes = self.processes.read();
+                let groups = group_by_core(&processes, metrics.cpu.per_core_usage.len(), 1.0);
+
+                egui::Grid::new("core_map")
+                    .num_columns(3)
+                    .striped(true)
+                    .spacing([20.0, 4.0])
+                    .show(ui, |ui| {
+                        for (core, (usage, group)) in metrics.cpu.per_core_usage.iter().zip(&groups).enumerate() {
+                            ui.label(format!("CPU{}", core));
+                            ui.label(format!("{:.1}%", usage));
+                            ui.label(
+                                group
+                                    .iter()
+                                    .take(6)
+                                    .map(|p| format!("{} ({:.1}%)", p.info.name, p.stats.cpu_usage))
+                                    .collect::<Vec<_>>()
+                                    .join(", "),
+                            );
+                            ui.end_row();
+                        }
+                    });
+            });
+
         if !metrics.gpus.is_empty() {
             ui.add_space(20.0);
             ui.heading("GPU Information");
@@ -258,21 +833,217 @@ impl ProcessMonitorApp {
             for gpu in &metrics.gpus {
                 ui.group(|ui| {
                     ui.label(format!("Name: {}", gpu.name));
-                    ui.add(
-                        egui::ProgressBar::new(gpu.usage / 100.0)
-                            .text(format!("{:.1}% usage", gpu.usage)),
-                    );
-                    if gpu.memory_total > 0 {
-                        ui.label(format!(
-                            "VRAM: {:.1} / {:.1} GB",
-                            gpu.memory_used as f64 / (1024.0 * 1024.0 * 1024.0),
-                            gpu.memory_total as f64 / (1024.0 * 1024.0 * 1024.0)
-                        ));
+                    match gpu.usage {
+                        Some(usage) => {
+                            ui.add(egui::ProgressBar::new(usage / 100.0).text(format!("{:.1}% usage", usage)));
+                        }
+                        None => {
+                            ui.label("Usage: unavailable");
+                        }
+                    }
+                    match (gpu.memory_used, gpu.memory_total) {
+                        (Some(used), Some(total)) if total > 0 => {
+                            ui.label(format!(
+                                "VRAM: {:.1} / {:.1} GB",
+                                used as f64 / (1024.0 * 1024.0 * 1024.0),
+                                total as f64 / (1024.0 * 1024.0 * 1024.0)
+                            ));
+                        }
+                        _ => {
+                            ui.label("VRAM: unavailable");
+                        }
                     }
                     if let Some(temp) = gpu.temperature {
-                        ui.label(format!("Temperature: {:.1}°C", temp));
+                        ui.label(format!("Temperature: {}", format_temp(temp, self.temperature_unit)));
+                    }
+                });
+            }
+        }
+    }
+
+    /// Monospace row for `process` with search matches in the name, user and PID cells highlighted
+    fn highlighted_row(&self, ui: &egui::Ui, process: &ProcessSnapshot, is_new: bool) -> egui::text::LayoutJob {
+        let font_id = egui::TextStyle::Monospace.resolve(ui.style());
+        let base_color = if is_new { egui::Color32::LIGHT_GREEN } else { ui.visuals().text_color() };
+        let plain = egui::TextFormat::simple(font_id.clone(), base_color);
+        let highlighted = egui::TextFormat::simple(font_id.clone(), egui::Color32::YELLOW);
+        let spike = egui::TextFormat::simple(font_id, egui::Color32::RED);
+
+        let mut job = egui::text::LayoutJob::default();
+        let cells = format_row_cells(&self.process_columns, process, self.show_command_line);
+        for (index, (column, cell)) in self.process_columns.iter().zip(&cells).enumerate() {
+            if index > 0 {
+                job.append(" ", 0.0, plain.clone());
+            }
+            if *column == ProcessColumn::MajFlt && is_major_fault_spike(&process.stats) {
+                job.append(cell, 0.0, spike.clone());
+                continue;
+            }
+            let ranges = if column.searchable() { highlight_ranges(cell, &self.search_query) } else { Vec::new() };
+            for (piece, is_match) in segments(cell, &ranges) {
+                job.append(piece, 0.0, if is_match { highlighted.clone() } else { plain.clone() });
+            }
+        }
+        if is_new {
+            job.append("  NEW", 0.0, plain);
+        }
+        job
+    }
+
+    /// Process count, CPU and memory summed per container; host processes last
+    fn draw_container_groups(&self, ui: &mut egui::Ui, processes: &[ProcessSnapshot]) {
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            egui::Grid::new("container_groups").striped(true).num_columns(4).show(ui, |ui| {
+                ui.strong("Container");
+                ui.strong("Processes");
+                ui.strong("CPU");
+                ui.strong("Memory");
+                ui.end_row();
+
+                for group in group_by_container(processes) {
+                    ui.monospace(group.container.as_deref().map_or("(host)", short_id));
+                    ui.label(group.processes.to_string());
+                    ui.label(format!("{:.1}%", group.cpu_usage));
+                    ui.label(format!("{:.1} MB", group.memory_usage as f64 / (1024.0 * 1024.0)));
+                    ui.end_row();
+                }
+            });
+        });
+    }
+
+    fn draw_gpu_processes(&mut self, ui: &mut egui::Ui, processes: &[ProcessSnapshot]) {
+        let using_gpu = gpu_processes(processes);
+        if using_gpu.is_empty() {
+            ui.label("No process holds GPU memory, or the GPU driver doesn't report per-process usage.");
+            return;
+        }
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            egui::Grid::new("gpu_processes").striped(true).num_columns(6).show(ui, |ui| {
+                ui.strong("PID");
+                ui.strong("Name");
+                ui.strong("User");
+                ui.strong("GPU Memory");
+                ui.strong("CPU");
+                ui.strong("Memory");
+                ui.end_row();
+
+                for process in using_gpu {
+                    let selected = self.selected_process == Some(process.key());
+                    if ui.selectable_label(selected, process.info.pid.to_string()).clicked() {
+                        self.selected_process = Some(process.key());
+                    }
+                    ui.label(&process.info.name);
+                    ui.label(&process.info.user);
+                    let gpu_memory = process.stats.gpu_memory_bytes.unwrap_or(0);
+                    ui.label(format!("{:.1} MB", gpu_memory as f64 / (1024.0 * 1024.0)));
+                    ui.label(format!("{:.1}%", process.stats.cpu_usage));
+                    ui.label(format!("{:.1} MB", process.stats.memory_usage as f64 / (1024.0 * 1024.0)));
+                    ui.end_row();
+                }
+            });
+        });
+    }
+
+    fn draw_memory_growth(&mut self, ui: &mut egui::Ui, processes: &[ProcessSnapshot]) {
+        let growing = top_memory_growth(processes);
+        if growing.is_empty() {
+            ui.label("No process grew since the last refresh.");
+            return;
+        }
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            egui::Grid::new("memory_growth").striped(true).num_columns(5).show(ui, |ui| {
+                ui.strong("PID");
+                ui.strong("Name");
+                ui.strong("User");
+                ui.strong("ΔMem");
+                ui.strong("Memory");
+                ui.end_row();
+
+                for process in growing {
+                    let selected = self.selected_process == Some(process.key());
+                    if ui.selectable_label(selected, process.info.pid.to_string()).clicked() {
+                        self.selected_process = Some(process.key());
+                    }
+                    ui.label(&process.info.name);
+                    ui.label(&process.info.user);
+                    ui.colored_label(
+                        egui::Color32::YELLOW,
+                        format!("{:+.1} MB", process.stats.memory_delta as f64 / (1024.0 * 1024.0)),
+                    );
+                    ui.label(format!("{:.1} MB", process.stats.memory_usage as f64 / (1024.0 * 1024.0)));
+                    ui.end_row();
+                }
+            });
+        });
+    }
+
+    fn draw_user_groups(&mut self, ui: &mut egui::Ui, processes: &[ProcessSnapshot]) {
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            egui::Grid::new("user_groups").striped(true).num_columns(6).show(ui, |ui| {
+                ui.strong("User");
+                ui.strong("UID");
+                ui.strong("Processes");
+                ui.strong("CPU");
+                ui.strong("Memory");
+                ui.label("");
+                ui.end_row();
+
+                for group in group_by_user(processes) {
+                    ui.label(&group.user);
+                    ui.label(group.uid.to_string());
+                    ui.label(group.processes.to_string());
+                    ui.label(format!("{:.1}%", group.cpu_usage));
+                    ui.label(format!("{:.1} MB", group.memory_usage as f64 / (1024.0 * 1024.0)));
+                    let button = ui
+                        .add_enabled(group.uid != 0, egui::Button::new("Kill all..."))
+                        .on_disabled_hover_text("Root's processes can't be killed all at once");
+                    if button.clicked() {
+                        self.user_kill = Some((group, String::new(), false));
                     }
+                    ui.end_row();
+                }
+            });
+        });
+
+        let mut confirmed = None;
+        if let Some((ref group, ref mut typed, ref mut force)) = self.user_kill {
+            egui::Window::new("Kill All Processes of User")
+                .collapsible(false)
+                .resizable(false)
+                .show(ui.ctx(), |ui| {
+                    ui.label(format!(
+                        "Terminate all {} processes of {} (uid {})?",
+                        group.processes, group.user, group.uid
+                    ));
+                    ui.label("Protected processes and procmon's own session are skipped.");
+                    ui.add_space(5.0);
+                    ui.checkbox(force, "Use SIGKILL instead of SIGTERM");
+                    ui.horizontal(|ui| {
+                        ui.label("Type the user name to confirm:");
+                        ui.text_edit_singleline(typed);
+                    });
+                    ui.add_space(10.0);
+
+                    ui.horizontal(|ui| {
+                        let matches = *typed == group.user;
+                        if ui.add_enabled(matches, egui::Button::new("Kill All")).clicked() {
+                            confirmed = Some(true);
+                        }
+                        if ui.button("Cancel").clicked() {
+                            confirmed = Some(false);
+                        }
+                    });
                 });
+        }
+        if let Some(confirmed) = confirmed {
+            if let Some((group, _, force)) = self.user_kill.take() {
+                if confirmed {
+                    let signal = if force { Signal::Kill } else { Signal::Term };
+                    self.status_message = match self.monitor.read().kill_user_processes(group.uid, signal, &self.protected) {
+                        Ok(results) => summarize_signal_results(&group.user, signal, &results),
+                        Err(e) => format!("Cannot list processes of {}: {}", group.user, e),
+                    };
+                }
             }
         }
     }
@@ -283,82 +1054,171 @@ impl ProcessMonitorApp {
 
         ui.horizontal(|ui| {
             ui.label("Sort by:");
-            if ui.selectable_label(self.sort_by_cpu, "CPU").clicked() {
-                self.sort_by_cpu = true;
+            ui.selectable_value(&mut self.sort_column, SortColumn::Cpu, "CPU");
+            ui.selectable_value(&mut self.sort_column, SortColumn::CpuAvg, "CPU Avg");
+            ui.selectable_value(&mut self.sort_column, SortColumn::Memory, "Memory");
+            ui.selectable_value(&mut self.sort_column, SortColumn::MemoryGrowth, "ΔMem");
+            ui.selectable_value(&mut self.sort_column, SortColumn::Age, "Age");
+            ui.selectable_value(&mut self.sort_column, SortColumn::Swap, "Swap");
+            ui.separator();
+            ui.checkbox(&mut self.show_command_line, "Show command line");
+            ui.checkbox(&mut self.show_threads, "Show threads")
+                .on_hover_text("List the threads of the selected process under it, with CPU usage per thread");
+            if ui.checkbox(&mut self.group_by_container, "Group by container").changed() {
+                self.group_by_user = false;
+                self.show_gpu_processes = false;
+                self.show_memory_growth = false;
             }
-            if ui.selectable_label(!self.sort_by_cpu, "Memory").clicked() {
-                self.sort_by_cpu = false;
+            if ui.checkbox(&mut self.group_by_user, "Group by user").changed() {
+                self.group_by_container = false;
+                self.show_gpu_processes = false;
+                self.show_memory_growth = false;
+            }
+            if ui.checkbox(&mut self.show_gpu_processes, "GPU processes").changed() {
+                self.group_by_container = false;
+                self.group_by_user = false;
+                self.show_memory_growth = false;
+            }
+            if ui.checkbox(&mut self.show_memory_growth, "Memory growth").changed() {
+                self.group_by_container = false;
+                self.group_by_user = false;
+                self.show_gpu_processes = false;
+            }
+            ui.separator();
+            ui.label("Density:");
+            for density in Density::ALL {
+                ui.selectable_value(&mut self.density, density, density.name());
             }
         });
 
-        ui.add_space(10.0);
+        ui.horizontal(|ui| {
+            ui.label("Search:");
+            ui.text_edit_singleline(&mut self.search_query);
+            ui.checkbox(&mut self.search_environment, "Include environment")
+                .on_hover_text("Also match environment variables of your own processes (slow)");
+            if !self.search_query.is_empty() && ui.button("Clear").clicked() {
+                self.search_query.clear();
+            }
+        });
 
-        let mut processes = self.processes.read().clone();
+        ui.horizontal(|ui| {
+            ui.label("State:");
+            for status in [
+                ProcessStatus::Running,
+                ProcessStatus::Sleeping,
+                ProcessStatus::DiskSleep,
+                ProcessStatus::Stopped,
+                ProcessStatus::Zombie,
+            ] {
+                let mut checked = self.state_filter.contains(status);
+                if ui.checkbox(&mut checked, format!("{:?}", status)).changed() {
+                    self.state_filter.toggle(status);
+                }
+            }
+            ui.separator();
+            if ui.button("Zombies only").clicked() {
+                self.state_filter = StateFilter::only(ProcessStatus::Zombie);
+            }
+            if self.state_filter.is_active() && ui.button("All states").clicked() {
+                self.state_filter.clear();
+            }
+        });
 
-        if self.sort_by_cpu {
-            processes.sort_by(|a, b| b.stats.cpu_usage.partial_cmp(&a.stats.cpu_usage).unwrap());
-        } else {
-            processes.sort_by(|a, b| b.stats.memory_usage.cmp(&a.stats.memory_usage));
+        if !self.status_message.is_empty() {
+            ui.colored_label(egui::Color32::YELLOW, &self.status_message);
         }
+        ui.add_space(10.0);
 
-        // Header
-        ui.horizontal(|ui| {
-            ui.label(egui::RichText::new("PID").strong().size(14.0));
-            ui.add_space(20.0);
-            ui.label(egui::RichText::new("Name").strong().size(14.0));
-            ui.add_space(120.0);
-            ui.label(egui::RichText::new("User").strong().size(14.0));
-            ui.add_space(60.0);
-            ui.label(egui::RichText::new("CPU %").strong().size(14.0));
-            ui.add_space(40.0);
-            ui.label(egui::RichText::new("Memory (MB)").strong().size(14.0));
-            ui.add_space(40.0);
-            ui.label(egui::RichText::new("Disk I/O (MB)").strong().size(14.0));
-            ui.add_space(40.0);
-            ui.label(egui::RichText::new("Status").strong().size(14.0));
-        });
+        let mut processes: Vec<ProcessSnapshot> = self
+            .processes
+            .read()
+            .iter()
+            .filter(|p| self.state_filter.matches(&p.info))
+            .filter(|p| {
+                matches_search(&p.info, &self.search_query)
+                    || (self.search_environment && environment_matches(&p.info, &self.search_query))
+            })
+            .cloned()
+            .collect();
+
+        if self.group_by_container {
+            self.draw_container_groups(ui, &processes);
+            return;
+        }
+        if self.group_by_user {
+            self.draw_user_groups(ui, &processes);
+            return;
+        }
+        if self.show_gpu_processes {
+            self.draw_gpu_processes(ui, &processes);
+            return;
+        }
+        if self.show_memory_growth {
+            self.draw_memory_growth(ui, &processes);
+            return;
+        }
+
+        // Descending: highest usage first, or the longest running process first for Age
+        sort_processes(&mut processes, self.sort_column, false);
+
+        // Header, in the same monospace layout as the rows so columns line up
+        ui.label(
+            egui::RichText::new(format_header_line(&self.process_columns, self.show_command_line))
+                .monospace()
+                .strong(),
+        );
         ui.separator();
 
-        egui::ScrollArea::vertical().show(ui, |ui| {
-            for (i, process) in processes.iter().take(100).enumerate() {
-                let is_selected = self.selected_process == Some(i);
+        let new_processes = self.new_processes.clone();
+        let now = Instant::now();
+        if self.show_threads {
+            self.update_threads();
+        }
+
+        // Leave room below the list for the totals footer
+        let totals = ProcessTotals::of(&processes);
+        let footer_height = ui.text_style_height(&egui::TextStyle::Body) + ui.spacing().item_spacing.y * 3.0;
+        egui::ScrollArea::vertical().max_height(ui.available_height() - footer_height).show(ui, |ui| {
+            ui.spacing_mut().item_spacing.y = self.density.row_spacing();
+            for process in processes.iter().take(100) {
+                let is_selected = self.selected_process == Some(process.key());
+                let is_new = new_processes.read().is_new(process.info.pid, now);
 
                 // Create a single clickable row
-                let row_text = format!(
-                    "{:<8} {:<20} {:<12} {:>6.1} {:>12.1} {:>12.1} {:?}",
-                    process.info.pid,
-                    if process.info.name.len() > 20 {
-                        format!("{}...", &process.info.name[..17])
-                    } else {
-                        process.info.name.clone()
-                    },
-                    if process.info.user.len() > 12 {
-                        format!("{}...", &process.info.user[..9])
+                let row: egui::WidgetText = if !self.search_query.is_empty() {
+                    self.highlighted_row(ui, process, is_new).into()
+                } else {
+                    let row_text = format_row_line(&self.process_columns, process, self.show_command_line);
+                    if process.info.is_own {
+                        egui::RichText::new(format!("{}  SELF", row_text)).monospace().weak().into()
+                    } else if is_new {
+                        egui::RichText::new(format!("{}  NEW", row_text))
+                            .monospace()
+                            .color(egui::Color32::LIGHT_GREEN)
+                            .into()
                     } else {
-                        process.info.user.clone()
-                    },
-                    process.stats.cpu_usage,
-                    process.stats.memory_usage as f64 / (1024.0 * 1024.0),
-                    (process.stats.disk_read_bytes + process.stats.disk_write_bytes) as f64 / (1024.0 * 1024.0),
-                    process.info.status
-                );
+                        egui::RichText::new(row_text).monospace().into()
+                    }
+                };
 
-                let response = ui.selectable_label(is_selected, egui::RichText::new(row_text).monospace());
+                let mut response = ui.selectable_label(is_selected, row);
+                if self.show_command_line {
+                    response = response.on_hover_text(&process.info.name);
+                }
 
                 if response.clicked() {
-                    self.selected_process = Some(i);
-                    self.selected_process_pid = Some(process.info.pid);
+                    self.selected_process = Some(process.key());
                 }
 
                 response.context_menu(|ui| {
-                    self.selected_process_pid = Some(process.info.pid);
+                    self.selected_process = Some(process.key());
 
                     if ui.button("Kill Process").clicked() {
-                        self.kill_process(process.info.pid);
+                        self.kill_process(&process.info);
                         ui.close_menu();
                     }
                     if ui.button("Kill Process Tree").clicked() {
-                        self.kill_process_tree(process.info.pid);
+                        self.kill_process_tree(&process.info);
                         ui.close_menu();
                     }
                     if ui.button("Open Process Folder").clicked() {
@@ -372,32 +1232,319 @@ impl ProcessMonitorApp {
                         ui.close_menu();
                     }
                     if ui.button("Restart Process").clicked() {
-                        self.restart_process(process.info.pid, &process.info.exe_path, &process.info.command_line);
+                        self.restart_process(&process.info);
+                        ui.close_menu();
+                    }
+                    if ui.button("Throttle Disk I/O...").clicked() {
+                        self.throttle_target = Some(process.info.pid);
+                        ui.close_menu();
+                    }
+                    if ui.button("Details...").clicked() {
+                        self.open_process_detail(process);
+                        ui.close_menu();
+                    }
+                    if process.info.status == ProcessStatus::Zombie
+                        && ui.button("Signal parent (SIGCHLD)").on_hover_text(REAP_EXPLANATION).clicked()
+                    {
+                        self.signal_zombie_parent(&process.info);
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    if ui.button("Copy Row").clicked() {
+                        ui.ctx().copy_text(process.clipboard_text());
                         ui.close_menu();
                     }
                 });
+
+                let threads = self.threads.as_ref().filter(|(key, _, _)| self.show_threads && *key == process.key());
+                if let Some((_, _, threads)) = threads {
+                    for thread in &threads.items {
+                        ui.label(
+                            egui::RichText::new(format_thread_line(&self.process_columns, thread, self.show_command_line))
+                                .monospace()
+                                .weak(),
+                        );
+                    }
+                    if threads.is_truncated() {
+                        ui.weak(format!("Threads: {}", threads.summary()));
+                    }
+                }
             }
         });
+
+        ui.separator();
+        ui.label(
+            egui::RichText::new(format!(
+                "Total of {} processes: CPU {:.1}%, memory {:.1} MB",
+                totals.count,
+                totals.cpu_usage,
+                totals.memory_usage as f64 / (1024.0 * 1024.0)
+            ))
+            .strong(),
+        );
+
+        if let Some(pid) = self.throttle_target {
+            egui::Window::new("Throttle Disk I/O")
+                .collapsible(false)
+                .resizable(false)
+                .show(ui.ctx(), |ui| {
+                    ui.label(format!("Cap the disk bandwidth of PID {} (0 = unlimited).", pid));
+                    ui.label("Moves the process into a cgroup below its own; requires root.");
+                    ui.label("0 for both lifts an existing throttle.");
+                    ui.add_space(10.0);
+
+                    egui::Grid::new("throttle_grid").num_columns(2).show(ui, |ui| {
+                        ui.label("Read MB/s:");
+                        ui.add(egui::DragValue::new(&mut self.throttle_read_mb).range(0..=10_000));
+                        ui.end_row();
+                        ui.label("Write MB/s:");
+                        ui.add(egui::DragValue::new(&mut self.throttle_write_mb).range(0..=10_000));
+                        ui.end_row();
+                    });
+                    ui.add_space(10.0);
+
+                    ui.horizontal(|ui| {
+                        if ui.button("Apply").clicked() {
+                            self.throttle_io(pid);
+                            self.throttle_target = None;
+                        }
+                        if ui.button("Cancel").clicked() {
+                            self.throttle_target = None;
+                        }
+                    });
+                });
+        }
+
+        self.draw_process_detail(ui.ctx());
+    }
+
+    /// Re-read the threads of the selected process on a background thread when the selection
+    /// changed or once per refresh interval
+    fn update_threads(&mut self) {
+        if self.threads_job.as_ref().is_some_and(|(_, job)| job.is_finished()) {
+            if let Some((key, job)) = self.threads_job.take() {
+                self.threads = Some((key, Instant::now(), job.join().unwrap_or_default()));
+            }
+        }
+        let Some(key) = self.selected_process else {
+            self.threads = None;
+            return;
+        };
+        let interval = self.refresh_schedule.read().interval();
+        let stale = self.threads.as_ref().is_none_or(|(cached, read, _)| *cached != key || read.elapsed() >= interval);
+        if stale && self.threads_job.is_none() {
+            let usage = self.monitor.read().thread_usage(key.pid);
+            let max_entries = self.detail_entries;
+            self.threads_job = Some((key, std::thread::spawn(move || read_threads(key.pid, &usage, max_entries))));
+        }
+    }
+
+    /// Read the detail of `process` on a background thread; listing the descriptors of a busy
+    /// server takes a while
+    fn open_process_detail(&mut self, process: &ProcessSnapshot) {
+        if self.detail_job.is_some() {
+            return;
+        }
+        let process = process.clone();
+        let user_threads = user_thread_count(&self.processes.read(), process.info.uid);
+        let (debug, max_entries) = (self.debug, self.detail_entries);
+        self.detail_job = Some(std::thread::spawn(move || {
+            let mut detail = ProcessDetail::load(&process, user_threads, max_entries);
+            if debug {
+                detail.load_raw_fields();
+            }
+            detail
+        }));
+    }
+
+    fn poll_detail_job(&mut self) {
+        if !self.detail_job.as_ref().is_some_and(|job| job.is_finished()) {
+            return;
+        }
+        if let Some(job) = self.detail_job.take() {
+            match job.join() {
+                Ok(detail) => self.process_detail = Some(detail),
+                Err(_) => self.status_message = "Loading process details panicked".to_string(),
+            }
+        }
+    }
+
+    /// Command line and environment wrapped in a scrollable window, however long they are
+    fn draw_process_detail(&mut self, ctx: &egui::Context) {
+        self.poll_detail_job();
+        if self.detail_job.is_some() {
+            // Keep polling while nothing else triggers a repaint
+            ctx.request_repaint_after(Duration::from_millis(100));
+        }
+        let Some(detail) = &self.process_detail else {
+            return;
+        };
+        let mut open = true;
+        let mut copy = false;
+        egui::Window::new(format!("{} (PID {})", detail.name, detail.pid))
+            .id(egui::Id::new("process_detail"))
+            .open(&mut open)
+            .default_size([700.0, 450.0])
+            .show(ctx, |ui| {
+                if ui.button("Copy Full").on_hover_text("Command line and environment, untruncated").clicked() {
+                    copy = true;
+                }
+                ui.separator();
+                egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+                    ui.strong("Command line");
+                    ui.add(egui::Label::new(egui::RichText::new(&detail.command_line).monospace()).wrap());
+                    ui.add_space(10.0);
+                    ui.strong("Page faults");
+                    ui.horizontal(|ui| {
+                        ui.label(format!("minor {}  major {}", detail.minor_faults, detail.major_faults));
+                        let delta = format!("(+{} since last refresh)", detail.major_faults_delta);
+                        if detail.major_faults_delta >= MAJOR_FAULT_SPIKE {
+                            ui.colored_label(egui::Color32::RED, delta);
+                        } else {
+                            ui.label(delta);
+                        }
+                    });
+                    if !detail.limits.is_empty() {
+                        ui.add_space(10.0);
+                        ui.strong("Limits");
+                        egui::Grid::new("process_limits").num_columns(4).striped(true).show(ui, |ui| {
+                            ui.strong("Limit");
+                            ui.strong("Soft");
+                            ui.strong("Hard");
+                            ui.strong("Used");
+                            ui.end_row();
+                            for limit in &detail.limits {
+                                let color = if limit.near_limit() { egui::Color32::RED } else { ui.visuals().text_color() };
+                                ui.colored_label(color, &limit.name);
+                                ui.colored_label(color, limit.soft.to_string());
+                                ui.colored_label(color, limit.hard.to_string());
+                                ui.colored_label(color, limit.used.map_or_else(String::new, |used| used.to_string()));
+                                ui.end_row();
+                            }
+                        });
+                    }
+                    if detail.open_files.total > 0 {
+                        ui.add_space(10.0);
+                        if detail.open_files.is_truncated() {
+                            ui.strong(format!("Open files ({})", detail.open_files.summary()));
+                        } else {
+                            ui.strong(format!("Open files ({})", detail.open_files.total));
+                        }
+                        egui::Grid::new("open_files").num_columns(2).striped(true).show(ui, |ui| {
+                            for (fd, target) in &detail.open_files.items {
+                                ui.monospace(fd.to_string());
+                                ui.monospace(target);
+                                ui.end_row();
+                            }
+                        });
+                    }
+                    ui.add_space(10.0);
+                    ui.strong("Environment");
+                    match &detail.environment {
+                        Some(environment) => {
+                            for entry in environment {
+                                ui.add(egui::Label::new(egui::RichText::new(entry).monospace()).wrap());
+                            }
+                        }
+                        None => {
+                            ui.label("Not readable (another user's process; run as root)");
+                        }
+                    }
+                    if !detail.raw_fields.is_empty() {
+                        ui.add_space(10.0);
+                        ui.strong("Raw /proc fields (--debug)");
+                        egui::Grid::new("raw_proc_fields").num_columns(4).striped(true).show(ui, |ui| {
+                            ui.strong("File");
+                            ui.strong("Field");
+                            ui.strong("Raw");
+                            ui.strong("Parsed");
+                            ui.end_row();
+                            for field in &detail.raw_fields {
+                                ui.label(field.source);
+                                ui.label(&field.name);
+                                ui.monospace(&field.raw);
+                                ui.label(&field.parsed);
+                                ui.end_row();
+                            }
+                        });
+                    }
+                });
+            });
+
+        if copy {
+            ctx.copy_text(detail.full_text());
+            self.status_message = "Copied command line and environment to clipboard".to_string();
+        }
+        if !open {
+            self.process_detail = None;
+        }
+    }
+
+    fn stop_service(&mut self, service_name: &str, mode: StopMode) {
+        let result = self.service_manager.read().stop_service_with_mode(service_name, mode);
+        self.status_message = match result {
+            Ok(_) => format!("Stopped service: {}", service_name),
+            Err(e) => format!("Failed to stop {}: {}", service_name, e),
+        };
+    }
+
+    fn throttle_io(&mut self, pid: u32) {
+        let to_bps = |mb: u64| (mb > 0).then(|| mb * 1024 * 1024);
+        let monitor = self.monitor.read();
+        let result = match (to_bps(self.throttle_read_mb), to_bps(self.throttle_write_mb)) {
+            (None, None) => monitor.remove_io_throttle(pid).map(|()| format!("Lifted the I/O throttle of PID {}", pid)),
+            (read_bps, write_bps) => monitor
+                .throttle_io(pid, read_bps, write_bps)
+                .map(|cgroup| format!("Throttled I/O of PID {} via {}", pid, cgroup.display())),
+        };
+        drop(monitor);
+        self.status_message = match result {
+            Ok(message) => message,
+            Err(e) => format!("Throttle failed: {}", e),
+        };
+    }
+
+    /// Services header label that sorts by `column`; clicking the current column flips the order
+    fn service_sort_header(&mut self, ui: &mut egui::Ui, column: ServiceSortColumn, label: &str) {
+        let active = self.service_sort_column == column;
+        let text = if active {
+            format!("{} {}", label, if self.service_sort_ascending { "↑" } else { "↓" })
+        } else {
+            label.to_string()
+        };
+        if ui.selectable_label(active, egui::RichText::new(text).strong().size(14.0)).clicked() {
+            if active {
+                self.service_sort_ascending = !self.service_sort_ascending;
+            } else {
+                self.service_sort_column = column;
+                self.service_sort_ascending = true;
+            }
+        }
     }
 
     fn draw_services_redesigned(&mut self, ui: &mut egui::Ui) {
         ui.heading("Services");
         ui.add_space(10.0);
 
+        if let Some(status) = self.services_backoff.read().status(Instant::now()) {
+            ui.colored_label(egui::Color32::from_rgb(255, 191, 0), status);
+            ui.add_space(10.0);
+        }
+
         let mut services = self.services.read().clone();
-        services.sort_by(|a, b| a.name.cmp(&b.name));
+        sort_services(&mut services, self.service_sort_column, self.service_sort_ascending);
 
-        // Header
+        // Header; the Name, State, PID and Memory labels sort the list when clicked
         ui.horizontal(|ui| {
-            ui.label(egui::RichText::new("Name").strong().size(14.0));
+            self.service_sort_header(ui, ServiceSortColumn::Name, "Name");
             ui.add_space(150.0);
-            ui.label(egui::RichText::new("State").strong().size(14.0));
+            self.service_sort_header(ui, ServiceSortColumn::State, "State");
             ui.add_space(60.0);
             ui.label(egui::RichText::new("Enabled").strong().size(14.0));
             ui.add_space(40.0);
-            ui.label(egui::RichText::new("PID").strong().size(14.0));
+            self.service_sort_header(ui, ServiceSortColumn::Pid, "PID");
             ui.add_space(60.0);
-            ui.label(egui::RichText::new("Memory (MB)").strong().size(14.0));
+            self.service_sort_header(ui, ServiceSortColumn::Memory, "Memory (MB)");
             ui.add_space(40.0);
             ui.label(egui::RichText::new("Description").strong().size(14.0));
         });
@@ -449,10 +1596,13 @@ impl ProcessMonitorApp {
                     }
 
                     if ui.button("Stop").clicked() {
-                        let sm = self.service_manager.read();
-                        match sm.stop_service(&service_name) {
-                            Ok(_) => self.status_message = format!("Stopped service: {}", service_name),
-                            Err(e) => self.status_message = format!("Failed to stop {}: {}", service_name, e),
+                        let dependents = self.service_manager.read().dependents(&service_name);
+                        match plan_stop(dependents) {
+                            Ok(StopPlan::Immediate) => self.stop_service(&service_name, StopMode::Default),
+                            Ok(StopPlan::NeedsConfirmation { dependents }) => {
+                                self.pending_stop = Some((service_name.clone(), dependents));
+                            }
+                            Err(e) => self.status_message = format!("{:#}", e),
                         }
                         ui.close_menu();
                     }
@@ -485,47 +1635,193 @@ impl ProcessMonitorApp {
                         }
                         ui.close_menu();
                     }
+
+                    ui.separator();
+
+                    if ui.button("Show Dependencies").clicked() {
+                        let sm = self.service_manager.read();
+                        match (sm.dependencies(&service_name), sm.dependents(&service_name)) {
+                            (Ok(dependencies), Ok(dependents)) => {
+                                self.service_dependency_view = Some((service_name.clone(), dependencies, dependents));
+                            }
+                            (Err(e), _) | (_, Err(e)) => self.status_message = format!("{:#}", e),
+                        }
+                        ui.close_menu();
+                    }
+
+                    if ui.button("Copy Row").clicked() {
+                        ui.ctx().copy_text(service.clipboard_text());
+                        ui.close_menu();
+                    }
                 });
             }
         });
+
+        let mut stop_choice = None;
+        if let Some((ref name, ref dependents)) = self.pending_stop {
+            egui::Window::new("Confirm Stop")
+                .collapsible(false)
+                .resizable(false)
+                .show(ui.ctx(), |ui| {
+                    ui.label(format!("{} other units depend on {}:", dependents.len(), name));
+                    egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                        for unit in dependents {
+                            ui.monospace(unit);
+                        }
+                    });
+                    ui.add_space(10.0);
+
+                    ui.horizontal(|ui| {
+                        if ui.button("Stop").clicked() {
+                            stop_choice = Some(Some(StopMode::Default));
+                        }
+                        if ui.button("Stop (replace irreversibly)")
+                            .on_hover_text("Use --job-mode=replace-irreversibly so queued jobs can't undo the stop")
+                            .clicked()
+                        {
+                            stop_choice = Some(Some(StopMode::ReplaceIrreversibly));
+                        }
+                        if ui.button("Cancel").clicked() {
+                            stop_choice = Some(None);
+                        }
+                    });
+                });
+        }
+        // Outer Some: a button was pressed; inner None: cancelled
+        if let Some(choice) = stop_choice {
+            if let Some((name, _)) = self.pending_stop.take() {
+                if let Some(mode) = choice {
+                    self.stop_service(&name, mode);
+                }
+            }
+        }
+
+        let mut close_dependencies = false;
+        if let Some((ref name, ref dependencies, ref dependents)) = self.service_dependency_view {
+            egui::Window::new(format!("Dependencies of {}", name))
+                .collapsible(false)
+                .show(ui.ctx(), |ui| {
+                    ui.columns(2, |columns| {
+                        columns[0].strong(format!("Required by ({})", dependents.len()));
+                        columns[0].label("Stopping this unit affects:");
+                        egui::ScrollArea::vertical().id_salt("dependents").max_height(300.0).show(&mut columns[0], |ui| {
+                            for unit in dependents {
+                                ui.monospace(unit);
+                            }
+                        });
+
+                        columns[1].strong(format!("Depends on ({})", dependencies.len()));
+                        columns[1].label("Units this one needs:");
+                        egui::ScrollArea::vertical().id_salt("dependencies").max_height(300.0).show(&mut columns[1], |ui| {
+                            for unit in dependencies {
+                                ui.monospace(unit);
+                            }
+                        });
+                    });
+                    ui.add_space(10.0);
+                    if ui.button("Close").clicked() {
+                        close_dependencies = true;
+                    }
+                });
+        }
+        if close_dependencies {
+            self.service_dependency_view = None;
+        }
+    }
+
+    fn kill_process(&mut self, info: &ProcessInfo) {
+        self.status_message = match kill_process(info, &self.protected) {
+            Ok(()) => format!("Sent kill signal to PID {}", info.pid),
+            Err(e) => format!("Cannot kill PID {}: {}", info.pid, e),
+        };
+    }
+
+    fn signal_zombie_parent(&mut self, zombie: &ProcessInfo) {
+        self.status_message = match signal_parent(zombie) {
+            Ok(parent) => format!(
+                "Sent SIGCHLD to parent PID {}; stop the parent if PID {} stays a zombie",
+                parent, zombie.pid
+            ),
+            Err(e) => format!("Signal parent failed: {}", e),
+        };
     }
 
-    fn kill_process(&mut self, pid: u32) {
-        let _ = std::process::Command::new("kill")
-            .arg(pid.to_string())
-            .output();
-        self.status_message = format!("Sent kill signal to PID {}", pid);
+    fn kill_process_tree(&mut self, info: &ProcessInfo) {
+        let result = kill_process_tree(info, &self.processes.read(), &self.protected);
+        self.status_message = match result {
+            Ok(()) => format!("Sent kill signal to PID {} and children", info.pid),
+            Err(e) => format!("Cannot kill PID {} and children: {}", info.pid, e),
+        };
     }
 
-    fn kill_process_tree(&mut self, pid: u32) {
-        let _ = std::process::Command::new("kill")
-            .arg("-TERM")
-            .arg("--")
-            .arg(format!("-{}", pid))
-            .output();
-        self.status_message = format!("Sent kill signal to PID {} and children", pid);
+    fn restart_process(&mut self, info: &ProcessInfo) {
+        self.status_message = match restart_process(info, &self.protected) {
+            Ok(()) => format!("Restarted PID {}", info.pid),
+            Err(e) => format!("Cannot restart PID {}: {}", info.pid, e),
+        };
     }
 
-    fn restart_process(&mut self, pid: u32, exe_path: &Option<std::path::PathBuf>, cmd_line: &[String]) {
-        // Kill first
-        let _ = std::process::Command::new("kill")
-            .arg(pid.to_string())
-            .output();
+    /// NFS/CIFS/SSHFS mounts apart from the local disks; `df` only runs on them when asked,
+    /// with a timeout, since it can hang on a dead server
+    fn draw_network_mounts(&mut self, ui: &mut egui::Ui) {
+        ui.group(|ui| {
+            ui.horizontal(|ui| {
+                ui.heading("Network Mounts");
+                if ui.button("Check Usage").clicked() {
+                    self.check_network_mounts();
+                }
+            });
+            egui::Grid::new("network_mounts").num_columns(4).striped(true).spacing([10.0, 5.0]).show(ui, |ui| {
+                ui.strong("Source");
+                ui.strong("Filesystem");
+                ui.strong("Used");
+                ui.strong("Mount Point");
+                ui.end_row();
 
-        // Wait a bit
-        std::thread::sleep(std::time::Duration::from_millis(100));
+                for mount in &self.network_mounts {
+                    ui.monospace(&mount.source);
+                    ui.label(&mount.filesystem);
+                    match mount.usage {
+                        Some((used, size)) => {
+                            let fraction = if size > 0 { (used as f64 / size as f64) as f32 } else { 0.0 };
+                            ui.add(egui::ProgressBar::new(fraction).desired_width(180.0).text(format!(
+                                "{:.2} / {:.2} GB",
+                                used as f64 / (1024.0 * 1024.0 * 1024.0),
+                                size as f64 / (1024.0 * 1024.0 * 1024.0)
+                            )));
+                        }
+                        None => {
+                            ui.label("not checked");
+                        }
+                    }
+                    ui.label(&mount.mount_point);
+                    ui.end_row();
+                }
+            });
+        });
+    }
 
-        // Restart
-        if let Some(exe) = exe_path {
-            let mut command = std::process::Command::new(exe);
-            if cmd_line.len() > 1 {
-                command.args(&cmd_line[1..]);
+    fn check_network_mounts(&mut self) {
+        let pm = self.partition_manager.read();
+        if let Ok(mut mounts) = pm.list_network_mounts() {
+            carry_over_usage(&mut mounts, &self.network_mounts);
+            self.network_mounts = mounts;
+        }
+        let mut failed = Vec::new();
+        for mount in &mut self.network_mounts {
+            match pm.check_network_mount(&mount.mount_point) {
+                Ok(usage) => mount.usage = Some(usage),
+                Err(e) => {
+                    mount.usage = None;
+                    failed.push(format!("{}: {}", mount.mount_point, e));
+                }
             }
-            let _ = command.spawn();
-            self.status_message = format!("Restarted PID {}", pid);
-        } else {
-            self.status_message = format!("Cannot restart PID {}: no executable path", pid);
         }
+        self.status_message = if failed.is_empty() {
+            format!("Checked {} network mounts", self.network_mounts.len())
+        } else {
+            format!("Network mount check failed for {}", failed.join(", "))
+        };
     }
 
     fn draw_partitions(&mut self, ui: &mut egui::Ui) {
@@ -544,13 +1840,61 @@ impl ProcessMonitorApp {
                     *self.disks.write() = disk_list;
                     self.status_message = "Disks refreshed".to_string();
                 }
+                if let Ok(mut mounts) = pm.list_network_mounts() {
+                    carry_over_usage(&mut mounts, &self.network_mounts);
+                    self.network_mounts = mounts;
+                }
             }
 
             ui.label("(Requires root for full partition management)");
         });
+        let can_modify = self.partition_manager.read().can_modify();
+
+        if let Some(status) = self.disks_backoff.read().status(Instant::now()) {
+            ui.add_space(10.0);
+            ui.colored_label(egui::Color32::from_rgb(255, 191, 0), status);
+        }
+
+        self.poll_fsck_job();
+        self.poll_benchmark_job();
+        if self.benchmark_job.is_some() {
+            ui.ctx().request_repaint_after(Duration::from_millis(250));
+        }
+        if let Some(job) = &self.fsck_job {
+            let progress = *job.progress.read();
+            let text = match progress.pass {
+                Some(pass) => format!("Checking {}: pass {} - {:.1}%", job.device, pass, progress.percent),
+                None => format!("Checking {}: {:.1}%", job.device, progress.percent),
+            };
+            ui.add_space(10.0);
+            ui.add(egui::ProgressBar::new(progress.percent / 100.0).text(text));
+        }
+
+        self.poll_image_job();
+        if let Some(job) = &self.image_job {
+            let fraction = if job.total_bytes > 0 {
+                (job.copied_bytes as f64 / job.total_bytes as f64).clamp(0.0, 1.0) as f32
+            } else {
+                0.0
+            };
+            let text = format!(
+                "Imaging {} to {}: {:.2} / {:.2} GB",
+                job.device,
+                job.out_path.display(),
+                job.copied_bytes as f64 / (1024.0 * 1024.0 * 1024.0),
+                job.total_bytes as f64 / (1024.0 * 1024.0 * 1024.0)
+            );
+            ui.add_space(10.0);
+            ui.add(egui::ProgressBar::new(fraction).text(text));
+        }
 
         ui.add_space(15.0);
 
+        if !self.network_mounts.is_empty() {
+            self.draw_network_mounts(ui);
+            ui.add_space(15.0);
+        }
+
         let disks = self.disks.read().clone();
 
         if disks.is_empty() {
@@ -558,9 +1902,13 @@ impl ProcessMonitorApp {
             return;
         }
 
+        self.handle_partition_keys(ui.ctx(), &disks);
+        ui.label("←/→: select disk | ↑/↓: select partition | Enter: expand/collapse");
+        ui.add_space(5.0);
+
         egui::ScrollArea::vertical().show(ui, |ui| {
             for (disk_idx, disk) in disks.iter().enumerate() {
-                let is_disk_selected = self.selected_disk == Some(disk_idx);
+                let is_disk_selected = self.disk_cursor.disk == Some(disk_idx);
 
                 ui.group(|ui| {
                     ui.horizontal(|ui| {
@@ -573,12 +1921,27 @@ impl ProcessMonitorApp {
                             )
                         );
                         if response.clicked() {
-                            self.selected_disk = Some(disk_idx);
-                            self.selected_partition = None;
+                            if is_disk_selected {
+                                self.disk_cursor.toggle_expanded();
+                            } else {
+                                self.disk_cursor.select_disk(disk_idx);
+                            }
+                        }
+
+                        if ui.button("Benchmark").on_hover_text("Read-only sequential read test (hdparm -t)").clicked() {
+                            self.benchmark_disk(&disk.device);
+                        }
+                        if let Some(result) = self.benchmark_results.get(&disk.device) {
+                            ui.label(format!("{:.1} MB/s", result.mb_per_sec));
+                        }
+                        if disk.removable
+                            && ui.button("Eject").on_hover_text("Unmount all partitions and power the disk off").clicked()
+                        {
+                            self.eject_disk(&disk.device);
                         }
                     });
 
-                    if is_disk_selected && !disk.partitions.is_empty() {
+                    if self.disk_cursor.is_expanded(disk_idx) && !disk.partitions.is_empty() {
                         ui.add_space(10.0);
                         ui.separator();
                         ui.add_space(10.0);
@@ -598,11 +1961,11 @@ impl ProcessMonitorApp {
                                 ui.end_row();
 
                                 for (part_idx, partition) in disk.partitions.iter().enumerate() {
-                                    let is_selected = self.selected_partition == Some(part_idx);
+                                    let is_selected = self.disk_cursor.partition == Some(part_idx);
 
                                     let response = ui.selectable_label(is_selected, &partition.device);
                                     if response.clicked() {
-                                        self.selected_partition = Some(part_idx);
+                                        self.disk_cursor.select_partition(disk_idx, part_idx);
                                     }
 
                                     ui.label(partition.filesystem.as_deref().unwrap_or("unknown"));
@@ -611,7 +1974,7 @@ impl ProcessMonitorApp {
 
                                     let used_gb = partition.used_bytes as f64 / (1024.0 * 1024.0 * 1024.0);
                                     let used_percent = if partition.size_bytes > 0 {
-                                        (partition.used_bytes as f64 / partition.size_bytes as f64 * 100.0)
+                                        partition.used_bytes as f64 / partition.size_bytes as f64 * 100.0
                                     } else {
                                         0.0
                                     };
@@ -620,21 +1983,67 @@ impl ProcessMonitorApp {
                                     ui.label(partition.mount_point.as_deref().unwrap_or("-"));
 
                                     ui.horizontal(|ui| {
-                                        if ui.button("Format").clicked() {
+                                        if ui
+                                            .add_enabled(can_modify, egui::Button::new("Format"))
+                                            .on_disabled_hover_text(REQUIRES_ROOT)
+                                            .clicked()
+                                        {
                                             self.show_format_dialog = true;
-                                            self.selected_disk = Some(disk_idx);
-                                            self.selected_partition = Some(part_idx);
+                                            self.disk_cursor.select_partition(disk_idx, part_idx);
                                         }
 
-                                        if ui.button("Delete").clicked() {
+                                        if ui
+                                            .add_enabled(can_modify, egui::Button::new("Delete"))
+                                            .on_disabled_hover_text(REQUIRES_ROOT)
+                                            .clicked()
+                                        {
                                             self.show_delete_confirm = true;
-                                            self.selected_disk = Some(disk_idx);
-                                            self.selected_partition = Some(part_idx);
+                                            self.disk_cursor.select_partition(disk_idx, part_idx);
                                         }
 
-                                        if partition.filesystem.is_some() && ui.button("Check").clicked() {
+                                        if partition.filesystem.as_deref() == Some("swap") {
+                                            let label = if partition.is_active_swap() { "Swap Off" } else { "Swap On" };
+                                            if ui.button(label).clicked() {
+                                                self.toggle_swap(partition);
+                                            }
+                                        } else if partition.filesystem.is_some()
+                                            && ui
+                                                .add_enabled(can_modify, egui::Button::new("Check"))
+                                                .on_disabled_hover_text(REQUIRES_ROOT)
+                                                .clicked()
+                                        {
                                             self.check_partition(disk_idx, part_idx);
                                         }
+
+                                        let temp_mount = partition
+                                            .mount_point
+                                            .as_deref()
+                                            .is_some_and(|mount_point| is_temp_mount_point(std::path::Path::new(mount_point)));
+                                        if temp_mount && ui.button("Unmount").clicked() {
+                                            self.unmount_temp(partition);
+                                        }
+
+                                        if let Some(mount_point) = &partition.mount_point {
+                                            if ui.button("Disk Usage").on_hover_text("Largest directories (du -x)").clicked() {
+                                                self.open_directory_usage(PathBuf::from(mount_point));
+                                            }
+                                        }
+
+                                        if ui
+                                            .add_enabled(can_modify, egui::Button::new("Create Image"))
+                                            .on_hover_text("Copy the partition to an image file (dd)")
+                                            .on_disabled_hover_text(REQUIRES_ROOT)
+                                            .clicked()
+                                        {
+                                            self.open_image_dialog(partition);
+                                        }
+
+                                        if let Some(number) = partition.partition_number {
+                                            if ui.button("Set Type").clicked() {
+                                                self.type_code = COMMON_PARTITION_TYPES[0].code.to_string();
+                                                self.type_target = Some((disk.device.clone(), number, partition.device.clone()));
+                                            }
+                                        }
                                     });
 
                                     ui.end_row();
@@ -647,6 +2056,8 @@ impl ProcessMonitorApp {
             }
         });
 
+        self.draw_directory_usage(ui.ctx());
+
         // Format dialog
         if self.show_format_dialog {
             egui::Window::new("Format Partition")
@@ -668,18 +2079,130 @@ impl ProcessMonitorApp {
                                 ui.selectable_value(&mut self.format_filesystem, "ntfs".to_string(), "ntfs");
                                 ui.selectable_value(&mut self.format_filesystem, "fat32".to_string(), "fat32");
                                 ui.selectable_value(&mut self.format_filesystem, "f2fs".to_string(), "f2fs");
+                                ui.selectable_value(&mut self.format_filesystem, "swap".to_string(), "swap");
                             });
                     });
 
+                    if self.format_filesystem != "swap" {
+                        ui.checkbox(&mut self.format_auto_mount, format!("Mount under {} afterwards", TEMP_MOUNT_ROOT));
+                    }
+
                     ui.add_space(10.0);
 
                     ui.horizontal(|ui| {
-                        if ui.button("Format").clicked() {
+                        if hold_button(ui, "Hold to Format", &mut self.format_hold) {
                             self.format_partition();
                             self.show_format_dialog = false;
                         }
                         if ui.button("Cancel").clicked() {
                             self.show_format_dialog = false;
+                            self.format_hold.reset();
+                        }
+                    });
+                });
+        }
+
+        // Offer to persist a mount for a freshly formatted partition
+        if let Some((device, filesystem)) = self.fstab_candidate.clone() {
+            egui::Window::new("Add to fstab")
+                .collapsible(false)
+                .resizable(false)
+                .show(ui.ctx(), |ui| {
+                    ui.label(format!("Mount {} ({}) at boot?", device, filesystem));
+                    ui.add_space(10.0);
+
+                    egui::Grid::new("fstab_entry").num_columns(2).show(ui, |ui| {
+                        ui.label("Mount point:");
+                        ui.text_edit_singleline(&mut self.fstab_mount_point);
+                        ui.end_row();
+
+                        ui.label("Options:");
+                        ui.text_edit_singleline(&mut self.fstab_options);
+                        ui.end_row();
+                    });
+
+                    ui.add_space(10.0);
+
+                    ui.horizontal(|ui| {
+                        if ui.button("Add to fstab").clicked() {
+                            self.add_to_fstab(&device, &filesystem);
+                            self.fstab_candidate = None;
+                        }
+                        if ui.button("Skip").clicked() {
+                            self.fstab_candidate = None;
+                        }
+                    });
+                });
+        }
+
+        // Create image dialog
+        if let Some((device, _)) = self.image_source.clone() {
+            egui::Window::new("Create Image")
+                .collapsible(false)
+                .resizable(false)
+                .show(ui.ctx(), |ui| {
+                    ui.label(format!("Copy {} to an image file", device));
+                    ui.add_space(10.0);
+
+                    ui.horizontal(|ui| {
+                        ui.label("Image file:");
+                        ui.text_edit_singleline(&mut self.image_out_path);
+                    });
+                    ui.checkbox(&mut self.image_force, "Image even if mounted read-write")
+                        .on_hover_text("Files written during the copy can leave the image inconsistent");
+
+                    ui.add_space(10.0);
+
+                    ui.horizontal(|ui| {
+                        if ui.button("Create Image").clicked() {
+                            self.image_partition();
+                        }
+                        if ui.button("Cancel").clicked() {
+                            self.image_source = None;
+                        }
+                    });
+                });
+        }
+
+        // Partition type dialog
+        if let Some((disk_device, number, device)) = self.type_target.clone() {
+            egui::Window::new("Set Partition Type")
+                .collapsible(false)
+                .resizable(false)
+                .show(ui.ctx(), |ui| {
+                    ui.label(format!("Partition type of {}", device));
+                    ui.add_space(10.0);
+
+                    let selected = COMMON_PARTITION_TYPES
+                        .iter()
+                        .find(|partition_type| partition_type.code.eq_ignore_ascii_case(self.type_code.trim()))
+                        .map_or("Custom".to_string(), |partition_type| format!("{} {}", partition_type.code, partition_type.name));
+                    egui::ComboBox::from_label("Common types")
+                        .selected_text(selected)
+                        .show_ui(ui, |ui| {
+                            for partition_type in COMMON_PARTITION_TYPES {
+                                let label = format!("{} {}", partition_type.code, partition_type.name);
+                                ui.selectable_value(&mut self.type_code, partition_type.code.to_string(), label);
+                            }
+                        });
+                    ui.horizontal(|ui| {
+                        ui.label("Type code or GUID:");
+                        ui.text_edit_singleline(&mut self.type_code);
+                    });
+
+                    ui.add_space(10.0);
+
+                    ui.horizontal(|ui| {
+                        if ui.button("Apply").clicked() {
+                            let result = self.partition_manager.read().set_partition_type(&disk_device, number, &self.type_code);
+                            self.status_message = match result {
+                                Ok(()) => format!("Set type of {} to {}", device, self.type_code.trim().to_ascii_uppercase()),
+                                Err(e) => format!("Type change failed: {}", e),
+                            };
+                            self.type_target = None;
+                        }
+                        if ui.button("Cancel").clicked() {
+                            self.type_target = None;
                         }
                     });
                 });
@@ -696,20 +2219,131 @@ impl ProcessMonitorApp {
                     ui.add_space(10.0);
 
                     ui.horizontal(|ui| {
-                        if ui.button("Delete").clicked() {
+                        if hold_button(ui, "Hold to Delete", &mut self.delete_hold) {
                             self.delete_partition();
                             self.show_delete_confirm = false;
                         }
                         if ui.button("Cancel").clicked() {
                             self.show_delete_confirm = false;
+                            self.delete_hold.reset();
                         }
                     });
                 });
         }
     }
 
+    /// Arrow keys and Enter move through disks and partitions, as in the TUI
+    fn handle_partition_keys(&mut self, ctx: &egui::Context, disks: &[Disk]) {
+        let dialog_open = self.show_format_dialog
+            || self.show_delete_confirm
+            || self.fstab_candidate.is_some()
+            || self.image_source.is_some()
+            || self.type_target.is_some();
+        if dialog_open || ctx.wants_keyboard_input() {
+            return;
+        }
+
+        let (left, right, up, down, enter) = ctx.input(|i| {
+            (
+                i.key_pressed(egui::Key::ArrowLeft),
+                i.key_pressed(egui::Key::ArrowRight),
+                i.key_pressed(egui::Key::ArrowUp),
+                i.key_pressed(egui::Key::ArrowDown),
+                i.key_pressed(egui::Key::Enter),
+            )
+        });
+        if left {
+            self.disk_cursor.previous_disk(disks.len());
+        }
+        if right {
+            self.disk_cursor.next_disk(disks.len());
+        }
+        let partition_count = self.disk_cursor.disk.and_then(|disk| disks.get(disk)).map_or(0, |disk| disk.partitions.len());
+        if up {
+            self.disk_cursor.previous_partition(partition_count);
+        }
+        if down {
+            self.disk_cursor.next_partition(partition_count);
+        }
+        if enter {
+            self.disk_cursor.toggle_expanded();
+        }
+    }
+
+    fn open_image_dialog(&mut self, partition: &Partition) {
+        let dir = std::env::current_dir().unwrap_or_default();
+        let file_name = default_image_file_name(&partition.device, chrono::Local::now());
+        self.image_out_path = dir.join(file_name).display().to_string();
+        self.image_force = false;
+        self.image_source = Some((partition.device.clone(), partition.size_bytes));
+    }
+
+    /// Start copying the partition of the open "Create Image" dialog; the dialog stays open on refusal
+    fn image_partition(&mut self) {
+        if self.image_job.is_some() {
+            self.status_message = "An image is already being created".to_string();
+            return;
+        }
+        let Some((device, total_bytes)) = self.image_source.clone() else {
+            return;
+        };
+
+        let out_path = PathBuf::from(self.image_out_path.trim());
+        let result = self.partition_manager.read().image_partition(&device, &out_path, self.image_force);
+        match result {
+            Ok(progress) => {
+                self.status_message = format!("Imaging {} to {}...", device, out_path.display());
+                self.image_job = Some(ImageJob { device, out_path, total_bytes, copied_bytes: 0, progress });
+                self.image_source = None;
+            }
+            Err(e) => {
+                self.status_message = format!("Image failed: {}", e);
+            }
+        }
+    }
+
+    fn poll_image_job(&mut self) {
+        let Some(job) = self.image_job.as_mut() else {
+            return;
+        };
+
+        let finished = loop {
+            match job.progress.try_recv() {
+                Ok(ImageProgress::Copied(bytes)) => job.copied_bytes = bytes,
+                Ok(ImageProgress::Done(bytes)) => {
+                    break Some(format!(
+                        "Imaged {} to {} ({:.2} GB)",
+                        job.device,
+                        job.out_path.display(),
+                        bytes as f64 / (1024.0 * 1024.0 * 1024.0)
+                    ));
+                }
+                Ok(ImageProgress::Failed(error)) => break Some(format!("Image of {} failed: {}", job.device, error)),
+                Err(TryRecvError::Empty) => break None,
+                Err(TryRecvError::Disconnected) => break Some(format!("Image of {} stopped unexpectedly", job.device)),
+            }
+        };
+
+        if let Some(message) = finished {
+            self.status_message = message;
+            self.image_job = None;
+        }
+    }
+
+    /// Unmount a partition mounted under `/mnt/procmon` and remove its directory
+    fn unmount_temp(&mut self, partition: &Partition) {
+        let Some(mount_point) = partition.mount_point.as_deref() else {
+            return;
+        };
+        let result = self.partition_manager.read().unmount_temp(std::path::Path::new(mount_point));
+        self.status_message = match result {
+            Ok(()) => format!("Unmounted {} and removed {}", partition.device, mount_point),
+            Err(e) => format!("Unmount failed: {}", e),
+        };
+    }
+
     fn format_partition(&mut self) {
-        if let (Some(disk_idx), Some(part_idx)) = (self.selected_disk, self.selected_partition) {
+        if let (Some(disk_idx), Some(part_idx)) = (self.disk_cursor.disk, self.disk_cursor.partition) {
             let disks = self.disks.read();
             if let Some(disk) = disks.get(disk_idx) {
                 if let Some(partition) = disk.partitions.get(part_idx) {
@@ -720,6 +2354,17 @@ impl ProcessMonitorApp {
                                 "Successfully formatted {} as {}",
                                 partition.device, self.format_filesystem
                             );
+                            if self.format_auto_mount && self.format_filesystem != "swap" {
+                                let label = pm.get_partition_label(&partition.device);
+                                match pm.mount_temp(&partition.device, label.as_deref()) {
+                                    Ok(mount_point) => {
+                                        self.status_message.push_str(&format!(", mounted at {}", mount_point.display()))
+                                    }
+                                    Err(e) => self.status_message.push_str(&format!(", but mounting failed: {}", e)),
+                                }
+                            }
+                            self.fstab_mount_point = format!("/mnt/{}", partition.device.trim_start_matches("/dev/"));
+                            self.fstab_candidate = Some((partition.device.clone(), self.format_filesystem.clone()));
                         }
                         Err(e) => {
                             self.status_message = format!("Format failed: {}", e);
@@ -730,8 +2375,107 @@ impl ProcessMonitorApp {
         }
     }
 
+    /// Spinner plus time since the last successful refresh; amber once collection stalls
+    fn draw_refresh_indicator(&self, ui: &mut egui::Ui) {
+        if ui.button("Refresh now").on_hover_text("Collect new data immediately (F5)").clicked() {
+            self.refresh_schedule.write().request_now();
+        }
+
+        let since = self.last_refresh.read().elapsed();
+        match staleness(since, self.refresh_schedule.read().interval()) {
+            Staleness::Live => {
+                ui.label(format!("{:.1}s ago", since.as_secs_f32()));
+                ui.spinner();
+            }
+            Staleness::Stale => {
+                ui.colored_label(egui::Color32::from_rgb(255, 191, 0), format!("Stalled: {:.1}s since last update", since.as_secs_f32()));
+            }
+        }
+    }
+
+    /// Copy the selected process or partition row (`y`)
+    fn copy_selected(&mut self, ctx: &egui::Context) {
+        let text = match self.selected_tab {
+            1 => self.selected_process.and_then(|key| {
+                self.processes.read().iter().find(|p| p.key() == key).map(|p| p.clipboard_text())
+            }),
+            5 => match (self.disk_cursor.disk, self.disk_cursor.partition) {
+                (Some(disk_idx), Some(part_idx)) => self
+                    .disks
+                    .read()
+                    .get(disk_idx)
+                    .and_then(|disk| disk.partitions.get(part_idx))
+                    .map(|p| p.clipboard_text()),
+                _ => None,
+            },
+            _ => None,
+        };
+
+        if let Some(text) = text {
+            ctx.copy_text(text);
+            self.status_message = "Copied selection to clipboard".to_string();
+        }
+    }
+
+    fn benchmark_disk(&mut self, device: &str) {
+        if let Some(job) = &self.benchmark_job {
+            self.status_message = format!("Benchmark of {} is still running", job.device);
+            return;
+        }
+        let partition_manager = self.partition_manager.read().clone();
+        let thread_device = device.to_string();
+        let handle = std::thread::spawn(move || partition_manager.benchmark_read(&thread_device));
+        self.status_message = format!("Benchmarking {}...", device);
+        self.benchmark_job = Some(BenchmarkJob { device: device.to_string(), handle });
+    }
+
+    fn poll_benchmark_job(&mut self) {
+        if !self.benchmark_job.as_ref().is_some_and(|job| job.handle.is_finished()) {
+            return;
+        }
+        if let Some(job) = self.benchmark_job.take() {
+            self.status_message = match job.handle.join() {
+                Ok(Ok(result)) => {
+                    let message = format!("{}: {:.1} MB/s sequential read", job.device, result.mb_per_sec);
+                    self.benchmark_results.insert(job.device, result);
+                    message
+                }
+                Ok(Err(e)) => format!("Benchmark failed: {}", e),
+                Err(_) => format!("Benchmark of {} panicked", job.device),
+            };
+        }
+    }
+
+    fn eject_disk(&mut self, device: &str) {
+        let pm = self.partition_manager.read();
+        self.status_message = match pm.eject(device) {
+            Ok(()) => format!("Ejected {}; it can be unplugged now", device),
+            Err(e) => format!("Eject failed: {}", e),
+        };
+        if let Ok(disk_list) = pm.list_disks() {
+            *self.disks.write() = disk_list;
+        }
+    }
+
+    fn add_to_fstab(&mut self, device: &str, filesystem: &str) {
+        let pm = self.partition_manager.read();
+        let Some(uuid) = pm.get_partition_uuid(device) else {
+            self.status_message = format!("Could not read UUID of {}", device);
+            return;
+        };
+
+        match pm.append_to_fstab(&uuid, &self.fstab_mount_point, filesystem, Some(&self.fstab_options)) {
+            Ok(_) => {
+                self.status_message = format!("Added {} to /etc/fstab at {}", device, self.fstab_mount_point);
+            }
+            Err(e) => {
+                self.status_message = format!("fstab update failed: {}", e);
+            }
+        }
+    }
+
     fn delete_partition(&mut self) {
-        if let (Some(disk_idx), Some(part_idx)) = (self.selected_disk, self.selected_partition) {
+        if let (Some(disk_idx), Some(part_idx)) = (self.disk_cursor.disk, self.disk_cursor.partition) {
             let disks = self.disks.read();
             if let Some(disk) = disks.get(disk_idx) {
                 if let Some(partition) = disk.partitions.get(part_idx) {
@@ -751,26 +2495,184 @@ impl ProcessMonitorApp {
         }
     }
 
+    fn toggle_swap(&mut self, partition: &Partition) {
+        let pm = self.partition_manager.read();
+        let (result, action) = if partition.is_active_swap() {
+            (pm.swapoff(&partition.device), "Disabled")
+        } else {
+            (pm.swapon(&partition.device), "Enabled")
+        };
+
+        match result {
+            Ok(_) => {
+                self.status_message = format!("{} swap on {}", action, partition.device);
+                if let Ok(memory) = self.monitor.read().refresh_memory_metrics() {
+                    self.system_metrics.write().memory = memory;
+                }
+                if let Ok(disk_list) = pm.list_disks() {
+                    *self.disks.write() = disk_list;
+                }
+            }
+            Err(e) => {
+                self.status_message = format!("Swap change failed: {}", e);
+            }
+        }
+    }
+
     fn check_partition(&mut self, disk_idx: usize, part_idx: usize) {
+        if self.fsck_job.is_some() {
+            self.status_message = "A filesystem check is already running".to_string();
+            return;
+        }
+
         let disks = self.disks.read();
         if let Some(disk) = disks.get(disk_idx) {
             if let Some(partition) = disk.partitions.get(part_idx) {
-                if let Some(ref fs) = partition.filesystem {
-                    let pm = self.partition_manager.read();
-                    match pm.check_filesystem(&partition.device, fs, false) {
-                        Ok(_) => {
-                            self.status_message = format!("Filesystem check completed for {}", partition.device);
-                        }
-                        Err(e) => {
-                            self.status_message = format!("Check failed: {}", e);
-                        }
-                    }
+                if let Some(fs) = partition.filesystem.clone() {
+                    let device = partition.device.clone();
+                    let progress = Arc::new(RwLock::new(FsckProgress { pass: None, percent: 0.0 }));
+                    let progress_clone = progress.clone();
+                    let thread_device = device.clone();
+                    let partition_manager = self.partition_manager.read().clone();
+
+                    let handle = std::thread::spawn(move || {
+                        partition_manager.check_filesystem_with_progress(&thread_device, &fs, false, |p| {
+                            *progress_clone.write() = p;
+                        })
+                    });
+
+                    self.status_message = format!("Checking {}...", device);
+                    self.fsck_job = Some(FsckJob { device, progress, handle });
                 }
             }
         }
     }
 
+    fn poll_fsck_job(&mut self) {
+        if !self.fsck_job.as_ref().is_some_and(|job| job.handle.is_finished()) {
+            return;
+        }
+
+        if let Some(job) = self.fsck_job.take() {
+            self.status_message = match job.handle.join() {
+                Ok(Ok(_)) => format!("Filesystem check completed for {}", job.device),
+                Ok(Err(e)) => format!("Check failed: {}", e),
+                Err(_) => format!("Check of {} panicked", job.device),
+            };
+        }
+    }
+
+    fn open_directory_usage(&mut self, mount_point: PathBuf) {
+        self.directory_usage = Some(DirectoryUsage {
+            mount_point: mount_point.clone(),
+            path: mount_point.clone(),
+            entries: Vec::new(),
+            error: None,
+            job: None,
+        });
+        self.scan_directory_usage(mount_point);
+    }
+
+    fn scan_directory_usage(&mut self, path: PathBuf) {
+        let Some(usage) = &mut self.directory_usage else {
+            return;
+        };
+        usage.entries.clear();
+        usage.error = None;
+        // Replacing a running scan kills it
+        usage.job = None;
+        match self.partition_manager.read().scan_directories(&path, DIRECTORY_USAGE_DEPTH) {
+            Ok(scan) => usage.job = Some(scan),
+            Err(e) => usage.error = Some(e.to_string()),
+        }
+        usage.path = path;
+    }
+
+    fn poll_directory_usage(&mut self) {
+        let Some(usage) = &mut self.directory_usage else {
+            return;
+        };
+        let Some(result) = usage.job.as_mut().and_then(|job| job.poll()) else {
+            return;
+        };
+        usage.job = None;
+        match result {
+            Ok(entries) => usage.entries = entries,
+            Err(e) => usage.error = Some(e.to_string()),
+        }
+    }
+
+    /// Largest directories below the drill-down path; clicking one scans its subdirectories
+    fn draw_directory_usage(&mut self, ctx: &egui::Context) {
+        self.poll_directory_usage();
+        let Some(usage) = &self.directory_usage else {
+            return;
+        };
+        let mut open = true;
+        let mut scan = None;
+        egui::Window::new(format!("Disk Usage: {}", usage.path.display()))
+            .id(egui::Id::new("directory_usage"))
+            .open(&mut open)
+            .default_size([600.0, 400.0])
+            .show(ctx, |ui| {
+                let scanning = usage.job.is_some();
+                ui.horizontal(|ui| {
+                    let at_top = usage.path == usage.mount_point;
+                    if ui.add_enabled(!scanning && !at_top, egui::Button::new("Up")).clicked() {
+                        scan = usage.path.parent().map(std::path::Path::to_path_buf);
+                    }
+                    if scanning {
+                        ui.spinner();
+                        ui.label("Scanning with du -x...");
+                    }
+                });
+                if let Some(error) = &usage.error {
+                    ui.colored_label(egui::Color32::RED, error);
+                }
+                ui.separator();
+
+                let total: u64 = usage.entries.iter().map(|(_, size)| size).sum();
+                egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+                    egui::Grid::new("directory_usage_entries").num_columns(3).striped(true).show(ui, |ui| {
+                        ui.strong("Size");
+                        ui.strong("Share");
+                        ui.strong("Directory");
+                        ui.end_row();
+                        for (path, size) in &usage.entries {
+                            let fraction = if total > 0 { *size as f64 / total as f64 } else { 0.0 };
+                            ui.label(format!("{:.2} GB", *size as f64 / (1024.0 * 1024.0 * 1024.0)));
+                            ui.add(egui::ProgressBar::new(fraction as f32).desired_width(120.0).text(format!("{:.1}%", fraction * 100.0)));
+                            let name = path.strip_prefix(&usage.path).unwrap_or(path).display().to_string();
+                            if ui.add_enabled(!scanning, egui::Link::new(name)).clicked() {
+                                scan = Some(path.clone());
+                            }
+                            ui.end_row();
+                        }
+                    });
+                });
+            });
+
+        if !open {
+            self.directory_usage = None;
+        } else if let Some(path) = scan {
+            self.scan_directory_usage(path);
+        }
+    }
+
     fn draw_storage(&mut self, ui: &mut egui::Ui) {
+        if self.deleted_files_job.as_ref().is_some_and(|job| job.is_finished()) {
+            if let Some(job) = self.deleted_files_job.take() {
+                match job.join() {
+                    Ok(files) => self.deleted_files = files,
+                    Err(_) => self.status_message = "Scanning for deleted open files panicked".to_string(),
+                }
+            }
+        }
+        let scan_due = self.deleted_files_scanned.is_none_or(|at| at.elapsed() >= DELETED_FILES_SCAN_INTERVAL);
+        if scan_due && self.deleted_files_job.is_none() {
+            self.deleted_files_job = Some(std::thread::spawn(|| scan_deleted_open_files(std::path::Path::new("/proc"))));
+            self.deleted_files_scanned = Some(Instant::now());
+        }
         let metrics = self.system_metrics.read();
         let processes = self.processes.read().clone();
 
@@ -791,19 +2693,30 @@ impl ProcessMonitorApp {
                     ui.strong(name);
                     ui.add_space(5.0);
 
-                    // Read/Write bars
+                    // Read/Write rate bars, scaled to the device's peak throughput
+                    self.disk_scale.observe(name, &[disk_metrics.read_bytes_per_sec, disk_metrics.write_bytes_per_sec]);
+                    let scale_mb = self.disk_scale.denominator(name) as f64 / (1024.0 * 1024.0);
+
                     ui.horizontal(|ui| {
                         ui.label("Read:");
                         let read_mb = disk_metrics.read_bytes as f64 / (1024.0 * 1024.0);
-                        ui.add(egui::ProgressBar::new(((read_mb / 10000.0).min(1.0)) as f32)
-                            .text(format!("{:.2} MB ({} ops)", read_mb, disk_metrics.read_ops)));
+                        let rate_mb = disk_metrics.read_bytes_per_sec as f64 / (1024.0 * 1024.0);
+                        ui.add(egui::ProgressBar::new(self.disk_scale.fraction(name, disk_metrics.read_bytes_per_sec))
+                            .text(format!(
+                                "{:.2} / {:.2} MB/s (total {:.2} MB, {} ops)",
+                                rate_mb, scale_mb, read_mb, disk_metrics.read_ops
+                            )));
                     });
 
                     ui.horizontal(|ui| {
                         ui.label("Write:");
                         let write_mb = disk_metrics.write_bytes as f64 / (1024.0 * 1024.0);
-                        ui.add(egui::ProgressBar::new(((write_mb / 10000.0).min(1.0)) as f32)
-                            .text(format!("{:.2} MB ({} ops)", write_mb, disk_metrics.write_ops)));
+                        let rate_mb = disk_metrics.write_bytes_per_sec as f64 / (1024.0 * 1024.0);
+                        ui.add(egui::ProgressBar::new(self.disk_scale.fraction(name, disk_metrics.write_bytes_per_sec))
+                            .text(format!(
+                                "{:.2} / {:.2} MB/s (total {:.2} MB, {} ops)",
+                                rate_mb, scale_mb, write_mb, disk_metrics.write_ops
+                            )));
                     });
                 });
                 ui.add_space(10.0);
@@ -818,11 +2731,7 @@ impl ProcessMonitorApp {
             ui.add_space(10.0);
 
             let mut sorted_processes = processes.clone();
-            sorted_processes.sort_by(|a, b| {
-                let a_io = a.stats.disk_read_bytes + a.stats.disk_write_bytes;
-                let b_io = b.stats.disk_read_bytes + b.stats.disk_write_bytes;
-                b_io.cmp(&a_io)
-            });
+            sort_processes(&mut sorted_processes, SortColumn::DiskIo, false);
 
             egui::Grid::new("disk_io_processes")
                 .num_columns(5)
@@ -853,6 +2762,54 @@ impl ProcessMonitorApp {
                         ui.end_row();
                     }
                 });
+
+            ui.add_space(20.0);
+            ui.separator();
+            ui.add_space(20.0);
+
+            // Space that `du` can't see: removed files a process still has open
+            let (file_count, held) = space_held(&self.deleted_files);
+            ui.horizontal(|ui| {
+                ui.heading("Deleted but Open Files");
+                if ui.button("Rescan").clicked() {
+                    self.deleted_files_scanned = None;
+                }
+            });
+            if self.deleted_files.is_empty() {
+                ui.label("No deleted files are held open (only your own processes are visible unless run as root)");
+            } else {
+                ui.label(format!(
+                    "{:.1} MB held by {} files; restart or stop the process to free it",
+                    held as f64 / (1024.0 * 1024.0),
+                    file_count
+                ));
+                ui.add_space(5.0);
+                egui::Grid::new("deleted_open_files")
+                    .num_columns(5)
+                    .striped(true)
+                    .spacing([10.0, 5.0])
+                    .show(ui, |ui| {
+                        ui.strong("PID");
+                        ui.strong("Name");
+                        ui.strong("FD");
+                        ui.strong("Size (MB)");
+                        ui.strong("Path");
+                        ui.end_row();
+
+                        for file in self.deleted_files.iter().take(50) {
+                            let name = processes
+                                .iter()
+                                .find(|p| p.info.pid == file.pid)
+                                .map_or("?", |p| p.info.name.as_str());
+                            ui.label(file.pid.to_string());
+                            ui.label(name);
+                            ui.label(file.fd.to_string());
+                            ui.label(format!("{:.2}", file.size as f64 / (1024.0 * 1024.0)));
+                            ui.monospace(file.path.display().to_string());
+                            ui.end_row();
+                        }
+                    });
+            }
         });
     }
 
@@ -871,21 +2828,39 @@ impl ProcessMonitorApp {
             for (name, net_metrics) in &metrics.network {
                 ui.group(|ui| {
                     ui.strong(name);
+                    for address in &net_metrics.addresses {
+                        let text = egui::RichText::new(format_address(address)).monospace();
+                        match AddressScope::of(address) {
+                            AddressScope::Global => ui.label(text),
+                            AddressScope::LinkLocal | AddressScope::Loopback => ui.label(text.weak()),
+                        };
+                    }
                     ui.add_space(5.0);
 
-                    // Received/Sent bars
+                    // Received/Sent rate bars, scaled to the link speed or the interface's peak
+                    self.network_scale.observe_interface(name, net_metrics.rx_bytes_per_sec, net_metrics.tx_bytes_per_sec);
+                    let scale_mb = self.network_scale.denominator(name) as f64 / (1024.0 * 1024.0);
+
                     ui.horizontal(|ui| {
                         ui.label("Received:");
                         let recv_mb = net_metrics.bytes_received as f64 / (1024.0 * 1024.0);
-                        ui.add(egui::ProgressBar::new(((recv_mb / 10000.0).min(1.0)) as f32)
-                            .text(format!("{:.2} MB ({} packets)", recv_mb, net_metrics.packets_received)));
+                        let rate_mb = net_metrics.rx_bytes_per_sec as f64 / (1024.0 * 1024.0);
+                        ui.add(egui::ProgressBar::new(self.network_scale.fraction(name, net_metrics.rx_bytes_per_sec))
+                            .text(format!(
+                                "{:.2} / {:.2} MB/s (total {:.2} MB, {} packets)",
+                                rate_mb, scale_mb, recv_mb, net_metrics.packets_received
+                            )));
                     });
 
                     ui.horizontal(|ui| {
                         ui.label("Sent:");
                         let sent_mb = net_metrics.bytes_sent as f64 / (1024.0 * 1024.0);
-                        ui.add(egui::ProgressBar::new(((sent_mb / 10000.0).min(1.0)) as f32)
-                            .text(format!("{:.2} MB ({} packets)", sent_mb, net_metrics.packets_sent)));
+                        let rate_mb = net_metrics.tx_bytes_per_sec as f64 / (1024.0 * 1024.0);
+                        ui.add(egui::ProgressBar::new(self.network_scale.fraction(name, net_metrics.tx_bytes_per_sec))
+                            .text(format!(
+                                "{:.2} / {:.2} MB/s (total {:.2} MB, {} packets)",
+                                rate_mb, scale_mb, sent_mb, net_metrics.packets_sent
+                            )));
                     });
 
                     if net_metrics.errors_in > 0 || net_metrics.errors_out > 0 {
@@ -907,36 +2882,43 @@ impl ProcessMonitorApp {
             ui.add_space(10.0);
 
             let mut sorted_processes = processes.clone();
-            sorted_processes.sort_by(|a, b| {
-                let a_net = a.stats.network_rx_bytes + a.stats.network_tx_bytes;
-                let b_net = b.stats.network_rx_bytes + b.stats.network_tx_bytes;
-                b_net.cmp(&a_net)
-            });
+            sort_by_network(&mut sorted_processes, self.network_sort);
 
             egui::Grid::new("network_processes")
-                .num_columns(5)
+                .num_columns(7)
                 .striped(true)
                 .spacing([10.0, 5.0])
                 .show(ui, |ui| {
                     ui.strong("PID");
                     ui.strong("Name");
                     ui.strong("User");
+                    // The sorted column carries the arrow; clicking the other one switches to it
+                    for (sort, label) in [(NetworkSort::Rate, "Rate (KB/s)"), (NetworkSort::Total, "Total (MB)")] {
+                        let active = self.network_sort == sort;
+                        let text = if active { format!("{} ↓", label) } else { label.to_string() };
+                        if ui.selectable_label(active, egui::RichText::new(text).strong()).clicked() && !active {
+                            self.network_sort = self.network_sort.toggle();
+                        }
+                    }
                     ui.strong("RX (MB)");
                     ui.strong("TX (MB)");
                     ui.end_row();
 
                     for process in sorted_processes.iter().take(20) {
+                        let rate_kb = process.stats.network_bytes_per_sec as f64 / 1024.0;
                         let rx_mb = process.stats.network_rx_bytes as f64 / (1024.0 * 1024.0);
                         let tx_mb = process.stats.network_tx_bytes as f64 / (1024.0 * 1024.0);
 
                         // Only show processes with network activity
-                        if rx_mb < 0.01 && tx_mb < 0.01 {
+                        if rate_kb < 0.01 && rx_mb < 0.01 && tx_mb < 0.01 {
                             continue;
                         }
 
                         ui.label(process.info.pid.to_string());
                         ui.label(&process.info.name);
                         ui.label(&process.info.user);
+                        ui.label(format!("{:.1}", rate_kb));
+                        ui.label(format!("{:.2}", rx_mb + tx_mb));
                         ui.label(format!("{:.2}", rx_mb));
                         ui.label(format!("{:.2}", tx_mb));
                         ui.end_row();
@@ -945,12 +2927,57 @@ impl ProcessMonitorApp {
         });
     }
 
+    /// Write the retained alert history to a timestamped file in the working directory.
+    fn export_alerts(&mut self, format: ExportFormat) {
+        let alerts = self.alerts.read().clone();
+        let result = std::env::current_dir()
+            .map_err(anyhow::Error::from)
+            .and_then(|dir| export_alerts(&alerts, &dir, format, chrono::Utc::now()));
+
+        self.status_message = match result {
+            Ok(path) => format!("Exported {} alerts to {}", alerts.len(), path.display()),
+            Err(e) => format!("Export failed: {}", e),
+        };
+    }
+
     fn draw_alerts(&mut self, ui: &mut egui::Ui) {
-        let alerts = self.alerts.read();
+        ui.heading(format!("Alerts ({})", self.alerts.read().len()));
+
+        let mut export_format = None;
+        ui.horizontal(|ui| {
+            if ui.button("Export JSON").clicked() {
+                export_format = Some(ExportFormat::Json);
+            }
+            if ui.button("Export CSV").clicked() {
+                export_format = Some(ExportFormat::Csv);
+            }
+            ui.separator();
+
+            ui.label("Desktop notifications:");
+            let mut min_severity = self.notify_min_severity.write();
+            egui::ComboBox::from_id_salt("notify_min_severity")
+                .selected_text(match *min_severity {
+                    Some(severity) => format!("{:?} and above", severity),
+                    None => "Off".to_string(),
+                })
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut *min_severity, Some(Severity::Critical), "Critical and above");
+                    ui.selectable_value(&mut *min_severity, Some(Severity::Warning), "Warning and above");
+                    ui.selectable_value(&mut *min_severity, Some(Severity::Info), "Info and above");
+                    ui.selectable_value(&mut *min_severity, None, "Off");
+                });
+        });
 
-        ui.heading(format!("Alerts ({})", alerts.len()));
+        if let Some(format) = export_format {
+            self.export_alerts(format);
+        }
+        if !self.status_message.is_empty() {
+            ui.colored_label(egui::Color32::YELLOW, &self.status_message);
+        }
         ui.add_space(10.0);
 
+        let alerts = self.alerts.read();
+
         egui::ScrollArea::vertical().show(ui, |ui| {
             for alert in alerts.iter().rev().take(50) {
                 let color = match alert.severity {
@@ -975,12 +3002,52 @@ impl ProcessMonitorApp {
             }
         });
     }
+
+    fn draw_logs(&mut self, ui: &mut egui::Ui) {
+        let entries = self.log_buffer.entries();
+        ui.heading(format!("Logs ({} recent events)", entries.len()));
+        ui.add_space(10.0);
+
+        egui::ScrollArea::vertical().stick_to_bottom(true).auto_shrink([false, false]).show(ui, |ui| {
+            for entry in &entries {
+                let color = match entry.level {
+                    Level::ERROR => egui::Color32::RED,
+                    Level::WARN => egui::Color32::YELLOW,
+                    Level::INFO => egui::Color32::GREEN,
+                    Level::DEBUG => egui::Color32::LIGHT_BLUE,
+                    Level::TRACE => egui::Color32::GRAY,
+                };
+                ui.horizontal(|ui| {
+                    ui.monospace(entry.timestamp.format("%H:%M:%S").to_string());
+                    ui.colored_label(color, egui::RichText::new(format!("{:<5}", entry.level)).monospace().strong());
+                    ui.colored_label(egui::Color32::GRAY, format!("{}:", entry.target));
+                    ui.label(&entry.message);
+                });
+            }
+        });
+    }
 }
 
 impl eframe::App for ProcessMonitorApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         ctx.request_repaint();
 
+        if !ctx.wants_keyboard_input() && ctx.input(|i| i.key_pressed(egui::Key::Y)) {
+            self.copy_selected(ctx);
+        }
+        if ctx.input(|i| i.key_pressed(egui::Key::F5)) {
+            self.refresh_schedule.write().request_now();
+        }
+
+        // Alerts count as seen while their tab is shown
+        let alert_badge = {
+            let alerts = self.alerts.read();
+            if self.selected_tab == 6 {
+                self.alert_acknowledgement.acknowledge(&alerts);
+            }
+            self.alert_acknowledgement.badge(&alerts)
+        };
+
         egui::TopBottomPanel::top("tabs").show(ctx, |ui| {
             ui.horizontal(|ui| {
                 ui.selectable_value(&mut self.selected_tab, 0, "Dashboard");
@@ -990,7 +3057,38 @@ impl eframe::App for ProcessMonitorApp {
                 ui.selectable_value(&mut self.selected_tab, 4, "Network");
                 ui.selectable_value(&mut self.selected_tab, 5, "Partitions");
                 ui.selectable_value(&mut self.selected_tab, 6, "Alerts");
+                ui.selectable_value(&mut self.selected_tab, 7, "Logs");
+                if let (Some(label), Some(severity)) = (alert_badge.label(), alert_badge.highest) {
+                    let color = match severity {
+                        Severity::Critical => egui::Color32::RED,
+                        Severity::Warning => egui::Color32::YELLOW,
+                        Severity::Info => egui::Color32::LIGHT_BLUE,
+                    };
+                    ui.label(egui::RichText::new(label).color(color).strong());
+                }
+
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    self.draw_refresh_indicator(ui);
+                });
             });
+            if self.monitor.read().in_container() {
+                ui.colored_label(egui::Color32::GRAY, CONTAINER_NOTE);
+            }
+            if let Some(warning) = self.config_warnings.first() {
+                let all: Vec<String> = self.config_warnings.iter().map(|warning| warning.to_string()).collect();
+                let mut dismiss = false;
+                ui.horizontal(|ui| {
+                    let text = match self.config_warnings.len() {
+                        1 => format!("Config: {}", warning),
+                        count => format!("Config: {} (+{} more)", warning, count - 1),
+                    };
+                    ui.colored_label(egui::Color32::YELLOW, text).on_hover_text(all.join("\n"));
+                    dismiss = ui.small_button("Dismiss").clicked();
+                });
+                if dismiss {
+                    self.config_warnings.clear();
+                }
+            }
         });
 
         egui::CentralPanel::default().show(ctx, |ui| {
@@ -1002,8 +3100,28 @@ impl eframe::App for ProcessMonitorApp {
                 4 => self.draw_network_redesigned(ui),
                 5 => self.draw_partitions(ui),
                 6 => self.draw_alerts(ui),
+                7 => self.draw_logs(ui),
                 _ => {}
             }
         });
     }
 }
+
+/// A button that only returns true after being held down for the whole [`HoldToConfirm`]
+/// duration, filling up red while held
+fn hold_button(ui: &mut egui::Ui, label: &str, hold: &mut HoldToConfirm) -> bool {
+    let response = ui
+        .add(egui::Button::new(label))
+        .on_hover_text("Press and hold to confirm");
+    let now = Instant::now();
+    let confirmed = hold.update(response.is_pointer_button_down_on(), now);
+
+    let progress = hold.progress(now);
+    if progress > 0.0 {
+        let rect = response.rect;
+        let filled = egui::Rect::from_min_size(rect.min, egui::vec2(rect.width() * progress, rect.height()));
+        ui.painter()
+            .rect_filled(filled, 2.0, egui::Color32::from_rgba_unmultiplied(220, 50, 50, 110));
+    }
+    confirmed
+}
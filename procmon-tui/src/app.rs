@@ -1,29 +1,100 @@
 use anyhow::Result;
 use procmon_core::{
-    MisbehaviorDetector, SystemMetrics, SystemMonitor,
-    process::ProcessSnapshot,
-    ServiceManager, SystemService,
+    GaugeSmoothing, GaugeValues, MisbehaviorDetector, MisbehaviorRule, NewProcessTracker, SystemInfo, SystemMetrics, SystemMonitor,
+    restart::restart_process,
+    kill::{kill_process, kill_process_tree, summarize_signal_results, Signal},
+    users::group_by_user,
+    own::SelfProcesses,
+    privilege::{ESCALATION_ENV, REQUIRES_ROOT},
+    click::DoubleClick,
+    config::{Config, ConfigWarning, DisplayConfig},
+    fds::{scan_deleted_open_files, DeletedOpenFile, DELETED_FILES_SCAN_INTERVAL},
+    protect::ProtectedSet,
+    bell::{AlertBell, AlertSound},
+    badge::{AlertAcknowledgement, AlertBadge},
+    detail::{ProcessDetail, DEFAULT_DETAIL_ENTRIES},
+    limits::user_thread_count,
+    threads::{read_threads, ThreadInfo},
+    logging::LogBuffer,
+    snapshot::{Snapshot, SnapshotWriter},
+    baseline::{Anomaly, BaselineTracker},
+    process::{find_parent_index, ProcessKey, ProcessSnapshot, ProcessStatus, StateFilter},
+    ServiceManager, SystemService, StopMode, StopPlan,
+    service::plan_stop,
+    partition::{
+        carry_over_usage, default_image_file_name, validate_type_code, BenchmarkResult, FsckProgress, ImageProgress, NetworkMount, DirectoryScan, DIRECTORY_USAGE_DEPTH,
+        TEMP_MOUNT_ROOT,
+    },
+    clipboard::ClipboardText,
+    columns::ProcessColumn,
+    temperature::TemperatureUnit,
+    density::Density,
+    cores::CoreLabels,
+    counters::SystemCounters,
+    selection::reconcile_selection,
+    search::{environment_matches, matches_search},
+    util::{Capped, RefreshSchedule, SourceBackoff, Staleness},
+    throttle::parse_throttle_input,
+    zombie::signal_parent,
+    export::{export_alerts, ExportFormat},
 };
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{Receiver, TryRecvError};
+use std::sync::Arc;
+use std::thread::JoinHandle;
 use std::time::{Duration, Instant};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum Tab {
-    Dashboard,
-    Processes,
-    Services,
-    Storage,
-    Network,
-    Partitions,
-    Alerts,
+pub use procmon_core::sort::{NetworkSort, ServiceSortColumn, SortColumn};
+pub use procmon_core::tab::Tab;
+
+/// A filesystem check running on a background thread
+pub struct FsckJob {
+    pub device: String,
+    pub progress: Arc<Mutex<FsckProgress>>,
+    handle: JoinHandle<Result<String>>,
+}
+
+/// Disk usage drill-down below a mount point, listing the directories in `path`
+pub struct DirectoryUsage {
+    pub mount_point: PathBuf,
+    pub path: PathBuf,
+    pub entries: Vec<(PathBuf, u64)>,
+    pub selected: usize,
+    pub error: Option<String>,
+    /// `du` scan of `path` while it runs
+    job: Option<DirectoryScan>,
+}
+
+impl DirectoryUsage {
+    pub fn scanning(&self) -> bool {
+        self.job.is_some()
+    }
+}
+
+/// Confirmation for signalling every process of a user; the user name has to be typed
+pub struct UserKillPrompt {
+    pub uid: u32,
+    pub user: String,
+    pub processes: usize,
+    pub typed: String,
+    pub signal: Signal,
+}
+
+/// `hdparm -t` running on a background thread; it reads for several seconds
+pub struct BenchmarkJob {
+    pub device: String,
+    handle: JoinHandle<Result<BenchmarkResult>>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum SortColumn {
-    Name,
-    Cpu,
-    Memory,
-    DiskIo,
-    User,
+/// A partition being copied to an image file by dd
+pub struct ImageJob {
+    pub device: String,
+    pub out_path: PathBuf,
+    pub total_bytes: u64,
+    pub copied_bytes: u64,
+    progress: Receiver<ImageProgress>,
 }
 
 pub struct App {
@@ -32,86 +103,257 @@ pub struct App {
     pub partition_manager: procmon_core::PartitionManager,
     pub service_manager: ServiceManager,
     pub system_metrics: SystemMetrics,
+    pub system_info: SystemInfo,
+    // Smoothed copies of the dashboard gauges; system_metrics stays raw for alerting
+    pub gauge_smoothing: Option<GaugeSmoothing>,
+    smoothing_alpha: f64,
+    pub gauges: GaugeValues,
+    // Bar labels for the per-core chart, kept across frames
+    pub core_labels: CoreLabels,
+    pub system_counters: SystemCounters,
+    pub show_system_info: bool,
+    pub show_core_map: bool,
+    /// Processes tab shows per-container totals instead of the process list
+    pub show_containers: bool,
+    /// Processes tab shows per-user totals instead of the process list
+    pub show_users: bool,
+    /// Processes tab shows only processes holding GPU memory
+    pub show_gpu_processes: bool,
+    /// Processes tab shows the processes whose memory grew the most since the last refresh
+    pub show_memory_growth: bool,
+    /// Threads of the selected process are listed under it
+    pub show_threads: bool,
+    pub threads: Capped<ThreadInfo>,
+    threads_of: Option<ProcessKey>,
+    /// Threads being read on a background thread, with the process they belong to
+    threads_job: Option<(ProcessKey, JoinHandle<Capped<ThreadInfo>>)>,
+    pub selected_user: usize,
+    /// Kill-all-of-a-user confirmation while it is open
+    pub user_kill_prompt: Option<UserKillPrompt>,
     pub processes: Vec<ProcessSnapshot>,
+    pub new_processes: NewProcessTracker,
     pub filtered_processes: Vec<ProcessSnapshot>,
     pub services: Vec<SystemService>,
     pub filtered_services: Vec<SystemService>,
     pub disks: Vec<procmon_core::Disk>,
+    /// NFS/CIFS/SSHFS mounts; their usage is only read when asked for, since `df` can hang on them
+    pub network_mounts: Vec<NetworkMount>,
     pub alerts: Vec<procmon_core::MisbehaviorAlert>,
     pub current_tab: Tab,
+    // Process table columns in display order
+    pub process_columns: Vec<ProcessColumn>,
+    // Unit temperatures are displayed in
+    pub temperature_unit: TemperatureUnit,
+    /// Spacing of the process table rows
+    pub density: Density,
+    /// Processes the kill and restart actions refuse to touch
+    protected: ProtectedSet,
+    alert_bell: AlertBell,
+    /// Alerts up to here have been seen on the Alerts tab
+    alert_acknowledgement: AlertAcknowledgement,
+    /// Recent log events shown on the Logs tab
+    pub log_buffer: LogBuffer,
+    /// How many events the Logs tab is scrolled back from the newest; 0 follows new events
+    pub log_scroll: usize,
+    /// Periodic snapshots to disk, when enabled with `--snapshot-dir`
+    snapshots: Option<SnapshotWriter>,
+    /// Hourly usage history behind the dashboard's "unusual" hints
+    baseline: Option<BaselineTracker>,
     pub selected_process: usize,
     pub selected_service: usize,
     pub selected_disk: usize,
     pub selected_partition: usize,
     pub sort_column: SortColumn,
     pub sort_ascending: bool,
+    pub service_sort_column: ServiceSortColumn,
+    pub service_sort_ascending: bool,
+    /// Order of the network tab's process table
+    pub network_sort: NetworkSort,
     pub show_only_misbehaving: bool,
     pub show_context_menu: bool,
     pub show_service_menu: bool,
     pub show_partition_menu: bool,
+    pub fstab_candidate: Option<(String, String)>, // (device, filesystem) of the last formatted partition
+    pub mount_candidate: Option<String>, // device of the last formatted partition, until it is mounted
+    pub fsck_job: Option<FsckJob>,
+    pub directory_usage: Option<DirectoryUsage>,
+    pub image_job: Option<ImageJob>,
+    pub benchmark_job: Option<BenchmarkJob>,
+    pub benchmark_results: HashMap<String, BenchmarkResult>,
+    /// Deleted files still held open, rescanned while the Storage tab is shown
+    pub deleted_files: Vec<DeletedOpenFile>,
+    deleted_files_scanned: Option<Instant>,
+    /// Deleted-file scan running on a background thread
+    deleted_files_job: Option<JoinHandle<Vec<DeletedOpenFile>>>,
+    // Kept alive so X11 keeps serving the copied text after `copy_selected` returns
+    clipboard: Option<arboard::Clipboard>,
     pub context_menu_pid: Option<u32>,
+    // Text typed into the I/O throttle prompt while it is open
+    pub throttle_input: Option<String>,
+    /// Command line and environment popup, scrolled down by `detail_scroll` lines
+    pub process_detail: Option<ProcessDetail>,
+    detail_job: Option<JoinHandle<ProcessDetail>>,
+    /// Open files and threads listed per process
+    detail_entries: usize,
+    pub detail_scroll: u16,
+    /// `--debug`: the detail popup also lists raw `/proc` fields
+    debug: bool,
+    // Type code typed into the partition type prompt while it is open
+    pub partition_type_input: Option<String>,
+    // Units the service-menu service depends on, and units that depend on it
+    pub service_dependencies: Vec<String>,
+    pub service_dependents: Vec<String>,
+    // Dependents listed in the stop confirmation while it is open
+    pub pending_stop: Option<Vec<String>>,
     pub context_menu_service: Option<String>,
     pub status_message: Option<String>,
     pub search_query: String,
+    // Process states the list is restricted to, combined with the search
+    pub state_filter: StateFilter,
     pub search_mode: bool,
+    /// Opt-in: the search also reads process environments, which is slow and sensitive
+    pub search_environment: bool,
+    pub show_command_line: bool,
     pub scroll_offset: usize,
     pub process_list_area: Option<(u16, u16, u16, u16)>, // (x, y, width, height) for process table
-    last_update: Instant,
-    update_interval: Duration,
-    last_click_time: Option<Instant>,
-    last_click_row: Option<usize>,
+    refresh: RefreshSchedule,
+    /// Alerts kept in the Alerts tab
+    max_alerts: usize,
+    /// Problems in config.toml or the rules file, shown above the footer
+    pub config_warnings: Vec<ConfigWarning>,
+    /// `systemctl` polling, spaced out while it keeps failing
+    pub services_backoff: SourceBackoff,
+    /// Double-clicking a process row opens its context menu, unless turned off
+    double_click: DoubleClick,
 }
 
 impl App {
-    pub async fn new() -> Result<Self> {
-        let monitor = SystemMonitor::new();
+    pub async fn new(
+        startup_tab: Tab,
+        process_columns: Vec<ProcessColumn>,
+        temperature_unit: TemperatureUnit,
+        density: Density,
+        protected: ProtectedSet,
+        alert_sound: AlertSound,
+        log_buffer: LogBuffer,
+    ) -> Result<Self> {
+        let monitor = SystemMonitor::new_warmed();
         let detector = MisbehaviorDetector::new();
         let partition_manager = procmon_core::PartitionManager::new();
         let service_manager = ServiceManager::new();
 
-        monitor.refresh();
         let system_metrics = monitor.get_system_metrics()?;
+        let system_info = monitor.get_system_info();
+        let mut gauge_smoothing = GaugeSmoothing::new(GaugeSmoothing::DEFAULT_ALPHA);
+        let gauges = gauge_smoothing.update(&system_metrics);
+        let mut core_labels = CoreLabels::default();
+        core_labels.update(system_metrics.cpu.per_core_usage.len());
         let processes = monitor.get_all_processes()?;
+        let system_counters = SystemCounters::collect(&processes);
         let disks = partition_manager.list_disks().unwrap_or_default();
-        let services = service_manager.list_services().unwrap_or_default();
+        let network_mounts = partition_manager.list_network_mounts().unwrap_or_default();
+        let mut services = service_manager.list_services().unwrap_or_default();
+        procmon_core::sort::sort_services(&mut services, ServiceSortColumn::Name, true);
 
         let filtered_processes = processes.clone();
         let filtered_services = services.clone();
 
+        let mut new_processes = NewProcessTracker::default();
+        let pids: Vec<u32> = processes.iter().map(|p| p.info.pid).collect();
+        new_processes.update(&pids, Instant::now());
+
         Ok(Self {
             monitor,
             detector,
             partition_manager,
             service_manager,
             system_metrics,
+            system_info,
+            gauge_smoothing: Some(gauge_smoothing),
+            smoothing_alpha: GaugeSmoothing::DEFAULT_ALPHA,
+            gauges,
+            core_labels,
+            system_counters,
+            show_system_info: true,
+            show_core_map: false,
+            show_containers: false,
+            show_users: false,
+            show_gpu_processes: false,
+            show_memory_growth: false,
+            show_threads: false,
+            threads: Capped::default(),
+            threads_of: None,
+            threads_job: None,
+            selected_user: 0,
+            user_kill_prompt: None,
             processes,
+            new_processes,
             filtered_processes,
             services,
             filtered_services,
             disks,
+            network_mounts,
             alerts: Vec::new(),
-            current_tab: Tab::Dashboard,
+            current_tab: startup_tab,
+            process_columns,
+            temperature_unit,
+            density,
+            protected,
+            alert_bell: AlertBell::new(alert_sound),
+            alert_acknowledgement: AlertAcknowledgement::default(),
+            log_buffer,
+            log_scroll: 0,
+            snapshots: None,
+            baseline: None,
             selected_process: 0,
             selected_service: 0,
             selected_disk: 0,
             selected_partition: 0,
             sort_column: SortColumn::Cpu,
             sort_ascending: false,
+            service_sort_column: ServiceSortColumn::Name,
+            service_sort_ascending: true,
+            network_sort: NetworkSort::default(),
             show_only_misbehaving: false,
             show_context_menu: false,
             show_service_menu: false,
             show_partition_menu: false,
+            fstab_candidate: None,
+            mount_candidate: None,
+            fsck_job: None,
+            directory_usage: None,
+            image_job: None,
+            benchmark_job: None,
+            benchmark_results: HashMap::new(),
+            deleted_files: Vec::new(),
+            deleted_files_scanned: None,
+            deleted_files_job: None,
+            clipboard: None,
             context_menu_pid: None,
+            throttle_input: None,
+            process_detail: None,
+            detail_job: None,
+            detail_entries: DEFAULT_DETAIL_ENTRIES,
+            detail_scroll: 0,
+            debug: false,
+            partition_type_input: None,
+            service_dependencies: Vec::new(),
+            service_dependents: Vec::new(),
+            pending_stop: None,
             context_menu_service: None,
             status_message: None,
             search_query: String::new(),
+            state_filter: StateFilter::default(),
             search_mode: false,
+            search_environment: false,
+            show_command_line: false,
             scroll_offset: 0,
             process_list_area: None,
-            last_update: Instant::now(),
-            update_interval: Duration::from_millis(1000),
-            last_click_time: None,
-            last_click_row: None,
+            refresh: RefreshSchedule::new(Duration::from_millis(1000), Instant::now()),
+            max_alerts: DisplayConfig::default().max_alerts,
+            config_warnings: Vec::new(),
+            services_backoff: SourceBackoff::new("systemctl", Duration::from_millis(1000)),
+            double_click: DoubleClick::default(),
         })
     }
 
@@ -119,33 +361,23 @@ impl App {
         // Check if click is within process list area
         if let Some((area_x, area_y, area_width, area_height)) = self.process_list_area {
             if x >= area_x && x < area_x + area_width && y >= area_y && y < area_y + area_height {
-                // Calculate which row was clicked (accounting for borders and header)
-                // area_y is the top of the block, +1 for border, +2 for header with spacing
-                let header_offset = 3; // border + header + spacing
+                // Calculate which row was clicked (accounting for border, header and row spacing)
+                let header_offset = self.density.first_row_offset();
                 if y >= area_y + header_offset {
-                    let clicked_row = (y - area_y - header_offset) as usize;
+                    let clicked_row = ((y - area_y - header_offset) / (1 + self.density.row_margin())) as usize;
                     let actual_index = clicked_row + self.scroll_offset;
+                    // Thread rows sit right below the selected process and can't be selected
+                    let actual_index = match actual_index.checked_sub(self.selected_process + 1) {
+                        Some(below) if below < self.threads.items.len() => return,
+                        Some(_) => actual_index - self.threads.items.len(),
+                        None => actual_index,
+                    };
 
                     if actual_index < self.filtered_processes.len() {
-                        // Check for double-click (within 500ms)
-                        let now = Instant::now();
-                        let is_double_click = if let (Some(last_time), Some(last_row)) = (self.last_click_time, self.last_click_row) {
-                            now.duration_since(last_time) < Duration::from_millis(500) && last_row == actual_index
-                        } else {
-                            false
-                        };
-
+                        // A click selects; a double-click also opens the context menu
                         self.selected_process = actual_index;
-
-                        if is_double_click {
-                            // Double-click opens context menu
+                        if self.double_click.click(actual_index, Instant::now()) {
                             self.toggle_context_menu();
-                            self.last_click_time = None;
-                            self.last_click_row = None;
-                        } else {
-                            // Single click just selects
-                            self.last_click_time = Some(now);
-                            self.last_click_row = Some(actual_index);
                         }
                     }
                 }
@@ -153,10 +385,86 @@ impl App {
         }
     }
 
+    /// Double-click window for process rows; `None` leaves the context menu to m/Enter
+    pub fn set_double_click(&mut self, window: Option<Duration>) {
+        self.double_click = DoubleClick::new(window);
+    }
+
+    pub fn set_debug(&mut self, debug: bool) {
+        self.debug = debug;
+    }
+
+    /// Refresh interval, ignore/allow lists, alert limits and gauge smoothing from `config.toml`
+    pub fn apply_config(&mut self, config: &Config) {
+        self.refresh = RefreshSchedule::new(config.general.refresh_interval(), self.refresh.last_update());
+        self.monitor.set_ignored(config.lists.ignore.clone());
+        self.detector.set_allowed(config.lists.allow.clone());
+        self.max_alerts = config.display.max_alerts;
+        self.detector.set_max_alerts_per_sec(config.display.alert_rate_cap());
+        self.smoothing_alpha = config.display.gauge_alpha();
+        if self.gauge_smoothing.is_some() {
+            self.gauge_smoothing = Some(GaugeSmoothing::new(self.smoothing_alpha));
+        }
+        self.detail_entries = config.display.detail_entries;
+    }
+
+    pub fn set_config_warnings(&mut self, warnings: Vec<ConfigWarning>) {
+        self.config_warnings = warnings;
+    }
+
+    /// Alert rules from the configured rules file, replacing the built-in ones
+    pub fn set_rules(&mut self, rules: Vec<MisbehaviorRule>) {
+        self.detector.set_rules(rules);
+    }
+
+    pub fn set_escalation(&mut self, escalation: Option<Vec<String>>) {
+        self.partition_manager.set_escalation(escalation);
+    }
+
+    /// Refuse a partition change with a status message when procmon is neither root nor
+    /// configured to escalate
+    fn require_partition_privileges(&mut self, action: &str) -> bool {
+        if self.partition_manager.can_modify() {
+            return true;
+        }
+        self.status_message = Some(format!("{} {} (run as root or set {})", action, REQUIRES_ROOT, ESCALATION_ENV));
+        false
+    }
+
+    pub fn set_snapshot_writer(&mut self, writer: SnapshotWriter) {
+        self.snapshots = Some(writer);
+    }
+
+    pub fn set_baseline_tracker(&mut self, tracker: BaselineTracker) {
+        self.baseline = Some(tracker);
+    }
+
+    /// Tag or hide procmon's own processes, and alert on them only in `Include` mode
+    pub fn set_self_processes(&mut self, mode: SelfProcesses) {
+        self.monitor.set_self_processes(mode);
+        self.detector.set_own_alerts(mode.alerts());
+    }
+
+    /// CPU or memory usage this hour far from the same hour on previous days
+    pub fn anomalies(&self) -> Vec<Anomaly> {
+        self.baseline.as_ref().map(|baseline| baseline.anomalies()).unwrap_or_default()
+    }
+
     pub fn set_process_list_area(&mut self, x: u16, y: u16, width: u16, height: u16) {
         self.process_list_area = Some((x, y, width, height));
     }
 
+    /// Also match the search against process environments (`/proc/{pid}/environ`)
+    pub fn toggle_environment_search(&mut self) {
+        self.search_environment = !self.search_environment;
+        self.status_message = Some(if self.search_environment {
+            "Search includes environment variables of your processes".to_string()
+        } else {
+            "Search no longer includes environment variables".to_string()
+        });
+        self.filter_processes();
+    }
+
     pub fn toggle_search_mode(&mut self) {
         self.search_mode = !self.search_mode;
         if !self.search_mode {
@@ -179,21 +487,32 @@ impl App {
         self.scroll_offset = 0;
     }
 
+    /// Rebuild the filtered list, keeping the selected process selected
     fn filter_processes(&mut self) {
-        if self.search_query.is_empty() {
+        let selected = self.filtered_processes.get(self.selected_process).map(|p| p.key());
+
+        if self.search_query.is_empty() && !self.state_filter.is_active() {
             self.filtered_processes = self.processes.clone();
         } else {
-            let query_lower = self.search_query.to_lowercase();
             self.filtered_processes = self.processes
                 .iter()
+                .filter(|p| self.state_filter.matches(&p.info))
                 .filter(|p| {
-                    p.info.name.to_lowercase().contains(&query_lower)
-                        || p.info.pid.to_string().contains(&query_lower)
-                        || p.info.user.to_lowercase().contains(&query_lower)
+                    matches_search(&p.info, &self.search_query)
+                        || (self.search_environment && environment_matches(&p.info, &self.search_query))
                 })
                 .cloned()
                 .collect();
         }
+
+        let (selected, scroll) = reconcile_selection(
+            &self.filtered_processes,
+            selected,
+            self.selected_process,
+            self.scroll_offset,
+        );
+        self.selected_process = selected;
+        self.scroll_offset = scroll;
     }
 
     pub fn scroll_up(&mut self, amount: usize) {
@@ -209,6 +528,20 @@ impl App {
         self.scroll_offset = (self.scroll_offset + amount).min(max_scroll);
     }
 
+    /// Scroll the Logs tab back towards older events
+    pub fn scroll_logs_up(&mut self, amount: usize) {
+        self.log_scroll = (self.log_scroll + amount).min(self.log_buffer.len().saturating_sub(1));
+    }
+
+    pub fn scroll_logs_down(&mut self, amount: usize) {
+        self.log_scroll = self.log_scroll.saturating_sub(amount);
+    }
+
+    /// Jump back to the newest events and keep up with new ones
+    pub fn follow_logs(&mut self) {
+        self.log_scroll = 0;
+    }
+
     pub fn get_filtered_processes(&self) -> &[ProcessSnapshot] {
         &self.filtered_processes
     }
@@ -264,9 +597,99 @@ impl App {
         } else {
             self.status_message = Some("Failed to refresh disks".to_string());
         }
+        self.refresh_network_mounts();
+    }
+
+    /// Re-read the network mount list, keeping usage already checked for mounts still present
+    fn refresh_network_mounts(&mut self) {
+        let Ok(mut mounts) = self.partition_manager.list_network_mounts() else {
+            return;
+        };
+        carry_over_usage(&mut mounts, &self.network_mounts);
+        self.network_mounts = mounts;
+    }
+
+    /// Run `df` on every network mount, each with a timeout so a dead server can't hang the UI
+    pub fn check_network_mounts(&mut self) {
+        self.refresh_network_mounts();
+        if self.network_mounts.is_empty() {
+            self.status_message = Some("No network mounts".to_string());
+            return;
+        }
+        let mut failed = Vec::new();
+        for mount in &mut self.network_mounts {
+            match self.partition_manager.check_network_mount(&mount.mount_point) {
+                Ok(usage) => mount.usage = Some(usage),
+                Err(e) => {
+                    mount.usage = None;
+                    failed.push(format!("{}: {}", mount.mount_point, e));
+                }
+            }
+        }
+        self.status_message = Some(if failed.is_empty() {
+            format!("Checked {} network mounts", self.network_mounts.len())
+        } else {
+            format!("Network mount check failed for {}", failed.join(", "))
+        });
+    }
+
+    pub fn benchmark_selected_disk(&mut self) -> Result<()> {
+        let Some(disk) = self.disks.get(self.selected_disk) else {
+            self.status_message = Some("No disk selected".to_string());
+            return Ok(());
+        };
+
+        if let Some(job) = &self.benchmark_job {
+            self.status_message = Some(format!("Benchmark of {} is still running", job.device));
+            return Ok(());
+        }
+
+        let device = disk.device.clone();
+        let partition_manager = self.partition_manager.clone();
+        let thread_device = device.clone();
+        let handle = std::thread::spawn(move || partition_manager.benchmark_read(&thread_device));
+        self.status_message = Some(format!("Benchmarking {}...", device));
+        self.benchmark_job = Some(BenchmarkJob { device, handle });
+
+        Ok(())
+    }
+
+    fn poll_benchmark_job(&mut self) {
+        if !self.benchmark_job.as_ref().is_some_and(|job| job.handle.is_finished()) {
+            return;
+        }
+        if let Some(job) = self.benchmark_job.take() {
+            match job.handle.join() {
+                Ok(Ok(result)) => {
+                    self.status_message = Some(format!("{}: {:.1} MB/s sequential read", job.device, result.mb_per_sec));
+                    self.benchmark_results.insert(job.device, result);
+                }
+                Ok(Err(e)) => self.status_message = Some(format!("Benchmark failed: {}", e)),
+                Err(_) => self.status_message = Some(format!("Benchmark of {} panicked", job.device)),
+            }
+        }
+    }
+
+    /// Write the retained alert history to a timestamped file in the working directory.
+    pub fn export_alerts(&mut self, format: ExportFormat) -> Result<()> {
+        let dir = std::env::current_dir()?;
+        match export_alerts(&self.alerts, &dir, format, chrono::Utc::now()) {
+            Ok(path) => {
+                self.status_message = Some(format!("Exported {} alerts to {}", self.alerts.len(), path.display()));
+            }
+            Err(e) => {
+                self.status_message = Some(format!("Export failed: {}", e));
+            }
+        }
+
+        Ok(())
     }
 
     pub fn format_selected_partition(&mut self, filesystem: &str) -> Result<()> {
+        if !self.require_partition_privileges("Format") {
+            return Ok(());
+        }
+
         if self.selected_disk >= self.disks.len() {
             self.status_message = Some("No disk selected".to_string());
             return Ok(());
@@ -283,7 +706,17 @@ impl App {
 
         match self.partition_manager.format_partition(device, filesystem, None) {
             Ok(_) => {
-                self.status_message = Some(format!("Formatted {} as {}", device, filesystem));
+                if filesystem == "swap" {
+                    self.status_message = Some(format!("Formatted {} as {}. Press F to add it to /etc/fstab", device, filesystem));
+                    self.mount_candidate = None;
+                } else {
+                    self.status_message = Some(format!(
+                        "Formatted {} as {}. Press F to add it to /etc/fstab, M to mount it under {}",
+                        device, filesystem, TEMP_MOUNT_ROOT
+                    ));
+                    self.mount_candidate = Some(device.clone());
+                }
+                self.fstab_candidate = Some((device.clone(), filesystem.to_string()));
                 self.refresh_disks();
             }
             Err(e) => {
@@ -294,7 +727,225 @@ impl App {
         Ok(())
     }
 
+    /// Mount the most recently formatted partition under `/mnt/procmon/<label or UUID>`
+    pub fn mount_formatted(&mut self) {
+        let Some(device) = self.mount_candidate.take() else {
+            self.status_message = Some("No newly formatted partition".to_string());
+            return;
+        };
+
+        let label = self.partition_manager.get_partition_label(&device);
+        self.status_message = Some(match self.partition_manager.mount_temp(&device, label.as_deref()) {
+            Ok(mount_point) => format!("Mounted {} at {} (U unmounts it)", device, mount_point.display()),
+            Err(e) => format!("Mount failed: {}", e),
+        });
+        self.refresh_disks();
+    }
+
+    /// Open the disk usage drill-down at the mount point of the selected partition
+    pub fn open_directory_usage(&mut self) {
+        let Some(partition) = self.disks.get(self.selected_disk).and_then(|disk| disk.partitions.get(self.selected_partition))
+        else {
+            self.status_message = Some("No partition selected".to_string());
+            return;
+        };
+        let Some(mount_point) = partition.mount_point.clone() else {
+            self.status_message = Some(format!("{} is not mounted", partition.device));
+            return;
+        };
+        let mount_point = PathBuf::from(mount_point);
+        self.directory_usage = Some(DirectoryUsage {
+            mount_point: mount_point.clone(),
+            path: mount_point.clone(),
+            entries: Vec::new(),
+            selected: 0,
+            error: None,
+            job: None,
+        });
+        self.scan_directory_usage(mount_point);
+    }
+
+    fn scan_directory_usage(&mut self, path: PathBuf) {
+        let Some(usage) = &mut self.directory_usage else {
+            return;
+        };
+        usage.entries.clear();
+        usage.selected = 0;
+        usage.error = None;
+        // Replacing a running scan kills it
+        usage.job = None;
+        match self.partition_manager.scan_directories(&path, DIRECTORY_USAGE_DEPTH) {
+            Ok(scan) => usage.job = Some(scan),
+            Err(e) => usage.error = Some(e.to_string()),
+        }
+        usage.path = path;
+    }
+
+    /// Collect the result of a finished `du` scan
+    fn poll_directory_usage(&mut self) {
+        let Some(usage) = &mut self.directory_usage else {
+            return;
+        };
+        let Some(result) = usage.job.as_mut().and_then(|job| job.poll()) else {
+            return;
+        };
+        usage.job = None;
+        match result {
+            Ok(entries) => usage.entries = entries,
+            Err(e) => usage.error = Some(e.to_string()),
+        }
+    }
+
+    pub fn next_directory(&mut self) {
+        if let Some(usage) = &mut self.directory_usage {
+            if usage.selected + 1 < usage.entries.len() {
+                usage.selected += 1;
+            }
+        }
+    }
+
+    pub fn previous_directory(&mut self) {
+        if let Some(usage) = &mut self.directory_usage {
+            usage.selected = usage.selected.saturating_sub(1);
+        }
+    }
+
+    /// Scan the selected directory's own subdirectories
+    pub fn open_selected_directory(&mut self) {
+        let Some(usage) = &self.directory_usage else {
+            return;
+        };
+        if usage.scanning() {
+            return;
+        }
+        if let Some((path, _)) = usage.entries.get(usage.selected) {
+            self.scan_directory_usage(path.clone());
+        }
+    }
+
+    /// Go back to the parent directory, but not above the mount point
+    pub fn directory_usage_up(&mut self) {
+        let Some(usage) = &self.directory_usage else {
+            return;
+        };
+        if usage.scanning() || usage.path == usage.mount_point {
+            return;
+        }
+        if let Some(parent) = usage.path.parent() {
+            self.scan_directory_usage(parent.to_path_buf());
+        }
+    }
+
+    /// Closing kills a running `du`
+    pub fn close_directory_usage(&mut self) {
+        self.directory_usage = None;
+    }
+
+    /// Unmount the selected partition if procmon mounted it, removing its mount directory
+    pub fn unmount_selected_partition(&mut self) {
+        let Some(partition) = self.disks.get(self.selected_disk).and_then(|disk| disk.partitions.get(self.selected_partition))
+        else {
+            self.status_message = Some("No partition selected".to_string());
+            return;
+        };
+        let Some(mount_point) = partition.mount_point.clone() else {
+            self.status_message = Some(format!("{} is not mounted", partition.device));
+            return;
+        };
+
+        self.status_message = Some(match self.partition_manager.unmount_temp(Path::new(&mount_point)) {
+            Ok(()) => format!("Unmounted {} and removed {}", partition.device, mount_point),
+            Err(e) => format!("Unmount failed: {}", e),
+        });
+        self.refresh_disks();
+    }
+
+    /// Unmount and power off the selected disk if it is removable
+    pub fn eject_selected_disk(&mut self) {
+        let Some(disk) = self.disks.get(self.selected_disk) else {
+            self.status_message = Some("No disk selected".to_string());
+            return;
+        };
+        if !disk.removable {
+            self.status_message = Some(format!("{} is not a removable disk", disk.device));
+            return;
+        }
+
+        let device = disk.device.clone();
+        self.status_message = Some(match self.partition_manager.eject(&device) {
+            Ok(()) => format!("Ejected {}; it can be unplugged now", device),
+            Err(e) => format!("Eject failed: {}", e),
+        });
+        self.refresh_disks();
+    }
+
+    /// Persist a mount for the most recently formatted partition under `/mnt/<device name>`
+    pub fn add_formatted_to_fstab(&mut self) -> Result<()> {
+        let Some((device, filesystem)) = self.fstab_candidate.take() else {
+            self.status_message = Some("No newly formatted partition".to_string());
+            return Ok(());
+        };
+
+        let Some(uuid) = self.partition_manager.get_partition_uuid(&device) else {
+            self.status_message = Some(format!("Could not read UUID of {}", device));
+            return Ok(());
+        };
+
+        let mount_point = format!("/mnt/{}", device.trim_start_matches("/dev/"));
+        match self.partition_manager.append_to_fstab(&uuid, &mount_point, &filesystem, None) {
+            Ok(_) => {
+                self.status_message = Some(format!("Added {} to /etc/fstab at {}", device, mount_point));
+            }
+            Err(e) => {
+                self.status_message = Some(format!("fstab update failed: {}", e));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Turn swap on or off for the selected swap partition
+    pub fn toggle_selected_swap(&mut self) -> Result<()> {
+        let Some(partition) = self
+            .disks
+            .get(self.selected_disk)
+            .and_then(|disk| disk.partitions.get(self.selected_partition))
+            .cloned()
+        else {
+            self.status_message = Some("No partition selected".to_string());
+            return Ok(());
+        };
+
+        if partition.filesystem.as_deref() != Some("swap") {
+            self.status_message = Some(format!("{} is not a swap partition", partition.device));
+            return Ok(());
+        }
+
+        let (result, action) = if partition.is_active_swap() {
+            (self.partition_manager.swapoff(&partition.device), "Disabled")
+        } else {
+            (self.partition_manager.swapon(&partition.device), "Enabled")
+        };
+
+        match result {
+            Ok(_) => {
+                self.status_message = Some(format!("{} swap on {}", action, partition.device));
+                self.system_metrics.memory = self.monitor.refresh_memory_metrics()?;
+                self.refresh_disks();
+            }
+            Err(e) => {
+                self.status_message = Some(format!("Swap change failed: {}", e));
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn delete_selected_partition(&mut self) -> Result<()> {
+        if !self.require_partition_privileges("Delete") {
+            return Ok(());
+        }
+
         if self.selected_disk >= self.disks.len() {
             self.status_message = Some("No disk selected".to_string());
             return Ok(());
@@ -325,6 +976,15 @@ impl App {
     }
 
     pub fn check_selected_partition(&mut self) -> Result<()> {
+        if !self.require_partition_privileges("Check") {
+            return Ok(());
+        }
+
+        if self.fsck_job.is_some() {
+            self.status_message = Some("A filesystem check is already running".to_string());
+            return Ok(());
+        }
+
         if self.selected_disk >= self.disks.len() {
             self.status_message = Some("No disk selected".to_string());
             return Ok(());
@@ -337,15 +997,21 @@ impl App {
         }
 
         let partition = &disk.partitions[self.selected_partition];
-        if let Some(ref fs) = partition.filesystem {
-            match self.partition_manager.check_filesystem(&partition.device, fs, false) {
-                Ok(result) => {
-                    self.status_message = Some(format!("Check complete. See logs for details."));
-                }
-                Err(e) => {
-                    self.status_message = Some(format!("Check failed: {}", e));
-                }
-            }
+        if let Some(fs) = partition.filesystem.clone() {
+            let device = partition.device.clone();
+            let progress = Arc::new(Mutex::new(FsckProgress { pass: None, percent: 0.0 }));
+            let progress_clone = progress.clone();
+            let thread_device = device.clone();
+            let partition_manager = self.partition_manager.clone();
+
+            let handle = std::thread::spawn(move || {
+                partition_manager.check_filesystem_with_progress(&thread_device, &fs, false, |p| {
+                    *progress_clone.lock() = p;
+                })
+            });
+
+            self.status_message = Some(format!("Checking {}...", device));
+            self.fsck_job = Some(FsckJob { device, progress, handle });
         } else {
             self.status_message = Some("No filesystem detected".to_string());
         }
@@ -353,16 +1019,195 @@ impl App {
         Ok(())
     }
 
+    /// Collect the result of a finished background filesystem check
+    fn poll_fsck_job(&mut self) {
+        if !self.fsck_job.as_ref().is_some_and(|job| job.handle.is_finished()) {
+            return;
+        }
+
+        if let Some(job) = self.fsck_job.take() {
+            self.status_message = Some(match job.handle.join() {
+                Ok(Ok(_)) => format!("Check of {} complete. See logs for details.", job.device),
+                Ok(Err(e)) => format!("Check failed: {}", e),
+                Err(_) => format!("Check of {} panicked", job.device),
+            });
+        }
+    }
+
+    /// Copy the selected partition to `<device>-<timestamp>.img` in the working directory.
+    ///
+    /// Refused while the partition is mounted read-write unless `force` is set.
+    pub fn image_selected_partition(&mut self, force: bool) {
+        if !self.require_partition_privileges("Create image") {
+            return;
+        }
+
+        if self.image_job.is_some() {
+            self.status_message = Some("An image is already being created".to_string());
+            return;
+        }
+
+        let Some(partition) = self.disks.get(self.selected_disk).and_then(|disk| disk.partitions.get(self.selected_partition))
+        else {
+            self.status_message = Some("No partition selected".to_string());
+            return;
+        };
+        let device = partition.device.clone();
+        let total_bytes = partition.size_bytes;
+
+        let out_path = match std::env::current_dir() {
+            Ok(dir) => dir.join(default_image_file_name(&device, chrono::Local::now())),
+            Err(e) => {
+                self.status_message = Some(format!("Image failed: {}", e));
+                return;
+            }
+        };
+
+        match self.partition_manager.image_partition(&device, &out_path, force) {
+            Ok(progress) => {
+                self.status_message = Some(format!("Imaging {} to {}...", device, out_path.display()));
+                self.image_job = Some(ImageJob { device, out_path, total_bytes, copied_bytes: 0, progress });
+            }
+            Err(e) => {
+                let hint = if force { "" } else { " (I forces it)" };
+                self.status_message = Some(format!("Image failed: {}{}", e, hint));
+            }
+        }
+    }
+
+    pub fn start_partition_type_input(&mut self) {
+        let has_partition = self
+            .disks
+            .get(self.selected_disk)
+            .is_some_and(|disk| self.selected_partition < disk.partitions.len());
+        if has_partition {
+            self.partition_type_input = Some(String::new());
+        } else {
+            self.status_message = Some("No partition selected".to_string());
+        }
+    }
+
+    pub fn cancel_partition_type_input(&mut self) {
+        self.partition_type_input = None;
+    }
+
+    pub fn add_partition_type_char(&mut self, c: char) {
+        if let Some(ref mut input) = self.partition_type_input {
+            if c.is_ascii_hexdigit() || c == '-' {
+                input.push(c.to_ascii_uppercase());
+            }
+        }
+    }
+
+    pub fn remove_partition_type_char(&mut self) {
+        if let Some(ref mut input) = self.partition_type_input {
+            input.pop();
+        }
+    }
+
+    /// Set the type code typed into the prompt on the selected partition
+    pub fn apply_partition_type(&mut self) {
+        let Some(input) = self.partition_type_input.take() else {
+            return;
+        };
+        let Some(disk) = self.disks.get(self.selected_disk) else {
+            return;
+        };
+        let Some(partition) = disk.partitions.get(self.selected_partition) else {
+            return;
+        };
+        let Some(number) = partition.partition_number else {
+            self.status_message = Some("Cannot determine partition number".to_string());
+            return;
+        };
+
+        let result = validate_type_code(&input)
+            .and_then(|code| self.partition_manager.set_partition_type(&disk.device, number, &code).map(|_| code));
+        self.status_message = Some(match result {
+            Ok(code) => format!("Set type of {} to {}", partition.device, code),
+            Err(e) => format!("Type change failed: {}", e),
+        });
+        self.refresh_disks();
+    }
+
+    /// Take in progress from a running partition image and report when it ends
+    fn poll_image_job(&mut self) {
+        let Some(job) = self.image_job.as_mut() else {
+            return;
+        };
+
+        let finished = loop {
+            match job.progress.try_recv() {
+                Ok(ImageProgress::Copied(bytes)) => job.copied_bytes = bytes,
+                Ok(ImageProgress::Done(bytes)) => {
+                    break Some(format!(
+                        "Imaged {} to {} ({:.2} GB)",
+                        job.device,
+                        job.out_path.display(),
+                        bytes as f64 / (1024.0 * 1024.0 * 1024.0)
+                    ));
+                }
+                Ok(ImageProgress::Failed(error)) => break Some(format!("Image of {} failed: {}", job.device, error)),
+                Err(TryRecvError::Empty) => break None,
+                Err(TryRecvError::Disconnected) => break Some(format!("Image of {} stopped unexpectedly", job.device)),
+            }
+        };
+
+        if let Some(message) = finished {
+            self.status_message = Some(message);
+            self.image_job = None;
+        }
+    }
+
     pub async fn update(&mut self) -> Result<()> {
-        if self.last_update.elapsed() >= self.update_interval {
+        self.poll_fsck_job();
+        self.poll_image_job();
+        self.poll_benchmark_job();
+        self.poll_directory_usage();
+        self.poll_detail_job();
+        self.poll_threads_job();
+
+        let selected = self.filtered_processes.get(self.selected_process).map(|p| p.key());
+        if self.show_threads && selected != self.threads_of {
+            self.refresh_threads();
+        }
+
+        self.poll_deleted_files_job();
+        let scan_due = self.deleted_files_scanned.is_none_or(|at| at.elapsed() >= DELETED_FILES_SCAN_INTERVAL);
+        if self.current_tab == Tab::Storage && scan_due && self.deleted_files_job.is_none() {
+            self.deleted_files_job = Some(std::thread::spawn(|| scan_deleted_open_files(Path::new("/proc"))));
+            self.deleted_files_scanned = Some(Instant::now());
+        }
+
+        if self.refresh.is_due(Instant::now()) {
             self.monitor.refresh();
-            self.system_metrics = self.monitor.get_system_metrics()?;
-            self.processes = self.monitor.get_all_processes()?;
+            // A failed collection keeps the last data on screen, marked stale, until one succeeds
+            let collected = self.monitor.get_system_metrics().and_then(|metrics| Ok((metrics, self.monitor.get_all_processes()?)));
+            let Some((metrics, processes)) = self.refresh.record(collected, Instant::now()) else {
+                return Ok(());
+            };
+            self.system_metrics = metrics;
+            self.gauges = match self.gauge_smoothing.as_mut() {
+                Some(smoothing) => smoothing.update(&self.system_metrics),
+                None => GaugeValues::from(&self.system_metrics),
+            };
+            self.core_labels.update(self.system_metrics.cpu.per_core_usage.len());
+            if let Some(baseline) = self.baseline.as_mut() {
+                let (cpu, memory) = (self.system_metrics.cpu.total_usage as f64, self.system_metrics.memory.used_percent());
+                if let Err(e) = baseline.observe(chrono::Local::now(), cpu, memory) {
+                    tracing::warn!("Failed to save usage baseline: {:#}", e);
+                }
+            }
+            self.processes = processes;
+            self.system_counters = SystemCounters::collect(&self.processes);
 
             // Update services list
-            if let Ok(services) = self.service_manager.list_services() {
-                self.services = services;
-                self.filtered_services = self.services.clone();
+            let now = Instant::now();
+            if self.services_backoff.is_due(now) {
+                if let Some(services) = self.services_backoff.record(self.service_manager.list_services(), now) {
+                    self.services = services;
+                    self.sort_services();
+                }
             }
 
             // Check for misbehaving processes
@@ -371,78 +1216,65 @@ impl App {
                 let process_alerts = self.detector.check_process(process);
                 new_alerts.extend(process_alerts);
             }
+            new_alerts.extend(self.detector.check_system(&self.processes, &self.system_metrics));
+            let new_alerts = self.detector.limit_rate(new_alerts);
 
-            // Keep only recent alerts (last 100)
+            // Keep only the most recent alerts
             self.alerts.extend(new_alerts);
-            if self.alerts.len() > 100 {
-                self.alerts.drain(0..self.alerts.len() - 100);
+            if self.alerts.len() > self.max_alerts {
+                self.alerts.drain(0..self.alerts.len() - self.max_alerts);
             }
 
             // Cleanup detector state for dead processes
             let active_pids: Vec<u32> = self.processes.iter().map(|p| p.info.pid).collect();
             self.detector.cleanup_dead_processes(&active_pids);
+            self.new_processes.update(&active_pids, Instant::now());
+
+            if let Some(writer) = self.snapshots.as_mut() {
+                let snapshot = Snapshot {
+                    timestamp: self.system_metrics.timestamp,
+                    metrics: &self.system_metrics,
+                    processes: &self.processes,
+                    alerts: &self.alerts,
+                };
+                if let Err(e) = writer.write_if_due(&snapshot, Instant::now()) {
+                    tracing::warn!("Failed to write snapshot: {:#}", e);
+                }
+            }
 
             // Sort processes and apply filter
             self.sort_processes();
             self.filter_processes();
-
-            self.last_update = Instant::now();
+            if self.show_threads {
+                self.refresh_threads();
+            }
         }
 
         Ok(())
     }
 
+    fn sort_services(&mut self) {
+        procmon_core::sort::sort_services(&mut self.services, self.service_sort_column, self.service_sort_ascending);
+        self.filtered_services = self.services.clone();
+    }
+
+    pub fn next_service_sort_column(&mut self) {
+        self.service_sort_column = self.service_sort_column.next();
+        self.sort_services();
+    }
+
+    pub fn toggle_service_sort_ascending(&mut self) {
+        self.service_sort_ascending = !self.service_sort_ascending;
+        self.sort_services();
+    }
+
+    /// Switch the network tab between current rate and total traffic
+    pub fn toggle_network_sort(&mut self) {
+        self.network_sort = self.network_sort.toggle();
+    }
+
     fn sort_processes(&mut self) {
-        let ascending = self.sort_ascending;
-        match self.sort_column {
-            SortColumn::Name => {
-                self.processes.sort_by(|a, b| {
-                    if ascending {
-                        a.info.name.cmp(&b.info.name)
-                    } else {
-                        b.info.name.cmp(&a.info.name)
-                    }
-                });
-            }
-            SortColumn::Cpu => {
-                self.processes.sort_by(|a, b| {
-                    if ascending {
-                        a.stats.cpu_usage.partial_cmp(&b.stats.cpu_usage).unwrap()
-                    } else {
-                        b.stats.cpu_usage.partial_cmp(&a.stats.cpu_usage).unwrap()
-                    }
-                });
-            }
-            SortColumn::Memory => {
-                self.processes.sort_by(|a, b| {
-                    if ascending {
-                        a.stats.memory_usage.cmp(&b.stats.memory_usage)
-                    } else {
-                        b.stats.memory_usage.cmp(&a.stats.memory_usage)
-                    }
-                });
-            }
-            SortColumn::DiskIo => {
-                self.processes.sort_by(|a, b| {
-                    let a_io = a.stats.disk_read_bytes + a.stats.disk_write_bytes;
-                    let b_io = b.stats.disk_read_bytes + b.stats.disk_write_bytes;
-                    if ascending {
-                        a_io.cmp(&b_io)
-                    } else {
-                        b_io.cmp(&a_io)
-                    }
-                });
-            }
-            SortColumn::User => {
-                self.processes.sort_by(|a, b| {
-                    if ascending {
-                        a.info.user.cmp(&b.info.user)
-                    } else {
-                        b.info.user.cmp(&a.info.user)
-                    }
-                });
-            }
-        }
+        procmon_core::sort::sort_processes(&mut self.processes, self.sort_column, self.sort_ascending);
     }
 
     pub fn next_process(&mut self) {
@@ -463,9 +1295,38 @@ impl App {
         }
     }
 
+    /// Select the parent of the selected process, clearing the search if it hides the parent.
+    pub fn select_parent_process(&mut self) {
+        let Some(child) = self.filtered_processes.get(self.selected_process).cloned() else {
+            return;
+        };
+
+        let mut index = find_parent_index(&self.filtered_processes, &child);
+        if index.is_none() && find_parent_index(&self.processes, &child).is_some() {
+            self.search_mode = false;
+            self.search_query.clear();
+            self.show_only_misbehaving = false;
+            self.state_filter.clear();
+            self.filter_processes();
+            index = find_parent_index(&self.filtered_processes, &child);
+        }
+
+        match index {
+            Some(index) => {
+                self.selected_process = index;
+                self.ensure_selected_visible();
+                self.status_message = None;
+            }
+            None => self.status_message = Some("parent not found".to_string()),
+        }
+    }
+
     fn ensure_selected_visible(&mut self) {
-        // Assume visible area is around 20 rows (will be adjusted dynamically in UI)
-        let visible_rows = 20;
+        // Until the table has been drawn, assume around 20 rows
+        let visible_rows = self
+            .process_list_area
+            .map_or(20, |(_, _, _, height)| self.density.visible_rows(height))
+            .max(1);
 
         // If selected is below visible area, scroll down
         if self.selected_process >= self.scroll_offset + visible_rows {
@@ -478,11 +1339,6 @@ impl App {
         }
     }
 
-    pub fn set_visible_rows(&mut self, rows: usize) {
-        // This will be called from UI to set the actual visible area
-        // For now we use a default in ensure_selected_visible
-    }
-
     pub fn next_tab(&mut self) {
         self.current_tab = match self.current_tab {
             Tab::Dashboard => Tab::Processes,
@@ -491,65 +1347,312 @@ impl App {
             Tab::Storage => Tab::Network,
             Tab::Network => Tab::Partitions,
             Tab::Partitions => Tab::Alerts,
-            Tab::Alerts => Tab::Dashboard,
+            Tab::Alerts => Tab::Logs,
+            Tab::Logs => Tab::Dashboard,
         };
     }
 
     pub fn previous_tab(&mut self) {
         self.current_tab = match self.current_tab {
-            Tab::Dashboard => Tab::Alerts,
+            Tab::Dashboard => Tab::Logs,
             Tab::Processes => Tab::Dashboard,
             Tab::Services => Tab::Processes,
             Tab::Storage => Tab::Services,
             Tab::Network => Tab::Storage,
             Tab::Partitions => Tab::Network,
             Tab::Alerts => Tab::Partitions,
+            Tab::Logs => Tab::Alerts,
         };
     }
 
     pub fn set_tab(&mut self, index: usize) {
-        self.current_tab = match index {
-            0 => Tab::Dashboard,
-            1 => Tab::Processes,
-            2 => Tab::Services,
-            3 => Tab::Storage,
-            4 => Tab::Network,
-            5 => Tab::Partitions,
-            6 => Tab::Alerts,
-            _ => self.current_tab,
-        };
+        if let Some(tab) = Tab::ALL.get(index) {
+            self.current_tab = *tab;
+        }
     }
 
     pub fn toggle_sort_ascending(&mut self) {
         self.sort_ascending = !self.sort_ascending;
         self.sort_processes();
+        self.filter_processes();
     }
 
     pub fn next_sort_column(&mut self) {
         self.sort_column = match self.sort_column {
             SortColumn::Name => SortColumn::Cpu,
-            SortColumn::Cpu => SortColumn::Memory,
-            SortColumn::Memory => SortColumn::DiskIo,
+            SortColumn::Cpu => SortColumn::CpuAvg,
+            SortColumn::CpuAvg => SortColumn::Memory,
+            SortColumn::Memory => SortColumn::MemoryGrowth,
+            SortColumn::MemoryGrowth => SortColumn::DiskIo,
             SortColumn::DiskIo => SortColumn::User,
-            SortColumn::User => SortColumn::Name,
+            SortColumn::User => SortColumn::Age,
+            SortColumn::Age => SortColumn::Swap,
+            SortColumn::Swap | SortColumn::Pid => SortColumn::Name,
         };
         self.sort_processes();
+        self.filter_processes();
+    }
+
+    /// Copy the selected process, service or partition row as a line of text
+    pub fn copy_selected(&mut self) {
+        let text = match self.current_tab {
+            Tab::Processes => self.filtered_processes.get(self.selected_process).map(|p| p.clipboard_text()),
+            Tab::Services => self.filtered_services.get(self.selected_service).map(|s| s.clipboard_text()),
+            Tab::Partitions => self
+                .disks
+                .get(self.selected_disk)
+                .and_then(|disk| disk.partitions.get(self.selected_partition))
+                .map(|p| p.clipboard_text()),
+            _ => None,
+        };
+
+        let Some(text) = text else {
+            self.status_message = Some("Nothing selected to copy".to_string());
+            return;
+        };
+        self.copy_to_clipboard(text);
+    }
+
+    fn copy_to_clipboard(&mut self, text: String) {
+        if self.clipboard.is_none() {
+            match arboard::Clipboard::new() {
+                Ok(clipboard) => self.clipboard = Some(clipboard),
+                Err(e) => {
+                    self.status_message = Some(format!("Clipboard unavailable: {}", e));
+                    return;
+                }
+            }
+        }
+
+        if let Some(clipboard) = self.clipboard.as_mut() {
+            self.status_message = Some(match clipboard.set_text(text) {
+                Ok(_) => "Copied selection to clipboard".to_string(),
+                Err(e) => format!("Copy failed: {}", e),
+            });
+        }
+    }
+
+    /// Time since the last successful refresh
+    pub fn since_last_update(&self) -> Duration {
+        self.refresh.last_update().elapsed()
+    }
+
+    pub fn staleness(&self) -> Staleness {
+        self.refresh.staleness(Instant::now())
+    }
+
+    /// The last refresh failed, so the data shown is from the last one that worked
+    pub fn refresh_failing(&self) -> bool {
+        self.refresh.failing_since().is_some()
+    }
+
+    /// Badge for the Alerts tab title; alerts count as seen while that tab is shown
+    pub fn alert_badge(&mut self) -> AlertBadge {
+        if self.current_tab == Tab::Alerts {
+            self.alert_acknowledgement.acknowledge(&self.alerts);
+        }
+        self.alert_acknowledgement.badge(&self.alerts)
+    }
+
+    /// Ring the configured alert sound if a critical alert appeared since the last frame
+    pub fn ring_alert_bell(&mut self) {
+        if self.alert_bell.should_ring(&self.alerts, Instant::now()) {
+            if let Err(e) = self.alert_bell.ring() {
+                self.status_message = Some(format!("Alert sound failed: {}", e));
+            }
+        }
+    }
+
+    /// Refresh on the next update instead of waiting out the interval
+    pub fn refresh_now(&mut self) {
+        self.refresh.request_now();
+    }
+
+    /// Switch the dashboard core chart between usage bars and the core-to-process map
+    pub fn toggle_core_map(&mut self) {
+        self.show_core_map = !self.show_core_map;
+    }
+
+    /// Switch the Processes tab between the process list and per-container totals
+    pub fn toggle_container_view(&mut self) {
+        self.show_containers = !self.show_containers;
+        self.show_users = false;
+        self.show_gpu_processes = false;
+        self.show_memory_growth = false;
+    }
+
+    /// Switch the Processes tab between the process list and per-user totals
+    pub fn toggle_user_view(&mut self) {
+        self.show_users = !self.show_users;
+        self.show_containers = false;
+        self.show_gpu_processes = false;
+        self.show_memory_growth = false;
+    }
+
+    /// Switch the Processes tab between the process list and processes using a GPU
+    pub fn toggle_gpu_view(&mut self) {
+        self.show_gpu_processes = !self.show_gpu_processes;
+        self.show_containers = false;
+        self.show_users = false;
+        self.show_memory_growth = false;
+    }
+
+    /// Switch the Processes tab between the process list and the biggest memory growth
+    pub fn toggle_memory_growth_view(&mut self) {
+        self.show_memory_growth = !self.show_memory_growth;
+        self.show_containers = false;
+        self.show_users = false;
+        self.show_gpu_processes = false;
+    }
+
+    /// List the threads of the selected process under it, or fold them away again
+    pub fn toggle_threads(&mut self) {
+        self.show_threads = !self.show_threads;
+        self.refresh_threads();
+    }
+
+    /// Re-read the threads of the selected process on a background thread, e.g. after it
+    /// changed or a refresh. While a read is running another one isn't started; the finished
+    /// read starts the next if the selection moved on in the meantime.
+    fn refresh_threads(&mut self) {
+        let key = self
+            .filtered_processes
+            .get(self.selected_process)
+            .map(|p| p.key())
+            .filter(|_| self.show_threads);
+        if key != self.threads_of {
+            self.threads = Capped::default();
+        }
+        self.threads_of = key;
+        if let (Some(key), None) = (key, &self.threads_job) {
+            let usage = self.monitor.thread_usage(key.pid);
+            let max_entries = self.detail_entries;
+            self.threads_job = Some((key, std::thread::spawn(move || read_threads(key.pid, &usage, max_entries))));
+        }
+    }
+
+    fn poll_threads_job(&mut self) {
+        if !self.threads_job.as_ref().is_some_and(|(_, job)| job.is_finished()) {
+            return;
+        }
+        if let Some((key, job)) = self.threads_job.take() {
+            let threads = job.join().unwrap_or_default();
+            if self.threads_of == Some(key) {
+                self.threads = threads;
+            } else if self.threads_of.is_some() {
+                self.refresh_threads();
+            }
+        }
+    }
+
+    pub fn next_user(&mut self) {
+        let count = group_by_user(&self.processes).len();
+        if count > 0 {
+            self.selected_user = (self.selected_user + 1) % count;
+        }
+    }
+
+    pub fn previous_user(&mut self) {
+        let count = group_by_user(&self.processes).len();
+        if count > 0 {
+            self.selected_user = (self.selected_user + count - 1) % count;
+        }
+    }
+
+    /// Ask for the user name before terminating every process of the selected user
+    pub fn start_user_kill(&mut self) {
+        let groups = group_by_user(&self.processes);
+        let Some(group) = groups.get(self.selected_user.min(groups.len().saturating_sub(1))) else {
+            return;
+        };
+        if group.uid == 0 {
+            self.status_message = Some("Refusing to kill every root process".to_string());
+            return;
+        }
+        self.user_kill_prompt = Some(UserKillPrompt {
+            uid: group.uid,
+            user: group.user.clone(),
+            processes: group.processes,
+            typed: String::new(),
+            signal: Signal::Term,
+        });
+    }
+
+    pub fn cancel_user_kill(&mut self) {
+        self.user_kill_prompt = None;
+    }
+
+    pub fn add_user_kill_char(&mut self, c: char) {
+        if let Some(ref mut prompt) = self.user_kill_prompt {
+            prompt.typed.push(c);
+        }
+    }
+
+    pub fn remove_user_kill_char(&mut self) {
+        if let Some(ref mut prompt) = self.user_kill_prompt {
+            prompt.typed.pop();
+        }
+    }
+
+    /// Switch the prompt between SIGTERM and SIGKILL
+    pub fn toggle_user_kill_signal(&mut self) {
+        if let Some(ref mut prompt) = self.user_kill_prompt {
+            prompt.signal = match prompt.signal {
+                Signal::Term => Signal::Kill,
+                Signal::Kill => Signal::Term,
+            };
+        }
+    }
+
+    /// Signal every process of the prompted user if the typed name matches
+    pub fn confirm_user_kill(&mut self) {
+        let Some(prompt) = self.user_kill_prompt.take() else {
+            return;
+        };
+        if prompt.typed != prompt.user {
+            self.status_message = Some(format!("Typed name does not match {}; nothing was killed", prompt.user));
+            return;
+        }
+        self.status_message = Some(match self.monitor.kill_user_processes(prompt.uid, prompt.signal, &self.protected) {
+            Ok(results) => summarize_signal_results(&prompt.user, prompt.signal, &results),
+            Err(e) => format!("Cannot list processes of {}: {}", prompt.user, e),
+        });
+    }
+
+    pub fn toggle_gauge_smoothing(&mut self) {
+        self.gauge_smoothing = match self.gauge_smoothing {
+            Some(_) => None,
+            None => Some(GaugeSmoothing::new(self.smoothing_alpha)),
+        };
+        self.gauges = GaugeValues::from(&self.system_metrics);
+    }
+
+    pub fn toggle_system_info(&mut self) {
+        self.show_system_info = !self.show_system_info;
+    }
+
+    pub fn toggle_command_line(&mut self) {
+        self.show_command_line = !self.show_command_line;
     }
 
     pub fn toggle_filter(&mut self) {
         self.show_only_misbehaving = !self.show_only_misbehaving;
     }
 
+    pub fn toggle_state_filter(&mut self, status: ProcessStatus) {
+        self.state_filter.toggle(status);
+        self.filter_processes();
+    }
+
+    /// Show only zombies, or everything again if that is already the filter
+    pub fn toggle_zombies_only(&mut self) {
+        let zombies_only = StateFilter::only(ProcessStatus::Zombie);
+        self.state_filter = if self.state_filter == zombies_only { StateFilter::default() } else { zombies_only };
+        self.filter_processes();
+    }
+
     pub fn get_tab_index(&self) -> usize {
-        match self.current_tab {
-            Tab::Dashboard => 0,
-            Tab::Processes => 1,
-            Tab::Services => 2,
-            Tab::Storage => 3,
-            Tab::Network => 4,
-            Tab::Partitions => 5,
-            Tab::Alerts => 6,
-        }
+        self.current_tab.index()
     }
 
     pub fn toggle_context_menu(&mut self) {
@@ -563,12 +1666,134 @@ impl App {
         }
     }
 
+    /// Open the detail popup for the process the context menu is open for
+    pub fn open_process_detail(&mut self) {
+        let Some(pid) = self.context_menu_pid else {
+            return;
+        };
+        if self.detail_job.is_some() {
+            return;
+        }
+        if let Some(process) = self.processes.iter().find(|p| p.info.pid == pid) {
+            // Listing the descriptors of a busy server takes a while; keep the UI responsive
+            let process = process.clone();
+            let user_threads = user_thread_count(&self.processes, process.info.uid);
+            let (debug, max_entries) = (self.debug, self.detail_entries);
+            self.detail_job = Some(std::thread::spawn(move || {
+                let mut detail = ProcessDetail::load(&process, user_threads, max_entries);
+                if debug {
+                    detail.load_raw_fields();
+                }
+                detail
+            }));
+            self.status_message = Some(format!("Loading details of PID {}...", pid));
+            self.show_context_menu = false;
+        }
+    }
+
+    /// Show the detail popup once its background load finished
+    fn poll_detail_job(&mut self) {
+        if !self.detail_job.as_ref().is_some_and(|job| job.is_finished()) {
+            return;
+        }
+        if let Some(job) = self.detail_job.take() {
+            match job.join() {
+                Ok(detail) => {
+                    self.process_detail = Some(detail);
+                    self.detail_scroll = 0;
+                    self.status_message = None;
+                }
+                Err(_) => self.status_message = Some("Loading process details panicked".to_string()),
+            }
+        }
+    }
+
+    fn poll_deleted_files_job(&mut self) {
+        if !self.deleted_files_job.as_ref().is_some_and(|job| job.is_finished()) {
+            return;
+        }
+        if let Some(job) = self.deleted_files_job.take() {
+            match job.join() {
+                Ok(files) => self.deleted_files = files,
+                Err(_) => self.status_message = Some("Scanning for deleted open files panicked".to_string()),
+            }
+        }
+    }
+
+    pub fn close_process_detail(&mut self) {
+        self.process_detail = None;
+    }
+
+    pub fn scroll_detail_up(&mut self, lines: u16) {
+        self.detail_scroll = self.detail_scroll.saturating_sub(lines);
+    }
+
+    /// The popup clamps the offset to its content when drawing
+    pub fn scroll_detail_down(&mut self, lines: u16) {
+        self.detail_scroll = self.detail_scroll.saturating_add(lines);
+    }
+
+    /// Copy the untruncated command line and environment of the open detail popup
+    pub fn copy_process_detail(&mut self) {
+        if let Some(text) = self.process_detail.as_ref().map(|detail| detail.full_text()) {
+            self.copy_to_clipboard(text);
+        }
+    }
+
+    pub fn start_throttle_input(&mut self) {
+        if self.context_menu_pid.is_some() {
+            self.throttle_input = Some(String::new());
+        }
+    }
+
+    pub fn cancel_throttle_input(&mut self) {
+        self.throttle_input = None;
+    }
+
+    pub fn add_throttle_char(&mut self, c: char) {
+        if let Some(ref mut input) = self.throttle_input {
+            if c.is_ascii_digit() || c == ' ' {
+                input.push(c);
+            }
+        }
+    }
+
+    pub fn remove_throttle_char(&mut self) {
+        if let Some(ref mut input) = self.throttle_input {
+            input.pop();
+        }
+    }
+
+    /// Apply the limits typed into the throttle prompt to the context-menu process
+    pub fn apply_throttle(&mut self) -> Result<()> {
+        let (Some(pid), Some(input)) = (self.context_menu_pid, self.throttle_input.take()) else {
+            return Ok(());
+        };
+
+        let result = parse_throttle_input(&input).and_then(|limits| match limits {
+            (None, None) => self.monitor.remove_io_throttle(pid).map(|()| format!("Lifted the I/O throttle of PID {}", pid)),
+            (read_bps, write_bps) => self
+                .monitor
+                .throttle_io(pid, read_bps, write_bps)
+                .map(|cgroup| format!("Throttled I/O of PID {} via {}", pid, cgroup.display())),
+        });
+        self.status_message = Some(match result {
+            Ok(message) => message,
+            Err(e) => format!("Throttle failed: {}", e),
+        });
+        self.show_context_menu = false;
+        self.context_menu_pid = None;
+
+        Ok(())
+    }
+
     pub fn kill_process(&mut self) -> Result<()> {
         if let Some(pid) = self.context_menu_pid {
-            use std::process::Command;
-            Command::new("kill")
-                .arg(pid.to_string())
-                .output()?;
+            if let Some(process) = self.processes.iter().find(|p| p.info.pid == pid) {
+                if let Err(e) = kill_process(&process.info, &self.protected) {
+                    self.status_message = Some(format!("Cannot kill PID {}: {}", pid, e));
+                }
+            }
             self.show_context_menu = false;
             self.context_menu_pid = None;
 
@@ -583,13 +1808,12 @@ impl App {
 
     pub fn kill_process_tree(&mut self) -> Result<()> {
         if let Some(pid) = self.context_menu_pid {
-            use std::process::Command;
             // Kill process and all children
-            Command::new("kill")
-                .arg("-TERM")
-                .arg("--")
-                .arg(format!("-{}", pid))
-                .output()?;
+            if let Some(process) = self.processes.iter().find(|p| p.info.pid == pid) {
+                if let Err(e) = kill_process_tree(&process.info, &self.processes, &self.protected) {
+                    self.status_message = Some(format!("Cannot kill PID {} and children: {}", pid, e));
+                }
+            }
             self.show_context_menu = false;
             self.context_menu_pid = None;
 
@@ -602,14 +1826,35 @@ impl App {
         Ok(())
     }
 
+    /// Send SIGCHLD to the parent of the context-menu zombie so it reaps it
+    pub fn signal_zombie_parent(&mut self) {
+        let Some(pid) = self.context_menu_pid else {
+            return;
+        };
+
+        let result = match self.processes.iter().find(|p| p.info.pid == pid) {
+            Some(process) => signal_parent(&process.info),
+            None => Err(anyhow::anyhow!("Process {} not found", pid)),
+        };
+        self.status_message = Some(match result {
+            Ok(parent) => format!("Sent SIGCHLD to parent PID {}; stop the parent if PID {} stays a zombie", parent, pid),
+            Err(e) => format!("Signal parent failed: {}", e),
+        });
+        self.show_context_menu = false;
+        self.context_menu_pid = None;
+    }
+
     pub fn open_process_folder(&mut self) -> Result<()> {
         if let Some(pid) = self.context_menu_pid {
             if let Some(process) = self.processes.iter().find(|p| p.info.pid == pid) {
                 if let Some(exe_path) = &process.info.exe_path {
                     if let Some(parent) = exe_path.parent() {
-                        use std::process::Command;
+                        use std::process::{Command, Stdio};
+                        // Its warnings would land on the display
                         Command::new("xdg-open")
                             .arg(parent)
+                            .stdout(Stdio::null())
+                            .stderr(Stdio::null())
                             .spawn()?;
                     }
                 }
@@ -623,27 +1868,9 @@ impl App {
     pub fn restart_process(&mut self) -> Result<()> {
         if let Some(pid) = self.context_menu_pid {
             if let Some(process) = self.processes.iter().find(|p| p.info.pid == pid) {
-                // Get the command line and executable path
-                let exe_path = process.info.exe_path.clone();
-                let cmd_line = process.info.command_line.clone();
-
-                // Kill the process first
-                use std::process::Command;
-                Command::new("kill")
-                    .arg(pid.to_string())
-                    .output()?;
-
-                // Wait a bit for the process to terminate
-                std::thread::sleep(std::time::Duration::from_millis(100));
-
-                // Restart the process with the same command line
-                if let Some(exe) = exe_path {
-                    let mut command = Command::new(exe);
-                    if cmd_line.len() > 1 {
-                        // Skip the first argument (the executable itself)
-                        command.args(&cmd_line[1..]);
-                    }
-                    command.spawn()?;
+                // Refused restarts (e.g. another user's process without root) leave it running
+                if let Err(e) = restart_process(&process.info, &self.protected) {
+                    self.status_message = Some(format!("Cannot restart PID {}: {}", pid, e));
                 }
             }
             self.show_context_menu = false;
@@ -679,7 +1906,16 @@ impl App {
         if !self.filtered_services.is_empty() && self.selected_service < self.filtered_services.len() {
             self.show_service_menu = !self.show_service_menu;
             if self.show_service_menu {
-                self.context_menu_service = Some(self.filtered_services[self.selected_service].name.clone());
+                let name = self.filtered_services[self.selected_service].name.clone();
+                self.service_dependencies = self.service_manager.dependencies(&name).unwrap_or_default();
+                self.service_dependents = match self.service_manager.dependents(&name) {
+                    Ok(dependents) => dependents,
+                    Err(e) => {
+                        self.status_message = Some(format!("{:#}", e));
+                        Vec::new()
+                    }
+                };
+                self.context_menu_service = Some(name);
             } else {
                 self.context_menu_service = None;
             }
@@ -696,22 +1932,46 @@ impl App {
             // Refresh service list
             if let Ok(services) = self.service_manager.list_services() {
                 self.services = services;
-                self.filtered_services = self.services.clone();
+                self.sort_services();
             }
         }
         Ok(())
     }
 
+    /// Stop the service-menu service, asking for confirmation first if other units depend on it
     pub fn stop_service(&mut self) -> Result<()> {
+        let Some(service_name) = self.context_menu_service.clone() else {
+            return Ok(());
+        };
+
+        match plan_stop(self.service_manager.dependents(&service_name)) {
+            Ok(StopPlan::Immediate) => self.confirm_stop(StopMode::Default),
+            Ok(StopPlan::NeedsConfirmation { dependents }) => {
+                self.pending_stop = Some(dependents);
+                Ok(())
+            }
+            Err(e) => {
+                self.status_message = Some(format!("{:#}", e));
+                Err(e)
+            }
+        }
+    }
+
+    pub fn cancel_stop(&mut self) {
+        self.pending_stop = None;
+    }
+
+    pub fn confirm_stop(&mut self, mode: StopMode) -> Result<()> {
+        self.pending_stop = None;
         if let Some(ref service_name) = self.context_menu_service {
-            self.service_manager.stop_service(service_name)?;
+            self.service_manager.stop_service_with_mode(service_name, mode)?;
             self.show_service_menu = false;
             self.context_menu_service = None;
 
             // Refresh service list
             if let Ok(services) = self.service_manager.list_services() {
                 self.services = services;
-                self.filtered_services = self.services.clone();
+                self.sort_services();
             }
         }
         Ok(())
@@ -726,7 +1986,7 @@ impl App {
             // Refresh service list
             if let Ok(services) = self.service_manager.list_services() {
                 self.services = services;
-                self.filtered_services = self.services.clone();
+                self.sort_services();
             }
         }
         Ok(())
@@ -741,7 +2001,7 @@ impl App {
             // Refresh service list
             if let Ok(services) = self.service_manager.list_services() {
                 self.services = services;
-                self.filtered_services = self.services.clone();
+                self.sort_services();
             }
         }
         Ok(())
@@ -756,7 +2016,7 @@ impl App {
             // Refresh service list
             if let Ok(services) = self.service_manager.list_services() {
                 self.services = services;
-                self.filtered_services = self.services.clone();
+                self.sort_services();
             }
         }
         Ok(())
@@ -8,6 +8,26 @@ use crossterm::{
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
+use procmon_core::export::ExportFormat;
+use procmon_core::columns::{process_columns, COLUMNS_ENV};
+use procmon_core::tab::{startup_tab, STARTUP_TAB_ENV};
+use procmon_core::temperature::{temperature_unit, TEMP_UNIT_ENV};
+use procmon_core::density::{density, DENSITY_ENV};
+use procmon_core::protect::ProtectedSet;
+use procmon_core::bell::{alert_sound, ALERT_SOUND_ENV};
+use procmon_core::health::{run_check, HealthReport, CHECK_FLAG};
+use procmon_core::once::{once_options, run_once};
+use procmon_core::logging::{default_log_file, init_logging, log_config, LogTarget};
+use procmon_core::snapshot::{snapshot_config, SnapshotWriter, SNAPSHOT_DIR_ENV};
+use procmon_core::baseline::{baseline_sigma, BaselineTracker, BASELINE_SIGMA_ENV};
+use procmon_core::own::{self_processes, SELF_PROCESSES_ENV};
+use procmon_core::click::{double_click, DOUBLE_CLICK_ENV};
+use procmon_core::privilege::{escalation, ESCALATION_ENV};
+use procmon_core::detail::DEBUG_FLAG;
+use procmon_core::config::Config;
+use procmon_core::MisbehaviorDetector;
+use procmon_core::StopMode;
+use procmon_core::process::ProcessStatus;
 use ratatui::{
     backend::CrosstermBackend,
     Terminal,
@@ -17,21 +37,90 @@ use std::time::Duration;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Setup logging
-    tracing_subscriber::fmt::init();
+    // Resolve preferences before touching the terminal so errors print normally
+    let args: Vec<String> = std::env::args().skip(1).collect();
 
-    // Setup terminal
+    // Logs go to a file unless asked otherwise; stderr would draw over the display
+    let default_log = default_log_file("procmon-tui.log").map_or(LogTarget::Off, LogTarget::File);
+    let rust_log = std::env::var("RUST_LOG").ok();
+    let log_sinks = init_logging(&log_config(args.clone(), rust_log.as_deref(), default_log)?)?;
+
+    if args.iter().any(|arg| arg == CHECK_FLAG) {
+        let report = run_check(&mut MisbehaviorDetector::new()).unwrap_or_else(|e| HealthReport::unknown(&e));
+        println!("{}", report.summary);
+        std::process::exit(report.status.exit_code());
+    }
+    // config.toml fills in whatever the command line and environment leave open
+    let (config, mut config_warnings) = Config::load();
+    let (rules, rule_warnings) = config.load_rules();
+    config_warnings.extend(rule_warnings);
+    for warning in &config_warnings {
+        tracing::warn!("Config: {}", warning);
+    }
+    let tab_preference = std::env::var(STARTUP_TAB_ENV).ok().or(config.general.startup_tab.clone());
+    let startup_tab = startup_tab(args.clone(), tab_preference.as_deref())?;
+    let columns_preference = std::env::var(COLUMNS_ENV).ok().or(config.display.columns.clone());
+    let process_columns = process_columns(args.clone(), columns_preference.as_deref())?;
+    if let Some(options) = once_options(args.clone())? {
+        println!("{}", run_once(&options, &process_columns)?);
+        return Ok(());
+    }
+    let temp_unit_preference = std::env::var(TEMP_UNIT_ENV).ok().or(config.display.temperature_unit.clone());
+    let temperature_unit = temperature_unit(args.clone(), temp_unit_preference.as_deref())?;
+    let density_preference = std::env::var(DENSITY_ENV).ok().or(config.display.density.clone());
+    let density = density(args.clone(), density_preference.as_deref())?;
+    let snapshot_preference = std::env::var(SNAPSHOT_DIR_ENV).ok();
+    let snapshots = snapshot_config(args.clone(), snapshot_preference.as_deref())?;
+    let sigma_preference = std::env::var(BASELINE_SIGMA_ENV).ok();
+    let baseline_sigma = baseline_sigma(args.clone(), sigma_preference.as_deref())?;
+    let alert_sound_preference = std::env::var(ALERT_SOUND_ENV).ok();
+    let self_processes_preference = std::env::var(SELF_PROCESSES_ENV).ok();
+    let self_processes = self_processes(args.clone(), self_processes_preference.as_deref())?;
+    let double_click_preference = std::env::var(DOUBLE_CLICK_ENV).ok();
+    let double_click = double_click(args.clone(), double_click_preference.as_deref())?;
+    let escalation_preference = std::env::var(ESCALATION_ENV).ok();
+    let escalation = escalation(args.clone(), escalation_preference.as_deref())?;
+    let debug = args.iter().any(|arg| arg == DEBUG_FLAG);
+    let alert_sound = alert_sound(args, alert_sound_preference.as_deref())?;
+    let mut protected = ProtectedSet::load()?;
+    protected.add_names(config.lists.protect.clone());
+
+    // Setup terminal; `--log-file -` output waits until the display is gone
+    log_sinks.stderr.hold();
+    let default_panic = std::panic::take_hook();
+    let log_gate_on_panic = log_sinks.stderr.clone();
+    std::panic::set_hook(Box::new(move |info| {
+        let _ = disable_raw_mode();
+        let _ = execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture);
+        let _ = log_gate_on_panic.release();
+        default_panic(info);
+    }));
     enable_raw_mode()?;
     let mut stdout = io::stdout();
     execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    // Create app
-    let mut app = App::new().await?;
-
-    // Run app
-    let res = run_app(&mut terminal, &mut app).await;
+    // Create and run app; the terminal is restored even if startup fails
+    let res = match App::new(startup_tab, process_columns, temperature_unit, density, protected, alert_sound, log_sinks.recent.clone()).await {
+        Ok(mut app) => {
+            app.set_baseline_tracker(BaselineTracker::load_default(baseline_sigma));
+            app.set_self_processes(self_processes);
+            app.set_double_click(double_click);
+            app.set_escalation(escalation);
+            app.set_debug(debug);
+            app.apply_config(&config);
+            app.set_config_warnings(config_warnings);
+            if let Some(rules) = rules {
+                app.set_rules(rules);
+            }
+            if let Some(config) = snapshots {
+                app.set_snapshot_writer(SnapshotWriter::new(config));
+            }
+            run_app(&mut terminal, &mut app).await
+        }
+        Err(e) => Err(e),
+    };
 
     // Restore terminal
     disable_raw_mode()?;
@@ -41,6 +130,7 @@ async fn main() -> Result<()> {
         DisableMouseCapture
     )?;
     terminal.show_cursor()?;
+    log_sinks.stderr.release()?;
 
     if let Err(err) = res {
         eprintln!("Error: {:?}", err);
@@ -55,12 +145,70 @@ async fn run_app<B: ratatui::backend::Backend>(
 ) -> Result<()> {
     loop {
         terminal.draw(|f| ui::draw(f, app))?;
+        app.ring_alert_bell();
 
         if event::poll(Duration::from_millis(100))? {
             match event::read()? {
                 Event::Key(key) => {
-                    // Handle search mode separately
-                    if app.search_mode {
+                    // Confirmation popups, the throttle, user kill and partition type prompts and search mode take all typed keys
+                    if app.pending_stop.is_some() {
+                        match key.code {
+                            KeyCode::Char('y') => {
+                                let _ = app.confirm_stop(StopMode::Default);
+                            }
+                            KeyCode::Char('R') => {
+                                let _ = app.confirm_stop(StopMode::ReplaceIrreversibly);
+                            }
+                            KeyCode::Char('n') | KeyCode::Esc => app.cancel_stop(),
+                            _ => {}
+                        }
+                    } else if app.process_detail.is_some() {
+                        match key.code {
+                            KeyCode::Up => app.scroll_detail_up(1),
+                            KeyCode::Down => app.scroll_detail_down(1),
+                            KeyCode::PageUp => app.scroll_detail_up(10),
+                            KeyCode::PageDown => app.scroll_detail_down(10),
+                            KeyCode::Char('y') => app.copy_process_detail(),
+                            KeyCode::Esc | KeyCode::Char('q') => app.close_process_detail(),
+                            _ => {}
+                        }
+                    } else if app.directory_usage.is_some() {
+                        match key.code {
+                            KeyCode::Up => app.previous_directory(),
+                            KeyCode::Down => app.next_directory(),
+                            KeyCode::Enter => app.open_selected_directory(),
+                            KeyCode::Backspace => app.directory_usage_up(),
+                            KeyCode::Esc | KeyCode::Char('q') => app.close_directory_usage(),
+                            _ => {}
+                        }
+                    } else if app.throttle_input.is_some() {
+                        match key.code {
+                            KeyCode::Char(c) => app.add_throttle_char(c),
+                            KeyCode::Backspace => app.remove_throttle_char(),
+                            KeyCode::Esc => app.cancel_throttle_input(),
+                            KeyCode::Enter => {
+                                let _ = app.apply_throttle();
+                            }
+                            _ => {}
+                        }
+                    } else if app.user_kill_prompt.is_some() {
+                        match key.code {
+                            KeyCode::Char(c) => app.add_user_kill_char(c),
+                            KeyCode::Backspace => app.remove_user_kill_char(),
+                            KeyCode::Tab => app.toggle_user_kill_signal(),
+                            KeyCode::Esc => app.cancel_user_kill(),
+                            KeyCode::Enter => app.confirm_user_kill(),
+                            _ => {}
+                        }
+                    } else if app.partition_type_input.is_some() {
+                        match key.code {
+                            KeyCode::Char(c) => app.add_partition_type_char(c),
+                            KeyCode::Backspace => app.remove_partition_type_char(),
+                            KeyCode::Esc => app.cancel_partition_type_input(),
+                            KeyCode::Enter => app.apply_partition_type(),
+                            _ => {}
+                        }
+                    } else if app.search_mode {
                         match key.code {
                             KeyCode::Char(c) => app.add_search_char(c),
                             KeyCode::Backspace => app.remove_search_char(),
@@ -75,11 +223,16 @@ async fn run_app<B: ratatui::backend::Backend>(
                                 return Ok(());
                             }
                             KeyCode::Char('/') => app.toggle_search_mode(),
+                            KeyCode::F(5) => app.refresh_now(),
                             KeyCode::Up => {
                                 if app.current_tab == app::Tab::Partitions {
                                     app.previous_partition();
                                 } else if app.current_tab == app::Tab::Services {
                                     app.previous_service();
+                                } else if app.current_tab == app::Tab::Processes && app.show_users {
+                                    app.previous_user();
+                                } else if app.current_tab == app::Tab::Logs {
+                                    app.scroll_logs_up(1);
                                 } else {
                                     app.previous_process();
                                 }
@@ -89,10 +242,17 @@ async fn run_app<B: ratatui::backend::Backend>(
                                     app.next_partition();
                                 } else if app.current_tab == app::Tab::Services {
                                     app.next_service();
+                                } else if app.current_tab == app::Tab::Processes && app.show_users {
+                                    app.next_user();
+                                } else if app.current_tab == app::Tab::Logs {
+                                    app.scroll_logs_down(1);
                                 } else {
                                     app.next_process();
                                 }
                             }
+                            KeyCode::PageUp if app.current_tab == app::Tab::Logs => app.scroll_logs_up(10),
+                            KeyCode::PageDown if app.current_tab == app::Tab::Logs => app.scroll_logs_down(10),
+                            KeyCode::End if app.current_tab == app::Tab::Logs => app.follow_logs(),
                             KeyCode::PageUp => app.scroll_up(10),
                             KeyCode::PageDown => app.scroll_down(10, 20),
                             KeyCode::Left if app.current_tab == app::Tab::Partitions => {
@@ -110,9 +270,51 @@ async fn run_app<B: ratatui::backend::Backend>(
                             KeyCode::Char('5') => app.set_tab(4),
                             KeyCode::Char('6') => app.set_tab(5),
                             KeyCode::Char('7') => app.set_tab(6),
+                            KeyCode::Char('8') => app.set_tab(7),
+                            KeyCode::Char('a') if app.current_tab == app::Tab::Services => app.toggle_service_sort_ascending(),
                             KeyCode::Char('a') => app.toggle_sort_ascending(),
-                            KeyCode::Char('s') => app.next_sort_column(),
+                            KeyCode::Char('s') if app.current_tab == app::Tab::Services && !app.show_service_menu => {
+                                app.next_service_sort_column()
+                            }
+                            KeyCode::Char('s') if app.current_tab == app::Tab::Network => app.toggle_network_sort(),
+                            KeyCode::Char('s') if !app.show_service_menu => app.next_sort_column(),
                             KeyCode::Char('f') => app.toggle_filter(),
+                            KeyCode::Char('y') => app.copy_selected(),
+                            KeyCode::Char('J') if app.current_tab == app::Tab::Alerts => {
+                                let _ = app.export_alerts(ExportFormat::Json);
+                            }
+                            KeyCode::Char('C') if app.current_tab == app::Tab::Alerts => {
+                                let _ = app.export_alerts(ExportFormat::Csv);
+                            }
+                            KeyCode::Char('P') if app.current_tab == app::Tab::Processes => app.select_parent_process(),
+                            KeyCode::Char('G') if app.current_tab == app::Tab::Processes => app.toggle_container_view(),
+                            KeyCode::Char('u') if app.current_tab == app::Tab::Processes => app.toggle_user_view(),
+                            KeyCode::Char('V') if app.current_tab == app::Tab::Processes => app.toggle_gpu_view(),
+                            KeyCode::Char('M') if app.current_tab == app::Tab::Processes => app.toggle_memory_growth_view(),
+                            KeyCode::Char('H') if app.current_tab == app::Tab::Processes => app.toggle_threads(),
+                            KeyCode::Char('K') if app.current_tab == app::Tab::Processes && app.show_users => {
+                                app.start_user_kill()
+                            }
+                            KeyCode::Char('E') if app.current_tab == app::Tab::Processes => app.toggle_environment_search(),
+                            KeyCode::Char('R') if app.current_tab == app::Tab::Processes => {
+                                app.toggle_state_filter(ProcessStatus::Running)
+                            }
+                            KeyCode::Char('S') if app.current_tab == app::Tab::Processes => {
+                                app.toggle_state_filter(ProcessStatus::Sleeping)
+                            }
+                            KeyCode::Char('D') if app.current_tab == app::Tab::Processes => {
+                                app.toggle_state_filter(ProcessStatus::DiskSleep)
+                            }
+                            KeyCode::Char('T') if app.current_tab == app::Tab::Processes => {
+                                app.toggle_state_filter(ProcessStatus::Stopped)
+                            }
+                            KeyCode::Char('Z') if app.current_tab == app::Tab::Processes => {
+                                app.toggle_state_filter(ProcessStatus::Zombie)
+                            }
+                            KeyCode::Char('z') if app.current_tab == app::Tab::Processes => app.toggle_zombies_only(),
+                            KeyCode::Char('i') if app.current_tab == app::Tab::Dashboard && !app.show_context_menu => app.toggle_system_info(),
+                            KeyCode::Char('h') if app.current_tab == app::Tab::Dashboard => app.toggle_core_map(),
+                            KeyCode::Char('g') if app.current_tab == app::Tab::Dashboard => app.toggle_gauge_smoothing(),
                             KeyCode::Char('m') | KeyCode::Enter => {
                                 if app.current_tab == app::Tab::Partitions {
                                     app.toggle_partition_menu();
@@ -125,6 +327,19 @@ async fn run_app<B: ratatui::backend::Backend>(
                             KeyCode::Char('r') if app.current_tab == app::Tab::Partitions => {
                                 app.refresh_disks();
                             }
+                            KeyCode::Char('N') if app.current_tab == app::Tab::Partitions => app.check_network_mounts(),
+                            KeyCode::Char('X') if app.current_tab == app::Tab::Partitions => app.eject_selected_disk(),
+                            KeyCode::Char('B') if app.current_tab == app::Tab::Partitions => {
+                                let _ = app.benchmark_selected_disk();
+                            }
+                            KeyCode::Char('F') if app.current_tab == app::Tab::Partitions && app.fstab_candidate.is_some() => {
+                                let _ = app.add_formatted_to_fstab();
+                            }
+                            KeyCode::Char('M') if app.current_tab == app::Tab::Partitions && app.mount_candidate.is_some() => {
+                                app.mount_formatted();
+                            }
+                            KeyCode::Char('U') if app.current_tab == app::Tab::Partitions => app.unmount_selected_partition(),
+                            KeyCode::Char('D') if app.current_tab == app::Tab::Partitions => app.open_directory_usage(),
                             KeyCode::Char('d') if app.show_partition_menu => {
                                 let _ = app.delete_selected_partition();
                                 app.show_partition_menu = false;
@@ -133,6 +348,7 @@ async fn run_app<B: ratatui::backend::Backend>(
                                 let _ = app.check_selected_partition();
                                 app.show_partition_menu = false;
                             }
+                            KeyCode::Char('c') => app.toggle_command_line(),
                             KeyCode::Char('e') if app.show_partition_menu => {
                                 let _ = app.format_selected_partition("ext4");
                                 app.show_partition_menu = false;
@@ -149,6 +365,26 @@ async fn run_app<B: ratatui::backend::Backend>(
                                 let _ = app.format_selected_partition("ntfs");
                                 app.show_partition_menu = false;
                             }
+                            KeyCode::Char('w') if app.show_partition_menu => {
+                                let _ = app.format_selected_partition("swap");
+                                app.show_partition_menu = false;
+                            }
+                            KeyCode::Char('o') if app.show_partition_menu => {
+                                let _ = app.toggle_selected_swap();
+                                app.show_partition_menu = false;
+                            }
+                            KeyCode::Char('i') if app.show_partition_menu => {
+                                app.image_selected_partition(false);
+                                app.show_partition_menu = false;
+                            }
+                            KeyCode::Char('I') if app.show_partition_menu => {
+                                app.image_selected_partition(true);
+                                app.show_partition_menu = false;
+                            }
+                            KeyCode::Char('T') if app.show_partition_menu => {
+                                app.start_partition_type_input();
+                                app.show_partition_menu = false;
+                            }
                             KeyCode::Char('k') if app.show_context_menu => {
                                 let _ = app.kill_process();
                             }
@@ -161,6 +397,9 @@ async fn run_app<B: ratatui::backend::Backend>(
                             KeyCode::Char('r') if app.show_context_menu => {
                                 let _ = app.restart_process();
                             }
+                            KeyCode::Char('i') if app.show_context_menu => app.start_throttle_input(),
+                            KeyCode::Char('d') if app.show_context_menu => app.open_process_detail(),
+                            KeyCode::Char('p') if app.show_context_menu => app.signal_zombie_parent(),
                             // Service menu actions
                             KeyCode::Char('s') if app.show_service_menu => {
                                 let _ = app.start_service();
@@ -1,11 +1,30 @@
-use crate::app::{App, SortColumn, Tab};
+use crate::app::{App, NetworkSort, SortColumn, Tab};
+use procmon_core::badge::AlertBadge;
+use procmon_core::detail::wrap_text;
 use procmon_core::detector::Severity;
+use procmon_core::metrics::{format_address, AddressScope};
+use procmon_core::faults::{is_major_fault_spike, MAJOR_FAULT_SPIKE};
+use procmon_core::fds::space_held;
+use procmon_core::process::{ProcessStatus, ProcessTotals};
+use procmon_core::columns::{header_labels, ProcessColumn};
+use procmon_core::counters::{limit_level, LimitLevel};
+use procmon_core::temperature::format_temp;
+use procmon_core::search::{highlight_ranges, segments};
+use procmon_core::container::{group_by_container, short_id};
+use procmon_core::users::group_by_user;
+use procmon_core::gpu::gpu_processes;
+use procmon_core::growth::top_memory_growth;
+use procmon_core::sort::sort_by_network;
+use procmon_core::partition::{filesystem_usage, FilesystemUsage, COMMON_PARTITION_TYPES};
+use procmon_core::privilege::REQUIRES_ROOT;
+use procmon_core::util::Staleness;
+use tracing::Level;
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
     widgets::{
-        Bar, BarChart, BarGroup, Block, Borders, Cell, Gauge, List, ListItem, Paragraph, Row,
+        Bar, BarChart, BarGroup, Block, Borders, Cell, Clear, Gauge, LineGauge, List, ListItem, Paragraph, Row,
         Table, Tabs,
     },
     Frame,
@@ -21,23 +40,39 @@ pub fn draw(f: &mut Frame, app: &mut App) {
         ])
         .split(f.area());
 
-    draw_tabs(f, app, chunks[0]);
+    let alert_badge = app.alert_badge();
+    draw_tabs(f, app, &alert_badge, chunks[0]);
     draw_main_content(f, app, chunks[1]);
     draw_footer(f, app, chunks[2]);
 }
 
-fn draw_tabs(f: &mut Frame, app: &App, area: Rect) {
+fn draw_tabs(f: &mut Frame, app: &App, alert_badge: &AlertBadge, area: Rect) {
+    let mut alerts_title = vec![Span::raw("Alerts (7)")];
+    if let (Some(label), Some(severity)) = (alert_badge.label(), alert_badge.highest) {
+        let color = match severity {
+            Severity::Critical => Color::Red,
+            Severity::Warning => Color::Yellow,
+            Severity::Info => Color::Blue,
+        };
+        alerts_title.push(Span::styled(format!(" {}", label), Style::default().fg(color).add_modifier(Modifier::BOLD)));
+    }
     let titles = vec![
-        "Dashboard (1)",
-        "Processes (2)",
-        "Services (3)",
-        "Storage (4)",
-        "Network (5)",
-        "Partitions (6)",
-        "Alerts (7)"
+        Line::from("Dashboard (1)"),
+        Line::from("Processes (2)"),
+        Line::from("Services (3)"),
+        Line::from("Storage (4)"),
+        Line::from("Network (5)"),
+        Line::from("Partitions (6)"),
+        Line::from(alerts_title),
+        Line::from("Logs (8)"),
     ];
+    let mut title = vec![Span::raw("Process Monitor with Partition Manager")];
+    if app.monitor.in_container() {
+        title.push(Span::styled(" - in a container, host data may be unavailable", Style::default().fg(Color::Yellow)));
+    }
+    let title = Line::from(title);
     let tabs = Tabs::new(titles)
-        .block(Block::default().borders(Borders::ALL).title("Process Monitor with Partition Manager"))
+        .block(Block::default().borders(Borders::ALL).title(title))
         .select(app.get_tab_index())
         .style(Style::default().fg(Color::White))
         .highlight_style(
@@ -52,28 +87,169 @@ fn draw_tabs(f: &mut Frame, app: &App, area: Rect) {
 fn draw_main_content(f: &mut Frame, app: &mut App, area: Rect) {
     match app.current_tab {
         Tab::Dashboard => draw_dashboard(f, app, area),
+        Tab::Processes if app.show_containers => draw_containers(f, app, area),
+        Tab::Processes if app.show_users => draw_users(f, app, area),
+        Tab::Processes if app.show_gpu_processes => draw_gpu_processes(f, app, area),
+        Tab::Processes if app.show_memory_growth => draw_memory_growth(f, app, area),
         Tab::Processes => draw_processes(f, app, area),
         Tab::Services => draw_services(f, app, area),
         Tab::Storage => draw_storage(f, app, area),
         Tab::Network => draw_network(f, app, area),
         Tab::Partitions => draw_partitions(f, app, area),
         Tab::Alerts => draw_alerts(f, app, area),
+        Tab::Logs => draw_logs(f, app, area),
     }
 }
 
 fn draw_dashboard(f: &mut Frame, app: &App, area: Rect) {
+    // Per-node memory only matters on NUMA machines
+    let numa_nodes = &app.system_metrics.memory.numa_nodes;
+    let numa_height = if numa_nodes.len() > 1 { numa_nodes.len() as u16 + 2 } else { 0 };
+    // The fullest few filesystems; the Partitions tab has the rest
+    let filesystems = filesystem_usage(&app.disks);
+    let filesystems_height = if filesystems.is_empty() { 0 } else { filesystems.len().min(4) as u16 + 2 };
+
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
+            Constraint::Length(if app.show_system_info { 4 } else { 3 }),
             Constraint::Length(7),
+            Constraint::Length(3),
+            Constraint::Length(numa_height),
+            Constraint::Length(filesystems_height),
             Constraint::Length(10),
             Constraint::Min(0),
         ])
         .split(area);
 
-    draw_system_overview(f, app, chunks[0]);
-    draw_cpu_cores(f, app, chunks[1]);
-    draw_top_processes(f, app, chunks[2]);
+    draw_system_info(f, app, chunks[0]);
+    draw_system_overview(f, app, chunks[1]);
+    draw_system_counters(f, app, chunks[2]);
+    if numa_height > 0 {
+        draw_numa_nodes(f, app, chunks[3]);
+    }
+    if filesystems_height > 0 {
+        draw_filesystems(f, &filesystems, chunks[4]);
+    }
+    draw_cpu_cores(f, app, chunks[5]);
+    draw_top_processes(f, app, chunks[6]);
+}
+
+/// One used/total gauge per mounted filesystem, fullest first
+fn draw_filesystems(f: &mut Frame, filesystems: &[FilesystemUsage], area: Rect) {
+    const GB: f64 = 1024.0 * 1024.0 * 1024.0;
+
+    let block = Block::default().borders(Borders::ALL).title("Filesystems");
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    for (row, fs) in filesystems.iter().take(inner.height as usize).enumerate() {
+        let percent = fs.percent();
+        let color = match limit_level(Some(percent)) {
+            LimitLevel::Normal => Color::Green,
+            LimitLevel::Warning => Color::Yellow,
+            LimitLevel::Critical => Color::Red,
+        };
+        let gauge = LineGauge::default()
+            .filled_style(Style::default().fg(color))
+            .ratio((percent as f64 / 100.0).clamp(0.0, 1.0))
+            .label(format!(
+                "{:<20} {:>7.1} / {:>7.1} GB {:>5.1}%",
+                fs.mount_point,
+                fs.used_bytes as f64 / GB,
+                fs.size_bytes as f64 / GB,
+                percent
+            ));
+        let row_area = Rect { y: inner.y + row as u16, height: 1, ..inner };
+        f.render_widget(gauge, row_area);
+    }
+}
+
+fn draw_numa_nodes(f: &mut Frame, app: &App, area: Rect) {
+    const GB: f64 = 1024.0 * 1024.0 * 1024.0;
+
+    let lines: Vec<Line> = app.system_metrics.memory.numa_nodes
+        .iter()
+        .map(|node| {
+            let percent = if node.total > 0 { node.used() as f32 / node.total as f32 * 100.0 } else { 0.0 };
+            Line::from(Span::styled(
+                format!(
+                    "Node {}: {:.1} / {:.1} GB ({:.1}%)",
+                    node.id,
+                    node.used() as f64 / GB,
+                    node.total as f64 / GB,
+                    percent
+                ),
+                Style::default().fg(get_usage_color(percent)),
+            ))
+        })
+        .collect();
+
+    let paragraph = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title("NUMA Nodes"));
+    f.render_widget(paragraph, area);
+}
+
+fn draw_system_counters(f: &mut Frame, app: &App, area: Rect) {
+    let counters = &app.system_counters;
+    let counter = |label: &str, value: u64, limit: Option<u64>, percent: Option<f32>| {
+        let text = match (limit, percent) {
+            (Some(limit), Some(percent)) => format!("{}: {} / {} ({:.1}%)", label, value, limit, percent),
+            _ => format!("{}: {}", label, value),
+        };
+        let color = match limit_level(percent) {
+            LimitLevel::Normal => Color::Green,
+            LimitLevel::Warning => Color::Yellow,
+            LimitLevel::Critical => Color::Red,
+        };
+        Span::styled(text, Style::default().fg(color))
+    };
+
+    let mut spans = vec![
+        counter("Processes", counters.processes, None, None),
+        Span::raw("  |  "),
+        counter("PIDs", counters.pids_in_use, counters.pid_max, counters.pids_percent()),
+        Span::raw("  |  "),
+        counter("Threads", counters.threads, counters.threads_max, counters.threads_percent()),
+        Span::raw("  |  "),
+        counter("Open files", counters.open_files, counters.file_max, counters.open_files_percent()),
+    ];
+    // Hints from the hourly baseline, when usage is far from the same hour on other days
+    for anomaly in app.anomalies() {
+        spans.push(Span::raw("  |  "));
+        spans.push(Span::styled(anomaly.describe(), Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD)));
+    }
+    let line = Line::from(spans);
+
+    let paragraph = Paragraph::new(line)
+        .block(Block::default().borders(Borders::ALL).title("System Counters"));
+    f.render_widget(paragraph, area);
+}
+
+fn draw_system_info(f: &mut Frame, app: &App, area: Rect) {
+    let info = &app.system_info;
+
+    let (title, lines) = if app.show_system_info {
+        (
+            "System Information (i: collapse)",
+            vec![
+                Line::from(format!(
+                    "Host: {}  |  OS: {} {}  |  Kernel: {}  |  Arch: {}",
+                    info.hostname, info.os_name, info.os_version, info.kernel_version, info.architecture
+                )),
+                Line::from(format!("CPU: {}", info.cpu_model)),
+            ],
+        )
+    } else {
+        (
+            "System Information (i: expand)",
+            vec![Line::from(format!("{} - {} {}", info.hostname, info.os_name, info.os_version))],
+        )
+    };
+
+    let paragraph = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(paragraph, area);
 }
 
 fn draw_system_overview(f: &mut Frame, app: &App, area: Rect) {
@@ -89,32 +265,32 @@ fn draw_system_overview(f: &mut Frame, app: &App, area: Rect) {
 
     // CPU Usage
     let cpu_gauge = Gauge::default()
-        .block(Block::default().borders(Borders::ALL).title("CPU Usage"))
-        .gauge_style(Style::default().fg(get_usage_color(app.system_metrics.cpu.total_usage)))
-        .percent(app.system_metrics.cpu.total_usage as u16)
-        .label(format!("{:.1}%", app.system_metrics.cpu.total_usage));
+        .block(Block::default().borders(Borders::ALL).title(if app.gauge_smoothing.is_some() { "CPU Usage (smoothed)" } else { "CPU Usage" }))
+        .gauge_style(Style::default().fg(get_usage_color(app.gauges.cpu_usage)))
+        .percent(app.gauges.cpu_usage as u16)
+        .label(format!("{:.1}%", app.gauges.cpu_usage));
     f.render_widget(cpu_gauge, chunks[0]);
 
     // Memory Usage
-    let mem_percent = (app.system_metrics.memory.used as f64 / app.system_metrics.memory.total as f64 * 100.0) as u16;
+    let mem_percent = (app.gauges.memory_used as f64 / app.gauges.memory_total as f64 * 100.0) as u16;
     let mem_gauge = Gauge::default()
         .block(Block::default().borders(Borders::ALL).title("Memory"))
         .gauge_style(Style::default().fg(get_usage_color(mem_percent as f32)))
         .percent(mem_percent)
         .label(format!(
             "{:.1} / {:.1} GB",
-            app.system_metrics.memory.used as f64 / (1024.0 * 1024.0 * 1024.0),
-            app.system_metrics.memory.total as f64 / (1024.0 * 1024.0 * 1024.0)
+            app.gauges.memory_used as f64 / (1024.0 * 1024.0 * 1024.0),
+            app.gauges.memory_total as f64 / (1024.0 * 1024.0 * 1024.0)
         ));
     f.render_widget(mem_gauge, chunks[1]);
 
     // CPU Temperature
-    let temp_text = if let Some(temp) = app.system_metrics.cpu.temperature {
-        format!("{:.1}°C", temp)
+    let temp_text = if let Some(temp) = app.gauges.temperature {
+        format_temp(temp, app.temperature_unit)
     } else {
         "N/A".to_string()
     };
-    let temp_color = app.system_metrics.cpu.temperature
+    let temp_color = app.gauges.temperature
         .map(|t| {
             if t > 80.0 {
                 Color::Red
@@ -133,7 +309,11 @@ fn draw_system_overview(f: &mut Frame, app: &App, area: Rect) {
 
     // GPU Info
     let gpu_text = if let Some(gpu) = app.system_metrics.gpus.first() {
-        format!("{}\n{:.1}%", gpu.name, gpu.usage)
+        let usage = gpu.usage.map_or("n/a".to_string(), |usage| format!("{:.1}%", usage));
+        match gpu.temperature {
+            Some(temp) => format!("{}\n{}  {}", gpu.name, usage, format_temp(temp, app.temperature_unit)),
+            None => format!("{}\n{}", gpu.name, usage),
+        }
     } else {
         "No GPU\nDetected".to_string()
     };
@@ -144,26 +324,34 @@ fn draw_system_overview(f: &mut Frame, app: &App, area: Rect) {
 }
 
 fn draw_cpu_cores(f: &mut Frame, app: &App, area: Rect) {
-    let core_data: Vec<(&str, u64)> = app.system_metrics.cpu.per_core_usage
-        .iter()
-        .enumerate()
-        .map(|(i, usage)| {
-            (Box::leak(format!("{}", i).into_boxed_str()) as &str, *usage as u64)
-        })
-        .collect();
+    if app.show_core_map {
+        draw_core_map(f, app, area);
+        return;
+    }
 
-    let bars: Vec<Bar> = core_data
+    let per_core = &app.system_metrics.cpu.per_core_usage;
+    if per_core.is_empty() {
+        let placeholder = Paragraph::new("No per-core CPU data available")
+            .block(Block::default().borders(Borders::ALL).title("CPU Cores"))
+            .alignment(Alignment::Center);
+        f.render_widget(placeholder, area);
+        return;
+    }
+
+    let bars: Vec<Bar> = app.core_labels
+        .labels()
         .iter()
-        .map(|(label, value)| {
+        .zip(per_core)
+        .map(|(label, usage)| {
             Bar::default()
-                .value(*value)
-                .label(Line::from(*label))
-                .style(Style::default().fg(get_usage_color(*value as f32)))
+                .value(*usage as u64)
+                .label(Line::from(label.as_str()))
+                .style(Style::default().fg(get_usage_color(*usage)))
         })
         .collect();
 
     let chart = BarChart::default()
-        .block(Block::default().borders(Borders::ALL).title("CPU Cores"))
+        .block(Block::default().borders(Borders::ALL).title("CPU Cores (h: process map)"))
         .data(BarGroup::default().bars(&bars))
         .bar_width(3)
         .bar_gap(1);
@@ -171,9 +359,260 @@ fn draw_cpu_cores(f: &mut Frame, app: &App, area: Rect) {
     f.render_widget(chart, area);
 }
 
+/// One line per core: its usage followed by the busiest processes that last ran there
+fn draw_core_map(f: &mut Frame, app: &App, area: Rect) {
+    let per_core = &app.system_metrics.cpu.per_core_usage;
+    let groups = procmon_core::cores::group_by_core(&app.processes, per_core.len(), 1.0);
+
+    let lines: Vec<Line> = per_core
+        .iter()
+        .zip(&groups)
+        .enumerate()
+        .map(|(core, (usage, processes))| {
+            let mut spans = vec![Span::styled(
+                format!("CPU{:<3} {:>5.1}%  ", core, usage),
+                Style::default().fg(get_usage_color(*usage)),
+            )];
+            spans.push(Span::raw(
+                processes
+                    .iter()
+                    .take(4)
+                    .map(|p| format!("{} ({:.1}%)", p.info.name, p.stats.cpu_usage))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ));
+            Line::from(spans)
+        })
+        .collect();
+
+    let paragraph = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title("Core → Process Map (h: usage bars)"));
+    f.render_widget(paragraph, area);
+}
+
+/// Process count, CPU and memory summed per container; host processes last
+fn draw_containers(f: &mut Frame, app: &App, area: Rect) {
+    let groups = group_by_container(&app.filtered_processes);
+
+    let rows: Vec<Row> = groups
+        .iter()
+        .map(|group| {
+            let container = group.container.as_deref().map_or("(host)", short_id);
+            Row::new(vec![
+                Cell::from(container.to_string()),
+                Cell::from(group.processes.to_string()),
+                Cell::from(format!("{:.1}%", group.cpu_usage)).style(Style::default().fg(get_usage_color(group.cpu_usage))),
+                Cell::from(format!("{:.1} MB", group.memory_usage as f64 / (1024.0 * 1024.0))),
+            ])
+        })
+        .collect();
+
+    let table = Table::new(
+        rows,
+        [Constraint::Length(14), Constraint::Length(10), Constraint::Length(10), Constraint::Min(12)],
+    )
+    .header(
+        Row::new(vec!["Container", "Processes", "CPU", "Memory"])
+            .style(Style::default().add_modifier(Modifier::BOLD))
+            .bottom_margin(1),
+    )
+    .block(Block::default().borders(Borders::ALL).title("Containers (G: process list)"));
+    f.render_widget(table, area);
+}
+
+/// Processes holding GPU memory, most first
+fn draw_gpu_processes(f: &mut Frame, app: &App, area: Rect) {
+    let processes = gpu_processes(&app.filtered_processes);
+    let rows: Vec<Row> = processes
+        .iter()
+        .map(|process| {
+            let gpu_memory = process.stats.gpu_memory_bytes.unwrap_or(0);
+            Row::new(vec![
+                Cell::from(process.info.pid.to_string()),
+                Cell::from(process.info.name.clone()),
+                Cell::from(process.info.user.clone()),
+                Cell::from(format!("{:.1} MB", gpu_memory as f64 / (1024.0 * 1024.0))),
+                Cell::from(format!("{:.1}%", process.stats.cpu_usage))
+                    .style(Style::default().fg(get_usage_color(process.stats.cpu_usage))),
+                Cell::from(format!("{:.1} MB", process.stats.memory_usage as f64 / (1024.0 * 1024.0))),
+            ])
+        })
+        .collect();
+
+    let title = if processes.is_empty() {
+        "GPU Processes (V: process list) - no per-process GPU usage reported"
+    } else {
+        "GPU Processes (V: process list)"
+    };
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(8),
+            Constraint::Min(16),
+            Constraint::Length(12),
+            Constraint::Length(12),
+            Constraint::Length(8),
+            Constraint::Length(12),
+        ],
+    )
+    .header(
+        Row::new(vec!["PID", "Name", "User", "GPU Memory", "CPU", "Memory"])
+            .style(Style::default().add_modifier(Modifier::BOLD))
+            .bottom_margin(1),
+    )
+    .block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(table, area);
+}
+
+/// Processes whose resident memory grew since the last refresh, most growth first
+fn draw_memory_growth(f: &mut Frame, app: &App, area: Rect) {
+    let processes = top_memory_growth(&app.filtered_processes);
+    let rows: Vec<Row> = processes
+        .iter()
+        .map(|process| {
+            Row::new(vec![
+                Cell::from(process.info.pid.to_string()),
+                Cell::from(process.info.name.clone()),
+                Cell::from(process.info.user.clone()),
+                Cell::from(format!("{:+.1} MB", process.stats.memory_delta as f64 / (1024.0 * 1024.0)))
+                    .style(Style::default().fg(Color::Yellow)),
+                Cell::from(format!("{:.1} MB", process.stats.memory_usage as f64 / (1024.0 * 1024.0))),
+            ])
+        })
+        .collect();
+
+    let title = if processes.is_empty() {
+        "Memory Growth (M: process list) - no process grew since the last refresh"
+    } else {
+        "Memory Growth (M: process list)"
+    };
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(8),
+            Constraint::Min(16),
+            Constraint::Length(12),
+            Constraint::Length(14),
+            Constraint::Length(12),
+        ],
+    )
+    .header(
+        Row::new(vec!["PID", "Name", "User", "ΔMem", "Memory"])
+            .style(Style::default().add_modifier(Modifier::BOLD))
+            .bottom_margin(1),
+    )
+    .block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(table, area);
+}
+
+/// Process count, CPU and memory summed per user, with the kill-all confirmation on top
+fn draw_users(f: &mut Frame, app: &App, area: Rect) {
+    use ratatui::widgets::TableState;
+
+    let groups = group_by_user(&app.processes);
+    let rows: Vec<Row> = groups
+        .iter()
+        .map(|group| {
+            Row::new(vec![
+                Cell::from(group.user.clone()),
+                Cell::from(group.uid.to_string()),
+                Cell::from(group.processes.to_string()),
+                Cell::from(format!("{:.1}%", group.cpu_usage)).style(Style::default().fg(get_usage_color(group.cpu_usage))),
+                Cell::from(format!("{:.1} MB", group.memory_usage as f64 / (1024.0 * 1024.0))),
+            ])
+        })
+        .collect();
+
+    let mut title = "Users (u: process list, ↑↓: Select, K: Kill all of user)".to_string();
+    if let Some(ref message) = app.status_message {
+        title.push_str(&format!(" - {}", message));
+    }
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Min(16),
+            Constraint::Length(8),
+            Constraint::Length(10),
+            Constraint::Length(10),
+            Constraint::Length(12),
+        ],
+    )
+    .header(
+        Row::new(vec!["User", "UID", "Processes", "CPU", "Memory"])
+            .style(Style::default().add_modifier(Modifier::BOLD))
+            .bottom_margin(1),
+    )
+    .block(Block::default().borders(Borders::ALL).title(title))
+    .row_highlight_style(Style::default().bg(Color::Blue).fg(Color::White).add_modifier(Modifier::BOLD))
+    .highlight_symbol(">> ");
+
+    let mut table_state = TableState::default();
+    table_state.select(Some(app.selected_user.min(groups.len().saturating_sub(1))));
+    f.render_stateful_widget(table, area, &mut table_state);
+
+    let Some(ref prompt) = app.user_kill_prompt else {
+        return;
+    };
+    let lines = vec![
+        Line::from(Span::styled(
+            format!("Send SIG{} to all {} processes of {} (uid {})?", prompt.signal.name(), prompt.processes, prompt.user, prompt.uid),
+            Style::default().add_modifier(Modifier::BOLD),
+        )),
+        Line::from(Span::styled(
+            "Protected processes and this session are skipped.",
+            Style::default().fg(Color::Gray),
+        )),
+        Line::from(""),
+        Line::from(Span::styled(
+            format!("Type the user name to confirm: {}_", prompt.typed),
+            Style::default().fg(Color::Cyan),
+        )),
+        Line::from(""),
+        Line::from(Span::styled(
+            "Enter - Confirm, Tab - TERM/KILL, ESC - Cancel",
+            Style::default().fg(Color::Gray),
+        )),
+    ];
+    let popup_width = 64.min(f.area().width);
+    let popup_height = (lines.len() as u16 + 2).min(f.area().height);
+    let popup_area = Rect {
+        x: (f.area().width.saturating_sub(popup_width)) / 2,
+        y: (f.area().height.saturating_sub(popup_height)) / 2,
+        width: popup_width,
+        height: popup_height,
+    };
+    f.render_widget(Clear, popup_area);
+    f.render_widget(
+        Paragraph::new(lines).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Red))
+                .title("Kill All Processes of User")
+                .style(Style::default().bg(Color::Black)),
+        ),
+        popup_area,
+    );
+}
+
+/// `text` with every match of the search `query` in bold yellow
+fn highlight_matches(text: &str, query: &str) -> Line<'static> {
+    let ranges = highlight_ranges(text, query);
+    let spans: Vec<Span> = segments(text, &ranges)
+        .into_iter()
+        .map(|(piece, highlighted)| {
+            if highlighted {
+                Span::styled(piece.to_string(), Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+            } else {
+                Span::raw(piece.to_string())
+            }
+        })
+        .collect();
+    Line::from(spans)
+}
+
 fn draw_top_processes(f: &mut Frame, app: &App, area: Rect) {
     let mut processes = app.processes.clone();
-    processes.sort_by(|a, b| b.stats.cpu_usage.partial_cmp(&a.stats.cpu_usage).unwrap());
+    procmon_core::sort::sort_processes(&mut processes, SortColumn::Cpu, false);
     processes.truncate(10);
 
     let rows: Vec<Row> = processes
@@ -231,52 +670,113 @@ fn draw_processes(f: &mut Frame, app: &mut App, area: Rect) {
         SortColumn::Name => "Name",
         SortColumn::Cpu => "CPU",
         SortColumn::Memory => "Memory",
+        SortColumn::MemoryGrowth => "ΔMem",
         SortColumn::DiskIo => "Disk I/O",
         SortColumn::User => "User",
+        SortColumn::Age => "Age",
+        SortColumn::Swap => "Swap",
+        SortColumn::CpuAvg => "CPU Avg",
+        SortColumn::Pid => "PID",
     };
 
     let filtered_procs = app.get_filtered_processes();
+    let now = std::time::Instant::now();
 
-    let rows: Vec<Row> = filtered_procs
+    let mut rows: Vec<Row> = filtered_procs
         .iter()
-        .enumerate()
-        .map(|(i, p)| {
-            Row::new(vec![
-                Cell::from(p.info.pid.to_string()),
-                Cell::from(p.info.name.clone()),
-                Cell::from(p.info.user.clone()),
-                Cell::from(format!("{:.1}%", p.stats.cpu_usage)),
-                Cell::from(format!("{:.1}", p.stats.memory_usage as f64 / (1024.0 * 1024.0))),
-                Cell::from(format!("{:.1}", (p.stats.disk_read_bytes + p.stats.disk_write_bytes) as f64 / (1024.0 * 1024.0))),
-                Cell::from(format!("{:?}", p.info.status)),
-            ])
+        .map(|p| {
+            let is_new = app.new_processes.is_new(p.info.pid, now);
+            let name = if p.info.is_own {
+                format!("[SELF] {}", ProcessColumn::Name.cell(p, app.show_command_line))
+            } else if is_new {
+                format!("[NEW] {}", ProcessColumn::Name.cell(p, app.show_command_line))
+            } else {
+                ProcessColumn::Name.cell(p, app.show_command_line)
+            };
+            let row_style = if p.info.is_own {
+                Style::default().fg(Color::DarkGray)
+            } else if is_new {
+                Style::default().fg(Color::LightGreen)
+            } else {
+                Style::default()
+            };
+
+            let cells: Vec<Cell> = app.process_columns
+                .iter()
+                .map(|column| {
+                    let text = match column {
+                        ProcessColumn::Name => name.clone(),
+                        _ => column.cell(p, app.show_command_line),
+                    };
+                    if column.searchable() && !app.search_query.is_empty() {
+                        Cell::from(highlight_matches(&text, &app.search_query))
+                    } else if *column == ProcessColumn::MajFlt && is_major_fault_spike(&p.stats) {
+                        Cell::from(text).style(Style::default().fg(Color::Red).add_modifier(Modifier::BOLD))
+                    } else {
+                        Cell::from(text)
+                    }
+                })
+                .collect();
+            Row::new(cells).style(row_style).bottom_margin(app.density.row_margin())
         })
         .collect();
 
-    let title = if app.search_mode {
+    // Threads of the selected process go right under it
+    if app.selected_process < rows.len() {
+        let thread_rows = app.threads.items.iter().map(|thread| {
+            let cells: Vec<Cell> = app.process_columns.iter().map(|column| Cell::from(column.thread_cell(thread))).collect();
+            Row::new(cells).style(Style::default().fg(Color::Gray)).bottom_margin(app.density.row_margin())
+        });
+        rows.splice(app.selected_process + 1..app.selected_process + 1, thread_rows);
+    }
+
+    let mut title = if app.search_mode {
         format!("Processes ({}) - Search Mode Active", filtered_procs.len())
     } else {
-        format!("Processes ({}) - Sort: {} {} - ↑↓: Select, Enter: Menu, /: Search",
+        format!("Processes ({}) - Sort: {} {} - ↑↓: Select, Enter: Menu, /: Search, P: Parent, H: Threads",
             filtered_procs.len(), sort_column_name, sort_indicator)
     };
+    if app.state_filter.is_active() {
+        title.push_str(&format!(" - State: {}", app.state_filter.label()));
+    }
+    if app.threads.is_truncated() {
+        title.push_str(&format!(" - Threads: {}", app.threads.summary()));
+    }
+    if let Some(ref message) = app.status_message {
+        title.push_str(&format!(" - {}", message));
+    }
 
-    let table = Table::new(
-        rows,
-        [
-            Constraint::Length(8),
-            Constraint::Min(20),
-            Constraint::Length(12),
-            Constraint::Length(10),
-            Constraint::Length(12),
-            Constraint::Length(12),
-            Constraint::Length(10),
-        ],
-    )
+    // The name column takes whatever width the others leave
+    let widths: Vec<Constraint> = app.process_columns
+        .iter()
+        .map(|column| {
+            let width = column.width(app.show_command_line) as u16;
+            match column {
+                ProcessColumn::Name => Constraint::Min(width),
+                _ => Constraint::Length(width),
+            }
+        })
+        .collect();
+
+    // Totals of every listed process, under the columns they belong to
+    let totals = ProcessTotals::of(filtered_procs);
+    let footer: Vec<Cell> = app.process_columns
+        .iter()
+        .map(|column| match column {
+            ProcessColumn::Name => Cell::from(format!("Total of {} processes", totals.count)),
+            ProcessColumn::Cpu => Cell::from(format!("{:.1}%", totals.cpu_usage)),
+            ProcessColumn::Mem => Cell::from(format!("{:.1}", totals.memory_usage as f64 / (1024.0 * 1024.0))),
+            _ => Cell::from(""),
+        })
+        .collect();
+
+    let table = Table::new(rows, widths)
     .header(
-        Row::new(vec!["PID", "Name", "User", "CPU %", "Mem (MB)", "Disk (MB)", "Status"])
+        Row::new(header_labels(&app.process_columns, app.show_command_line))
             .style(Style::default().add_modifier(Modifier::BOLD))
-            .bottom_margin(1),
+            .bottom_margin(app.density.header_margin()),
     )
+    .footer(Row::new(footer).style(Style::default().add_modifier(Modifier::BOLD)).top_margin(1))
     .block(
         Block::default()
             .borders(Borders::ALL)
@@ -299,9 +799,14 @@ fn draw_processes(f: &mut Frame, app: &mut App, area: Rect) {
     // Draw search bar if in search mode
     if let Some(search_area) = search_area {
         let search_text = format!("Search: {}", app.search_query);
+        let search_title = if app.search_environment {
+            "Search incl. environment (ESC to exit)"
+        } else {
+            "Search (ESC to exit)"
+        };
         let search_bar = Paragraph::new(search_text)
             .style(Style::default().fg(Color::Yellow))
-            .block(Block::default().borders(Borders::ALL).title("Search (ESC to exit)"));
+            .block(Block::default().borders(Borders::ALL).title(search_title));
         f.render_widget(search_bar, search_area);
     }
 
@@ -309,42 +814,175 @@ fn draw_processes(f: &mut Frame, app: &mut App, area: Rect) {
     if app.show_context_menu {
         draw_context_menu(f, app);
     }
+
+    if app.process_detail.is_some() {
+        draw_process_detail(f, app);
+    }
 }
 
-fn draw_context_menu(f: &mut Frame, app: &App) {
-    // Create a centered popup
+/// Full command line and environment, wrapped to the popup and scrolled with ↑/↓
+fn draw_process_detail(f: &mut Frame, app: &mut App) {
+    let Some(detail) = &app.process_detail else {
+        return;
+    };
     let area = f.area();
-    let popup_width = 40;
-    let popup_height = 8;
-    let popup_x = (area.width.saturating_sub(popup_width)) / 2;
-    let popup_y = (area.height.saturating_sub(popup_height)) / 2;
-
     let popup_area = Rect {
-        x: popup_x,
-        y: popup_y,
-        width: popup_width,
-        height: popup_height,
+        x: area.width / 10,
+        y: area.height / 10,
+        width: area.width - area.width / 5,
+        height: area.height - area.height / 5,
+    };
+    // Borders take a column on each side
+    let width = popup_area.width.saturating_sub(2) as usize;
+    let heading = Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD);
+
+    let mut lines = vec![Line::from(Span::styled("Command line", heading))];
+    lines.extend(wrap_text(&detail.command_line, width).into_iter().map(Line::from));
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled("Page faults", heading)));
+    let spike_style = if detail.major_faults_delta >= MAJOR_FAULT_SPIKE {
+        Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)
+    } else {
+        Style::default()
     };
+    lines.push(Line::from(vec![
+        Span::raw(format!("minor {}  major {} ", detail.minor_faults, detail.major_faults)),
+        Span::styled(format!("(+{} since last refresh)", detail.major_faults_delta), spike_style),
+    ]));
+    if !detail.limits.is_empty() {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled("Limits (soft / hard, used)", heading)));
+        for limit in &detail.limits {
+            let style = if limit.near_limit() {
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            let used = limit.used.map_or_else(String::new, |used| used.to_string());
+            lines.push(Line::from(Span::styled(
+                format!("{:<26}{:>12} / {:<12}{:>12}", limit.name, limit.soft.to_string(), limit.hard.to_string(), used),
+                style,
+            )));
+        }
+    }
+    if detail.open_files.total > 0 {
+        lines.push(Line::from(""));
+        let title = if detail.open_files.is_truncated() {
+            format!("Open files ({})", detail.open_files.summary())
+        } else {
+            format!("Open files ({})", detail.open_files.total)
+        };
+        lines.push(Line::from(Span::styled(title, heading)));
+        for (fd, target) in &detail.open_files.items {
+            lines.push(Line::from(vec![
+                Span::styled(format!("{:>6}  ", fd), Style::default().fg(Color::Gray)),
+                Span::raw(target.clone()),
+            ]));
+        }
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled("Environment", heading)));
+    match &detail.environment {
+        Some(environment) => {
+            for entry in environment {
+                lines.extend(wrap_text(entry, width).into_iter().map(Line::from));
+            }
+        }
+        None => lines.push(Line::from(Span::styled(
+            "Not readable (another user's process; run as root)",
+            Style::default().fg(Color::Gray),
+        ))),
+    }
+    if !detail.raw_fields.is_empty() {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled("Raw /proc fields (--debug)", heading)));
+        for field in &detail.raw_fields {
+            lines.push(Line::from(vec![
+                Span::styled(format!("{:<7}{:<18}", field.source, field.name), Style::default().fg(Color::Gray)),
+                // Tabs (between the ids of a Uid: line) would break the terminal layout
+                Span::raw(format!("{:<24} ", field.raw.replace('\t', " "))),
+                Span::styled(format!("-> {}", field.parsed), Style::default().fg(Color::Green)),
+            ]));
+        }
+    }
+
+    let visible = popup_area.height.saturating_sub(2);
+    let max_scroll = (lines.len() as u16).saturating_sub(visible);
+    let title = format!(
+        "{} (PID: {}) - ↑↓/PgUp/PgDn: scroll, y: copy full, ESC: close",
+        detail.name, detail.pid
+    );
+    app.detail_scroll = app.detail_scroll.min(max_scroll);
+
+    let paragraph = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Yellow))
+                .title(title),
+        )
+        .scroll((app.detail_scroll, 0));
+    f.render_widget(Clear, popup_area);
+    f.render_widget(paragraph, popup_area);
+}
+
+fn draw_context_menu(f: &mut Frame, app: &App) {
+    // Create a centered popup
+    let area = f.area();
+    let popup_width = 44.min(area.width);
 
     // Get selected process info from filtered processes
     let filtered_procs = app.get_filtered_processes();
-    let process_info = if !filtered_procs.is_empty() && app.selected_process < filtered_procs.len() {
-        let p = &filtered_procs[app.selected_process];
-        format!("{} (PID: {})", p.info.name, p.info.pid)
-    } else {
-        "No process selected".to_string()
+    let selected = filtered_procs.get(app.selected_process);
+    let process_info = match selected {
+        Some(p) => format!("{} (PID: {})", p.info.name, p.info.pid),
+        None => "No process selected".to_string(),
     };
+    let is_zombie = selected.is_some_and(|p| p.info.status == ProcessStatus::Zombie);
 
-    let menu_items = vec![
+    let mut menu_items = vec![
         Line::from(Span::styled(process_info, Style::default().add_modifier(Modifier::BOLD))),
         Line::from(""),
         Line::from(Span::raw("k - Kill process")),
         Line::from(Span::raw("t - Kill process tree")),
         Line::from(Span::raw("o - Open process folder")),
         Line::from(Span::raw("r - Restart process")),
-        Line::from(""),
-        Line::from(Span::styled("ESC - Close menu", Style::default().fg(Color::Gray))),
+        Line::from(Span::raw("i - Throttle disk I/O (root)")),
+        Line::from(Span::raw("d - Details (command line, environment)")),
     ];
+    if is_zombie {
+        menu_items.push(Line::from(Span::raw("p - Signal parent (SIGCHLD)")));
+        menu_items.push(Line::from(Span::styled(
+            "  Zombies can't be killed; their parent",
+            Style::default().fg(Color::Gray),
+        )));
+        menu_items.push(Line::from(Span::styled(
+            "  must reap them. SIGCHLD prompts it.",
+            Style::default().fg(Color::Gray),
+        )));
+    }
+    if let Some(ref input) = app.throttle_input {
+        menu_items.push(Line::from(""));
+        menu_items.push(Line::from(Span::styled(
+            format!("MB/s read [write], 0=none: {}_", input),
+            Style::default().fg(Color::Cyan),
+        )));
+        menu_items.push(Line::from(Span::styled("  0 for both lifts the throttle", Style::default().fg(Color::Gray))));
+    }
+    menu_items.push(Line::from(""));
+    menu_items.push(Line::from(Span::styled("ESC - Close menu", Style::default().fg(Color::Gray))));
+
+    // Borders take the remaining two rows
+    let popup_height = (menu_items.len() as u16 + 2).min(area.height);
+    let popup_x = (area.width.saturating_sub(popup_width)) / 2;
+    let popup_y = (area.height.saturating_sub(popup_height)) / 2;
+
+    let popup_area = Rect {
+        x: popup_x,
+        y: popup_y,
+        width: popup_width,
+        height: popup_height,
+    };
 
     let paragraph = Paragraph::new(menu_items)
         .block(
@@ -362,6 +1000,7 @@ fn draw_context_menu(f: &mut Frame, app: &App) {
 fn draw_services(f: &mut Frame, app: &App, area: Rect) {
     use ratatui::widgets::TableState;
     use procmon_core::ServiceState;
+    use procmon_core::sort::ServiceSortColumn;
 
     let services = &app.filtered_services;
 
@@ -402,7 +1041,29 @@ fn draw_services(f: &mut Frame, app: &App, area: Rect) {
         })
         .collect();
 
-    let title = format!("Services ({}) - ↑↓: Select, Enter: Menu", services.len());
+    let sort_indicator = if app.service_sort_ascending { "↑" } else { "↓" };
+    let title = format!(
+        "Services ({}) - Sort: {} {} - ↑↓: Select, Enter: Menu, s: Sort, a: Order",
+        services.len(),
+        app.service_sort_column.label(),
+        sort_indicator
+    );
+    let header = [
+        ("Name", Some(ServiceSortColumn::Name)),
+        ("State", Some(ServiceSortColumn::State)),
+        ("Sub State", None),
+        ("Enabled", None),
+        ("PID", Some(ServiceSortColumn::Pid)),
+        ("Memory", Some(ServiceSortColumn::Memory)),
+        ("Description", None),
+    ]
+    .map(|(label, column)| {
+        if column == Some(app.service_sort_column) {
+            format!("{} {}", label, sort_indicator)
+        } else {
+            label.to_string()
+        }
+    });
 
     let table = Table::new(
         rows,
@@ -417,15 +1078,17 @@ fn draw_services(f: &mut Frame, app: &App, area: Rect) {
         ],
     )
     .header(
-        Row::new(vec!["Name", "State", "Sub State", "Enabled", "PID", "Memory", "Description"])
+        Row::new(header)
             .style(Style::default().add_modifier(Modifier::BOLD))
             .bottom_margin(1),
     )
-    .block(
-        Block::default()
-            .borders(Borders::ALL)
-            .title(title)
-    )
+    .block({
+        let block = Block::default().borders(Borders::ALL).title(title);
+        match app.services_backoff.status(std::time::Instant::now()) {
+            Some(status) => block.title_bottom(Line::styled(status, Style::default().fg(Color::Rgb(255, 191, 0)))),
+            None => block,
+        }
+    })
     .row_highlight_style(
         Style::default()
             .bg(Color::Blue)
@@ -444,23 +1107,76 @@ fn draw_services(f: &mut Frame, app: &App, area: Rect) {
     if app.show_service_menu {
         draw_service_menu(f, app);
     }
+
+    if let Some(ref dependents) = app.pending_stop {
+        draw_stop_confirmation(f, app, dependents);
+    }
 }
 
-fn draw_service_menu(f: &mut Frame, app: &App) {
-    // Create a centered popup
-    let area = f.area();
-    let popup_width = 40;
-    let popup_height = 10;
-    let popup_x = (area.width.saturating_sub(popup_width)) / 2;
-    let popup_y = (area.height.saturating_sub(popup_height)) / 2;
+fn draw_stop_confirmation(f: &mut Frame, app: &App, dependents: &[String]) {
+    let service = app.context_menu_service.as_deref().unwrap_or("service");
+
+    let mut lines = vec![
+        Line::from(Span::styled(
+            format!("Stop {}?", service),
+            Style::default().add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+    ];
+    lines.extend(unit_list_lines("These units depend on it", dependents));
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::raw("y - Stop")));
+    lines.push(Line::from(Span::raw("R - Stop (replace-irreversibly)")));
+    lines.push(Line::from(Span::styled("n/ESC - Cancel", Style::default().fg(Color::Gray))));
 
+    let area = f.area();
+    let popup_width = 50.min(area.width);
+    let popup_height = (lines.len() as u16 + 2).min(area.height);
     let popup_area = Rect {
-        x: popup_x,
-        y: popup_y,
+        x: (area.width.saturating_sub(popup_width)) / 2,
+        y: (area.height.saturating_sub(popup_height)) / 2,
         width: popup_width,
         height: popup_height,
     };
 
+    let paragraph = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Red))
+                .title("Confirm Stop")
+                .style(Style::default().bg(Color::Black))
+        )
+        .alignment(Alignment::Left);
+
+    f.render_widget(Clear, popup_area);
+    f.render_widget(paragraph, popup_area);
+}
+
+/// Units shown per dependency list in the service menu before "+N more"
+const MAX_LISTED_UNITS: usize = 5;
+
+fn unit_list_lines(heading: &str, units: &[String]) -> Vec<Line<'static>> {
+    let mut lines = vec![Line::from(Span::styled(
+        format!("{} ({})", heading, units.len()),
+        Style::default().fg(Color::Cyan),
+    ))];
+    if units.is_empty() {
+        lines.push(Line::from(Span::styled("  none", Style::default().fg(Color::Gray))));
+    }
+    for unit in units.iter().take(MAX_LISTED_UNITS) {
+        lines.push(Line::from(format!("  {}", unit)));
+    }
+    if units.len() > MAX_LISTED_UNITS {
+        lines.push(Line::from(Span::styled(
+            format!("  +{} more", units.len() - MAX_LISTED_UNITS),
+            Style::default().fg(Color::Gray),
+        )));
+    }
+    lines
+}
+
+fn draw_service_menu(f: &mut Frame, app: &App) {
     // Get selected service info
     let service_info = if !app.filtered_services.is_empty() && app.selected_service < app.filtered_services.len() {
         let s = &app.filtered_services[app.selected_service];
@@ -469,7 +1185,7 @@ fn draw_service_menu(f: &mut Frame, app: &App) {
         "No service selected".to_string()
     };
 
-    let menu_items = vec![
+    let mut menu_items = vec![
         Line::from(Span::styled(service_info, Style::default().add_modifier(Modifier::BOLD))),
         Line::from(""),
         Line::from(Span::raw("s - Start service")),
@@ -478,8 +1194,26 @@ fn draw_service_menu(f: &mut Frame, app: &App) {
         Line::from(Span::raw("e - Enable service")),
         Line::from(Span::raw("d - Disable service")),
         Line::from(""),
-        Line::from(Span::styled("ESC - Close menu", Style::default().fg(Color::Gray))),
     ];
+    // What stopping the unit affects, and what it needs, so the blast radius is visible
+    menu_items.extend(unit_list_lines("Required by", &app.service_dependents));
+    menu_items.extend(unit_list_lines("Depends on", &app.service_dependencies));
+    menu_items.push(Line::from(""));
+    menu_items.push(Line::from(Span::styled("ESC - Close menu", Style::default().fg(Color::Gray))));
+
+    // Create a centered popup
+    let area = f.area();
+    let popup_width = 50.min(area.width);
+    let popup_height = (menu_items.len() as u16 + 2).min(area.height);
+    let popup_x = (area.width.saturating_sub(popup_width)) / 2;
+    let popup_y = (area.height.saturating_sub(popup_height)) / 2;
+
+    let popup_area = Rect {
+        x: popup_x,
+        y: popup_y,
+        width: popup_width,
+        height: popup_height,
+    };
 
     let paragraph = Paragraph::new(menu_items)
         .block(
@@ -497,7 +1231,7 @@ fn draw_service_menu(f: &mut Frame, app: &App) {
 fn draw_storage(f: &mut Frame, app: &App, area: Rect) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+        .constraints([Constraint::Percentage(35), Constraint::Percentage(40), Constraint::Percentage(25)])
         .split(area);
 
     // Disk I/O summary
@@ -524,11 +1258,7 @@ fn draw_storage(f: &mut Frame, app: &App, area: Rect) {
 
     // Top processes by disk I/O
     let mut processes = app.processes.clone();
-    processes.sort_by(|a, b| {
-        let a_io = a.stats.disk_read_bytes + a.stats.disk_write_bytes;
-        let b_io = b.stats.disk_read_bytes + b.stats.disk_write_bytes;
-        b_io.cmp(&a_io)
-    });
+    procmon_core::sort::sort_processes(&mut processes, SortColumn::DiskIo, false);
     processes.truncate(20);
 
     let rows: Vec<Row> = processes
@@ -562,6 +1292,39 @@ fn draw_storage(f: &mut Frame, app: &App, area: Rect) {
     .block(Block::default().borders(Borders::ALL).title("Processes by Disk I/O"));
 
     f.render_widget(table, chunks[1]);
+    draw_deleted_files(f, app, chunks[2]);
+}
+
+/// Removed files a process still holds open: disk space `du` can't account for
+fn draw_deleted_files(f: &mut Frame, app: &App, area: Rect) {
+    let (file_count, held) = space_held(&app.deleted_files);
+    let items: Vec<ListItem> = app
+        .deleted_files
+        .iter()
+        .map(|file| {
+            let name = app
+                .processes
+                .iter()
+                .find(|p| p.info.pid == file.pid)
+                .map_or("?", |p| p.info.name.as_str());
+            ListItem::new(format!(
+                "{:>10.2} MB  PID {} ({}) fd {}  {}",
+                file.size as f64 / (1024.0 * 1024.0),
+                file.pid,
+                name,
+                file.fd,
+                file.path.display()
+            ))
+        })
+        .collect();
+
+    let title = format!(
+        "Deleted but Open Files ({}, {:.1} MB held)",
+        file_count,
+        held as f64 / (1024.0 * 1024.0)
+    );
+    let style = if app.deleted_files.is_empty() { Style::default() } else { Style::default().fg(Color::Yellow) };
+    f.render_widget(List::new(items).style(style).block(Block::default().borders(Borders::ALL).title(title)), area);
 }
 
 fn draw_network(f: &mut Frame, app: &App, area: Rect) {
@@ -584,7 +1347,15 @@ fn draw_network(f: &mut Frame, app: &App, area: Rect) {
                 metrics.packets_received,
                 metrics.packets_sent
             );
-            ListItem::new(content)
+            let mut lines = vec![Line::from(content)];
+            lines.extend(metrics.addresses.iter().map(|address| {
+                let color = match AddressScope::of(address) {
+                    AddressScope::Global => Color::Cyan,
+                    AddressScope::LinkLocal | AddressScope::Loopback => Color::DarkGray,
+                };
+                Line::from(Span::styled(format!("    {}", format_address(address)), Style::default().fg(color)))
+            }));
+            ListItem::new(lines)
         })
         .collect();
 
@@ -592,11 +1363,55 @@ fn draw_network(f: &mut Frame, app: &App, area: Rect) {
         .block(Block::default().borders(Borders::ALL).title("Network Interfaces"));
     f.render_widget(net_list, chunks[0]);
 
-    // Top processes by network (placeholder - we don't have per-process network stats yet)
-    let text = Paragraph::new("Per-process network statistics not yet available.\nThis will show processes sorted by network usage.")
-        .block(Block::default().borders(Borders::ALL).title("Processes by Network Usage"))
-        .alignment(Alignment::Center);
-    f.render_widget(text, chunks[1]);
+    // Top processes by TCP traffic, busiest first
+    let mut processes: Vec<_> = app
+        .processes
+        .iter()
+        .filter(|p| p.stats.network_bytes_per_sec > 0 || p.stats.network_rx_bytes + p.stats.network_tx_bytes > 0)
+        .cloned()
+        .collect();
+    sort_by_network(&mut processes, app.network_sort);
+    let mb = |bytes: u64| bytes as f64 / (1024.0 * 1024.0);
+    let rows: Vec<Row> = processes
+        .iter()
+        .take(chunks[1].height.saturating_sub(3) as usize)
+        .map(|p| {
+            Row::new(vec![
+                Cell::from(p.info.pid.to_string()),
+                Cell::from(p.info.name.clone()),
+                Cell::from(format!("{:.1}", p.stats.network_bytes_per_sec as f64 / 1024.0)),
+                Cell::from(format!("{:.2}", mb(p.stats.network_rx_bytes + p.stats.network_tx_bytes))),
+                Cell::from(format!("{:.2}", mb(p.stats.network_rx_bytes))),
+                Cell::from(format!("{:.2}", mb(p.stats.network_tx_bytes))),
+            ])
+        })
+        .collect();
+
+    // The sorted column carries the arrow
+    let header = ["PID", "Name", "Rate (KB/s)", "Total (MB)", "RX (MB)", "TX (MB)"].map(|label| {
+        let sorted = match app.network_sort {
+            NetworkSort::Rate => "Rate (KB/s)",
+            NetworkSort::Total => "Total (MB)",
+        };
+        if label == sorted { format!("{} ↓", label) } else { label.to_string() }
+    });
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(8),
+            Constraint::Min(16),
+            Constraint::Length(13),
+            Constraint::Length(12),
+            Constraint::Length(10),
+            Constraint::Length(10),
+        ],
+    )
+    .header(Row::new(header.to_vec()).style(Style::default().add_modifier(Modifier::BOLD)))
+    .block(Block::default().borders(Borders::ALL).title(format!(
+        "Processes by TCP Traffic (s: Sort by {})",
+        app.network_sort.toggle().label()
+    )));
+    f.render_widget(table, chunks[1]);
 }
 
 fn draw_alerts(f: &mut Frame, app: &App, area: Rect) {
@@ -640,13 +1455,130 @@ fn draw_alerts(f: &mut Frame, app: &App, area: Rect) {
     let alert_list = List::new(alert_items).block(
         Block::default()
             .borders(Borders::ALL)
-            .title(format!("Alerts ({} total)", app.alerts.len())),
+            .title(alerts_title(app)),
     );
 
     f.render_widget(alert_list, area);
 }
 
+fn alerts_title(app: &App) -> String {
+    let mut title = format!("Alerts ({} total) - J: Export JSON, C: Export CSV", app.alerts.len());
+    if let Some(ref message) = app.status_message {
+        title.push_str(&format!(" - {}", message));
+    }
+    title
+}
+
+fn draw_logs(f: &mut Frame, app: &App, area: Rect) {
+    let entries = app.log_buffer.entries();
+    let height = area.height.saturating_sub(2) as usize;
+    let end = entries.len().saturating_sub(app.log_scroll);
+    let start = end.saturating_sub(height);
+
+    let lines: Vec<ListItem> = entries[start..end]
+        .iter()
+        .map(|entry| {
+            let level_color = match entry.level {
+                Level::ERROR => Color::Red,
+                Level::WARN => Color::Yellow,
+                Level::INFO => Color::Green,
+                Level::DEBUG => Color::Cyan,
+                Level::TRACE => Color::DarkGray,
+            };
+            ListItem::new(Line::from(vec![
+                Span::raw(format!("{} ", entry.timestamp.format("%H:%M:%S"))),
+                Span::styled(format!("{:<5} ", entry.level), Style::default().fg(level_color).add_modifier(Modifier::BOLD)),
+                Span::styled(format!("{}: ", entry.target), Style::default().fg(Color::DarkGray)),
+                Span::raw(entry.message.clone()),
+            ]))
+        })
+        .collect();
+
+    let mut title = format!("Logs ({} recent events) - ↑↓/PgUp/PgDn: Scroll, End: Follow", entries.len());
+    if app.log_scroll > 0 {
+        title.push_str(&format!(" - {} newer events below", app.log_scroll));
+    }
+    let list = List::new(lines).block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(list, area);
+}
+
+/// Largest directories below the current drill-down path, each with its share of the path's total
+fn draw_directory_usage(f: &mut Frame, app: &App) {
+    use ratatui::widgets::TableState;
+
+    let Some(usage) = &app.directory_usage else {
+        return;
+    };
+    let area = f.area();
+    let popup_area = Rect {
+        x: area.width / 10,
+        y: area.height / 10,
+        width: area.width - area.width / 5,
+        height: area.height - area.height / 5,
+    };
+
+    let title = format!(
+        "Disk usage: {} - ↑↓: select, Enter: open, Backspace: up, ESC: close",
+        usage.path.display()
+    );
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow))
+        .title(title);
+    f.render_widget(Clear, popup_area);
+
+    let message = if usage.scanning() {
+        Some(("Scanning with du -x...".to_string(), Color::Gray))
+    } else if let Some(error) = &usage.error {
+        Some((error.clone(), Color::Red))
+    } else if usage.entries.is_empty() {
+        Some(("No subdirectories".to_string(), Color::Gray))
+    } else {
+        None
+    };
+    if let Some((message, color)) = message {
+        f.render_widget(Paragraph::new(message).style(Style::default().fg(color)).block(block), popup_area);
+        return;
+    }
+
+    let total: u64 = usage.entries.iter().map(|(_, size)| size).sum();
+    let rows: Vec<Row> = usage
+        .entries
+        .iter()
+        .map(|(path, size)| {
+            let percent = if total > 0 { *size as f64 / total as f64 * 100.0 } else { 0.0 };
+            let name = path.strip_prefix(&usage.path).unwrap_or(path);
+            Row::new(vec![
+                Cell::from(format!("{:.2} GB", *size as f64 / (1024.0 * 1024.0 * 1024.0))),
+                Cell::from(format!("{:.1}%", percent)),
+                Cell::from(name.display().to_string()),
+            ])
+        })
+        .collect();
+    let table = Table::new(rows, [Constraint::Length(12), Constraint::Length(7), Constraint::Min(20)])
+        .header(Row::new(vec!["Size", "Share", "Directory"]).style(Style::default().add_modifier(Modifier::BOLD)))
+        .block(block)
+        .row_highlight_style(Style::default().bg(Color::Blue).fg(Color::White).add_modifier(Modifier::BOLD))
+        .highlight_symbol(">> ");
+
+    let mut table_state = TableState::default();
+    table_state.select(Some(usage.selected));
+    f.render_stateful_widget(table, popup_area, &mut table_state);
+}
+
 fn draw_partitions(f: &mut Frame, app: &App, area: Rect) {
+    let area = if app.network_mounts.is_empty() {
+        area
+    } else {
+        let height = app.network_mounts.len().min(6) as u16 + 3;
+        let split = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(height)])
+            .split(area);
+        draw_network_mounts(f, app, split[1]);
+        split[0]
+    };
+
     if app.disks.is_empty() {
         let text = Paragraph::new("No disks found or permission denied.\nRun with sudo for full partition management capabilities.")
             .block(Block::default().borders(Borders::ALL).title("Partition Manager"))
@@ -655,6 +1587,81 @@ fn draw_partitions(f: &mut Frame, app: &App, area: Rect) {
         return;
     }
 
+    let (area, fsck_area) = if app.fsck_job.is_some() {
+        let split = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(3)])
+            .split(area);
+        (split[0], Some(split[1]))
+    } else {
+        (area, None)
+    };
+
+    let (area, image_area) = if app.image_job.is_some() {
+        let split = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(3)])
+            .split(area);
+        (split[0], Some(split[1]))
+    } else {
+        (area, None)
+    };
+
+    let (area, type_area) = if app.partition_type_input.is_some() {
+        let split = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(4)])
+            .split(area);
+        (split[0], Some(split[1]))
+    } else {
+        (area, None)
+    };
+
+    if let (Some(input), Some(type_area)) = (&app.partition_type_input, type_area) {
+        let common: Vec<String> = COMMON_PARTITION_TYPES
+            .iter()
+            .map(|partition_type| format!("{} {}", partition_type.code, partition_type.name))
+            .collect();
+        let prompt = Paragraph::new(vec![
+            Line::from(Span::styled(format!("Type code or GUID: {}_", input), Style::default().fg(Color::Cyan))),
+            Line::from(Span::styled(common.join(" | "), Style::default().fg(Color::Gray))),
+        ])
+        .block(Block::default().borders(Borders::ALL).title("Partition Type (Enter: apply, ESC: cancel)"));
+        f.render_widget(prompt, type_area);
+    }
+
+    if let (Some(job), Some(image_area)) = (&app.image_job, image_area) {
+        let ratio = if job.total_bytes > 0 {
+            (job.copied_bytes as f64 / job.total_bytes as f64).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let gauge = Gauge::default()
+            .block(Block::default().borders(Borders::ALL).title(format!("Imaging {} to {}", job.device, job.out_path.display())))
+            .gauge_style(Style::default().fg(Color::Magenta))
+            .ratio(ratio)
+            .label(format!(
+                "{:.2} / {:.2} GB",
+                job.copied_bytes as f64 / (1024.0 * 1024.0 * 1024.0),
+                job.total_bytes as f64 / (1024.0 * 1024.0 * 1024.0)
+            ));
+        f.render_widget(gauge, image_area);
+    }
+
+    if let (Some(job), Some(fsck_area)) = (&app.fsck_job, fsck_area) {
+        let progress = *job.progress.lock();
+        let label = match progress.pass {
+            Some(pass) => format!("Pass {} - {:.1}%", pass, progress.percent),
+            None => format!("{:.1}%", progress.percent),
+        };
+        let gauge = Gauge::default()
+            .block(Block::default().borders(Borders::ALL).title(format!("Checking {}", job.device)))
+            .gauge_style(Style::default().fg(Color::Cyan))
+            .percent(progress.percent as u16)
+            .label(label);
+        f.render_widget(gauge, fsck_area);
+    }
+
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
@@ -666,19 +1673,40 @@ fn draw_partitions(f: &mut Frame, app: &App, area: Rect) {
         .iter()
         .map(|disk| {
             let size_gb = disk.size_bytes as f64 / (1024.0 * 1024.0 * 1024.0);
-            let content = format!(
+            let mut content = format!(
                 "{} - {} ({:.2} GB) - {} partitions",
                 disk.device,
                 disk.model,
                 size_gb,
                 disk.partitions.len()
             );
+            if let Some(result) = app.benchmark_results.get(&disk.device) {
+                content.push_str(&format!(" - read {:.1} MB/s", result.mb_per_sec));
+            }
+            if disk.removable {
+                content.push_str(" [removable]");
+            }
             ListItem::new(content)
         })
         .collect();
 
-    let disk_list = List::new(disk_items)
-        .block(Block::default().borders(Borders::ALL).title("Disks (Select with ↑↓)"));
+    let mut disk_title = match &app.fstab_candidate {
+        Some((device, _)) => {
+            format!("Disks (Select with ↑↓, B: Benchmark, D: Disk usage, U: Unmount, X: Eject) | F: Add {} to fstab", device)
+        }
+        None => "Disks (Select with ↑↓, B: Benchmark, D: Disk usage, U: Unmount, X: Eject)".to_string(),
+    };
+    if let Some(device) = &app.mount_candidate {
+        disk_title.push_str(&format!(" | M: Mount {}", device));
+    }
+    let mut disk_block = Block::default().borders(Borders::ALL).title(disk_title);
+    if !app.partition_manager.can_modify() {
+        disk_block = disk_block.title_bottom(Line::styled(
+            format!("Format/Delete/Check/Image {}", REQUIRES_ROOT),
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+    let disk_list = List::new(disk_items).block(disk_block);
     f.render_widget(disk_list, chunks[0]);
 
     // Partition table for selected disk
@@ -698,7 +1726,7 @@ fn draw_partitions(f: &mut Frame, app: &App, area: Rect) {
                     let size_gb = p.size_bytes as f64 / (1024.0 * 1024.0 * 1024.0);
                     let used_gb = p.used_bytes as f64 / (1024.0 * 1024.0 * 1024.0);
                     let used_percent = if p.size_bytes > 0 {
-                        (p.used_bytes as f64 / p.size_bytes as f64 * 100.0)
+                        p.used_bytes as f64 / p.size_bytes as f64 * 100.0
                     } else {
                         0.0
                     };
@@ -743,19 +1771,101 @@ fn draw_partitions(f: &mut Frame, app: &App, area: Rect) {
             f.render_widget(table, chunks[1]);
         }
     }
+
+    if app.directory_usage.is_some() {
+        draw_directory_usage(f, app);
+    }
+}
+
+/// NFS/CIFS/SSHFS mounts, kept apart from local disks; usage stays unknown until checked with N
+fn draw_network_mounts(f: &mut Frame, app: &App, area: Rect) {
+    let rows: Vec<Row> = app
+        .network_mounts
+        .iter()
+        .map(|mount| {
+            let usage = match mount.usage {
+                Some((used, size)) => {
+                    let percent = if size > 0 { used as f64 / size as f64 * 100.0 } else { 0.0 };
+                    format!(
+                        "{:.2} / {:.2} GB ({:.1}%)",
+                        used as f64 / (1024.0 * 1024.0 * 1024.0),
+                        size as f64 / (1024.0 * 1024.0 * 1024.0),
+                        percent
+                    )
+                }
+                None => "not checked".to_string(),
+            };
+            Row::new(vec![
+                Cell::from(mount.source.clone()),
+                Cell::from(mount.filesystem.clone()),
+                Cell::from(usage),
+                Cell::from(mount.mount_point.clone()),
+            ])
+        })
+        .collect();
+
+    let table = Table::new(
+        rows,
+        [Constraint::Min(24), Constraint::Length(11), Constraint::Length(28), Constraint::Min(20)],
+    )
+    .header(Row::new(vec!["Source", "Filesystem", "Used", "Mount Point"]).style(Style::default().add_modifier(Modifier::BOLD)))
+    .block(Block::default().borders(Borders::ALL).title("Network Mounts (N: Check usage)"));
+    f.render_widget(table, area);
 }
 
 fn draw_footer(f: &mut Frame, app: &App, area: Rect) {
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Min(0), Constraint::Length(16)])
+        .split(area);
+
     let text = if app.search_mode {
         "Search Mode: Type to search, Backspace to delete, Enter/ESC to exit"
     } else {
-        "q: Quit | Tab: Next Tab | 1-7: Switch Tabs | ↑↓: Navigate | /: Search | s: Sort | a: Order | m: Menu | PgUp/PgDn: Scroll | Mouse Wheel: Scroll"
+        "q: Quit | Tab: Next Tab | 1-8: Switch Tabs | ↑↓: Navigate | /: Search | s: Sort | a: Order | c: Cmdline | y: Copy | m: Menu | PgUp/PgDn: Scroll | Mouse Wheel: Scroll"
     };
+    let mut block = Block::default().borders(Borders::ALL);
+    if let Some(warning) = app.config_warnings.first() {
+        let more = match app.config_warnings.len() {
+            1 => String::new(),
+            count => format!(" (+{} more in the log)", count - 1),
+        };
+        block = block.title(Span::styled(
+            format!(" Config: {}{} ", warning, more),
+            Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+        ));
+    }
     let footer = Paragraph::new(text)
         .style(Style::default().fg(Color::Gray))
         .alignment(Alignment::Center)
-        .block(Block::default().borders(Borders::ALL));
-    f.render_widget(footer, area);
+        .block(block);
+    f.render_widget(footer, chunks[0]);
+
+    draw_refresh_indicator(f, app, chunks[1]);
+}
+
+/// Spinner plus time since the last successful refresh; amber once collection stalls or fails
+fn draw_refresh_indicator(f: &mut Frame, app: &App, area: Rect) {
+    const SPINNER: [char; 4] = ['|', '/', '-', '\\'];
+
+    let since = app.since_last_update();
+    let (frame, color) = match app.staleness() {
+        Staleness::Live => {
+            let frame = SPINNER[(since.as_millis() / 250) as usize % SPINNER.len()];
+            (frame, Color::Green)
+        }
+        Staleness::Stale => ('!', Color::Rgb(255, 191, 0)),
+    };
+
+    let mut block = Block::default().borders(Borders::ALL);
+    if app.refresh_failing() {
+        block = block.title(" failing ");
+    }
+    let indicator = Paragraph::new(format!("{} {:.1}s ago", frame, since.as_secs_f32()))
+        .style(Style::default().fg(color))
+        .alignment(Alignment::Center)
+        .block(block);
+    f.render_widget(indicator, area);
 }
 
 fn get_usage_color(usage: f32) -> Color {